@@ -5,12 +5,18 @@ CREATE TABLE users (
     name VARCHAR2(255),
     filing_status VARCHAR2(32),
     agi NUMBER(14,2),
+    agi_ciphertext VARCHAR2(128),
     marginal_tax_rate NUMBER(6,4),
     itemize_deductions NUMBER(1),
     provider VARCHAR2(50),
     is_encrypted NUMBER(1) DEFAULT 0,
     encrypted_payload CLOB,
     vault_credential_id VARCHAR2(512),
+    tokens_valid_after TIMESTAMP WITH TIME ZONE,
+    locked_at TIMESTAMP WITH TIME ZONE,
+    locked_reason VARCHAR2(255),
+    -- 1 once the sign-in provider has vouched for this email address
+    email_verified NUMBER(1) DEFAULT 0 NOT NULL,
     created_at TIMESTAMP WITH TIME ZONE DEFAULT CURRENT_TIMESTAMP,
     updated_at TIMESTAMP WITH TIME ZONE
 );
@@ -48,10 +54,10 @@ CREATE TABLE charities (
     classification VARCHAR2(255),
     nonprofit_type VARCHAR2(255),
     deductibility VARCHAR2(64),
-    street VARCHAR2(255),
-    city VARCHAR2(120),
-    state VARCHAR2(16),
-    zip VARCHAR2(20),
+    street VARCHAR2(512),
+    city VARCHAR2(512),
+    state VARCHAR2(512),
+    zip VARCHAR2(512),
     is_encrypted NUMBER(1) DEFAULT 0,
     encrypted_payload CLOB,
     created_at TIMESTAMP WITH TIME ZONE DEFAULT CURRENT_TIMESTAMP,
@@ -78,6 +84,8 @@ CREATE TABLE receipts (
     ocr_status VARCHAR2(50),
     is_encrypted NUMBER(1) DEFAULT 0,
     encrypted_payload CLOB,
+    -- Hex SHA-256 of the stored object, recorded when the receipt is confirmed
+    content_hash VARCHAR2(64),
     created_at TIMESTAMP WITH TIME ZONE DEFAULT CURRENT_TIMESTAMP,
     updated_at TIMESTAMP WITH TIME ZONE,
     CONSTRAINT fk_receipts_donation FOREIGN KEY (donation_id) REFERENCES donations(id)
@@ -85,6 +93,8 @@ CREATE TABLE receipts (
 
 CREATE INDEX idx_receipts_donation ON receipts(donation_id);
 CREATE INDEX idx_receipts_donation_key ON receipts(donation_id, receipt_key);
+CREATE INDEX idx_receipts_content_hash ON receipts(content_hash);
+CREATE INDEX idx_receipts_key ON receipts(receipt_key);
 
 -- OCR extraction/table for receipts (fields embedded in receipts above)
 
@@ -139,6 +149,220 @@ CREATE TABLE audit_revisions (
 
 CREATE INDEX idx_audit_revisions_table_record ON audit_revisions(table_name, record_id, created_at);
 
+-- Per-user change feed consumed by offline clients via /api/sync/changes
+CREATE SEQUENCE sync_change_seq ORDER;
+
+CREATE TABLE sync_changes (
+    seq NUMBER PRIMARY KEY,
+    user_id VARCHAR2(255) NOT NULL,
+    table_name VARCHAR2(32) NOT NULL,
+    record_id VARCHAR2(255) NOT NULL,
+    operation VARCHAR2(16) NOT NULL,
+    created_at TIMESTAMP WITH TIME ZONE DEFAULT CURRENT_TIMESTAMP
+);
+
+CREATE INDEX idx_sync_changes_user_seq ON sync_changes(user_id, seq);
+
+-- Responses to mutating requests sent with an Idempotency-Key, replayed on retries until expires_at
+CREATE TABLE idempotency_keys (
+    user_id VARCHAR2(255) NOT NULL,
+    idem_key VARCHAR2(255) NOT NULL,
+    request_hash VARCHAR2(64) NOT NULL,
+    status_code NUMBER(3),
+    content_type VARCHAR2(255),
+    response_body CLOB,
+    created_at TIMESTAMP WITH TIME ZONE DEFAULT CURRENT_TIMESTAMP,
+    expires_at TIMESTAMP WITH TIME ZONE NOT NULL,
+    CONSTRAINT pk_idempotency_keys PRIMARY KEY (user_id, idem_key)
+);
+
+CREATE INDEX idx_idempotency_keys_expires ON idempotency_keys(expires_at);
+
+-- Personal access tokens for scripted API access; only an argon2 hash of the secret is stored
+CREATE TABLE personal_access_tokens (
+    id VARCHAR2(64) PRIMARY KEY,
+    user_id VARCHAR2(255) NOT NULL,
+    name VARCHAR2(255) NOT NULL,
+    scopes VARCHAR2(255) NOT NULL,
+    token_hash VARCHAR2(255) NOT NULL,
+    created_at TIMESTAMP WITH TIME ZONE DEFAULT CURRENT_TIMESTAMP,
+    expires_at TIMESTAMP WITH TIME ZONE NOT NULL,
+    last_used_at TIMESTAMP WITH TIME ZONE,
+    revoked_at TIMESTAMP WITH TIME ZONE,
+    CONSTRAINT fk_pat_user FOREIGN KEY (user_id) REFERENCES users(id)
+);
+
+CREATE INDEX idx_pat_user ON personal_access_tokens(user_id, created_at);
+
+-- WebAuthn passkeys; id is the base64url credential id, public_key the authenticator's COSE_Key
+CREATE TABLE webauthn_credentials (
+    id VARCHAR2(1400) PRIMARY KEY,
+    user_id VARCHAR2(255) NOT NULL,
+    name VARCHAR2(255) NOT NULL,
+    public_key VARCHAR2(2048) NOT NULL,
+    algorithm NUMBER(6) NOT NULL,
+    sign_count NUMBER(10) DEFAULT 0 NOT NULL,
+    transports VARCHAR2(255),
+    created_at TIMESTAMP WITH TIME ZONE DEFAULT CURRENT_TIMESTAMP,
+    last_used_at TIMESTAMP WITH TIME ZONE,
+    CONSTRAINT fk_webauthn_cred_user FOREIGN KEY (user_id) REFERENCES users(id)
+);
+
+CREATE INDEX idx_webauthn_cred_user ON webauthn_credentials(user_id, created_at);
+
+-- Single-use WebAuthn ceremony challenges; user_id is NULL for usernameless login
+CREATE TABLE webauthn_challenges (
+    id VARCHAR2(64) PRIMARY KEY,
+    user_id VARCHAR2(255),
+    purpose VARCHAR2(16) NOT NULL,
+    challenge VARCHAR2(128) NOT NULL,
+    created_at TIMESTAMP WITH TIME ZONE DEFAULT CURRENT_TIMESTAMP,
+    expires_at TIMESTAMP WITH TIME ZONE NOT NULL
+);
+
+CREATE INDEX idx_webauthn_challenges_expires ON webauthn_challenges(expires_at);
+
+-- Revoked session JWTs, kept until the token would have expired anyway
+CREATE TABLE revoked_tokens (
+    jti VARCHAR2(64) PRIMARY KEY,
+    user_id VARCHAR2(255) NOT NULL,
+    revoked_at TIMESTAMP WITH TIME ZONE DEFAULT CURRENT_TIMESTAMP,
+    expires_at TIMESTAMP WITH TIME ZONE NOT NULL
+);
+
+CREATE INDEX idx_revoked_tokens_expires ON revoked_tokens(expires_at);
+
+-- One row per issued session JWT, so users can see where they are signed in
+CREATE TABLE user_sessions (
+    jti VARCHAR2(64) PRIMARY KEY,
+    user_id VARCHAR2(255) NOT NULL,
+    user_agent VARCHAR2(512),
+    ip_address VARCHAR2(64),
+    created_at TIMESTAMP WITH TIME ZONE NOT NULL,
+    last_seen_at TIMESTAMP WITH TIME ZONE,
+    expires_at TIMESTAMP WITH TIME ZONE NOT NULL,
+    revoked_at TIMESTAMP WITH TIME ZONE,
+    CONSTRAINT fk_user_sessions_user FOREIGN KEY (user_id) REFERENCES users(id)
+);
+
+CREATE INDEX idx_user_sessions_user ON user_sessions(user_id, expires_at);
+
+-- Rotating refresh tokens; only a SHA-256 hash is kept. A family is one sign-in
+-- (its id is the session id) and is revoked as a whole if a used token comes back
+CREATE TABLE refresh_tokens (
+    token_hash VARCHAR2(64) PRIMARY KEY,
+    family_id VARCHAR2(64) NOT NULL,
+    user_id VARCHAR2(255) NOT NULL,
+    created_at TIMESTAMP WITH TIME ZONE DEFAULT CURRENT_TIMESTAMP,
+    expires_at TIMESTAMP WITH TIME ZONE NOT NULL,
+    used_at TIMESTAMP WITH TIME ZONE,
+    revoked_at TIMESTAMP WITH TIME ZONE,
+    CONSTRAINT fk_refresh_tokens_user FOREIGN KEY (user_id) REFERENCES users(id)
+);
+
+CREATE INDEX idx_refresh_tokens_family ON refresh_tokens(family_id);
+CREATE INDEX idx_refresh_tokens_user ON refresh_tokens(user_id, expires_at);
+
+-- Read-only access for an accountant, identified by email, to chosen tax years
+CREATE TABLE accountant_grants (
+    id VARCHAR2(64) PRIMARY KEY,
+    owner_user_id VARCHAR2(255) NOT NULL,
+    accountant_email VARCHAR2(255) NOT NULL,
+    accountant_user_id VARCHAR2(255),
+    tax_years VARCHAR2(200) NOT NULL,
+    allow_agi NUMBER(1) DEFAULT 0 NOT NULL,
+    created_at TIMESTAMP WITH TIME ZONE DEFAULT CURRENT_TIMESTAMP,
+    accepted_at TIMESTAMP WITH TIME ZONE,
+    revoked_at TIMESTAMP WITH TIME ZONE,
+    CONSTRAINT fk_accountant_grants_owner FOREIGN KEY (owner_user_id) REFERENCES users(id)
+);
+
+CREATE INDEX idx_accountant_grants_owner ON accountant_grants(owner_user_id);
+CREATE INDEX idx_accountant_grants_email ON accountant_grants(accountant_email);
+CREATE INDEX idx_accountant_grants_accountant ON accountant_grants(accountant_user_id);
+
+-- Re-encryption of a user's vault from one key to another; the newest row is the current run
+CREATE TABLE vault_key_rotations (
+    id VARCHAR2(64) PRIMARY KEY,
+    user_id VARCHAR2(255) NOT NULL,
+    old_key_id VARCHAR2(32) NOT NULL,
+    new_key_id VARCHAR2(32) NOT NULL,
+    new_credential_id VARCHAR2(512),
+    status VARCHAR2(20) NOT NULL,
+    total_items NUMBER DEFAULT 0 NOT NULL,
+    processed_items NUMBER DEFAULT 0 NOT NULL,
+    failed_table VARCHAR2(30),
+    failed_record_id VARCHAR2(255),
+    error VARCHAR2(1000),
+    created_at TIMESTAMP WITH TIME ZONE DEFAULT CURRENT_TIMESTAMP,
+    updated_at TIMESTAMP WITH TIME ZONE,
+    completed_at TIMESTAMP WITH TIME ZONE,
+    CONSTRAINT fk_vault_key_rotations_user FOREIGN KEY (user_id) REFERENCES users(id)
+);
+
+CREATE INDEX idx_vault_key_rotations_user ON vault_key_rotations(user_id, created_at);
+
+-- Per-user data key for server-side column encryption, wrapped by the DATA_ENCRYPTION_KEY master key.
+-- No foreign key: a new profile's AGI is sealed before its user row exists.
+CREATE TABLE user_data_keys (
+    user_id VARCHAR2(255) PRIMARY KEY,
+    wrapped_key VARCHAR2(128) NOT NULL,
+    master_key_id VARCHAR2(32) NOT NULL,
+    created_at TIMESTAMP WITH TIME ZONE DEFAULT CURRENT_TIMESTAMP
+);
+
+-- Client-computed HMAC tokens (charity, year, category, dedupe) for searching encrypted donations
+CREATE TABLE donation_index_tokens (
+    donation_id VARCHAR2(255) NOT NULL,
+    user_id VARCHAR2(255) NOT NULL,
+    token_kind VARCHAR2(16) NOT NULL,
+    token VARCHAR2(128) NOT NULL,
+    created_at TIMESTAMP WITH TIME ZONE DEFAULT CURRENT_TIMESTAMP,
+    CONSTRAINT pk_donation_index_tokens PRIMARY KEY (donation_id, token_kind),
+    CONSTRAINT fk_donation_index_tokens_donation FOREIGN KEY (donation_id) REFERENCES donations(id) ON DELETE CASCADE
+);
+
+CREATE INDEX idx_donation_index_tokens_lookup ON donation_index_tokens(user_id, token_kind, token);
+
+-- Provider account (OAuth/OIDC `sub`) behind each user, for provider-initiated security events
+CREATE TABLE user_identities (
+    provider VARCHAR2(50) NOT NULL,
+    subject VARCHAR2(255) NOT NULL,
+    user_id VARCHAR2(255) NOT NULL,
+    created_at TIMESTAMP WITH TIME ZONE DEFAULT CURRENT_TIMESTAMP,
+    CONSTRAINT pk_user_identities PRIMARY KEY (provider, subject),
+    CONSTRAINT fk_user_identities_user FOREIGN KEY (user_id) REFERENCES users(id)
+);
+
+CREATE INDEX idx_user_identities_user ON user_identities(user_id);
+
+-- Processed security event token ids (RISC), kept while a replay would still be accepted
+CREATE TABLE security_events (
+    jti VARCHAR2(255) PRIMARY KEY,
+    issuer VARCHAR2(255) NOT NULL,
+    event_types VARCHAR2(2000),
+    received_at TIMESTAMP WITH TIME ZONE DEFAULT CURRENT_TIMESTAMP,
+    expires_at TIMESTAMP WITH TIME ZONE NOT NULL
+);
+
+CREATE INDEX idx_security_events_expires ON security_events(expires_at);
+
+-- Multipart receipt uploads in progress; a row goes when its upload is completed or aborted
+CREATE TABLE receipt_uploads (
+    id VARCHAR2(64) PRIMARY KEY,
+    user_id VARCHAR2(255) NOT NULL,
+    receipt_key VARCHAR2(1024) NOT NULL,
+    upload_id VARCHAR2(1024) NOT NULL,
+    content_type VARCHAR2(255),
+    upload_size NUMBER NOT NULL,
+    part_size NUMBER NOT NULL,
+    part_count NUMBER NOT NULL,
+    created_at TIMESTAMP WITH TIME ZONE DEFAULT CURRENT_TIMESTAMP,
+    CONSTRAINT fk_receipt_uploads_user FOREIGN KEY (user_id) REFERENCES users(id)
+);
+
+CREATE INDEX idx_receipt_uploads_created ON receipt_uploads(created_at);
+
 -- Default Users for testing
 MERGE INTO users t
 USING (SELECT 'dev-1' id, 'dev@local' email, 'Developer' name, 'local' provider FROM dual) s
@@ -1,6 +1,11 @@
 // Organized by responsibility for maintainability.
 include!("auth_sections/flow/oauth_flow.rs");
+include!("auth_sections/flow/revocation.rs");
+include!("auth_sections/flow/sessions.rs");
+include!("auth_sections/flow/refresh_tokens.rs");
 include!("auth_sections/flow/risc.rs");
+include!("auth_sections/flow/access_tokens.rs");
+include!("auth_sections/flow/passkeys.rs");
 include!("auth_sections/flow/crypto_utils.rs");
 include!("auth_sections/profile/profile_handlers.rs");
 include!("auth_sections/support/token_and_provider_support.rs");
@@ -0,0 +1,262 @@
+use argon2::password_hash::{rand_core::OsRng, PasswordHash, PasswordHasher, PasswordVerifier, SaltString};
+use argon2::Argon2;
+
+const ACCESS_TOKEN_PREFIX: &str = "dtp_";
+const DEFAULT_ACCESS_TOKEN_DAYS: i64 = 90;
+const MAX_ACCESS_TOKEN_DAYS: i64 = 365;
+
+/// `read`: any GET. `reports`: GET under /api/reports. `import`: POST /api/donations/import. `write`: any mutation.
+pub const ACCESS_TOKEN_SCOPES: &[&str] = &["read", "reports", "import", "write"];
+
+#[derive(Deserialize, utoipa::ToSchema)]
+pub struct CreateAccessTokenRequest {
+    pub name: String,
+    pub scopes: Vec<String>,
+    /// Defaults to 90; at most 365.
+    pub expires_in_days: Option<i64>,
+}
+
+#[derive(Serialize, utoipa::ToSchema)]
+pub struct CreatedAccessTokenResponse {
+    /// Shown only once; send it as `Authorization: Bearer <token>`.
+    pub token: String,
+    pub access_token: crate::db::models::AccessToken,
+}
+
+/// Bearer value from the Authorization header only; personal access tokens are never read from cookies.
+pub fn extract_bearer_token(headers: &HeaderMap) -> Option<String> {
+    headers
+        .get(header::AUTHORIZATION)
+        .and_then(|h| h.to_str().ok())
+        .and_then(|value| value.strip_prefix("Bearer "))
+        .map(str::to_string)
+}
+
+pub fn is_access_token(token: &str) -> bool {
+    token.starts_with(ACCESS_TOKEN_PREFIX)
+}
+
+/// Splits `dtp_<id>_<secret>` into its lookup id and secret.
+fn parse_access_token(token: &str) -> Option<(&str, &str)> {
+    let (id, secret) = token.strip_prefix(ACCESS_TOKEN_PREFIX)?.split_once('_')?;
+    if id.is_empty() || secret.is_empty() {
+        return None;
+    }
+    Some((id, secret))
+}
+
+fn access_token_allows(scopes: &[String], method: &axum::http::Method, path: &str) -> bool {
+    // Tokens cannot manage credentials, sessions or accountant access, unlock the vault or delete the account, whatever their scopes.
+    if path.starts_with("/api/me/tokens")
+        || path == "/api/me/logout-all"
+        || path.starts_with("/api/me/sessions")
+        || path.starts_with("/api/me/accountants")
+        || path.starts_with("/api/me/passkeys")
+        || path.starts_with("/api/me/vault")
+        || (path == "/api/me" && method == axum::http::Method::DELETE)
+    {
+        return false;
+    }
+    let has = |scope: &str| scopes.iter().any(|s| s == scope);
+    if method == axum::http::Method::GET || method == axum::http::Method::HEAD {
+        return has("read") || (has("reports") && path.starts_with("/api/reports/"));
+    }
+    if method == axum::http::Method::POST && path == "/api/donations/import" {
+        return has("import") || has("write");
+    }
+    has("write")
+}
+
+/// Resolves a personal access token to its owner and checks its scopes against the request.
+pub async fn authenticate_access_token(
+    db: &crate::db::DbPool,
+    token: &str,
+    method: &axum::http::Method,
+    path: &str,
+) -> Result<AuthenticatedUser, (StatusCode, &'static str)> {
+    let (token_id, secret) = parse_access_token(token).ok_or((StatusCode::UNAUTHORIZED, "Unauthorized"))?;
+    let active = match crate::db::access_tokens::find_active_token(db, token_id).await {
+        Ok(Some(active)) => active,
+        Ok(None) => return Err((StatusCode::UNAUTHORIZED, "Unauthorized")),
+        Err(e) => {
+            tracing::error!("Failed to load access token: {}", e);
+            return Err((StatusCode::INTERNAL_SERVER_ERROR, "Database Error"));
+        }
+    };
+
+    // Argon2 verification is deliberately slow; keep it off the async workers.
+    let secret = secret.to_string();
+    let token_hash = active.token_hash.clone();
+    let verified = tokio::task::spawn_blocking(move || {
+        PasswordHash::new(&token_hash)
+            .map(|parsed| Argon2::default().verify_password(secret.as_bytes(), &parsed).is_ok())
+            .unwrap_or(false)
+    })
+    .await
+    .unwrap_or(false);
+    if !verified {
+        return Err((StatusCode::UNAUTHORIZED, "Unauthorized"));
+    }
+
+    if !access_token_allows(&active.scopes, method, path) {
+        return Err((StatusCode::FORBIDDEN, "Token scope does not allow this request"));
+    }
+
+    if let Err(e) = crate::db::access_tokens::touch_token(db, token_id).await {
+        tracing::warn!("Failed to record access token use: {}", e);
+    }
+
+    Ok(AuthenticatedUser {
+        id: active.user_id,
+        email: active.email,
+        name: active.name,
+        provider: active.provider,
+        session_id: None,
+    })
+}
+
+#[utoipa::path(
+    get,
+    path = "/api/me/tokens",
+    tag = "profile",
+    responses((status = 200, description = "`{tokens: [AccessToken]}`, newest first, including revoked and expired", body = serde_json::Value))
+)]
+pub async fn list_access_tokens(
+    State(state): State<AppState>,
+    user: AuthenticatedUser,
+) -> impl IntoResponse {
+    match crate::db::access_tokens::list_tokens(&state.db, &user.id).await {
+        Ok(tokens) => Json(serde_json::json!({ "tokens": tokens })).into_response(),
+        Err(e) => {
+            tracing::error!("Failed to list access tokens: {}", e);
+            (StatusCode::INTERNAL_SERVER_ERROR, "Database Error").into_response()
+        }
+    }
+}
+
+#[utoipa::path(
+    post,
+    path = "/api/me/tokens",
+    tag = "profile",
+    request_body = CreateAccessTokenRequest,
+    responses(
+        (status = 201, description = "Token created", body = CreatedAccessTokenResponse),
+        (status = 400, description = "Missing name, unknown scope or invalid expiry", body = String),
+    )
+)]
+pub async fn create_access_token(
+    State(state): State<AppState>,
+    user: AuthenticatedUser,
+    Json(req): Json<CreateAccessTokenRequest>,
+) -> impl IntoResponse {
+    let name = req.name.trim();
+    if name.is_empty() || name.len() > 255 {
+        return (StatusCode::BAD_REQUEST, "Name required").into_response();
+    }
+    let mut scopes = req.scopes.clone();
+    scopes.sort();
+    scopes.dedup();
+    if scopes.is_empty() || scopes.iter().any(|s| !ACCESS_TOKEN_SCOPES.contains(&s.as_str())) {
+        return (StatusCode::BAD_REQUEST, "Unknown or missing scope").into_response();
+    }
+    let expires_in_days = req.expires_in_days.unwrap_or(DEFAULT_ACCESS_TOKEN_DAYS);
+    if !(1..=MAX_ACCESS_TOKEN_DAYS).contains(&expires_in_days) {
+        return (StatusCode::BAD_REQUEST, "Expiry must be between 1 and 365 days").into_response();
+    }
+
+    let token_id = uuid::Uuid::new_v4().simple().to_string();
+    let secret = format!("{}{}", uuid::Uuid::new_v4().simple(), uuid::Uuid::new_v4().simple());
+    let salt = SaltString::generate(&mut OsRng);
+    let token_hash = match Argon2::default().hash_password(secret.as_bytes(), &salt) {
+        Ok(hash) => hash.to_string(),
+        Err(e) => {
+            tracing::error!("Failed to hash access token: {}", e);
+            return (StatusCode::INTERNAL_SERVER_ERROR, "Failed to create token").into_response();
+        }
+    };
+
+    let input = crate::db::models::NewAccessToken {
+        id: token_id.clone(),
+        user_id: user.id.clone(),
+        name: name.to_string(),
+        scopes,
+        token_hash,
+        expires_in_days,
+    };
+    match crate::db::access_tokens::create_token(&state.db, &input).await {
+        Ok(access_token) => (
+            StatusCode::CREATED,
+            Json(CreatedAccessTokenResponse {
+                token: format!("{}{}_{}", ACCESS_TOKEN_PREFIX, token_id, secret),
+                access_token,
+            }),
+        )
+            .into_response(),
+        Err(e) => {
+            tracing::error!("Failed to store access token: {}", e);
+            (StatusCode::INTERNAL_SERVER_ERROR, "Database Error").into_response()
+        }
+    }
+}
+
+#[utoipa::path(
+    delete,
+    path = "/api/me/tokens/{id}",
+    tag = "profile",
+    params(("id" = String, Path, description = "Access token id")),
+    responses(
+        (status = 200, description = "Revoked", body = String),
+        (status = 404, description = "Not found or already revoked", body = String),
+    )
+)]
+pub async fn revoke_access_token(
+    Path(token_id): Path<String>,
+    State(state): State<AppState>,
+    user: AuthenticatedUser,
+) -> impl IntoResponse {
+    match crate::db::access_tokens::revoke_token(&state.db, &user.id, &token_id).await {
+        Ok(true) => (StatusCode::OK, "Revoked").into_response(),
+        Ok(false) => (StatusCode::NOT_FOUND, "Not found").into_response(),
+        Err(e) => {
+            tracing::error!("Failed to revoke access token: {}", e);
+            (StatusCode::INTERNAL_SERVER_ERROR, "Database Error").into_response()
+        }
+    }
+}
+
+#[cfg(test)]
+mod access_token_tests {
+    use super::*;
+    use axum::http::Method;
+
+    #[test]
+    fn test_parse_access_token_requires_prefix_id_and_secret() {
+        assert_eq!(parse_access_token("dtp_abc_def"), Some(("abc", "def")));
+        assert_eq!(parse_access_token("dtp_abc_"), None);
+        assert_eq!(parse_access_token("dtp__def"), None);
+        assert_eq!(parse_access_token("eyJhbGciOi.x.y"), None);
+    }
+
+    #[test]
+    fn test_access_token_scopes_gate_methods_and_paths() {
+        let reports = vec!["reports".to_string()];
+        assert!(access_token_allows(&reports, &Method::GET, "/api/reports/export"));
+        assert!(!access_token_allows(&reports, &Method::GET, "/api/donations"));
+
+        let import = vec!["import".to_string()];
+        assert!(access_token_allows(&import, &Method::POST, "/api/donations/import"));
+        assert!(!access_token_allows(&import, &Method::POST, "/api/donations"));
+
+        let all = vec!["read".to_string(), "write".to_string()];
+        assert!(access_token_allows(&all, &Method::PUT, "/api/donations/d1"));
+        assert!(!access_token_allows(&all, &Method::POST, "/api/me/tokens"));
+        assert!(!access_token_allows(&all, &Method::GET, "/api/me/tokens"));
+        assert!(!access_token_allows(&all, &Method::DELETE, "/api/me"));
+        assert!(!access_token_allows(&all, &Method::GET, "/api/me/passkeys"));
+        assert!(!access_token_allows(&all, &Method::POST, "/api/me/vault/unlock"));
+        assert!(!access_token_allows(&all, &Method::POST, "/api/me/logout-all"));
+        assert!(!access_token_allows(&all, &Method::GET, "/api/me/sessions"));
+        assert!(!access_token_allows(&all, &Method::DELETE, "/api/me/sessions/s1"));
+        assert!(!access_token_allows(&all, &Method::POST, "/api/me/accountants"));
+    }
+}
@@ -8,7 +8,7 @@ use crate::AppState;
 use crate::db::models::UserProfileUpsert;
 use oauth2::{
     basic::BasicClient, AuthUrl, ClientId, ClientSecret, RedirectUrl, TokenUrl,
-    AuthorizationCode, PkceCodeChallenge, PkceCodeVerifier,
+    AuthorizationCode, PkceCodeChallenge, PkceCodeVerifier, Scope,
 };
 use oauth2::TokenResponse;
 use std::env;
@@ -25,7 +25,6 @@ static JWT_SECRET: OnceLock<String> = OnceLock::new();
 static JWT_ISSUER: OnceLock<Option<String>> = OnceLock::new();
 static JWT_AUDIENCE: OnceLock<Option<String>> = OnceLock::new();
 static HTTP_CLIENT: OnceLock<reqwest::Client> = OnceLock::new();
-static REVOKED_JTIS: OnceLock<Mutex<HashMap<String, usize>>> = OnceLock::new();
 
 #[derive(Deserialize)]
 #[serde(untagged)]
@@ -47,9 +46,12 @@ pub struct DevLoginRequest {
 #[derive(Serialize)]
 pub struct AuthResponse {
     user: UserProfile,
+    /// Present only for `X-Token-Delivery: bearer` sign-ins.
+    #[serde(flatten)]
+    tokens: Option<SessionTokens>,
 }
 
-#[derive(Serialize, Deserialize, Clone)]
+#[derive(Serialize, Deserialize, Clone, utoipa::ToSchema)]
 pub struct UserProfile {
     pub id: String,
     pub email: String,
@@ -64,8 +66,9 @@ pub struct UserProfile {
     pub vault_credential_id: Option<String>,
 }
 
-#[derive(Deserialize)]
+#[derive(Deserialize, utoipa::ToSchema)]
 pub struct UpdateMeRequest {
+    /// Read-only: set by the sign-in provider. Only the current address is accepted.
     pub email: Option<String>,
     pub name: Option<String>,
     pub filing_status: Option<String>,
@@ -82,7 +85,14 @@ pub struct UpdateMeRequest {
 struct Claims {
     sub: String,
     exp: usize,
+    /// Absent from tokens issued before "log out everywhere" existed.
+    #[serde(default)]
+    iat: usize,
     jti: String,
+    /// Session the token was issued or refreshed under: the jti of the first
+    /// token of the sign-in. Absent from tokens issued before refresh tokens.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    sid: Option<String>,
     email: String,
     provider: String,
     name: String,
@@ -90,6 +100,13 @@ struct Claims {
     aud: Option<String>,
 }
 
+impl Claims {
+    /// Session id, falling back to the jti for tokens that carry none.
+    fn session_id(&self) -> &str {
+        self.sid.as_deref().unwrap_or(&self.jti)
+    }
+}
+
 #[derive(Debug, Serialize, Deserialize)]
 struct StateClaims {
     exp: usize,
@@ -97,24 +114,34 @@ struct StateClaims {
     nonce: String,
 }
 
+#[derive(Clone)]
 pub struct AuthenticatedUser {
     pub id: String,
     pub email: String,
     pub name: String,
     pub provider: String,
+    /// Session the JWT belongs to; None for personal access tokens.
+    pub session_id: Option<String>,
 }
 
 impl<S> FromRequestParts<S> for AuthenticatedUser
 where
+    AppState: axum::extract::FromRef<S>,
     S: Send + Sync + 'static,
 {
     type Rejection = (StatusCode, String);
 
-    async fn from_request_parts(parts: &mut Parts, _state: &S) -> Result<Self, Self::Rejection> {
+    async fn from_request_parts(parts: &mut Parts, state: &S) -> Result<Self, Self::Rejection> {
+        // require_auth has already resolved the caller, including personal access tokens.
+        if let Some(user) = parts.extensions.get::<AuthenticatedUser>() {
+            return Ok(user.clone());
+        }
+
         let token = extract_token(parts)
             .ok_or((StatusCode::UNAUTHORIZED, "Missing auth token".to_string()))?;
 
-        validate_token_str(&token)
+        let state = <AppState as axum::extract::FromRef<S>>::from_ref(state);
+        validate_token_str(&state.db, &token).await
     }
 }
 
@@ -147,7 +174,7 @@ pub fn extract_token_from_headers(headers: &HeaderMap) -> Option<String> {
 }
 
 // Validate a token string and return AuthenticatedUser (used by extractor & middleware)
-pub fn validate_token_str(token: &str) -> Result<AuthenticatedUser, (StatusCode, String)> {
+pub async fn validate_token_str(db: &crate::db::DbPool, token: &str) -> Result<AuthenticatedUser, (StatusCode, String)> {
     let secret = jwt_secret().map_err(|_| {
         tracing::error!("JWT_SECRET not set");
         (StatusCode::INTERNAL_SERVER_ERROR, "Server configuration error".to_string())
@@ -176,71 +203,41 @@ pub fn validate_token_str(token: &str) -> Result<AuthenticatedUser, (StatusCode,
         return Err((StatusCode::UNAUTHORIZED, "Token expired".to_string()));
     }
 
-    if is_token_revoked(&token_data.claims.jti, token_data.claims.exp) {
-        return Err((StatusCode::UNAUTHORIZED, "Invalid token".to_string()));
+    match is_token_revoked(db, &token_data.claims).await {
+        Ok(false) => {}
+        Ok(true) => return Err((StatusCode::UNAUTHORIZED, "Invalid token".to_string())),
+        Err(e) => {
+            // Fail closed: a token we cannot check is not accepted.
+            tracing::error!("Token revocation check failed: {}", e);
+            return Err((StatusCode::SERVICE_UNAVAILABLE, "Service unavailable".to_string()));
+        }
     }
 
+    let session_id = token_data.claims.session_id().to_string();
     Ok(AuthenticatedUser {
         id: token_data.claims.sub,
         email: token_data.claims.email,
         name: token_data.claims.name,
         provider: token_data.claims.provider,
+        session_id: Some(session_id),
     })
 }
 
-pub fn revoke_token_str(token: &str) -> Result<(), ()> {
-    let secret = jwt_secret().map_err(|_| ())?;
-
-    let mut validation = Validation::default();
-    validation.validate_exp = false;
-    if let Some(issuer) = jwt_issuer() {
-        validation.set_issuer(&[issuer.as_str()]);
-    }
-    if let Some(audience) = jwt_audience() {
-        validation.set_audience(&[audience.as_str()]);
-    }
-
-    let token_data = decode::<Claims>(
-        token,
-        &DecodingKey::from_secret(secret.as_bytes()),
-        &validation,
-    ).map_err(|_| ())?;
-
-    let now_ts = Utc::now().timestamp();
-    let now = if now_ts > 0 { now_ts as usize } else { 0 };
-    let revocations = revoked_jtis();
-    let mut guard = revocations.lock().map_err(|_| ())?;
-    guard.retain(|_, exp| *exp > now);
-    guard.insert(token_data.claims.jti, token_data.claims.exp);
-    Ok(())
-}
-
-fn revoked_jtis() -> &'static Mutex<HashMap<String, usize>> {
-    REVOKED_JTIS.get_or_init(|| Mutex::new(HashMap::new()))
-}
-
-fn is_token_revoked(jti: &str, exp: usize) -> bool {
-    let now_ts = Utc::now().timestamp();
-    let now = if now_ts > 0 { now_ts as usize } else { 0 };
-    let revocations = revoked_jtis();
-    if let Ok(mut guard) = revocations.lock() {
-        guard.retain(|_, expires_at| *expires_at > now);
-        if exp <= now {
-            return true;
-        }
-        return guard.contains_key(jti);
-    }
-    false
-}
-
 pub async fn login(Path(provider): Path<String>) -> impl IntoResponse {
     // In a real app, you would have a map of clients for each provider.
     // Here is a simplified example for one provider or generic logic.
     
-    let cfg = match load_provider_config(&provider) {
+    let mut cfg = match load_provider_config(&provider) {
         Ok(c) => c,
         Err(e) => return (StatusCode::BAD_REQUEST, e).into_response(),
     };
+    let oidc = match resolve_oidc_provider(&mut cfg).await {
+        Ok(m) => m,
+        Err(e) => {
+            tracing::error!("OIDC discovery failed for {}: {}", provider, e);
+            return (StatusCode::BAD_GATEWAY, "OpenID provider discovery failed").into_response();
+        }
+    };
 
     let auth_url = match AuthUrl::new(cfg.auth_url.clone()) {
         Ok(v) => v,
@@ -261,7 +258,7 @@ pub async fn login(Path(provider): Path<String>) -> impl IntoResponse {
         .set_token_uri(token_url)
         .set_redirect_uri(redirect_url);
 
-    let state = match create_state_token(&provider) {
+    let (state, nonce) = match create_state_token_with_nonce(&provider) {
         Ok(s) => s,
         Err(e) => return (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()).into_response(),
     };
@@ -270,10 +267,18 @@ pub async fn login(Path(provider): Path<String>) -> impl IntoResponse {
     // Generate PKCE challenge
     let (pkce_challenge, pkce_verifier) = PkceCodeChallenge::new_random_sha256();
 
-    let (authorize_url, _csrf_state) = client
+    let mut authorize_request = client
         .authorize_url(|| oauth2::CsrfToken::new(state))
-        .set_pkce_challenge(pkce_challenge)
-        .url();
+        .set_pkce_challenge(pkce_challenge);
+    if oidc.is_some() {
+        // The nonce binds the id_token to this browser's state cookie.
+        authorize_request = authorize_request
+            .add_scope(Scope::new("openid".to_string()))
+            .add_scope(Scope::new("email".to_string()))
+            .add_scope(Scope::new("profile".to_string()))
+            .add_extra_param("nonce", nonce);
+    }
+    let (authorize_url, _csrf_state) = authorize_request.url();
 
     let mut response = Redirect::to(authorize_url.as_str()).into_response();
     let state_cookie = build_oauth_state_cookie(&state_for_cookie);
@@ -294,12 +299,20 @@ pub async fn callback(
     Path(provider): Path<String>,
     headers: HeaderMap,
     State(state): State<AppState>,
+    client: SessionClient,
     axum::extract::Form(params): axum::extract::Form<AuthCallback>,
 ) -> impl IntoResponse {
-    let cfg = match load_provider_config(&provider) {
+    let mut cfg = match load_provider_config(&provider) {
         Ok(c) => c,
         Err(e) => return (StatusCode::BAD_REQUEST, e).into_response(),
     };
+    let oidc = match resolve_oidc_provider(&mut cfg).await {
+        Ok(m) => m,
+        Err(e) => {
+            tracing::error!("OIDC discovery failed for {}: {}", provider, e);
+            return (StatusCode::BAD_GATEWAY, "OpenID provider discovery failed").into_response();
+        }
+    };
 
     let profile = match params {
         AuthCallback::Code { code, state: oauth_state } => {
@@ -309,54 +322,102 @@ pub async fn callback(
                 return (StatusCode::UNAUTHORIZED, "Invalid state").into_response();
             }
 
-            if let Err(e) = validate_state_token(&oauth_state, &provider) {
-                tracing::warn!("OAuth state invalid: {}", e);
-                return (StatusCode::UNAUTHORIZED, "Invalid state").into_response();
-            }
-
-            let pkce_verifier_cookie = extract_cookie_by_name(&headers, "pkce_verifier");
-            let pkce_verifier = pkce_verifier_cookie.map(PkceCodeVerifier::new);
-
-            let auth_url = AuthUrl::new(cfg.auth_url.clone()).unwrap();
-            let token_url = TokenUrl::new(cfg.token_url.clone()).unwrap();
-            let redirect_url = RedirectUrl::new(cfg.redirect_url.clone()).unwrap();
-
-            let client = BasicClient::new(ClientId::new(cfg.client_id))
-                .set_client_secret(ClientSecret::new(cfg.client_secret))
-                .set_auth_uri(auth_url)
-                .set_token_uri(token_url)
-                .set_redirect_uri(redirect_url);
-
-            let http_client = match oauth_http_client() {
-                Ok(c) => c,
+            let nonce = match validate_state_token(&oauth_state, &provider) {
+                Ok(n) => n,
                 Err(e) => {
-                    tracing::error!("OAuth HTTP client init failed: {}", e);
-                    return (StatusCode::INTERNAL_SERVER_ERROR, "Server configuration error").into_response();
+                    tracing::warn!("OAuth state invalid: {}", e);
+                    return (StatusCode::UNAUTHORIZED, "Invalid state").into_response();
                 }
             };
 
-            let mut token_req = client.exchange_code(AuthorizationCode::new(code));
-            if let Some(verifier) = pkce_verifier {
-                token_req = token_req.set_pkce_verifier(verifier);
-            } else {
-                tracing::warn!("Missing PKCE verifier cookie");
-            }
+            let pkce_verifier_cookie = extract_cookie_by_name(&headers, "pkce_verifier");
 
-            let token_result = match token_req
-                .request_async(http_client)
-                .await {
+            if let Some(metadata) = oidc.as_ref() {
+                let http_client = match oauth_http_client() {
+                    Ok(c) => c,
+                    Err(e) => {
+                        tracing::error!("OAuth HTTP client init failed: {}", e);
+                        return (StatusCode::INTERNAL_SERVER_ERROR, "Server configuration error").into_response();
+                    }
+                };
+                let id_token = match crate::oidc::exchange_code(
+                    http_client,
+                    metadata,
+                    &cfg.client_id,
+                    &cfg.client_secret,
+                    &cfg.redirect_url,
+                    &code,
+                    pkce_verifier_cookie.as_deref(),
+                ).await {
                     Ok(t) => t,
                     Err(e) => {
-                        tracing::error!("OAuth token exchange failed: {}", e);
+                        tracing::error!("OIDC token exchange failed: {}", e);
                         return (StatusCode::BAD_GATEWAY, "OAuth token exchange failed").into_response();
                     }
                 };
+                let identity = match crate::oidc::validate_id_token(
+                    http_client,
+                    metadata,
+                    &cfg.client_id,
+                    &id_token,
+                    Some(&nonce),
+                ).await {
+                    Ok(i) => i,
+                    Err(e) => {
+                        tracing::warn!("OIDC id_token rejected: {}", e);
+                        return (StatusCode::UNAUTHORIZED, "Invalid credential").into_response();
+                    }
+                };
+                ProviderProfile {
+                    id: identity.subject,
+                    email: identity.email,
+                    name: identity.name,
+                    email_verified: identity.email_verified == Some(true),
+                }
+            } else {
+                let pkce_verifier = pkce_verifier_cookie.map(PkceCodeVerifier::new);
+
+                let auth_url = AuthUrl::new(cfg.auth_url.clone()).unwrap();
+                let token_url = TokenUrl::new(cfg.token_url.clone()).unwrap();
+                let redirect_url = RedirectUrl::new(cfg.redirect_url.clone()).unwrap();
+
+                let client = BasicClient::new(ClientId::new(cfg.client_id))
+                    .set_client_secret(ClientSecret::new(cfg.client_secret))
+                    .set_auth_uri(auth_url)
+                    .set_token_uri(token_url)
+                    .set_redirect_uri(redirect_url);
+
+                let http_client = match oauth_http_client() {
+                    Ok(c) => c,
+                    Err(e) => {
+                        tracing::error!("OAuth HTTP client init failed: {}", e);
+                        return (StatusCode::INTERNAL_SERVER_ERROR, "Server configuration error").into_response();
+                    }
+                };
 
-            match fetch_user_profile(&cfg.userinfo_url, token_result.access_token().secret()).await {
-                Ok(p) => p,
-                Err(e) => {
-                    tracing::error!("Userinfo fetch failed: {}", e);
-                    return (StatusCode::BAD_GATEWAY, "Userinfo fetch failed").into_response();
+                let mut token_req = client.exchange_code(AuthorizationCode::new(code));
+                if let Some(verifier) = pkce_verifier {
+                    token_req = token_req.set_pkce_verifier(verifier);
+                } else {
+                    tracing::warn!("Missing PKCE verifier cookie");
+                }
+
+                let token_result = match token_req
+                    .request_async(http_client)
+                    .await {
+                        Ok(t) => t,
+                        Err(e) => {
+                            tracing::error!("OAuth token exchange failed: {}", e);
+                            return (StatusCode::BAD_GATEWAY, "OAuth token exchange failed").into_response();
+                        }
+                    };
+
+                match fetch_user_profile(&cfg.userinfo_url, token_result.access_token().secret()).await {
+                    Ok(p) => p,
+                    Err(e) => {
+                        tracing::error!("Userinfo fetch failed: {}", e);
+                        return (StatusCode::BAD_GATEWAY, "Userinfo fetch failed").into_response();
+                    }
                 }
             }
         }
@@ -392,6 +453,8 @@ pub async fn callback(
                 sub: String,
                 email: String,
                 name: String,
+                #[serde(default)]
+                email_verified: bool,
             }
 
             // 1. Decode the JWT header to get the key ID (kid)
@@ -459,13 +522,34 @@ pub async fn callback(
                 id: token_data.claims.sub,
                 email: token_data.claims.email,
                 name: token_data.claims.name,
+                email_verified: token_data.claims.email_verified,
             }
         }
     };
 
-    // 1. Check if user exists by email
-    let existing_user = crate::db::users::get_user_profile_by_email(&state.db, &profile.email).await;
-    
+    let provider_subject = profile.id.clone();
+    let email_verified = profile.email_verified;
+
+    // 1. Find the account linked to this provider account, or one with a verified matching email
+    let existing_user = resolve_login_account(&state.db, &provider, &profile).await;
+
+    // An unverified address must not take over the account that holds it, and can't start a
+    // second account with it either.
+    if matches!(existing_user, Ok(None)) && !email_verified {
+        match crate::db::users::get_user_profile_by_email(&state.db, &profile.email).await {
+            Ok(None) => {}
+            Ok(Some(_)) => {
+                tracing::warn!("Unverified {} login refused for an email that has an account", provider);
+                return (StatusCode::CONFLICT, "Email address not verified by this sign-in provider").into_response();
+            }
+            Err(e) => {
+                tracing::error!("Email lookup failed: {}", e);
+                return (StatusCode::SERVICE_UNAVAILABLE, "Authentication unavailable").into_response();
+            }
+        }
+    }
+    let provider_email = profile.email.clone();
+
     let user = match existing_user {
         Ok(Some((id, row))) => {
             UserProfile {
@@ -529,24 +613,40 @@ pub async fn callback(
         vault_credential_id: user.vault_credential_id.clone(),
     }).await;
 
-    let token = match create_jwt(&user) {
+    // The provider's verdict covers the address it sent, which a linked account may no longer use.
+    let email_verified = email_verified && provider_email.eq_ignore_ascii_case(&user.email);
+    if let Err(e) = crate::db::account_security::set_email_verified(&state.db, &user.id, email_verified).await {
+        tracing::warn!("Failed to record email verification for user {}: {}", user.id, e);
+    }
+
+    // Provider security events name the provider account, not ours.
+    if let Err(e) = crate::db::account_security::link_identity(&state.db, &provider, &provider_subject, &user.id).await {
+        tracing::warn!("Failed to record {} identity for user {}: {}", provider, user.id, e);
+    }
+
+    match crate::db::account_security::is_user_locked(&state.db, &user.id).await {
+        Ok(false) => {}
+        Ok(true) => {
+            tracing::warn!("Sign-in refused for locked user {}", user.id);
+            return (StatusCode::FORBIDDEN, "Account is locked").into_response();
+        }
+        Err(e) => {
+            tracing::error!("Account lock check failed: {}", e);
+            return (StatusCode::SERVICE_UNAVAILABLE, "Authentication unavailable").into_response();
+        }
+    }
+
+    let tokens = match start_session(&state.db, &user, &client).await {
         Ok(t) => t,
         Err(e) => {
-            tracing::error!("JWT creation failed: {}", e);
+            tracing::error!("Session creation failed: {}", e);
             return (StatusCode::INTERNAL_SERVER_ERROR, "Auth failed").into_response();
         }
     };
 
-    let cookie = build_auth_cookie(&token);
+    // Also emits a readable CSRF cookie so client-side JS can add the X-CSRF-Token header.
     let mut response = Redirect::to("/").into_response();
-    if let Ok(header_value) = HeaderValue::from_str(&cookie) {
-        response.headers_mut().insert(header::SET_COOKIE, header_value);
-    }
-    // Also emit a readable CSRF cookie so client-side JS can add the X-CSRF-Token header.
-    let csrf_cookie = build_csrf_cookie(&token);
-    if let Ok(header_value) = HeaderValue::from_str(&csrf_cookie) {
-        response.headers_mut().append(header::SET_COOKIE, header_value);
-    }
+    set_session_cookies(&mut response, &tokens);
     
     // Clear the oauth_state cookie if it exists (only for AuthCallback::Code flow)
     let clear_state_cookie = clear_oauth_state_cookie();
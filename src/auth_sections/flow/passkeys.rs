@@ -0,0 +1,669 @@
+const PASSKEY_CHALLENGE_TTL_SECS: i64 = 300;
+const PASSKEY_PURPOSE_REGISTER: &str = "register";
+const PASSKEY_PURPOSE_LOGIN: &str = "login";
+const PASSKEY_PURPOSE_VAULT: &str = "vault";
+
+#[derive(Serialize, utoipa::ToSchema)]
+pub struct PasskeyOptionsResponse {
+    /// Send back with the ceremony result; each challenge is single use and expires after five minutes.
+    pub challenge_id: String,
+    /// Options for `navigator.credentials.create()` / `.get()`, with binary fields base64url-encoded.
+    #[serde(rename = "publicKey")]
+    pub public_key: Value,
+}
+
+#[derive(Deserialize, utoipa::ToSchema)]
+pub struct PasskeyAttestationResponse {
+    #[serde(rename = "clientDataJSON")]
+    pub client_data_json: String,
+    #[serde(rename = "attestationObject")]
+    pub attestation_object: String,
+    #[serde(default)]
+    pub transports: Vec<String>,
+}
+
+/// `PublicKeyCredential.toJSON()` output of a registration ceremony.
+#[derive(Deserialize, utoipa::ToSchema)]
+pub struct PasskeyRegistrationCredential {
+    pub id: String,
+    pub response: PasskeyAttestationResponse,
+}
+
+#[derive(Deserialize, utoipa::ToSchema)]
+pub struct RegisterPasskeyRequest {
+    pub challenge_id: String,
+    pub name: Option<String>,
+    pub credential: PasskeyRegistrationCredential,
+}
+
+#[derive(Deserialize, utoipa::ToSchema)]
+pub struct PasskeyAssertionResponse {
+    #[serde(rename = "clientDataJSON")]
+    pub client_data_json: String,
+    #[serde(rename = "authenticatorData")]
+    pub authenticator_data: String,
+    pub signature: String,
+    #[serde(rename = "userHandle")]
+    pub user_handle: Option<String>,
+}
+
+/// `PublicKeyCredential.toJSON()` output of an authentication ceremony.
+#[derive(Deserialize, utoipa::ToSchema)]
+pub struct PasskeyAssertionCredential {
+    pub id: String,
+    pub response: PasskeyAssertionResponse,
+}
+
+#[derive(Deserialize, utoipa::ToSchema)]
+pub struct PasskeyAssertionRequest {
+    pub challenge_id: String,
+    pub credential: PasskeyAssertionCredential,
+}
+
+#[derive(Serialize, utoipa::ToSchema)]
+pub struct VaultUnlockResponse {
+    pub vault_credential_id: String,
+}
+
+fn relying_party() -> Result<crate::webauthn::RelyingParty, (StatusCode, &'static str)> {
+    crate::webauthn::RelyingParty::from_env().map_err(|e| {
+        tracing::error!("WebAuthn is not configured: {}", e);
+        (StatusCode::SERVICE_UNAVAILABLE, "Passkeys are not configured")
+    })
+}
+
+/// Credential ids are stored base64url; clients may send either alphabet.
+fn normalize_credential_id(id: &str) -> Option<String> {
+    crate::webauthn::decode_b64url(id)
+        .ok()
+        .filter(|bytes| !bytes.is_empty())
+        .map(|bytes| crate::webauthn::encode_b64url(&bytes))
+}
+
+/// With `PASSKEY_VAULT_GATING=true`, a vault credential that is a registered
+/// passkey is only released by `/api/me/vault/unlock` after a fresh assertion.
+pub(crate) async fn vault_credential_is_gated(db: &crate::db::DbPool, user_id: &str, vault_credential_id: Option<&str>) -> bool {
+    if env::var("PASSKEY_VAULT_GATING").map(|v| v != "true").unwrap_or(true) {
+        return false;
+    }
+    let Some(id) = vault_credential_id.and_then(normalize_credential_id) else {
+        return false;
+    };
+    match crate::db::passkeys::find_credential(db, &id).await {
+        Ok(Some(stored)) => stored.user_id == user_id,
+        Ok(None) => false,
+        Err(e) => {
+            // Fail closed: hiding the id only forces an unlock ceremony.
+            tracing::error!("Failed to check vault passkey: {}", e);
+            true
+        }
+    }
+}
+
+/// The vault passkey's normalized id while `vault_credential_is_gated`; its rawId is the
+/// vault key material, so responses other than a vault unlock must not carry it.
+async fn gated_vault_passkey_id(db: &crate::db::DbPool, user_id: &str) -> Result<Option<String>, (StatusCode, &'static str)> {
+    let vault_credential_id = match crate::db::users::get_user_profile(db, user_id).await {
+        Ok(Some(profile)) => profile.9,
+        Ok(None) => None,
+        Err(e) => {
+            tracing::error!("Failed to load profile: {}", e);
+            return Err((StatusCode::INTERNAL_SERVER_ERROR, "Database Error"));
+        }
+    };
+    if !vault_credential_is_gated(db, user_id, vault_credential_id.as_deref()).await {
+        return Ok(None);
+    }
+    Ok(vault_credential_id.as_deref().and_then(normalize_credential_id))
+}
+
+/// Blanks the id of the gated vault passkey so it is listed without its key material.
+fn withhold_vault_passkey_id(passkeys: &mut [crate::db::models::PasskeyCredential], vault_passkey_id: Option<&str>) {
+    for passkey in passkeys.iter_mut().filter(|p| Some(p.id.as_str()) == vault_passkey_id) {
+        passkey.id = String::new();
+    }
+}
+
+async fn redact_gated_vault_credential(db: &crate::db::DbPool, profile: &mut UserProfile) {
+    if vault_credential_is_gated(db, &profile.id, profile.vault_credential_id.as_deref()).await {
+        profile.vault_credential_id = None;
+    }
+}
+
+async fn issue_passkey_challenge(
+    db: &crate::db::DbPool,
+    user_id: Option<&str>,
+    purpose: &str,
+) -> Result<(String, Vec<u8>), (StatusCode, &'static str)> {
+    let challenge = crate::webauthn::new_challenge();
+    let id = uuid::Uuid::new_v4().simple().to_string();
+    let input = crate::db::models::NewWebauthnChallenge {
+        id: id.clone(),
+        user_id: user_id.map(str::to_string),
+        purpose: purpose.to_string(),
+        challenge: crate::webauthn::encode_b64url(&challenge),
+        ttl_seconds: PASSKEY_CHALLENGE_TTL_SECS,
+    };
+    if let Err(e) = crate::db::passkeys::create_challenge(db, &input).await {
+        tracing::error!("Failed to store WebAuthn challenge: {}", e);
+        return Err((StatusCode::INTERNAL_SERVER_ERROR, "Database Error"));
+    }
+    Ok((id, challenge))
+}
+
+async fn take_passkey_challenge(
+    db: &crate::db::DbPool,
+    challenge_id: &str,
+    purpose: &str,
+    user_id: Option<&str>,
+) -> Result<Vec<u8>, (StatusCode, &'static str)> {
+    let challenge = match crate::db::passkeys::take_challenge(db, challenge_id, purpose).await {
+        Ok(Some(c)) => c,
+        Ok(None) => return Err((StatusCode::BAD_REQUEST, "Challenge expired or already used")),
+        Err(e) => {
+            tracing::error!("Failed to load WebAuthn challenge: {}", e);
+            return Err((StatusCode::INTERNAL_SERVER_ERROR, "Database Error"));
+        }
+    };
+    if challenge.user_id.as_deref() != user_id {
+        return Err((StatusCode::BAD_REQUEST, "Challenge expired or already used"));
+    }
+    crate::webauthn::decode_b64url(&challenge.challenge).map_err(|e| {
+        tracing::error!("Stored WebAuthn challenge is corrupt: {}", e);
+        (StatusCode::INTERNAL_SERVER_ERROR, "Server Error")
+    })
+}
+
+/// Verifies an assertion for a stored credential and advances its counter.
+async fn verify_passkey_assertion(
+    db: &crate::db::DbPool,
+    rp: &crate::webauthn::RelyingParty,
+    challenge: &[u8],
+    credential: &PasskeyAssertionCredential,
+) -> Result<crate::db::models::StoredPasskey, (StatusCode, &'static str)> {
+    let unauthorized = || (StatusCode::UNAUTHORIZED, "Passkey verification failed");
+    let credential_id = normalize_credential_id(&credential.id).ok_or_else(unauthorized)?;
+    let stored = match crate::db::passkeys::find_credential(db, &credential_id).await {
+        Ok(Some(stored)) => stored,
+        Ok(None) => return Err(unauthorized()),
+        Err(e) => {
+            tracing::error!("Failed to load passkey: {}", e);
+            return Err((StatusCode::INTERNAL_SERVER_ERROR, "Database Error"));
+        }
+    };
+
+    // The user handle, when sent, must name the credential's owner.
+    if let Some(handle) = credential.response.user_handle.as_deref().filter(|h| !h.is_empty()) {
+        let handle = crate::webauthn::decode_b64url(handle).map_err(|_| unauthorized())?;
+        if handle != stored.user_id.as_bytes() {
+            tracing::warn!("Passkey user handle does not match credential owner");
+            return Err(unauthorized());
+        }
+    }
+
+    let decode = |value: &str| crate::webauthn::decode_b64url(value).map_err(|_| unauthorized());
+    let client_data_json = decode(&credential.response.client_data_json)?;
+    let authenticator_data = decode(&credential.response.authenticator_data)?;
+    let signature = decode(&credential.response.signature)?;
+    let public_key = decode(&stored.public_key)?;
+
+    let verified = crate::webauthn::verify_assertion(
+        rp,
+        challenge,
+        &client_data_json,
+        &authenticator_data,
+        &signature,
+        &public_key,
+        u32::try_from(stored.sign_count).unwrap_or(u32::MAX),
+        true,
+    )
+    .map_err(|e| {
+        tracing::warn!("Passkey assertion rejected: {}", e);
+        unauthorized()
+    })?;
+
+    match crate::db::passkeys::record_assertion(db, &stored.id, stored.sign_count, verified.sign_count as i64).await {
+        Ok(true) => Ok(stored),
+        Ok(false) => {
+            tracing::warn!("Passkey counter changed concurrently; rejecting assertion");
+            Err(unauthorized())
+        }
+        Err(e) => {
+            tracing::error!("Failed to record passkey use: {}", e);
+            Err((StatusCode::INTERNAL_SERVER_ERROR, "Database Error"))
+        }
+    }
+}
+
+fn credential_descriptors(passkeys: &[crate::db::models::PasskeyCredential]) -> Vec<Value> {
+    passkeys
+        .iter()
+        .map(|p| serde_json::json!({ "type": "public-key", "id": p.id, "transports": p.transports }))
+        .collect()
+}
+
+#[utoipa::path(
+    post,
+    path = "/api/me/passkeys/options",
+    tag = "passkeys",
+    responses(
+        (status = 200, description = "Registration options for `navigator.credentials.create()`", body = PasskeyOptionsResponse),
+        (status = 503, description = "WebAuthn is not configured", body = String),
+    )
+)]
+pub async fn passkey_registration_options(
+    State(state): State<AppState>,
+    user: AuthenticatedUser,
+) -> impl IntoResponse {
+    let rp = match relying_party() {
+        Ok(rp) => rp,
+        Err(resp) => return resp.into_response(),
+    };
+    let mut existing = match crate::db::passkeys::list_credentials(&state.db, &user.id).await {
+        Ok(list) => list,
+        Err(e) => {
+            tracing::error!("Failed to list passkeys: {}", e);
+            return (StatusCode::INTERNAL_SERVER_ERROR, "Database Error").into_response();
+        }
+    };
+    // The gated vault passkey is left out of excludeCredentials, at worst allowing a
+    // second credential on the same authenticator.
+    match gated_vault_passkey_id(&state.db, &user.id).await {
+        Ok(Some(vault_passkey_id)) => existing.retain(|p| p.id != vault_passkey_id),
+        Ok(None) => {}
+        Err(resp) => return resp.into_response(),
+    }
+    let (challenge_id, challenge) = match issue_passkey_challenge(&state.db, Some(&user.id), PASSKEY_PURPOSE_REGISTER).await {
+        Ok(c) => c,
+        Err(resp) => return resp.into_response(),
+    };
+
+    Json(PasskeyOptionsResponse {
+        challenge_id,
+        public_key: serde_json::json!({
+            "challenge": crate::webauthn::encode_b64url(&challenge),
+            "rp": { "id": rp.id, "name": rp.name },
+            "user": {
+                "id": crate::webauthn::encode_b64url(user.id.as_bytes()),
+                "name": user.email,
+                "displayName": user.name,
+            },
+            "pubKeyCredParams": [
+                { "type": "public-key", "alg": crate::webauthn::COSE_ALG_ES256 },
+                { "type": "public-key", "alg": crate::webauthn::COSE_ALG_RS256 },
+            ],
+            "timeout": PASSKEY_CHALLENGE_TTL_SECS * 1000,
+            "attestation": "none",
+            // Discoverable credentials allow usernameless sign-in.
+            "authenticatorSelection": {
+                "residentKey": "required",
+                "requireResidentKey": true,
+                "userVerification": "preferred",
+            },
+            "excludeCredentials": credential_descriptors(&existing),
+        }),
+    })
+    .into_response()
+}
+
+#[utoipa::path(
+    post,
+    path = "/api/me/passkeys",
+    tag = "passkeys",
+    request_body = RegisterPasskeyRequest,
+    responses(
+        (status = 201, description = "Passkey registered", body = crate::db::models::PasskeyCredential),
+        (status = 400, description = "Challenge expired, or the attestation failed verification", body = String),
+        (status = 409, description = "Credential already registered", body = String),
+    )
+)]
+pub async fn register_passkey(
+    State(state): State<AppState>,
+    user: AuthenticatedUser,
+    Json(req): Json<RegisterPasskeyRequest>,
+) -> impl IntoResponse {
+    let rp = match relying_party() {
+        Ok(rp) => rp,
+        Err(resp) => return resp.into_response(),
+    };
+    let name = req.name.as_deref().map(str::trim).filter(|n| !n.is_empty()).unwrap_or("Passkey");
+    if name.len() > 255 {
+        return (StatusCode::BAD_REQUEST, "Name too long").into_response();
+    }
+    let challenge = match take_passkey_challenge(&state.db, &req.challenge_id, PASSKEY_PURPOSE_REGISTER, Some(&user.id)).await {
+        Ok(c) => c,
+        Err(resp) => return resp.into_response(),
+    };
+
+    let (client_data_json, attestation_object) = match (
+        crate::webauthn::decode_b64url(&req.credential.response.client_data_json),
+        crate::webauthn::decode_b64url(&req.credential.response.attestation_object),
+    ) {
+        (Ok(c), Ok(a)) => (c, a),
+        _ => return (StatusCode::BAD_REQUEST, "Malformed credential").into_response(),
+    };
+    let registered = match crate::webauthn::verify_registration(&rp, &challenge, &client_data_json, &attestation_object, false) {
+        Ok(r) => r,
+        Err(e) => {
+            tracing::warn!("Passkey registration rejected: {}", e);
+            return (StatusCode::BAD_REQUEST, "Passkey verification failed").into_response();
+        }
+    };
+    let credential_id = crate::webauthn::encode_b64url(&registered.credential_id);
+    if normalize_credential_id(&req.credential.id).as_deref() != Some(credential_id.as_str()) {
+        return (StatusCode::BAD_REQUEST, "Credential id does not match attestation").into_response();
+    }
+
+    let transports: Vec<String> = req
+        .credential
+        .response
+        .transports
+        .iter()
+        .filter(|t| matches!(t.as_str(), "usb" | "nfc" | "ble" | "internal" | "hybrid" | "smart-card"))
+        .cloned()
+        .collect();
+    let input = crate::db::models::NewPasskeyCredential {
+        id: credential_id.clone(),
+        user_id: user.id.clone(),
+        name: name.to_string(),
+        public_key: crate::webauthn::encode_b64url(&registered.public_key),
+        algorithm: registered.algorithm,
+        sign_count: registered.sign_count as i64,
+        transports: transports.clone(),
+    };
+    match crate::db::passkeys::insert_credential(&state.db, &input).await {
+        Ok(true) => {}
+        Ok(false) => return (StatusCode::CONFLICT, "Passkey already registered").into_response(),
+        Err(e) => {
+            tracing::error!("Failed to store passkey: {}", e);
+            return (StatusCode::INTERNAL_SERVER_ERROR, "Database Error").into_response();
+        }
+    }
+
+    (
+        StatusCode::CREATED,
+        Json(crate::db::models::PasskeyCredential {
+            id: credential_id,
+            name: input.name,
+            algorithm: input.algorithm,
+            transports,
+            created_at: Utc::now(),
+            last_used_at: None,
+        }),
+    )
+        .into_response()
+}
+
+#[utoipa::path(
+    get,
+    path = "/api/me/passkeys",
+    tag = "passkeys",
+    responses((status = 200, description = "`{passkeys: [PasskeyCredential]}`, newest first; the id of a gated vault passkey is empty", body = serde_json::Value))
+)]
+pub async fn list_passkeys(
+    State(state): State<AppState>,
+    user: AuthenticatedUser,
+) -> impl IntoResponse {
+    let mut passkeys = match crate::db::passkeys::list_credentials(&state.db, &user.id).await {
+        Ok(passkeys) => passkeys,
+        Err(e) => {
+            tracing::error!("Failed to list passkeys: {}", e);
+            return (StatusCode::INTERNAL_SERVER_ERROR, "Database Error").into_response();
+        }
+    };
+    match gated_vault_passkey_id(&state.db, &user.id).await {
+        Ok(vault_passkey_id) => withhold_vault_passkey_id(&mut passkeys, vault_passkey_id.as_deref()),
+        Err(resp) => return resp.into_response(),
+    }
+    Json(serde_json::json!({ "passkeys": passkeys })).into_response()
+}
+
+#[utoipa::path(
+    delete,
+    path = "/api/me/passkeys/{id}",
+    tag = "passkeys",
+    params(("id" = String, Path, description = "base64url credential id")),
+    responses(
+        (status = 204, description = "Passkey removed"),
+        (status = 404, description = "No such passkey", body = String),
+        (status = 409, description = "The passkey protects the encrypted vault", body = String),
+    )
+)]
+pub async fn delete_passkey(
+    State(state): State<AppState>,
+    user: AuthenticatedUser,
+    Path(id): Path<String>,
+) -> impl IntoResponse {
+    let Some(credential_id) = normalize_credential_id(&id) else {
+        return (StatusCode::NOT_FOUND, "Not found").into_response();
+    };
+    // Removing the vault passkey would leave the vault with no way to unlock once gated.
+    if let Ok(Some(profile)) = crate::db::users::get_user_profile(&state.db, &user.id).await {
+        if profile.9.as_deref().and_then(normalize_credential_id).as_deref() == Some(credential_id.as_str()) {
+            return (StatusCode::CONFLICT, "This passkey unlocks your encrypted vault").into_response();
+        }
+    }
+    match crate::db::passkeys::delete_credential(&state.db, &user.id, &credential_id).await {
+        Ok(true) => StatusCode::NO_CONTENT.into_response(),
+        Ok(false) => (StatusCode::NOT_FOUND, "Not found").into_response(),
+        Err(e) => {
+            tracing::error!("Failed to delete passkey: {}", e);
+            (StatusCode::INTERNAL_SERVER_ERROR, "Database Error").into_response()
+        }
+    }
+}
+
+/// Options for usernameless passkey sign-in.
+pub async fn passkey_login_options(State(state): State<AppState>) -> impl IntoResponse {
+    let rp = match relying_party() {
+        Ok(rp) => rp,
+        Err(resp) => return resp.into_response(),
+    };
+    let (challenge_id, challenge) = match issue_passkey_challenge(&state.db, None, PASSKEY_PURPOSE_LOGIN).await {
+        Ok(c) => c,
+        Err(resp) => return resp.into_response(),
+    };
+    Json(PasskeyOptionsResponse {
+        challenge_id,
+        public_key: serde_json::json!({
+            "challenge": crate::webauthn::encode_b64url(&challenge),
+            "rpId": rp.id,
+            "timeout": PASSKEY_CHALLENGE_TTL_SECS * 1000,
+            "userVerification": "required",
+            "allowCredentials": [],
+        }),
+    })
+    .into_response()
+}
+
+/// Completes passkey sign-in and issues the same session cookies as OAuth login,
+/// or the tokens themselves for bearer clients.
+pub async fn passkey_login(
+    State(state): State<AppState>,
+    client: SessionClient,
+    delivery: TokenDelivery,
+    Json(req): Json<PasskeyAssertionRequest>,
+) -> impl IntoResponse {
+    let rp = match relying_party() {
+        Ok(rp) => rp,
+        Err(resp) => return resp.into_response(),
+    };
+    let challenge = match take_passkey_challenge(&state.db, &req.challenge_id, PASSKEY_PURPOSE_LOGIN, None).await {
+        Ok(c) => c,
+        Err(resp) => return resp.into_response(),
+    };
+    let stored = match verify_passkey_assertion(&state.db, &rp, &challenge, &req.credential).await {
+        Ok(s) => s,
+        Err(resp) => return resp.into_response(),
+    };
+
+    let mut user = match crate::db::users::get_user_profile(&state.db, &stored.user_id).await {
+        Ok(Some((email, name, provider, filing_status, agi, marginal_tax_rate, itemize_deductions, is_encrypted, encrypted_payload, vault_credential_id))) => UserProfile {
+            id: stored.user_id.clone(),
+            email,
+            name,
+            provider,
+            filing_status,
+            agi,
+            marginal_tax_rate,
+            itemize_deductions,
+            is_encrypted,
+            encrypted_payload,
+            vault_credential_id,
+        },
+        Ok(None) => return (StatusCode::UNAUTHORIZED, "Passkey verification failed").into_response(),
+        Err(e) => {
+            tracing::error!("Failed to load passkey owner: {}", e);
+            return (StatusCode::INTERNAL_SERVER_ERROR, "Database Error").into_response();
+        }
+    };
+
+    match crate::db::account_security::is_user_locked(&state.db, &user.id).await {
+        Ok(false) => {}
+        Ok(true) => return (StatusCode::FORBIDDEN, "Account is locked").into_response(),
+        Err(e) => {
+            tracing::error!("Account lock check failed: {}", e);
+            return (StatusCode::SERVICE_UNAVAILABLE, "Authentication unavailable").into_response();
+        }
+    }
+
+    let tokens = match start_session(&state.db, &user, &client).await {
+        Ok(t) => t,
+        Err(e) => {
+            tracing::error!("Session creation failed: {}", e);
+            return (StatusCode::INTERNAL_SERVER_ERROR, "Auth failed").into_response();
+        }
+    };
+    redact_gated_vault_credential(&state.db, &mut user).await;
+
+    if delivery == TokenDelivery::Bearer {
+        return Json(AuthResponse { user, tokens: Some(tokens) }).into_response();
+    }
+    let mut response = Json(AuthResponse { user, tokens: None }).into_response();
+    set_session_cookies(&mut response, &tokens);
+    response
+}
+
+#[utoipa::path(
+    post,
+    path = "/api/me/vault/options",
+    tag = "passkeys",
+    responses(
+        (status = 200, description = "Assertion options for unlocking the vault", body = PasskeyOptionsResponse),
+        (status = 404, description = "The vault is not protected by a registered passkey", body = String),
+    )
+)]
+pub async fn vault_unlock_options(
+    State(state): State<AppState>,
+    user: AuthenticatedUser,
+) -> impl IntoResponse {
+    let rp = match relying_party() {
+        Ok(rp) => rp,
+        Err(resp) => return resp.into_response(),
+    };
+    let vault_credential_id = match crate::db::users::get_user_profile(&state.db, &user.id).await {
+        Ok(Some(profile)) => profile.9,
+        Ok(None) => None,
+        Err(e) => {
+            tracing::error!("Failed to load profile: {}", e);
+            return (StatusCode::INTERNAL_SERVER_ERROR, "Database Error").into_response();
+        }
+    };
+    if !vault_credential_is_gated(&state.db, &user.id, vault_credential_id.as_deref()).await {
+        return (StatusCode::NOT_FOUND, "Vault is not protected by a passkey").into_response();
+    }
+    let (challenge_id, challenge) = match issue_passkey_challenge(&state.db, Some(&user.id), PASSKEY_PURPOSE_VAULT).await {
+        Ok(c) => c,
+        Err(resp) => return resp.into_response(),
+    };
+    Json(PasskeyOptionsResponse {
+        challenge_id,
+        // allowCredentials stays empty: listing the id here would disclose the key material.
+        public_key: serde_json::json!({
+            "challenge": crate::webauthn::encode_b64url(&challenge),
+            "rpId": rp.id,
+            "timeout": PASSKEY_CHALLENGE_TTL_SECS * 1000,
+            "userVerification": "required",
+            "allowCredentials": [],
+        }),
+    })
+    .into_response()
+}
+
+#[utoipa::path(
+    post,
+    path = "/api/me/vault/unlock",
+    tag = "passkeys",
+    request_body = PasskeyAssertionRequest,
+    responses(
+        (status = 200, description = "Assertion verified with the vault passkey", body = VaultUnlockResponse),
+        (status = 401, description = "Assertion invalid or made with another passkey", body = String),
+    )
+)]
+pub async fn vault_unlock(
+    State(state): State<AppState>,
+    user: AuthenticatedUser,
+    Json(req): Json<PasskeyAssertionRequest>,
+) -> impl IntoResponse {
+    let rp = match relying_party() {
+        Ok(rp) => rp,
+        Err(resp) => return resp.into_response(),
+    };
+    let challenge = match take_passkey_challenge(&state.db, &req.challenge_id, PASSKEY_PURPOSE_VAULT, Some(&user.id)).await {
+        Ok(c) => c,
+        Err(resp) => return resp.into_response(),
+    };
+    let vault_credential_id = match crate::db::users::get_user_profile(&state.db, &user.id).await {
+        Ok(Some(profile)) => profile.9,
+        Ok(None) => None,
+        Err(e) => {
+            tracing::error!("Failed to load profile: {}", e);
+            return (StatusCode::INTERNAL_SERVER_ERROR, "Database Error").into_response();
+        }
+    };
+    let Some(vault_credential_id) = vault_credential_id else {
+        return (StatusCode::NOT_FOUND, "Vault is not protected by a passkey").into_response();
+    };
+    if normalize_credential_id(&vault_credential_id) != normalize_credential_id(&req.credential.id) {
+        return (StatusCode::UNAUTHORIZED, "Passkey verification failed").into_response();
+    }
+    let stored = match verify_passkey_assertion(&state.db, &rp, &challenge, &req.credential).await {
+        Ok(s) => s,
+        Err(resp) => return resp.into_response(),
+    };
+    if stored.user_id != user.id {
+        return (StatusCode::UNAUTHORIZED, "Passkey verification failed").into_response();
+    }
+
+    Json(VaultUnlockResponse { vault_credential_id }).into_response()
+}
+
+#[cfg(test)]
+mod passkey_tests {
+    use super::*;
+
+    fn passkey(id: &str) -> crate::db::models::PasskeyCredential {
+        crate::db::models::PasskeyCredential {
+            id: id.to_string(),
+            name: "Passkey".to_string(),
+            algorithm: crate::webauthn::COSE_ALG_ES256,
+            transports: Vec::new(),
+            created_at: Utc::now(),
+            last_used_at: None,
+        }
+    }
+
+    #[test]
+    fn test_gated_vault_passkey_id_is_absent_from_listing() {
+        let mut passkeys = vec![passkey("vault-cred"), passkey("other-cred")];
+        withhold_vault_passkey_id(&mut passkeys, Some("vault-cred"));
+        let listed = serde_json::to_string(&passkeys).unwrap();
+        assert!(!listed.contains("vault-cred"));
+        assert_eq!(passkeys[0].id, "");
+        assert_eq!(passkeys[1].id, "other-cred");
+
+        let mut ungated = vec![passkey("vault-cred")];
+        withhold_vault_passkey_id(&mut ungated, None);
+        assert_eq!(ungated[0].id, "vault-cred");
+    }
+}
@@ -0,0 +1,307 @@
+// Short-lived access JWTs are renewed with rotating refresh tokens. Each refresh
+// token works once: presenting it spends it and yields a successor in the same
+// family (one family per sign-in). A spent token coming back means a copy has
+// leaked, so the whole family and the session it backs are revoked.
+
+const REFRESH_TOKEN_PREFIX: &str = "dtr_";
+const REFRESH_COOKIE_NAME: &str = "refresh_token";
+/// Only the refresh and logout endpoints ever see the refresh cookie.
+const REFRESH_COOKIE_PATH: &str = "/auth";
+const TOKEN_DELIVERY_HEADER: &str = "x-token-delivery";
+const DEFAULT_ACCESS_TOKEN_TTL_MINUTES: i64 = 15;
+const DEFAULT_REFRESH_TOKEN_TTL_DAYS: i64 = 30;
+
+fn access_token_ttl_secs() -> i64 {
+    env::var("ACCESS_TOKEN_TTL_MINUTES")
+        .ok()
+        .and_then(|v| v.parse::<i64>().ok())
+        .filter(|minutes| *minutes > 0)
+        .unwrap_or(DEFAULT_ACCESS_TOKEN_TTL_MINUTES)
+        * 60
+}
+
+fn refresh_token_ttl_secs() -> i64 {
+    env::var("REFRESH_TOKEN_TTL_DAYS")
+        .ok()
+        .and_then(|v| v.parse::<i64>().ok())
+        .filter(|days| *days > 0)
+        .unwrap_or(DEFAULT_REFRESH_TOKEN_TTL_DAYS)
+        * 24
+        * 3600
+}
+
+/// A new refresh token and the hash it is stored under. The secret is random
+/// enough that a plain SHA-256 is as good as a slow hash here.
+fn new_refresh_token() -> (String, String) {
+    let token = format!(
+        "{}{}{}",
+        REFRESH_TOKEN_PREFIX,
+        uuid::Uuid::new_v4().simple(),
+        uuid::Uuid::new_v4().simple()
+    );
+    let hash = refresh_token_hash(&token);
+    (token, hash)
+}
+
+fn refresh_token_hash(token: &str) -> String {
+    use sha2::{Digest, Sha256};
+    Sha256::digest(token.as_bytes())
+        .iter()
+        .map(|b| format!("{:02x}", b))
+        .collect()
+}
+
+/// What a sign-in or refresh hands the client. Browsers get these as cookies;
+/// bearer clients get this body.
+#[derive(Serialize, Debug, Clone)]
+pub struct SessionTokens {
+    pub access_token: String,
+    pub token_type: &'static str,
+    /// Seconds until the access token expires.
+    pub expires_in: i64,
+    pub refresh_token: String,
+    /// Seconds until the refresh token expires if it is not used.
+    pub refresh_expires_in: i64,
+}
+
+impl SessionTokens {
+    fn new(access_token: String, refresh_token: String) -> Self {
+        SessionTokens {
+            access_token,
+            token_type: "Bearer",
+            expires_in: access_token_ttl_secs(),
+            refresh_token,
+            refresh_expires_in: refresh_token_ttl_secs(),
+        }
+    }
+}
+
+/// How a sign-in returns its tokens. Non-browser clients send
+/// `X-Token-Delivery: bearer` to receive them in the response body instead of
+/// as cookies.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TokenDelivery {
+    Cookie,
+    Bearer,
+}
+
+impl<S> FromRequestParts<S> for TokenDelivery
+where
+    S: Send + Sync,
+{
+    type Rejection = std::convert::Infallible;
+
+    async fn from_request_parts(parts: &mut Parts, _state: &S) -> Result<Self, Self::Rejection> {
+        let bearer = parts
+            .headers
+            .get(TOKEN_DELIVERY_HEADER)
+            .and_then(|v| v.to_str().ok())
+            .is_some_and(|v| v.trim().eq_ignore_ascii_case("bearer"));
+        Ok(if bearer { TokenDelivery::Bearer } else { TokenDelivery::Cookie })
+    }
+}
+
+fn build_refresh_cookie(token: &str) -> String {
+    let secure = env::var("RUST_ENV").unwrap_or_else(|_| "development".to_string()) == "production";
+    let mut cookie = format!(
+        "{}={}; HttpOnly; SameSite=Strict; Path={}; Max-Age={}",
+        REFRESH_COOKIE_NAME,
+        token,
+        REFRESH_COOKIE_PATH,
+        refresh_token_ttl_secs()
+    );
+    if secure {
+        cookie.push_str("; Secure");
+    }
+    cookie
+}
+
+fn clear_refresh_cookie() -> String {
+    let secure = env::var("RUST_ENV").unwrap_or_else(|_| "development".to_string()) == "production";
+    let mut cookie = format!(
+        "{}=; HttpOnly; SameSite=Strict; Path={}; Max-Age=0; Expires=Thu, 01 Jan 1970 00:00:00 GMT",
+        REFRESH_COOKIE_NAME, REFRESH_COOKIE_PATH
+    );
+    if secure {
+        cookie.push_str("; Secure");
+    }
+    cookie
+}
+
+/// Sets the access, refresh and CSRF cookies for a browser session.
+fn set_session_cookies(response: &mut axum::response::Response, tokens: &SessionTokens) {
+    for cookie in [
+        build_auth_cookie(&tokens.access_token),
+        build_refresh_cookie(&tokens.refresh_token),
+        build_csrf_cookie(&tokens.access_token),
+    ] {
+        if let Ok(header_value) = HeaderValue::from_str(&cookie) {
+            response.headers_mut().append(header::SET_COOKIE, header_value);
+        }
+    }
+}
+
+/// Spends a refresh token and mints the next access and refresh tokens for its
+/// session. Reuse of a spent token revokes the session before failing.
+async fn rotate_session_tokens(
+    db: &crate::db::DbPool,
+    presented: &str,
+) -> Result<SessionTokens, (StatusCode, &'static str)> {
+    if !presented.starts_with(REFRESH_TOKEN_PREFIX) {
+        return Err((StatusCode::UNAUTHORIZED, "Invalid refresh token"));
+    }
+    let (next_token, next_hash) = new_refresh_token();
+    let expires_at = Utc::now().timestamp() + refresh_token_ttl_secs();
+    let rotation = crate::db::refresh_tokens::rotate_token(db, &refresh_token_hash(presented), &next_hash, expires_at)
+        .await
+        .map_err(|e| {
+            tracing::error!("Refresh token rotation failed: {}", e);
+            (StatusCode::SERVICE_UNAVAILABLE, "Service unavailable")
+        })?;
+
+    let (user_id, session_id) = match rotation {
+        crate::db::models::RefreshRotation::Rotated { user_id, family_id } => (user_id, family_id),
+        crate::db::models::RefreshRotation::Reused { user_id, family_id } => {
+            tracing::warn!("Refresh token reused for user {}; revoking session {}", user_id, family_id);
+            revoke_reused_session(db, &user_id, &family_id).await;
+            return Err((StatusCode::UNAUTHORIZED, "Invalid refresh token"));
+        }
+        crate::db::models::RefreshRotation::Invalid => return Err((StatusCode::UNAUTHORIZED, "Invalid refresh token")),
+    };
+
+    match crate::db::account_security::is_user_locked(db, &user_id).await {
+        Ok(false) => {}
+        Ok(true) => return Err((StatusCode::FORBIDDEN, "Account is locked")),
+        Err(e) => {
+            tracing::error!("Account lock check failed: {}", e);
+            return Err((StatusCode::SERVICE_UNAVAILABLE, "Service unavailable"));
+        }
+    }
+
+    let user = match crate::db::users::get_user_profile(db, &user_id).await {
+        Ok(Some((email, name, provider, filing_status, agi, marginal_tax_rate, itemize_deductions, is_encrypted, encrypted_payload, vault_credential_id))) => UserProfile {
+            id: user_id,
+            email,
+            name,
+            provider,
+            filing_status,
+            agi,
+            marginal_tax_rate,
+            itemize_deductions,
+            is_encrypted,
+            encrypted_payload,
+            vault_credential_id,
+        },
+        Ok(None) => return Err((StatusCode::UNAUTHORIZED, "Invalid refresh token")),
+        Err(e) => {
+            tracing::error!("Failed to load user for refresh: {}", e);
+            return Err((StatusCode::SERVICE_UNAVAILABLE, "Service unavailable"));
+        }
+    };
+
+    let (access_token, _) = create_jwt(&user, Some(&session_id)).map_err(|e| {
+        tracing::error!("JWT creation failed: {}", e);
+        (StatusCode::INTERNAL_SERVER_ERROR, "Auth failed")
+    })?;
+    Ok(SessionTokens::new(access_token, next_token))
+}
+
+/// Ends the session behind a replayed refresh token, including access tokens
+/// already refreshed from it, and leaves a trace for the owner.
+async fn revoke_reused_session(db: &crate::db::DbPool, user_id: &str, session_id: &str) {
+    let exp = (Utc::now().timestamp() + access_token_ttl_secs()) as usize;
+    if let Err(e) = revoke_jti(db, session_id, user_id, exp).await {
+        tracing::error!("Failed to revoke session {} after refresh token reuse: {}", session_id, e);
+    }
+    let details = serde_json::json!({ "session_id": session_id }).to_string();
+    if let Err(e) = crate::db::audit::log_audit(
+        db,
+        &uuid::Uuid::new_v4().to_string(),
+        user_id,
+        "refresh_token_reuse",
+        "user_sessions",
+        &Some(session_id.to_string()),
+        &Some(details),
+    )
+    .await
+    {
+        tracing::warn!("Failed to audit refresh token reuse: {}", e);
+    }
+}
+
+/// Ends the session a refresh token belongs to, spent or not.
+async fn revoke_refresh_token_session(db: &crate::db::DbPool, presented: &str) -> anyhow::Result<()> {
+    if !presented.starts_with(REFRESH_TOKEN_PREFIX) {
+        return Ok(());
+    }
+    let Some((user_id, session_id)) = crate::db::refresh_tokens::find_family(db, &refresh_token_hash(presented)).await? else {
+        return Ok(());
+    };
+    let exp = (Utc::now().timestamp() + access_token_ttl_secs()) as usize;
+    revoke_jti(db, &session_id, &user_id, exp).await
+}
+
+#[derive(Deserialize, Default)]
+pub struct RefreshRequest {
+    refresh_token: Option<String>,
+}
+
+/// Exchanges a refresh token for a new access token and refresh token. Bearer
+/// clients post `{"refresh_token": ...}` and get the tokens back in the body;
+/// browsers send nothing and get fresh cookies.
+pub async fn refresh_session(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    body: Option<Json<RefreshRequest>>,
+) -> axum::response::Response {
+    let bearer_token = body.and_then(|Json(req)| req.refresh_token);
+    let delivery = if bearer_token.is_some() { TokenDelivery::Bearer } else { TokenDelivery::Cookie };
+    let Some(presented) = bearer_token.or_else(|| extract_cookie_by_name(&headers, REFRESH_COOKIE_NAME)) else {
+        return (StatusCode::UNAUTHORIZED, "Missing refresh token").into_response();
+    };
+
+    match rotate_session_tokens(&state.db, &presented).await {
+        Ok(tokens) if delivery == TokenDelivery::Bearer => Json(tokens).into_response(),
+        Ok(tokens) => {
+            let mut response = Json(serde_json::json!({ "expires_in": tokens.expires_in })).into_response();
+            set_session_cookies(&mut response, &tokens);
+            response
+        }
+        Err((status, message)) => {
+            let mut response = (status, message).into_response();
+            // A dead refresh cookie would otherwise be sent again on every attempt.
+            if delivery == TokenDelivery::Cookie && status == StatusCode::UNAUTHORIZED {
+                if let Ok(header_value) = HeaderValue::from_str(&clear_refresh_cookie()) {
+                    response.headers_mut().append(header::SET_COOKIE, header_value);
+                }
+            }
+            response
+        }
+    }
+}
+
+#[cfg(test)]
+mod refresh_token_tests {
+    use super::*;
+
+    #[test]
+    fn test_refresh_tokens_are_unique_and_stored_only_as_hashes() {
+        let (token, hash) = new_refresh_token();
+        let (other, other_hash) = new_refresh_token();
+        assert!(token.starts_with(REFRESH_TOKEN_PREFIX));
+        assert_ne!(token, other);
+        assert_ne!(hash, other_hash);
+        assert_eq!(hash.len(), 64);
+        assert!(!hash.contains(&token[REFRESH_TOKEN_PREFIX.len()..]));
+        assert_eq!(refresh_token_hash(&token), hash);
+    }
+
+    #[test]
+    fn test_refresh_cookie_is_scoped_to_auth_endpoints() {
+        let cookie = build_refresh_cookie("dtr_abc");
+        assert!(cookie.starts_with("refresh_token=dtr_abc;"));
+        assert!(cookie.contains("HttpOnly"));
+        assert!(cookie.contains("Path=/auth;"));
+        assert!(clear_refresh_cookie().contains("Path=/auth;"));
+    }
+}
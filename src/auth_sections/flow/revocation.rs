@@ -0,0 +1,269 @@
+// Session JWT revocation. Oracle is the source of truth so a logout is seen by
+// every replica and survives restarts; a short-lived in-process cache keeps the
+// per-request check off the database.
+
+const DEFAULT_REVOCATION_CACHE_SECS: u64 = 30;
+
+static REVOCATION_CACHE: OnceLock<Mutex<RevocationCache>> = OnceLock::new();
+
+#[derive(Default)]
+struct RevocationCache {
+    /// Revoked jti -> token exp. Revocation is permanent, so these never go stale.
+    revoked: HashMap<String, usize>,
+    /// jti -> (token exp, owner watermark, when the store last said it was not revoked).
+    verified: HashMap<String, (usize, Option<i64>, std::time::Instant)>,
+    /// user id -> watermark this replica set itself, applied ahead of stale `verified` entries.
+    local_watermarks: HashMap<String, i64>,
+}
+
+fn revocation_cache() -> &'static Mutex<RevocationCache> {
+    REVOCATION_CACHE.get_or_init(|| Mutex::new(RevocationCache::default()))
+}
+
+fn revocation_cache_ttl() -> std::time::Duration {
+    let secs = env::var("TOKEN_REVOCATION_CACHE_SECS")
+        .ok()
+        .and_then(|v| v.parse::<u64>().ok())
+        .unwrap_or(DEFAULT_REVOCATION_CACHE_SECS);
+    std::time::Duration::from_secs(secs)
+}
+
+fn unix_now() -> usize {
+    let now_ts = Utc::now().timestamp();
+    if now_ts > 0 { now_ts as usize } else { 0 }
+}
+
+/// A token is dead when its jti was revoked or it was issued at or before the
+/// owner's watermark. Tokens minted before `iat` was added carry 0 and so fall
+/// under any watermark.
+fn issued_before_watermark(iat: usize, watermark: Option<i64>) -> bool {
+    watermark.is_some_and(|w| (iat as i64) <= w)
+}
+
+/// Cached verdict, or None when the store must be asked.
+fn cached_revocation(claims: &Claims, ttl: std::time::Duration) -> Option<bool> {
+    let now = unix_now();
+    let mut cache = revocation_cache().lock().ok()?;
+    if claims.exp <= now {
+        return Some(true);
+    }
+    if cache.revoked.contains_key(&claims.jti) || cache.revoked.contains_key(claims.session_id()) {
+        return Some(true);
+    }
+    if issued_before_watermark(claims.iat, cache.local_watermarks.get(&claims.sub).copied()) {
+        return Some(true);
+    }
+    match cache.verified.get(&claims.jti) {
+        Some((_, watermark, checked_at)) if checked_at.elapsed() < ttl => {
+            Some(issued_before_watermark(claims.iat, *watermark))
+        }
+        Some(_) => {
+            cache.verified.remove(&claims.jti);
+            None
+        }
+        None => None,
+    }
+}
+
+fn remember_revocation_status(claims: &Claims, status: &crate::db::models::TokenRevocationStatus, ttl: std::time::Duration) {
+    let now = unix_now();
+    let Ok(mut cache) = revocation_cache().lock() else {
+        return;
+    };
+    cache.revoked.retain(|_, exp| *exp > now);
+    cache
+        .verified
+        .retain(|_, (exp, _, checked_at)| *exp > now && checked_at.elapsed() < ttl);
+    if status.revoked {
+        cache.revoked.insert(claims.jti.clone(), claims.exp);
+    } else if status.locked {
+        // A lock can be lifted, so it is neither permanent nor cached as verified.
+        cache.verified.remove(&claims.jti);
+    } else {
+        let watermark = status.tokens_valid_after.map(|t| t.timestamp());
+        cache
+            .verified
+            .insert(claims.jti.clone(), (claims.exp, watermark, std::time::Instant::now()));
+    }
+}
+
+async fn is_token_revoked(db: &crate::db::DbPool, claims: &Claims) -> anyhow::Result<bool> {
+    let ttl = revocation_cache_ttl();
+    if let Some(revoked) = cached_revocation(claims, ttl) {
+        return Ok(revoked);
+    }
+    let status = crate::db::revocations::token_status(db, &claims.jti, claims.session_id(), &claims.sub).await?;
+    remember_revocation_status(claims, &status, ttl);
+    // Cache misses happen about once per TTL per token, which is often enough for "last seen".
+    if !status.revoked && !status.locked {
+        if let Err(e) = crate::db::sessions::touch_session(db, claims.session_id()).await {
+            tracing::warn!("Failed to record session activity: {}", e);
+        }
+    }
+    Ok(status.revoked
+        || status.locked
+        || issued_before_watermark(claims.iat, status.tokens_valid_after.map(|t| t.timestamp())))
+}
+
+pub async fn revoke_token_str(db: &crate::db::DbPool, token: &str) -> anyhow::Result<()> {
+    let secret = jwt_secret()?;
+
+    let mut validation = Validation::default();
+    validation.validate_exp = false;
+    if let Some(issuer) = jwt_issuer() {
+        validation.set_issuer(&[issuer.as_str()]);
+    }
+    if let Some(audience) = jwt_audience() {
+        validation.set_audience(&[audience.as_str()]);
+    }
+
+    let claims = decode::<Claims>(token, &DecodingKey::from_secret(secret.as_bytes()), &validation)?.claims;
+    // Ending the session covers every token refreshed under it, none of which
+    // outlives a freshly issued access token.
+    let exp = claims.exp.max(unix_now() + access_token_ttl_secs() as usize);
+    revoke_jti(db, claims.session_id(), &claims.sub, exp).await
+}
+
+/// Revokes a session, or a lone pre-session JWT, by id, together with its
+/// refresh tokens; `exp` bounds how long the revocation is kept.
+async fn revoke_jti(db: &crate::db::DbPool, jti: &str, user_id: &str, exp: usize) -> anyhow::Result<()> {
+    if exp <= unix_now() {
+        return Ok(());
+    }
+
+    if let Ok(mut cache) = revocation_cache().lock() {
+        cache.verified.remove(jti);
+        cache.revoked.insert(jti.to_string(), exp);
+    }
+    crate::db::revocations::revoke_token(db, jti, user_id, exp as i64).await
+}
+
+/// Invalidates every session JWT the user holds ("log out everywhere").
+pub async fn revoke_all_user_tokens(db: &crate::db::DbPool, user_id: &str) -> anyhow::Result<()> {
+    let now = Utc::now().timestamp();
+    if let Ok(mut cache) = revocation_cache().lock() {
+        let watermark = cache.local_watermarks.entry(user_id.to_string()).or_insert(now);
+        *watermark = (*watermark).max(now);
+    }
+    crate::db::revocations::revoke_tokens_issued_before(db, user_id, now).await
+}
+
+#[utoipa::path(
+    post,
+    path = "/api/me/logout-all",
+    tag = "profile",
+    responses((status = 200, description = "Every session for this account, including this one, is signed out", body = String))
+)]
+pub async fn logout_everywhere(
+    State(state): State<AppState>,
+    user: AuthenticatedUser,
+    headers: HeaderMap,
+) -> axum::response::Response {
+    if let Err(e) = revoke_all_user_tokens(&state.db, &user.id).await {
+        tracing::error!("Failed to revoke sessions: {}", e);
+        return (StatusCode::INTERNAL_SERVER_ERROR, "Database Error").into_response();
+    }
+    logout(State(state), headers).await.into_response()
+}
+
+#[cfg(test)]
+mod revocation_tests {
+    use super::*;
+
+    fn claims(sub: &str, iat: usize) -> Claims {
+        Claims {
+            sub: sub.to_string(),
+            exp: unix_now() + 3600,
+            iat,
+            jti: uuid::Uuid::new_v4().to_string(),
+            sid: None,
+            email: "revocation@example.test".to_string(),
+            provider: "local".to_string(),
+            name: "Revocation Test".to_string(),
+            iss: None,
+            aud: None,
+        }
+    }
+
+    #[test]
+    fn test_watermark_rejects_tokens_issued_at_or_before_it() {
+        assert!(!issued_before_watermark(100, None));
+        assert!(issued_before_watermark(100, Some(100)));
+        assert!(issued_before_watermark(0, Some(1)));
+        assert!(!issued_before_watermark(101, Some(100)));
+    }
+
+    #[test]
+    fn test_cache_answers_from_store_status_until_ttl() {
+        let ttl = std::time::Duration::from_secs(60);
+        let now = unix_now();
+        let fresh = claims(&uuid::Uuid::new_v4().to_string(), now);
+        assert_eq!(cached_revocation(&fresh, ttl), None);
+
+        remember_revocation_status(&fresh, &crate::db::models::TokenRevocationStatus::default(), ttl);
+        assert_eq!(cached_revocation(&fresh, ttl), Some(false));
+        assert_eq!(cached_revocation(&fresh, std::time::Duration::ZERO), None);
+
+        let revoked = claims(&fresh.sub, now);
+        let status = crate::db::models::TokenRevocationStatus { revoked: true, ..Default::default() };
+        remember_revocation_status(&revoked, &status, ttl);
+        // Revocations are not subject to the TTL.
+        assert_eq!(cached_revocation(&revoked, std::time::Duration::ZERO), Some(true));
+
+        let locked = claims(&fresh.sub, now);
+        let status = crate::db::models::TokenRevocationStatus { locked: true, ..Default::default() };
+        remember_revocation_status(&locked, &status, ttl);
+        assert_eq!(cached_revocation(&locked, ttl), None);
+    }
+
+    #[test]
+    fn test_local_watermark_overrides_cached_verdicts() {
+        let ttl = std::time::Duration::from_secs(60);
+        let user = uuid::Uuid::new_v4().to_string();
+        let old = claims(&user, unix_now() - 10);
+        remember_revocation_status(&old, &crate::db::models::TokenRevocationStatus::default(), ttl);
+        assert_eq!(cached_revocation(&old, ttl), Some(false));
+
+        revocation_cache()
+            .lock()
+            .unwrap()
+            .local_watermarks
+            .insert(user.clone(), Utc::now().timestamp() - 5);
+        assert_eq!(cached_revocation(&old, ttl), Some(true));
+        assert_eq!(cached_revocation(&claims(&user, unix_now()), ttl), None);
+    }
+
+    #[tokio::test]
+    async fn test_bearer_delivered_token_can_mutate_without_csrf() {
+        use tower::ServiceExt;
+
+        let _ = JWT_SECRET.set("revocation-test-secret".to_string());
+        let secret = jwt_secret().unwrap();
+        let mut claims = claims(&uuid::Uuid::new_v4().to_string(), unix_now());
+        claims.iss = jwt_issuer();
+        claims.aud = jwt_audience();
+        let token = encode(&Header::default(), &claims, &EncodingKey::from_secret(secret.as_bytes())).unwrap();
+        // Stand in for the store's verdict; the test database is unreachable.
+        let ttl = std::time::Duration::from_secs(60);
+        remember_revocation_status(&claims, &crate::db::models::TokenRevocationStatus::default(), ttl);
+
+        let app = crate::http_pipeline_tests::guarded_router(
+            "/api/donations",
+            axum::routing::post(|user: AuthenticatedUser| async move { user.id }),
+        );
+        let bearer = axum::http::Request::post("/api/donations")
+            .header(header::AUTHORIZATION, format!("Bearer {token}"))
+            .body(axum::body::Body::empty())
+            .unwrap();
+        let response = app.clone().oneshot(bearer).await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+
+        // The same token as the auth cookie still needs the double-submit header.
+        let cookie = axum::http::Request::post("/api/donations")
+            .header(header::COOKIE, format!("{AUTH_COOKIE_NAME}={token}"))
+            .body(axum::body::Body::empty())
+            .unwrap();
+        let response = app.oneshot(cookie).await.unwrap();
+        assert_eq!(response.status(), StatusCode::FORBIDDEN);
+    }
+}
@@ -40,103 +40,179 @@ pub async fn get_google_jwks(kid: &str) -> anyhow::Result<JwkSet> {
     Ok(jwks)
 }
 
-#[derive(Deserialize)]
-pub struct RiscEvent {
-    pub iss: String,
-    pub sub: Option<String>,
-    pub aud: String,
-    pub iat: usize,
-    pub jti: String,
-    pub events: Value,
+/// Lock reasons written by security events start with this, so an
+/// `account-enabled` event only lifts locks it could have placed.
+const RISC_LOCK_REASON_PREFIX: &str = "risc:";
+
+fn risc_lock_accounts() -> bool {
+    env::var("RISC_LOCK_ACCOUNTS")
+        .map(|v| matches!(v.trim().to_ascii_lowercase().as_str(), "1" | "true" | "yes"))
+        .unwrap_or(false)
 }
 
-pub async fn risc_webhook(
-    State(_state): State<AppState>,
-    body: Bytes,
-) -> impl IntoResponse {
-    // 1. Get the raw token from the body
-    let token_str = String::from_utf8_lossy(&body);
+/// Applies one event to the matching account and records it in audit_logs.
+/// Events for accounts we do not know are acknowledged and dropped.
+async fn apply_security_event(
+    db: &crate::db::DbPool,
+    event: &crate::security_events::SecurityEvent,
+    event_type: &str,
+    body: &Value,
+    lock_accounts: bool,
+) -> anyhow::Result<()> {
+    use crate::security_events::SecurityAction;
+
+    let action = crate::security_events::action_for(event_type, lock_accounts);
+    match action {
+        SecurityAction::Verification => {
+            tracing::info!("RISC: verification event received (state={:?})", body.get("state"));
+            return Ok(());
+        }
+        SecurityAction::Ignore => {
+            tracing::debug!("RISC: no action for event type {}", event_type);
+            return Ok(());
+        }
+        _ => {}
+    }
 
-    // 2. Verify the JWT header to get the 'kid'
-    let header = match jsonwebtoken::decode_header(token_str.as_bytes()) {
-        Ok(h) => h,
-        Err(_) => return StatusCode::BAD_REQUEST.into_response(),
+    let Some(subject) = event.subject_for(body) else {
+        tracing::warn!("RISC: {} event {} has no subject", event_type, event.jti);
+        return Ok(());
     };
-
-    let kid = match header.kid {
-        Some(k) => k,
-        None => return StatusCode::BAD_REQUEST.into_response(),
+    let Some(user_id) = crate::db::account_security::find_user_by_identity(db, "google", &subject).await? else {
+        tracing::info!("RISC: {} event {} is for an unknown account", event_type, event.jti);
+        return Ok(());
     };
 
-    // 3. Fetch Google's public keys (using cache)
-    let jwks = match get_google_jwks(&kid).await {
-        Ok(j) => j,
-        Err(e) => {
-            tracing::error!("Failed to fetch Google JWKS: {}", e);
-            return StatusCode::INTERNAL_SERVER_ERROR.into_response();
+    let reason = crate::security_events::event_reason(body);
+    // Personal access tokens minted during a hijack would outlive the revoked sessions.
+    let mut access_tokens_revoked = None;
+    let audit_action = match action {
+        SecurityAction::RevokeSessions => {
+            revoke_all_user_tokens(db, &user_id).await?;
+            access_tokens_revoked = Some(crate::db::access_tokens::revoke_all_tokens(db, &user_id).await?);
+            "risc_revoke_sessions"
         }
+        SecurityAction::RevokeSessionsAndLock => {
+            let lock_reason = format!(
+                "{}{}",
+                RISC_LOCK_REASON_PREFIX,
+                reason.as_deref().unwrap_or("account-disabled")
+            );
+            crate::db::account_security::lock_user(db, &user_id, &lock_reason).await?;
+            revoke_all_user_tokens(db, &user_id).await?;
+            access_tokens_revoked = Some(crate::db::access_tokens::revoke_all_tokens(db, &user_id).await?);
+            "risc_lock"
+        }
+        SecurityAction::Unlock => {
+            if !crate::db::account_security::unlock_user(db, &user_id, RISC_LOCK_REASON_PREFIX).await? {
+                return Ok(());
+            }
+            "risc_unlock"
+        }
+        SecurityAction::Verification | SecurityAction::Ignore => return Ok(()),
     };
+    tracing::info!("RISC: {} for user {} (event {})", audit_action, user_id, event.jti);
+
+    let details = serde_json::json!({
+        "event": event_type,
+        "reason": reason,
+        "jti": event.jti,
+        "issuer": event.issuer,
+        "access_tokens_revoked": access_tokens_revoked,
+    })
+    .to_string();
+    crate::db::audit::log_audit(
+        db,
+        &uuid::Uuid::new_v4().to_string(),
+        &user_id,
+        audit_action,
+        "users",
+        &Some(user_id.clone()),
+        &Some(details),
+    )
+    .await
+}
 
-    // 4. Verify the JWT
-    let jwk = match jwks.find(&kid) {
-        Some(j) => j,
-        None => return StatusCode::BAD_REQUEST.into_response(),
-    };
-
-    let decoding_key = match DecodingKey::from_jwk(jwk) {
-        Ok(k) => k,
-        Err(_) => return StatusCode::INTERNAL_SERVER_ERROR.into_response(),
+#[utoipa::path(
+    post,
+    path = "/api/auth/risc",
+    tag = "auth",
+    security(()),
+    request_body(content = String, content_type = "application/secevent+jwt", description = "Signed security event token from Google RISC"),
+    responses(
+        (status = 202, description = "Event accepted, or already processed"),
+        (status = 400, description = "Malformed token"),
+        (status = 401, description = "Signature or claims invalid"),
+        (status = 503, description = "Security events are not configured"),
+    )
+)]
+pub async fn risc_webhook(
+    State(state): State<AppState>,
+    body: Bytes,
+) -> impl IntoResponse {
+    let Ok(token) = std::str::from_utf8(&body).map(str::trim) else {
+        return StatusCode::BAD_REQUEST.into_response();
     };
-
-    let mut validation = Validation::new(header.alg);
-    validation.set_issuer(&["https://accounts.google.com"]);
-    // In production, 'aud' MUST match your Google Client ID
-    if let Ok(client_id) = std::env::var("GOOGLE_CLIENT_ID") {
-        validation.set_audience(&[client_id]);
+    if jsonwebtoken::decode_header(token).is_err() {
+        return StatusCode::BAD_REQUEST.into_response();
     }
 
-    let token_data = match jsonwebtoken::decode::<RiscEvent>(
-        token_str.as_bytes(),
-        &decoding_key,
-        &validation,
-    ) {
-        Ok(d) => d,
+    let config = match crate::security_events::SecurityEventConfig::google_from_env() {
+        Ok(c) => c,
+        Err(e) => {
+            tracing::error!("RISC: {}", e);
+            return StatusCode::SERVICE_UNAVAILABLE.into_response();
+        }
+    };
+    let http_client = match oauth_http_client() {
+        Ok(c) => c,
         Err(e) => {
-            tracing::warn!("RISC token verification failed: {}", e);
+            tracing::error!("RISC: HTTP client unavailable: {}", e);
+            return StatusCode::SERVICE_UNAVAILABLE.into_response();
+        }
+    };
+    let event = match crate::security_events::verify_security_event(http_client, &config, token).await {
+        Ok(e) => e,
+        Err(e) => {
+            tracing::warn!("RISC token verification failed: {:#}", e);
             return StatusCode::UNAUTHORIZED.into_response();
         }
     };
 
-    let payload = token_data.claims;
-    
-    // Handle verification event separately to log it specifically
-    if payload.events.get("https://schemas.openid.net/secevent/risc/event-type/verification").is_some()
-       || payload.events.get("http://schemas.openid.net/event/risc/v1/verification").is_some() {
-        tracing::info!("RISC: Received verification event from Google");
-        return StatusCode::ACCEPTED.into_response();
+    // Older tokens are rejected outright, so the jti only needs keeping that long.
+    let replay_window_ends = event.issued_at + crate::security_events::MAX_EVENT_AGE_SECONDS;
+    let event_types = event.events.iter().map(|(t, _)| t.as_str()).collect::<Vec<_>>().join(" ");
+    let event_types: String = event_types.chars().take(2000).collect();
+    match crate::db::account_security::record_security_event(
+        &state.db,
+        &event.jti,
+        &event.issuer,
+        &event_types,
+        replay_window_ends,
+    )
+    .await
+    {
+        Ok(true) => {}
+        Ok(false) => {
+            tracing::info!("RISC: event {} already processed", event.jti);
+            return StatusCode::ACCEPTED.into_response();
+        }
+        Err(e) => {
+            tracing::error!("RISC: failed to record event {}: {}", event.jti, e);
+            return StatusCode::INTERNAL_SERVER_ERROR.into_response();
+        }
     }
 
-    tracing::info!(
-        "Verified RISC event: iss={}, sub={:?}, aud={}, iat={}, jti={}", 
-        payload.iss, payload.sub, payload.aud, payload.iat, payload.jti
-    );
-
-    // 4. Handle specific events
-    let is_revocation = payload.events.get("https://schemas.openid.net/secevent/risc/event-type/sessions-revoked").is_some()
-        || payload.events.get("https://schemas.openid.net/secevent/oauth/event-type/tokens-revoked").is_some()
-        || payload.events.get("https://schemas.openid.net/secevent/risc/event-type/account-disabled").is_some()
-        || payload.events.get("http://schemas.openid.net/event/risc/v1/token-revoked").is_some()
-        || payload.events.get("http://schemas.openid.net/event/risc/v1/account-disabled").is_some();
-
-    if is_revocation {
-        if let Some(sub) = payload.sub {
-            tracing::info!("RISC: Revoking all sessions for user sub: {}", sub);
-            // Implement your global user revocation logic here
-        } else {
-            tracing::warn!("RISC: Revocation event received, but 'sub' field is missing");
+    let lock_accounts = risc_lock_accounts();
+    for (event_type, body) in &event.events {
+        if let Err(e) = apply_security_event(&state.db, &event, event_type, body, lock_accounts).await {
+            tracing::error!("RISC: failed to apply {} from event {}: {}", event_type, event.jti, e);
+            // Let the sender's retry run the event again; every action is idempotent.
+            if let Err(e) = crate::db::account_security::forget_security_event(&state.db, &event.jti).await {
+                tracing::error!("RISC: failed to release event {}: {}", event.jti, e);
+            }
+            return StatusCode::INTERNAL_SERVER_ERROR.into_response();
         }
-    } else {
-        tracing::debug!("RISC: Received event, but no action taken for payload: {:?}", payload.events);
     }
 
     StatusCode::ACCEPTED.into_response()
@@ -0,0 +1,260 @@
+// Every sign-in is recorded as a session so users can see where they are
+// signed in and end a session remotely. A session lives as long as its refresh
+// tokens keep being used. Revocation itself does not depend on these rows;
+// they are the user-facing view of it.
+
+const MAX_USER_AGENT_LEN: usize = 512;
+
+/// Who is signing in: the browser's User-Agent and the client address. The peer
+/// address is used unless `TRUST_PROXY_HEADERS=true`, in which case the first
+/// `X-Forwarded-For` hop (or `X-Real-IP`) set by the reverse proxy wins.
+#[derive(Debug, Clone, Default)]
+pub struct SessionClient {
+    pub user_agent: Option<String>,
+    pub ip_address: Option<String>,
+}
+
+impl<S> FromRequestParts<S> for SessionClient
+where
+    S: Send + Sync,
+{
+    type Rejection = std::convert::Infallible;
+
+    async fn from_request_parts(parts: &mut Parts, _state: &S) -> Result<Self, Self::Rejection> {
+        let peer = parts
+            .extensions
+            .get::<axum::extract::ConnectInfo<std::net::SocketAddr>>()
+            .map(|info| info.0.ip());
+        Ok(session_client_from(&parts.headers, peer, trust_proxy_headers()))
+    }
+}
+
+fn trust_proxy_headers() -> bool {
+    env::var("TRUST_PROXY_HEADERS")
+        .map(|v| matches!(v.trim().to_ascii_lowercase().as_str(), "1" | "true" | "yes"))
+        .unwrap_or(false)
+}
+
+fn session_client_from(headers: &HeaderMap, peer: Option<std::net::IpAddr>, trust_proxy: bool) -> SessionClient {
+    let user_agent = headers
+        .get(header::USER_AGENT)
+        .and_then(|v| v.to_str().ok())
+        .map(str::trim)
+        .filter(|ua| !ua.is_empty())
+        .map(|ua| ua.chars().take(MAX_USER_AGENT_LEN).collect());
+
+    let forwarded = || {
+        headers
+            .get("x-forwarded-for")
+            .and_then(|v| v.to_str().ok())
+            .and_then(|v| v.split(',').next())
+            .or_else(|| headers.get("x-real-ip").and_then(|v| v.to_str().ok()))
+            .and_then(|ip| ip.trim().parse::<std::net::IpAddr>().ok())
+    };
+    let ip = if trust_proxy { forwarded().or(peer) } else { peer };
+
+    SessionClient {
+        user_agent,
+        ip_address: ip.map(|ip| ip.to_canonical().to_string()),
+    }
+}
+
+/// Short "Browser on OS" label for a User-Agent, for the sessions list.
+fn describe_device(user_agent: Option<&str>) -> String {
+    let Some(ua) = user_agent else {
+        return "Unknown device".to_string();
+    };
+    // Order matters: Edge and Opera also claim Chrome, and Chrome claims Safari.
+    let browser = if ua.contains("Edg/") {
+        Some("Edge")
+    } else if ua.contains("OPR/") {
+        Some("Opera")
+    } else if ua.contains("Firefox/") || ua.contains("FxiOS/") {
+        Some("Firefox")
+    } else if ua.contains("Chrome/") || ua.contains("CriOS/") {
+        Some("Chrome")
+    } else if ua.contains("Safari/") {
+        Some("Safari")
+    } else {
+        None
+    };
+    let os = if ua.contains("iPhone") || ua.contains("iPad") {
+        Some("iOS")
+    } else if ua.contains("Android") {
+        Some("Android")
+    } else if ua.contains("Windows") {
+        Some("Windows")
+    } else if ua.contains("Mac OS X") || ua.contains("Macintosh") {
+        Some("macOS")
+    } else if ua.contains("CrOS") {
+        Some("ChromeOS")
+    } else if ua.contains("Linux") {
+        Some("Linux")
+    } else {
+        None
+    };
+    match (browser, os) {
+        (Some(browser), Some(os)) => format!("{browser} on {os}"),
+        (Some(name), None) | (None, Some(name)) => name.to_string(),
+        (None, None) => ua.split_whitespace().next().unwrap_or("Unknown device").to_string(),
+    }
+}
+
+/// Begins a sign-in: an access JWT, the session it opens and the first refresh
+/// token of the session's family. A failed session record is logged rather
+/// than failing the sign-in: the session still works and can still be ended
+/// with "log out everywhere". A refresh token that was not stored would strand
+/// the client after one access token, so that does fail it.
+async fn start_session(
+    db: &crate::db::DbPool,
+    user: &UserProfile,
+    client: &SessionClient,
+) -> anyhow::Result<SessionTokens> {
+    let (access_token, claims) = create_jwt(user, None)?;
+    let session_id = claims.session_id().to_string();
+    let expires_at = Utc::now().timestamp() + refresh_token_ttl_secs();
+
+    let (refresh_token, token_hash) = new_refresh_token();
+    let refresh = crate::db::models::NewRefreshToken {
+        token_hash,
+        family_id: session_id.clone(),
+        user_id: claims.sub.clone(),
+        expires_at,
+    };
+    crate::db::refresh_tokens::create_token(db, &refresh).await?;
+
+    let session = crate::db::models::NewUserSession {
+        jti: session_id,
+        user_id: claims.sub,
+        user_agent: client.user_agent.clone(),
+        ip_address: client.ip_address.clone(),
+        issued_at: claims.iat as i64,
+        expires_at,
+    };
+    if let Err(e) = crate::db::sessions::create_session(db, &session).await {
+        tracing::warn!("Failed to record session for user {}: {}", session.user_id, e);
+    }
+    Ok(SessionTokens::new(access_token, refresh_token))
+}
+
+#[derive(Serialize, utoipa::ToSchema)]
+pub struct SessionResponse {
+    #[serde(flatten)]
+    pub session: crate::db::models::UserSession,
+    /// e.g. "Firefox on macOS", derived from the user agent.
+    pub device: String,
+    /// This is the session making the request.
+    pub current: bool,
+}
+
+#[utoipa::path(
+    get,
+    path = "/api/me/sessions",
+    tag = "profile",
+    responses((status = 200, description = "`{sessions: [SessionResponse]}`, active sessions, most recently seen first", body = serde_json::Value))
+)]
+pub async fn list_sessions(
+    State(state): State<AppState>,
+    user: AuthenticatedUser,
+) -> impl IntoResponse {
+    match crate::db::sessions::list_sessions(&state.db, &user.id).await {
+        Ok(sessions) => {
+            let sessions: Vec<SessionResponse> = sessions
+                .into_iter()
+                .map(|session| SessionResponse {
+                    device: describe_device(session.user_agent.as_deref()),
+                    current: user.session_id.as_deref() == Some(session.id.as_str()),
+                    session,
+                })
+                .collect();
+            Json(serde_json::json!({ "sessions": sessions })).into_response()
+        }
+        Err(e) => {
+            tracing::error!("Failed to list sessions: {}", e);
+            (StatusCode::INTERNAL_SERVER_ERROR, "Database Error").into_response()
+        }
+    }
+}
+
+#[utoipa::path(
+    delete,
+    path = "/api/me/sessions/{id}",
+    tag = "profile",
+    params(("id" = String, Path, description = "Session id")),
+    responses(
+        (status = 200, description = "Signed out; when it is the current session the auth cookie is cleared too", body = String),
+        (status = 404, description = "Not found or already ended", body = String),
+    )
+)]
+pub async fn revoke_session(
+    Path(session_id): Path<String>,
+    State(state): State<AppState>,
+    user: AuthenticatedUser,
+    headers: HeaderMap,
+) -> axum::response::Response {
+    let expires_at = match crate::db::sessions::active_session_expiry(&state.db, &user.id, &session_id).await {
+        Ok(Some(expires_at)) => expires_at,
+        Ok(None) => return (StatusCode::NOT_FOUND, "Not found").into_response(),
+        Err(e) => {
+            tracing::error!("Failed to load session: {}", e);
+            return (StatusCode::INTERNAL_SERVER_ERROR, "Database Error").into_response();
+        }
+    };
+    if let Err(e) = revoke_jti(&state.db, &session_id, &user.id, expires_at as usize).await {
+        tracing::error!("Failed to revoke session: {}", e);
+        return (StatusCode::INTERNAL_SERVER_ERROR, "Database Error").into_response();
+    }
+    if user.session_id.as_deref() == Some(session_id.as_str()) {
+        return logout(State(state), headers).await.into_response();
+    }
+    (StatusCode::OK, "Signed out").into_response()
+}
+
+#[utoipa::path(
+    delete,
+    path = "/api/me/sessions",
+    tag = "profile",
+    responses((status = 200, description = "Every session, including this one, is signed out", body = String))
+)]
+pub async fn revoke_all_sessions(
+    State(state): State<AppState>,
+    user: AuthenticatedUser,
+    headers: HeaderMap,
+) -> axum::response::Response {
+    logout_everywhere(State(state), user, headers).await
+}
+
+#[cfg(test)]
+mod session_tests {
+    use super::*;
+
+    #[test]
+    fn test_describe_device_names_browser_and_os() {
+        let firefox_mac = "Mozilla/5.0 (Macintosh; Intel Mac OS X 14.5; rv:128.0) Gecko/20100101 Firefox/128.0";
+        assert_eq!(describe_device(Some(firefox_mac)), "Firefox on macOS");
+        let edge_windows = "Mozilla/5.0 (Windows NT 10.0; Win64; x64) AppleWebKit/537.36 (KHTML, like Gecko) Chrome/126.0.0.0 Safari/537.36 Edg/126.0.0.0";
+        assert_eq!(describe_device(Some(edge_windows)), "Edge on Windows");
+        let safari_iphone = "Mozilla/5.0 (iPhone; CPU iPhone OS 17_5 like Mac OS X) AppleWebKit/605.1.15 (KHTML, like Gecko) Version/17.5 Mobile/15E148 Safari/604.1";
+        assert_eq!(describe_device(Some(safari_iphone)), "Safari on iOS");
+        assert_eq!(describe_device(Some("curl/8.5.0")), "curl/8.5.0");
+        assert_eq!(describe_device(None), "Unknown device");
+    }
+
+    #[test]
+    fn test_session_client_only_trusts_proxy_headers_when_configured() {
+        let mut headers = HeaderMap::new();
+        headers.insert(header::USER_AGENT, HeaderValue::from_static("curl/8.5.0"));
+        headers.insert("x-forwarded-for", HeaderValue::from_static("203.0.113.7, 10.0.0.2"));
+        let peer = Some("::ffff:10.0.0.2".parse().unwrap());
+
+        let direct = session_client_from(&headers, peer, false);
+        assert_eq!(direct.user_agent.as_deref(), Some("curl/8.5.0"));
+        assert_eq!(direct.ip_address.as_deref(), Some("10.0.0.2"));
+
+        let proxied = session_client_from(&headers, peer, true);
+        assert_eq!(proxied.ip_address.as_deref(), Some("203.0.113.7"));
+
+        let empty = session_client_from(&HeaderMap::new(), None, true);
+        assert!(empty.user_agent.is_none() && empty.ip_address.is_none());
+    }
+}
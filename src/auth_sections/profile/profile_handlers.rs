@@ -9,6 +9,12 @@ struct BackupData {
     receipts: Vec<crate::db::models::Receipt>,
 }
 
+#[utoipa::path(
+    delete,
+    path = "/api/me",
+    tag = "profile",
+    responses((status = 200, description = "Account data and receipts deleted; session cleared", body = String))
+)]
 pub async fn delete_me(
     State(state): State<AppState>,
     user: AuthenticatedUser,
@@ -25,17 +31,23 @@ pub async fn delete_me(
         }
     };
 
-    // 2. Delete files from storage (direct HTTP DELETE)
-    let client = reqwest::Client::new();
+    // 2. Delete files from storage
     for key in keys {
-        match crate::storage::presign_url(&state, "DELETE", &key, 300) {
-            Ok(url) => {
-                if let Err(e) = client.delete(&url).send().await {
-                    tracing::error!("Failed to delete file {} from storage: {}", key, e);
+        if let Err(e) = state.storage.delete(&key).await {
+            tracing::error!("Failed to delete file {} from storage: {}", key, e);
+        }
+    }
+
+    // Unfinished multipart uploads hold parts only their session can find.
+    match crate::db::receipt_uploads::list_user_uploads(&state.db, &user_id).await {
+        Ok(uploads) => {
+            for upload in uploads {
+                if let Err(e) = crate::routes::receipt_uploads::discard_upload(&state, &upload).await {
+                    tracing::error!("Failed to abort upload {} for deletion: {}", upload.id, e);
                 }
             }
-            Err(e) => tracing::error!("Failed to presign delete for {}: {}", key, e),
         }
+        Err(e) => tracing::error!("Failed to list uploads for deletion: {}", e),
     }
 
     // 3. Delete data from DB
@@ -45,11 +57,13 @@ pub async fn delete_me(
     }
 
     // 4. Logout
-    logout(headers).await.into_response()
+    logout(State(state), headers).await.into_response()
 }
 
 pub async fn dev_login(
     State(_state): State<AppState>,
+    client: SessionClient,
+    delivery: TokenDelivery,
     Json(payload): Json<DevLoginRequest>,
 ) -> impl IntoResponse {
     let env_mode = env::var("RUST_ENV").unwrap_or_else(|_| "development".to_string());
@@ -116,22 +130,18 @@ pub async fn dev_login(
             encrypted_payload: user.encrypted_payload.clone(),
             vault_credential_id: user.vault_credential_id.clone(),
         }).await;
-        match create_jwt(&user) {
-            Ok(token) => {
-                let cookie = build_auth_cookie(&token);
-                let mut response = Json(AuthResponse { user }).into_response();
-                if let Ok(header_value) = HeaderValue::from_str(&cookie) {
-                    response.headers_mut().insert(header::SET_COOKIE, header_value);
-                }
-                        // Also emit a readable CSRF cookie for client-side X-CSRF-Token usage
-                        let csrf_cookie = build_csrf_cookie(&token);
-                        if let Ok(header_value) = HeaderValue::from_str(&csrf_cookie) {
-                            response.headers_mut().append(header::SET_COOKIE, header_value);
-                        }
+        match start_session(&_state.db, &user, &client).await {
+            Ok(tokens) if delivery == TokenDelivery::Bearer => {
+                Json(AuthResponse { user, tokens: Some(tokens) }).into_response()
+            }
+            Ok(tokens) => {
+                let mut response = Json(AuthResponse { user, tokens: None }).into_response();
+                // Also emits a readable CSRF cookie for client-side X-CSRF-Token usage
+                set_session_cookies(&mut response, &tokens);
                 response
             },
             Err(e) => {
-                tracing::error!("JWT creation failed: {}", e);
+                tracing::error!("Session creation failed: {}", e);
                 (StatusCode::INTERNAL_SERVER_ERROR, "failed to create token").into_response()
             }
         }
@@ -140,7 +150,7 @@ pub async fn dev_login(
     }
 }
 
-pub async fn logout(headers: HeaderMap) -> impl IntoResponse {
+pub async fn logout(State(state): State<AppState>, headers: HeaderMap) -> impl IntoResponse {
     // Emit Set-Cookie headers that clear the auth cookie. Some browsers are picky
     // about SameSite/Secure attributes when clearing cookies, so send variants
     // that cover common cases.
@@ -158,17 +168,26 @@ pub async fn logout(headers: HeaderMap) -> impl IntoResponse {
     let mut response = (StatusCode::OK, "OK").into_response();
 
     if let Some(token) = extract_token_from_headers(&headers) {
-        let _ = revoke_token_str(&token);
+        if let Err(e) = revoke_token_str(&state.db, &token).await {
+            tracing::warn!("Failed to revoke token on logout: {}", e);
+        }
+    }
+    // The access cookie may already have expired; the refresh cookie still names the session.
+    if let Some(refresh_token) = extract_cookie_by_name(&headers, REFRESH_COOKIE_NAME) {
+        if let Err(e) = revoke_refresh_token_session(&state.db, &refresh_token).await {
+            tracing::warn!("Failed to revoke refresh token on logout: {}", e);
+        }
     }
 
     if let Ok(header_value) = HeaderValue::from_str(&cookie_strict) {
         response.headers_mut().append(header::SET_COOKIE, header_value);
     }
 
-    // Clear the readable CSRF cookie as well
-    let csrf_clear = clear_csrf_cookie();
-    if let Ok(header_value) = HeaderValue::from_str(&csrf_clear) {
-        response.headers_mut().append(header::SET_COOKIE, header_value);
+    // Clear the readable CSRF cookie and the refresh cookie as well
+    for cookie in [clear_csrf_cookie(), clear_refresh_cookie()] {
+        if let Ok(header_value) = HeaderValue::from_str(&cookie) {
+            response.headers_mut().append(header::SET_COOKIE, header_value);
+        }
     }
 
     // If running in a secure context, also emit a None+Secure variant which
@@ -186,24 +205,34 @@ pub async fn logout(headers: HeaderMap) -> impl IntoResponse {
     response
 }
 
+#[utoipa::path(
+    get,
+    path = "/api/me",
+    tag = "profile",
+    responses((status = 200, description = "The signed-in user's profile", body = UserProfile))
+)]
 pub async fn me(
     State(state): State<AppState>,
     user: AuthenticatedUser,
 ) -> impl IntoResponse {
     match crate::db::users::get_user_profile(&state.db, &user.id).await {
-        Ok(Some((email, name, provider, filing_status, agi, marginal_tax_rate, itemize_deductions, is_encrypted, encrypted_payload, vault_credential_id))) => Json(UserProfile {
-            id: user.id,
-            email,
-            name,
-            filing_status,
-            agi,
-            marginal_tax_rate,
-            itemize_deductions,
-            is_encrypted,
-            encrypted_payload,
-            vault_credential_id,
-            provider,
-        }).into_response(),
+        Ok(Some((email, name, provider, filing_status, agi, marginal_tax_rate, itemize_deductions, is_encrypted, encrypted_payload, vault_credential_id))) => {
+            let mut profile = UserProfile {
+                id: user.id,
+                email,
+                name,
+                filing_status,
+                agi,
+                marginal_tax_rate,
+                itemize_deductions,
+                is_encrypted,
+                encrypted_payload,
+                vault_credential_id,
+                provider,
+            };
+            redact_gated_vault_credential(&state.db, &mut profile).await;
+            Json(profile).into_response()
+        }
         Ok(None) => {
             let filing_status = None;
             let agi = None;
@@ -258,6 +287,13 @@ pub async fn me(
     }
 }
 
+#[utoipa::path(
+    get,
+    path = "/api/config",
+    tag = "profile",
+    security(()),
+    responses((status = 200, description = "Login options: `{allow_dev_login, google_enabled, google_client_id, oidc_providers, passkeys_enabled, oauth_state}`", body = serde_json::Value))
+)]
 pub async fn get_config(headers: HeaderMap) -> impl IntoResponse {
     let allow_dev_login = std::env::var("ALLOW_DEV_LOGIN")
         .map(|v| v.to_lowercase() == "true")
@@ -272,6 +308,11 @@ pub async fn get_config(headers: HeaderMap) -> impl IntoResponse {
         .collect();
     let google_enabled = providers.contains(&"google".to_string()) && std::env::var("GOOGLE_CLIENT_ID").is_ok();
     let google_client_id = std::env::var("GOOGLE_CLIENT_ID").ok();
+    let oidc_providers: Vec<Value> = oidc_login_providers()
+        .into_iter()
+        .map(|(id, name)| serde_json::json!({ "id": id, "name": name }))
+        .collect();
+    let passkeys_enabled = crate::webauthn::RelyingParty::from_env().is_ok();
 
     // Generate or retrieve OAuth state for CSRF protection
     let existing_state = extract_cookie_by_name(&headers, "oauth_state");
@@ -287,6 +328,8 @@ pub async fn get_config(headers: HeaderMap) -> impl IntoResponse {
         "allow_dev_login": allow_dev_login,
         "google_enabled": google_enabled,
         "google_client_id": google_client_id,
+        "oidc_providers": oidc_providers,
+        "passkeys_enabled": passkeys_enabled,
         "oauth_state": state_token
     })).into_response();
 
@@ -299,6 +342,26 @@ pub async fn get_config(headers: HeaderMap) -> impl IntoResponse {
     response
 }
 
+/// The account's email is the one its sign-in provider vouched for, and accounts and
+/// accountant invitations are matched on it, so it cannot be changed here. Resending
+/// the current address is accepted.
+fn resolve_profile_email(requested: Option<&str>, current: &str) -> Result<String, &'static str> {
+    match requested.map(str::trim).filter(|email| !email.is_empty()) {
+        Some(email) if !email.eq_ignore_ascii_case(current) => Err("Email is managed by your sign-in provider"),
+        _ => Ok(current.to_string()),
+    }
+}
+
+#[utoipa::path(
+    put,
+    path = "/api/me",
+    tag = "profile",
+    request_body = UpdateMeRequest,
+    responses(
+        (status = 200, description = "The updated profile", body = UserProfile),
+        (status = 400, description = "Name is required, or the email differs from the current one", body = String),
+    )
+)]
 pub async fn update_me(
     State(state): State<AppState>,
     user: AuthenticatedUser,
@@ -310,9 +373,11 @@ pub async fn update_me(
         _ => None,
     };
 
-    let email = req.email.map(|s| s.trim().to_string())
-        .or_else(|| existing.as_ref().map(|r| r.0.clone()))
-        .unwrap_or_else(|| user.email.clone());
+    let current_email = existing.as_ref().map(|r| r.0.clone()).unwrap_or_else(|| user.email.clone());
+    let email = match resolve_profile_email(req.email.as_deref(), &current_email) {
+        Ok(email) => email,
+        Err(msg) => return (StatusCode::BAD_REQUEST, msg).into_response(),
+    };
 
     let name = req.name.map(|s| s.trim().to_string())
         .or_else(|| existing.as_ref().map(|r| r.1.clone()))
@@ -357,19 +422,23 @@ pub async fn update_me(
         encrypted_payload: encrypted_payload.clone(),
         vault_credential_id: vault_credential_id.clone(),
     }).await {
-        Ok(_) => Json(UserProfile {
-            id: user.id,
-            email,
-            name,
-            filing_status,
-            agi,
-            marginal_tax_rate,
-            itemize_deductions,
-            is_encrypted,
-            encrypted_payload,
-            vault_credential_id,
-            provider: user.provider,
-        }).into_response(),
+        Ok(_) => {
+            let mut profile = UserProfile {
+                id: user.id,
+                email,
+                name,
+                filing_status,
+                agi,
+                marginal_tax_rate,
+                itemize_deductions,
+                is_encrypted,
+                encrypted_payload,
+                vault_credential_id,
+                provider: user.provider,
+            };
+            redact_gated_vault_credential(&state.db, &mut profile).await;
+            Json(profile).into_response()
+        }
         Err(e) => {
             tracing::error!("Failed saving profile: {}", e);
             (StatusCode::INTERNAL_SERVER_ERROR, "Database Error").into_response()
@@ -377,6 +446,12 @@ pub async fn update_me(
     }
 }
 
+#[utoipa::path(
+    get,
+    path = "/api/me/export",
+    tag = "profile",
+    responses((status = 200, description = "Zip with data.json, receipt files and manifest.json listing each receipt's SHA-256 and whether it matched the hash recorded at upload", content_type = "application/zip", body = String))
+)]
 pub async fn export_me(
     State(state): State<AppState>,
     user: AuthenticatedUser,
@@ -385,12 +460,13 @@ pub async fn export_me(
 
     // 1. Fetch metadata
     let profile_res = crate::db::users::get_user_profile(&state.db, &user_id).await;
-    let profile = match profile_res {
+    let mut profile = match profile_res {
         Ok(Some((email, name, provider, filing_status, agi, marginal_tax_rate, itemize_deductions, is_encrypted, encrypted_payload, vault_credential_id))) => {
             UserProfile { id: user_id.clone(), email, name, provider, filing_status, agi, marginal_tax_rate, itemize_deductions, is_encrypted, encrypted_payload, vault_credential_id }
         }
         _ => return (StatusCode::INTERNAL_SERVER_ERROR, "Failed to load profile").into_response(),
     };
+    redact_gated_vault_credential(&state.db, &mut profile).await;
 
     let charities = crate::db::charities::list_charities(&state.db, &user_id).await.unwrap_or_default();
     let donations = crate::db::donations::list_donations(&state.db, &user_id, None).await.unwrap_or_default();
@@ -423,35 +499,61 @@ pub async fn export_me(
             let _ = zip.start_file("data.json", options);
             let _ = zip.write_all(&json_data);
 
-            let client = reqwest::Client::new();
+            // Each receipt is hashed as it is copied and checked against the hash
+            // recorded at confirm time; manifest.json lists the outcome per receipt.
+            let mut manifest = Vec::with_capacity(backup_data.receipts.len());
             for receipt in &backup_data.receipts {
-                let url = match crate::storage::presign_url(&state, "GET", &receipt.key, 300) {
-                    Ok(url) => url,
-                    Err(e) => {
-                        tracing::error!("Backup: Failed to presign {}: {}", receipt.key, e);
-                        continue;
-                    }
-                };
-
-                if let Ok(resp) = client.get(&url).send().await {
-                    let status = resp.status();
-                    if status.is_success() {
-                        let mut stream = resp.bytes_stream();
-                        let file_name = format!("receipts/{}", receipt.file_name.as_deref().unwrap_or(&receipt.id));
-                        if zip.start_file(&file_name, options).is_ok() {
-                            while let Some(chunk_res) = futures::StreamExt::next(&mut stream).await {
-                                if let Ok(chunk) = chunk_res {
+                let file_name = format!("receipts/{}", receipt.file_name.as_deref().unwrap_or(&receipt.id));
+                let (sha256, status) = match state.storage.get(&receipt.key).await {
+                    Ok(Some(mut stream)) => {
+                        let mut hasher = crate::storage::ContentHasher::default();
+                        let mut complete = zip.start_file(&file_name, options).is_ok();
+                        while let Some(chunk_res) = futures::StreamExt::next(&mut stream).await {
+                            match chunk_res {
+                                Ok(chunk) => {
+                                    hasher.update(chunk.as_ref());
                                     let _ = zip.write_all(chunk.as_ref());
                                 }
+                                Err(e) => {
+                                    tracing::error!("Backup: Failed reading receipt {}: {}", receipt.key, e);
+                                    complete = false;
+                                    break;
+                                }
                             }
-                            tracing::info!("Backup: Added {} to zip", file_name);
                         }
-                    } else {
-                        tracing::error!("Backup: Failed to fetch receipt from storage: {} (Status: {})", url, status);
+                        let sha256 = hasher.finish();
+                        let status = match receipt.content_hash.as_deref() {
+                            _ if !complete => "unreadable",
+                            None => "unrecorded",
+                            Some(recorded) if recorded == sha256 => "verified",
+                            Some(_) => {
+                                tracing::error!("Backup: Receipt {} does not match its recorded hash", receipt.key);
+                                "mismatch"
+                            }
+                        };
+                        tracing::info!("Backup: Added {} to zip ({})", file_name, status);
+                        (complete.then_some(sha256), status)
                     }
-                } else {
-                    tracing::error!("Backup: Internal error fetching receipt: {}", url);
-                }
+                    Ok(None) => {
+                        tracing::error!("Backup: Receipt {} is missing from storage", receipt.key);
+                        (None, "missing")
+                    }
+                    Err(e) => {
+                        tracing::error!("Backup: Failed to fetch receipt {} from storage: {}", receipt.key, e);
+                        (None, "unreadable")
+                    }
+                };
+                manifest.push(serde_json::json!({
+                    "receipt_id": receipt.id,
+                    "file": file_name,
+                    "sha256": sha256,
+                    "recorded_sha256": receipt.content_hash,
+                    "status": status,
+                }));
+            }
+            let manifest = serde_json::json!({ "algorithm": "sha256", "receipts": manifest });
+            if zip.start_file("manifest.json", options).is_ok() {
+                let _ = zip.write_all(manifest.to_string().as_bytes());
             }
             if let Err(e) = zip.finish() {
                 tracing::error!("Backup: Failed to finish zip: {}", e);
@@ -477,6 +579,16 @@ pub async fn export_me(
     (headers, axum::body::Body::from_stream(stream)).into_response()
 }
 
+#[utoipa::path(
+    post,
+    path = "/api/me/import",
+    tag = "profile",
+    request_body(content = String, content_type = "multipart/form-data", description = "Backup zip from `/api/me/export` in the `file` field"),
+    responses(
+        (status = 200, description = "Restore completed", body = String),
+        (status = 400, description = "Missing or invalid backup file", body = String),
+    )
+)]
 pub async fn import_me(
     State(state): State<AppState>,
     user: AuthenticatedUser,
@@ -531,7 +643,16 @@ pub async fn import_me(
         }
     };
 
-    // 2. Import metadata into DB
+    // 2. Import metadata into DB. Exports made while the vault passkey gates the
+    // credential id omit it, so keep the current one rather than clearing it.
+    let vault_credential_id = match backup.profile.vault_credential_id {
+        Some(id) => Some(id),
+        None => crate::db::users::get_user_profile(&state.db, &user.id)
+            .await
+            .ok()
+            .flatten()
+            .and_then(|existing| existing.9),
+    };
     let profile_upsert = UserProfileUpsert {
         user_id: user.id.clone(),
         email: user.email.clone(),
@@ -543,7 +664,7 @@ pub async fn import_me(
         itemize_deductions: backup.profile.itemize_deductions,
         is_encrypted: backup.profile.is_encrypted,
         encrypted_payload: backup.profile.encrypted_payload,
-        vault_credential_id: backup.profile.vault_credential_id,
+        vault_credential_id,
     };
 
     if let Err(e) = crate::db::users::import_data(
@@ -559,7 +680,6 @@ pub async fn import_me(
     }
 
     // 3. Restore files to storage by streaming them one-by-one
-    let client = reqwest::Client::new();
     for receipt in &backup.receipts {
         let file_name = format!("receipts/{}", receipt.key.split('/').next_back().unwrap_or(&receipt.id));
         if let Ok(mut zip_file) = archive.by_name(&file_name) {
@@ -570,13 +690,8 @@ pub async fn import_me(
             }
             let mut file_data = Vec::new();
             if zip_file.read_to_end(&mut file_data).is_ok() {
-                match crate::storage::presign_url(&state, "PUT", &receipt.key, 300) {
-                    Ok(url) => {
-                        if let Err(e) = client.put(url).body(file_data).send().await {
-                            tracing::error!("Failed to restore file {} to storage: {}", receipt.key, e);
-                        }
-                    }
-                    Err(e) => tracing::error!("Failed to presign restore for {}: {}", receipt.key, e),
+                if let Err(e) = state.storage.put(&receipt.key, file_data.into(), None).await {
+                    tracing::error!("Failed to restore file {} to storage: {}", receipt.key, e);
                 }
             }
         }
@@ -585,22 +700,27 @@ pub async fn import_me(
     (StatusCode::OK, "Restore completed").into_response()
 }
 
-fn create_jwt(user: &UserProfile) -> anyhow::Result<String> {
+/// Mints a short-lived access JWT. `session_id` is None for a new sign-in, whose
+/// session then takes the token's own jti.
+fn create_jwt(user: &UserProfile, session_id: Option<&str>) -> anyhow::Result<(String, Claims)> {
     let expiration = Utc::now()
-        .checked_add_signed(Duration::days(1))
+        .checked_add_signed(Duration::seconds(access_token_ttl_secs()))
         .ok_or_else(|| anyhow::anyhow!("failed to compute expiration timestamp"))?
         .timestamp();
 
     let issuer = jwt_issuer();
     let audience = jwt_audience();
 
+    let jti = uuid::Uuid::new_v4().to_string();
     let claims = Claims {
         sub: user.id.clone(),
         email: user.email.clone(),
         provider: user.provider.clone(),
         name: user.name.clone(),
         exp: expiration as usize,
-        jti: uuid::Uuid::new_v4().to_string(),
+        iat: Utc::now().timestamp() as usize,
+        sid: Some(session_id.unwrap_or(&jti).to_string()),
+        jti,
         iss: issuer,
         aud: audience,
     };
@@ -608,7 +728,7 @@ fn create_jwt(user: &UserProfile) -> anyhow::Result<String> {
     let secret = jwt_secret()?;
     let token = encode(&Header::default(), &claims, &EncodingKey::from_secret(secret.as_bytes()))?;
 
-    Ok(token)
+    Ok((token, claims))
 }
 
 fn extract_token(parts: &Parts) -> Option<String> {
@@ -638,3 +758,18 @@ fn extract_token(parts: &Parts) -> Option<String> {
     }
     None
 }
+
+#[cfg(test)]
+mod profile_tests {
+    use super::*;
+
+    #[test]
+    fn test_profile_email_cannot_be_changed_to_another_address() {
+        let current = "owner@example.test";
+        assert_eq!(resolve_profile_email(None, current), Ok(current.to_string()));
+        assert_eq!(resolve_profile_email(Some("  "), current), Ok(current.to_string()));
+        assert_eq!(resolve_profile_email(Some(" Owner@Example.test "), current), Ok(current.to_string()));
+        assert!(resolve_profile_email(Some("cpa@firm.com"), current).is_err());
+        assert!(resolve_profile_email(Some("CPA@Firm.com"), current).is_err());
+    }
+}
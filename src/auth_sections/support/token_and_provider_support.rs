@@ -1,9 +1,10 @@
 fn build_auth_cookie(token: &str) -> String {
     let secure = env::var("RUST_ENV").unwrap_or_else(|_| "development".to_string()) == "production";
     let mut cookie = format!(
-        "{}={}; HttpOnly; SameSite=Strict; Path=/; Max-Age=14400",
+        "{}={}; HttpOnly; SameSite=Strict; Path=/; Max-Age={}",
         AUTH_COOKIE_NAME,
-        token
+        token,
+        access_token_ttl_secs()
     );
     if secure {
         cookie.push_str("; Secure");
@@ -11,11 +12,14 @@ fn build_auth_cookie(token: &str) -> String {
     cookie
 }
 
+// Outlives the access cookie so the page still has a CSRF value while the
+// session is being refreshed; each refresh replaces it.
 fn build_csrf_cookie(token: &str) -> String {
     let secure = env::var("RUST_ENV").unwrap_or_else(|_| "development".to_string()) == "production";
     let mut cookie = format!(
-        "csrf_token={}; SameSite=Strict; Path=/; Max-Age=14400",
-        token
+        "csrf_token={}; SameSite=Strict; Path=/; Max-Age={}",
+        token,
+        refresh_token_ttl_secs()
     );
     if secure {
         cookie.push_str("; Secure");
@@ -84,6 +88,8 @@ struct ProviderConfig {
     token_url: String,
     userinfo_url: String,
     redirect_url: String,
+    /// Set for OpenID Connect providers; endpoints then come from discovery.
+    issuer: Option<String>,
 }
 
 fn load_provider_config(provider: &str) -> Result<ProviderConfig, String> {
@@ -96,11 +102,28 @@ fn load_provider_config(provider: &str) -> Result<ProviderConfig, String> {
     let prefix = provider.to_uppercase();
     let client_id = env::var(format!("{}_CLIENT_ID", prefix)).map_err(|_| "Missing client id".to_string())?;
     let client_secret = env::var(format!("{}_CLIENT_SECRET", prefix)).map_err(|_| "Missing client secret".to_string())?;
+    let redirect_url = env::var(format!("{}_REDIRECT_URL", prefix))
+        .unwrap_or_else(|_| format!("http://localhost:8080/auth/callback/{}", provider));
+    let issuer = env::var(format!("{}_ISSUER", prefix))
+        .ok()
+        .map(|v| v.trim().to_string())
+        .filter(|v| !v.is_empty());
+
+    if issuer.is_some() {
+        return Ok(ProviderConfig {
+            client_id,
+            client_secret,
+            auth_url: String::new(),
+            token_url: String::new(),
+            userinfo_url: String::new(),
+            redirect_url,
+            issuer,
+        });
+    }
+
     let auth_url = env::var(format!("{}_AUTH_URL", prefix)).map_err(|_| "Missing auth url".to_string())?;
     let token_url = env::var(format!("{}_TOKEN_URL", prefix)).map_err(|_| "Missing token url".to_string())?;
     let userinfo_url = env::var(format!("{}_USERINFO_URL", prefix)).map_err(|_| "Missing userinfo url".to_string())?;
-    let redirect_url = env::var(format!("{}_REDIRECT_URL", prefix))
-        .unwrap_or_else(|_| format!("http://localhost:8080/auth/callback/{}", provider));
 
     Ok(ProviderConfig {
         client_id,
@@ -109,26 +132,70 @@ fn load_provider_config(provider: &str) -> Result<ProviderConfig, String> {
         token_url,
         userinfo_url,
         redirect_url,
+        issuer: None,
     })
 }
 
+/// For OIDC providers, fill in the endpoints from the (cached) discovery
+/// document and return it; plain OAuth providers resolve to `None`.
+async fn resolve_oidc_provider(cfg: &mut ProviderConfig) -> anyhow::Result<Option<crate::oidc::OidcMetadata>> {
+    let Some(issuer) = cfg.issuer.as_deref() else {
+        return Ok(None);
+    };
+    let metadata = crate::oidc::discover(oauth_http_client()?, issuer).await?;
+    cfg.auth_url = metadata.authorization_endpoint.clone();
+    cfg.token_url = metadata.token_endpoint.clone();
+    cfg.userinfo_url = metadata.userinfo_endpoint.clone().unwrap_or_default();
+    Ok(Some(metadata))
+}
+
+/// Enabled providers configured through OIDC discovery, as `(id, display name)`.
+fn oidc_login_providers() -> Vec<(String, String)> {
+    env::var("OAUTH_PROVIDERS")
+        .unwrap_or_default()
+        .split(',')
+        .map(|s| s.trim().to_lowercase())
+        .filter(|s| !s.is_empty())
+        .filter(|p| {
+            env::var(format!("{}_ISSUER", p.to_uppercase()))
+                .map(|v| !v.trim().is_empty())
+                .unwrap_or(false)
+        })
+        .map(|p| {
+            let display = env::var(format!("{}_DISPLAY_NAME", p.to_uppercase()))
+                .ok()
+                .filter(|v| !v.trim().is_empty())
+                .unwrap_or_else(|| p.clone());
+            (p, display)
+        })
+        .collect()
+}
+
 fn create_state_token(provider: &str) -> anyhow::Result<String> {
+    create_state_token_with_nonce(provider).map(|(token, _)| token)
+}
+
+/// Returns the signed state token and the nonce it carries, which OIDC
+/// logins forward to the provider so it is echoed back in the id_token.
+fn create_state_token_with_nonce(provider: &str) -> anyhow::Result<(String, String)> {
     let expiration = Utc::now()
         .checked_add_signed(Duration::minutes(10))
         .ok_or_else(|| anyhow::anyhow!("failed to compute state expiration timestamp"))?
         .timestamp();
+    let nonce = uuid::Uuid::new_v4().to_string();
     let state = StateClaims {
         exp: expiration as usize,
         provider: provider.to_string(),
-        nonce: uuid::Uuid::new_v4().to_string(),
+        nonce: nonce.clone(),
     };
     let secret = jwt_secret()?;
     let token = encode(&Header::default(), &state, &EncodingKey::from_secret(secret.as_bytes()))?;
-    Ok(token)
+    Ok((token, nonce))
 }
 
 
-fn validate_state_token(token: &str, provider: &str) -> anyhow::Result<()> {
+/// Validates the state token and returns its nonce.
+fn validate_state_token(token: &str, provider: &str) -> anyhow::Result<String> {
     let secret = jwt_secret()?;
     let mut validation = Validation::default();
     validation.validate_exp = true;
@@ -140,13 +207,34 @@ fn validate_state_token(token: &str, provider: &str) -> anyhow::Result<()> {
     if data.claims.provider.to_lowercase() != provider.to_lowercase() {
         return Err(anyhow::anyhow!("provider mismatch"));
     }
-    Ok(())
+    Ok(data.claims.nonce)
 }
 
 pub struct ProviderProfile {
     pub id: String,
     pub email: String,
     pub name: String,
+    /// The provider vouched for `email`; only then may it accept accountant invitations.
+    pub email_verified: bool,
+}
+
+/// The account a provider login signs in to: the one linked to this provider account, else,
+/// only when the provider vouches for the address, the one registered with its email.
+/// `None` means the login gets a new account.
+pub async fn resolve_login_account(
+    db: &crate::db::DbPool,
+    provider: &str,
+    profile: &ProviderProfile,
+) -> anyhow::Result<Option<(String, crate::db::UserProfileRow)>> {
+    if let Some(user_id) = crate::db::account_security::find_user_by_identity(db, provider, &profile.id).await? {
+        if let Some(row) = crate::db::users::get_user_profile(db, &user_id).await? {
+            return Ok(Some((user_id, row)));
+        }
+    }
+    if !profile.email_verified {
+        return Ok(None);
+    }
+    crate::db::users::get_user_profile_by_email(db, &profile.email).await
 }
 
 pub async fn fetch_user_profile(userinfo_url: &str, access_token: &str) -> anyhow::Result<ProviderProfile> {
@@ -176,8 +264,9 @@ pub async fn fetch_user_profile(userinfo_url: &str, access_token: &str) -> anyho
         .and_then(|v| v.as_str())
         .unwrap_or("User")
         .to_string();
+    let email_verified = json.get("email_verified").and_then(|v| v.as_bool()) == Some(true);
 
-    Ok(ProviderProfile { id, email, name })
+    Ok(ProviderProfile { id, email, name, email_verified })
 }
 
 fn jwt_secret() -> anyhow::Result<&'static str> {
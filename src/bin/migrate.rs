@@ -1,4 +1,6 @@
-use deductible_tracker::db::oracle::{connect_once, load_config};
+use deductible_tracker::db::oracle::{
+    apply_bootstrap_ddl, connect_once, encrypt_existing_columns, load_config,
+};
 use deductible_tracker::db::RuntimeMode;
 use std::env;
 use std::fs;
@@ -45,6 +47,7 @@ async fn main() -> anyhow::Result<()> {
         || env::var("RUN_SEED")
             .map(|value| value.eq_ignore_ascii_case("true"))
             .unwrap_or(false);
+    let encrypt_columns = args.contains(&"--encrypt-columns".to_string());
 
     let migration_path =
         env::var("MIGRATION_FILE").unwrap_or_else(|_| "migrations/init.sql".to_string());
@@ -98,5 +101,15 @@ async fn main() -> anyhow::Result<()> {
 
     conn.commit().await?;
     println!("Migration complete and committed (Oracle).");
+
+    if encrypt_columns {
+        println!("Encrypting existing plaintext columns with DATA_ENCRYPTION_KEY...");
+        apply_bootstrap_ddl(&conn).await?;
+        let report = encrypt_existing_columns(&conn).await?;
+        println!(
+            "Encrypted columns for {} users: {} AGI values, {} donation notes, {} charity addresses.",
+            report.users, report.agi_values, report.donation_notes, report.charity_addresses
+        );
+    }
     Ok(())
 }
@@ -0,0 +1,16 @@
+use std::env;
+
+/// Deletes receipt objects that no receipt row references and that are older
+/// than `RECEIPT_GC_GRACE_HOURS`. `--dry-run` only prints what would go.
+#[tokio::main]
+async fn main() -> anyhow::Result<()> {
+    dotenvy::dotenv().ok();
+
+    let dry_run = env::args().any(|arg| arg == "--dry-run");
+    let report = deductible_tracker::receipt_gc::run_from_env(dry_run).await?;
+    println!("{}", serde_json::to_string_pretty(&report)?);
+    if !report.failed.is_empty() {
+        anyhow::bail!("{} orphaned objects could not be deleted", report.failed.len());
+    }
+    Ok(())
+}
@@ -0,0 +1,29 @@
+use crate::db::models::{AccessToken, ActiveAccessToken, NewAccessToken};
+use crate::db::DbPool;
+
+pub async fn create_token(pool: &DbPool, input: &NewAccessToken) -> anyhow::Result<AccessToken> {
+    super::create_access_token(pool, input).await
+}
+
+pub async fn list_tokens(pool: &DbPool, user_id: &str) -> anyhow::Result<Vec<AccessToken>> {
+    super::list_access_tokens(pool, user_id).await
+}
+
+pub async fn revoke_token(pool: &DbPool, user_id: &str, token_id: &str) -> anyhow::Result<bool> {
+    super::revoke_access_token(pool, user_id, token_id).await
+}
+
+pub async fn revoke_all_tokens(pool: &DbPool, user_id: &str) -> anyhow::Result<u64> {
+    super::revoke_all_access_tokens(pool, user_id).await
+}
+
+pub async fn find_active_token(
+    pool: &DbPool,
+    token_id: &str,
+) -> anyhow::Result<Option<ActiveAccessToken>> {
+    super::find_active_access_token(pool, token_id).await
+}
+
+pub async fn touch_token(pool: &DbPool, token_id: &str) -> anyhow::Result<()> {
+    super::touch_access_token(pool, token_id).await
+}
@@ -0,0 +1,39 @@
+use crate::db::DbPool;
+
+pub async fn link_identity(pool: &DbPool, provider: &str, subject: &str, user_id: &str) -> anyhow::Result<()> {
+    super::link_user_identity(pool, provider, subject, user_id).await
+}
+
+pub async fn find_user_by_identity(pool: &DbPool, provider: &str, subject: &str) -> anyhow::Result<Option<String>> {
+    super::find_user_by_identity(pool, provider, subject).await
+}
+
+pub async fn record_security_event(
+    pool: &DbPool,
+    jti: &str,
+    issuer: &str,
+    event_types: &str,
+    expires_at: i64,
+) -> anyhow::Result<bool> {
+    super::record_security_event(pool, jti, issuer, event_types, expires_at).await
+}
+
+pub async fn forget_security_event(pool: &DbPool, jti: &str) -> anyhow::Result<()> {
+    super::forget_security_event(pool, jti).await
+}
+
+pub async fn lock_user(pool: &DbPool, user_id: &str, reason: &str) -> anyhow::Result<()> {
+    super::lock_user(pool, user_id, reason).await
+}
+
+pub async fn unlock_user(pool: &DbPool, user_id: &str, reason_prefix: &str) -> anyhow::Result<bool> {
+    super::unlock_user(pool, user_id, reason_prefix).await
+}
+
+pub async fn set_email_verified(pool: &DbPool, user_id: &str, verified: bool) -> anyhow::Result<()> {
+    super::set_email_verified(pool, user_id, verified).await
+}
+
+pub async fn is_user_locked(pool: &DbPool, user_id: &str) -> anyhow::Result<bool> {
+    super::is_user_locked(pool, user_id).await
+}
@@ -0,0 +1,36 @@
+use crate::db::models::{AccountantGrant, ClientGrant, NewAccountantGrant};
+use crate::db::DbPool;
+
+/// Returns None when the owner already has an active grant for this email.
+pub async fn create_grant(pool: &DbPool, input: &NewAccountantGrant) -> anyhow::Result<Option<AccountantGrant>> {
+    super::create_accountant_grant(pool, input).await
+}
+
+pub async fn list_grants(pool: &DbPool, owner_user_id: &str) -> anyhow::Result<Vec<AccountantGrant>> {
+    super::list_accountant_grants(pool, owner_user_id).await
+}
+
+pub async fn revoke_grant(pool: &DbPool, owner_user_id: &str, grant_id: &str) -> anyhow::Result<bool> {
+    super::revoke_accountant_grant(pool, owner_user_id, grant_id).await
+}
+
+pub async fn list_client_grants(
+    pool: &DbPool,
+    accountant_email: &str,
+    accountant_user_id: &str,
+) -> anyhow::Result<Vec<ClientGrant>> {
+    super::list_client_grants(pool, accountant_email, accountant_user_id).await
+}
+
+pub async fn find_client_grant(
+    pool: &DbPool,
+    grant_id: &str,
+    accountant_email: &str,
+    accountant_user_id: &str,
+) -> anyhow::Result<Option<ClientGrant>> {
+    super::find_client_grant(pool, grant_id, accountant_email, accountant_user_id).await
+}
+
+pub async fn mark_accepted(pool: &DbPool, grant_id: &str, accountant_user_id: &str) -> anyhow::Result<()> {
+    super::mark_accountant_grant_accepted(pool, grant_id, accountant_user_id).await
+}
@@ -0,0 +1,17 @@
+use crate::db::models::{BlindIndexDuplicateGroup, BlindIndexTokens, Donation};
+use crate::db::DbPool;
+
+/// Live donations of the user carrying every token set in `filter`.
+pub async fn find_donations(pool: &DbPool, user_id: &str, filter: &BlindIndexTokens) -> anyhow::Result<Vec<Donation>> {
+    super::find_donations_by_blind_index(pool, user_id, filter).await
+}
+
+/// Distinct year tokens of the user's live donations.
+pub async fn list_year_tokens(pool: &DbPool, user_id: &str) -> anyhow::Result<Vec<String>> {
+    super::list_blind_index_year_tokens(pool, user_id).await
+}
+
+/// Dedupe tokens shared by more than one live donation.
+pub async fn list_duplicates(pool: &DbPool, user_id: &str) -> anyhow::Result<Vec<BlindIndexDuplicateGroup>> {
+    super::list_blind_index_duplicates(pool, user_id).await
+}
@@ -2,6 +2,20 @@
 include!("core_sections/bootstrap/runtime_and_bootstrap.rs");
 include!("core_sections/donations/donations_and_receipts.rs");
 include!("core_sections/donations/donation_updates_and_valuations.rs");
+include!("core_sections/donations/receipt_updates_and_deletion.rs");
+include!("core_sections/donations/receipt_uploads.rs");
+include!("core_sections/donations/blind_index_search.rs");
+include!("core_sections/sync/batch_sync.rs");
+include!("core_sections/sync/change_feed.rs");
+include!("core_sections/idempotency/idempotency_keys.rs");
+include!("core_sections/auth/access_tokens.rs");
+include!("core_sections/auth/passkeys.rs");
+include!("core_sections/auth/revocations.rs");
+include!("core_sections/auth/account_security.rs");
+include!("core_sections/auth/sessions.rs");
+include!("core_sections/auth/refresh_tokens.rs");
+include!("core_sections/sharing/accountant_grants.rs");
+include!("core_sections/auth/vault_rotations.rs");
 include!("core_sections/charities/charities_and_receipt_ocr.rs");
 include!("core_sections/charities/charity_lookup_and_create.rs");
 include!("core_sections/charities/charity_updates_and_deletion.rs");
@@ -0,0 +1,37 @@
+pub async fn create_access_token(pool: &DbPool, input: &crate::db::models::NewAccessToken) -> anyhow::Result<crate::db::models::AccessToken> {
+    match &**pool {
+        DbPoolEnum::Oracle(p) => crate::db::oracle::access_tokens::create_token(p, input).await,
+    }
+}
+
+pub async fn list_access_tokens(pool: &DbPool, user_id: &str) -> anyhow::Result<Vec<crate::db::models::AccessToken>> {
+    match &**pool {
+        DbPoolEnum::Oracle(p) => crate::db::oracle::access_tokens::list_tokens(p, user_id).await,
+    }
+}
+
+/// Returns false when the token does not belong to the user or was already revoked.
+pub async fn revoke_access_token(pool: &DbPool, user_id: &str, token_id: &str) -> anyhow::Result<bool> {
+    match &**pool {
+        DbPoolEnum::Oracle(p) => crate::db::oracle::access_tokens::revoke_token(p, user_id, token_id).await,
+    }
+}
+
+/// Revokes every live token of the user; returns how many were revoked.
+pub async fn revoke_all_access_tokens(pool: &DbPool, user_id: &str) -> anyhow::Result<u64> {
+    match &**pool {
+        DbPoolEnum::Oracle(p) => crate::db::oracle::access_tokens::revoke_all_tokens(p, user_id).await,
+    }
+}
+
+pub async fn find_active_access_token(pool: &DbPool, token_id: &str) -> anyhow::Result<Option<crate::db::models::ActiveAccessToken>> {
+    match &**pool {
+        DbPoolEnum::Oracle(p) => crate::db::oracle::access_tokens::find_active_token(p, token_id).await,
+    }
+}
+
+pub async fn touch_access_token(pool: &DbPool, token_id: &str) -> anyhow::Result<()> {
+    match &**pool {
+        DbPoolEnum::Oracle(p) => crate::db::oracle::access_tokens::touch_token(p, token_id).await,
+    }
+}
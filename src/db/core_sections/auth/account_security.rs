@@ -0,0 +1,48 @@
+pub async fn link_user_identity(pool: &DbPool, provider: &str, subject: &str, user_id: &str) -> anyhow::Result<()> {
+    match &**pool {
+        DbPoolEnum::Oracle(p) => crate::db::oracle::account_security::link_identity(p, provider, subject, user_id).await,
+    }
+}
+
+pub async fn find_user_by_identity(pool: &DbPool, provider: &str, subject: &str) -> anyhow::Result<Option<String>> {
+    match &**pool {
+        DbPoolEnum::Oracle(p) => crate::db::oracle::account_security::find_user_by_identity(p, provider, subject).await,
+    }
+}
+
+/// Returns false for a jti that was already processed.
+pub async fn record_security_event(pool: &DbPool, jti: &str, issuer: &str, event_types: &str, expires_at: i64) -> anyhow::Result<bool> {
+    match &**pool {
+        DbPoolEnum::Oracle(p) => crate::db::oracle::account_security::record_security_event(p, jti, issuer, event_types, expires_at).await,
+    }
+}
+
+pub async fn forget_security_event(pool: &DbPool, jti: &str) -> anyhow::Result<()> {
+    match &**pool {
+        DbPoolEnum::Oracle(p) => crate::db::oracle::account_security::forget_security_event(p, jti).await,
+    }
+}
+
+pub async fn lock_user(pool: &DbPool, user_id: &str, reason: &str) -> anyhow::Result<()> {
+    match &**pool {
+        DbPoolEnum::Oracle(p) => crate::db::oracle::account_security::lock_user(p, user_id, reason).await,
+    }
+}
+
+pub async fn unlock_user(pool: &DbPool, user_id: &str, reason_prefix: &str) -> anyhow::Result<bool> {
+    match &**pool {
+        DbPoolEnum::Oracle(p) => crate::db::oracle::account_security::unlock_user(p, user_id, reason_prefix).await,
+    }
+}
+
+pub async fn set_email_verified(pool: &DbPool, user_id: &str, verified: bool) -> anyhow::Result<()> {
+    match &**pool {
+        DbPoolEnum::Oracle(p) => crate::db::oracle::account_security::set_email_verified(p, user_id, verified).await,
+    }
+}
+
+pub async fn is_user_locked(pool: &DbPool, user_id: &str) -> anyhow::Result<bool> {
+    match &**pool {
+        DbPoolEnum::Oracle(p) => crate::db::oracle::account_security::is_user_locked(p, user_id).await,
+    }
+}
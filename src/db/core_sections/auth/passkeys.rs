@@ -0,0 +1,44 @@
+pub async fn create_webauthn_challenge(pool: &DbPool, input: &crate::db::models::NewWebauthnChallenge) -> anyhow::Result<()> {
+    match &**pool {
+        DbPoolEnum::Oracle(p) => crate::db::oracle::passkeys::create_challenge(p, input).await,
+    }
+}
+
+/// Single use: returns None for unknown, expired, already consumed or wrong-purpose challenges.
+pub async fn take_webauthn_challenge(pool: &DbPool, id: &str, purpose: &str) -> anyhow::Result<Option<crate::db::models::WebauthnChallenge>> {
+    match &**pool {
+        DbPoolEnum::Oracle(p) => crate::db::oracle::passkeys::take_challenge(p, id, purpose).await,
+    }
+}
+
+/// Returns false when the credential id is already registered.
+pub async fn insert_passkey_credential(pool: &DbPool, input: &crate::db::models::NewPasskeyCredential) -> anyhow::Result<bool> {
+    match &**pool {
+        DbPoolEnum::Oracle(p) => crate::db::oracle::passkeys::insert_credential(p, input).await,
+    }
+}
+
+pub async fn list_passkey_credentials(pool: &DbPool, user_id: &str) -> anyhow::Result<Vec<crate::db::models::PasskeyCredential>> {
+    match &**pool {
+        DbPoolEnum::Oracle(p) => crate::db::oracle::passkeys::list_credentials(p, user_id).await,
+    }
+}
+
+pub async fn find_passkey_credential(pool: &DbPool, credential_id: &str) -> anyhow::Result<Option<crate::db::models::StoredPasskey>> {
+    match &**pool {
+        DbPoolEnum::Oracle(p) => crate::db::oracle::passkeys::find_credential(p, credential_id).await,
+    }
+}
+
+/// Returns false when the stored counter moved since it was read.
+pub async fn record_passkey_assertion(pool: &DbPool, credential_id: &str, previous_sign_count: i64, sign_count: i64) -> anyhow::Result<bool> {
+    match &**pool {
+        DbPoolEnum::Oracle(p) => crate::db::oracle::passkeys::record_assertion(p, credential_id, previous_sign_count, sign_count).await,
+    }
+}
+
+pub async fn delete_passkey_credential(pool: &DbPool, user_id: &str, credential_id: &str) -> anyhow::Result<bool> {
+    match &**pool {
+        DbPoolEnum::Oracle(p) => crate::db::oracle::passkeys::delete_credential(p, user_id, credential_id).await,
+    }
+}
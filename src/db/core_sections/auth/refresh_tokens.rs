@@ -0,0 +1,24 @@
+pub async fn create_refresh_token(pool: &DbPool, token: &crate::db::models::NewRefreshToken) -> anyhow::Result<()> {
+    match &**pool {
+        DbPoolEnum::Oracle(p) => crate::db::oracle::refresh_tokens::create_refresh_token(p, token).await,
+    }
+}
+
+pub async fn rotate_refresh_token(
+    pool: &DbPool,
+    presented_hash: &str,
+    next_hash: &str,
+    expires_at: i64,
+) -> anyhow::Result<crate::db::models::RefreshRotation> {
+    match &**pool {
+        DbPoolEnum::Oracle(p) => {
+            crate::db::oracle::refresh_tokens::rotate_refresh_token(p, presented_hash, next_hash, expires_at).await
+        }
+    }
+}
+
+pub async fn find_refresh_token_family(pool: &DbPool, token_hash: &str) -> anyhow::Result<Option<(String, String)>> {
+    match &**pool {
+        DbPoolEnum::Oracle(p) => crate::db::oracle::refresh_tokens::find_refresh_family(p, token_hash).await,
+    }
+}
@@ -0,0 +1,17 @@
+pub async fn revoke_session_token(pool: &DbPool, jti: &str, user_id: &str, expires_at: i64) -> anyhow::Result<()> {
+    match &**pool {
+        DbPoolEnum::Oracle(p) => crate::db::oracle::revocations::revoke_token(p, jti, user_id, expires_at).await,
+    }
+}
+
+pub async fn revoke_session_tokens_issued_before(pool: &DbPool, user_id: &str, valid_after: i64) -> anyhow::Result<()> {
+    match &**pool {
+        DbPoolEnum::Oracle(p) => crate::db::oracle::revocations::revoke_tokens_issued_before(p, user_id, valid_after).await,
+    }
+}
+
+pub async fn session_token_status(pool: &DbPool, jti: &str, session_id: &str, user_id: &str) -> anyhow::Result<crate::db::models::TokenRevocationStatus> {
+    match &**pool {
+        DbPoolEnum::Oracle(p) => crate::db::oracle::revocations::token_status(p, jti, session_id, user_id).await,
+    }
+}
@@ -0,0 +1,23 @@
+pub async fn create_user_session(pool: &DbPool, session: &crate::db::models::NewUserSession) -> anyhow::Result<()> {
+    match &**pool {
+        DbPoolEnum::Oracle(p) => crate::db::oracle::sessions::create_session(p, session).await,
+    }
+}
+
+pub async fn list_user_sessions(pool: &DbPool, user_id: &str) -> anyhow::Result<Vec<crate::db::models::UserSession>> {
+    match &**pool {
+        DbPoolEnum::Oracle(p) => crate::db::oracle::sessions::list_sessions(p, user_id).await,
+    }
+}
+
+pub async fn active_user_session_expiry(pool: &DbPool, user_id: &str, jti: &str) -> anyhow::Result<Option<i64>> {
+    match &**pool {
+        DbPoolEnum::Oracle(p) => crate::db::oracle::sessions::active_session_expiry(p, user_id, jti).await,
+    }
+}
+
+pub async fn touch_user_session(pool: &DbPool, jti: &str) -> anyhow::Result<()> {
+    match &**pool {
+        DbPoolEnum::Oracle(p) => crate::db::oracle::sessions::touch_session(p, jti).await,
+    }
+}
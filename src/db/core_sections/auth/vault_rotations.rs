@@ -0,0 +1,53 @@
+pub async fn latest_vault_rotation(pool: &DbPool, user_id: &str) -> anyhow::Result<Option<crate::db::models::VaultKeyRotation>> {
+    match &**pool {
+        DbPoolEnum::Oracle(p) => crate::db::oracle::vault_rotations::latest_rotation(p, user_id).await,
+    }
+}
+
+pub async fn create_vault_rotation(pool: &DbPool, input: &crate::db::models::NewVaultKeyRotation) -> anyhow::Result<crate::db::models::VaultKeyRotation> {
+    match &**pool {
+        DbPoolEnum::Oracle(p) => crate::db::oracle::vault_rotations::create_rotation(p, input).await,
+    }
+}
+
+pub async fn resume_vault_rotation(pool: &DbPool, rotation_id: &str) -> anyhow::Result<crate::db::models::VaultKeyRotation> {
+    match &**pool {
+        DbPoolEnum::Oracle(p) => crate::db::oracle::vault_rotations::resume_rotation(p, rotation_id).await,
+    }
+}
+
+pub async fn count_vault_rotation_items(pool: &DbPool, user_id: &str, rotated_prefix: &str) -> anyhow::Result<i64> {
+    match &**pool {
+        DbPoolEnum::Oracle(p) => crate::db::oracle::vault_rotations::count_pending_items(p, user_id, rotated_prefix).await,
+    }
+}
+
+pub async fn pending_vault_rotation_items(pool: &DbPool, user_id: &str, rotated_prefix: &str, limit: i64) -> anyhow::Result<Vec<crate::db::models::VaultRotationItem>> {
+    match &**pool {
+        DbPoolEnum::Oracle(p) => crate::db::oracle::vault_rotations::pending_items(p, user_id, rotated_prefix, limit).await,
+    }
+}
+
+pub async fn replace_vault_item_payload(pool: &DbPool, user_id: &str, item: &crate::db::models::VaultRotationItem, payload: &str) -> anyhow::Result<bool> {
+    match &**pool {
+        DbPoolEnum::Oracle(p) => crate::db::oracle::vault_rotations::replace_item_payload(p, user_id, item, payload).await,
+    }
+}
+
+pub async fn record_vault_rotation_progress(pool: &DbPool, rotation_id: &str, processed: i64) -> anyhow::Result<crate::db::models::VaultKeyRotation> {
+    match &**pool {
+        DbPoolEnum::Oracle(p) => crate::db::oracle::vault_rotations::record_progress(p, rotation_id, processed).await,
+    }
+}
+
+pub async fn fail_vault_rotation(pool: &DbPool, rotation_id: &str, failed_table: &str, failed_record_id: &str, error: &str) -> anyhow::Result<crate::db::models::VaultKeyRotation> {
+    match &**pool {
+        DbPoolEnum::Oracle(p) => crate::db::oracle::vault_rotations::fail_rotation(p, rotation_id, failed_table, failed_record_id, error).await,
+    }
+}
+
+pub async fn complete_vault_rotation(pool: &DbPool, user_id: &str, rotation_id: &str) -> anyhow::Result<crate::db::models::VaultKeyRotation> {
+    match &**pool {
+        DbPoolEnum::Oracle(p) => crate::db::oracle::vault_rotations::complete_rotation(p, user_id, rotation_id).await,
+    }
+}
@@ -4,6 +4,7 @@ use anyhow::anyhow;
 use std::sync::Arc;
 use serde_json::json;
 use uuid::Uuid;
+use crate::db::field_crypto;
 
 pub enum DbPoolEnum {
     Oracle(Pool),
@@ -186,6 +187,7 @@ pub async fn import_data(
             notes: donation.notes.clone(),
             is_encrypted: donation.is_encrypted,
             encrypted_payload: donation.encrypted_payload.clone(),
+            blind_index: None,
             created_at: donation.created_at,
         };
         let _ = add_donation(pool, &new_donation).await;
@@ -202,6 +204,7 @@ pub async fn import_data(
             size: receipt.size,
             is_encrypted: receipt.is_encrypted,
             encrypted_payload: receipt.encrypted_payload.clone(),
+            content_hash: receipt.content_hash.clone(),
             created_at: receipt.created_at,
         };
         let _ = add_receipt(pool, &new_receipt).await;
@@ -277,6 +280,32 @@ pub async fn add_receipt(
         DbPoolEnum::Oracle(p) => crate::db::oracle::receipts::add_receipt(p, &input, &created_at_str).await?,
     };
 
+    log_receipt_created(pool, donation_owner, &input, &created_at_str).await
+}
+
+/// Adds a receipt sharing the object at `input.key` with the user's other receipts.
+/// Returns false, adding nothing, when no receipt holds that key any more.
+pub async fn add_shared_receipt(
+    pool: &DbPool,
+    user_id: &str,
+    input: &NewReceipt,
+) -> anyhow::Result<bool> {
+    let created_at_str = input.created_at.to_rfc3339();
+    let added = match &**pool {
+        DbPoolEnum::Oracle(p) => crate::db::oracle::receipts::add_shared_receipt(p, user_id, input, &created_at_str).await?,
+    };
+    if added {
+        log_receipt_created(pool, Some(user_id.to_string()), input, &created_at_str).await?;
+    }
+    Ok(added)
+}
+
+async fn log_receipt_created(
+    pool: &DbPool,
+    donation_owner: Option<String>,
+    input: &NewReceipt,
+    created_at_str: &str,
+) -> anyhow::Result<()> {
     let revision = RevisionLogEntry {
         id: Uuid::new_v4().to_string(),
         user_id: donation_owner,
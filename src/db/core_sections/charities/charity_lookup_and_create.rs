@@ -46,8 +46,8 @@ pub async fn log_revision(
     let table_name = entry.table_name.clone();
     let record_id = entry.record_id.clone();
     let operation = entry.operation.clone();
-    let old_values_cloned = entry.old_values.clone();
-    let new_values_cloned = entry.new_values.clone();
+    let old_values_cloned = entry.old_values.clone().map(crate::db::oracle::data_keys::redact_revision);
+    let new_values_cloned = entry.new_values.clone().map(crate::db::oracle::data_keys::redact_revision);
     let created_at = chrono::Utc::now().to_rfc3339();
 
     match &**pool {
@@ -139,6 +139,7 @@ pub async fn find_charity_by_name_or_ein(
 ) -> anyhow::Result<Option<crate::db::models::Charity>> {
     match &**pool {
         DbPoolEnum::Oracle(p) => {
+            let key = crate::db::oracle::data_keys::for_read(p, user_id).await?;
             let conn = p.get().await?;
             if let Some(ein_val) = ein.clone().filter(|value| !value.is_empty()) {
                 let rows = conn
@@ -148,7 +149,7 @@ pub async fn find_charity_by_name_or_ein(
                     )
                     .await?;
                 if let Some(row) = rows.first() {
-                    return Ok(Some(charity_from_row(row)));
+                    return key.open_charity(charity_from_row(row)).map(Some);
                 }
             }
 
@@ -159,8 +160,9 @@ pub async fn find_charity_by_name_or_ein(
                     &crate::oracle_params![user_id.to_string(), normalized_name],
                 )
                 .await?;
-            let row = rows.first().map(charity_from_row);
-            Ok(row)
+            rows.first()
+                .map(|row| key.open_charity(charity_from_row(row)))
+                .transpose()
         }
     }
 }
@@ -193,6 +195,7 @@ pub async fn create_charity(pool: &DbPool, input: &crate::db::models::NewCharity
 
     match &**pool {
         DbPoolEnum::Oracle(p) => {
+            let key = crate::db::oracle::data_keys::for_write(p, &input.user_id).await?;
             let conn = p.get().await?;
             let trunc_name = input.name.chars().take(255).collect::<String>();
             let is_encrypted = input.is_encrypted.map(|v| if v { 1 } else { 0 });
@@ -210,10 +213,10 @@ pub async fn create_charity(pool: &DbPool, input: &crate::db::models::NewCharity
                         input.classification.clone(),
                         input.nonprofit_type.clone(),
                         input.deductibility.clone(),
-                        input.street.clone(),
-                        input.city.clone(),
-                        input.state.clone(),
-                        input.zip.clone(),
+                        key.seal(field_crypto::CHARITY_STREET, input.street.clone())?,
+                        key.seal(field_crypto::CHARITY_CITY, input.city.clone())?,
+                        key.seal(field_crypto::CHARITY_STATE, input.state.clone())?,
+                        key.seal(field_crypto::CHARITY_ZIP, input.zip.clone())?,
                         is_encrypted,
                         input.encrypted_payload.clone(),
                         created_at_str.clone(),
@@ -224,6 +227,7 @@ pub async fn create_charity(pool: &DbPool, input: &crate::db::models::NewCharity
                 tracing::error!("Failed to create charity: {}. SQL: {}", e, sql);
                 return Err(anyhow::anyhow!("Charity creation failed: {}", e));
             }
+            crate::db::oracle::sync_changes::record_change(&conn, &input.user_id, "charities", &input.id, "upsert").await?;
             conn.commit().await?;
         }
     };
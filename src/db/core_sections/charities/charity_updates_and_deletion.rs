@@ -19,6 +19,7 @@ pub async fn update_charity(pool: &DbPool, patch: &crate::db::models::CharityPat
     match &**pool {
         DbPoolEnum::Oracle(p) => {
             let charity_id_for_revision = charity_id.clone();
+            let key = crate::db::oracle::data_keys::for_write(p, &user_id).await?;
             let conn = p.get().await?;
             let existing_rows = conn
                 .query(
@@ -53,10 +54,10 @@ pub async fn update_charity(pool: &DbPool, patch: &crate::db::models::CharityPat
                             classification_cloned.clone(),
                             nonprofit_type_cloned.clone(),
                             deductibility_cloned.clone(),
-                            street_cloned.clone(),
-                            city_cloned.clone(),
-                            state_cloned.clone(),
-                            zip_cloned.clone(),
+                            key.seal(field_crypto::CHARITY_STREET, street_cloned.clone())?,
+                            key.seal(field_crypto::CHARITY_CITY, city_cloned.clone())?,
+                            key.seal(field_crypto::CHARITY_STATE, state_cloned.clone())?,
+                            key.seal(field_crypto::CHARITY_ZIP, zip_cloned.clone())?,
                             updated_at_str.clone(),
                             charity_id.clone(),
                             user_id.clone(),
@@ -67,6 +68,7 @@ pub async fn update_charity(pool: &DbPool, patch: &crate::db::models::CharityPat
                     tracing::error!("Failed to update charity: {}. SQL: {}", e, sql);
                     return Err(anyhow::anyhow!("Charity update failed: {}", e));
                 }
+                crate::db::oracle::sync_changes::record_change(&conn, &user_id, "charities", &charity_id, "upsert").await?;
                 conn.commit().await?;
                 let old_values = json!({
                     "id": charity_id,
@@ -188,6 +190,7 @@ pub async fn delete_charity(pool: &DbPool, user_id: &str, charity_id: &str) -> a
                 let existing_created_at = crate::db::oracle::row_opt_string(existing, 11);
                 let existing_updated_at = crate::db::oracle::row_opt_string(existing, 12);
 
+                crate::db::oracle::sync_changes::record_charity_receipt_deletes(&conn, &user_id, &charity_id).await?;
                 let del_receipts_sql = "DELETE FROM receipts WHERE donation_id IN (SELECT id FROM donations WHERE charity_id = :1 AND user_id = :2 AND deleted = 1)";
                 if let Err(e) = conn
                     .execute(
@@ -220,6 +223,7 @@ pub async fn delete_charity(pool: &DbPool, user_id: &str, charity_id: &str) -> a
                     tracing::error!("Failed to delete charity {}: {}", charity_id, e);
                     return Err(anyhow::anyhow!("Charity delete failed: {}", e));
                 }
+                crate::db::oracle::sync_changes::record_change(&conn, &user_id, "charities", &charity_id, "delete").await?;
                 conn.commit().await?;
                 Some(json!({
                     "id": charity_id,
@@ -0,0 +1,17 @@
+pub async fn find_donations_by_blind_index(pool: &DbPool, user_id: &str, filter: &crate::db::models::BlindIndexTokens) -> anyhow::Result<Vec<DonationModel>> {
+    match &**pool {
+        DbPoolEnum::Oracle(p) => crate::db::oracle::blind_index::find_donations(p, user_id, filter).await,
+    }
+}
+
+pub async fn list_blind_index_year_tokens(pool: &DbPool, user_id: &str) -> anyhow::Result<Vec<String>> {
+    match &**pool {
+        DbPoolEnum::Oracle(p) => crate::db::oracle::blind_index::list_year_tokens(p, user_id).await,
+    }
+}
+
+pub async fn list_blind_index_duplicates(pool: &DbPool, user_id: &str) -> anyhow::Result<Vec<crate::db::models::BlindIndexDuplicateGroup>> {
+    match &**pool {
+        DbPoolEnum::Oracle(p) => crate::db::oracle::blind_index::list_duplicate_groups(p, user_id).await,
+    }
+}
@@ -1,79 +1,3 @@
-pub async fn batch_sync(pool: &DbPool, user_id: &str, req: crate::db::models::BatchSyncRequest) -> anyhow::Result<()> {
-    let user_id = user_id.to_string();
-    let req = req.clone();
-
-    match &**pool {
-        DbPoolEnum::Oracle(pool_inner) => {
-            let conn = pool_inner.get().await?;
-
-            for donation in req.donations {
-                let id = donation.id.clone();
-                let action = donation.action.clone();
-
-                if action == "delete" {
-                    let sql = "UPDATE donations SET deleted = 1, updated_at = CURRENT_TIMESTAMP WHERE id = :1 AND user_id = :2";
-                    conn.execute(sql, &crate::oracle_params![id, user_id.clone()]).await?;
-                    continue;
-                }
-
-                let now = chrono::Utc::now();
-                let donation_date = donation.date.unwrap_or_else(|| now.date_naive());
-                let donation_year = donation.year.unwrap_or(donation_date.year());
-                let incoming_updated_at = donation.updated_at.unwrap_or(now).to_rfc3339();
-                let created_at = now.to_rfc3339();
-                let is_encrypted = donation.is_encrypted.map(|v| if v { 1 } else { 0 });
-                let sql = "MERGE INTO donations d USING (SELECT :1 AS id, :2 AS user_id, TO_DATE(:3, 'YYYY-MM-DD') AS donation_date, :4 AS donation_year, :5 AS donation_category, :6 AS donation_amount, :7 AS charity_id, :8 AS notes, :9 AS is_encrypted, :10 AS encrypted_payload, TO_TIMESTAMP_TZ(:11, 'YYYY-MM-DD\"T\"HH24:MI:SS.FF TZH:TZM') AS incoming_updated_at, TO_TIMESTAMP_TZ(:12, 'YYYY-MM-DD\"T\"HH24:MI:SS.FF TZH:TZM') AS incoming_created_at FROM dual) s ON (d.id = s.id AND d.user_id = s.user_id) WHEN MATCHED THEN UPDATE SET d.donation_date = s.donation_date, d.donation_year = s.donation_year, d.donation_category = s.donation_category, d.donation_amount = s.donation_amount, d.charity_id = s.charity_id, d.notes = s.notes, d.is_encrypted = s.is_encrypted, d.encrypted_payload = s.encrypted_payload, d.updated_at = s.incoming_updated_at, d.deleted = 0 WHERE d.updated_at IS NULL OR d.updated_at <= s.incoming_updated_at OR s.incoming_updated_at IS NULL WHEN NOT MATCHED THEN INSERT (id, user_id, donation_date, donation_year, donation_category, donation_amount, charity_id, notes, is_encrypted, encrypted_payload, created_at, updated_at, deleted) VALUES (s.id, s.user_id, s.donation_date, s.donation_year, s.donation_category, s.donation_amount, s.charity_id, s.notes, s.is_encrypted, s.encrypted_payload, s.incoming_created_at, s.incoming_updated_at, 0)";
-                conn.execute(
-                    sql,
-                    &crate::oracle_params![
-                        id,
-                        user_id.clone(),
-                        donation_date.format("%Y-%m-%d").to_string(),
-                        donation_year,
-                        donation.category,
-                        donation.amount,
-                        donation.charity_id,
-                        donation.notes,
-                        is_encrypted,
-                        donation.encrypted_payload,
-                        incoming_updated_at,
-                        created_at,
-                    ],
-                )
-                .await?;
-            }
-
-            for receipt in req.receipts {
-                if receipt.action != "create" {
-                    continue;
-                }
-
-                let is_encrypted = receipt.is_encrypted.map(|v| if v { 1 } else { 0 });
-                let sql = "MERGE INTO receipts r USING (SELECT :1 AS id, :2 AS donation_id, :3 AS receipt_key, :4 AS file_name, :5 AS content_type, :6 AS receipt_size, :7 AS is_encrypted, :8 AS encrypted_payload, TO_TIMESTAMP_TZ(:9, 'YYYY-MM-DD\"T\"HH24:MI:SS.FF TZH:TZM') AS created_at FROM dual) s ON (r.id = s.id) WHEN NOT MATCHED THEN INSERT (id, donation_id, receipt_key, file_name, content_type, receipt_size, is_encrypted, encrypted_payload, created_at) VALUES (s.id, s.donation_id, s.receipt_key, s.file_name, s.content_type, s.receipt_size, s.is_encrypted, s.encrypted_payload, s.created_at)";
-                conn.execute(
-                    sql,
-                    &crate::oracle_params![
-                        receipt.id,
-                        receipt.donation_id,
-                        receipt.key,
-                        receipt.file_name,
-                        receipt.content_type,
-                        receipt.size,
-                        is_encrypted,
-                        receipt.encrypted_payload,
-                        chrono::Utc::now().to_rfc3339(),
-                    ],
-                )
-                .await?;
-            }
-
-            conn.commit().await?;
-        }
-    }
-
-    Ok(())
-}
-
 pub async fn update_donation(pool: &DbPool, patch: &crate::db::models::DonationPatch) -> anyhow::Result<bool> {
     let patch = patch.clone();
     let category_owned = patch.category_opt.clone();
@@ -82,6 +6,7 @@ pub async fn update_donation(pool: &DbPool, patch: &crate::db::models::DonationP
 
     match &**pool {
         DbPoolEnum::Oracle(p) => {
+            let key = crate::db::oracle::data_keys::for_write(p, &patch.user_id).await?;
             let conn = p.get().await?;
             let user_id = patch.user_id.clone();
             let donation_id = patch.donation_id.clone();
@@ -113,7 +38,7 @@ pub async fn update_donation(pool: &DbPool, patch: &crate::db::models::DonationP
                         let existing_category = crate::db::oracle::row_opt_string(row, 2);
                         let existing_amount = crate::db::oracle::row_f64(row, 3);
                         let existing_charity_id = crate::db::oracle::row_opt_string(row, 4);
-                        let existing_notes = crate::db::oracle::row_opt_string(row, 5);
+                        let existing_notes = key.open(field_crypto::DONATION_NOTES, crate::db::oracle::row_opt_string(row, 5))?;
 
                         let new_date = date_opt.unwrap_or(existing_date.unwrap_or_else(|| chrono::Utc::now().date_naive()));
                         let new_year = year_opt.unwrap_or(existing_year.unwrap_or(new_date.year()));
@@ -168,7 +93,7 @@ pub async fn update_donation(pool: &DbPool, patch: &crate::db::models::DonationP
                                     new_category,
                                     new_amount,
                                     new_charity_id,
-                                    new_notes,
+                                    key.seal(field_crypto::DONATION_NOTES, new_notes)?,
                                     is_enc_val,
                                     new_encrypted_payload,
                                     new_updated_at,
@@ -181,6 +106,10 @@ pub async fn update_donation(pool: &DbPool, patch: &crate::db::models::DonationP
                             tracing::error!("Failed to update donation: {}. SQL: {}", e, sql);
                             return Err(anyhow::anyhow!("Donation update failed: {}", e));
                         }
+                        if let Some(tokens) = &patch.blind_index {
+                            crate::db::oracle::blind_index::replace_donation_tokens(&conn, &user_id, &donation_id, tokens).await?;
+                        }
+                        crate::db::oracle::sync_changes::record_change(&conn, &user_id, "donations", &donation_id, "upsert").await?;
                         conn.commit().await?;
                         Some((old_values, new_values))
                     }
@@ -190,7 +119,7 @@ pub async fn update_donation(pool: &DbPool, patch: &crate::db::models::DonationP
                     let existing_category = crate::db::oracle::row_opt_string(row, 2);
                     let existing_amount = crate::db::oracle::row_f64(row, 3);
                     let existing_charity_id = crate::db::oracle::row_opt_string(row, 4);
-                    let existing_notes = crate::db::oracle::row_opt_string(row, 5);
+                    let existing_notes = key.open(field_crypto::DONATION_NOTES, crate::db::oracle::row_opt_string(row, 5))?;
 
                     let new_date = date_opt.unwrap_or(existing_date.unwrap_or_else(|| chrono::Utc::now().date_naive()));
                     let new_year = year_opt.unwrap_or(existing_year.unwrap_or(new_date.year()));
@@ -245,7 +174,7 @@ pub async fn update_donation(pool: &DbPool, patch: &crate::db::models::DonationP
                                 new_category,
                                 new_amount,
                                 new_charity_id,
-                                new_notes,
+                                key.seal(field_crypto::DONATION_NOTES, new_notes)?,
                                 is_enc_val,
                                 new_encrypted_payload,
                                 new_updated_at,
@@ -258,6 +187,10 @@ pub async fn update_donation(pool: &DbPool, patch: &crate::db::models::DonationP
                         tracing::error!("Failed to update donation: {}. SQL: {}", e, sql);
                         return Err(anyhow::anyhow!("Donation update failed: {}", e));
                     }
+                    if let Some(tokens) = &patch.blind_index {
+                        crate::db::oracle::blind_index::replace_donation_tokens(&conn, &user_id, &donation_id, tokens).await?;
+                    }
+                    crate::db::oracle::sync_changes::record_change(&conn, &user_id, "donations", &donation_id, "upsert").await?;
                     conn.commit().await?;
                     Some((old_values, new_values))
                 }
@@ -135,6 +135,9 @@ pub async fn soft_delete_donation(pool: &DbPool, user_id: &str, donation_id: &st
                 return Err(anyhow::anyhow!("Donation soft delete failed: {}", e));
             }
 
+            crate::db::oracle::sync_changes::record_change(&conn, &user_id, "donations", &donation_id, "delete").await?;
+            crate::db::oracle::sync_changes::record_donation_receipt_deletes(&conn, &user_id, &donation_id).await?;
+
             if let Err(e) = conn
                 .execute(
                     "DELETE FROM receipts WHERE donation_id = :1",
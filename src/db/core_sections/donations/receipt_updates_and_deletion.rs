@@ -0,0 +1,207 @@
+fn build_receipt_revision_json(
+    receipt_id: &str,
+    donation_id: &str,
+    receipt_key: &str,
+    file_name: &Option<String>,
+    is_encrypted: Option<bool>,
+) -> String {
+    json!({
+        "id": receipt_id,
+        "donation_id": donation_id,
+        "receipt_key": receipt_key,
+        "file_name": file_name,
+        "is_encrypted": is_encrypted,
+    })
+    .to_string()
+}
+
+/// Applies a receipt rename/reassign on an open connection without committing.
+/// Returns the (old, new) revision payloads, or `None` when the receipt or the
+/// target donation does not belong to the user.
+async fn update_receipt_with_conn(
+    conn: &oracle_rs::Connection,
+    patch: &crate::db::models::ReceiptPatch,
+) -> anyhow::Result<Option<(String, String)>> {
+    let rows = conn
+        .query(
+            "SELECT r.donation_id, r.receipt_key, r.file_name, r.is_encrypted, r.encrypted_payload FROM receipts r JOIN donations d ON d.id = r.donation_id WHERE r.id = :1 AND d.user_id = :2",
+            &crate::oracle_params![patch.receipt_id.clone(), patch.user_id.clone()],
+        )
+        .await?;
+    let Some(existing) = rows.first() else {
+        return Ok(None);
+    };
+
+    let existing_donation_id = crate::db::oracle::row_string(existing, 0);
+    let receipt_key = crate::db::oracle::row_string(existing, 1);
+    let existing_file_name = crate::db::oracle::row_opt_string(existing, 2);
+    let existing_is_encrypted = crate::db::oracle::row_bool(existing, 3);
+    let existing_encrypted_payload = crate::db::oracle::row_opt_string(existing, 4);
+
+    let new_donation_id = patch.donation_id.clone().unwrap_or_else(|| existing_donation_id.clone());
+    if new_donation_id != existing_donation_id {
+        let target = conn
+            .query(
+                "SELECT 1 FROM donations WHERE id = :1 AND user_id = :2 AND deleted = 0",
+                &crate::oracle_params![new_donation_id.clone(), patch.user_id.clone()],
+            )
+            .await?;
+        if target.first().is_none() {
+            return Ok(None);
+        }
+    }
+
+    let new_file_name = patch.file_name.clone().or(existing_file_name.clone());
+    let new_is_encrypted = patch.is_encrypted.or(existing_is_encrypted);
+    let new_encrypted_payload = patch.encrypted_payload.clone().or(existing_encrypted_payload);
+
+    let sql = "UPDATE receipts SET donation_id = :1, file_name = :2, is_encrypted = :3, encrypted_payload = :4, updated_at = CURRENT_TIMESTAMP WHERE id = :5";
+    conn.execute(
+        sql,
+        &crate::oracle_params![
+            new_donation_id.clone(),
+            new_file_name.clone(),
+            new_is_encrypted.map(|v| if v { 1 } else { 0 }),
+            new_encrypted_payload,
+            patch.receipt_id.clone(),
+        ],
+    )
+    .await?;
+    crate::db::oracle::sync_changes::record_change(conn, &patch.user_id, "receipts", &patch.receipt_id, "upsert").await?;
+
+    let old_values = build_receipt_revision_json(
+        &patch.receipt_id,
+        &existing_donation_id,
+        &receipt_key,
+        &existing_file_name,
+        existing_is_encrypted,
+    );
+    let new_values = build_receipt_revision_json(
+        &patch.receipt_id,
+        &new_donation_id,
+        &receipt_key,
+        &new_file_name,
+        new_is_encrypted,
+    );
+    Ok(Some((old_values, new_values)))
+}
+
+/// Deletes a receipt row on an open connection without committing.
+/// Returns the storage key and old revision payload, or `None` when the receipt is not the user's.
+async fn delete_receipt_with_conn(
+    conn: &oracle_rs::Connection,
+    user_id: &str,
+    receipt_id: &str,
+) -> anyhow::Result<Option<(String, String)>> {
+    let rows = conn
+        .query(
+            "SELECT r.donation_id, r.receipt_key, r.file_name, r.is_encrypted FROM receipts r JOIN donations d ON d.id = r.donation_id WHERE r.id = :1 AND d.user_id = :2",
+            &crate::oracle_params![receipt_id.to_string(), user_id.to_string()],
+        )
+        .await?;
+    let Some(existing) = rows.first() else {
+        return Ok(None);
+    };
+
+    let donation_id = crate::db::oracle::row_string(existing, 0);
+    let receipt_key = crate::db::oracle::row_string(existing, 1);
+    let file_name = crate::db::oracle::row_opt_string(existing, 2);
+    let is_encrypted = crate::db::oracle::row_bool(existing, 3);
+
+    conn.execute(
+        "DELETE FROM receipts WHERE id = :1",
+        &crate::oracle_params![receipt_id.to_string()],
+    )
+    .await?;
+    crate::db::oracle::sync_changes::record_change(conn, user_id, "receipts", receipt_id, "delete").await?;
+
+    let old_values = build_receipt_revision_json(receipt_id, &donation_id, &receipt_key, &file_name, is_encrypted);
+    Ok(Some((receipt_key, old_values)))
+}
+
+pub async fn update_receipt(pool: &DbPool, patch: &crate::db::models::ReceiptPatch) -> anyhow::Result<bool> {
+    let revision_payload = match &**pool {
+        DbPoolEnum::Oracle(p) => {
+            let conn = p.get().await?;
+            let payload = update_receipt_with_conn(&conn, patch).await?;
+            if payload.is_some() {
+                conn.commit().await?;
+            }
+            payload
+        }
+    };
+
+    let Some((old_values, new_values)) = revision_payload else {
+        return Ok(false);
+    };
+    let revision = RevisionLogEntry {
+        id: Uuid::new_v4().to_string(),
+        user_id: Some(patch.user_id.clone()),
+        table_name: "receipts".to_string(),
+        record_id: patch.receipt_id.clone(),
+        operation: "update".to_string(),
+        old_values: Some(old_values),
+        new_values: Some(new_values),
+    };
+    log_revision(pool, &revision).await?;
+    Ok(true)
+}
+
+/// Removes the receipt row and returns its storage key so the caller can delete the object.
+pub async fn delete_receipt(pool: &DbPool, user_id: &str, receipt_id: &str) -> anyhow::Result<Option<String>> {
+    let deleted = match &**pool {
+        DbPoolEnum::Oracle(p) => {
+            let conn = p.get().await?;
+            let deleted = delete_receipt_with_conn(&conn, user_id, receipt_id).await?;
+            if deleted.is_some() {
+                conn.commit().await?;
+            }
+            deleted
+        }
+    };
+
+    let Some((receipt_key, old_values)) = deleted else {
+        return Ok(None);
+    };
+    let revision = RevisionLogEntry {
+        id: Uuid::new_v4().to_string(),
+        user_id: Some(user_id.to_string()),
+        table_name: "receipts".to_string(),
+        record_id: receipt_id.to_string(),
+        operation: "delete".to_string(),
+        old_values: Some(old_values),
+        new_values: None,
+    };
+    log_revision(pool, &revision).await?;
+    Ok(Some(receipt_key))
+}
+
+pub async fn list_all_receipt_keys(pool: &DbPool) -> anyhow::Result<Vec<String>> {
+    match &**pool {
+        DbPoolEnum::Oracle(p) => crate::db::oracle::receipts::list_all_receipt_keys(p).await,
+    }
+}
+
+pub async fn find_receipts_by_content_hash(pool: &DbPool, user_id: &str, content_hash: &str) -> anyhow::Result<Vec<crate::db::models::Receipt>> {
+    match &**pool {
+        DbPoolEnum::Oracle(p) => crate::db::oracle::receipts::find_receipts_by_content_hash(p, user_id, content_hash).await,
+    }
+}
+
+pub async fn content_hash_for_key(pool: &DbPool, user_id: &str, key: &str) -> anyhow::Result<Option<String>> {
+    match &**pool {
+        DbPoolEnum::Oracle(p) => crate::db::oracle::receipts::content_hash_for_key(p, user_id, key).await,
+    }
+}
+
+pub async fn receipt_key_in_use(pool: &DbPool, user_id: &str, key: &str) -> anyhow::Result<bool> {
+    match &**pool {
+        DbPoolEnum::Oracle(p) => crate::db::oracle::receipts::receipt_key_in_use(p, user_id, key).await,
+    }
+}
+
+pub async fn set_content_hash_for_key(pool: &DbPool, key: &str, content_hash: &str) -> anyhow::Result<()> {
+    match &**pool {
+        DbPoolEnum::Oracle(p) => crate::db::oracle::receipts::set_content_hash_for_key(p, key, content_hash).await,
+    }
+}
@@ -0,0 +1,29 @@
+pub async fn create_receipt_upload(pool: &DbPool, input: &crate::db::models::ReceiptUpload) -> anyhow::Result<()> {
+    match &**pool {
+        DbPoolEnum::Oracle(p) => crate::db::oracle::receipt_uploads::create_upload(p, input).await,
+    }
+}
+
+pub async fn get_receipt_upload(pool: &DbPool, user_id: &str, id: &str) -> anyhow::Result<Option<crate::db::models::ReceiptUpload>> {
+    match &**pool {
+        DbPoolEnum::Oracle(p) => crate::db::oracle::receipt_uploads::get_upload(p, user_id, id).await,
+    }
+}
+
+pub async fn list_user_receipt_uploads(pool: &DbPool, user_id: &str) -> anyhow::Result<Vec<crate::db::models::ReceiptUpload>> {
+    match &**pool {
+        DbPoolEnum::Oracle(p) => crate::db::oracle::receipt_uploads::list_user_uploads(p, user_id).await,
+    }
+}
+
+pub async fn list_receipt_uploads_started_before(pool: &DbPool, cutoff: chrono::DateTime<chrono::Utc>) -> anyhow::Result<Vec<crate::db::models::ReceiptUpload>> {
+    match &**pool {
+        DbPoolEnum::Oracle(p) => crate::db::oracle::receipt_uploads::list_uploads_started_before(p, cutoff).await,
+    }
+}
+
+pub async fn delete_receipt_upload(pool: &DbPool, id: &str) -> anyhow::Result<bool> {
+    match &**pool {
+        DbPoolEnum::Oracle(p) => crate::db::oracle::receipt_uploads::delete_upload(p, id).await,
+    }
+}
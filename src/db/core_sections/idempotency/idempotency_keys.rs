@@ -0,0 +1,19 @@
+/// Reserves `key` for the user's request with fingerprint `request_hash`, or reports why it cannot be reserved.
+pub async fn claim_idempotency_key(pool: &DbPool, user_id: &str, key: &str, request_hash: &str, ttl_secs: i64) -> anyhow::Result<crate::db::models::IdempotencyClaim> {
+    match &**pool {
+        DbPoolEnum::Oracle(p) => crate::db::oracle::idempotency::claim_key(p, user_id, key, request_hash, ttl_secs).await,
+    }
+}
+
+pub async fn store_idempotent_response(pool: &DbPool, user_id: &str, key: &str, response: &crate::db::models::StoredResponse) -> anyhow::Result<()> {
+    match &**pool {
+        DbPoolEnum::Oracle(p) => crate::db::oracle::idempotency::store_response(p, user_id, key, response).await,
+    }
+}
+
+/// Drops an unfinished claim so the client can retry with the same key.
+pub async fn release_idempotency_key(pool: &DbPool, user_id: &str, key: &str) -> anyhow::Result<()> {
+    match &**pool {
+        DbPoolEnum::Oracle(p) => crate::db::oracle::idempotency::release_key(p, user_id, key).await,
+    }
+}
@@ -0,0 +1,35 @@
+pub async fn create_accountant_grant(pool: &DbPool, input: &crate::db::models::NewAccountantGrant) -> anyhow::Result<Option<crate::db::models::AccountantGrant>> {
+    match &**pool {
+        DbPoolEnum::Oracle(p) => crate::db::oracle::accountant_grants::create_grant(p, input).await,
+    }
+}
+
+pub async fn list_accountant_grants(pool: &DbPool, owner_user_id: &str) -> anyhow::Result<Vec<crate::db::models::AccountantGrant>> {
+    match &**pool {
+        DbPoolEnum::Oracle(p) => crate::db::oracle::accountant_grants::list_grants(p, owner_user_id).await,
+    }
+}
+
+pub async fn revoke_accountant_grant(pool: &DbPool, owner_user_id: &str, grant_id: &str) -> anyhow::Result<bool> {
+    match &**pool {
+        DbPoolEnum::Oracle(p) => crate::db::oracle::accountant_grants::revoke_grant(p, owner_user_id, grant_id).await,
+    }
+}
+
+pub async fn list_client_grants(pool: &DbPool, accountant_email: &str, accountant_user_id: &str) -> anyhow::Result<Vec<crate::db::models::ClientGrant>> {
+    match &**pool {
+        DbPoolEnum::Oracle(p) => crate::db::oracle::accountant_grants::list_client_grants(p, accountant_email, accountant_user_id).await,
+    }
+}
+
+pub async fn find_client_grant(pool: &DbPool, grant_id: &str, accountant_email: &str, accountant_user_id: &str) -> anyhow::Result<Option<crate::db::models::ClientGrant>> {
+    match &**pool {
+        DbPoolEnum::Oracle(p) => crate::db::oracle::accountant_grants::find_client_grant(p, grant_id, accountant_email, accountant_user_id).await,
+    }
+}
+
+pub async fn mark_accountant_grant_accepted(pool: &DbPool, grant_id: &str, accountant_user_id: &str) -> anyhow::Result<()> {
+    match &**pool {
+        DbPoolEnum::Oracle(p) => crate::db::oracle::accountant_grants::mark_grant_accepted(p, grant_id, accountant_user_id).await,
+    }
+}
@@ -0,0 +1,345 @@
+const BATCH_SYNC_SAVEPOINT: &str = "batch_sync_item";
+
+enum SyncItemOutcome {
+    Applied,
+    /// Applied, and the receipt object stored under this key is now unreferenced.
+    Deleted(String),
+    Rejected(&'static str),
+}
+
+/// Applies an offline batch in dependency order: profile, charity upserts, donations,
+/// receipts, then charity deletes (so donations moved or deleted in the same batch no
+/// longer pin the charity). Each item runs behind its own savepoint so one bad row only
+/// rolls back itself.
+pub async fn batch_sync(pool: &DbPool, user_id: &str, req: crate::db::models::BatchSyncRequest) -> anyhow::Result<crate::db::models::BatchSyncResult> {
+    let user_id = user_id.to_string();
+    let req = req.clone();
+    let mut result = crate::db::models::BatchSyncResult::default();
+
+    match &**pool {
+        DbPoolEnum::Oracle(pool_inner) => {
+            let key = crate::db::oracle::data_keys::for_write(pool_inner, &user_id).await?;
+            let conn = pool_inner.get().await?;
+
+            if let Some(profile) = req.profile {
+                conn.savepoint(BATCH_SYNC_SAVEPOINT).await?;
+                let outcome = apply_profile_sync_item(&conn, &key, &user_id, &profile).await;
+                finish_sync_item(&conn, &mut result, outcome, "users", &user_id, "update").await?;
+            }
+
+            let (charity_deletes, charity_upserts): (Vec<_>, Vec<_>) =
+                req.charities.into_iter().partition(|charity| charity.action == "delete");
+
+            for charity in charity_upserts {
+                conn.savepoint(BATCH_SYNC_SAVEPOINT).await?;
+                let outcome = apply_charity_sync_item(&conn, &key, &user_id, &charity).await;
+                finish_sync_item(&conn, &mut result, outcome, "charities", &charity.id, &charity.action).await?;
+            }
+
+            for donation in req.donations {
+                conn.savepoint(BATCH_SYNC_SAVEPOINT).await?;
+                let outcome = apply_donation_sync_item(&conn, &key, &user_id, &donation).await;
+                finish_sync_item(&conn, &mut result, outcome, "donations", &donation.id, &donation.action).await?;
+            }
+
+            for receipt in req.receipts {
+                conn.savepoint(BATCH_SYNC_SAVEPOINT).await?;
+                let outcome = apply_receipt_sync_item(&conn, &user_id, &receipt).await;
+                if let Ok(SyncItemOutcome::Deleted(key)) = &outcome {
+                    result.deleted_receipts.push((receipt.id.clone(), key.clone()));
+                }
+                finish_sync_item(&conn, &mut result, outcome, "receipts", &receipt.id, &receipt.action).await?;
+            }
+
+            for charity in charity_deletes {
+                conn.savepoint(BATCH_SYNC_SAVEPOINT).await?;
+                let outcome = delete_charity_sync_item(&conn, &user_id, &charity.id).await;
+                finish_sync_item(&conn, &mut result, outcome, "charities", &charity.id, &charity.action).await?;
+            }
+
+            conn.commit().await?;
+        }
+    }
+
+    Ok(result)
+}
+
+async fn finish_sync_item(
+    conn: &oracle_rs::Connection,
+    result: &mut crate::db::models::BatchSyncResult,
+    outcome: anyhow::Result<SyncItemOutcome>,
+    table: &str,
+    id: &str,
+    action: &str,
+) -> anyhow::Result<()> {
+    let (reason, retryable) = match outcome {
+        Ok(SyncItemOutcome::Applied) | Ok(SyncItemOutcome::Deleted(_)) => {
+            result.applied += 1;
+            let op = if action == "delete" { "delete" } else { "upsert" };
+            result.applied_changes.push((table.to_string(), id.to_string(), op.to_string()));
+            return Ok(());
+        }
+        Ok(SyncItemOutcome::Rejected(reason)) => (reason.to_string(), false),
+        Err(e) => {
+            tracing::warn!("Batch sync failed for {} {}: {}", table, id, e);
+            (format!("Failed to apply {} change", table), true)
+        }
+    };
+
+    conn.rollback_to_savepoint(BATCH_SYNC_SAVEPOINT).await?;
+    result.failures.push(crate::db::models::BatchSyncFailure {
+        table: table.to_string(),
+        id: id.to_string(),
+        action: action.to_string(),
+        reason,
+        retryable,
+    });
+    Ok(())
+}
+
+async fn apply_profile_sync_item(
+    conn: &oracle_rs::Connection,
+    key: &crate::db::oracle::data_keys::FieldKey,
+    user_id: &str,
+    profile: &crate::db::models::ProfileSyncItem,
+) -> anyhow::Result<SyncItemOutcome> {
+    let rows = conn
+        .query(
+            "SELECT name, filing_status, agi, marginal_tax_rate, itemize_deductions, is_encrypted, encrypted_payload, agi_ciphertext FROM users WHERE id = :1",
+            &crate::oracle_params![user_id.to_string()],
+        )
+        .await?;
+    let Some(existing) = rows.first() else {
+        return Ok(SyncItemOutcome::Rejected("User profile not found"));
+    };
+
+    let name = profile.name.clone().unwrap_or_else(|| crate::db::oracle::row_string(existing, 0));
+    let filing_status = profile.filing_status.clone().or_else(|| crate::db::oracle::row_opt_string(existing, 1));
+    let agi = match profile.agi {
+        Some(agi) => Some(agi),
+        None => key.open_agi(crate::db::oracle::row_f64(existing, 2), crate::db::oracle::row_opt_string(existing, 7))?,
+    };
+    let (agi, agi_ciphertext) = key.seal_agi(agi)?;
+    let marginal_tax_rate = profile.marginal_tax_rate.or_else(|| crate::db::oracle::row_f64(existing, 3));
+    let itemize_deductions = profile
+        .itemize_deductions
+        .or_else(|| crate::db::oracle::row_bool(existing, 4))
+        .map(|v| if v { 1 } else { 0 });
+    let is_encrypted = profile
+        .is_encrypted
+        .or_else(|| crate::db::oracle::row_bool(existing, 5))
+        .map(|v| if v { 1 } else { 0 });
+    let encrypted_payload = profile.encrypted_payload.clone().or_else(|| crate::db::oracle::row_opt_string(existing, 6));
+
+    let sql = "UPDATE users SET name = :1, filing_status = :2, agi = :3, marginal_tax_rate = :4, itemize_deductions = :5, is_encrypted = :6, encrypted_payload = :7, agi_ciphertext = :8, updated_at = CURRENT_TIMESTAMP WHERE id = :9";
+    conn.execute(
+        sql,
+        &crate::oracle_params![
+            name,
+            filing_status,
+            agi,
+            marginal_tax_rate,
+            itemize_deductions,
+            is_encrypted,
+            encrypted_payload,
+            agi_ciphertext,
+            user_id.to_string(),
+        ],
+    )
+    .await?;
+    crate::db::oracle::sync_changes::record_change(conn, user_id, "users", user_id, "upsert").await?;
+    Ok(SyncItemOutcome::Applied)
+}
+
+async fn apply_charity_sync_item(
+    conn: &oracle_rs::Connection,
+    key: &crate::db::oracle::data_keys::FieldKey,
+    user_id: &str,
+    charity: &crate::db::models::CharitySyncItem,
+) -> anyhow::Result<SyncItemOutcome> {
+    let now = chrono::Utc::now();
+    let name = charity
+        .name
+        .as_deref()
+        .map(|value| value.trim().chars().take(255).collect::<String>())
+        .unwrap_or_default();
+    let incoming_updated_at = charity.updated_at.unwrap_or(now).to_rfc3339();
+    let created_at = now.to_rfc3339();
+    let is_encrypted = charity.is_encrypted.map(|v| if v { 1 } else { 0 });
+    let sql = "MERGE INTO charities c USING (SELECT :1 AS id, :2 AS user_id, :3 AS name, :4 AS ein, :5 AS category, :6 AS status, :7 AS classification, :8 AS nonprofit_type, :9 AS deductibility, :10 AS street, :11 AS city, :12 AS state, :13 AS zip, :14 AS is_encrypted, :15 AS encrypted_payload, TO_TIMESTAMP_TZ(:16, 'YYYY-MM-DD\"T\"HH24:MI:SS.FF TZH:TZM') AS incoming_updated_at, TO_TIMESTAMP_TZ(:17, 'YYYY-MM-DD\"T\"HH24:MI:SS.FF TZH:TZM') AS incoming_created_at FROM dual) s ON (c.id = s.id AND c.user_id = s.user_id) WHEN MATCHED THEN UPDATE SET c.name = s.name, c.ein = s.ein, c.category = s.category, c.status = s.status, c.classification = s.classification, c.nonprofit_type = s.nonprofit_type, c.deductibility = s.deductibility, c.street = s.street, c.city = s.city, c.state = s.state, c.zip = s.zip, c.is_encrypted = s.is_encrypted, c.encrypted_payload = s.encrypted_payload, c.updated_at = s.incoming_updated_at WHERE c.updated_at IS NULL OR c.updated_at <= s.incoming_updated_at WHEN NOT MATCHED THEN INSERT (id, user_id, name, ein, category, status, classification, nonprofit_type, deductibility, street, city, state, zip, is_encrypted, encrypted_payload, created_at, updated_at) VALUES (s.id, s.user_id, s.name, s.ein, s.category, s.status, s.classification, s.nonprofit_type, s.deductibility, s.street, s.city, s.state, s.zip, s.is_encrypted, s.encrypted_payload, s.incoming_created_at, s.incoming_updated_at)";
+    conn.execute(
+        sql,
+        &crate::oracle_params![
+            charity.id.clone(),
+            user_id.to_string(),
+            name,
+            charity.ein.clone(),
+            charity.category.clone(),
+            charity.status.clone(),
+            charity.classification.clone(),
+            charity.nonprofit_type.clone(),
+            charity.deductibility.clone(),
+            key.seal(field_crypto::CHARITY_STREET, charity.street.clone())?,
+            key.seal(field_crypto::CHARITY_CITY, charity.city.clone())?,
+            key.seal(field_crypto::CHARITY_STATE, charity.state.clone())?,
+            key.seal(field_crypto::CHARITY_ZIP, charity.zip.clone())?,
+            is_encrypted,
+            charity.encrypted_payload.clone(),
+            incoming_updated_at,
+            created_at,
+        ],
+    )
+    .await?;
+    crate::db::oracle::sync_changes::record_change(conn, user_id, "charities", &charity.id, "upsert").await?;
+    Ok(SyncItemOutcome::Applied)
+}
+
+async fn delete_charity_sync_item(
+    conn: &oracle_rs::Connection,
+    user_id: &str,
+    charity_id: &str,
+) -> anyhow::Result<SyncItemOutcome> {
+    let active_donations = conn
+        .query(
+            "SELECT 1 FROM donations WHERE user_id = :1 AND charity_id = :2 AND deleted = 0 FETCH FIRST 1 ROWS ONLY",
+            &crate::oracle_params![user_id.to_string(), charity_id.to_string()],
+        )
+        .await?;
+    if active_donations.first().is_some() {
+        return Ok(SyncItemOutcome::Rejected("Charity still has active donations"));
+    }
+
+    crate::db::oracle::sync_changes::record_charity_receipt_deletes(conn, user_id, charity_id).await?;
+    conn.execute(
+        "DELETE FROM receipts WHERE donation_id IN (SELECT id FROM donations WHERE charity_id = :1 AND user_id = :2 AND deleted = 1)",
+        &crate::oracle_params![charity_id.to_string(), user_id.to_string()],
+    )
+    .await?;
+    conn.execute(
+        "DELETE FROM donations WHERE charity_id = :1 AND user_id = :2 AND deleted = 1",
+        &crate::oracle_params![charity_id.to_string(), user_id.to_string()],
+    )
+    .await?;
+    conn.execute(
+        "DELETE FROM charities WHERE id = :1 AND user_id = :2",
+        &crate::oracle_params![charity_id.to_string(), user_id.to_string()],
+    )
+    .await?;
+    crate::db::oracle::sync_changes::record_change(conn, user_id, "charities", charity_id, "delete").await?;
+    Ok(SyncItemOutcome::Applied)
+}
+
+async fn apply_donation_sync_item(
+    conn: &oracle_rs::Connection,
+    key: &crate::db::oracle::data_keys::FieldKey,
+    user_id: &str,
+    donation: &crate::db::models::DonationSyncItem,
+) -> anyhow::Result<SyncItemOutcome> {
+    if donation.action == "delete" {
+        let sql = "UPDATE donations SET deleted = 1, updated_at = CURRENT_TIMESTAMP WHERE id = :1 AND user_id = :2";
+        conn.execute(sql, &crate::oracle_params![donation.id.clone(), user_id.to_string()]).await?;
+        crate::db::oracle::sync_changes::record_change(conn, user_id, "donations", &donation.id, "delete").await?;
+        crate::db::oracle::sync_changes::record_donation_receipt_deletes(conn, user_id, &donation.id).await?;
+        return Ok(SyncItemOutcome::Applied);
+    }
+
+    let now = chrono::Utc::now();
+    let donation_date = donation.date.unwrap_or_else(|| now.date_naive());
+    let donation_year = donation.year.unwrap_or(donation_date.year());
+    let incoming_updated_at = donation.updated_at.unwrap_or(now).to_rfc3339();
+    let created_at = now.to_rfc3339();
+    let is_encrypted = donation.is_encrypted.map(|v| if v { 1 } else { 0 });
+    let sql = "MERGE INTO donations d USING (SELECT :1 AS id, :2 AS user_id, TO_DATE(:3, 'YYYY-MM-DD') AS donation_date, :4 AS donation_year, :5 AS donation_category, :6 AS donation_amount, :7 AS charity_id, :8 AS notes, :9 AS is_encrypted, :10 AS encrypted_payload, TO_TIMESTAMP_TZ(:11, 'YYYY-MM-DD\"T\"HH24:MI:SS.FF TZH:TZM') AS incoming_updated_at, TO_TIMESTAMP_TZ(:12, 'YYYY-MM-DD\"T\"HH24:MI:SS.FF TZH:TZM') AS incoming_created_at FROM dual) s ON (d.id = s.id AND d.user_id = s.user_id) WHEN MATCHED THEN UPDATE SET d.donation_date = s.donation_date, d.donation_year = s.donation_year, d.donation_category = s.donation_category, d.donation_amount = s.donation_amount, d.charity_id = s.charity_id, d.notes = s.notes, d.is_encrypted = s.is_encrypted, d.encrypted_payload = s.encrypted_payload, d.updated_at = s.incoming_updated_at, d.deleted = 0 WHERE d.updated_at IS NULL OR d.updated_at <= s.incoming_updated_at OR s.incoming_updated_at IS NULL WHEN NOT MATCHED THEN INSERT (id, user_id, donation_date, donation_year, donation_category, donation_amount, charity_id, notes, is_encrypted, encrypted_payload, created_at, updated_at, deleted) VALUES (s.id, s.user_id, s.donation_date, s.donation_year, s.donation_category, s.donation_amount, s.charity_id, s.notes, s.is_encrypted, s.encrypted_payload, s.incoming_created_at, s.incoming_updated_at, 0)";
+    let merged = conn.execute(
+        sql,
+        &crate::oracle_params![
+            donation.id.clone(),
+            user_id.to_string(),
+            donation_date.format("%Y-%m-%d").to_string(),
+            donation_year,
+            donation.category.clone(),
+            donation.amount,
+            donation.charity_id.clone(),
+            key.seal(field_crypto::DONATION_NOTES, donation.notes.clone())?,
+            is_encrypted,
+            donation.encrypted_payload.clone(),
+            incoming_updated_at,
+            created_at,
+        ],
+    )
+    .await?;
+    // A stale update leaves the row, and so its tokens, as they were.
+    if let Some(tokens) = donation.blind_index.as_ref().filter(|_| merged.rows_affected > 0) {
+        crate::db::oracle::blind_index::replace_donation_tokens(conn, user_id, &donation.id, tokens).await?;
+    }
+    crate::db::oracle::sync_changes::record_change(conn, user_id, "donations", &donation.id, "upsert").await?;
+    Ok(SyncItemOutcome::Applied)
+}
+
+async fn apply_receipt_sync_item(
+    conn: &oracle_rs::Connection,
+    user_id: &str,
+    receipt: &crate::db::models::ReceiptSyncItem,
+) -> anyhow::Result<SyncItemOutcome> {
+    match receipt.action.as_str() {
+        "create" => {}
+        "update" => {
+            let patch = crate::db::models::ReceiptPatch {
+                receipt_id: receipt.id.clone(),
+                user_id: user_id.to_string(),
+                donation_id: Some(receipt.donation_id.trim().to_string()).filter(|value| !value.is_empty()),
+                file_name: receipt.file_name.clone(),
+                is_encrypted: receipt.is_encrypted,
+                encrypted_payload: receipt.encrypted_payload.clone(),
+            };
+            return Ok(match update_receipt_with_conn(conn, &patch).await? {
+                Some(_) => SyncItemOutcome::Applied,
+                None => SyncItemOutcome::Rejected("Receipt or target donation not found"),
+            });
+        }
+        "delete" => {
+            return Ok(match delete_receipt_with_conn(conn, user_id, &receipt.id).await? {
+                Some((key, _)) => SyncItemOutcome::Deleted(key),
+                // Already gone: deleting twice is not an error for an offline client.
+                None => SyncItemOutcome::Applied,
+            });
+        }
+        _ => return Ok(SyncItemOutcome::Rejected("Invalid receipt sync action")),
+    }
+
+    let owner = conn
+        .query(
+            "SELECT user_id FROM donations WHERE id = :1",
+            &crate::oracle_params![receipt.donation_id.clone()],
+        )
+        .await?;
+    match owner.first().map(|row| crate::db::oracle::row_string(row, 0)) {
+        Some(owner) if owner == user_id => {}
+        Some(_) => return Ok(SyncItemOutcome::Rejected("Donation not found for user")),
+        // The donation may still be on its way in a later batch, so this one can be retried.
+        None => anyhow::bail!("donation {} does not exist", receipt.donation_id),
+    }
+
+    let is_encrypted = receipt.is_encrypted.map(|v| if v { 1 } else { 0 });
+    let sql = "MERGE INTO receipts r USING (SELECT :1 AS id, :2 AS donation_id, :3 AS receipt_key, :4 AS file_name, :5 AS content_type, :6 AS receipt_size, :7 AS is_encrypted, :8 AS encrypted_payload, TO_TIMESTAMP_TZ(:9, 'YYYY-MM-DD\"T\"HH24:MI:SS.FF TZH:TZM') AS created_at FROM dual) s ON (r.id = s.id) WHEN NOT MATCHED THEN INSERT (id, donation_id, receipt_key, file_name, content_type, receipt_size, is_encrypted, encrypted_payload, created_at) VALUES (s.id, s.donation_id, s.receipt_key, s.file_name, s.content_type, s.receipt_size, s.is_encrypted, s.encrypted_payload, s.created_at)";
+    let merged = conn.execute(
+        sql,
+        &crate::oracle_params![
+            receipt.id.clone(),
+            receipt.donation_id.clone(),
+            receipt.key.clone(),
+            receipt.file_name.clone(),
+            receipt.content_type.clone(),
+            receipt.size,
+            is_encrypted,
+            receipt.encrypted_payload.clone(),
+            chrono::Utc::now().to_rfc3339(),
+        ],
+    )
+    .await?;
+    // An id that already exists is a replayed create (or someone else's row); nothing changed.
+    if merged.rows_affected > 0 {
+        crate::db::oracle::sync_changes::record_receipt_change(conn, &receipt.id, "upsert").await?;
+    }
+    Ok(SyncItemOutcome::Applied)
+}
@@ -0,0 +1,6 @@
+/// Returns the user's changes after `after_seq` (exclusive), oldest first, with tombstones for deletions.
+pub async fn list_sync_changes(pool: &DbPool, user_id: &str, after_seq: i64, limit: usize) -> anyhow::Result<crate::db::models::SyncChangePage> {
+    match &**pool {
+        DbPoolEnum::Oracle(p) => crate::db::oracle::sync_changes::list_changes(p, user_id, after_seq, limit).await,
+    }
+}
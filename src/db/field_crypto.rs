@@ -0,0 +1,147 @@
+// Server-side encryption of individual columns.
+//
+// A master key from `DATA_ENCRYPTION_KEY` wraps one random data key per user;
+// the data key seals the user's sensitive column values. A sealed value is
+// `enc:v1:<base64 iv||ciphertext>` and names its column as AAD, so a value
+// copied into another column fails to open. Values without the prefix were
+// written before encryption was enabled and are returned as they are.
+
+use aes_gcm::aead::{Aead, AeadCore, KeyInit, OsRng, Payload};
+use aes_gcm::{Aes256Gcm, Key, Nonce};
+use anyhow::{anyhow, bail};
+use base64::prelude::{Engine as _, BASE64_STANDARD};
+use sha2::{Digest, Sha256};
+
+pub(crate) const MASTER_KEY_ENV: &str = "DATA_ENCRYPTION_KEY";
+
+/// Prefix of every sealed column value.
+pub(crate) const SEALED_PREFIX: &str = "enc:v1:";
+
+pub(crate) const DONATION_NOTES: &str = "donations.notes";
+pub(crate) const USER_AGI: &str = "users.agi";
+pub(crate) const CHARITY_STREET: &str = "charities.street";
+pub(crate) const CHARITY_CITY: &str = "charities.city";
+pub(crate) const CHARITY_STATE: &str = "charities.state";
+pub(crate) const CHARITY_ZIP: &str = "charities.zip";
+
+const NONCE_LEN: usize = 12;
+
+/// The server master key. Its id, the first 8 bytes of its SHA-256 as hex, is
+/// stored beside every data key it wraps.
+pub(crate) struct MasterKey {
+    cipher: Aes256Gcm,
+    id: String,
+}
+
+/// A user's data key, unwrapped.
+pub(crate) struct DataKey {
+    cipher: Aes256Gcm,
+}
+
+fn seal(cipher: &Aes256Gcm, plaintext: &[u8], aad: &[u8]) -> anyhow::Result<Vec<u8>> {
+    let nonce = Aes256Gcm::generate_nonce(&mut OsRng);
+    let ciphertext = cipher
+        .encrypt(&nonce, Payload { msg: plaintext, aad })
+        .map_err(|e| anyhow!("Encryption error: {}", e))?;
+    let mut sealed = Vec::with_capacity(NONCE_LEN + ciphertext.len());
+    sealed.extend_from_slice(&nonce);
+    sealed.extend_from_slice(&ciphertext);
+    Ok(sealed)
+}
+
+fn open(cipher: &Aes256Gcm, sealed: &[u8], aad: &[u8]) -> anyhow::Result<Vec<u8>> {
+    if sealed.len() < NONCE_LEN {
+        bail!("Sealed value too short");
+    }
+    let (iv, ciphertext) = sealed.split_at(NONCE_LEN);
+    cipher
+        .decrypt(Nonce::from_slice(iv), Payload { msg: ciphertext, aad })
+        .map_err(|e| anyhow!("Decryption error: {}", e))
+}
+
+fn wrap_aad(user_id: &str) -> String {
+    format!("user_data_keys:{user_id}")
+}
+
+impl MasterKey {
+    pub(crate) fn from_base64(encoded: &str) -> anyhow::Result<Self> {
+        let bytes = BASE64_STANDARD
+            .decode(encoded.trim())
+            .map_err(|e| anyhow!("{} is not valid base64: {}", MASTER_KEY_ENV, e))?;
+        if bytes.len() != 32 {
+            bail!("{} must be 32 bytes, got {}", MASTER_KEY_ENV, bytes.len());
+        }
+        let digest = Sha256::digest(&bytes);
+        Ok(Self {
+            cipher: Aes256Gcm::new(Key::<Aes256Gcm>::from_slice(&bytes)),
+            id: digest[..8].iter().map(|b| format!("{:02x}", b)).collect(),
+        })
+    }
+
+    /// The configured master key, or None when server-side encryption is off.
+    pub(crate) fn from_env() -> anyhow::Result<Option<Self>> {
+        match std::env::var(MASTER_KEY_ENV) {
+            Ok(value) if !value.trim().is_empty() => Self::from_base64(&value).map(Some),
+            _ => Ok(None),
+        }
+    }
+
+    pub(crate) fn id(&self) -> &str {
+        &self.id
+    }
+
+    /// A fresh data key for `user_id` and its wrapped form for storage.
+    pub(crate) fn generate_data_key(&self, user_id: &str) -> anyhow::Result<(DataKey, String)> {
+        let key = Aes256Gcm::generate_key(&mut OsRng);
+        let wrapped = seal(&self.cipher, &key, wrap_aad(user_id).as_bytes())?;
+        Ok((DataKey { cipher: Aes256Gcm::new(&key) }, BASE64_STANDARD.encode(wrapped)))
+    }
+
+    pub(crate) fn unwrap_data_key(
+        &self,
+        user_id: &str,
+        wrapped: &str,
+        master_key_id: &str,
+    ) -> anyhow::Result<DataKey> {
+        if master_key_id != self.id {
+            bail!(
+                "Data key for user {} is wrapped by master key {}, not {}",
+                user_id,
+                master_key_id,
+                self.id
+            );
+        }
+        let sealed = BASE64_STANDARD
+            .decode(wrapped)
+            .map_err(|e| anyhow!("Failed to decode wrapped data key: {}", e))?;
+        let key = open(&self.cipher, &sealed, wrap_aad(user_id).as_bytes())?;
+        if key.len() != 32 {
+            bail!("Wrapped data key has the wrong length");
+        }
+        Ok(DataKey { cipher: Aes256Gcm::new(Key::<Aes256Gcm>::from_slice(&key)) })
+    }
+}
+
+pub(crate) fn is_sealed(value: &str) -> bool {
+    value.starts_with(SEALED_PREFIX)
+}
+
+impl DataKey {
+    pub(crate) fn seal(&self, column: &str, plaintext: &str) -> anyhow::Result<String> {
+        let sealed = seal(&self.cipher, plaintext.as_bytes(), column.as_bytes())?;
+        Ok(format!("{SEALED_PREFIX}{}", BASE64_STANDARD.encode(sealed)))
+    }
+
+    /// Opens a sealed value; anything without the prefix is legacy plaintext.
+    pub(crate) fn open(&self, column: &str, value: &str) -> anyhow::Result<String> {
+        let Some(body) = value.strip_prefix(SEALED_PREFIX) else {
+            return Ok(value.to_string());
+        };
+        let sealed = BASE64_STANDARD
+            .decode(body)
+            .map_err(|e| anyhow!("Failed to decode sealed {}: {}", column, e))?;
+        let plaintext = open(&self.cipher, &sealed, column.as_bytes())
+            .map_err(|e| anyhow!("Failed to open sealed {}: {}", column, e))?;
+        String::from_utf8(plaintext).map_err(|_| anyhow!("Sealed {} is not UTF-8", column))
+    }
+}
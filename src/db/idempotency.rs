@@ -0,0 +1,25 @@
+use crate::db::models::{IdempotencyClaim, StoredResponse};
+use crate::db::DbPool;
+
+pub async fn claim_key(
+    pool: &DbPool,
+    user_id: &str,
+    key: &str,
+    request_hash: &str,
+    ttl_secs: i64,
+) -> anyhow::Result<IdempotencyClaim> {
+    super::claim_idempotency_key(pool, user_id, key, request_hash, ttl_secs).await
+}
+
+pub async fn store_response(
+    pool: &DbPool,
+    user_id: &str,
+    key: &str,
+    response: &StoredResponse,
+) -> anyhow::Result<()> {
+    super::store_idempotent_response(pool, user_id, key, response).await
+}
+
+pub async fn release_key(pool: &DbPool, user_id: &str, key: &str) -> anyhow::Result<()> {
+    super::release_idempotency_key(pool, user_id, key).await
+}
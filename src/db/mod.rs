@@ -1,12 +1,24 @@
 pub mod core;
+pub(crate) mod field_crypto;
 pub mod models;
 pub mod oracle;
 
+pub mod access_tokens;
+pub mod accountant_grants;
+pub mod account_security;
 pub mod audit;
+pub mod blind_index;
 pub mod charities;
 pub mod donations;
+pub mod idempotency;
+pub mod passkeys;
+pub mod receipt_uploads;
 pub mod receipts;
+pub mod refresh_tokens;
+pub mod revocations;
+pub mod sessions;
 pub mod users;
 pub mod valuations;
+pub mod vault_rotations;
 
 pub use core::*;
@@ -28,6 +28,7 @@ pub struct NewDonation {
     pub notes: Option<String>,
     pub is_encrypted: Option<bool>,
     pub encrypted_payload: Option<String>,
+    pub blind_index: Option<BlindIndexTokens>,
     pub created_at: DateTime<Utc>,
 }
 
@@ -43,9 +44,32 @@ pub struct DonationPatch {
     pub notes: Option<String>,
     pub is_encrypted: Option<bool>,
     pub encrypted_payload: Option<String>,
+    pub blind_index: Option<BlindIndexTokens>,
     pub incoming_updated_at: Option<DateTime<Utc>>,
 }
 
+/// HMAC tokens a client derives from an encrypted donation's plaintext with a
+/// key the server never sees. Equal plaintext gives equal tokens, so the server
+/// can match them without learning the values. Sent with a donation, the set
+/// replaces every token stored for it; as a query, set fields must all match.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[cfg_attr(feature = "server", derive(utoipa::ToSchema))]
+pub struct BlindIndexTokens {
+    pub charity: Option<String>,
+    pub year: Option<String>,
+    pub category: Option<String>,
+    /// Token over the fields that make two donations the same gift.
+    pub dedupe: Option<String>,
+}
+
+/// Live donations sharing one dedupe token.
+#[derive(Debug, Clone, Serialize)]
+#[cfg_attr(feature = "server", derive(utoipa::ToSchema))]
+pub struct BlindIndexDuplicateGroup {
+    pub token: String,
+    pub donation_ids: Vec<String>,
+}
+
 #[derive(Debug, Clone)]
 pub struct NewReceipt {
     pub id: String,
@@ -56,9 +80,20 @@ pub struct NewReceipt {
     pub size: Option<i64>,
     pub is_encrypted: Option<bool>,
     pub encrypted_payload: Option<String>,
+    pub content_hash: Option<String>,
     pub created_at: DateTime<Utc>,
 }
 
+#[derive(Debug, Clone)]
+pub struct ReceiptPatch {
+    pub receipt_id: String,
+    pub user_id: String,
+    pub donation_id: Option<String>,
+    pub file_name: Option<String>,
+    pub is_encrypted: Option<bool>,
+    pub encrypted_payload: Option<String>,
+}
+
 #[derive(Debug, Clone)]
 pub struct NewCharity {
     pub id: String,
@@ -111,12 +146,54 @@ pub struct RevisionLogEntry {
 }
 
 #[derive(Debug, Clone, Deserialize)]
+#[cfg_attr(feature = "server", derive(utoipa::ToSchema))]
 pub struct BatchSyncRequest {
+    #[serde(default)]
+    pub profile: Option<ProfileSyncItem>,
+    #[serde(default)]
+    pub charities: Vec<CharitySyncItem>,
     pub donations: Vec<DonationSyncItem>,
     pub receipts: Vec<ReceiptSyncItem>,
 }
 
+#[derive(Debug, Clone, Default, Deserialize)]
+#[cfg_attr(feature = "server", derive(utoipa::ToSchema))]
+/// Profile fields a batch sync may change. Identity fields are left out: the
+/// email comes from sign-in and the vault credential has its own gated flow,
+/// so `email` or `vault_credential_id` sent here are ignored.
+pub struct ProfileSyncItem {
+    pub name: Option<String>,
+    pub filing_status: Option<String>,
+    pub agi: Option<f64>,
+    pub marginal_tax_rate: Option<f64>,
+    pub itemize_deductions: Option<bool>,
+    pub is_encrypted: Option<bool>,
+    pub encrypted_payload: Option<String>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[cfg_attr(feature = "server", derive(utoipa::ToSchema))]
+pub struct CharitySyncItem {
+    pub action: String, // "create", "update", "delete"
+    pub id: String,
+    pub name: Option<String>,
+    pub ein: Option<String>,
+    pub category: Option<String>,
+    pub status: Option<String>,
+    pub classification: Option<String>,
+    pub nonprofit_type: Option<String>,
+    pub deductibility: Option<String>,
+    pub street: Option<String>,
+    pub city: Option<String>,
+    pub state: Option<String>,
+    pub zip: Option<String>,
+    pub is_encrypted: Option<bool>,
+    pub encrypted_payload: Option<String>,
+    pub updated_at: Option<chrono::DateTime<chrono::Utc>>,
+}
+
 #[derive(Debug, Clone, Deserialize)]
+#[cfg_attr(feature = "server", derive(utoipa::ToSchema))]
 pub struct DonationSyncItem {
     pub action: String, // "create", "update", "delete"
     pub id: String,
@@ -128,14 +205,18 @@ pub struct DonationSyncItem {
     pub notes: Option<String>,
     pub is_encrypted: Option<bool>,
     pub encrypted_payload: Option<String>,
+    pub blind_index: Option<BlindIndexTokens>,
     pub updated_at: Option<chrono::DateTime<chrono::Utc>>,
 }
 
 #[derive(Debug, Clone, Deserialize)]
+#[cfg_attr(feature = "server", derive(utoipa::ToSchema))]
 pub struct ReceiptSyncItem {
-    pub action: String, // "create"
+    pub action: String, // "create", "update", "delete"
     pub id: String,
+    #[serde(default)]
     pub donation_id: String,
+    #[serde(default)]
     pub key: String,
     pub file_name: Option<String>,
     pub content_type: Option<String>,
@@ -144,7 +225,285 @@ pub struct ReceiptSyncItem {
     pub encrypted_payload: Option<String>,
 }
 
+#[derive(Serialize, Debug, Clone)]
+#[cfg_attr(feature = "server", derive(utoipa::ToSchema))]
+pub struct BatchSyncFailure {
+    pub table: String, // "users", "charities", "donations", "receipts"
+    pub id: String,
+    pub action: String,
+    pub reason: String,
+    pub retryable: bool,
+}
+
+#[derive(Serialize, Debug, Clone, Default)]
+#[cfg_attr(feature = "server", derive(utoipa::ToSchema))]
+pub struct BatchSyncResult {
+    pub applied: usize,
+    pub failures: Vec<BatchSyncFailure>,
+    /// (receipt id, object key) pairs whose storage objects the caller still has to remove.
+    #[serde(skip)]
+    pub deleted_receipts: Vec<(String, String)>,
+    /// (table, id, op) for every applied item, for notifying the user's other devices.
+    #[serde(skip)]
+    pub applied_changes: Vec<(String, String, String)>,
+}
+
+#[derive(Debug, Clone)]
+pub struct NewAccessToken {
+    pub id: String,
+    pub user_id: String,
+    pub name: String,
+    pub scopes: Vec<String>,
+    pub token_hash: String,
+    pub expires_in_days: i64,
+}
+
+#[derive(Serialize, Debug, Clone)]
+#[cfg_attr(feature = "server", derive(utoipa::ToSchema))]
+pub struct AccessToken {
+    pub id: String,
+    pub name: String,
+    pub scopes: Vec<String>,
+    pub created_at: DateTime<Utc>,
+    pub expires_at: DateTime<Utc>,
+    pub last_used_at: Option<DateTime<Utc>>,
+    pub revoked_at: Option<DateTime<Utc>>,
+}
+
+/// An unrevoked, unexpired token with its owner, for authenticating a request.
+#[derive(Debug, Clone)]
+pub struct ActiveAccessToken {
+    pub user_id: String,
+    pub email: String,
+    pub name: String,
+    pub provider: String,
+    pub scopes: Vec<String>,
+    pub token_hash: String,
+}
+
+#[derive(Debug, Clone)]
+pub struct NewWebauthnChallenge {
+    pub id: String,
+    /// None for usernameless login, where the user is only known after the assertion.
+    pub user_id: Option<String>,
+    pub purpose: String,
+    /// base64url of the random challenge bytes.
+    pub challenge: String,
+    pub ttl_seconds: i64,
+}
+
+#[derive(Debug, Clone)]
+pub struct WebauthnChallenge {
+    pub user_id: Option<String>,
+    pub challenge: String,
+}
+
+#[derive(Debug, Clone)]
+pub struct NewPasskeyCredential {
+    pub id: String,
+    pub user_id: String,
+    pub name: String,
+    pub public_key: String,
+    pub algorithm: i64,
+    pub sign_count: i64,
+    pub transports: Vec<String>,
+}
+
+#[derive(Serialize, Debug, Clone)]
+#[cfg_attr(feature = "server", derive(utoipa::ToSchema))]
+pub struct PasskeyCredential {
+    /// base64url credential id; empty in listings for the passkey that unlocks a gated vault.
+    pub id: String,
+    pub name: String,
+    /// COSE algorithm: -7 (ES256) or -257 (RS256).
+    pub algorithm: i64,
+    pub transports: Vec<String>,
+    pub created_at: DateTime<Utc>,
+    pub last_used_at: Option<DateTime<Utc>>,
+}
+
+/// A passkey with the key material needed to verify an assertion.
+#[derive(Debug, Clone)]
+pub struct StoredPasskey {
+    pub id: String,
+    pub user_id: String,
+    pub public_key: String,
+    pub sign_count: i64,
+}
+
+/// What the store knows about a session JWT: whether its jti was revoked, the
+/// owner's "issued before this instant is invalid" watermark and account lock.
+#[derive(Debug, Clone, Default)]
+pub struct TokenRevocationStatus {
+    pub revoked: bool,
+    pub tokens_valid_after: Option<DateTime<Utc>>,
+    /// The owner's account is locked; no token is honoured until it is unlocked.
+    pub locked: bool,
+}
+
+#[derive(Debug, Clone)]
+pub struct NewAccountantGrant {
+    pub id: String,
+    pub owner_user_id: String,
+    /// Lower-cased; the accountant signs in with an account using this address.
+    pub accountant_email: String,
+    pub tax_years: Vec<i32>,
+    pub allow_agi: bool,
+}
+
+/// Read-only access the owner gave an accountant, as the owner sees it.
+#[derive(Serialize, Debug, Clone)]
+#[cfg_attr(feature = "server", derive(utoipa::ToSchema))]
+pub struct AccountantGrant {
+    pub id: String,
+    pub accountant_email: String,
+    pub tax_years: Vec<i32>,
+    pub allow_agi: bool,
+    pub created_at: DateTime<Utc>,
+    /// First time the accountant used the grant.
+    pub accepted_at: Option<DateTime<Utc>>,
+    pub revoked_at: Option<DateTime<Utc>>,
+}
+
+/// An active grant as the accountant sees it.
+#[derive(Serialize, Debug, Clone)]
+#[cfg_attr(feature = "server", derive(utoipa::ToSchema))]
+pub struct ClientGrant {
+    pub id: String,
+    #[serde(skip_serializing)]
+    pub owner_user_id: String,
+    pub owner_name: String,
+    pub owner_email: String,
+    pub tax_years: Vec<i32>,
+    pub allow_agi: bool,
+}
+
+/// A new sign-in. `jti` is the session id (the jti of its first access token);
+/// times are unix seconds, and `expires_at` follows its refresh tokens.
+#[derive(Debug, Clone)]
+pub struct NewUserSession {
+    pub jti: String,
+    pub user_id: String,
+    pub user_agent: Option<String>,
+    pub ip_address: Option<String>,
+    pub issued_at: i64,
+    pub expires_at: i64,
+}
+
+/// An active sign-in: not signed out and with a refresh token still usable.
+#[derive(Serialize, Debug, Clone)]
+#[cfg_attr(feature = "server", derive(utoipa::ToSchema))]
+pub struct UserSession {
+    pub id: String,
+    pub user_agent: Option<String>,
+    pub ip_address: Option<String>,
+    pub created_at: DateTime<Utc>,
+    pub last_seen_at: Option<DateTime<Utc>>,
+    pub expires_at: DateTime<Utc>,
+}
+
+/// A refresh token about to be stored; only its SHA-256 hash is kept. The
+/// family id is the session id of the sign-in it belongs to.
+#[derive(Debug, Clone)]
+pub struct NewRefreshToken {
+    pub token_hash: String,
+    pub family_id: String,
+    pub user_id: String,
+    /// Unix seconds.
+    pub expires_at: i64,
+}
+
+/// Outcome of presenting a refresh token.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RefreshRotation {
+    /// The token was live; it is now spent and its successor stored.
+    Rotated { user_id: String, family_id: String },
+    /// The token was already spent, so it has leaked; the family must be revoked.
+    Reused { user_id: String, family_id: String },
+    /// Unknown, expired or revoked.
+    Invalid,
+}
+
+#[derive(Debug, Clone)]
+pub struct NewVaultKeyRotation {
+    pub id: String,
+    pub user_id: String,
+    pub old_key_id: String,
+    pub new_key_id: String,
+    /// Passkey the new key is derived from; becomes the vault credential when the run completes.
+    pub new_credential_id: Option<String>,
+    pub total_items: i64,
+}
+
+/// Progress of re-encrypting a vault from one key to another.
+#[derive(Serialize, Debug, Clone)]
+#[cfg_attr(feature = "server", derive(utoipa::ToSchema))]
+pub struct VaultKeyRotation {
+    pub id: String,
+    pub old_key_id: String,
+    pub new_key_id: String,
+    pub new_credential_id: Option<String>,
+    /// `running`, `failed` or `completed`.
+    pub status: String,
+    /// Items still under the old key when the run started.
+    pub total_items: i64,
+    pub processed_items: i64,
+    /// Table and id of the item that could not be decrypted, when `failed`.
+    pub failed_table: Option<String>,
+    pub failed_record_id: Option<String>,
+    pub error: Option<String>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: Option<DateTime<Utc>>,
+    pub completed_at: Option<DateTime<Utc>>,
+}
+
+/// An encrypted row not yet sealed with the rotation's new key. `object_key` is
+/// set for encrypted receipts, whose stored object is re-encrypted too.
+#[derive(Debug, Clone)]
+pub struct VaultRotationItem {
+    pub table: String,
+    pub id: String,
+    pub payload: String,
+    pub object_key: Option<String>,
+}
+
+#[derive(Debug, Clone)]
+pub struct StoredResponse {
+    pub status_code: u16,
+    pub content_type: Option<String>,
+    pub body: String,
+}
+
+#[derive(Debug, Clone)]
+pub enum IdempotencyClaim {
+    /// The key is new (or expired); the caller runs the request and stores the response.
+    Claimed,
+    Replay(StoredResponse),
+    /// Another request with this key has not finished yet.
+    InProgress,
+    /// The key was already used for a request with a different fingerprint.
+    Mismatch,
+}
+
+#[derive(Serialize, Debug, Clone)]
+#[cfg_attr(feature = "server", derive(utoipa::ToSchema))]
+pub struct SyncChange {
+    pub seq: i64,
+    pub table: String, // "users", "charities", "donations", "receipts"
+    pub id: String,
+    pub op: String, // "upsert", "delete"
+    pub data: Option<serde_json::Value>,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct SyncChangePage {
+    pub changes: Vec<SyncChange>,
+    pub last_seq: Option<i64>,
+    pub has_more: bool,
+}
+
 #[derive(Serialize, Deserialize, Debug, Clone)]
+#[cfg_attr(feature = "server", derive(utoipa::ToSchema))]
 pub struct Donation {
     pub id: String,
     pub user_id: String,
@@ -165,6 +524,7 @@ pub struct Donation {
 }
 
 #[derive(Serialize, Deserialize, Debug, Clone)]
+#[cfg_attr(feature = "server", derive(utoipa::ToSchema))]
 pub struct Receipt {
     pub id: String,
     pub donation_id: String,
@@ -178,6 +538,23 @@ pub struct Receipt {
     pub ocr_status: Option<String>,
     pub is_encrypted: Option<bool>,
     pub encrypted_payload: Option<String>,
+    /// Hex SHA-256 of the stored object; absent for receipts confirmed before hashing.
+    pub content_hash: Option<String>,
+    pub created_at: DateTime<Utc>,
+}
+
+/// A multipart receipt upload that was started and not yet completed or aborted.
+#[derive(Debug, Clone)]
+pub struct ReceiptUpload {
+    pub id: String,
+    pub user_id: String,
+    pub key: String,
+    /// The storage backend's id for the upload.
+    pub upload_id: String,
+    pub content_type: Option<String>,
+    pub size: i64,
+    pub part_size: i64,
+    pub part_count: i64,
     pub created_at: DateTime<Utc>,
 }
 
@@ -0,0 +1,131 @@
+use deadpool_oracle::Pool;
+
+use crate::db::models::{AccessToken, ActiveAccessToken, NewAccessToken};
+
+// last_used_at only needs minute-level accuracy; skip the write for tokens used more recently.
+const LAST_USED_RESOLUTION_SECS: i64 = 60;
+
+fn split_scopes(value: String) -> Vec<String> {
+    value
+        .split(',')
+        .filter(|scope| !scope.is_empty())
+        .map(str::to_string)
+        .collect()
+}
+
+pub(crate) async fn create_token(pool: &Pool, input: &NewAccessToken) -> anyhow::Result<AccessToken> {
+    let conn = pool.get().await?;
+    conn.execute(
+        "INSERT INTO personal_access_tokens (id, user_id, name, scopes, token_hash, created_at, expires_at) VALUES (:1, :2, :3, :4, :5, SYSTIMESTAMP, SYSTIMESTAMP + NUMTODSINTERVAL(:6, 'DAY'))",
+        &crate::oracle_params![
+            input.id.clone(),
+            input.user_id.clone(),
+            input.name.clone(),
+            input.scopes.join(","),
+            input.token_hash.clone(),
+            input.expires_in_days,
+        ],
+    )
+    .await?;
+    conn.commit().await?;
+
+    let rows = conn
+        .query(
+            "SELECT id, name, scopes, created_at, expires_at, last_used_at, revoked_at FROM personal_access_tokens WHERE id = :1",
+            &crate::oracle_params![input.id.clone()],
+        )
+        .await?;
+    let row = rows
+        .first()
+        .ok_or_else(|| anyhow::anyhow!("access token {} missing after insert", input.id))?;
+    Ok(AccessToken {
+        id: crate::db::oracle::row_string(row, 0),
+        name: crate::db::oracle::row_string(row, 1),
+        scopes: split_scopes(crate::db::oracle::row_string(row, 2)),
+        created_at: crate::db::oracle::row_datetime_utc(row, 3).unwrap_or_else(chrono::Utc::now),
+        expires_at: crate::db::oracle::row_datetime_utc(row, 4).unwrap_or_else(chrono::Utc::now),
+        last_used_at: crate::db::oracle::row_datetime_utc(row, 5),
+        revoked_at: crate::db::oracle::row_datetime_utc(row, 6),
+    })
+}
+
+pub(crate) async fn list_tokens(pool: &Pool, user_id: &str) -> anyhow::Result<Vec<AccessToken>> {
+    let conn = pool.get().await?;
+    let rows = conn
+        .query(
+            "SELECT id, name, scopes, created_at, expires_at, last_used_at, revoked_at FROM personal_access_tokens WHERE user_id = :1 ORDER BY created_at DESC",
+            &crate::oracle_params![user_id.to_string()],
+        )
+        .await?;
+
+    Ok(rows
+        .rows
+        .iter()
+        .map(|row| AccessToken {
+            id: crate::db::oracle::row_string(row, 0),
+            name: crate::db::oracle::row_string(row, 1),
+            scopes: split_scopes(crate::db::oracle::row_string(row, 2)),
+            created_at: crate::db::oracle::row_datetime_utc(row, 3).unwrap_or_else(chrono::Utc::now),
+            expires_at: crate::db::oracle::row_datetime_utc(row, 4).unwrap_or_else(chrono::Utc::now),
+            last_used_at: crate::db::oracle::row_datetime_utc(row, 5),
+            revoked_at: crate::db::oracle::row_datetime_utc(row, 6),
+        })
+        .collect())
+}
+
+pub(crate) async fn revoke_token(pool: &Pool, user_id: &str, token_id: &str) -> anyhow::Result<bool> {
+    let conn = pool.get().await?;
+    let result = conn
+        .execute(
+            "UPDATE personal_access_tokens SET revoked_at = SYSTIMESTAMP WHERE id = :1 AND user_id = :2 AND revoked_at IS NULL",
+            &crate::oracle_params![token_id.to_string(), user_id.to_string()],
+        )
+        .await?;
+    conn.commit().await?;
+    Ok(result.rows_affected > 0)
+}
+
+pub(crate) async fn revoke_all_tokens(pool: &Pool, user_id: &str) -> anyhow::Result<u64> {
+    let conn = pool.get().await?;
+    let result = conn
+        .execute(
+            "UPDATE personal_access_tokens SET revoked_at = SYSTIMESTAMP WHERE user_id = :1 AND revoked_at IS NULL",
+            &crate::oracle_params![user_id.to_string()],
+        )
+        .await?;
+    conn.commit().await?;
+    Ok(result.rows_affected)
+}
+
+pub(crate) async fn find_active_token(pool: &Pool, token_id: &str) -> anyhow::Result<Option<ActiveAccessToken>> {
+    let conn = pool.get().await?;
+    let rows = conn
+        .query(
+            "SELECT t.user_id, u.email, u.name, u.provider, t.scopes, t.token_hash FROM personal_access_tokens t JOIN users u ON u.id = t.user_id WHERE t.id = :1 AND t.revoked_at IS NULL AND t.expires_at > SYSTIMESTAMP AND u.locked_at IS NULL",
+            &crate::oracle_params![token_id.to_string()],
+        )
+        .await?;
+    let Some(row) = rows.first() else {
+        return Ok(None);
+    };
+
+    Ok(Some(ActiveAccessToken {
+        user_id: crate::db::oracle::row_string(row, 0),
+        email: crate::db::oracle::row_string(row, 1),
+        name: crate::db::oracle::row_string(row, 2),
+        provider: crate::db::oracle::row_string(row, 3),
+        scopes: split_scopes(crate::db::oracle::row_string(row, 4)),
+        token_hash: crate::db::oracle::row_string(row, 5),
+    }))
+}
+
+pub(crate) async fn touch_token(pool: &Pool, token_id: &str) -> anyhow::Result<()> {
+    let conn = pool.get().await?;
+    conn.execute(
+        "UPDATE personal_access_tokens SET last_used_at = SYSTIMESTAMP WHERE id = :1 AND (last_used_at IS NULL OR last_used_at < SYSTIMESTAMP - NUMTODSINTERVAL(:2, 'SECOND'))",
+        &crate::oracle_params![token_id.to_string(), LAST_USED_RESOLUTION_SECS],
+    )
+    .await?;
+    conn.commit().await?;
+    Ok(())
+}
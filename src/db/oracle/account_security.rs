@@ -0,0 +1,116 @@
+use deadpool_oracle::Pool;
+
+/// Remembers which provider account signed in as `user_id`. Users are matched
+/// by email at login, so the provider `sub` and our user id can differ.
+pub(crate) async fn link_identity(pool: &Pool, provider: &str, subject: &str, user_id: &str) -> anyhow::Result<()> {
+    let conn = pool.get().await?;
+    conn.execute(
+        "MERGE INTO user_identities t USING (SELECT :1 provider, :2 subject, :3 user_id FROM dual) s ON (t.provider = s.provider AND t.subject = s.subject) WHEN MATCHED THEN UPDATE SET t.user_id = s.user_id WHEN NOT MATCHED THEN INSERT (provider, subject, user_id, created_at) VALUES (s.provider, s.subject, s.user_id, SYSTIMESTAMP)",
+        &crate::oracle_params![provider.to_string(), subject.to_string(), user_id.to_string()],
+    )
+    .await?;
+    conn.commit().await?;
+    Ok(())
+}
+
+/// Accounts created before identities were recorded use the provider `sub` as their id.
+pub(crate) async fn find_user_by_identity(pool: &Pool, provider: &str, subject: &str) -> anyhow::Result<Option<String>> {
+    let conn = pool.get().await?;
+    let rows = conn
+        .query(
+            "SELECT user_id FROM user_identities WHERE provider = :1 AND subject = :2 UNION ALL SELECT id FROM users WHERE provider = :3 AND id = :4",
+            &crate::oracle_params![provider.to_string(), subject.to_string(), provider.to_string(), subject.to_string()],
+        )
+        .await?;
+    Ok(rows.first().map(|row| crate::db::oracle::row_string(row, 0)))
+}
+
+/// Returns false when the jti was already recorded, i.e. the event is a replay.
+pub(crate) async fn record_security_event(
+    pool: &Pool,
+    jti: &str,
+    issuer: &str,
+    event_types: &str,
+    expires_at: i64,
+) -> anyhow::Result<bool> {
+    let conn = pool.get().await?;
+    conn.execute("DELETE FROM security_events WHERE expires_at <= SYSTIMESTAMP", &[])
+        .await?;
+    match conn
+        .execute(
+            "INSERT INTO security_events (jti, issuer, event_types, received_at, expires_at) VALUES (:1, :2, :3, SYSTIMESTAMP, TIMESTAMP '1970-01-01 00:00:00 +00:00' + NUMTODSINTERVAL(:4, 'SECOND'))",
+            &crate::oracle_params![jti.to_string(), issuer.to_string(), event_types.to_string(), expires_at],
+        )
+        .await
+    {
+        Ok(_) => {}
+        Err(e) if e.to_string().contains("ORA-00001") => return Ok(false),
+        Err(e) => return Err(e.into()),
+    }
+    conn.commit().await?;
+    Ok(true)
+}
+
+/// Drops a recorded jti so the sender's retry is processed after a failed attempt.
+pub(crate) async fn forget_security_event(pool: &Pool, jti: &str) -> anyhow::Result<()> {
+    let conn = pool.get().await?;
+    conn.execute(
+        "DELETE FROM security_events WHERE jti = :1",
+        &crate::oracle_params![jti.to_string()],
+    )
+    .await?;
+    conn.commit().await?;
+    Ok(())
+}
+
+pub(crate) async fn lock_user(pool: &Pool, user_id: &str, reason: &str) -> anyhow::Result<()> {
+    let conn = pool.get().await?;
+    conn.execute(
+        "UPDATE users SET locked_at = SYSTIMESTAMP, locked_reason = :1 WHERE id = :2",
+        &crate::oracle_params![reason.to_string(), user_id.to_string()],
+    )
+    .await?;
+    conn.commit().await?;
+    Ok(())
+}
+
+/// Lifts a lock only if its reason starts with `reason_prefix`, so one source
+/// of locks cannot undo another's. Returns whether a lock was lifted.
+pub(crate) async fn unlock_user(pool: &Pool, user_id: &str, reason_prefix: &str) -> anyhow::Result<bool> {
+    let conn = pool.get().await?;
+    let result = conn
+        .execute(
+            "UPDATE users SET locked_at = NULL, locked_reason = NULL WHERE id = :1 AND locked_at IS NOT NULL AND locked_reason LIKE :2",
+            &crate::oracle_params![user_id.to_string(), format!("{reason_prefix}%")],
+        )
+        .await?;
+    conn.commit().await?;
+    Ok(result.rows_affected > 0)
+}
+
+/// Records whether the sign-in provider vouched for the user's email address.
+pub(crate) async fn set_email_verified(pool: &Pool, user_id: &str, verified: bool) -> anyhow::Result<()> {
+    let conn = pool.get().await?;
+    conn.execute(
+        "UPDATE users SET email_verified = :1 WHERE id = :2",
+        &crate::oracle_params![i64::from(verified), user_id.to_string()],
+    )
+    .await?;
+    conn.commit().await?;
+    Ok(())
+}
+
+pub(crate) async fn is_user_locked(pool: &Pool, user_id: &str) -> anyhow::Result<bool> {
+    let conn = pool.get().await?;
+    let rows = conn
+        .query(
+            "SELECT COUNT(*) FROM users WHERE id = :1 AND locked_at IS NOT NULL",
+            &crate::oracle_params![user_id.to_string()],
+        )
+        .await?;
+    Ok(rows
+        .first()
+        .and_then(|row| crate::db::oracle::row_i64(row, 0))
+        .unwrap_or_default()
+        > 0)
+}
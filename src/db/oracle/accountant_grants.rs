@@ -0,0 +1,166 @@
+use deadpool_oracle::Pool;
+
+use crate::db::models::{AccountantGrant, ClientGrant, NewAccountantGrant};
+
+fn join_years(years: &[i32]) -> String {
+    years.iter().map(i32::to_string).collect::<Vec<_>>().join(",")
+}
+
+fn split_years(value: String) -> Vec<i32> {
+    value
+        .split(',')
+        .filter_map(|year| year.trim().parse().ok())
+        .collect()
+}
+
+const GRANT_COLUMNS: &str = "id, accountant_email, tax_years, allow_agi, created_at, accepted_at, revoked_at";
+
+fn grant_from_row(row: &oracle_rs::Row) -> AccountantGrant {
+    AccountantGrant {
+        id: crate::db::oracle::row_string(row, 0),
+        accountant_email: crate::db::oracle::row_string(row, 1),
+        tax_years: split_years(crate::db::oracle::row_string(row, 2)),
+        allow_agi: crate::db::oracle::row_bool(row, 3).unwrap_or(false),
+        created_at: crate::db::oracle::row_datetime_utc(row, 4).unwrap_or_else(chrono::Utc::now),
+        accepted_at: crate::db::oracle::row_datetime_utc(row, 5),
+        revoked_at: crate::db::oracle::row_datetime_utc(row, 6),
+    }
+}
+
+/// Returns None when the owner already has an active grant for this email.
+pub(crate) async fn create_grant(pool: &Pool, input: &NewAccountantGrant) -> anyhow::Result<Option<AccountantGrant>> {
+    let conn = pool.get().await?;
+    let existing = conn
+        .query(
+            "SELECT COUNT(*) FROM accountant_grants WHERE owner_user_id = :1 AND accountant_email = :2 AND revoked_at IS NULL",
+            &crate::oracle_params![input.owner_user_id.clone(), input.accountant_email.clone()],
+        )
+        .await?;
+    if existing
+        .first()
+        .and_then(|row| crate::db::oracle::row_i64(row, 0))
+        .unwrap_or_default()
+        > 0
+    {
+        return Ok(None);
+    }
+
+    conn.execute(
+        "INSERT INTO accountant_grants (id, owner_user_id, accountant_email, tax_years, allow_agi, created_at) VALUES (:1, :2, :3, :4, :5, SYSTIMESTAMP)",
+        &crate::oracle_params![
+            input.id.clone(),
+            input.owner_user_id.clone(),
+            input.accountant_email.clone(),
+            join_years(&input.tax_years),
+            i64::from(input.allow_agi),
+        ],
+    )
+    .await?;
+    conn.commit().await?;
+
+    let rows = conn
+        .query(
+            &format!("SELECT {GRANT_COLUMNS} FROM accountant_grants WHERE id = :1"),
+            &crate::oracle_params![input.id.clone()],
+        )
+        .await?;
+    let row = rows
+        .first()
+        .ok_or_else(|| anyhow::anyhow!("accountant grant {} missing after insert", input.id))?;
+    Ok(Some(grant_from_row(row)))
+}
+
+pub(crate) async fn list_grants(pool: &Pool, owner_user_id: &str) -> anyhow::Result<Vec<AccountantGrant>> {
+    let conn = pool.get().await?;
+    let rows = conn
+        .query(
+            &format!("SELECT {GRANT_COLUMNS} FROM accountant_grants WHERE owner_user_id = :1 ORDER BY created_at DESC"),
+            &crate::oracle_params![owner_user_id.to_string()],
+        )
+        .await?;
+    Ok(rows.rows.iter().map(grant_from_row).collect())
+}
+
+pub(crate) async fn revoke_grant(pool: &Pool, owner_user_id: &str, grant_id: &str) -> anyhow::Result<bool> {
+    let conn = pool.get().await?;
+    let result = conn
+        .execute(
+            "UPDATE accountant_grants SET revoked_at = SYSTIMESTAMP WHERE id = :1 AND owner_user_id = :2 AND revoked_at IS NULL",
+            &crate::oracle_params![grant_id.to_string(), owner_user_id.to_string()],
+        )
+        .await?;
+    conn.commit().await?;
+    Ok(result.rows_affected > 0)
+}
+
+// An accepted grant belongs to the account that accepted it. A pending one is open only to an
+// account whose provider-verified email is the invited address.
+const CLIENT_QUERY: &str = "SELECT g.id, g.owner_user_id, u.name, u.email, g.tax_years, g.allow_agi FROM accountant_grants g JOIN users u ON u.id = g.owner_user_id WHERE g.revoked_at IS NULL AND g.owner_user_id <> :1 AND (g.accountant_user_id = :2 OR (g.accountant_user_id IS NULL AND g.accountant_email = :3 AND EXISTS (SELECT 1 FROM users a WHERE a.id = :4 AND a.email_verified = 1 AND LOWER(a.email) = :5)))";
+
+/// Binds for `CLIENT_QUERY`, one per placeholder.
+fn client_query_params(accountant_email: &str, accountant_user_id: &str) -> Vec<oracle_rs::Value> {
+    crate::oracle_params![
+        accountant_user_id.to_string(),
+        accountant_user_id.to_string(),
+        accountant_email.to_string(),
+        accountant_user_id.to_string(),
+        accountant_email.to_string(),
+    ]
+}
+
+fn client_from_row(row: &oracle_rs::Row) -> ClientGrant {
+    ClientGrant {
+        id: crate::db::oracle::row_string(row, 0),
+        owner_user_id: crate::db::oracle::row_string(row, 1),
+        owner_name: crate::db::oracle::row_string(row, 2),
+        owner_email: crate::db::oracle::row_string(row, 3),
+        tax_years: split_years(crate::db::oracle::row_string(row, 4)),
+        allow_agi: crate::db::oracle::row_bool(row, 5).unwrap_or(false),
+    }
+}
+
+/// Active grants accepted by, or still open to, the caller, excluding any they gave themselves.
+pub(crate) async fn list_client_grants(
+    pool: &Pool,
+    accountant_email: &str,
+    accountant_user_id: &str,
+) -> anyhow::Result<Vec<ClientGrant>> {
+    let conn = pool.get().await?;
+    let rows = conn
+        .query(
+            &format!("{CLIENT_QUERY} ORDER BY u.name"),
+            &client_query_params(accountant_email, accountant_user_id),
+        )
+        .await?;
+    Ok(rows.rows.iter().map(client_from_row).collect())
+}
+
+pub(crate) async fn find_client_grant(
+    pool: &Pool,
+    grant_id: &str,
+    accountant_email: &str,
+    accountant_user_id: &str,
+) -> anyhow::Result<Option<ClientGrant>> {
+    let mut params = client_query_params(accountant_email, accountant_user_id);
+    params.push(crate::db::oracle::to_value(grant_id.to_string()));
+    let conn = pool.get().await?;
+    let rows = conn
+        .query(
+            &format!("{CLIENT_QUERY} AND g.id = :6"),
+            &params,
+        )
+        .await?;
+    Ok(rows.first().map(client_from_row))
+}
+
+/// Records who first used a grant; later uses leave it unchanged.
+pub(crate) async fn mark_grant_accepted(pool: &Pool, grant_id: &str, accountant_user_id: &str) -> anyhow::Result<()> {
+    let conn = pool.get().await?;
+    conn.execute(
+        "UPDATE accountant_grants SET accepted_at = SYSTIMESTAMP, accountant_user_id = :1 WHERE id = :2 AND accepted_at IS NULL",
+        &crate::oracle_params![accountant_user_id.to_string(), grant_id.to_string()],
+    )
+    .await?;
+    conn.commit().await?;
+    Ok(())
+}
@@ -0,0 +1,106 @@
+use deadpool_oracle::Pool;
+use oracle_rs::Value;
+
+use crate::db::models::{BlindIndexDuplicateGroup, BlindIndexTokens, Donation};
+
+/// `(token_kind, token)` for every token set in `tokens`.
+pub(crate) fn token_entries(tokens: &BlindIndexTokens) -> Vec<(&'static str, &str)> {
+    [
+        ("charity", tokens.charity.as_deref()),
+        ("year", tokens.year.as_deref()),
+        ("category", tokens.category.as_deref()),
+        ("dedupe", tokens.dedupe.as_deref()),
+    ]
+    .into_iter()
+    .filter_map(|(kind, token)| token.map(|token| (kind, token)))
+    .collect()
+}
+
+/// Replaces the donation's tokens inside the caller's transaction.
+pub(crate) async fn replace_donation_tokens(
+    conn: &oracle_rs::Connection,
+    user_id: &str,
+    donation_id: &str,
+    tokens: &BlindIndexTokens,
+) -> anyhow::Result<()> {
+    conn.execute(
+        "DELETE FROM donation_index_tokens WHERE donation_id = :1 AND user_id = :2",
+        &crate::oracle_params![donation_id.to_string(), user_id.to_string()],
+    )
+    .await?;
+    for (kind, token) in token_entries(tokens) {
+        conn.execute(
+            "INSERT INTO donation_index_tokens (donation_id, user_id, token_kind, token) VALUES (:1, :2, :3, :4)",
+            &crate::oracle_params![
+                donation_id.to_string(),
+                user_id.to_string(),
+                kind.to_string(),
+                token.to_string(),
+            ],
+        )
+        .await?;
+    }
+    Ok(())
+}
+
+/// Live donations carrying every token set in `filter`.
+pub(crate) async fn find_donations(
+    pool: &Pool,
+    user_id: &str,
+    filter: &BlindIndexTokens,
+) -> anyhow::Result<Vec<Donation>> {
+    let key = crate::db::oracle::data_keys::for_read(pool, user_id).await?;
+    let conn = pool.get().await?;
+    let mut sql = "SELECT d.id, d.user_id, d.donation_year, d.donation_date, d.donation_category, d.donation_amount, d.charity_id, c.name, c.ein, d.notes, d.created_at, d.updated_at, d.is_encrypted, d.encrypted_payload FROM donations d JOIN charities c ON c.id = d.charity_id WHERE d.user_id = :1 AND d.deleted = 0".to_string();
+    let mut params: Vec<Value> = crate::oracle_params![user_id.to_string()];
+    for (kind, token) in token_entries(filter) {
+        let bind = params.len() + 1;
+        sql.push_str(&format!(
+            " AND EXISTS (SELECT 1 FROM donation_index_tokens t WHERE t.donation_id = d.id AND t.user_id = d.user_id AND t.token_kind = '{kind}' AND t.token = :{bind})"
+        ));
+        params.push(token.to_string().into());
+    }
+    sql.push_str(" ORDER BY d.donation_date DESC, d.id");
+    let rows = conn.query(&sql, &params).await?;
+    rows.rows
+        .iter()
+        .map(|row| key.open_donation(crate::db::oracle::sync_changes::donation_from_row(row)))
+        .collect()
+}
+
+/// Distinct year tokens of the user's live donations.
+pub(crate) async fn list_year_tokens(pool: &Pool, user_id: &str) -> anyhow::Result<Vec<String>> {
+    let conn = pool.get().await?;
+    let rows = conn
+        .query(
+            "SELECT DISTINCT t.token FROM donation_index_tokens t JOIN donations d ON d.id = t.donation_id WHERE t.user_id = :1 AND t.token_kind = 'year' AND d.deleted = 0 ORDER BY t.token",
+            &crate::oracle_params![user_id.to_string()],
+        )
+        .await?;
+    Ok(rows.rows.iter().map(|row| crate::db::oracle::row_string(row, 0)).collect())
+}
+
+/// Dedupe tokens shared by more than one live donation, oldest donation first.
+pub(crate) async fn list_duplicate_groups(
+    pool: &Pool,
+    user_id: &str,
+) -> anyhow::Result<Vec<BlindIndexDuplicateGroup>> {
+    let conn = pool.get().await?;
+    let rows = conn
+        .query(
+            "SELECT t.token, t.donation_id FROM donation_index_tokens t JOIN donations d ON d.id = t.donation_id WHERE t.user_id = :1 AND t.token_kind = 'dedupe' AND d.deleted = 0 ORDER BY t.token, d.created_at, d.id",
+            &crate::oracle_params![user_id.to_string()],
+        )
+        .await?;
+    let mut groups: Vec<BlindIndexDuplicateGroup> = Vec::new();
+    for row in &rows.rows {
+        let token = crate::db::oracle::row_string(row, 0);
+        let donation_id = crate::db::oracle::row_string(row, 1);
+        match groups.last_mut() {
+            Some(group) if group.token == token => group.donation_ids.push(donation_id),
+            _ => groups.push(BlindIndexDuplicateGroup { token, donation_ids: vec![donation_id] }),
+        }
+    }
+    groups.retain(|group| group.donation_ids.len() > 1);
+    Ok(groups)
+}
@@ -1,7 +1,13 @@
 use deadpool_oracle::Pool;
+use oracle_rs::Connection;
 
 pub(crate) async fn run_bootstrap_ddl(pool: &Pool) -> anyhow::Result<()> {
     let conn = pool.get().await?;
+    apply_bootstrap_ddl(&conn).await
+}
+
+/// Brings an existing schema up to date; statements that are already applied fail and are skipped.
+pub async fn apply_bootstrap_ddl(conn: &Connection) -> anyhow::Result<()> {
     for sql in [
         "ALTER TABLE users ADD (filing_status VARCHAR2(32))",
         "ALTER TABLE users ADD (agi NUMBER(14,2))",
@@ -11,6 +17,11 @@ pub(crate) async fn run_bootstrap_ddl(pool: &Pool) -> anyhow::Result<()> {
         "ALTER TABLE users ADD (encrypted_payload CLOB)",
         "ALTER TABLE users ADD (vault_credential_id VARCHAR2(512))",
         "ALTER TABLE users ADD (updated_at TIMESTAMP)",
+        "ALTER TABLE users ADD (tokens_valid_after TIMESTAMP WITH TIME ZONE)",
+        "ALTER TABLE users ADD (locked_at TIMESTAMP WITH TIME ZONE)",
+        "ALTER TABLE users ADD (locked_reason VARCHAR2(255))",
+        "ALTER TABLE users ADD (agi_ciphertext VARCHAR2(128))",
+        "ALTER TABLE users ADD (email_verified NUMBER(1) DEFAULT 0 NOT NULL)",
         "ALTER TABLE charities ADD (category VARCHAR2(255))",
         "ALTER TABLE charities ADD (status VARCHAR2(255))",
         "ALTER TABLE charities ADD (classification VARCHAR2(255))",
@@ -20,6 +31,7 @@ pub(crate) async fn run_bootstrap_ddl(pool: &Pool) -> anyhow::Result<()> {
         "ALTER TABLE charities ADD (city VARCHAR2(120))",
         "ALTER TABLE charities ADD (state VARCHAR2(16))",
         "ALTER TABLE charities ADD (zip VARCHAR2(20))",
+        "ALTER TABLE charities MODIFY (street VARCHAR2(512), city VARCHAR2(512), state VARCHAR2(512), zip VARCHAR2(512))",
         "ALTER TABLE charities ADD (is_encrypted NUMBER(1) DEFAULT 0)",
         "ALTER TABLE charities ADD (encrypted_payload CLOB)",
         "ALTER TABLE donations ADD (donation_category VARCHAR2(32))",
@@ -29,6 +41,7 @@ pub(crate) async fn run_bootstrap_ddl(pool: &Pool) -> anyhow::Result<()> {
         "ALTER TABLE receipts ADD (is_encrypted NUMBER(1) DEFAULT 0)",
         "ALTER TABLE receipts ADD (encrypted_payload CLOB)",
         "ALTER TABLE receipts ADD (updated_at TIMESTAMP)",
+        "ALTER TABLE receipts ADD (content_hash VARCHAR2(64))",
         "ALTER TABLE audit_logs ADD (updated_at TIMESTAMP)",
         "ALTER TABLE val_categories ADD (created_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP)",
         "ALTER TABLE val_categories ADD (updated_at TIMESTAMP)",
@@ -37,6 +50,41 @@ pub(crate) async fn run_bootstrap_ddl(pool: &Pool) -> anyhow::Result<()> {
         "CREATE TABLE audit_revisions (id VARCHAR2(255) PRIMARY KEY, user_id VARCHAR2(255), table_name VARCHAR2(255) NOT NULL, record_id VARCHAR2(255) NOT NULL, operation VARCHAR2(16) NOT NULL, old_values VARCHAR2(4000), new_values VARCHAR2(4000), created_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP, updated_at TIMESTAMP, CONSTRAINT fk_audit_revisions_user FOREIGN KEY (user_id) REFERENCES users(id))",
         "CREATE INDEX idx_audit_revisions_table_record ON audit_revisions(table_name, record_id, created_at)",
         "CREATE INDEX idx_charities_user_ein ON charities(user_id, ein)",
+        "CREATE SEQUENCE sync_change_seq ORDER",
+        "CREATE TABLE sync_changes (seq NUMBER PRIMARY KEY, user_id VARCHAR2(255) NOT NULL, table_name VARCHAR2(32) NOT NULL, record_id VARCHAR2(255) NOT NULL, operation VARCHAR2(16) NOT NULL, created_at TIMESTAMP WITH TIME ZONE DEFAULT CURRENT_TIMESTAMP)",
+        "CREATE INDEX idx_sync_changes_user_seq ON sync_changes(user_id, seq)",
+        "CREATE TABLE idempotency_keys (user_id VARCHAR2(255) NOT NULL, idem_key VARCHAR2(255) NOT NULL, request_hash VARCHAR2(64) NOT NULL, status_code NUMBER(3), content_type VARCHAR2(255), response_body CLOB, created_at TIMESTAMP WITH TIME ZONE DEFAULT CURRENT_TIMESTAMP, expires_at TIMESTAMP WITH TIME ZONE NOT NULL, CONSTRAINT pk_idempotency_keys PRIMARY KEY (user_id, idem_key))",
+        "CREATE INDEX idx_idempotency_keys_expires ON idempotency_keys(expires_at)",
+        "CREATE TABLE personal_access_tokens (id VARCHAR2(64) PRIMARY KEY, user_id VARCHAR2(255) NOT NULL, name VARCHAR2(255) NOT NULL, scopes VARCHAR2(255) NOT NULL, token_hash VARCHAR2(255) NOT NULL, created_at TIMESTAMP WITH TIME ZONE DEFAULT CURRENT_TIMESTAMP, expires_at TIMESTAMP WITH TIME ZONE NOT NULL, last_used_at TIMESTAMP WITH TIME ZONE, revoked_at TIMESTAMP WITH TIME ZONE, CONSTRAINT fk_pat_user FOREIGN KEY (user_id) REFERENCES users(id))",
+        "CREATE INDEX idx_pat_user ON personal_access_tokens(user_id, created_at)",
+        "CREATE TABLE webauthn_credentials (id VARCHAR2(1400) PRIMARY KEY, user_id VARCHAR2(255) NOT NULL, name VARCHAR2(255) NOT NULL, public_key VARCHAR2(2048) NOT NULL, algorithm NUMBER(6) NOT NULL, sign_count NUMBER(10) DEFAULT 0 NOT NULL, transports VARCHAR2(255), created_at TIMESTAMP WITH TIME ZONE DEFAULT CURRENT_TIMESTAMP, last_used_at TIMESTAMP WITH TIME ZONE, CONSTRAINT fk_webauthn_cred_user FOREIGN KEY (user_id) REFERENCES users(id))",
+        "CREATE INDEX idx_webauthn_cred_user ON webauthn_credentials(user_id, created_at)",
+        "CREATE TABLE webauthn_challenges (id VARCHAR2(64) PRIMARY KEY, user_id VARCHAR2(255), purpose VARCHAR2(16) NOT NULL, challenge VARCHAR2(128) NOT NULL, created_at TIMESTAMP WITH TIME ZONE DEFAULT CURRENT_TIMESTAMP, expires_at TIMESTAMP WITH TIME ZONE NOT NULL)",
+        "CREATE INDEX idx_webauthn_challenges_expires ON webauthn_challenges(expires_at)",
+        "CREATE TABLE revoked_tokens (jti VARCHAR2(64) PRIMARY KEY, user_id VARCHAR2(255) NOT NULL, revoked_at TIMESTAMP WITH TIME ZONE DEFAULT CURRENT_TIMESTAMP, expires_at TIMESTAMP WITH TIME ZONE NOT NULL)",
+        "CREATE INDEX idx_revoked_tokens_expires ON revoked_tokens(expires_at)",
+        "CREATE TABLE user_sessions (jti VARCHAR2(64) PRIMARY KEY, user_id VARCHAR2(255) NOT NULL, user_agent VARCHAR2(512), ip_address VARCHAR2(64), created_at TIMESTAMP WITH TIME ZONE NOT NULL, last_seen_at TIMESTAMP WITH TIME ZONE, expires_at TIMESTAMP WITH TIME ZONE NOT NULL, revoked_at TIMESTAMP WITH TIME ZONE, CONSTRAINT fk_user_sessions_user FOREIGN KEY (user_id) REFERENCES users(id))",
+        "CREATE INDEX idx_user_sessions_user ON user_sessions(user_id, expires_at)",
+        "CREATE TABLE refresh_tokens (token_hash VARCHAR2(64) PRIMARY KEY, family_id VARCHAR2(64) NOT NULL, user_id VARCHAR2(255) NOT NULL, created_at TIMESTAMP WITH TIME ZONE DEFAULT CURRENT_TIMESTAMP, expires_at TIMESTAMP WITH TIME ZONE NOT NULL, used_at TIMESTAMP WITH TIME ZONE, revoked_at TIMESTAMP WITH TIME ZONE, CONSTRAINT fk_refresh_tokens_user FOREIGN KEY (user_id) REFERENCES users(id))",
+        "CREATE INDEX idx_refresh_tokens_family ON refresh_tokens(family_id)",
+        "CREATE INDEX idx_refresh_tokens_user ON refresh_tokens(user_id, expires_at)",
+        "CREATE TABLE accountant_grants (id VARCHAR2(64) PRIMARY KEY, owner_user_id VARCHAR2(255) NOT NULL, accountant_email VARCHAR2(255) NOT NULL, accountant_user_id VARCHAR2(255), tax_years VARCHAR2(200) NOT NULL, allow_agi NUMBER(1) DEFAULT 0 NOT NULL, created_at TIMESTAMP WITH TIME ZONE DEFAULT CURRENT_TIMESTAMP, accepted_at TIMESTAMP WITH TIME ZONE, revoked_at TIMESTAMP WITH TIME ZONE, CONSTRAINT fk_accountant_grants_owner FOREIGN KEY (owner_user_id) REFERENCES users(id))",
+        "CREATE INDEX idx_accountant_grants_owner ON accountant_grants(owner_user_id)",
+        "CREATE INDEX idx_accountant_grants_email ON accountant_grants(accountant_email)",
+        "CREATE INDEX idx_accountant_grants_accountant ON accountant_grants(accountant_user_id)",
+        "CREATE TABLE vault_key_rotations (id VARCHAR2(64) PRIMARY KEY, user_id VARCHAR2(255) NOT NULL, old_key_id VARCHAR2(32) NOT NULL, new_key_id VARCHAR2(32) NOT NULL, new_credential_id VARCHAR2(512), status VARCHAR2(20) NOT NULL, total_items NUMBER DEFAULT 0 NOT NULL, processed_items NUMBER DEFAULT 0 NOT NULL, failed_table VARCHAR2(30), failed_record_id VARCHAR2(255), error VARCHAR2(1000), created_at TIMESTAMP WITH TIME ZONE DEFAULT CURRENT_TIMESTAMP, updated_at TIMESTAMP WITH TIME ZONE, completed_at TIMESTAMP WITH TIME ZONE, CONSTRAINT fk_vault_key_rotations_user FOREIGN KEY (user_id) REFERENCES users(id))",
+        "CREATE INDEX idx_vault_key_rotations_user ON vault_key_rotations(user_id, created_at)",
+        "CREATE TABLE user_data_keys (user_id VARCHAR2(255) PRIMARY KEY, wrapped_key VARCHAR2(128) NOT NULL, master_key_id VARCHAR2(32) NOT NULL, created_at TIMESTAMP WITH TIME ZONE DEFAULT CURRENT_TIMESTAMP)",
+        "CREATE TABLE donation_index_tokens (donation_id VARCHAR2(255) NOT NULL, user_id VARCHAR2(255) NOT NULL, token_kind VARCHAR2(16) NOT NULL, token VARCHAR2(128) NOT NULL, created_at TIMESTAMP WITH TIME ZONE DEFAULT CURRENT_TIMESTAMP, CONSTRAINT pk_donation_index_tokens PRIMARY KEY (donation_id, token_kind), CONSTRAINT fk_donation_index_tokens_donation FOREIGN KEY (donation_id) REFERENCES donations(id) ON DELETE CASCADE)",
+        "CREATE INDEX idx_donation_index_tokens_lookup ON donation_index_tokens(user_id, token_kind, token)",
+        "CREATE TABLE user_identities (provider VARCHAR2(50) NOT NULL, subject VARCHAR2(255) NOT NULL, user_id VARCHAR2(255) NOT NULL, created_at TIMESTAMP WITH TIME ZONE DEFAULT CURRENT_TIMESTAMP, CONSTRAINT pk_user_identities PRIMARY KEY (provider, subject), CONSTRAINT fk_user_identities_user FOREIGN KEY (user_id) REFERENCES users(id))",
+        "CREATE INDEX idx_user_identities_user ON user_identities(user_id)",
+        "CREATE TABLE security_events (jti VARCHAR2(255) PRIMARY KEY, issuer VARCHAR2(255) NOT NULL, event_types VARCHAR2(2000), received_at TIMESTAMP WITH TIME ZONE DEFAULT CURRENT_TIMESTAMP, expires_at TIMESTAMP WITH TIME ZONE NOT NULL)",
+        "CREATE INDEX idx_security_events_expires ON security_events(expires_at)",
+        "CREATE TABLE receipt_uploads (id VARCHAR2(64) PRIMARY KEY, user_id VARCHAR2(255) NOT NULL, receipt_key VARCHAR2(1024) NOT NULL, upload_id VARCHAR2(1024) NOT NULL, content_type VARCHAR2(255), upload_size NUMBER NOT NULL, part_size NUMBER NOT NULL, part_count NUMBER NOT NULL, created_at TIMESTAMP WITH TIME ZONE DEFAULT CURRENT_TIMESTAMP, CONSTRAINT fk_receipt_uploads_user FOREIGN KEY (user_id) REFERENCES users(id))",
+        "CREATE INDEX idx_receipt_uploads_created ON receipt_uploads(created_at)",
+        "CREATE INDEX idx_receipts_content_hash ON receipts(content_hash)",
+        "CREATE INDEX idx_receipts_key ON receipts(receipt_key)",
         "CREATE INDEX idx_val_items_category_name ON val_items(category_id, name)",
         "CREATE INDEX idx_val_items_lower_name ON val_items(LOWER(name))",
     ] {
@@ -3,6 +3,7 @@ use chrono::Utc;
 use deadpool_oracle::Pool;
 
 pub(crate) async fn list_charities(pool: &Pool, user_id: &str) -> anyhow::Result<Vec<Charity>> {
+    let key = crate::db::oracle::data_keys::for_read(pool, user_id).await?;
     let conn = pool.get().await?;
     let sql = "SELECT id, user_id, name, ein, created_at, updated_at, nonprofit_type, deductibility, street, city, state, zip, category, status, classification, is_encrypted, encrypted_payload FROM charities WHERE user_id = :1 ORDER BY name ASC";
     let rows = conn
@@ -10,7 +11,7 @@ pub(crate) async fn list_charities(pool: &Pool, user_id: &str) -> anyhow::Result
         .await?;
     let mut out = Vec::new();
     for row in &rows.rows {
-        out.push(Charity {
+        out.push(key.open_charity(Charity {
             id: crate::db::oracle::row_string(row, 0),
             user_id: crate::db::oracle::row_string(row, 1),
             name: crate::db::oracle::row_string(row, 2),
@@ -28,7 +29,7 @@ pub(crate) async fn list_charities(pool: &Pool, user_id: &str) -> anyhow::Result
             classification: crate::db::oracle::row_opt_string(row, 14),
             is_encrypted: crate::db::oracle::row_bool(row, 15),
             encrypted_payload: crate::db::oracle::row_opt_string(row, 16),
-        });
+        })?);
     }
     Ok(out)
 }
@@ -0,0 +1,317 @@
+use anyhow::{anyhow, bail, Context};
+use deadpool_oracle::Pool;
+use oracle_rs::Connection;
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex, OnceLock};
+
+use crate::db::field_crypto::{self, DataKey, MasterKey};
+use crate::db::models::{Charity, Donation};
+
+const REDACTED: &str = "[encrypted]";
+
+/// Revision snapshot fields that hold sealed columns.
+const REDACTED_REVISION_FIELDS: [&str; 6] = ["notes", "agi", "street", "city", "state", "zip"];
+
+/// The configured master key, read once. A malformed key is reported on every
+/// call so the pool refuses to start rather than writing plaintext.
+pub(crate) fn master_key() -> anyhow::Result<Option<&'static MasterKey>> {
+    static MASTER_KEY: OnceLock<Result<Option<MasterKey>, String>> = OnceLock::new();
+    MASTER_KEY
+        .get_or_init(|| MasterKey::from_env().map_err(|e| e.to_string()))
+        .as_ref()
+        .map(Option::as_ref)
+        .map_err(|e| anyhow!("{}", e))
+}
+
+fn cache() -> &'static Mutex<HashMap<String, Arc<DataKey>>> {
+    static DATA_KEYS: OnceLock<Mutex<HashMap<String, Arc<DataKey>>>> = OnceLock::new();
+    DATA_KEYS.get_or_init(|| Mutex::new(HashMap::new()))
+}
+
+fn cached(user_id: &str) -> Option<Arc<DataKey>> {
+    cache().lock().ok()?.get(user_id).cloned()
+}
+
+/// Drops a user's key from the cache once its row is gone.
+pub(crate) fn forget(user_id: &str) {
+    if let Ok(mut keys) = cache().lock() {
+        keys.remove(user_id);
+    }
+}
+
+/// A user's data key for sealing and opening columns; empty when server-side
+/// encryption is off, in which case values pass through unchanged.
+#[derive(Clone, Default)]
+pub(crate) struct FieldKey(Option<Arc<DataKey>>);
+
+impl FieldKey {
+    pub(crate) fn seal(&self, column: &str, value: Option<String>) -> anyhow::Result<Option<String>> {
+        match (&self.0, value) {
+            (Some(key), Some(value)) => key.seal(column, &value).map(Some),
+            (_, value) => Ok(value),
+        }
+    }
+
+    pub(crate) fn open(&self, column: &str, value: Option<String>) -> anyhow::Result<Option<String>> {
+        match (&self.0, value) {
+            (Some(key), Some(value)) => key.open(column, &value).map(Some),
+            (None, Some(value)) if field_crypto::is_sealed(&value) => bail!(
+                "{} is sealed but no data key is available; is {} set?",
+                column,
+                field_crypto::MASTER_KEY_ENV
+            ),
+            (_, value) => Ok(value),
+        }
+    }
+
+    /// Splits an AGI into the `agi` and `agi_ciphertext` columns: sealed, the
+    /// numeric column is left empty.
+    pub(crate) fn seal_agi(&self, agi: Option<f64>) -> anyhow::Result<(Option<f64>, Option<String>)> {
+        match (&self.0, agi) {
+            (Some(key), Some(agi)) => Ok((None, Some(key.seal(field_crypto::USER_AGI, &agi.to_string())?))),
+            (_, agi) => Ok((agi, None)),
+        }
+    }
+
+    pub(crate) fn open_agi(&self, agi: Option<f64>, agi_ciphertext: Option<String>) -> anyhow::Result<Option<f64>> {
+        match self.open(field_crypto::USER_AGI, agi_ciphertext)? {
+            Some(value) => value
+                .trim()
+                .parse()
+                .map(Some)
+                .map_err(|_| anyhow!("Sealed AGI is not a number")),
+            None => Ok(agi),
+        }
+    }
+
+    pub(crate) fn open_charity(&self, mut charity: Charity) -> anyhow::Result<Charity> {
+        charity.street = self.open(field_crypto::CHARITY_STREET, charity.street)?;
+        charity.city = self.open(field_crypto::CHARITY_CITY, charity.city)?;
+        charity.state = self.open(field_crypto::CHARITY_STATE, charity.state)?;
+        charity.zip = self.open(field_crypto::CHARITY_ZIP, charity.zip)?;
+        Ok(charity)
+    }
+
+    pub(crate) fn open_donation(&self, mut donation: Donation) -> anyhow::Result<Donation> {
+        donation.notes = self.open(field_crypto::DONATION_NOTES, donation.notes)?;
+        Ok(donation)
+    }
+}
+
+async fn load_data_key(conn: &Connection, master: &MasterKey, user_id: &str) -> anyhow::Result<Option<Arc<DataKey>>> {
+    let rows = conn
+        .query(
+            "SELECT wrapped_key, master_key_id FROM user_data_keys WHERE user_id = :1",
+            &crate::oracle_params![user_id.to_string()],
+        )
+        .await?;
+    let Some(row) = rows.first() else {
+        return Ok(None);
+    };
+    let key = Arc::new(master.unwrap_data_key(
+        user_id,
+        &crate::db::oracle::row_string(row, 0),
+        &crate::db::oracle::row_string(row, 1),
+    )?);
+    if let Ok(mut keys) = cache().lock() {
+        keys.insert(user_id.to_string(), key.clone());
+    }
+    Ok(Some(key))
+}
+
+/// Loads the user's data key, creating and committing one if they have none.
+/// Commits on `conn`, so call it before the caller's own statements.
+async fn create_data_key(conn: &Connection, master: &MasterKey, user_id: &str) -> anyhow::Result<Arc<DataKey>> {
+    if let Some(key) = load_data_key(conn, master, user_id).await? {
+        return Ok(key);
+    }
+    let (key, wrapped) = master.generate_data_key(user_id)?;
+    let inserted = conn
+        .execute(
+            "INSERT INTO user_data_keys (user_id, wrapped_key, master_key_id, created_at) VALUES (:1, :2, :3, SYSTIMESTAMP)",
+            &crate::oracle_params![user_id.to_string(), wrapped, master.id().to_string()],
+        )
+        .await;
+    match inserted {
+        Ok(_) => conn.commit().await?,
+        // Another request created it first; use theirs.
+        Err(e) if e.to_string().contains("ORA-00001") => {
+            return load_data_key(conn, master, user_id)
+                .await?
+                .ok_or_else(|| anyhow!("data key for user {} vanished", user_id));
+        }
+        Err(e) => return Err(e.into()),
+    }
+    let key = Arc::new(key);
+    if let Ok(mut keys) = cache().lock() {
+        keys.insert(user_id.to_string(), key.clone());
+    }
+    Ok(key)
+}
+
+/// The key for reading a user's columns. A user without a key has nothing
+/// sealed, so none is created.
+pub(crate) async fn for_read(pool: &Pool, user_id: &str) -> anyhow::Result<FieldKey> {
+    let Some(master) = master_key()? else {
+        return Ok(FieldKey::default());
+    };
+    if let Some(key) = cached(user_id) {
+        return Ok(FieldKey(Some(key)));
+    }
+    let conn = pool.get().await?;
+    Ok(FieldKey(load_data_key(&conn, master, user_id).await?))
+}
+
+/// The key for writing a user's columns, created on first use.
+pub(crate) async fn for_write(pool: &Pool, user_id: &str) -> anyhow::Result<FieldKey> {
+    let Some(master) = master_key()? else {
+        return Ok(FieldKey::default());
+    };
+    if let Some(key) = cached(user_id) {
+        return Ok(FieldKey(Some(key)));
+    }
+    let conn = pool.get().await?;
+    Ok(FieldKey(Some(create_data_key(&conn, master, user_id).await?)))
+}
+
+/// Replaces sealed fields in an audit revision snapshot, which would otherwise
+/// keep a plaintext copy. Unchanged when server-side encryption is off.
+pub(crate) fn redact_revision(values: String) -> String {
+    if !matches!(master_key(), Ok(Some(_))) {
+        return values;
+    }
+    let Ok(serde_json::Value::Object(mut snapshot)) = serde_json::from_str(&values) else {
+        return values;
+    };
+    for field in REDACTED_REVISION_FIELDS {
+        if let Some(value) = snapshot.get_mut(field).filter(|value| !value.is_null()) {
+            *value = serde_json::Value::String(REDACTED.to_string());
+        }
+    }
+    serde_json::Value::Object(snapshot).to_string()
+}
+
+/// Counts of values sealed by [`encrypt_existing_columns`].
+#[derive(Debug, Default)]
+pub struct ColumnEncryptionReport {
+    pub users: usize,
+    pub agi_values: usize,
+    pub donation_notes: usize,
+    pub charity_addresses: usize,
+}
+
+/// Seals plaintext left in the encrypted columns from before
+/// `DATA_ENCRYPTION_KEY` was set, one user per transaction. Each row is only
+/// rewritten if it still holds the value that was read, and sealed values are
+/// skipped, so the run can be interrupted and repeated.
+pub async fn encrypt_existing_columns(conn: &Connection) -> anyhow::Result<ColumnEncryptionReport> {
+    let master = master_key()?
+        .ok_or_else(|| anyhow!("{} must be set to encrypt existing rows", field_crypto::MASTER_KEY_ENV))?;
+    let sealed_pattern = format!("{}%", field_crypto::SEALED_PREFIX);
+    let rows = conn
+        .query(
+            "SELECT u.id FROM users u WHERE u.agi IS NOT NULL OR EXISTS (SELECT 1 FROM donations d WHERE d.user_id = u.id AND d.notes NOT LIKE :1) OR EXISTS (SELECT 1 FROM charities c WHERE c.user_id = u.id AND (c.street NOT LIKE :1 OR c.city NOT LIKE :1 OR c.state NOT LIKE :1 OR c.zip NOT LIKE :1)) ORDER BY u.id",
+            &crate::oracle_params![sealed_pattern.clone()],
+        )
+        .await?;
+    let user_ids: Vec<String> = rows.rows.iter().map(|row| crate::db::oracle::row_string(row, 0)).collect();
+
+    let mut report = ColumnEncryptionReport::default();
+    for user_id in user_ids {
+        let key = FieldKey(Some(create_data_key(conn, master, &user_id).await?));
+        encrypt_user_columns(conn, &key, &user_id, &sealed_pattern, &mut report)
+            .await
+            .with_context(|| format!("encrypting columns of user {}", user_id))?;
+        conn.commit().await?;
+        report.users += 1;
+    }
+    Ok(report)
+}
+
+async fn encrypt_user_columns(
+    conn: &Connection,
+    key: &FieldKey,
+    user_id: &str,
+    sealed_pattern: &str,
+    report: &mut ColumnEncryptionReport,
+) -> anyhow::Result<()> {
+    // TO_CHAR keeps the exact stored decimal for both sealing and the guard.
+    let rows = conn
+        .query(
+            "SELECT TO_CHAR(agi) FROM users WHERE id = :1 AND agi IS NOT NULL",
+            &crate::oracle_params![user_id.to_string()],
+        )
+        .await?;
+    if let Some(agi) = rows.first().and_then(|row| crate::db::oracle::row_opt_string(row, 0)) {
+        let sealed = key.seal(field_crypto::USER_AGI, Some(agi.clone()))?;
+        let updated = conn
+            .execute(
+                "UPDATE users SET agi = NULL, agi_ciphertext = :1 WHERE id = :2 AND TO_CHAR(agi) = :3",
+                &crate::oracle_params![sealed, user_id.to_string(), agi],
+            )
+            .await?;
+        report.agi_values += updated.rows_affected as usize;
+    }
+
+    let rows = conn
+        .query(
+            "SELECT id, notes FROM donations WHERE user_id = :1 AND notes NOT LIKE :2",
+            &crate::oracle_params![user_id.to_string(), sealed_pattern.to_string()],
+        )
+        .await?;
+    for row in &rows.rows {
+        let id = crate::db::oracle::row_string(row, 0);
+        let notes = crate::db::oracle::row_opt_string(row, 1);
+        let sealed = key.seal(field_crypto::DONATION_NOTES, notes.clone())?;
+        let updated = conn
+            .execute(
+                "UPDATE donations SET notes = :1 WHERE id = :2 AND notes = :3",
+                &crate::oracle_params![sealed, id, notes],
+            )
+            .await?;
+        report.donation_notes += updated.rows_affected as usize;
+    }
+
+    let rows = conn
+        .query(
+            "SELECT id, street, city, state, zip FROM charities WHERE user_id = :1 AND (street NOT LIKE :2 OR city NOT LIKE :2 OR state NOT LIKE :2 OR zip NOT LIKE :2)",
+            &crate::oracle_params![user_id.to_string(), sealed_pattern.to_string()],
+        )
+        .await?;
+    for row in &rows.rows {
+        let id = crate::db::oracle::row_string(row, 0);
+        let current: Vec<Option<String>> = (1..=4).map(|i| crate::db::oracle::row_opt_string(row, i)).collect();
+        let columns = [
+            field_crypto::CHARITY_STREET,
+            field_crypto::CHARITY_CITY,
+            field_crypto::CHARITY_STATE,
+            field_crypto::CHARITY_ZIP,
+        ];
+        let mut sealed = Vec::with_capacity(4);
+        for (column, value) in columns.into_iter().zip(&current) {
+            sealed.push(match value {
+                Some(value) if field_crypto::is_sealed(value) => Some(value.clone()),
+                value => key.seal(column, value.clone())?,
+            });
+        }
+        // DECODE treats two NULLs as equal, unlike `=`.
+        let updated = conn
+            .execute(
+                "UPDATE charities SET street = :1, city = :2, state = :3, zip = :4 WHERE id = :5 AND DECODE(street, :6, 1, 0) = 1 AND DECODE(city, :7, 1, 0) = 1 AND DECODE(state, :8, 1, 0) = 1 AND DECODE(zip, :9, 1, 0) = 1",
+                &crate::oracle_params![
+                    sealed[0].clone(),
+                    sealed[1].clone(),
+                    sealed[2].clone(),
+                    sealed[3].clone(),
+                    id,
+                    current[0].clone(),
+                    current[1].clone(),
+                    current[2].clone(),
+                    current[3].clone(),
+                ],
+            )
+            .await?;
+        report.charity_addresses += updated.rows_affected as usize;
+    }
+    Ok(())
+}
@@ -13,6 +13,8 @@ pub(crate) async fn add_donation(
     input: &NewDonation,
     created_at: &str,
 ) -> anyhow::Result<()> {
+    let key = crate::db::oracle::data_keys::for_write(pool, &input.user_id).await?;
+    let notes = key.seal(crate::db::field_crypto::DONATION_NOTES, input.notes.clone())?;
     let conn = pool.get().await?;
     let donation_date = input.date.format("%Y-%m-%d").to_string();
     let is_encrypted = input.is_encrypted.map(|v| if v { 1 } else { 0 });
@@ -27,13 +29,24 @@ pub(crate) async fn add_donation(
             input.category.clone(),
             input.amount,
             input.charity_id.clone(),
-            input.notes.clone(),
+            notes,
             is_encrypted,
             input.encrypted_payload.clone(),
             created_at.to_string(),
         ],
     )
     .await?;
+    if let Some(tokens) = &input.blind_index {
+        crate::db::oracle::blind_index::replace_donation_tokens(&conn, &input.user_id, &input.id, tokens).await?;
+    }
+    crate::db::oracle::sync_changes::record_change(
+        &conn,
+        &input.user_id,
+        "donations",
+        &input.id,
+        "upsert",
+    )
+    .await?;
     conn.commit().await?;
     Ok(())
 }
@@ -43,6 +56,7 @@ pub(crate) async fn list_donations(
     user_id: &str,
     year: Option<i32>,
 ) -> anyhow::Result<Vec<DonationModel>> {
+    let key = crate::db::oracle::data_keys::for_read(pool, user_id).await?;
     let conn = pool.get().await?;
     let sql = if year.is_some() {
         "SELECT d.id, d.user_id, d.donation_year, d.donation_date, d.donation_category, d.donation_amount, d.charity_id, c.name, c.ein, d.notes, d.created_at, d.updated_at, d.is_encrypted, d.encrypted_payload FROM donations d JOIN charities c ON c.id = d.charity_id WHERE d.user_id = :1 AND d.donation_year = :2 AND d.deleted = 0"
@@ -58,7 +72,7 @@ pub(crate) async fn list_donations(
     };
     let mut out = Vec::new();
     for row in &rows.rows {
-        out.push(DonationModel {
+        out.push(key.open_donation(DonationModel {
             id: crate::db::oracle::row_string(row, 0),
             user_id: crate::db::oracle::row_string(row, 1),
             year: crate::db::oracle::row_i64(row, 2).unwrap_or_default() as i32,
@@ -76,7 +90,7 @@ pub(crate) async fn list_donations(
             created_at: crate::db::oracle::row_datetime_utc(row, 10).unwrap_or_else(Utc::now),
             updated_at: crate::db::oracle::row_datetime_utc(row, 11).unwrap_or_else(Utc::now),
             deleted: false,
-        });
+        })?);
     }
     Ok(out)
 }
@@ -86,6 +100,7 @@ pub(crate) async fn list_donations_since(
     user_id: &str,
     since: &str,
 ) -> anyhow::Result<Vec<DonationModel>> {
+    let key = crate::db::oracle::data_keys::for_read(pool, user_id).await?;
     let conn = pool.get().await?;
     let sql = "SELECT d.id, d.user_id, d.donation_year, d.donation_date, d.donation_category, d.donation_amount, d.charity_id, c.name, c.ein, d.notes, d.created_at, d.updated_at, d.deleted, d.is_encrypted, d.encrypted_payload FROM donations d JOIN charities c ON c.id = d.charity_id WHERE d.user_id = :1 AND (d.updated_at > TO_TIMESTAMP_TZ(:2, 'YYYY-MM-DD\"T\"HH24:MI:SS.FF TZH:TZM') OR d.created_at > TO_TIMESTAMP_TZ(:2, 'YYYY-MM-DD\"T\"HH24:MI:SS.FF TZH:TZM'))";
     let rows = conn
@@ -96,7 +111,7 @@ pub(crate) async fn list_donations_since(
         .await?;
     let mut out = Vec::new();
     for row in &rows.rows {
-        out.push(DonationModel {
+        out.push(key.open_donation(DonationModel {
             id: crate::db::oracle::row_string(row, 0),
             user_id: crate::db::oracle::row_string(row, 1),
             year: crate::db::oracle::row_i64(row, 2).unwrap_or_default() as i32,
@@ -114,7 +129,7 @@ pub(crate) async fn list_donations_since(
             created_at: crate::db::oracle::row_datetime_utc(row, 10).unwrap_or_else(Utc::now),
             updated_at: crate::db::oracle::row_datetime_utc(row, 11).unwrap_or_else(Utc::now),
             deleted: crate::db::oracle::row_bool(row, 12).unwrap_or(false),
-        });
+        })?);
     }
     Ok(out)
 }
@@ -0,0 +1,99 @@
+use deadpool_oracle::Pool;
+
+use crate::db::models::{IdempotencyClaim, StoredResponse};
+
+// A claim with no stored response after this long belongs to a request that died mid-flight.
+const ABANDONED_CLAIM_SECS: i64 = 120;
+
+pub(crate) async fn claim_key(
+    pool: &Pool,
+    user_id: &str,
+    key: &str,
+    request_hash: &str,
+    ttl_secs: i64,
+) -> anyhow::Result<IdempotencyClaim> {
+    let conn = pool.get().await?;
+    conn.execute(
+        "DELETE FROM idempotency_keys WHERE user_id = :1 AND (expires_at < SYSTIMESTAMP OR (status_code IS NULL AND created_at < SYSTIMESTAMP - NUMTODSINTERVAL(:2, 'SECOND')))",
+        &crate::oracle_params![user_id.to_string(), ABANDONED_CLAIM_SECS],
+    )
+    .await?;
+
+    let inserted = conn
+        .execute(
+            "INSERT INTO idempotency_keys (user_id, idem_key, request_hash, created_at, expires_at) VALUES (:1, :2, :3, SYSTIMESTAMP, SYSTIMESTAMP + NUMTODSINTERVAL(:4, 'SECOND'))",
+            &crate::oracle_params![
+                user_id.to_string(),
+                key.to_string(),
+                request_hash.to_string(),
+                ttl_secs,
+            ],
+        )
+        .await;
+    match inserted {
+        Ok(_) => {
+            conn.commit().await?;
+            return Ok(IdempotencyClaim::Claimed);
+        }
+        Err(e) if e.to_string().contains("ORA-00001") => {}
+        Err(e) => return Err(e.into()),
+    }
+    // Keep the expiry cleanup even though the insert lost the race.
+    conn.commit().await?;
+
+    let rows = conn
+        .query(
+            "SELECT request_hash, status_code, content_type, response_body FROM idempotency_keys WHERE user_id = :1 AND idem_key = :2",
+            &crate::oracle_params![user_id.to_string(), key.to_string()],
+        )
+        .await?;
+    let Some(row) = rows.first() else {
+        // Released between our insert and select; let the client retry.
+        return Ok(IdempotencyClaim::InProgress);
+    };
+
+    if crate::db::oracle::row_string(row, 0) != request_hash {
+        return Ok(IdempotencyClaim::Mismatch);
+    }
+    match crate::db::oracle::row_i64(row, 1) {
+        Some(status_code) => Ok(IdempotencyClaim::Replay(StoredResponse {
+            status_code: status_code as u16,
+            content_type: crate::db::oracle::row_opt_string(row, 2),
+            body: crate::db::oracle::row_opt_string(row, 3).unwrap_or_default(),
+        })),
+        None => Ok(IdempotencyClaim::InProgress),
+    }
+}
+
+pub(crate) async fn store_response(
+    pool: &Pool,
+    user_id: &str,
+    key: &str,
+    response: &StoredResponse,
+) -> anyhow::Result<()> {
+    let conn = pool.get().await?;
+    conn.execute(
+        "UPDATE idempotency_keys SET status_code = :1, content_type = :2, response_body = :3 WHERE user_id = :4 AND idem_key = :5",
+        &crate::oracle_params![
+            response.status_code as i64,
+            response.content_type.clone(),
+            response.body.clone(),
+            user_id.to_string(),
+            key.to_string(),
+        ],
+    )
+    .await?;
+    conn.commit().await?;
+    Ok(())
+}
+
+pub(crate) async fn release_key(pool: &Pool, user_id: &str, key: &str) -> anyhow::Result<()> {
+    let conn = pool.get().await?;
+    conn.execute(
+        "DELETE FROM idempotency_keys WHERE user_id = :1 AND idem_key = :2 AND status_code IS NULL",
+        &crate::oracle_params![user_id.to_string(), key.to_string()],
+    )
+    .await?;
+    conn.commit().await?;
+    Ok(())
+}
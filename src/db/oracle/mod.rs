@@ -13,15 +13,30 @@ use crate::db::models::UserProfileUpsert;
 
 mod bootstrap;
 mod row_helpers;
+pub(crate) mod access_tokens;
+pub(crate) mod accountant_grants;
+pub(crate) mod account_security;
+pub(crate) mod blind_index;
 pub(crate) mod charities;
+pub(crate) mod data_keys;
+pub(crate) mod idempotency;
 pub mod donations;
+pub(crate) mod passkeys;
+pub(crate) mod receipt_uploads;
 pub(crate) mod receipts;
+pub(crate) mod refresh_tokens;
+pub(crate) mod revocations;
+pub(crate) mod sessions;
+pub(crate) mod sync_changes;
+pub(crate) mod vault_rotations;
 mod wallet_config;
 
 pub(crate) use row_helpers::{
     parse_utc_from_opt_string, row_bool, row_datetime_utc, row_f64, row_i64, row_naive_date,
     row_opt_string, row_string,
 };
+pub use bootstrap::apply_bootstrap_ddl;
+pub use data_keys::{encrypt_existing_columns, ColumnEncryptionReport};
 use bootstrap::run_bootstrap_ddl;
 use wallet_config::validate_wallet_password;
 
@@ -169,6 +184,12 @@ pub(crate) async fn init_pool(
 
     run_bootstrap_ddl(&pool).await?;
 
+    if data_keys::master_key()?.is_some() {
+        eprintln!("[DB] Server-side column encryption is enabled");
+    } else if runtime_mode == RuntimeMode::Production {
+        eprintln!("[DB] WARNING: DATA_ENCRYPTION_KEY is not set; notes, AGI and charity addresses are stored in plaintext");
+    }
+
     eprintln!("[DB] Pool created successfully (Oracle)");
     Ok(Arc::new(DbPoolEnum::Oracle(pool)))
 }
@@ -178,26 +199,27 @@ pub(crate) async fn get_user_profile_by_email(
     email: &str,
 ) -> anyhow::Result<Option<(String, UserProfileRow)>> {
     let conn = pool.get().await?;
-    let sql = "SELECT id, email, name, provider, filing_status, agi, marginal_tax_rate, itemize_deductions, is_encrypted, encrypted_payload, vault_credential_id FROM users WHERE email = :1";
+    let sql = "SELECT id, email, name, provider, filing_status, agi, marginal_tax_rate, itemize_deductions, is_encrypted, encrypted_payload, vault_credential_id, agi_ciphertext FROM users WHERE email = :1";
     let rows = conn
         .query(sql, &crate::oracle_params![email.to_string()])
         .await?;
+    drop(conn);
     let Some(row) = rows.first() else {
         return Ok(None);
     };
 
-    Ok(Some((
-        row_string(row, 0),
-        user_profile_row_from_row(row, 1),
-    )))
+    let user_id = row_string(row, 0);
+    let key = data_keys::for_read(pool, &user_id).await?;
+    Ok(Some((user_id, user_profile_row_from_row(row, 1, &key)?)))
 }
 
 pub(crate) async fn get_user_profile(
     pool: &Pool,
     user_id: &str,
 ) -> anyhow::Result<Option<UserProfileRow>> {
+    let key = data_keys::for_read(pool, user_id).await?;
     let conn = pool.get().await?;
-    let sql = "SELECT email, name, provider, filing_status, agi, marginal_tax_rate, itemize_deductions, is_encrypted, encrypted_payload, vault_credential_id FROM users WHERE id = :1";
+    let sql = "SELECT email, name, provider, filing_status, agi, marginal_tax_rate, itemize_deductions, is_encrypted, encrypted_payload, vault_credential_id, agi_ciphertext FROM users WHERE id = :1";
     let rows = conn
         .query(sql, &crate::oracle_params![user_id.to_string()])
         .await?;
@@ -205,13 +227,15 @@ pub(crate) async fn get_user_profile(
         return Ok(None);
     };
 
-    Ok(Some(user_profile_row_from_row(row, 0)))
+    user_profile_row_from_row(row, 0, &key).map(Some)
 }
 
 pub(crate) async fn upsert_user_profile(
     pool: &Pool,
     input: &UserProfileUpsert,
 ) -> anyhow::Result<()> {
+    let key = data_keys::for_write(pool, &input.user_id).await?;
+    let (agi, agi_ciphertext) = key.seal_agi(input.agi)?;
     let conn = pool.get().await?;
     let itemize_deductions = input
         .itemize_deductions
@@ -219,7 +243,7 @@ pub(crate) async fn upsert_user_profile(
     let is_encrypted = input
         .is_encrypted
         .map(|value| if value { 1 } else { 0 });
-    let sql = "MERGE INTO users u USING (SELECT :1 AS id, :2 AS email, :3 AS name, :4 AS provider, :5 AS filing_status, :6 AS agi, :7 AS marginal_tax_rate, :8 AS itemize_deductions, :9 AS is_encrypted, :10 AS encrypted_payload, :11 AS vault_credential_id FROM dual) s ON (u.id = s.id) WHEN MATCHED THEN UPDATE SET u.email = s.email, u.name = s.name, u.provider = s.provider, u.filing_status = s.filing_status, u.agi = s.agi, u.marginal_tax_rate = s.marginal_tax_rate, u.itemize_deductions = s.itemize_deductions, u.is_encrypted = s.is_encrypted, u.encrypted_payload = s.encrypted_payload, u.vault_credential_id = s.vault_credential_id WHEN NOT MATCHED THEN INSERT (id, email, name, provider, filing_status, agi, marginal_tax_rate, itemize_deductions, is_encrypted, encrypted_payload, vault_credential_id) VALUES (s.id, s.email, s.name, s.provider, s.filing_status, s.agi, s.marginal_tax_rate, s.itemize_deductions, s.is_encrypted, s.encrypted_payload, s.vault_credential_id)";
+    let sql = "MERGE INTO users u USING (SELECT :1 AS id, :2 AS email, :3 AS name, :4 AS provider, :5 AS filing_status, :6 AS agi, :7 AS marginal_tax_rate, :8 AS itemize_deductions, :9 AS is_encrypted, :10 AS encrypted_payload, :11 AS vault_credential_id, :12 AS agi_ciphertext FROM dual) s ON (u.id = s.id) WHEN MATCHED THEN UPDATE SET u.email = s.email, u.name = s.name, u.provider = s.provider, u.filing_status = s.filing_status, u.agi = s.agi, u.agi_ciphertext = s.agi_ciphertext, u.marginal_tax_rate = s.marginal_tax_rate, u.itemize_deductions = s.itemize_deductions, u.is_encrypted = s.is_encrypted, u.encrypted_payload = s.encrypted_payload, u.vault_credential_id = s.vault_credential_id WHEN NOT MATCHED THEN INSERT (id, email, name, provider, filing_status, agi, agi_ciphertext, marginal_tax_rate, itemize_deductions, is_encrypted, encrypted_payload, vault_credential_id) VALUES (s.id, s.email, s.name, s.provider, s.filing_status, s.agi, s.agi_ciphertext, s.marginal_tax_rate, s.itemize_deductions, s.is_encrypted, s.encrypted_payload, s.vault_credential_id)";
     conn.execute(
         sql,
         &crate::oracle_params![
@@ -228,15 +252,17 @@ pub(crate) async fn upsert_user_profile(
             input.name.clone(),
             input.provider.clone(),
             input.filing_status.clone(),
-            input.agi,
+            agi,
             input.marginal_tax_rate,
             itemize_deductions,
             is_encrypted,
             input.encrypted_payload.clone(),
             input.vault_credential_id.clone(),
+            agi_ciphertext,
         ],
     )
     .await?;
+    sync_changes::record_change(&conn, &input.user_id, "users", &input.user_id, "upsert").await?;
     conn.commit().await?;
     Ok(())
 }
@@ -249,6 +275,11 @@ pub(crate) async fn delete_user_data(pool: &Pool, user_id: &str) -> anyhow::Resu
         &crate::oracle_params![user_id.to_string()],
     )
     .await?;
+    conn.execute(
+        "DELETE FROM donation_index_tokens WHERE user_id = :1",
+        &crate::oracle_params![user_id.to_string()],
+    )
+    .await?;
     conn.execute(
         "DELETE FROM donations WHERE user_id = :1",
         &crate::oracle_params![user_id.to_string()],
@@ -269,6 +300,66 @@ pub(crate) async fn delete_user_data(pool: &Pool, user_id: &str) -> anyhow::Resu
         &crate::oracle_params![user_id.to_string()],
     )
     .await?;
+    conn.execute(
+        "DELETE FROM sync_changes WHERE user_id = :1",
+        &crate::oracle_params![user_id.to_string()],
+    )
+    .await?;
+    conn.execute(
+        "DELETE FROM idempotency_keys WHERE user_id = :1",
+        &crate::oracle_params![user_id.to_string()],
+    )
+    .await?;
+    conn.execute(
+        "DELETE FROM personal_access_tokens WHERE user_id = :1",
+        &crate::oracle_params![user_id.to_string()],
+    )
+    .await?;
+    conn.execute(
+        "DELETE FROM webauthn_credentials WHERE user_id = :1",
+        &crate::oracle_params![user_id.to_string()],
+    )
+    .await?;
+    conn.execute(
+        "DELETE FROM webauthn_challenges WHERE user_id = :1",
+        &crate::oracle_params![user_id.to_string()],
+    )
+    .await?;
+    conn.execute(
+        "DELETE FROM user_identities WHERE user_id = :1",
+        &crate::oracle_params![user_id.to_string()],
+    )
+    .await?;
+    conn.execute(
+        "DELETE FROM user_sessions WHERE user_id = :1",
+        &crate::oracle_params![user_id.to_string()],
+    )
+    .await?;
+    conn.execute(
+        "DELETE FROM refresh_tokens WHERE user_id = :1",
+        &crate::oracle_params![user_id.to_string()],
+    )
+    .await?;
+    conn.execute(
+        "DELETE FROM accountant_grants WHERE owner_user_id = :1",
+        &crate::oracle_params![user_id.to_string()],
+    )
+    .await?;
+    conn.execute(
+        "DELETE FROM receipt_uploads WHERE user_id = :1",
+        &crate::oracle_params![user_id.to_string()],
+    )
+    .await?;
+    conn.execute(
+        "DELETE FROM vault_key_rotations WHERE user_id = :1",
+        &crate::oracle_params![user_id.to_string()],
+    )
+    .await?;
+    conn.execute(
+        "DELETE FROM user_data_keys WHERE user_id = :1",
+        &crate::oracle_params![user_id.to_string()],
+    )
+    .await?;
     conn.execute(
         "DELETE FROM users WHERE id = :1",
         &crate::oracle_params![user_id.to_string()],
@@ -276,6 +367,7 @@ pub(crate) async fn delete_user_data(pool: &Pool, user_id: &str) -> anyhow::Resu
     .await?;
 
     conn.commit().await?;
+    data_keys::forget(user_id);
     Ok(())
 }
 
@@ -484,6 +576,8 @@ pub(crate) async fn write_clob_by_id(
     Ok(())
 }
 
+/// Maps the profile columns starting at `offset`; `agi_ciphertext` follows them.
+#[allow(clippy::too_many_arguments)]
 fn user_profile_row_from_parts(
     email: String,
     name: String,
@@ -508,21 +602,21 @@ fn user_profile_row_from_parts(
     )
 }
 
-fn user_profile_row_from_row(row: &Row, offset: usize) -> UserProfileRow {
-    user_profile_row_from_parts(
+fn user_profile_row_from_row(row: &Row, offset: usize, key: &data_keys::FieldKey) -> anyhow::Result<UserProfileRow> {
+    Ok(user_profile_row_from_parts(
         row_string(row, offset),
         row_string(row, offset + 1),
         row_opt_string(row, offset + 2).unwrap_or_else(|| "local".to_string()),
         row_opt_string(row, offset + 3),
         UserProfileNumericFields {
-            agi: row_f64(row, offset + 4),
+            agi: key.open_agi(row_f64(row, offset + 4), row_opt_string(row, offset + 10))?,
             marginal_tax_rate: row_f64(row, offset + 5),
             itemize_deductions: row_bool(row, offset + 6),
         },
         row_bool(row, offset + 7),
         row_opt_string(row, offset + 8),
         row_opt_string(row, offset + 9),
-    )
+    ))
 }
 
 struct UserProfileNumericFields {
@@ -0,0 +1,171 @@
+use deadpool_oracle::Pool;
+
+use crate::db::models::{
+    NewPasskeyCredential, NewWebauthnChallenge, PasskeyCredential, StoredPasskey, WebauthnChallenge,
+};
+
+fn split_transports(value: Option<String>) -> Vec<String> {
+    value
+        .unwrap_or_default()
+        .split(',')
+        .filter(|t| !t.is_empty())
+        .map(str::to_string)
+        .collect()
+}
+
+pub(crate) async fn create_challenge(pool: &Pool, input: &NewWebauthnChallenge) -> anyhow::Result<()> {
+    let conn = pool.get().await?;
+    // Abandoned ceremonies are cleaned up whenever a new one starts.
+    conn.execute(
+        "DELETE FROM webauthn_challenges WHERE expires_at <= SYSTIMESTAMP",
+        &[],
+    )
+    .await?;
+    conn.execute(
+        "INSERT INTO webauthn_challenges (id, user_id, purpose, challenge, created_at, expires_at) VALUES (:1, :2, :3, :4, SYSTIMESTAMP, SYSTIMESTAMP + NUMTODSINTERVAL(:5, 'SECOND'))",
+        &crate::oracle_params![
+            input.id.clone(),
+            input.user_id.clone(),
+            input.purpose.clone(),
+            input.challenge.clone(),
+            input.ttl_seconds,
+        ],
+    )
+    .await?;
+    conn.commit().await?;
+    Ok(())
+}
+
+/// Consumes a challenge: it is deleted whether or not it is still valid, and
+/// only the caller whose DELETE removed the row gets it back.
+pub(crate) async fn take_challenge(pool: &Pool, id: &str, purpose: &str) -> anyhow::Result<Option<WebauthnChallenge>> {
+    let conn = pool.get().await?;
+    let rows = conn
+        .query(
+            "SELECT user_id, challenge FROM webauthn_challenges WHERE id = :1 AND purpose = :2 AND expires_at > SYSTIMESTAMP",
+            &crate::oracle_params![id.to_string(), purpose.to_string()],
+        )
+        .await?;
+    let found = rows.first().map(|row| WebauthnChallenge {
+        user_id: crate::db::oracle::row_opt_string(row, 0),
+        challenge: crate::db::oracle::row_string(row, 1),
+    });
+
+    let deleted = conn
+        .execute(
+            "DELETE FROM webauthn_challenges WHERE id = :1",
+            &crate::oracle_params![id.to_string()],
+        )
+        .await?;
+    conn.commit().await?;
+
+    if deleted.rows_affected == 0 {
+        return Ok(None);
+    }
+    Ok(found)
+}
+
+/// Returns false when the credential id is already registered.
+pub(crate) async fn insert_credential(pool: &Pool, input: &NewPasskeyCredential) -> anyhow::Result<bool> {
+    let conn = pool.get().await?;
+    let transports = if input.transports.is_empty() {
+        None
+    } else {
+        Some(input.transports.join(","))
+    };
+    match conn
+        .execute(
+            "INSERT INTO webauthn_credentials (id, user_id, name, public_key, algorithm, sign_count, transports, created_at) VALUES (:1, :2, :3, :4, :5, :6, :7, SYSTIMESTAMP)",
+            &crate::oracle_params![
+                input.id.clone(),
+                input.user_id.clone(),
+                input.name.clone(),
+                input.public_key.clone(),
+                input.algorithm,
+                input.sign_count,
+                transports,
+            ],
+        )
+        .await
+    {
+        Ok(_) => {}
+        Err(e) if e.to_string().contains("ORA-00001") => return Ok(false),
+        Err(e) => return Err(e.into()),
+    }
+    conn.commit().await?;
+    Ok(true)
+}
+
+pub(crate) async fn list_credentials(pool: &Pool, user_id: &str) -> anyhow::Result<Vec<PasskeyCredential>> {
+    let conn = pool.get().await?;
+    let rows = conn
+        .query(
+            "SELECT id, name, algorithm, transports, created_at, last_used_at FROM webauthn_credentials WHERE user_id = :1 ORDER BY created_at DESC",
+            &crate::oracle_params![user_id.to_string()],
+        )
+        .await?;
+
+    Ok(rows
+        .rows
+        .iter()
+        .map(|row| PasskeyCredential {
+            id: crate::db::oracle::row_string(row, 0),
+            name: crate::db::oracle::row_string(row, 1),
+            algorithm: crate::db::oracle::row_i64(row, 2).unwrap_or_default(),
+            transports: split_transports(crate::db::oracle::row_opt_string(row, 3)),
+            created_at: crate::db::oracle::row_datetime_utc(row, 4).unwrap_or_else(chrono::Utc::now),
+            last_used_at: crate::db::oracle::row_datetime_utc(row, 5),
+        })
+        .collect())
+}
+
+pub(crate) async fn find_credential(pool: &Pool, credential_id: &str) -> anyhow::Result<Option<StoredPasskey>> {
+    let conn = pool.get().await?;
+    let rows = conn
+        .query(
+            "SELECT id, user_id, public_key, sign_count FROM webauthn_credentials WHERE id = :1",
+            &crate::oracle_params![credential_id.to_string()],
+        )
+        .await?;
+    let Some(row) = rows.first() else {
+        return Ok(None);
+    };
+
+    Ok(Some(StoredPasskey {
+        id: crate::db::oracle::row_string(row, 0),
+        user_id: crate::db::oracle::row_string(row, 1),
+        public_key: crate::db::oracle::row_string(row, 2),
+        sign_count: crate::db::oracle::row_i64(row, 3).unwrap_or_default(),
+    }))
+}
+
+/// Compare-and-set on the signature counter so two concurrent uses of the
+/// same assertion cannot both succeed.
+pub(crate) async fn record_assertion(
+    pool: &Pool,
+    credential_id: &str,
+    previous_sign_count: i64,
+    sign_count: i64,
+) -> anyhow::Result<bool> {
+    let conn = pool.get().await?;
+    let result = conn
+        .execute(
+            "UPDATE webauthn_credentials SET sign_count = :1, last_used_at = SYSTIMESTAMP WHERE id = :2 AND sign_count = :3",
+            &crate::oracle_params![sign_count, credential_id.to_string(), previous_sign_count],
+        )
+        .await?;
+    conn.commit().await?;
+    Ok(result.rows_affected > 0)
+}
+
+pub(crate) async fn delete_credential(pool: &Pool, user_id: &str, credential_id: &str) -> anyhow::Result<bool> {
+    let conn = pool.get().await?;
+    let result = conn
+        .execute(
+            "DELETE FROM webauthn_credentials WHERE id = :1 AND user_id = :2",
+            &crate::oracle_params![credential_id.to_string(), user_id.to_string()],
+        )
+        .await?;
+    conn.commit().await?;
+    Ok(result.rows_affected > 0)
+}
@@ -0,0 +1,89 @@
+use chrono::{DateTime, Utc};
+use deadpool_oracle::Pool;
+
+use crate::db::models::ReceiptUpload;
+
+const UPLOAD_COLUMNS: &str = "id, user_id, receipt_key, upload_id, content_type, upload_size, part_size, part_count, created_at";
+
+fn upload_from_row(row: &oracle_rs::Row) -> ReceiptUpload {
+    ReceiptUpload {
+        id: crate::db::oracle::row_string(row, 0),
+        user_id: crate::db::oracle::row_string(row, 1),
+        key: crate::db::oracle::row_string(row, 2),
+        upload_id: crate::db::oracle::row_string(row, 3),
+        content_type: crate::db::oracle::row_opt_string(row, 4),
+        size: crate::db::oracle::row_i64(row, 5).unwrap_or_default(),
+        part_size: crate::db::oracle::row_i64(row, 6).unwrap_or_default(),
+        part_count: crate::db::oracle::row_i64(row, 7).unwrap_or_default(),
+        created_at: crate::db::oracle::row_datetime_utc(row, 8).unwrap_or_else(Utc::now),
+    }
+}
+
+pub(crate) async fn create_upload(pool: &Pool, input: &ReceiptUpload) -> anyhow::Result<()> {
+    let conn = pool.get().await?;
+    conn.execute(
+        "INSERT INTO receipt_uploads (id, user_id, receipt_key, upload_id, content_type, upload_size, part_size, part_count, created_at) VALUES (:1, :2, :3, :4, :5, :6, :7, :8, SYSTIMESTAMP)",
+        &crate::oracle_params![
+            input.id.clone(),
+            input.user_id.clone(),
+            input.key.clone(),
+            input.upload_id.clone(),
+            input.content_type.clone(),
+            input.size,
+            input.part_size,
+            input.part_count,
+        ],
+    )
+    .await?;
+    conn.commit().await?;
+    Ok(())
+}
+
+pub(crate) async fn get_upload(pool: &Pool, user_id: &str, id: &str) -> anyhow::Result<Option<ReceiptUpload>> {
+    let conn = pool.get().await?;
+    let rows = conn
+        .query(
+            &format!("SELECT {UPLOAD_COLUMNS} FROM receipt_uploads WHERE id = :1 AND user_id = :2"),
+            &crate::oracle_params![id.to_string(), user_id.to_string()],
+        )
+        .await?;
+    Ok(rows.first().map(upload_from_row))
+}
+
+pub(crate) async fn list_user_uploads(pool: &Pool, user_id: &str) -> anyhow::Result<Vec<ReceiptUpload>> {
+    let conn = pool.get().await?;
+    let rows = conn
+        .query(
+            &format!("SELECT {UPLOAD_COLUMNS} FROM receipt_uploads WHERE user_id = :1"),
+            &crate::oracle_params![user_id.to_string()],
+        )
+        .await?;
+    Ok(rows.rows.iter().map(upload_from_row).collect())
+}
+
+/// Uploads of any user started before `cutoff`, oldest first.
+pub(crate) async fn list_uploads_started_before(
+    pool: &Pool,
+    cutoff: DateTime<Utc>,
+) -> anyhow::Result<Vec<ReceiptUpload>> {
+    let conn = pool.get().await?;
+    let rows = conn
+        .query(
+            &format!("SELECT {UPLOAD_COLUMNS} FROM receipt_uploads WHERE created_at < TIMESTAMP '1970-01-01 00:00:00 +00:00' + NUMTODSINTERVAL(:1, 'SECOND') ORDER BY created_at"),
+            &crate::oracle_params![cutoff.timestamp()],
+        )
+        .await?;
+    Ok(rows.rows.iter().map(upload_from_row).collect())
+}
+
+pub(crate) async fn delete_upload(pool: &Pool, id: &str) -> anyhow::Result<bool> {
+    let conn = pool.get().await?;
+    let deleted = conn
+        .execute(
+            "DELETE FROM receipt_uploads WHERE id = :1",
+            &crate::oracle_params![id.to_string()],
+        )
+        .await?;
+    conn.commit().await?;
+    Ok(deleted.rows_affected > 0)
+}
@@ -14,8 +14,40 @@ pub(crate) async fn add_receipt(
     created_at: &str,
 ) -> anyhow::Result<()> {
     let conn = pool.get().await?;
+    insert_receipt(&conn, input, created_at).await?;
+    conn.commit().await?;
+    Ok(())
+}
+
+/// Adds a receipt that points at another receipt's object. Under the owner's lock, which
+/// `receipt_key_in_use` also takes, it checks that a receipt still holds `input.key`; if none
+/// does, the object may already be deleted and nothing is added. Returns whether it was added.
+pub(crate) async fn add_shared_receipt(
+    pool: &Pool,
+    user_id: &str,
+    input: &NewReceipt,
+    created_at: &str,
+) -> anyhow::Result<bool> {
+    let conn = pool.get().await?;
+    crate::db::oracle::sync_changes::lock_user_feed(&conn, user_id).await?;
+    let holders = conn
+        .query(
+            "SELECT 1 FROM receipts WHERE receipt_key = :1 FETCH FIRST 1 ROWS ONLY",
+            &crate::oracle_params![input.key.clone()],
+        )
+        .await?;
+    if holders.first().is_none() {
+        conn.rollback().await?;
+        return Ok(false);
+    }
+    insert_receipt(&conn, input, created_at).await?;
+    conn.commit().await?;
+    Ok(true)
+}
+
+async fn insert_receipt(conn: &oracle_rs::Connection, input: &NewReceipt, created_at: &str) -> anyhow::Result<()> {
     let is_encrypted = input.is_encrypted.map(|v| if v { 1 } else { 0 });
-    let sql = "INSERT INTO receipts (id, donation_id, receipt_key, file_name, content_type, receipt_size, ocr_text, ocr_date, ocr_amount, ocr_status, is_encrypted, encrypted_payload, created_at) VALUES (:1,:2,:3,:4,:5,:6,:7,:8,:9,:10,:11,:12, TO_TIMESTAMP_TZ(:13, 'YYYY-MM-DD\"T\"HH24:MI:SS.FF TZH:TZM'))";
+    let sql = "INSERT INTO receipts (id, donation_id, receipt_key, file_name, content_type, receipt_size, ocr_text, ocr_date, ocr_amount, ocr_status, is_encrypted, encrypted_payload, content_hash, created_at) VALUES (:1,:2,:3,:4,:5,:6,:7,:8,:9,:10,:11,:12,:13, TO_TIMESTAMP_TZ(:14, 'YYYY-MM-DD\"T\"HH24:MI:SS.FF TZH:TZM'))";
     conn.execute(
         sql,
         &crate::oracle_params![
@@ -31,11 +63,12 @@ pub(crate) async fn add_receipt(
             Option::<String>::None,
             is_encrypted,
             input.encrypted_payload.clone(),
+            input.content_hash.clone(),
             created_at.to_string(),
         ],
     )
     .await?;
-    conn.commit().await?;
+    crate::db::oracle::sync_changes::record_receipt_change(conn, &input.id, "upsert").await?;
     Ok(())
 }
 
@@ -46,9 +79,9 @@ pub(crate) async fn list_receipts(
 ) -> anyhow::Result<Vec<Receipt>> {
     let conn = pool.get().await?;
     let sql = if donation_id.is_some() {
-        "SELECT r.id, r.donation_id, r.receipt_key, r.file_name, r.content_type, r.receipt_size, r.ocr_text, r.ocr_date, r.ocr_amount, r.ocr_status, r.created_at, r.is_encrypted, r.encrypted_payload FROM receipts r JOIN donations d ON d.id = r.donation_id WHERE d.user_id = :1 AND r.donation_id = :2 AND d.deleted = 0"
+        "SELECT r.id, r.donation_id, r.receipt_key, r.file_name, r.content_type, r.receipt_size, r.ocr_text, r.ocr_date, r.ocr_amount, r.ocr_status, r.created_at, r.is_encrypted, r.encrypted_payload, r.content_hash FROM receipts r JOIN donations d ON d.id = r.donation_id WHERE d.user_id = :1 AND r.donation_id = :2 AND d.deleted = 0"
     } else {
-        "SELECT r.id, r.donation_id, r.receipt_key, r.file_name, r.content_type, r.receipt_size, r.ocr_text, r.ocr_date, r.ocr_amount, r.ocr_status, r.created_at, r.is_encrypted, r.encrypted_payload FROM receipts r JOIN donations d ON d.id = r.donation_id WHERE d.user_id = :1 AND d.deleted = 0"
+        "SELECT r.id, r.donation_id, r.receipt_key, r.file_name, r.content_type, r.receipt_size, r.ocr_text, r.ocr_date, r.ocr_amount, r.ocr_status, r.created_at, r.is_encrypted, r.encrypted_payload, r.content_hash FROM receipts r JOIN donations d ON d.id = r.donation_id WHERE d.user_id = :1 AND d.deleted = 0"
     };
     let rows = if let Some(donation_id) = donation_id {
         conn.query(
@@ -76,6 +109,7 @@ pub(crate) async fn list_receipts(
             ocr_status: crate::db::oracle::row_opt_string(row, 9),
             is_encrypted: crate::db::oracle::row_bool(row, 11),
             encrypted_payload: crate::db::oracle::row_opt_string(row, 12),
+            content_hash: crate::db::oracle::row_opt_string(row, 13),
             created_at: crate::db::oracle::row_datetime_utc(row, 10).unwrap_or_else(Utc::now),
         });
     }
@@ -89,9 +123,9 @@ pub(crate) async fn list_receipt_summaries(
 ) -> anyhow::Result<Vec<Receipt>> {
     let conn = pool.get().await?;
     let sql = if donation_id.is_some() {
-        "SELECT r.id, r.donation_id, r.receipt_key, r.file_name, r.content_type, r.receipt_size, r.created_at, r.is_encrypted, r.encrypted_payload FROM receipts r JOIN donations d ON d.id = r.donation_id WHERE d.user_id = :1 AND r.donation_id = :2 AND d.deleted = 0"
+        "SELECT r.id, r.donation_id, r.receipt_key, r.file_name, r.content_type, r.receipt_size, r.created_at, r.is_encrypted, r.encrypted_payload, r.content_hash FROM receipts r JOIN donations d ON d.id = r.donation_id WHERE d.user_id = :1 AND r.donation_id = :2 AND d.deleted = 0"
     } else {
-        "SELECT r.id, r.donation_id, r.receipt_key, r.file_name, r.content_type, r.receipt_size, r.created_at, r.is_encrypted, r.encrypted_payload FROM receipts r JOIN donations d ON d.id = r.donation_id WHERE d.user_id = :1 AND d.deleted = 0"
+        "SELECT r.id, r.donation_id, r.receipt_key, r.file_name, r.content_type, r.receipt_size, r.created_at, r.is_encrypted, r.encrypted_payload, r.content_hash FROM receipts r JOIN donations d ON d.id = r.donation_id WHERE d.user_id = :1 AND d.deleted = 0"
     };
     let rows = if let Some(donation_id) = donation_id {
         conn.query(
@@ -119,6 +153,7 @@ pub(crate) async fn list_receipt_summaries(
             ocr_status: None,
             is_encrypted: crate::db::oracle::row_bool(row, 7),
             encrypted_payload: crate::db::oracle::row_opt_string(row, 8),
+            content_hash: crate::db::oracle::row_opt_string(row, 9),
             created_at: crate::db::oracle::row_datetime_utc(row, 6).unwrap_or_else(Utc::now),
         });
     }
@@ -131,7 +166,7 @@ pub(crate) async fn get_receipt(
     receipt_id: &str,
 ) -> anyhow::Result<Option<Receipt>> {
     let conn = pool.get().await?;
-    let sql = "SELECT r.id, r.donation_id, r.receipt_key, r.file_name, r.content_type, r.receipt_size, r.ocr_text, r.ocr_date, r.ocr_amount, r.ocr_status, r.created_at, r.is_encrypted, r.encrypted_payload FROM receipts r JOIN donations d ON d.id = r.donation_id WHERE d.user_id = :1 AND r.id = :2";
+    let sql = "SELECT r.id, r.donation_id, r.receipt_key, r.file_name, r.content_type, r.receipt_size, r.ocr_text, r.ocr_date, r.ocr_amount, r.ocr_status, r.created_at, r.is_encrypted, r.encrypted_payload, r.content_hash FROM receipts r JOIN donations d ON d.id = r.donation_id WHERE d.user_id = :1 AND r.id = :2";
     let rows = conn
         .query(
             sql,
@@ -151,6 +186,7 @@ pub(crate) async fn get_receipt(
         ocr_status: crate::db::oracle::row_opt_string(r, 9),
         is_encrypted: crate::db::oracle::row_bool(r, 11),
         encrypted_payload: crate::db::oracle::row_opt_string(r, 12),
+        content_hash: crate::db::oracle::row_opt_string(r, 13),
         created_at: crate::db::oracle::row_datetime_utc(r, 10)
             .unwrap_or_else(|| parse_utc_or_now(None)),
     });
@@ -189,7 +225,95 @@ pub(crate) async fn set_receipt_ocr(
         ],
     )
     .await?;
+    crate::db::oracle::sync_changes::record_receipt_change(&conn, id, "upsert").await?;
+
+    conn.commit().await?;
+    Ok(())
+}
+
+/// Storage keys of every receipt row, for diffing against the bucket.
+pub(crate) async fn list_all_receipt_keys(pool: &Pool) -> anyhow::Result<Vec<String>> {
+    let conn = pool.get().await?;
+    let rows = conn.query("SELECT receipt_key FROM receipts", &[]).await?;
+    Ok(rows
+        .rows
+        .iter()
+        .map(|row| crate::db::oracle::row_string(row, 0))
+        .filter(|key| !key.is_empty())
+        .collect())
+}
+
+/// The user's receipts on live donations whose stored object has `content_hash`.
+pub(crate) async fn find_receipts_by_content_hash(
+    pool: &Pool,
+    user_id: &str,
+    content_hash: &str,
+) -> anyhow::Result<Vec<Receipt>> {
+    let conn = pool.get().await?;
+    let sql = "SELECT r.id, r.donation_id, r.receipt_key, r.file_name, r.content_type, r.receipt_size, r.created_at, r.is_encrypted, r.encrypted_payload, r.content_hash FROM receipts r JOIN donations d ON d.id = r.donation_id WHERE d.user_id = :1 AND r.content_hash = :2 AND d.deleted = 0 ORDER BY r.created_at";
+    let rows = conn
+        .query(
+            sql,
+            &crate::oracle_params![user_id.to_string(), content_hash.to_string()],
+        )
+        .await?;
+    Ok(rows
+        .rows
+        .iter()
+        .map(|row| Receipt {
+            id: crate::db::oracle::row_string(row, 0),
+            donation_id: crate::db::oracle::row_string(row, 1),
+            key: crate::db::oracle::row_string(row, 2),
+            file_name: crate::db::oracle::row_opt_string(row, 3),
+            content_type: crate::db::oracle::row_opt_string(row, 4),
+            size: crate::db::oracle::row_i64(row, 5),
+            ocr_text: None,
+            ocr_date: None,
+            ocr_amount: None,
+            ocr_status: None,
+            is_encrypted: crate::db::oracle::row_bool(row, 7),
+            encrypted_payload: crate::db::oracle::row_opt_string(row, 8),
+            content_hash: crate::db::oracle::row_opt_string(row, 9),
+            created_at: crate::db::oracle::row_datetime_utc(row, 6).unwrap_or_else(Utc::now),
+        })
+        .collect())
+}
 
+/// Content hash recorded for the receipt stored at `key`, if any receipt is.
+pub(crate) async fn content_hash_for_key(pool: &Pool, user_id: &str, key: &str) -> anyhow::Result<Option<String>> {
+    let conn = pool.get().await?;
+    let sql = "SELECT MAX(r.content_hash) FROM receipts r JOIN donations d ON d.id = r.donation_id WHERE d.user_id = :1 AND r.receipt_key = :2";
+    let rows = conn
+        .query(sql, &crate::oracle_params![user_id.to_string(), key.to_string()])
+        .await?;
+    Ok(rows.first().and_then(|row| crate::db::oracle::row_opt_string(row, 0)))
+}
+
+/// Whether any receipt row still points at `key`; deduplicated receipts share objects.
+/// The check runs under the owner's lock, so an `add_shared_receipt` for the key either
+/// committed before it or runs after it and finds no holder.
+pub(crate) async fn receipt_key_in_use(pool: &Pool, user_id: &str, key: &str) -> anyhow::Result<bool> {
+    let conn = pool.get().await?;
+    crate::db::oracle::sync_changes::lock_user_feed(&conn, user_id).await?;
+    let rows = conn
+        .query(
+            "SELECT 1 FROM receipts WHERE receipt_key = :1 FETCH FIRST 1 ROWS ONLY",
+            &crate::oracle_params![key.to_string()],
+        )
+        .await?;
+    conn.rollback().await?;
+    Ok(rows.first().is_some())
+}
+
+/// Records a new hash for every receipt sharing the object at `key`, after the
+/// object was rewritten.
+pub(crate) async fn set_content_hash_for_key(pool: &Pool, key: &str, content_hash: &str) -> anyhow::Result<()> {
+    let conn = pool.get().await?;
+    conn.execute(
+        "UPDATE receipts SET content_hash = :1 WHERE receipt_key = :2",
+        &crate::oracle_params![content_hash.to_string(), key.to_string()],
+    )
+    .await?;
     conn.commit().await?;
     Ok(())
 }
@@ -0,0 +1,96 @@
+use deadpool_oracle::Pool;
+
+use crate::db::models::{NewRefreshToken, RefreshRotation};
+
+/// Stores the first refresh token of a new sign-in. Spent tokens are kept until
+/// they expire so a replay can still be recognised; expired rows are purged on
+/// the way in.
+pub(crate) async fn create_refresh_token(pool: &Pool, token: &NewRefreshToken) -> anyhow::Result<()> {
+    let conn = pool.get().await?;
+    conn.execute(
+        "DELETE FROM refresh_tokens WHERE user_id = :1 AND expires_at <= SYSTIMESTAMP",
+        &crate::oracle_params![token.user_id.clone()],
+    )
+    .await?;
+    conn.execute(
+        "INSERT INTO refresh_tokens (token_hash, family_id, user_id, created_at, expires_at) VALUES (:1, :2, :3, SYSTIMESTAMP, TIMESTAMP '1970-01-01 00:00:00 +00:00' + NUMTODSINTERVAL(:4, 'SECOND'))",
+        &crate::oracle_params![
+            token.token_hash.clone(),
+            token.family_id.clone(),
+            token.user_id.clone(),
+            token.expires_at,
+        ],
+    )
+    .await?;
+    conn.commit().await?;
+    Ok(())
+}
+
+/// Spends the presented token and stores `next_hash` in its family, expiring at
+/// `expires_at` (unix seconds); the family's session is extended to match. The
+/// conditional update is the claim, so two concurrent presentations of one
+/// token cannot both rotate it.
+pub(crate) async fn rotate_refresh_token(
+    pool: &Pool,
+    presented_hash: &str,
+    next_hash: &str,
+    expires_at: i64,
+) -> anyhow::Result<RefreshRotation> {
+    let conn = pool.get().await?;
+    let claimed = conn
+        .execute(
+            "UPDATE refresh_tokens SET used_at = SYSTIMESTAMP WHERE token_hash = :1 AND used_at IS NULL AND revoked_at IS NULL AND expires_at > SYSTIMESTAMP",
+            &crate::oracle_params![presented_hash.to_string()],
+        )
+        .await?;
+    let rows = conn
+        .query(
+            "SELECT user_id, family_id, used_at FROM refresh_tokens WHERE token_hash = :1",
+            &crate::oracle_params![presented_hash.to_string()],
+        )
+        .await?;
+    let Some(row) = rows.first() else {
+        return Ok(RefreshRotation::Invalid);
+    };
+    let user_id = crate::db::oracle::row_string(row, 0);
+    let family_id = crate::db::oracle::row_string(row, 1);
+
+    if claimed.rows_affected == 0 {
+        let spent = crate::db::oracle::row_datetime_utc(row, 2).is_some();
+        return Ok(if spent {
+            RefreshRotation::Reused { user_id, family_id }
+        } else {
+            RefreshRotation::Invalid
+        });
+    }
+
+    conn.execute(
+        "INSERT INTO refresh_tokens (token_hash, family_id, user_id, created_at, expires_at) VALUES (:1, :2, :3, SYSTIMESTAMP, TIMESTAMP '1970-01-01 00:00:00 +00:00' + NUMTODSINTERVAL(:4, 'SECOND'))",
+        &crate::oracle_params![next_hash.to_string(), family_id.clone(), user_id.clone(), expires_at],
+    )
+    .await?;
+    conn.execute(
+        "UPDATE user_sessions SET expires_at = TIMESTAMP '1970-01-01 00:00:00 +00:00' + NUMTODSINTERVAL(:1, 'SECOND'), last_seen_at = SYSTIMESTAMP WHERE jti = :2 AND revoked_at IS NULL",
+        &crate::oracle_params![expires_at, family_id.clone()],
+    )
+    .await?;
+    conn.commit().await?;
+    Ok(RefreshRotation::Rotated { user_id, family_id })
+}
+
+/// Owner and family of a refresh token, spent or not, as `(user_id, family_id)`.
+pub(crate) async fn find_refresh_family(pool: &Pool, token_hash: &str) -> anyhow::Result<Option<(String, String)>> {
+    let conn = pool.get().await?;
+    let rows = conn
+        .query(
+            "SELECT user_id, family_id FROM refresh_tokens WHERE token_hash = :1",
+            &crate::oracle_params![token_hash.to_string()],
+        )
+        .await?;
+    Ok(rows.first().map(|row| {
+        (
+            crate::db::oracle::row_string(row, 0),
+            crate::db::oracle::row_string(row, 1),
+        )
+    }))
+}
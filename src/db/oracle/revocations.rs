@@ -0,0 +1,79 @@
+use deadpool_oracle::Pool;
+
+use crate::db::models::TokenRevocationStatus;
+
+/// Records a revoked jti. Revocations are only needed until the token expires,
+/// so expired rows are purged on the way in.
+pub(crate) async fn revoke_token(pool: &Pool, jti: &str, user_id: &str, expires_at: i64) -> anyhow::Result<()> {
+    let conn = pool.get().await?;
+    conn.execute("DELETE FROM revoked_tokens WHERE expires_at <= SYSTIMESTAMP", &[])
+        .await?;
+    match conn
+        .execute(
+            "INSERT INTO revoked_tokens (jti, user_id, revoked_at, expires_at) VALUES (:1, :2, SYSTIMESTAMP, TIMESTAMP '1970-01-01 00:00:00 +00:00' + NUMTODSINTERVAL(:3, 'SECOND'))",
+            &crate::oracle_params![jti.to_string(), user_id.to_string(), expires_at],
+        )
+        .await
+    {
+        Ok(_) => {}
+        // Already revoked.
+        Err(e) if e.to_string().contains("ORA-00001") => {}
+        Err(e) => return Err(e.into()),
+    }
+    conn.execute(
+        "UPDATE user_sessions SET revoked_at = SYSTIMESTAMP WHERE jti = :1 AND revoked_at IS NULL",
+        &crate::oracle_params![jti.to_string()],
+    )
+    .await?;
+    // A session id is also its refresh-token family, which must not outlive it.
+    conn.execute(
+        "UPDATE refresh_tokens SET revoked_at = SYSTIMESTAMP WHERE family_id = :1 AND revoked_at IS NULL",
+        &crate::oracle_params![jti.to_string()],
+    )
+    .await?;
+    conn.commit().await?;
+    Ok(())
+}
+
+/// Moves the user's watermark forward to `valid_after` (unix seconds); it never moves back.
+pub(crate) async fn revoke_tokens_issued_before(pool: &Pool, user_id: &str, valid_after: i64) -> anyhow::Result<()> {
+    let conn = pool.get().await?;
+    conn.execute(
+        "UPDATE users SET tokens_valid_after = TIMESTAMP '1970-01-01 00:00:00 +00:00' + NUMTODSINTERVAL(:1, 'SECOND') WHERE id = :2 AND (tokens_valid_after IS NULL OR tokens_valid_after < TIMESTAMP '1970-01-01 00:00:00 +00:00' + NUMTODSINTERVAL(:3, 'SECOND'))",
+        &crate::oracle_params![valid_after, user_id.to_string(), valid_after],
+    )
+    .await?;
+    conn.execute(
+        "UPDATE user_sessions SET revoked_at = SYSTIMESTAMP WHERE user_id = :1 AND revoked_at IS NULL AND created_at <= TIMESTAMP '1970-01-01 00:00:00 +00:00' + NUMTODSINTERVAL(:2, 'SECOND')",
+        &crate::oracle_params![user_id.to_string(), valid_after],
+    )
+    .await?;
+    conn.execute(
+        "UPDATE refresh_tokens SET revoked_at = SYSTIMESTAMP WHERE user_id = :1 AND revoked_at IS NULL AND created_at <= TIMESTAMP '1970-01-01 00:00:00 +00:00' + NUMTODSINTERVAL(:2, 'SECOND')",
+        &crate::oracle_params![user_id.to_string(), valid_after],
+    )
+    .await?;
+    conn.commit().await?;
+    Ok(())
+}
+
+/// `session_id` is the session the token belongs to; revoking it revokes every
+/// token refreshed from it. Tokens that predate sessions pass their own jti.
+pub(crate) async fn token_status(pool: &Pool, jti: &str, session_id: &str, user_id: &str) -> anyhow::Result<TokenRevocationStatus> {
+    let conn = pool.get().await?;
+    let rows = conn
+        .query(
+            "SELECT (SELECT COUNT(*) FROM revoked_tokens WHERE jti IN (:1, :2)), (SELECT tokens_valid_after FROM users WHERE id = :3), (SELECT COUNT(*) FROM users WHERE id = :4 AND locked_at IS NOT NULL) FROM dual",
+            &crate::oracle_params![jti.to_string(), session_id.to_string(), user_id.to_string(), user_id.to_string()],
+        )
+        .await?;
+    let Some(row) = rows.first() else {
+        return Ok(TokenRevocationStatus::default());
+    };
+
+    Ok(TokenRevocationStatus {
+        revoked: crate::db::oracle::row_i64(row, 0).unwrap_or_default() > 0,
+        tokens_valid_after: crate::db::oracle::row_datetime_utc(row, 1),
+        locked: crate::db::oracle::row_i64(row, 2).unwrap_or_default() > 0,
+    })
+}
@@ -0,0 +1,80 @@
+use deadpool_oracle::Pool;
+
+use crate::db::models::{NewUserSession, UserSession};
+
+// last_seen_at is shown with minute-level accuracy; skip the write for sessions seen more recently.
+const LAST_SEEN_RESOLUTION_SECS: i64 = 60;
+
+/// Records an issued session JWT. Sessions are only listed until they expire,
+/// so expired rows are purged on the way in.
+pub(crate) async fn create_session(pool: &Pool, session: &NewUserSession) -> anyhow::Result<()> {
+    let conn = pool.get().await?;
+    conn.execute(
+        "DELETE FROM user_sessions WHERE user_id = :1 AND expires_at <= SYSTIMESTAMP",
+        &crate::oracle_params![session.user_id.clone()],
+    )
+    .await?;
+    conn.execute(
+        "INSERT INTO user_sessions (jti, user_id, user_agent, ip_address, created_at, last_seen_at, expires_at) VALUES (:1, :2, :3, :4, TIMESTAMP '1970-01-01 00:00:00 +00:00' + NUMTODSINTERVAL(:5, 'SECOND'), SYSTIMESTAMP, TIMESTAMP '1970-01-01 00:00:00 +00:00' + NUMTODSINTERVAL(:6, 'SECOND'))",
+        &crate::oracle_params![
+            session.jti.clone(),
+            session.user_id.clone(),
+            session.user_agent.clone(),
+            session.ip_address.clone(),
+            session.issued_at,
+            session.expires_at,
+        ],
+    )
+    .await?;
+    conn.commit().await?;
+    Ok(())
+}
+
+pub(crate) async fn list_sessions(pool: &Pool, user_id: &str) -> anyhow::Result<Vec<UserSession>> {
+    let conn = pool.get().await?;
+    let rows = conn
+        .query(
+            "SELECT jti, user_agent, ip_address, created_at, last_seen_at, expires_at FROM user_sessions WHERE user_id = :1 AND revoked_at IS NULL AND expires_at > SYSTIMESTAMP ORDER BY NVL(last_seen_at, created_at) DESC",
+            &crate::oracle_params![user_id.to_string()],
+        )
+        .await?;
+
+    Ok(rows
+        .rows
+        .iter()
+        .map(|row| UserSession {
+            id: crate::db::oracle::row_string(row, 0),
+            user_agent: crate::db::oracle::row_opt_string(row, 1),
+            ip_address: crate::db::oracle::row_opt_string(row, 2),
+            created_at: crate::db::oracle::row_datetime_utc(row, 3).unwrap_or_else(chrono::Utc::now),
+            last_seen_at: crate::db::oracle::row_datetime_utc(row, 4),
+            expires_at: crate::db::oracle::row_datetime_utc(row, 5).unwrap_or_else(chrono::Utc::now),
+        })
+        .collect())
+}
+
+/// Expiry (unix seconds) of the user's active session `jti`, or None if it is not theirs or already ended.
+pub(crate) async fn active_session_expiry(pool: &Pool, user_id: &str, jti: &str) -> anyhow::Result<Option<i64>> {
+    let conn = pool.get().await?;
+    let rows = conn
+        .query(
+            "SELECT expires_at FROM user_sessions WHERE jti = :1 AND user_id = :2 AND revoked_at IS NULL AND expires_at > SYSTIMESTAMP",
+            &crate::oracle_params![jti.to_string(), user_id.to_string()],
+        )
+        .await?;
+    Ok(rows
+        .first()
+        .and_then(|row| crate::db::oracle::row_datetime_utc(row, 0))
+        .map(|t| t.timestamp()))
+}
+
+pub(crate) async fn touch_session(pool: &Pool, jti: &str) -> anyhow::Result<()> {
+    let conn = pool.get().await?;
+    conn.execute(
+        "UPDATE user_sessions SET last_seen_at = SYSTIMESTAMP WHERE jti = :1 AND revoked_at IS NULL AND (last_seen_at IS NULL OR last_seen_at < SYSTIMESTAMP - NUMTODSINTERVAL(:2, 'SECOND'))",
+        &crate::oracle_params![jti.to_string(), LAST_SEEN_RESOLUTION_SECS],
+    )
+    .await?;
+    conn.commit().await?;
+    Ok(())
+}
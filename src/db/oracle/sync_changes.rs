@@ -0,0 +1,349 @@
+use chrono::Utc;
+use deadpool_oracle::Pool;
+use oracle_rs::{Connection, Row};
+use serde_json::json;
+use std::collections::HashMap;
+
+use crate::db::models::{Charity, Donation, Receipt, SyncChange, SyncChangePage};
+use crate::db::oracle::data_keys::FieldKey;
+
+/// Takes the owner's row lock before a sequence number is drawn. The lock is held until the
+/// caller's transaction ends, so writers to one user's feed commit in sequence order and a
+/// reader never sees a committed entry above one that is still in flight.
+pub(crate) async fn lock_user_feed(conn: &Connection, user_id: &str) -> anyhow::Result<()> {
+    conn.query(
+        "SELECT id FROM users WHERE id = :1 FOR UPDATE",
+        &crate::oracle_params![user_id.to_string()],
+    )
+    .await?;
+    Ok(())
+}
+
+/// Appends a change-log entry on the caller's connection; it commits with the caller's transaction.
+pub(crate) async fn record_change(
+    conn: &Connection,
+    user_id: &str,
+    table_name: &str,
+    record_id: &str,
+    operation: &str,
+) -> anyhow::Result<()> {
+    lock_user_feed(conn, user_id).await?;
+    conn.execute(
+        "INSERT INTO sync_changes (seq, user_id, table_name, record_id, operation, created_at) VALUES (sync_change_seq.NEXTVAL, :1, :2, :3, :4, SYSTIMESTAMP)",
+        &crate::oracle_params![
+            user_id.to_string(),
+            table_name.to_string(),
+            record_id.to_string(),
+            operation.to_string(),
+        ],
+    )
+    .await?;
+    Ok(())
+}
+
+/// Records a receipt change, resolving the owner through the receipt's donation.
+/// For deletions this must run before the receipt row is removed.
+pub(crate) async fn record_receipt_change(
+    conn: &Connection,
+    receipt_id: &str,
+    operation: &str,
+) -> anyhow::Result<()> {
+    let owner = conn
+        .query(
+            "SELECT d.user_id FROM receipts r JOIN donations d ON d.id = r.donation_id WHERE r.id = :1",
+            &crate::oracle_params![receipt_id.to_string()],
+        )
+        .await?;
+    let Some(user_id) = owner.rows.first().map(|row| crate::db::oracle::row_string(row, 0)) else {
+        return Ok(());
+    };
+    lock_user_feed(conn, &user_id).await?;
+    conn.execute(
+        "INSERT INTO sync_changes (seq, user_id, table_name, record_id, operation, created_at) SELECT sync_change_seq.NEXTVAL, d.user_id, 'receipts', r.id, :2, SYSTIMESTAMP FROM receipts r JOIN donations d ON d.id = r.donation_id WHERE r.id = :1",
+        &crate::oracle_params![receipt_id.to_string(), operation.to_string()],
+    )
+    .await?;
+    Ok(())
+}
+
+/// Records tombstones for the receipts of a donation that is about to be deleted.
+pub(crate) async fn record_donation_receipt_deletes(
+    conn: &Connection,
+    user_id: &str,
+    donation_id: &str,
+) -> anyhow::Result<()> {
+    lock_user_feed(conn, user_id).await?;
+    conn.execute(
+        "INSERT INTO sync_changes (seq, user_id, table_name, record_id, operation, created_at) SELECT sync_change_seq.NEXTVAL, :1, 'receipts', r.id, 'delete', SYSTIMESTAMP FROM receipts r JOIN donations d ON d.id = r.donation_id WHERE d.user_id = :1 AND d.id = :2",
+        &crate::oracle_params![user_id.to_string(), donation_id.to_string()],
+    )
+    .await?;
+    Ok(())
+}
+
+/// Records tombstones for the receipts still attached to a charity's soft-deleted donations
+/// before the charity delete purges them.
+pub(crate) async fn record_charity_receipt_deletes(
+    conn: &Connection,
+    user_id: &str,
+    charity_id: &str,
+) -> anyhow::Result<()> {
+    lock_user_feed(conn, user_id).await?;
+    conn.execute(
+        "INSERT INTO sync_changes (seq, user_id, table_name, record_id, operation, created_at) SELECT sync_change_seq.NEXTVAL, :1, 'receipts', r.id, 'delete', SYSTIMESTAMP FROM receipts r JOIN donations d ON d.id = r.donation_id WHERE d.user_id = :1 AND d.charity_id = :2 AND d.deleted = 1",
+        &crate::oracle_params![user_id.to_string(), charity_id.to_string()],
+    )
+    .await?;
+    Ok(())
+}
+
+struct ChangeEntry {
+    seq: i64,
+    table_name: String,
+    record_id: String,
+    operation: String,
+}
+
+/// Returns the next page of changes after `after_seq`, collapsed to the latest entry per record
+/// and hydrated with the current row. Records that no longer exist are reported as deletions.
+pub(crate) async fn list_changes(
+    pool: &Pool,
+    user_id: &str,
+    after_seq: i64,
+    limit: usize,
+) -> anyhow::Result<SyncChangePage> {
+    let key = crate::db::oracle::data_keys::for_read(pool, user_id).await?;
+    let conn = pool.get().await?;
+    let rows = conn
+        .query(
+            "SELECT seq, table_name, record_id, operation FROM sync_changes WHERE user_id = :1 AND seq > :2 ORDER BY seq FETCH FIRST :3 ROWS ONLY",
+            &crate::oracle_params![user_id.to_string(), after_seq, limit as i64],
+        )
+        .await?;
+
+    let mut entries: Vec<ChangeEntry> = Vec::new();
+    for row in &rows.rows {
+        entries.push(ChangeEntry {
+            seq: crate::db::oracle::row_i64(row, 0).unwrap_or_default(),
+            table_name: crate::db::oracle::row_string(row, 1),
+            record_id: crate::db::oracle::row_string(row, 2),
+            operation: crate::db::oracle::row_string(row, 3),
+        });
+    }
+
+    let Some(last_seq) = entries.last().map(|entry| entry.seq) else {
+        return Ok(SyncChangePage::default());
+    };
+    let has_more = entries.len() >= limit;
+
+    // Keep only the latest entry per record, preserving sequence order.
+    let mut latest: HashMap<(String, String), i64> = HashMap::new();
+    for entry in &entries {
+        latest.insert((entry.table_name.clone(), entry.record_id.clone()), entry.seq);
+    }
+    entries.retain(|entry| {
+        latest.get(&(entry.table_name.clone(), entry.record_id.clone())) == Some(&entry.seq)
+    });
+
+    let wants = |table: &str| {
+        entries
+            .iter()
+            .any(|entry| entry.table_name == table && entry.operation != "delete")
+    };
+    let mut current: HashMap<(String, String), serde_json::Value> = HashMap::new();
+    if wants("users") {
+        if let Some(profile) = load_profile(&conn, &key, user_id).await? {
+            current.insert(("users".to_string(), user_id.to_string()), profile);
+        }
+    }
+    if wants("charities") {
+        for (id, value) in load_charities(&conn, &key, user_id, after_seq, last_seq).await? {
+            current.insert(("charities".to_string(), id), value);
+        }
+    }
+    if wants("donations") {
+        for (id, value) in load_donations(&conn, &key, user_id, after_seq, last_seq).await? {
+            current.insert(("donations".to_string(), id), value);
+        }
+    }
+    if wants("receipts") {
+        for (id, value) in load_receipts(&conn, user_id, after_seq, last_seq).await? {
+            current.insert(("receipts".to_string(), id), value);
+        }
+    }
+
+    let changes = entries
+        .into_iter()
+        .map(|entry| {
+            let data = if entry.operation == "delete" {
+                None
+            } else {
+                current.remove(&(entry.table_name.clone(), entry.record_id.clone()))
+            };
+            SyncChange {
+                seq: entry.seq,
+                op: if data.is_some() { "upsert" } else { "delete" }.to_string(),
+                table: entry.table_name,
+                id: entry.record_id,
+                data,
+            }
+        })
+        .collect();
+
+    Ok(SyncChangePage {
+        changes,
+        last_seq: Some(last_seq),
+        has_more,
+    })
+}
+
+const CHANGED_IDS_SQL: &str = "SELECT record_id FROM sync_changes WHERE user_id = :1 AND table_name = :2 AND seq > :3 AND seq <= :4";
+
+async fn load_profile(conn: &Connection, key: &FieldKey, user_id: &str) -> anyhow::Result<Option<serde_json::Value>> {
+    let rows = conn
+        .query(
+            "SELECT email, name, provider, filing_status, agi, marginal_tax_rate, itemize_deductions, is_encrypted, encrypted_payload, vault_credential_id, agi_ciphertext FROM users WHERE id = :1",
+            &crate::oracle_params![user_id.to_string()],
+        )
+        .await?;
+    rows.first().map(|row| {
+        let profile = super::user_profile_row_from_row(row, 0, key)?;
+        Ok(json!({
+            "id": user_id,
+            "email": profile.0,
+            "name": profile.1,
+            "provider": profile.2,
+            "filing_status": profile.3,
+            "agi": profile.4,
+            "marginal_tax_rate": profile.5,
+            "itemize_deductions": profile.6,
+            "is_encrypted": profile.7,
+            "encrypted_payload": profile.8,
+            "vault_credential_id": profile.9,
+        }))
+    })
+    .transpose()
+}
+
+async fn load_charities(
+    conn: &Connection,
+    key: &FieldKey,
+    user_id: &str,
+    after_seq: i64,
+    last_seq: i64,
+) -> anyhow::Result<Vec<(String, serde_json::Value)>> {
+    let sql = format!("SELECT id, user_id, name, ein, created_at, updated_at, nonprofit_type, deductibility, street, city, state, zip, category, status, classification, is_encrypted, encrypted_payload FROM charities WHERE user_id = :1 AND id IN ({CHANGED_IDS_SQL})");
+    let rows = conn
+        .query(
+            &sql,
+            &crate::oracle_params![user_id.to_string(), "charities".to_string(), after_seq, last_seq],
+        )
+        .await?;
+    rows.rows
+        .iter()
+        .map(|row| {
+            let charity = key.open_charity(Charity {
+                id: crate::db::oracle::row_string(row, 0),
+                user_id: crate::db::oracle::row_string(row, 1),
+                name: crate::db::oracle::row_string(row, 2),
+                ein: crate::db::oracle::row_opt_string(row, 3),
+                created_at: crate::db::oracle::row_datetime_utc(row, 4).unwrap_or_else(Utc::now),
+                updated_at: crate::db::oracle::row_datetime_utc(row, 5).unwrap_or_else(Utc::now),
+                nonprofit_type: crate::db::oracle::row_opt_string(row, 6),
+                deductibility: crate::db::oracle::row_opt_string(row, 7),
+                street: crate::db::oracle::row_opt_string(row, 8),
+                city: crate::db::oracle::row_opt_string(row, 9),
+                state: crate::db::oracle::row_opt_string(row, 10),
+                zip: crate::db::oracle::row_opt_string(row, 11),
+                category: crate::db::oracle::row_opt_string(row, 12),
+                status: crate::db::oracle::row_opt_string(row, 13),
+                classification: crate::db::oracle::row_opt_string(row, 14),
+                is_encrypted: crate::db::oracle::row_bool(row, 15),
+                encrypted_payload: crate::db::oracle::row_opt_string(row, 16),
+            })?;
+            Ok((charity.id.clone(), json!(charity)))
+        })
+        .collect()
+}
+
+async fn load_donations(
+    conn: &Connection,
+    key: &FieldKey,
+    user_id: &str,
+    after_seq: i64,
+    last_seq: i64,
+) -> anyhow::Result<Vec<(String, serde_json::Value)>> {
+    let sql = format!("SELECT d.id, d.user_id, d.donation_year, d.donation_date, d.donation_category, d.donation_amount, d.charity_id, c.name, c.ein, d.notes, d.created_at, d.updated_at, d.is_encrypted, d.encrypted_payload FROM donations d LEFT JOIN charities c ON c.id = d.charity_id WHERE d.user_id = :1 AND d.deleted = 0 AND d.id IN ({CHANGED_IDS_SQL})");
+    let rows = conn
+        .query(
+            &sql,
+            &crate::oracle_params![user_id.to_string(), "donations".to_string(), after_seq, last_seq],
+        )
+        .await?;
+    rows.rows
+        .iter()
+        .map(|row| {
+            let donation = key.open_donation(donation_from_row(row))?;
+            Ok((donation.id.clone(), json!(donation)))
+        })
+        .collect()
+}
+
+async fn load_receipts(
+    conn: &Connection,
+    user_id: &str,
+    after_seq: i64,
+    last_seq: i64,
+) -> anyhow::Result<Vec<(String, serde_json::Value)>> {
+    let sql = format!("SELECT r.id, r.donation_id, r.receipt_key, r.file_name, r.content_type, r.receipt_size, r.ocr_text, r.ocr_date, r.ocr_amount, r.ocr_status, r.created_at, r.is_encrypted, r.encrypted_payload, r.content_hash FROM receipts r JOIN donations d ON d.id = r.donation_id WHERE d.user_id = :1 AND d.deleted = 0 AND r.id IN ({CHANGED_IDS_SQL})");
+    let rows = conn
+        .query(
+            &sql,
+            &crate::oracle_params![user_id.to_string(), "receipts".to_string(), after_seq, last_seq],
+        )
+        .await?;
+    Ok(rows
+        .rows
+        .iter()
+        .map(|row| {
+            let receipt = Receipt {
+                id: crate::db::oracle::row_string(row, 0),
+                donation_id: crate::db::oracle::row_string(row, 1),
+                key: crate::db::oracle::row_string(row, 2),
+                file_name: crate::db::oracle::row_opt_string(row, 3),
+                content_type: crate::db::oracle::row_opt_string(row, 4),
+                size: crate::db::oracle::row_i64(row, 5),
+                ocr_text: crate::db::oracle::row_opt_string(row, 6),
+                ocr_date: crate::db::oracle::row_naive_date(row, 7),
+                ocr_amount: crate::db::oracle::row_i64(row, 8),
+                ocr_status: crate::db::oracle::row_opt_string(row, 9),
+                is_encrypted: crate::db::oracle::row_bool(row, 11),
+                encrypted_payload: crate::db::oracle::row_opt_string(row, 12),
+                content_hash: crate::db::oracle::row_opt_string(row, 13),
+                created_at: crate::db::oracle::row_datetime_utc(row, 10).unwrap_or_else(Utc::now),
+            };
+            (receipt.id.clone(), json!(receipt))
+        })
+        .collect())
+}
+
+pub(crate) fn donation_from_row(row: &Row) -> Donation {
+    Donation {
+        id: crate::db::oracle::row_string(row, 0),
+        user_id: crate::db::oracle::row_string(row, 1),
+        year: crate::db::oracle::row_i64(row, 2).unwrap_or_default() as i32,
+        date: crate::db::oracle::row_naive_date(row, 3).unwrap_or_else(|| Utc::now().date_naive()),
+        category: crate::db::oracle::row_opt_string(row, 4),
+        amount: crate::db::oracle::row_f64(row, 5),
+        charity_id: crate::db::oracle::row_string(row, 6),
+        charity_name: crate::db::oracle::row_string(row, 7),
+        charity_ein: crate::db::oracle::row_opt_string(row, 8),
+        notes: crate::db::oracle::row_opt_string(row, 9),
+        is_encrypted: crate::db::oracle::row_bool(row, 12),
+        encrypted_payload: crate::db::oracle::row_opt_string(row, 13),
+        shared_with: None,
+        created_at: crate::db::oracle::row_datetime_utc(row, 10).unwrap_or_else(Utc::now),
+        updated_at: crate::db::oracle::row_datetime_utc(row, 11).unwrap_or_else(Utc::now),
+        deleted: false,
+    }
+}
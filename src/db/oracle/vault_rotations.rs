@@ -0,0 +1,249 @@
+use deadpool_oracle::Pool;
+
+use crate::db::models::{NewVaultKeyRotation, VaultKeyRotation, VaultRotationItem};
+
+const ROTATION_COLUMNS: &str = "id, old_key_id, new_key_id, new_credential_id, status, total_items, processed_items, failed_table, failed_record_id, error, created_at, updated_at, completed_at";
+
+const PENDING_FILTER: &str = "is_encrypted = 1 AND encrypted_payload IS NOT NULL AND encrypted_payload NOT LIKE :2";
+
+/// Encrypted tables in the order they are rotated, as `(table, SELECT of id,
+/// payload and object key)`. The profile goes last so it stays readable with
+/// the old key for as long as anything else does.
+fn pending_queries() -> [(&'static str, String); 4] {
+    [
+        (
+            "charities",
+            format!("SELECT id, encrypted_payload, NULL FROM charities WHERE user_id = :1 AND {PENDING_FILTER} ORDER BY id"),
+        ),
+        (
+            "donations",
+            format!("SELECT id, encrypted_payload, NULL FROM donations WHERE user_id = :1 AND {PENDING_FILTER} ORDER BY id"),
+        ),
+        (
+            "receipts",
+            "SELECT r.id, r.encrypted_payload, r.receipt_key FROM receipts r JOIN donations d ON d.id = r.donation_id WHERE d.user_id = :1 AND r.is_encrypted = 1 AND r.encrypted_payload IS NOT NULL AND r.encrypted_payload NOT LIKE :2 ORDER BY r.id".to_string(),
+        ),
+        (
+            "users",
+            format!("SELECT id, encrypted_payload, NULL FROM users WHERE id = :1 AND {PENDING_FILTER}"),
+        ),
+    ]
+}
+
+fn rotation_from_row(row: &oracle_rs::Row) -> VaultKeyRotation {
+    VaultKeyRotation {
+        id: crate::db::oracle::row_string(row, 0),
+        old_key_id: crate::db::oracle::row_string(row, 1),
+        new_key_id: crate::db::oracle::row_string(row, 2),
+        new_credential_id: crate::db::oracle::row_opt_string(row, 3),
+        status: crate::db::oracle::row_string(row, 4),
+        total_items: crate::db::oracle::row_i64(row, 5).unwrap_or_default(),
+        processed_items: crate::db::oracle::row_i64(row, 6).unwrap_or_default(),
+        failed_table: crate::db::oracle::row_opt_string(row, 7),
+        failed_record_id: crate::db::oracle::row_opt_string(row, 8),
+        error: crate::db::oracle::row_opt_string(row, 9),
+        created_at: crate::db::oracle::row_datetime_utc(row, 10).unwrap_or_else(chrono::Utc::now),
+        updated_at: crate::db::oracle::row_datetime_utc(row, 11),
+        completed_at: crate::db::oracle::row_datetime_utc(row, 12),
+    }
+}
+
+async fn find_rotation(conn: &oracle_rs::Connection, rotation_id: &str) -> anyhow::Result<VaultKeyRotation> {
+    let rows = conn
+        .query(
+            &format!("SELECT {ROTATION_COLUMNS} FROM vault_key_rotations WHERE id = :1"),
+            &crate::oracle_params![rotation_id.to_string()],
+        )
+        .await?;
+    rows.first()
+        .map(rotation_from_row)
+        .ok_or_else(|| anyhow::anyhow!("vault key rotation {} not found", rotation_id))
+}
+
+pub(crate) async fn latest_rotation(pool: &Pool, user_id: &str) -> anyhow::Result<Option<VaultKeyRotation>> {
+    let conn = pool.get().await?;
+    let rows = conn
+        .query(
+            &format!("SELECT {ROTATION_COLUMNS} FROM vault_key_rotations WHERE user_id = :1 ORDER BY created_at DESC FETCH FIRST 1 ROWS ONLY"),
+            &crate::oracle_params![user_id.to_string()],
+        )
+        .await?;
+    Ok(rows.first().map(rotation_from_row))
+}
+
+pub(crate) async fn create_rotation(pool: &Pool, input: &NewVaultKeyRotation) -> anyhow::Result<VaultKeyRotation> {
+    let conn = pool.get().await?;
+    conn.execute(
+        "INSERT INTO vault_key_rotations (id, user_id, old_key_id, new_key_id, new_credential_id, status, total_items, processed_items, created_at, updated_at) VALUES (:1, :2, :3, :4, :5, 'running', :6, 0, SYSTIMESTAMP, SYSTIMESTAMP)",
+        &crate::oracle_params![
+            input.id.clone(),
+            input.user_id.clone(),
+            input.old_key_id.clone(),
+            input.new_key_id.clone(),
+            input.new_credential_id.clone(),
+            input.total_items,
+        ],
+    )
+    .await?;
+    conn.commit().await?;
+    find_rotation(&conn, &input.id).await
+}
+
+/// Puts a failed run back to `running`, clearing the failure.
+pub(crate) async fn resume_rotation(pool: &Pool, rotation_id: &str) -> anyhow::Result<VaultKeyRotation> {
+    let conn = pool.get().await?;
+    conn.execute(
+        "UPDATE vault_key_rotations SET status = 'running', failed_table = NULL, failed_record_id = NULL, error = NULL, updated_at = SYSTIMESTAMP WHERE id = :1",
+        &crate::oracle_params![rotation_id.to_string()],
+    )
+    .await?;
+    conn.commit().await?;
+    find_rotation(&conn, rotation_id).await
+}
+
+/// Encrypted items of the user whose payload does not start with `rotated_prefix`.
+pub(crate) async fn count_pending_items(pool: &Pool, user_id: &str, rotated_prefix: &str) -> anyhow::Result<i64> {
+    let conn = pool.get().await?;
+    let pattern = format!("{rotated_prefix}%");
+    let mut total = 0;
+    for (_, select) in pending_queries() {
+        let rows = conn
+            .query(
+                &format!("SELECT COUNT(*) FROM ({select})"),
+                &crate::oracle_params![user_id.to_string(), pattern.clone()],
+            )
+            .await?;
+        total += rows
+            .first()
+            .and_then(|row| crate::db::oracle::row_i64(row, 0))
+            .unwrap_or_default();
+    }
+    Ok(total)
+}
+
+/// Up to `limit` items whose payload does not start with `rotated_prefix`, in rotation order.
+pub(crate) async fn pending_items(
+    pool: &Pool,
+    user_id: &str,
+    rotated_prefix: &str,
+    limit: i64,
+) -> anyhow::Result<Vec<VaultRotationItem>> {
+    let conn = pool.get().await?;
+    let pattern = format!("{rotated_prefix}%");
+    let mut items = Vec::new();
+    for (table, select) in pending_queries() {
+        let remaining = limit - items.len() as i64;
+        if remaining <= 0 {
+            break;
+        }
+        let rows = conn
+            .query(
+                &format!("{select} FETCH FIRST :3 ROWS ONLY"),
+                &crate::oracle_params![user_id.to_string(), pattern.clone(), remaining],
+            )
+            .await?;
+        items.extend(rows.iter().map(|row| VaultRotationItem {
+            table: table.to_string(),
+            id: crate::db::oracle::row_string(row, 0),
+            payload: crate::db::oracle::row_string(row, 1),
+            object_key: crate::db::oracle::row_opt_string(row, 2),
+        }));
+    }
+    Ok(items)
+}
+
+/// Swaps an item's payload for its re-encrypted form, only if it still holds
+/// the payload that was read; false when the client rewrote it meanwhile.
+pub(crate) async fn replace_item_payload(
+    pool: &Pool,
+    user_id: &str,
+    item: &VaultRotationItem,
+    payload: &str,
+) -> anyhow::Result<bool> {
+    let ownership = match item.table.as_str() {
+        "charities" | "donations" => "user_id = :4",
+        "receipts" => "donation_id IN (SELECT id FROM donations WHERE user_id = :4)",
+        "users" => "id = :4",
+        other => anyhow::bail!("table {} is not part of the vault", other),
+    };
+    let conn = pool.get().await?;
+    let updated = conn
+        .execute(
+            &format!(
+                "UPDATE {} SET encrypted_payload = :1, updated_at = SYSTIMESTAMP WHERE id = :2 AND DBMS_LOB.COMPARE(encrypted_payload, TO_CLOB(:3)) = 0 AND {ownership}",
+                item.table
+            ),
+            &crate::oracle_params![
+                payload.to_string(),
+                item.id.clone(),
+                item.payload.clone(),
+                user_id.to_string(),
+            ],
+        )
+        .await?;
+    if updated.rows_affected == 0 {
+        return Ok(false);
+    }
+    if item.table == "receipts" {
+        crate::db::oracle::sync_changes::record_receipt_change(&conn, &item.id, "upsert").await?;
+    } else {
+        crate::db::oracle::sync_changes::record_change(&conn, user_id, &item.table, &item.id, "upsert").await?;
+    }
+    conn.commit().await?;
+    Ok(true)
+}
+
+pub(crate) async fn record_progress(pool: &Pool, rotation_id: &str, processed: i64) -> anyhow::Result<VaultKeyRotation> {
+    let conn = pool.get().await?;
+    conn.execute(
+        "UPDATE vault_key_rotations SET processed_items = processed_items + :1, updated_at = SYSTIMESTAMP WHERE id = :2",
+        &crate::oracle_params![processed, rotation_id.to_string()],
+    )
+    .await?;
+    conn.commit().await?;
+    find_rotation(&conn, rotation_id).await
+}
+
+pub(crate) async fn fail_rotation(
+    pool: &Pool,
+    rotation_id: &str,
+    failed_table: &str,
+    failed_record_id: &str,
+    error: &str,
+) -> anyhow::Result<VaultKeyRotation> {
+    let conn = pool.get().await?;
+    conn.execute(
+        "UPDATE vault_key_rotations SET status = 'failed', failed_table = :1, failed_record_id = :2, error = SUBSTR(:3, 1, 1000), updated_at = SYSTIMESTAMP WHERE id = :4",
+        &crate::oracle_params![
+            failed_table.to_string(),
+            failed_record_id.to_string(),
+            error.to_string(),
+            rotation_id.to_string(),
+        ],
+    )
+    .await?;
+    conn.commit().await?;
+    find_rotation(&conn, rotation_id).await
+}
+
+/// Marks the run complete and, when it names one, makes the new passkey the
+/// vault credential in the same transaction.
+pub(crate) async fn complete_rotation(pool: &Pool, user_id: &str, rotation_id: &str) -> anyhow::Result<VaultKeyRotation> {
+    let conn = pool.get().await?;
+    conn.execute(
+        "UPDATE vault_key_rotations SET status = 'completed', completed_at = SYSTIMESTAMP, updated_at = SYSTIMESTAMP WHERE id = :1 AND user_id = :2",
+        &crate::oracle_params![rotation_id.to_string(), user_id.to_string()],
+    )
+    .await?;
+    let switched = conn
+        .execute(
+            "UPDATE users SET vault_credential_id = (SELECT new_credential_id FROM vault_key_rotations WHERE id = :1), updated_at = SYSTIMESTAMP WHERE id = :2 AND EXISTS (SELECT 1 FROM vault_key_rotations WHERE id = :1 AND new_credential_id IS NOT NULL)",
+            &crate::oracle_params![rotation_id.to_string(), user_id.to_string()],
+        )
+        .await?;
+    if switched.rows_affected > 0 {
+        crate::db::oracle::sync_changes::record_change(&conn, user_id, "users", user_id, "upsert").await?;
+    }
+    conn.commit().await?;
+    find_rotation(&conn, rotation_id).await
+}
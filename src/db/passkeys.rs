@@ -0,0 +1,41 @@
+use crate::db::models::{
+    NewPasskeyCredential, NewWebauthnChallenge, PasskeyCredential, StoredPasskey, WebauthnChallenge,
+};
+use crate::db::DbPool;
+
+pub async fn create_challenge(pool: &DbPool, input: &NewWebauthnChallenge) -> anyhow::Result<()> {
+    super::create_webauthn_challenge(pool, input).await
+}
+
+pub async fn take_challenge(
+    pool: &DbPool,
+    id: &str,
+    purpose: &str,
+) -> anyhow::Result<Option<WebauthnChallenge>> {
+    super::take_webauthn_challenge(pool, id, purpose).await
+}
+
+pub async fn insert_credential(pool: &DbPool, input: &NewPasskeyCredential) -> anyhow::Result<bool> {
+    super::insert_passkey_credential(pool, input).await
+}
+
+pub async fn list_credentials(pool: &DbPool, user_id: &str) -> anyhow::Result<Vec<PasskeyCredential>> {
+    super::list_passkey_credentials(pool, user_id).await
+}
+
+pub async fn find_credential(pool: &DbPool, credential_id: &str) -> anyhow::Result<Option<StoredPasskey>> {
+    super::find_passkey_credential(pool, credential_id).await
+}
+
+pub async fn record_assertion(
+    pool: &DbPool,
+    credential_id: &str,
+    previous_sign_count: i64,
+    sign_count: i64,
+) -> anyhow::Result<bool> {
+    super::record_passkey_assertion(pool, credential_id, previous_sign_count, sign_count).await
+}
+
+pub async fn delete_credential(pool: &DbPool, user_id: &str, credential_id: &str) -> anyhow::Result<bool> {
+    super::delete_passkey_credential(pool, user_id, credential_id).await
+}
@@ -0,0 +1,27 @@
+use chrono::{DateTime, Utc};
+
+use crate::db::models::ReceiptUpload;
+use crate::db::DbPool;
+
+pub async fn create_upload(pool: &DbPool, input: &ReceiptUpload) -> anyhow::Result<()> {
+    super::create_receipt_upload(pool, input).await
+}
+
+/// The user's upload session, or None if it belongs to someone else.
+pub async fn get_upload(pool: &DbPool, user_id: &str, id: &str) -> anyhow::Result<Option<ReceiptUpload>> {
+    super::get_receipt_upload(pool, user_id, id).await
+}
+
+pub async fn list_user_uploads(pool: &DbPool, user_id: &str) -> anyhow::Result<Vec<ReceiptUpload>> {
+    super::list_user_receipt_uploads(pool, user_id).await
+}
+
+/// Sessions of all users started before `cutoff`, oldest first.
+pub async fn list_uploads_started_before(pool: &DbPool, cutoff: DateTime<Utc>) -> anyhow::Result<Vec<ReceiptUpload>> {
+    super::list_receipt_uploads_started_before(pool, cutoff).await
+}
+
+/// False when the session was already gone.
+pub async fn delete_upload(pool: &DbPool, id: &str) -> anyhow::Result<bool> {
+    super::delete_receipt_upload(pool, id).await
+}
@@ -1,4 +1,4 @@
-use crate::db::models::{NewReceipt, Receipt};
+use crate::db::models::{NewReceipt, Receipt, ReceiptPatch};
 use crate::db::DbPool;
 
 pub async fn list_receipts(
@@ -29,6 +29,23 @@ pub async fn add_receipt(pool: &DbPool, input: &NewReceipt) -> anyhow::Result<()
     super::add_receipt(pool, input).await
 }
 
+/// Adds a receipt sharing an existing object; false when no receipt holds its key any more.
+pub async fn add_shared_receipt(pool: &DbPool, user_id: &str, input: &NewReceipt) -> anyhow::Result<bool> {
+    super::add_shared_receipt(pool, user_id, input).await
+}
+
+pub async fn update_receipt(pool: &DbPool, patch: &ReceiptPatch) -> anyhow::Result<bool> {
+    super::update_receipt(pool, patch).await
+}
+
+pub async fn delete_receipt(
+    pool: &DbPool,
+    user_id: &str,
+    receipt_id: &str,
+) -> anyhow::Result<Option<String>> {
+    super::delete_receipt(pool, user_id, receipt_id).await
+}
+
 pub async fn set_receipt_ocr(
     pool: &DbPool,
     receipt_id: &str,
@@ -39,3 +56,29 @@ pub async fn set_receipt_ocr(
 ) -> anyhow::Result<()> {
     super::set_receipt_ocr(pool, receipt_id, ocr_text, ocr_date, ocr_amount, ocr_status).await
 }
+
+/// Storage keys of all users' receipts.
+pub async fn list_all_receipt_keys(pool: &DbPool) -> anyhow::Result<Vec<String>> {
+    super::list_all_receipt_keys(pool).await
+}
+
+/// The user's live receipts holding a file with this content hash.
+pub async fn find_receipts_by_content_hash(
+    pool: &DbPool,
+    user_id: &str,
+    content_hash: &str,
+) -> anyhow::Result<Vec<Receipt>> {
+    super::find_receipts_by_content_hash(pool, user_id, content_hash).await
+}
+
+pub async fn content_hash_for_key(pool: &DbPool, user_id: &str, key: &str) -> anyhow::Result<Option<String>> {
+    super::content_hash_for_key(pool, user_id, key).await
+}
+
+pub async fn receipt_key_in_use(pool: &DbPool, user_id: &str, key: &str) -> anyhow::Result<bool> {
+    super::receipt_key_in_use(pool, user_id, key).await
+}
+
+pub async fn set_content_hash_for_key(pool: &DbPool, key: &str, content_hash: &str) -> anyhow::Result<()> {
+    super::set_content_hash_for_key(pool, key, content_hash).await
+}
@@ -0,0 +1,19 @@
+use crate::db::models::{NewRefreshToken, RefreshRotation};
+use crate::db::DbPool;
+
+pub async fn create_token(pool: &DbPool, token: &NewRefreshToken) -> anyhow::Result<()> {
+    super::create_refresh_token(pool, token).await
+}
+
+pub async fn rotate_token(
+    pool: &DbPool,
+    presented_hash: &str,
+    next_hash: &str,
+    expires_at: i64,
+) -> anyhow::Result<RefreshRotation> {
+    super::rotate_refresh_token(pool, presented_hash, next_hash, expires_at).await
+}
+
+pub async fn find_family(pool: &DbPool, token_hash: &str) -> anyhow::Result<Option<(String, String)>> {
+    super::find_refresh_token_family(pool, token_hash).await
+}
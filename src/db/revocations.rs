@@ -0,0 +1,14 @@
+use crate::db::models::TokenRevocationStatus;
+use crate::db::DbPool;
+
+pub async fn revoke_token(pool: &DbPool, jti: &str, user_id: &str, expires_at: i64) -> anyhow::Result<()> {
+    super::revoke_session_token(pool, jti, user_id, expires_at).await
+}
+
+pub async fn revoke_tokens_issued_before(pool: &DbPool, user_id: &str, valid_after: i64) -> anyhow::Result<()> {
+    super::revoke_session_tokens_issued_before(pool, user_id, valid_after).await
+}
+
+pub async fn token_status(pool: &DbPool, jti: &str, session_id: &str, user_id: &str) -> anyhow::Result<TokenRevocationStatus> {
+    super::session_token_status(pool, jti, session_id, user_id).await
+}
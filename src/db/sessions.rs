@@ -0,0 +1,18 @@
+use crate::db::models::{NewUserSession, UserSession};
+use crate::db::DbPool;
+
+pub async fn create_session(pool: &DbPool, session: &NewUserSession) -> anyhow::Result<()> {
+    super::create_user_session(pool, session).await
+}
+
+pub async fn list_sessions(pool: &DbPool, user_id: &str) -> anyhow::Result<Vec<UserSession>> {
+    super::list_user_sessions(pool, user_id).await
+}
+
+pub async fn active_session_expiry(pool: &DbPool, user_id: &str, jti: &str) -> anyhow::Result<Option<i64>> {
+    super::active_user_session_expiry(pool, user_id, jti).await
+}
+
+pub async fn touch_session(pool: &DbPool, jti: &str) -> anyhow::Result<()> {
+    super::touch_user_session(pool, jti).await
+}
@@ -0,0 +1,60 @@
+use crate::db::models::{NewVaultKeyRotation, VaultKeyRotation, VaultRotationItem};
+use crate::db::DbPool;
+
+pub async fn latest_rotation(pool: &DbPool, user_id: &str) -> anyhow::Result<Option<VaultKeyRotation>> {
+    super::latest_vault_rotation(pool, user_id).await
+}
+
+pub async fn create_rotation(pool: &DbPool, input: &NewVaultKeyRotation) -> anyhow::Result<VaultKeyRotation> {
+    super::create_vault_rotation(pool, input).await
+}
+
+/// Puts a failed run back to `running`, clearing the failure.
+pub async fn resume_rotation(pool: &DbPool, rotation_id: &str) -> anyhow::Result<VaultKeyRotation> {
+    super::resume_vault_rotation(pool, rotation_id).await
+}
+
+/// Encrypted items of the user whose payload does not start with `rotated_prefix`.
+pub async fn count_pending_items(pool: &DbPool, user_id: &str, rotated_prefix: &str) -> anyhow::Result<i64> {
+    super::count_vault_rotation_items(pool, user_id, rotated_prefix).await
+}
+
+/// Up to `limit` items whose payload does not start with `rotated_prefix`:
+/// charities, donations, receipts, then the profile.
+pub async fn pending_items(
+    pool: &DbPool,
+    user_id: &str,
+    rotated_prefix: &str,
+    limit: i64,
+) -> anyhow::Result<Vec<VaultRotationItem>> {
+    super::pending_vault_rotation_items(pool, user_id, rotated_prefix, limit).await
+}
+
+/// False when the item no longer holds the payload that was read.
+pub async fn replace_item_payload(
+    pool: &DbPool,
+    user_id: &str,
+    item: &VaultRotationItem,
+    payload: &str,
+) -> anyhow::Result<bool> {
+    super::replace_vault_item_payload(pool, user_id, item, payload).await
+}
+
+pub async fn record_progress(pool: &DbPool, rotation_id: &str, processed: i64) -> anyhow::Result<VaultKeyRotation> {
+    super::record_vault_rotation_progress(pool, rotation_id, processed).await
+}
+
+pub async fn fail_rotation(
+    pool: &DbPool,
+    rotation_id: &str,
+    failed_table: &str,
+    failed_record_id: &str,
+    error: &str,
+) -> anyhow::Result<VaultKeyRotation> {
+    super::fail_vault_rotation(pool, rotation_id, failed_table, failed_record_id, error).await
+}
+
+/// Completes the run and switches the vault credential to its new passkey, if any.
+pub async fn complete_rotation(pool: &DbPool, user_id: &str, rotation_id: &str) -> anyhow::Result<VaultKeyRotation> {
+    super::complete_vault_rotation(pool, user_id, rotation_id).await
+}
@@ -0,0 +1,48 @@
+use serde::Serialize;
+use tokio::sync::broadcast;
+
+// Slow subscribers that fall further behind than this are told to resync instead.
+const EVENT_BUS_CAPACITY: usize = 1024;
+
+#[derive(Clone, Debug, Serialize)]
+pub struct ChangeEvent {
+    #[serde(skip)]
+    pub user_id: String,
+    pub table: String, // "users", "charities", "donations", "receipts"
+    pub id: String,
+    pub op: String, // "upsert", "delete", "ocr"
+}
+
+/// In-process fan-out of per-user change notifications for `/api/events`.
+/// Only covers this instance; clients still converge through the change feed.
+#[derive(Clone)]
+pub struct EventBus {
+    sender: broadcast::Sender<ChangeEvent>,
+}
+
+impl Default for EventBus {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl EventBus {
+    pub fn new() -> Self {
+        let (sender, _) = broadcast::channel(EVENT_BUS_CAPACITY);
+        Self { sender }
+    }
+
+    pub fn publish(&self, user_id: &str, table: &str, id: &str, op: &str) {
+        // Sending only fails when nobody is listening, which is the common case.
+        let _ = self.sender.send(ChangeEvent {
+            user_id: user_id.to_string(),
+            table: table.to_string(),
+            id: id.to_string(),
+            op: op.to_string(),
+        });
+    }
+
+    pub fn subscribe(&self) -> broadcast::Receiver<ChangeEvent> {
+        self.sender.subscribe()
+    }
+}
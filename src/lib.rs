@@ -3,13 +3,27 @@ pub mod db;
 #[cfg(feature = "server")]
 mod auth;
 #[cfg(feature = "server")]
+mod events;
+#[cfg(feature = "server")]
 mod observability;
 #[cfg(feature = "server")]
 mod ocr;
 #[cfg(feature = "server")]
+mod oidc;
+#[cfg(feature = "server")]
+mod pdf;
+#[cfg(feature = "server")]
+pub mod receipt_gc;
+#[cfg(feature = "server")]
 mod routes;
 #[cfg(feature = "server")]
+mod security_events;
+#[cfg(feature = "server")]
 mod storage;
+#[cfg(feature = "server")]
+mod vault_envelope;
+#[cfg(feature = "server")]
+mod webauthn;
 
 pub use crate::db as db_mod;
 
@@ -19,3 +33,7 @@ include!("main_sections/bootstrap/server_bootstrap.rs");
 include!("main_sections/http/http_pipeline_and_assets.rs");
 #[cfg(feature = "asset-pipeline")]
 include!("main_sections/assets/asset_helpers.rs");
+
+#[cfg(all(test, feature = "server"))]
+#[path = "main_sections/http/http_pipeline_tests.rs"]
+mod http_pipeline_tests;
@@ -2,7 +2,7 @@ use axum::{
     extract::State,
     routing::{delete, get, post},
     Router,
-    middleware::{from_fn, Next},
+    middleware::{from_fn, from_fn_with_state, Next},
     http::{HeaderName, HeaderValue, StatusCode},
     response::{Html, IntoResponse},
 };
@@ -34,17 +34,16 @@ use url::Url;
 #[derive(Clone)]
 pub struct AppState {
     pub db: DbPool,
-    pub storage_endpoint: String,
-    pub bucket_name: String,
-    pub storage_region: String,
-    pub storage_access_key_id: String,
-    pub storage_secret_access_key: String,
+    pub storage: Arc<dyn crate::storage::ObjectStore>,
+    /// Largest receipt accepted, from `RECEIPT_MAX_UPLOAD_MB`.
+    pub max_receipt_bytes: i64,
     pub mistral_api_endpoint: Url,
     pub mistral_api_key: Option<String>,
     pub mistral_model: String,
     pub index_template: String,
     pub service_worker_script: String,
     pub asset_entrypoints: AssetEntrypoints,
+    pub events: crate::events::EventBus,
 }
 
 #[derive(Clone, serde::Serialize, serde::Deserialize)]
@@ -86,30 +85,33 @@ pub async fn run_app() -> anyhow::Result<()> {
     let db_pool = db::init_pool().await?;
     tracing::info!("Database connection pool initialized successfully");
 
-    // Oracle Object Storage Setup
-    let storage_endpoint = env::var("OBJECT_STORAGE_ENDPOINT").expect("OBJECT_STORAGE_ENDPOINT must be set");
-    let bucket_name = env::var("OBJECT_STORAGE_BUCKET").expect("OBJECT_STORAGE_BUCKET must be set");
-    let storage_region = env::var("OCI_REGION").expect("OCI_REGION must be set");
-    let storage_access_key_id = env::var("OCI_ACCESS_KEY_ID").expect("OCI_ACCESS_KEY_ID must be set");
-    let storage_secret_access_key = env::var("OCI_SECRET_ACCESS_KEY").expect("OCI_SECRET_ACCESS_KEY must be set");
+    // Object Storage Setup (S3-compatible, local filesystem or in-memory)
+    let storage = crate::storage::from_env()?;
+    let max_receipt_bytes = env::var("RECEIPT_MAX_UPLOAD_MB")
+        .ok()
+        .and_then(|v| v.parse::<i64>().ok())
+        .filter(|mb| *mb > 0)
+        .unwrap_or(10)
+        * 1024
+        * 1024;
     let mistral_api_endpoint = crate::ocr::load_mistral_api_endpoint()?;
     let mistral_api_key = env::var("MISTRAL_API_KEY").ok().filter(|value| !value.trim().is_empty());
     let mistral_model = env::var("MISTRAL_MODEL").unwrap_or_else(|_| "mistral-ocr-latest".to_string());
 
     let state = AppState {
         db: db_pool,
-        storage_endpoint,
-        bucket_name,
-        storage_region,
-        storage_access_key_id,
-        storage_secret_access_key,
+        storage,
+        max_receipt_bytes,
         mistral_api_endpoint,
         mistral_api_key,
         mistral_model,
         index_template,
         service_worker_script,
         asset_entrypoints,
+        events: crate::events::EventBus::new(),
     };
+    crate::receipt_gc::spawn_scheduled(&state);
+    crate::receipt_gc::spawn_upload_reaper(&state);
 
     let governor_config = Arc::new(
         GovernorConfigBuilder::default()
@@ -205,7 +207,10 @@ pub async fn run_app() -> anyhow::Result<()> {
                 header::AUTHORIZATION,
                 header::ACCEPT,
                 HeaderName::from_static("x-csrf-token"),
+                HeaderName::from_static("idempotency-key"),
+                HeaderName::from_static("x-token-delivery"),
             ])
+            .expose_headers([HeaderName::from_static("idempotent-replayed")])
             .allow_credentials(true)
     };
 
@@ -213,7 +218,10 @@ pub async fn run_app() -> anyhow::Result<()> {
         .route("/auth/login/{provider}", get(auth::login))
         .route("/auth/callback/{provider}", get(auth::callback).post(auth::callback))
         .route("/auth/logout", post(auth::logout))
+        .route("/auth/refresh", post(auth::refresh_session))
         .route("/auth/dev/login", post(auth::dev_login))
+        .route("/auth/passkey/options", post(auth::passkey_login_options))
+        .route("/auth/passkey/login", post(auth::passkey_login))
         .route("/api/auth/risc", post(auth::risc_webhook))
         .layer(GovernorLayer::new(auth_governor_config));
 
@@ -221,30 +229,78 @@ pub async fn run_app() -> anyhow::Result<()> {
         .route("/api/donations", get(routes::donations::list_donations).post(routes::donations::create_donation))
         .route("/api/donations/{id}", delete(routes::donations::delete_donation).put(routes::donations::update_donation))
         .route("/api/donations/import", post(routes::donations::import_donations))
+        .route("/api/donations/blind-index", get(routes::blind_index::search_donations))
+        .route("/api/donations/blind-index/years", get(routes::blind_index::list_year_tokens))
+        .route("/api/donations/blind-index/duplicates", get(routes::blind_index::list_duplicates))
         .route("/api/charities", get(routes::charities::list_charities).post(routes::charities::create_charity))
         .route("/api/charities/{id}", delete(routes::charities::delete_charity).put(routes::charities::update_charity))
         .route("/api/charities/search", get(routes::charities::search_charities))
         .route("/api/charities/lookup/{ein}", get(routes::charities::lookup_charity_by_ein))
         .route("/api/receipts/upload", post(routes::receipts::generate_upload_url))
+        .route("/api/receipts/upload-form", post(routes::receipts::generate_upload_form))
         .route("/api/receipts/presign", post(routes::receipts::generate_read_url))
         .route("/api/receipts/confirm", post(routes::receipts::confirm_receipt))
+        .route("/api/receipts/multipart", post(routes::receipt_uploads::start_multipart_upload))
+        .route("/api/receipts/multipart/{id}", delete(routes::receipt_uploads::abort_multipart_upload))
+        .route("/api/receipts/multipart/{id}/parts", post(routes::receipt_uploads::sign_upload_parts))
+        .route("/api/receipts/multipart/{id}/complete", post(routes::receipt_uploads::complete_multipart_upload))
         .route("/api/receipts/ocr", post(routes::receipts::ocr_receipt))
         .route("/api/receipts", get(routes::receipts::list_receipts))
+        .route("/api/receipts/{id}", delete(routes::receipts::delete_receipt).put(routes::receipts::update_receipt))
         .route("/api/valuations/suggest", post(routes::valuations::suggest))
         .route("/api/valuations/seed", post(routes::valuations::seed))
         .route("/api/valuations/tree", get(routes::valuations::tree))
         .route("/api/reports/years", get(routes::reports::list_available_years))
         .route("/api/reports/export", get(routes::reports::export_csv))
         .route("/api/reports/export/txf", get(routes::reports::export_tax_txf))
+        .route("/api/reports/export/pdf", get(routes::reports::export_pdf))
         .route("/api/reports/audit", get(routes::reports::export_audit_csv))
         .route("/api/tax/marginal-rate", get(routes::tax::marginal_rate))
         .route("/api/sync/batch", post(routes::sync::batch_sync))
+        .route("/api/sync/changes", get(routes::sync::list_changes))
+        .route("/api/events", get(routes::events::stream_events))
         .route("/api/me", get(auth::me).put(auth::update_me).delete(auth::delete_me))
         .route("/api/me/export", get(auth::export_me))
         .route("/api/me/import", post(auth::import_me))
+        .route("/api/me/logout-all", post(auth::logout_everywhere))
+        .route("/api/me/sessions", get(auth::list_sessions).delete(auth::revoke_all_sessions))
+        .route("/api/me/sessions/{id}", delete(auth::revoke_session))
+        .route("/api/me/accountants", get(routes::accountants::list_grants).post(routes::accountants::create_grant))
+        .route("/api/me/accountants/{id}", delete(routes::accountants::revoke_grant))
+        .route("/api/clients", get(routes::accountants::list_clients))
+        .route("/api/clients/{id}/profile", get(routes::accountants::client_profile))
+        .route("/api/clients/{id}/donations", get(routes::accountants::client_donations))
+        .route("/api/clients/{id}/charities", get(routes::accountants::client_charities))
+        .route("/api/clients/{id}/receipts", get(routes::accountants::client_receipts))
+        .route("/api/clients/{id}/receipts/{receipt_id}/url", get(routes::accountants::client_receipt_url))
+        .route("/api/clients/{id}/reports/export", get(routes::accountants::client_export_csv))
+        .route("/api/clients/{id}/reports/export/txf", get(routes::accountants::client_export_txf))
+        .route("/api/me/tokens", get(auth::list_access_tokens).post(auth::create_access_token))
+        .route("/api/me/tokens/{id}", delete(auth::revoke_access_token))
+        .route("/api/me/passkeys", get(auth::list_passkeys).post(auth::register_passkey))
+        .route("/api/me/passkeys/options", post(auth::passkey_registration_options))
+        .route("/api/me/passkeys/{id}", delete(auth::delete_passkey))
+        .route("/api/me/vault/options", post(auth::vault_unlock_options))
+        .route("/api/me/vault/unlock", post(auth::vault_unlock))
+        .route("/api/me/vault/rotation", get(routes::vault::rotation_status).post(routes::vault::rotate_vault))
         .route("/api/config", get(auth::get_config))
+        .route(
+            "/storage",
+            post(routes::storage::accept_local_form)
+                .layer(axum::extract::DefaultBodyLimit::max(routes::storage::local_form_body_limit(
+                    state.max_receipt_bytes,
+                ))),
+        )
+        .route(
+            "/storage/{*key}",
+            get(routes::storage::serve_local_object)
+                .put(routes::storage::serve_local_object)
+                .delete(routes::storage::serve_local_object),
+        )
+        .route("/api/openapi.json", get(routes::openapi::openapi_json))
         .merge(auth_router)
-        .layer(from_fn(require_auth))
+        .layer(from_fn_with_state(state.clone(), idempotency_keys))
+        .layer(from_fn_with_state(state.clone(), require_auth))
         .layer(cors)
         .layer(GovernorLayer::new(governor_config));
 
@@ -292,7 +348,8 @@ pub async fn run_app() -> anyhow::Result<()> {
             HeaderName::from_static("x-permitted-cross-domain-policies"),
             HeaderValue::from_static("none"),
         ))
-        .layer(SetResponseHeaderLayer::overriding(
+        // Handlers that serve untrusted bytes (`/storage/`) set a stricter policy of their own.
+        .layer(SetResponseHeaderLayer::if_not_present(
             header::CONTENT_SECURITY_POLICY,
             HeaderValue::from_static("default-src 'self'; script-src 'self' https://accounts.google.com; script-src-elem 'self' https://accounts.google.com; style-src 'self' 'unsafe-inline' https://accounts.google.com; font-src 'self' data:; img-src 'self' data: blob: https://*.oraclecloud.com; connect-src 'self' https://accounts.google.com https://*.oraclecloud.com; frame-src https://accounts.google.com; frame-ancestors 'none'; base-uri 'self'; form-action 'self'; upgrade-insecure-requests"),
         ))
@@ -177,18 +177,37 @@ async fn health_check() -> &'static str {
     "OK"
 }
 
-async fn require_auth(req: Request<Body>, next: Next) -> impl IntoResponse {
+/// API paths served without a session. Security event pushes carry no session: the
+/// signed event token is their authentication.
+fn is_public_api_path(path: &str) -> bool {
+    matches!(path, "/api/config" | "/api/openapi.json" | "/api/auth/risc")
+}
+
+async fn require_auth(State(state): State<AppState>, mut req: Request<Body>, next: Next) -> impl IntoResponse {
     // Guard only API endpoints here; non-API routes are SPA/document requests.
     let path = req.uri().path();
-    if req.method() == axum::http::Method::OPTIONS || !path.starts_with("/api/") || path == "/api/config" {
+    if req.method() == axum::http::Method::OPTIONS || !path.starts_with("/api/") || is_public_api_path(path) {
         return next.run(req).await;
     }
 
-    // CSRF Protection for state-changing methods
+    // Personal access tokens come only from the Authorization header, so CSRF does not apply.
+    if let Some(token) = auth::extract_bearer_token(req.headers()).filter(|t| auth::is_access_token(t)) {
+        return match auth::authenticate_access_token(&state.db, &token, req.method(), path).await {
+            Ok(user) => {
+                req.extensions_mut().insert(user);
+                next.run(req).await
+            }
+            Err((status, message)) => (status, message).into_response(),
+        };
+    }
+
+    // CSRF Protection for state-changing methods. Only the auth cookie is sent by the browser
+    // on its own; a token in the Authorization header (bearer delivery) cannot be forged cross-site.
     if matches!(
         req.method(),
         &axum::http::Method::POST | &axum::http::Method::PUT | &axum::http::Method::DELETE | &axum::http::Method::PATCH
-    ) {
+    ) && auth::extract_bearer_token(req.headers()).is_none()
+    {
         let headers = req.headers();
         let auth_token = auth::extract_token_from_headers(headers);
         if let Some(_auth) = auth_token {
@@ -227,8 +246,15 @@ async fn require_auth(req: Request<Body>, next: Next) -> impl IntoResponse {
     // Check headers for token
     let headers: &HeaderMap = req.headers();
     if let Some(token) = auth::extract_token_from_headers(headers) {
-        if auth::validate_token_str(&token).is_ok() {
-            return next.run(req).await;
+        match auth::validate_token_str(&state.db, &token).await {
+            Ok(user) => {
+                req.extensions_mut().insert(user);
+                return next.run(req).await;
+            }
+            Err((StatusCode::SERVICE_UNAVAILABLE, message)) => {
+                return (StatusCode::SERVICE_UNAVAILABLE, message).into_response();
+            }
+            Err(_) => {}
         }
     }
 
@@ -236,6 +262,149 @@ async fn require_auth(req: Request<Body>, next: Next) -> impl IntoResponse {
     (axum::http::StatusCode::UNAUTHORIZED, "Unauthorized").into_response()
 }
 
+const IDEMPOTENCY_KEY_HEADER: &str = "idempotency-key";
+const MAX_IDEMPOTENT_BODY_BYTES: usize = 16 * 1024 * 1024;
+
+fn idempotency_ttl_secs() -> i64 {
+    env::var("IDEMPOTENCY_TTL_HOURS")
+        .ok()
+        .and_then(|v| v.parse::<i64>().ok())
+        .filter(|hours| *hours > 0)
+        .unwrap_or(24)
+        * 3600
+}
+
+fn is_valid_idempotency_key(key: &str) -> bool {
+    !key.is_empty() && key.len() <= 255 && key.bytes().all(|b| b.is_ascii_graphic())
+}
+
+/// Hex SHA-256 over method, path with query, and body: a retry must match all three.
+fn idempotency_fingerprint(method: &axum::http::Method, path_and_query: &str, body: &[u8]) -> String {
+    use sha2::{Digest, Sha256};
+    let mut hasher = Sha256::new();
+    hasher.update(method.as_str().as_bytes());
+    hasher.update(b"\n");
+    hasher.update(path_and_query.as_bytes());
+    hasher.update(b"\n");
+    hasher.update(body);
+    hasher
+        .finalize()
+        .iter()
+        .map(|b| format!("{:02x}", b))
+        .collect()
+}
+
+/// Replays the stored response for a mutating request retried with the same `Idempotency-Key`.
+/// Runs inside `require_auth`, so keys are scoped to the authenticated user.
+async fn idempotency_keys(State(state): State<AppState>, req: Request<Body>, next: Next) -> axum::response::Response {
+    if !matches!(
+        req.method(),
+        &axum::http::Method::POST | &axum::http::Method::PUT | &axum::http::Method::DELETE | &axum::http::Method::PATCH
+    ) {
+        return next.run(req).await;
+    }
+    let Some(key) = req.headers().get(IDEMPOTENCY_KEY_HEADER) else {
+        return next.run(req).await;
+    };
+    let key = match key.to_str() {
+        Ok(k) if is_valid_idempotency_key(k) => k.to_string(),
+        _ => return (StatusCode::BAD_REQUEST, "Invalid Idempotency-Key").into_response(),
+    };
+    let Some(user) = req.extensions().get::<auth::AuthenticatedUser>().cloned() else {
+        return next.run(req).await;
+    };
+
+    let (parts, body) = req.into_parts();
+    let body = match axum::body::to_bytes(body, MAX_IDEMPOTENT_BODY_BYTES).await {
+        Ok(bytes) => bytes,
+        Err(_) => return (StatusCode::PAYLOAD_TOO_LARGE, "Request body too large").into_response(),
+    };
+    let path_and_query = parts
+        .uri
+        .path_and_query()
+        .map(|pq| pq.as_str())
+        .unwrap_or_else(|| parts.uri.path());
+    let fingerprint = idempotency_fingerprint(&parts.method, path_and_query, &body);
+
+    match db::idempotency::claim_key(&state.db, &user.id, &key, &fingerprint, idempotency_ttl_secs()).await {
+        Ok(db::models::IdempotencyClaim::Claimed) => {}
+        Ok(db::models::IdempotencyClaim::Replay(stored)) => {
+            let status = StatusCode::from_u16(stored.status_code).unwrap_or(StatusCode::OK);
+            let mut response = (status, stored.body).into_response();
+            if let Some(content_type) = stored.content_type.and_then(|ct| HeaderValue::from_str(&ct).ok()) {
+                response.headers_mut().insert(header::CONTENT_TYPE, content_type);
+            } else {
+                response.headers_mut().remove(header::CONTENT_TYPE);
+            }
+            response.headers_mut().insert(
+                HeaderName::from_static("idempotent-replayed"),
+                HeaderValue::from_static("true"),
+            );
+            return response;
+        }
+        Ok(db::models::IdempotencyClaim::InProgress) => {
+            return (StatusCode::CONFLICT, "A request with this Idempotency-Key is still in progress").into_response();
+        }
+        Ok(db::models::IdempotencyClaim::Mismatch) => {
+            return (StatusCode::UNPROCESSABLE_ENTITY, "Idempotency-Key was already used with a different request").into_response();
+        }
+        Err(e) => {
+            tracing::error!("Failed to claim idempotency key: {}", e);
+            return (StatusCode::INTERNAL_SERVER_ERROR, "Database error").into_response();
+        }
+    }
+
+    let response = next.run(Request::from_parts(parts, Body::from(body))).await;
+    let (parts, body) = response.into_parts();
+    // Streamed or oversized responses (exports, downloads) are passed through rather than
+    // buffered into memory and the idempotency table; the key is released so a retry re-runs.
+    let storable = axum::body::HttpBody::size_hint(&body)
+        .exact()
+        .is_some_and(|len| len <= MAX_IDEMPOTENT_BODY_BYTES as u64);
+    if !storable {
+        if let Err(e) = db::idempotency::release_key(&state.db, &user.id, &key).await {
+            tracing::error!("Failed to release idempotency key: {}", e);
+        }
+        return axum::response::Response::from_parts(parts, body);
+    }
+    let body = match axum::body::to_bytes(body, MAX_IDEMPOTENT_BODY_BYTES).await {
+        Ok(bytes) => bytes,
+        Err(e) => {
+            tracing::error!("Failed to buffer idempotent response: {}", e);
+            if let Err(e) = db::idempotency::release_key(&state.db, &user.id, &key).await {
+                tracing::error!("Failed to release idempotency key: {}", e);
+            }
+            return StatusCode::INTERNAL_SERVER_ERROR.into_response();
+        }
+    };
+
+    // Server errors are not final; let the client retry them with the same key.
+    let text = std::str::from_utf8(&body).ok();
+    match text {
+        Some(text) if !parts.status.is_server_error() => {
+            let stored = db::models::StoredResponse {
+                status_code: parts.status.as_u16(),
+                content_type: parts
+                    .headers
+                    .get(header::CONTENT_TYPE)
+                    .and_then(|v| v.to_str().ok())
+                    .map(str::to_string),
+                body: text.to_string(),
+            };
+            if let Err(e) = db::idempotency::store_response(&state.db, &user.id, &key, &stored).await {
+                tracing::error!("Failed to store idempotent response: {}", e);
+            }
+        }
+        _ => {
+            if let Err(e) = db::idempotency::release_key(&state.db, &user.id, &key).await {
+                tracing::error!("Failed to release idempotency key: {}", e);
+            }
+        }
+    }
+
+    axum::response::Response::from_parts(parts, Body::from(body))
+}
+
 async fn serve_index(State(state): State<AppState>) -> impl IntoResponse {
     let bootstrap = serde_json::json!({
         "dexie": state.asset_entrypoints.dexie,
@@ -658,4 +827,3 @@ fn prepare_fingerprinted_assets() -> anyhow::Result<AssetEntrypoints> {
 
     Ok(entrypoints)
 }
-
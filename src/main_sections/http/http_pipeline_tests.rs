@@ -0,0 +1,59 @@
+use super::*;
+use tower::ServiceExt;
+
+/// State whose database is never reachable, for exercising the middleware in isolation.
+pub(crate) fn test_state() -> AppState {
+    let driver_config = oracle_rs::Config::new("127.0.0.1", 9, "unreachable", "test", "test");
+    let pool = deadpool_oracle::PoolBuilder::new(driver_config)
+        .wait_timeout(Some(std::time::Duration::from_millis(200)))
+        .create_timeout(Some(std::time::Duration::from_millis(200)))
+        .build()
+        .expect("lazy pool");
+    AppState {
+        db: Arc::new(db::DbPoolEnum::Oracle(pool)),
+        storage: Arc::new(crate::storage::MemoryStore::new(
+            "test",
+            crate::storage::LocalUrlSigner::new("", b"test-signing-secret"),
+        )),
+        max_receipt_bytes: 10 * 1024 * 1024,
+        mistral_api_endpoint: Url::parse("http://127.0.0.1:9/").expect("url"),
+        mistral_api_key: None,
+        mistral_model: "test".to_string(),
+        index_template: String::new(),
+        service_worker_script: String::new(),
+        asset_entrypoints: AssetEntrypoints {
+            app: String::new(),
+            upload: String::new(),
+            dexie: String::new(),
+            service_worker_version: String::new(),
+            asset_rewrites: HashMap::new(),
+            precache_assets: Vec::new(),
+        },
+        events: crate::events::EventBus::new(),
+    }
+}
+
+/// `handler` mounted at `path` behind the same auth and idempotency layers as the API.
+pub(crate) fn guarded_router(path: &str, handler: axum::routing::MethodRouter<AppState>) -> Router {
+    let state = test_state();
+    Router::new()
+        .route(path, handler)
+        .layer(from_fn_with_state(state.clone(), idempotency_keys))
+        .layer(from_fn_with_state(state.clone(), require_auth))
+        .with_state(state)
+}
+
+#[tokio::test]
+async fn test_security_event_push_needs_no_session() {
+    let app = guarded_router("/api/auth/risc", post(|| async { StatusCode::ACCEPTED }));
+    let set = Request::post("/api/auth/risc")
+        .header(header::CONTENT_TYPE, "application/secevent+jwt")
+        .body(Body::from("eyJhbGciOiJSUzI1NiJ9.eyJqdGkiOiJ0ZXN0In0.c2ln"))
+        .unwrap();
+    let response = app.clone().oneshot(set).await.unwrap();
+    assert_eq!(response.status(), StatusCode::ACCEPTED);
+
+    let app = guarded_router("/api/me", post(|| async { StatusCode::OK }));
+    let response = app.oneshot(Request::post("/api/me").body(Body::empty()).unwrap()).await.unwrap();
+    assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+}
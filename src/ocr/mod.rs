@@ -5,7 +5,7 @@ mod endpoint;
 
 pub(crate) use endpoint::load_mistral_api_endpoint;
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, utoipa::ToSchema)]
 pub struct DonationReceiptSuggestion {
     pub date_of_donation: Option<NaiveDate>,
     pub organization_name: Option<String>,
@@ -13,6 +13,7 @@ pub struct DonationReceiptSuggestion {
     pub item_name: Option<String>,
     pub amount_usd: Option<f64>,
     #[serde(flatten)]
+    #[schema(ignore)]
     pub unknown_fields: serde_json::Map<String, serde_json::Value>,
 }
 
@@ -105,23 +106,13 @@ mod real {
         key: &str,
         hinted_content_type: Option<&str>,
     ) -> anyhow::Result<ReceiptAnalysis> {
-        let url = crate::storage::presign_url(state, "GET", key, 300)?;
-        let client = Client::new();
-        let response = client
-            .get(&url)
-            .send()
+        let bytes = state
+            .storage
+            .get_bytes(key)
             .await
-            .context("downloading uploaded receipt")?;
+            .context("downloading uploaded receipt")?
+            .ok_or_else(|| anyhow!("uploaded receipt not found in storage"))?;
 
-        if !response.status().is_success() {
-            return Err(anyhow!("failed to download object: {}", response.status()));
-        }
-
-        let bytes = response
-            .bytes()
-            .await
-            .context("reading uploaded receipt bytes")?;
-        
         analyze_receipt_bytes(state, &bytes, hinted_content_type).await
     }
 
@@ -0,0 +1,299 @@
+use anyhow::{anyhow, Context};
+use chrono::{DateTime, Duration, Utc};
+use jsonwebtoken::jwk::{Jwk, JwkSet};
+use jsonwebtoken::{Algorithm, DecodingKey, Validation};
+use serde::Deserialize;
+use serde_json::Value;
+use std::collections::HashMap;
+use std::sync::{OnceLock, RwLock};
+use url::Url;
+
+/// Provider metadata is re-fetched at most this often per issuer.
+const DISCOVERY_CACHE_TTL_MINUTES: i64 = 60;
+/// Signing keys are trusted for this long before the JWKS is fetched again.
+/// An unknown `kid` always triggers an early refresh so key rotation works.
+const JWKS_CACHE_TTL_MINUTES: i64 = 60;
+const ID_TOKEN_LEEWAY_SECONDS: u64 = 60;
+
+static DISCOVERY_CACHE: OnceLock<RwLock<HashMap<String, CachedMetadata>>> = OnceLock::new();
+static JWKS_CACHE: OnceLock<RwLock<HashMap<String, CachedKeys>>> = OnceLock::new();
+
+/// The subset of `.well-known/openid-configuration` the login flow relies on.
+#[derive(Debug, Clone, Deserialize)]
+pub(crate) struct OidcMetadata {
+    pub issuer: String,
+    pub authorization_endpoint: String,
+    pub token_endpoint: String,
+    #[serde(default)]
+    pub userinfo_endpoint: Option<String>,
+    pub jwks_uri: String,
+    #[serde(default)]
+    pub id_token_signing_alg_values_supported: Vec<String>,
+}
+
+/// Identity asserted by a validated id_token.
+#[derive(Debug, Clone)]
+pub(crate) struct OidcIdentity {
+    pub subject: String,
+    pub email: String,
+    pub email_verified: Option<bool>,
+    pub name: String,
+}
+
+struct CachedMetadata {
+    metadata: OidcMetadata,
+    expires_at: DateTime<Utc>,
+}
+
+struct CachedKeys {
+    keys: JwkSet,
+    expires_at: DateTime<Utc>,
+}
+
+#[derive(Deserialize)]
+struct IdTokenClaims {
+    sub: String,
+    #[serde(default)]
+    aud: Value,
+    #[serde(default)]
+    azp: Option<String>,
+    #[serde(default)]
+    nonce: Option<String>,
+    #[serde(default)]
+    email: Option<String>,
+    #[serde(default)]
+    email_verified: Option<Value>,
+    #[serde(default)]
+    name: Option<String>,
+    #[serde(default)]
+    preferred_username: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct TokenEndpointResponse {
+    #[serde(default)]
+    id_token: Option<String>,
+}
+
+fn normalize_issuer(issuer: &str) -> &str {
+    issuer.trim_end_matches('/')
+}
+
+/// Issuers must be https; plain http is only accepted for loopback hosts so
+/// a local Keycloak or the test issuer can be used during development.
+fn validate_issuer_url(issuer: &str) -> anyhow::Result<Url> {
+    let url = Url::parse(issuer).context("OIDC issuer must be an absolute URL")?;
+    let loopback = matches!(url.host_str(), Some("localhost") | Some("127.0.0.1") | Some("[::1]"));
+    if url.scheme() != "https" && !(url.scheme() == "http" && loopback) {
+        return Err(anyhow!("OIDC issuer must use https"));
+    }
+    if url.query().is_some() || url.fragment().is_some() {
+        return Err(anyhow!("OIDC issuer must not contain a query or fragment"));
+    }
+    Ok(url)
+}
+
+pub(crate) fn discovery_url(issuer: &str) -> String {
+    format!("{}/.well-known/openid-configuration", normalize_issuer(issuer))
+}
+
+/// Fetch (or return the cached) discovery document for `issuer`.
+pub(crate) async fn discover(client: &reqwest::Client, issuer: &str) -> anyhow::Result<OidcMetadata> {
+    let key = normalize_issuer(issuer).to_string();
+    let cache = DISCOVERY_CACHE.get_or_init(|| RwLock::new(HashMap::new()));
+    {
+        let guard = cache.read().map_err(|_| anyhow!("OIDC discovery cache lock poisoned"))?;
+        if let Some(entry) = guard.get(&key) {
+            if entry.expires_at > Utc::now() {
+                return Ok(entry.metadata.clone());
+            }
+        }
+    }
+
+    validate_issuer_url(issuer)?;
+    let resp = client
+        .get(discovery_url(issuer))
+        .send()
+        .await
+        .context("OIDC discovery request failed")?;
+    if !resp.status().is_success() {
+        return Err(anyhow!("OIDC discovery response status {}", resp.status()));
+    }
+    let metadata: OidcMetadata = resp.json().await.context("OIDC discovery document is invalid")?;
+
+    // The document must describe the issuer we asked about (OIDC Discovery §4.3).
+    if normalize_issuer(&metadata.issuer) != key {
+        return Err(anyhow!(
+            "OIDC discovery issuer mismatch: expected {}, got {}",
+            key,
+            metadata.issuer
+        ));
+    }
+
+    let mut guard = cache.write().map_err(|_| anyhow!("OIDC discovery cache lock poisoned"))?;
+    guard.insert(
+        key,
+        CachedMetadata {
+            metadata: metadata.clone(),
+            expires_at: Utc::now() + Duration::minutes(DISCOVERY_CACHE_TTL_MINUTES),
+        },
+    );
+    Ok(metadata)
+}
+
+fn select_key(keys: &JwkSet, kid: Option<&str>) -> Option<Jwk> {
+    match kid {
+        Some(kid) => keys.find(kid).cloned(),
+        // Without a kid we only accept an unambiguous single-key set.
+        None if keys.keys.len() == 1 => keys.keys.first().cloned(),
+        None => None,
+    }
+}
+
+pub(crate) async fn signing_key(client: &reqwest::Client, jwks_uri: &str, kid: Option<&str>) -> anyhow::Result<Jwk> {
+    let cache = JWKS_CACHE.get_or_init(|| RwLock::new(HashMap::new()));
+    {
+        let guard = cache.read().map_err(|_| anyhow!("OIDC JWKS cache lock poisoned"))?;
+        if let Some(entry) = guard.get(jwks_uri) {
+            if entry.expires_at > Utc::now() {
+                if let Some(jwk) = select_key(&entry.keys, kid) {
+                    return Ok(jwk);
+                }
+            }
+        }
+    }
+
+    tracing::info!("Fetching OIDC JWKS from {} (reason: expired or missing kid {:?})", jwks_uri, kid);
+    let resp = client
+        .get(jwks_uri)
+        .send()
+        .await
+        .context("OIDC JWKS request failed")?;
+    if !resp.status().is_success() {
+        return Err(anyhow!("OIDC JWKS response status {}", resp.status()));
+    }
+    let keys: JwkSet = resp.json().await.context("OIDC JWKS document is invalid")?;
+    let selected = select_key(&keys, kid);
+
+    let mut guard = cache.write().map_err(|_| anyhow!("OIDC JWKS cache lock poisoned"))?;
+    guard.insert(
+        jwks_uri.to_string(),
+        CachedKeys {
+            keys,
+            expires_at: Utc::now() + Duration::minutes(JWKS_CACHE_TTL_MINUTES),
+        },
+    );
+
+    selected.ok_or_else(|| anyhow!("OIDC JWKS has no signing key for kid {:?}", kid))
+}
+
+/// Redeem an authorization code at the token endpoint and return the raw id_token.
+/// Client credentials are sent with `client_secret_basic`, the discovery default.
+pub(crate) async fn exchange_code(
+    client: &reqwest::Client,
+    metadata: &OidcMetadata,
+    client_id: &str,
+    client_secret: &str,
+    redirect_uri: &str,
+    code: &str,
+    pkce_verifier: Option<&str>,
+) -> anyhow::Result<String> {
+    let mut form = vec![
+        ("grant_type", "authorization_code"),
+        ("code", code),
+        ("redirect_uri", redirect_uri),
+    ];
+    if let Some(verifier) = pkce_verifier {
+        form.push(("code_verifier", verifier));
+    }
+
+    // RFC 6749 §2.3.1: credentials are form-urlencoded before base64.
+    let encoded_id: String = url::form_urlencoded::byte_serialize(client_id.as_bytes()).collect();
+    let encoded_secret: String = url::form_urlencoded::byte_serialize(client_secret.as_bytes()).collect();
+
+    let resp = client
+        .post(&metadata.token_endpoint)
+        .basic_auth(encoded_id, Some(encoded_secret))
+        .header(reqwest::header::ACCEPT, "application/json")
+        .form(&form)
+        .send()
+        .await
+        .context("OIDC token request failed")?;
+    if !resp.status().is_success() {
+        return Err(anyhow!("OIDC token response status {}", resp.status()));
+    }
+    let body: TokenEndpointResponse = resp.json().await.context("OIDC token response is invalid")?;
+    body.id_token.ok_or_else(|| anyhow!("OIDC token response did not include an id_token"))
+}
+
+fn parse_email_verified(value: Option<&Value>) -> Option<bool> {
+    match value? {
+        Value::Bool(b) => Some(*b),
+        // Some providers (notably older Cognito and ADFS builds) send a string.
+        Value::String(s) => s.parse().ok(),
+        _ => None,
+    }
+}
+
+/// Verify an id_token's signature against the issuer's JWKS and check the
+/// issuer, audience, expiry and (when the login flow sent one) nonce.
+pub(crate) async fn validate_id_token(
+    client: &reqwest::Client,
+    metadata: &OidcMetadata,
+    client_id: &str,
+    id_token: &str,
+    expected_nonce: Option<&str>,
+) -> anyhow::Result<OidcIdentity> {
+    let header = jsonwebtoken::decode_header(id_token).context("id_token header is invalid")?;
+    if matches!(header.alg, Algorithm::HS256 | Algorithm::HS384 | Algorithm::HS512) {
+        return Err(anyhow!("id_token must be signed with an asymmetric algorithm"));
+    }
+    let alg_name = format!("{:?}", header.alg);
+    if !metadata.id_token_signing_alg_values_supported.is_empty()
+        && !metadata.id_token_signing_alg_values_supported.iter().any(|a| a == &alg_name)
+    {
+        return Err(anyhow!("id_token algorithm {} is not advertised by the issuer", alg_name));
+    }
+
+    let jwk = signing_key(client, &metadata.jwks_uri, header.kid.as_deref()).await?;
+    let decoding_key = DecodingKey::from_jwk(&jwk).context("OIDC signing key is unusable")?;
+
+    let mut validation = Validation::new(header.alg);
+    validation.leeway = ID_TOKEN_LEEWAY_SECONDS;
+    validation.set_issuer(&[metadata.issuer.as_str()]);
+    validation.set_audience(&[client_id]);
+    validation.set_required_spec_claims(&["exp", "iss", "aud", "sub"]);
+
+    let claims = jsonwebtoken::decode::<IdTokenClaims>(id_token, &decoding_key, &validation)
+        .context("id_token verification failed")?
+        .claims;
+
+    // OIDC Core §3.1.3.7: with several audiences the authorized party must be us.
+    let multiple_audiences = claims.aud.as_array().map(|a| a.len() > 1).unwrap_or(false);
+    if (multiple_audiences || claims.azp.is_some()) && claims.azp.as_deref() != Some(client_id) {
+        return Err(anyhow!("id_token authorized party mismatch"));
+    }
+
+    if let Some(expected) = expected_nonce {
+        if claims.nonce.as_deref() != Some(expected) {
+            return Err(anyhow!("id_token nonce mismatch"));
+        }
+    }
+
+    let email = claims
+        .email
+        .filter(|e| !e.trim().is_empty())
+        .ok_or_else(|| anyhow!("id_token has no email claim; request the email scope"))?;
+    let name = claims
+        .name
+        .or(claims.preferred_username)
+        .filter(|n| !n.trim().is_empty())
+        .unwrap_or_else(|| email.clone());
+
+    Ok(OidcIdentity {
+        subject: claims.sub,
+        email_verified: parse_email_verified(claims.email_verified.as_ref()),
+        email,
+        name,
+    })
+}
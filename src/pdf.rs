@@ -0,0 +1,109 @@
+// Minimal PDF writer for plain-text reports.
+//
+// Renders lines of text in Helvetica on US Letter pages, as many pages as the
+// lines need. Text is limited to the font's WinAnsi range; anything outside it
+// is written as `?`. No compression, images or embedded fonts. Reports can
+// hold decrypted vault data, so buffers other than the result are zeroed on drop.
+
+use zeroize::Zeroizing;
+
+const PAGE_WIDTH: u32 = 612;
+const PAGE_HEIGHT: u32 = 792;
+const MARGIN: u32 = 54;
+const TITLE_SIZE: u32 = 14;
+const FONT_SIZE: u32 = 10;
+const LEADING: u32 = 14;
+
+/// Lines that fit below the title on one page.
+pub(crate) const LINES_PER_PAGE: usize = ((PAGE_HEIGHT - 2 * MARGIN - 2 * LEADING) / LEADING) as usize;
+
+fn push_text(out: &mut String, text: &str) {
+    out.push('(');
+    for c in text.chars() {
+        match c {
+            '(' | ')' | '\\' => {
+                out.push('\\');
+                out.push(c);
+            }
+            ' '..='~' => out.push(c),
+            '\u{a0}'..='\u{ff}' => out.push_str(&format!("\\{:03o}", c as u32)),
+            _ => out.push('?'),
+        }
+    }
+    out.push_str(") Tj");
+}
+
+fn page_content(title: &str, page: usize, pages: usize, lines: &[String]) -> Zeroizing<String> {
+    let top = PAGE_HEIGHT - MARGIN;
+    let mut content = Zeroizing::new(String::with_capacity(64 * (lines.len() + 4)));
+    content.push_str(&format!("BT /F1 {TITLE_SIZE} Tf {MARGIN} {top} Td "));
+    push_text(&mut content, title);
+    content.push_str(" ET\n");
+    content.push_str(&format!(
+        "BT /F1 {FONT_SIZE} Tf {} {} Td (Page {} of {}) Tj ET\n",
+        PAGE_WIDTH - MARGIN - 60,
+        MARGIN / 2,
+        page + 1,
+        pages
+    ));
+    content.push_str(&format!("BT /F1 {FONT_SIZE} Tf {LEADING} TL {MARGIN} {} Td\n", top - 2 * LEADING));
+    for line in lines {
+        push_text(&mut content, line);
+        content.push_str(" T*\n");
+    }
+    content.push_str("ET\n");
+    content
+}
+
+/// A PDF of `lines` under `title`, repeated on every page with a page number.
+pub(crate) fn render_text_pdf(title: &str, lines: &[String]) -> Vec<u8> {
+    let chunks: Vec<&[String]> = if lines.is_empty() {
+        vec![&[]]
+    } else {
+        lines.chunks(LINES_PER_PAGE).collect()
+    };
+    let pages = chunks.len();
+
+    // Objects: 1 catalog, 2 page tree, 3 font, then a page and its content per page.
+    let page_ids: Vec<usize> = (0..pages).map(|i| 4 + 2 * i).collect();
+    let mut objects = Zeroizing::new(vec![
+        "<< /Type /Catalog /Pages 2 0 R >>".to_string(),
+        format!(
+            "<< /Type /Pages /Kids [{}] /Count {} >>",
+            page_ids.iter().map(|id| format!("{id} 0 R")).collect::<Vec<_>>().join(" "),
+            pages
+        ),
+        "<< /Type /Font /Subtype /Type1 /BaseFont /Helvetica /Encoding /WinAnsiEncoding >>".to_string(),
+    ]);
+    for (i, chunk) in chunks.iter().enumerate() {
+        let content = page_content(title, i, pages, chunk);
+        objects.push(format!(
+            "<< /Type /Page /Parent 2 0 R /MediaBox [0 0 {PAGE_WIDTH} {PAGE_HEIGHT}] /Resources << /Font << /F1 3 0 R >> >> /Contents {} 0 R >>",
+            page_ids[i] + 1
+        ));
+        let mut stream = format!("<< /Length {} >>\nstream\n", content.len());
+        stream.push_str(&content);
+        stream.push_str("endstream");
+        objects.push(stream);
+    }
+
+    let size: usize = objects.iter().map(|object| object.len() + 32).sum();
+    let mut out = Vec::with_capacity(size + 64 * objects.len() + 128);
+    out.extend_from_slice(b"%PDF-1.4\n");
+    let mut offsets = Vec::with_capacity(objects.len());
+    for (i, object) in objects.iter().enumerate() {
+        offsets.push(out.len());
+        out.extend_from_slice(format!("{} 0 obj\n", i + 1).as_bytes());
+        out.extend_from_slice(object.as_bytes());
+        out.extend_from_slice(b"\nendobj\n");
+    }
+    let xref = out.len();
+    out.extend_from_slice(format!("xref\n0 {}\n0000000000 65535 f \n", objects.len() + 1).as_bytes());
+    for offset in offsets {
+        out.extend_from_slice(format!("{offset:010} 00000 n \n").as_bytes());
+    }
+    out.extend_from_slice(
+        format!("trailer\n<< /Size {} /Root 1 0 R >>\nstartxref\n{}\n%%EOF\n", objects.len() + 1, xref).as_bytes(),
+    );
+    out
+}
@@ -0,0 +1,236 @@
+// Garbage collection of receipt objects that no receipt row points at.
+//
+// Uploads that are never confirmed, and account deletions whose storage calls
+// failed, leave objects under `receipts/` behind. A sweep lists them, diffs the
+// keys against `receipts.receipt_key` and deletes the unreferenced ones once
+// they are older than a grace period, which leaves in-flight uploads alone.
+// In dry-run mode it only reports what it would delete.
+//
+// Multipart uploads that are started and never completed are aborted the same
+// way once their session is older than `RECEIPT_UPLOAD_TTL_HOURS`.
+
+use chrono::{DateTime, Duration, Utc};
+use serde::Serialize;
+use std::collections::HashSet;
+
+use crate::db::DbPool;
+use crate::storage::ObjectStore;
+
+const RECEIPTS_PREFIX: &str = "receipts/";
+const DEFAULT_GRACE_HOURS: i64 = 24;
+const DEFAULT_UPLOAD_TTL_HOURS: i64 = 24;
+/// How often stale multipart uploads are looked for.
+const UPLOAD_REAPER_INTERVAL_SECS: u64 = 3600;
+
+#[derive(Debug, Serialize)]
+pub struct OrphanedObject {
+    pub key: String,
+    pub size: u64,
+    pub last_modified: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ReceiptGcReport {
+    pub dry_run: bool,
+    /// Objects younger than this were kept even if unreferenced.
+    pub cutoff: DateTime<Utc>,
+    pub scanned: usize,
+    pub referenced: usize,
+    /// Unreferenced objects still inside the grace period.
+    pub in_grace: usize,
+    /// Unreferenced objects past the grace period: deleted, or to be deleted on a dry run.
+    pub orphans: Vec<OrphanedObject>,
+    pub reclaimed_bytes: u64,
+    /// Keys whose deletion failed; they are picked up again by the next sweep.
+    pub failed: Vec<String>,
+}
+
+/// `RECEIPT_GC_GRACE_HOURS`, 24 by default.
+pub fn grace_period_from_env() -> Duration {
+    let hours = std::env::var("RECEIPT_GC_GRACE_HOURS")
+        .ok()
+        .and_then(|v| v.parse::<i64>().ok())
+        .filter(|hours| *hours >= 0)
+        .unwrap_or(DEFAULT_GRACE_HOURS);
+    Duration::hours(hours)
+}
+
+/// Sweeps the store once. Objects are listed before receipt rows are read, so a
+/// receipt confirmed during the sweep is seen as referenced.
+pub(crate) async fn sweep(
+    db: &DbPool,
+    store: &dyn ObjectStore,
+    grace: Duration,
+    dry_run: bool,
+) -> anyhow::Result<ReceiptGcReport> {
+    let objects = store.list(RECEIPTS_PREFIX).await?;
+    let referenced: HashSet<String> = crate::db::receipts::list_all_receipt_keys(db)
+        .await?
+        .iter()
+        .map(|key| store.normalize_key(key))
+        .collect();
+    sweep_objects(store, objects, &referenced, Utc::now() - grace, dry_run).await
+}
+
+async fn sweep_objects(
+    store: &dyn ObjectStore,
+    objects: Vec<crate::storage::ObjectMeta>,
+    referenced: &HashSet<String>,
+    cutoff: DateTime<Utc>,
+    dry_run: bool,
+) -> anyhow::Result<ReceiptGcReport> {
+    let mut report = ReceiptGcReport {
+        dry_run,
+        cutoff,
+        scanned: objects.len(),
+        referenced: 0,
+        in_grace: 0,
+        orphans: Vec::new(),
+        reclaimed_bytes: 0,
+        failed: Vec::new(),
+    };
+    for object in objects {
+        if referenced.contains(&object.key) {
+            report.referenced += 1;
+            continue;
+        }
+        // Without a timestamp there is no telling an upload in progress apart.
+        if object.last_modified.is_none_or(|modified| modified > cutoff) {
+            report.in_grace += 1;
+            continue;
+        }
+        if !dry_run {
+            if let Err(e) = store.delete(&object.key).await {
+                tracing::error!("Receipt GC failed to delete {}: {}", object.key, e);
+                report.failed.push(object.key);
+                continue;
+            }
+        }
+        report.reclaimed_bytes += object.size;
+        report.orphans.push(OrphanedObject {
+            key: object.key,
+            size: object.size,
+            last_modified: object.last_modified,
+        });
+    }
+    Ok(report)
+}
+
+/// Runs one sweep with the database and storage configured in the environment,
+/// for the `receipt-gc` command.
+pub async fn run_from_env(dry_run: bool) -> anyhow::Result<ReceiptGcReport> {
+    let db = crate::db::init_pool().await?;
+    let store = crate::storage::from_env()?;
+    sweep(&db, store.as_ref(), grace_period_from_env(), dry_run).await
+}
+
+/// Sweeps every `RECEIPT_GC_INTERVAL_HOURS` in the background when that is set.
+/// `RECEIPT_GC_DRY_RUN=true` logs the reports without deleting anything.
+pub(crate) fn spawn_scheduled(state: &crate::AppState) {
+    let Some(interval_hours) = std::env::var("RECEIPT_GC_INTERVAL_HOURS")
+        .ok()
+        .and_then(|v| v.parse::<u64>().ok())
+        .filter(|hours| *hours > 0)
+    else {
+        return;
+    };
+    let dry_run = std::env::var("RECEIPT_GC_DRY_RUN").is_ok_and(|v| v.eq_ignore_ascii_case("true"));
+    let grace = grace_period_from_env();
+    let state = state.clone();
+    tokio::spawn(async move {
+        let mut ticker = tokio::time::interval(std::time::Duration::from_secs(interval_hours * 3600));
+        loop {
+            ticker.tick().await;
+            match sweep(&state.db, state.storage.as_ref(), grace, dry_run).await {
+                Ok(report) => tracing::info!(
+                    "Receipt GC{}: scanned {}, referenced {}, in grace {}, orphans {} ({} bytes), failed {}",
+                    if dry_run { " (dry run)" } else { "" },
+                    report.scanned,
+                    report.referenced,
+                    report.in_grace,
+                    report.orphans.len(),
+                    report.reclaimed_bytes,
+                    report.failed.len()
+                ),
+                Err(e) => tracing::error!("Receipt GC failed: {}", e),
+            }
+        }
+    });
+}
+
+/// `RECEIPT_UPLOAD_TTL_HOURS`, 24 by default.
+fn upload_ttl_from_env() -> Duration {
+    let hours = std::env::var("RECEIPT_UPLOAD_TTL_HOURS")
+        .ok()
+        .and_then(|v| v.parse::<i64>().ok())
+        .filter(|hours| *hours > 0)
+        .unwrap_or(DEFAULT_UPLOAD_TTL_HOURS);
+    Duration::hours(hours)
+}
+
+/// Aborts the multipart uploads started before `cutoff` and drops their
+/// sessions. Returns how many were aborted; failures are retried next time.
+pub(crate) async fn abort_stale_uploads(state: &crate::AppState, cutoff: DateTime<Utc>) -> anyhow::Result<usize> {
+    let uploads = crate::db::receipt_uploads::list_uploads_started_before(&state.db, cutoff).await?;
+    let mut aborted = 0;
+    for upload in uploads {
+        match crate::routes::receipt_uploads::discard_upload(state, &upload).await {
+            Ok(()) => aborted += 1,
+            Err(e) => tracing::error!("Failed to abort stale upload {}: {}", upload.id, e),
+        }
+    }
+    Ok(aborted)
+}
+
+/// Aborts stale multipart uploads every hour.
+pub(crate) fn spawn_upload_reaper(state: &crate::AppState) {
+    let ttl = upload_ttl_from_env();
+    let state = state.clone();
+    tokio::spawn(async move {
+        let mut ticker = tokio::time::interval(std::time::Duration::from_secs(UPLOAD_REAPER_INTERVAL_SECS));
+        loop {
+            ticker.tick().await;
+            match abort_stale_uploads(&state, Utc::now() - ttl).await {
+                Ok(0) => {}
+                Ok(aborted) => tracing::info!("Aborted {} stale receipt uploads", aborted),
+                Err(e) => tracing::error!("Stale upload sweep failed: {}", e),
+            }
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::storage::{LocalUrlSigner, MemoryStore};
+    use axum::body::Bytes;
+
+    #[tokio::test]
+    async fn test_sweep_deletes_only_old_unreferenced_objects() {
+        let store = MemoryStore::new("local", LocalUrlSigner::new("", b"secret"));
+        for key in ["receipts/u1/2026/kept.pdf", "receipts/u1/2026/orphan.pdf", "receipts/u2/2026/orphan.png"] {
+            store.put(key, Bytes::from_static(b"12345"), None).await.unwrap();
+        }
+        let referenced: HashSet<String> = ["receipts/u1/2026/kept.pdf".to_string()].into();
+        let objects = || async { store.list(RECEIPTS_PREFIX).await.unwrap() };
+
+        // Everything is younger than a cutoff in the past.
+        let report = sweep_objects(&store, objects().await, &referenced, Utc::now() - Duration::hours(1), false)
+            .await
+            .unwrap();
+        assert_eq!((report.scanned, report.referenced, report.in_grace), (3, 1, 2));
+        assert!(report.orphans.is_empty());
+
+        let future = Utc::now() + Duration::seconds(1);
+        let report = sweep_objects(&store, objects().await, &referenced, future, true).await.unwrap();
+        assert_eq!(report.orphans.len(), 2);
+        assert_eq!(report.reclaimed_bytes, 10);
+        assert_eq!(store.list(RECEIPTS_PREFIX).await.unwrap().len(), 3, "dry run deletes nothing");
+
+        let report = sweep_objects(&store, objects().await, &referenced, future, false).await.unwrap();
+        assert_eq!(report.orphans.len(), 2);
+        assert!(report.failed.is_empty());
+        let left: Vec<String> = store.list(RECEIPTS_PREFIX).await.unwrap().into_iter().map(|m| m.key).collect();
+        assert_eq!(left, vec!["receipts/u1/2026/kept.pdf".to_string()]);
+    }
+}
@@ -0,0 +1,616 @@
+use crate::auth::AuthenticatedUser;
+use crate::AppState;
+use axum::{
+    extract::{Json, Path, Query, State},
+    http::StatusCode,
+    response::{IntoResponse, Json as AxumJson, Response},
+};
+use chrono::Datelike;
+use serde::Deserialize;
+use serde_json::json;
+use std::collections::HashSet;
+
+use crate::db;
+use crate::db::models::{ClientGrant, Donation, NewAccountantGrant};
+
+const MAX_GRANT_YEARS: usize = 20;
+const EARLIEST_TAX_YEAR: i32 = 1990;
+const RECEIPT_READ_EXPIRATION_SECS: u64 = 300;
+
+#[derive(Deserialize, utoipa::ToSchema)]
+pub struct CreateAccountantGrantRequest {
+    /// The accountant signs in with an account using this address, verified by their sign-in provider.
+    /// The first account to use the grant keeps it.
+    pub email: String,
+    pub tax_years: Vec<i32>,
+    /// Share the profile AGI. Defaults to false.
+    pub allow_agi: Option<bool>,
+}
+
+#[derive(Deserialize, utoipa::IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct ClientYearParams {
+    /// One of the granted years; all granted years when omitted.
+    pub year: Option<i32>,
+}
+
+#[derive(Deserialize, utoipa::IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct ClientReceiptParams {
+    pub donation_id: Option<String>,
+}
+
+/// Lower-cased email and sorted, de-duplicated years, or why the request is invalid.
+fn validate_grant_request(
+    req: &CreateAccountantGrantRequest,
+    owner_email: &str,
+    current_year: i32,
+) -> Result<(String, Vec<i32>), &'static str> {
+    let email = req.email.trim().to_lowercase();
+    let valid_email = email.len() <= 255
+        && email
+            .split_once('@')
+            .is_some_and(|(local, domain)| !local.is_empty() && domain.contains('.') && !domain.contains('@'));
+    if !valid_email {
+        return Err("A valid email address is required");
+    }
+    if email == owner_email.trim().to_lowercase() {
+        return Err("You cannot invite yourself");
+    }
+
+    let mut years = req.tax_years.clone();
+    years.sort_unstable();
+    years.dedup();
+    if years.is_empty() || years.len() > MAX_GRANT_YEARS {
+        return Err("Choose between 1 and 20 tax years");
+    }
+    if years.iter().any(|y| !(EARLIEST_TAX_YEAR..=current_year + 1).contains(y)) {
+        return Err("Tax year out of range");
+    }
+    Ok((email, years))
+}
+
+/// Audit action names for each kind of accountant access.
+#[derive(Clone, Copy)]
+enum ClientAccess {
+    Profile,
+    Donations,
+    Charities,
+    Receipts,
+    ReceiptDownload,
+    ExportCsv,
+    ExportTxf,
+}
+
+impl ClientAccess {
+    fn audit_action(self) -> &'static str {
+        match self {
+            ClientAccess::Profile => "accountant_view_profile",
+            ClientAccess::Donations => "accountant_list_donations",
+            ClientAccess::Charities => "accountant_list_charities",
+            ClientAccess::Receipts => "accountant_list_receipts",
+            ClientAccess::ReceiptDownload => "accountant_read_receipt",
+            ClientAccess::ExportCsv => "accountant_export_csv",
+            ClientAccess::ExportTxf => "accountant_export_txf",
+        }
+    }
+}
+
+/// Resolves the caller's grant and records the access in the owner's audit log.
+/// An access that cannot be audited is refused.
+async fn authorize_client_access(
+    state: &AppState,
+    user: &AuthenticatedUser,
+    grant_id: &str,
+    access: ClientAccess,
+    year: Option<i32>,
+    record_id: Option<&str>,
+) -> Result<ClientGrant, (StatusCode, &'static str)> {
+    let grant = match db::accountant_grants::find_client_grant(&state.db, grant_id, &user.email.to_lowercase(), &user.id).await {
+        Ok(Some(grant)) => grant,
+        Ok(None) => return Err((StatusCode::NOT_FOUND, "Not found")),
+        Err(e) => {
+            tracing::error!("Failed to load accountant grant: {}", e);
+            return Err((StatusCode::INTERNAL_SERVER_ERROR, "Database Error"));
+        }
+    };
+    if year.is_some_and(|y| !grant.tax_years.contains(&y)) {
+        return Err((StatusCode::FORBIDDEN, "Tax year not shared"));
+    }
+
+    if let Err(e) = db::accountant_grants::mark_accepted(&state.db, &grant.id, &user.id).await {
+        tracing::warn!("Failed to record accountant grant acceptance: {}", e);
+    }
+
+    let details = json!({
+        "grant_id": grant.id,
+        "accountant_id": user.id,
+        "accountant_email": user.email,
+        "year": year,
+        "record_id": record_id,
+    })
+    .to_string();
+    if let Err(e) = db::audit::log_audit(
+        &state.db,
+        &uuid::Uuid::new_v4().to_string(),
+        &grant.owner_user_id,
+        access.audit_action(),
+        "accountant_grants",
+        &Some(grant.id.clone()),
+        &Some(details),
+    )
+    .await
+    {
+        tracing::error!("Failed to audit accountant access: {}", e);
+        return Err((StatusCode::INTERNAL_SERVER_ERROR, "Database Error"));
+    }
+    Ok(grant)
+}
+
+/// The owner's donations in `year`, or in every granted year.
+async fn scoped_donations(state: &AppState, grant: &ClientGrant, year: Option<i32>) -> anyhow::Result<Vec<Donation>> {
+    let donations = db::donations::list_donations(&state.db, &grant.owner_user_id, year).await?;
+    Ok(donations
+        .into_iter()
+        .filter(|d| grant.tax_years.contains(&d.year))
+        .collect())
+}
+
+#[utoipa::path(
+    get,
+    path = "/api/me/accountants",
+    tag = "accountants",
+    responses((status = 200, description = "`{grants: [AccountantGrant]}`, newest first, including revoked", body = serde_json::Value))
+)]
+pub async fn list_grants(
+    State(state): State<AppState>,
+    user: AuthenticatedUser,
+) -> impl IntoResponse {
+    match db::accountant_grants::list_grants(&state.db, &user.id).await {
+        Ok(grants) => AxumJson(json!({ "grants": grants })).into_response(),
+        Err(e) => {
+            tracing::error!("Failed to list accountant grants: {}", e);
+            (StatusCode::INTERNAL_SERVER_ERROR, "Database Error").into_response()
+        }
+    }
+}
+
+#[utoipa::path(
+    post,
+    path = "/api/me/accountants",
+    tag = "accountants",
+    request_body = CreateAccountantGrantRequest,
+    responses(
+        (status = 201, description = "Accountant invited", body = crate::db::models::AccountantGrant),
+        (status = 400, description = "Invalid email or tax years", body = String),
+        (status = 409, description = "This accountant already has access", body = String),
+    )
+)]
+pub async fn create_grant(
+    State(state): State<AppState>,
+    user: AuthenticatedUser,
+    Json(req): Json<CreateAccountantGrantRequest>,
+) -> impl IntoResponse {
+    let (email, tax_years) = match validate_grant_request(&req, &user.email, chrono::Utc::now().year()) {
+        Ok(valid) => valid,
+        Err(msg) => return (StatusCode::BAD_REQUEST, msg).into_response(),
+    };
+    let input = NewAccountantGrant {
+        id: uuid::Uuid::new_v4().to_string(),
+        owner_user_id: user.id.clone(),
+        accountant_email: email,
+        tax_years,
+        allow_agi: req.allow_agi.unwrap_or(false),
+    };
+    let grant = match db::accountant_grants::create_grant(&state.db, &input).await {
+        Ok(Some(grant)) => grant,
+        Ok(None) => return (StatusCode::CONFLICT, "This accountant already has access").into_response(),
+        Err(e) => {
+            tracing::error!("Failed to create accountant grant: {}", e);
+            return (StatusCode::INTERNAL_SERVER_ERROR, "Database Error").into_response();
+        }
+    };
+
+    let details = json!({
+        "accountant_email": grant.accountant_email,
+        "tax_years": grant.tax_years,
+        "allow_agi": grant.allow_agi,
+    })
+    .to_string();
+    if let Err(e) = db::audit::log_audit(
+        &state.db,
+        &uuid::Uuid::new_v4().to_string(),
+        &user.id,
+        "accountant_grant_created",
+        "accountant_grants",
+        &Some(grant.id.clone()),
+        &Some(details),
+    )
+    .await
+    {
+        tracing::warn!("Failed to audit accountant grant: {}", e);
+    }
+    (StatusCode::CREATED, AxumJson(grant)).into_response()
+}
+
+#[utoipa::path(
+    delete,
+    path = "/api/me/accountants/{id}",
+    tag = "accountants",
+    params(("id" = String, Path, description = "Grant id")),
+    responses(
+        (status = 200, description = "Revoked", body = String),
+        (status = 404, description = "Not found or already revoked", body = String),
+    )
+)]
+pub async fn revoke_grant(
+    Path(grant_id): Path<String>,
+    State(state): State<AppState>,
+    user: AuthenticatedUser,
+) -> impl IntoResponse {
+    match db::accountant_grants::revoke_grant(&state.db, &user.id, &grant_id).await {
+        Ok(true) => {
+            if let Err(e) = db::audit::log_audit(
+                &state.db,
+                &uuid::Uuid::new_v4().to_string(),
+                &user.id,
+                "accountant_grant_revoked",
+                "accountant_grants",
+                &Some(grant_id.clone()),
+                &None,
+            )
+            .await
+            {
+                tracing::warn!("Failed to audit accountant grant revocation: {}", e);
+            }
+            (StatusCode::OK, "Revoked").into_response()
+        }
+        Ok(false) => (StatusCode::NOT_FOUND, "Not found").into_response(),
+        Err(e) => {
+            tracing::error!("Failed to revoke accountant grant: {}", e);
+            (StatusCode::INTERNAL_SERVER_ERROR, "Database Error").into_response()
+        }
+    }
+}
+
+#[utoipa::path(
+    get,
+    path = "/api/clients",
+    tag = "accountants",
+    responses((status = 200, description = "`{clients: [ClientGrant]}`: people who shared tax years with the caller's email", body = serde_json::Value))
+)]
+pub async fn list_clients(
+    State(state): State<AppState>,
+    user: AuthenticatedUser,
+) -> impl IntoResponse {
+    match db::accountant_grants::list_client_grants(&state.db, &user.email.to_lowercase(), &user.id).await {
+        Ok(clients) => AxumJson(json!({ "clients": clients })).into_response(),
+        Err(e) => {
+            tracing::error!("Failed to list clients: {}", e);
+            (StatusCode::INTERNAL_SERVER_ERROR, "Database Error").into_response()
+        }
+    }
+}
+
+#[utoipa::path(
+    get,
+    path = "/api/clients/{id}/profile",
+    tag = "accountants",
+    params(("id" = String, Path, description = "Grant id")),
+    responses(
+        (status = 200, description = "`{name, email, filing_status, itemize_deductions, agi, tax_years}`; agi is null unless shared", body = serde_json::Value),
+        (status = 404, description = "No active grant for the caller", body = String),
+    )
+)]
+pub async fn client_profile(
+    Path(grant_id): Path<String>,
+    State(state): State<AppState>,
+    user: AuthenticatedUser,
+) -> Response {
+    let grant = match authorize_client_access(&state, &user, &grant_id, ClientAccess::Profile, None, None).await {
+        Ok(grant) => grant,
+        Err(e) => return e.into_response(),
+    };
+    match db::users::get_user_profile(&state.db, &grant.owner_user_id).await {
+        Ok(Some(row)) => AxumJson(json!({
+            "name": row.1,
+            "email": row.0,
+            "filing_status": row.3,
+            "itemize_deductions": row.6,
+            "agi": if grant.allow_agi { row.4 } else { None },
+            "tax_years": grant.tax_years,
+        }))
+        .into_response(),
+        Ok(None) => (StatusCode::NOT_FOUND, "Not found").into_response(),
+        Err(e) => {
+            tracing::error!("Failed to load client profile: {}", e);
+            (StatusCode::INTERNAL_SERVER_ERROR, "Database Error").into_response()
+        }
+    }
+}
+
+#[utoipa::path(
+    get,
+    path = "/api/clients/{id}/donations",
+    tag = "accountants",
+    params(("id" = String, Path, description = "Grant id"), ClientYearParams),
+    responses(
+        (status = 200, description = "`{donations: [Donation]}` in the granted years", body = serde_json::Value),
+        (status = 403, description = "Year not shared", body = String),
+        (status = 404, description = "No active grant for the caller", body = String),
+    )
+)]
+pub async fn client_donations(
+    Path(grant_id): Path<String>,
+    State(state): State<AppState>,
+    user: AuthenticatedUser,
+    Query(params): Query<ClientYearParams>,
+) -> Response {
+    let grant = match authorize_client_access(&state, &user, &grant_id, ClientAccess::Donations, params.year, None).await {
+        Ok(grant) => grant,
+        Err(e) => return e.into_response(),
+    };
+    match scoped_donations(&state, &grant, params.year).await {
+        Ok(donations) => AxumJson(json!({ "donations": donations })).into_response(),
+        Err(e) => {
+            tracing::error!("Failed to list client donations: {}", e);
+            (StatusCode::INTERNAL_SERVER_ERROR, "Database Error").into_response()
+        }
+    }
+}
+
+#[utoipa::path(
+    get,
+    path = "/api/clients/{id}/charities",
+    tag = "accountants",
+    params(("id" = String, Path, description = "Grant id")),
+    responses(
+        (status = 200, description = "`{charities: [CharityResponse]}` given to in the granted years", body = serde_json::Value),
+        (status = 404, description = "No active grant for the caller", body = String),
+    )
+)]
+pub async fn client_charities(
+    Path(grant_id): Path<String>,
+    State(state): State<AppState>,
+    user: AuthenticatedUser,
+) -> Response {
+    let grant = match authorize_client_access(&state, &user, &grant_id, ClientAccess::Charities, None, None).await {
+        Ok(grant) => grant,
+        Err(e) => return e.into_response(),
+    };
+    let result = async {
+        let used: HashSet<String> = scoped_donations(&state, &grant, None)
+            .await?
+            .into_iter()
+            .map(|d| d.charity_id)
+            .collect();
+        let charities = db::charities::list_charities(&state.db, &grant.owner_user_id).await?;
+        anyhow::Ok(
+            charities
+                .into_iter()
+                .filter(|c| used.contains(&c.id))
+                .map(crate::routes::charities::handlers::CharityResponse::from)
+                .collect::<Vec<_>>(),
+        )
+    }
+    .await;
+    match result {
+        Ok(charities) => AxumJson(json!({ "charities": charities })).into_response(),
+        Err(e) => {
+            tracing::error!("Failed to list client charities: {}", e);
+            (StatusCode::INTERNAL_SERVER_ERROR, "Database Error").into_response()
+        }
+    }
+}
+
+#[utoipa::path(
+    get,
+    path = "/api/clients/{id}/receipts",
+    tag = "accountants",
+    params(("id" = String, Path, description = "Grant id"), ClientReceiptParams),
+    responses(
+        (status = 200, description = "`{receipts: [Receipt]}` for donations in the granted years", body = serde_json::Value),
+        (status = 404, description = "No active grant for the caller", body = String),
+    )
+)]
+pub async fn client_receipts(
+    Path(grant_id): Path<String>,
+    State(state): State<AppState>,
+    user: AuthenticatedUser,
+    Query(params): Query<ClientReceiptParams>,
+) -> Response {
+    let grant = match authorize_client_access(
+        &state,
+        &user,
+        &grant_id,
+        ClientAccess::Receipts,
+        None,
+        params.donation_id.as_deref(),
+    )
+    .await
+    {
+        Ok(grant) => grant,
+        Err(e) => return e.into_response(),
+    };
+    let result = async {
+        let in_scope: HashSet<String> = scoped_donations(&state, &grant, None)
+            .await?
+            .into_iter()
+            .map(|d| d.id)
+            .collect();
+        let receipts =
+            db::receipts::list_receipt_summaries(&state.db, &grant.owner_user_id, params.donation_id.clone()).await?;
+        anyhow::Ok(
+            receipts
+                .into_iter()
+                .filter(|r| in_scope.contains(&r.donation_id))
+                .collect::<Vec<_>>(),
+        )
+    }
+    .await;
+    match result {
+        Ok(receipts) => AxumJson(json!({ "receipts": receipts })).into_response(),
+        Err(e) => {
+            tracing::error!("Failed to list client receipts: {}", e);
+            (StatusCode::INTERNAL_SERVER_ERROR, "Database Error").into_response()
+        }
+    }
+}
+
+#[utoipa::path(
+    get,
+    path = "/api/clients/{id}/receipts/{receipt_id}/url",
+    tag = "accountants",
+    params(
+        ("id" = String, Path, description = "Grant id"),
+        ("receipt_id" = String, Path, description = "Receipt id"),
+    ),
+    responses(
+        (status = 200, description = "Presigned GET: `{download_url, expires_in}`", body = serde_json::Value),
+        (status = 404, description = "No active grant, or the receipt is outside the granted years", body = String),
+    )
+)]
+pub async fn client_receipt_url(
+    Path((grant_id, receipt_id)): Path<(String, String)>,
+    State(state): State<AppState>,
+    user: AuthenticatedUser,
+) -> Response {
+    let grant = match authorize_client_access(
+        &state,
+        &user,
+        &grant_id,
+        ClientAccess::ReceiptDownload,
+        None,
+        Some(&receipt_id),
+    )
+    .await
+    {
+        Ok(grant) => grant,
+        Err(e) => return e.into_response(),
+    };
+    let receipt = match db::receipts::get_receipt(&state.db, &grant.owner_user_id, &receipt_id).await {
+        Ok(Some(receipt)) => receipt,
+        Ok(None) => return (StatusCode::NOT_FOUND, "Not found").into_response(),
+        Err(e) => {
+            tracing::error!("Failed to load client receipt: {}", e);
+            return (StatusCode::INTERNAL_SERVER_ERROR, "Database Error").into_response();
+        }
+    };
+    let in_scope = match scoped_donations(&state, &grant, None).await {
+        Ok(donations) => donations.iter().any(|d| d.id == receipt.donation_id),
+        Err(e) => {
+            tracing::error!("Failed to list client donations: {}", e);
+            return (StatusCode::INTERNAL_SERVER_ERROR, "Database Error").into_response();
+        }
+    };
+    let key = state.storage.normalize_key(&receipt.key);
+    if !in_scope || !key.starts_with(&crate::storage::user_receipt_prefix(&grant.owner_user_id)) {
+        return (StatusCode::NOT_FOUND, "Not found").into_response();
+    }
+
+    match state.storage.presign("GET", &key, RECEIPT_READ_EXPIRATION_SECS) {
+        Ok(download_url) => AxumJson(json!({
+            "download_url": download_url,
+            "expires_in": RECEIPT_READ_EXPIRATION_SECS,
+        }))
+        .into_response(),
+        Err(e) => {
+            tracing::error!("Storage Presign Read Error: {}", e);
+            (StatusCode::INTERNAL_SERVER_ERROR, "Storage Error").into_response()
+        }
+    }
+}
+
+#[utoipa::path(
+    get,
+    path = "/api/clients/{id}/reports/export",
+    tag = "accountants",
+    params(("id" = String, Path, description = "Grant id"), ClientYearParams),
+    responses(
+        (status = 200, description = "Donations in the granted years as CSV", content_type = "text/csv", body = String),
+        (status = 403, description = "Year not shared", body = String),
+    )
+)]
+pub async fn client_export_csv(
+    Path(grant_id): Path<String>,
+    State(state): State<AppState>,
+    user: AuthenticatedUser,
+    Query(params): Query<ClientYearParams>,
+) -> Response {
+    client_export(&state, &user, &grant_id, params.year, ClientAccess::ExportCsv).await
+}
+
+#[utoipa::path(
+    get,
+    path = "/api/clients/{id}/reports/export/txf",
+    tag = "accountants",
+    params(("id" = String, Path, description = "Grant id"), ClientYearParams),
+    responses(
+        (status = 200, description = "Donations in the granted years in TXF", content_type = "application/octet-stream", body = String),
+        (status = 403, description = "Year not shared", body = String),
+    )
+)]
+pub async fn client_export_txf(
+    Path(grant_id): Path<String>,
+    State(state): State<AppState>,
+    user: AuthenticatedUser,
+    Query(params): Query<ClientYearParams>,
+) -> Response {
+    client_export(&state, &user, &grant_id, params.year, ClientAccess::ExportTxf).await
+}
+
+async fn client_export(
+    state: &AppState,
+    user: &AuthenticatedUser,
+    grant_id: &str,
+    year: Option<i32>,
+    access: ClientAccess,
+) -> Response {
+    let grant = match authorize_client_access(state, user, grant_id, access, year, None).await {
+        Ok(grant) => grant,
+        Err(e) => return e.into_response(),
+    };
+    match scoped_donations(state, &grant, year).await {
+        Ok(list) => match access {
+            ClientAccess::ExportTxf => crate::routes::reports::donations_txf_response(&list),
+            _ => crate::routes::reports::donations_csv_response(&list),
+        },
+        Err(e) => {
+            tracing::error!("Failed to export client donations: {}", e);
+            (StatusCode::INTERNAL_SERVER_ERROR, "Database Error").into_response()
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn request(email: &str, tax_years: Vec<i32>) -> CreateAccountantGrantRequest {
+        CreateAccountantGrantRequest {
+            email: email.to_string(),
+            tax_years,
+            allow_agi: None,
+        }
+    }
+
+    #[test]
+    fn test_validate_grant_request_normalizes_email_and_years() {
+        let (email, years) =
+            validate_grant_request(&request("  CPA@Example.com ", vec![2025, 2024, 2025]), "me@example.com", 2025)
+                .expect("valid request");
+        assert_eq!(email, "cpa@example.com");
+        assert_eq!(years, vec![2024, 2025]);
+    }
+
+    #[test]
+    fn test_validate_grant_request_rejects_bad_input() {
+        let owner = "me@example.com";
+        assert!(validate_grant_request(&request("not-an-email", vec![2024]), owner, 2025).is_err());
+        assert!(validate_grant_request(&request("a@b@example.com", vec![2024]), owner, 2025).is_err());
+        assert!(validate_grant_request(&request("ME@example.com", vec![2024]), owner, 2025).is_err());
+        assert!(validate_grant_request(&request("cpa@example.com", vec![]), owner, 2025).is_err());
+        assert!(validate_grant_request(&request("cpa@example.com", vec![2027]), owner, 2025).is_err());
+        assert!(validate_grant_request(&request("cpa@example.com", (2000..2025).collect()), owner, 2025).is_err());
+    }
+}
@@ -0,0 +1,143 @@
+use crate::auth::AuthenticatedUser;
+use crate::db;
+use crate::db::models::BlindIndexTokens;
+use crate::AppState;
+use axum::{
+    extract::{Query, State},
+    http::StatusCode,
+    response::{IntoResponse, Json as AxumJson},
+};
+use serde::Deserialize;
+use serde_json::json;
+
+const MAX_TOKEN_LEN: usize = 128;
+
+#[derive(Deserialize, utoipa::IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct BlindIndexQuery {
+    /// Charity token; every token given must match.
+    pub charity: Option<String>,
+    pub year: Option<String>,
+    pub category: Option<String>,
+    pub dedupe: Option<String>,
+}
+
+/// Tokens are opaque, but must be base64, base64url or hex of at most 128 characters.
+pub(crate) fn validate_blind_index(tokens: &BlindIndexTokens) -> Result<(), &'static str> {
+    let valid = |token: &str| {
+        !token.is_empty()
+            && token.len() <= MAX_TOKEN_LEN
+            && token
+                .bytes()
+                .all(|b| b.is_ascii_alphanumeric() || matches!(b, b'+' | b'/' | b'=' | b'-' | b'_'))
+    };
+    let all = [&tokens.charity, &tokens.year, &tokens.category, &tokens.dedupe];
+    if all.into_iter().flatten().all(|token| valid(token)) {
+        Ok(())
+    } else {
+        Err("Blind index tokens must be 1-128 characters of base64 or hex")
+    }
+}
+
+fn filter_from_query(params: BlindIndexQuery) -> Result<BlindIndexTokens, &'static str> {
+    let filter = BlindIndexTokens {
+        charity: params.charity,
+        year: params.year,
+        category: params.category,
+        dedupe: params.dedupe,
+    };
+    if db::oracle::blind_index::token_entries(&filter).is_empty() {
+        return Err("At least one blind index token is required");
+    }
+    validate_blind_index(&filter)?;
+    Ok(filter)
+}
+
+#[utoipa::path(
+    get,
+    path = "/api/donations/blind-index",
+    tag = "donations",
+    params(BlindIndexQuery),
+    responses(
+        (status = 200, description = "`{donations: [Donation]}` carrying every given token", body = serde_json::Value),
+        (status = 400, description = "No token given, or a token is malformed", body = String),
+    )
+)]
+pub async fn search_donations(
+    State(state): State<AppState>,
+    user: AuthenticatedUser,
+    Query(params): Query<BlindIndexQuery>,
+) -> impl IntoResponse {
+    let filter = match filter_from_query(params) {
+        Ok(filter) => filter,
+        Err(message) => return (StatusCode::BAD_REQUEST, message).into_response(),
+    };
+    match db::blind_index::find_donations(&state.db, &user.id, &filter).await {
+        Ok(donations) => AxumJson(json!({ "donations": donations })).into_response(),
+        Err(e) => {
+            tracing::error!("Blind index search failed: {}", e);
+            (StatusCode::INTERNAL_SERVER_ERROR, "Database Error").into_response()
+        }
+    }
+}
+
+#[utoipa::path(
+    get,
+    path = "/api/donations/blind-index/years",
+    tag = "donations",
+    responses(
+        (status = 200, description = "`{year_tokens: [String]}`, the distinct year tokens of live donations", body = serde_json::Value),
+    )
+)]
+pub async fn list_year_tokens(State(state): State<AppState>, user: AuthenticatedUser) -> impl IntoResponse {
+    match db::blind_index::list_year_tokens(&state.db, &user.id).await {
+        Ok(year_tokens) => AxumJson(json!({ "year_tokens": year_tokens })).into_response(),
+        Err(e) => {
+            tracing::error!("Failed to list blind index years: {}", e);
+            (StatusCode::INTERNAL_SERVER_ERROR, "Database Error").into_response()
+        }
+    }
+}
+
+#[utoipa::path(
+    get,
+    path = "/api/donations/blind-index/duplicates",
+    tag = "donations",
+    responses(
+        (status = 200, description = "`{duplicates: [BlindIndexDuplicateGroup]}`, donations sharing a dedupe token", body = serde_json::Value),
+    )
+)]
+pub async fn list_duplicates(State(state): State<AppState>, user: AuthenticatedUser) -> impl IntoResponse {
+    match db::blind_index::list_duplicates(&state.db, &user.id).await {
+        Ok(duplicates) => AxumJson(json!({ "duplicates": duplicates })).into_response(),
+        Err(e) => {
+            tracing::error!("Failed to list blind index duplicates: {}", e);
+            (StatusCode::INTERNAL_SERVER_ERROR, "Database Error").into_response()
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn query(charity: Option<&str>, year: Option<&str>) -> BlindIndexQuery {
+        BlindIndexQuery {
+            charity: charity.map(str::to_string),
+            year: year.map(str::to_string),
+            category: None,
+            dedupe: None,
+        }
+    }
+
+    #[test]
+    fn test_search_requires_a_well_formed_token() {
+        assert!(filter_from_query(query(None, None)).is_err());
+        assert!(filter_from_query(query(Some("abc' OR 1=1"), None)).is_err());
+        assert!(filter_from_query(query(Some(&"a".repeat(129)), None)).is_err());
+
+        let filter = filter_from_query(query(Some("q3Zk-_09+/="), Some("9f86d081884c7d65"))).unwrap();
+        assert_eq!(filter.year.as_deref(), Some("9f86d081884c7d65"));
+        assert_eq!(filter.category, None);
+    }
+}
@@ -1,6 +1,6 @@
 mod api_fetch;
 mod charity_enrichment;
-mod handlers;
+pub(crate) mod handlers;
 
 pub use handlers::{
     create_charity, delete_charity, list_charities, lookup_charity_by_ein, search_charities,
@@ -24,7 +24,7 @@ struct CharityResult {
     location: String,
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, utoipa::ToSchema)]
 pub struct CreateCharityRequest {
     pub name: String,
     pub ein: Option<String>,
@@ -41,7 +41,7 @@ pub struct CreateCharityRequest {
     pub encrypted_payload: Option<String>,
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, utoipa::ToSchema)]
 pub struct UpdateCharityRequest {
     pub name: String,
     pub ein: Option<String>,
@@ -58,7 +58,7 @@ pub struct UpdateCharityRequest {
     pub encrypted_payload: Option<String>,
 }
 
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, utoipa::ToSchema)]
 pub struct CharityResponse {
     pub id: String,
     pub name: String,
@@ -74,6 +74,35 @@ pub struct CharityResponse {
     pub zip: Option<String>,
 }
 
+impl From<crate::db::models::Charity> for CharityResponse {
+    fn from(c: crate::db::models::Charity) -> Self {
+        CharityResponse {
+            id: c.id,
+            name: c.name,
+            ein: c.ein,
+            category: c.category,
+            status: c.status,
+            classification: c.classification,
+            nonprofit_type: c.nonprofit_type,
+            deductibility: c.deductibility,
+            street: c.street,
+            city: c.city,
+            state: c.state,
+            zip: c.zip,
+        }
+    }
+}
+
+#[utoipa::path(
+    get,
+    path = "/api/charities/search",
+    tag = "charities",
+    params(("q" = String, Query, description = "Name or EIN to search ProPublica for")),
+    responses(
+        (status = 200, description = "`{results: [{ein, name, location}]}`", body = serde_json::Value),
+        (status = 502, description = "Upstream API error", body = String),
+    )
+)]
 pub async fn search_charities(
     _user: AuthenticatedUser,
     Query(params): Query<HashMap<String, String>>,
@@ -120,6 +149,17 @@ pub async fn search_charities(
     }
 }
 
+#[utoipa::path(
+    get,
+    path = "/api/charities/lookup/{ein}",
+    tag = "charities",
+    params(("ein" = String, Path, description = "Employer identification number")),
+    responses(
+        (status = 200, description = "`{charity}` with ProPublica details", body = serde_json::Value),
+        (status = 400, description = "Invalid EIN", body = String),
+        (status = 404, description = "No organization found", body = String),
+    )
+)]
 pub async fn lookup_charity_by_ein(
     Path(ein): Path<String>,
     _user: AuthenticatedUser,
@@ -135,6 +175,14 @@ pub async fn lookup_charity_by_ein(
     }
 }
 
+#[utoipa::path(
+    get,
+    path = "/api/charities",
+    tag = "charities",
+    responses(
+        (status = 200, description = "`{charities: [CharityResponse]}`", body = serde_json::Value),
+    )
+)]
 pub async fn list_charities(
     State(state): State<AppState>,
     user: AuthenticatedUser,
@@ -143,20 +191,7 @@ pub async fn list_charities(
         Ok(list) => {
             let out: Vec<CharityResponse> = list
                 .into_iter()
-                .map(|c| CharityResponse {
-                    id: c.id,
-                    name: c.name,
-                    ein: c.ein,
-                    category: c.category,
-                    status: c.status,
-                    classification: c.classification,
-                    nonprofit_type: c.nonprofit_type,
-                    deductibility: c.deductibility,
-                    street: c.street,
-                    city: c.city,
-                    state: c.state,
-                    zip: c.zip,
-                })
+                .map(CharityResponse::from)
                 .collect();
             (StatusCode::OK, AxumJson(json!({ "charities": out }))).into_response()
         }
@@ -167,6 +202,17 @@ pub async fn list_charities(
     }
 }
 
+#[utoipa::path(
+    post,
+    path = "/api/charities",
+    tag = "charities",
+    request_body = CreateCharityRequest,
+    responses(
+        (status = 201, description = "Created: `{charity, created: true}`", body = serde_json::Value),
+        (status = 200, description = "Matched an existing charity: `{charity, created: false}`", body = serde_json::Value),
+        (status = 400, description = "Name required", body = String),
+    )
+)]
 pub async fn create_charity(
     State(state): State<AppState>,
     user: AuthenticatedUser,
@@ -319,6 +365,7 @@ pub async fn create_charity(
         tracing::error!("Charity create error: {}", e);
         return (StatusCode::INTERNAL_SERVER_ERROR, "Database Error").into_response();
     }
+    state.events.publish(&user.id, "charities", &new_charity.id, "upsert");
 
     let payload = CharityResponse {
         id: new_charity.id.clone(),
@@ -341,6 +388,17 @@ pub async fn create_charity(
         .into_response()
 }
 
+#[utoipa::path(
+    delete,
+    path = "/api/charities/{id}",
+    tag = "charities",
+    params(("id" = String, Path, description = "Charity id")),
+    responses(
+        (status = 200, description = "Deleted", body = String),
+        (status = 404, description = "Not found", body = String),
+        (status = 409, description = "Charity has donations", body = String),
+    )
+)]
 pub async fn delete_charity(
     Path(charity_id): Path<String>,
     State(state): State<AppState>,
@@ -359,7 +417,10 @@ pub async fn delete_charity(
     }
 
     match crate::db::charities::delete_charity(&state.db, &user.id, &charity_id).await {
-        Ok(true) => (StatusCode::OK, "Deleted").into_response(),
+        Ok(true) => {
+            state.events.publish(&user.id, "charities", &charity_id, "delete");
+            (StatusCode::OK, "Deleted").into_response()
+        }
         Ok(false) => match crate::db::charities::count_donations_for_charity(
             &state.db,
             &user.id,
@@ -386,6 +447,17 @@ pub async fn delete_charity(
     }
 }
 
+#[utoipa::path(
+    put,
+    path = "/api/charities/{id}",
+    tag = "charities",
+    params(("id" = String, Path, description = "Charity id")),
+    request_body = UpdateCharityRequest,
+    responses(
+        (status = 200, description = "Updated: `{charity, updated: true}`", body = serde_json::Value),
+        (status = 404, description = "Not found", body = String),
+    )
+)]
 pub async fn update_charity(
     Path(charity_id): Path<String>,
     State(state): State<AppState>,
@@ -436,6 +508,7 @@ pub async fn update_charity(
 
     match crate::db::charities::update_charity(&state.db, &patch).await {
         Ok(true) => {
+            state.events.publish(&user.id, "charities", &charity_id, "upsert");
             let payload = CharityResponse {
                 id: charity_id,
                 name: name_owned,
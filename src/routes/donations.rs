@@ -7,7 +7,7 @@ use axum::{
 use serde::Deserialize;
 // Donation model is provided via DB helper responses; no direct import needed here.
 use crate::auth::AuthenticatedUser;
-use crate::db::models::{DonationPatch, NewCharity, NewDonation};
+use crate::db::models::{BlindIndexTokens, DonationPatch, NewCharity, NewDonation};
 use chrono::{DateTime, Datelike, NaiveDate, Utc};
 use uuid::Uuid;
 
@@ -22,7 +22,7 @@ fn normalize_category(input: &Option<String>) -> Option<String> {
     })
 }
 
-#[derive(Deserialize)]
+#[derive(Deserialize, utoipa::ToSchema)]
 pub struct CreateDonationRequest {
     pub date: Option<String>, // YYYY-MM-DD
     pub charity_name: String,
@@ -34,13 +34,24 @@ pub struct CreateDonationRequest {
     pub id: Option<String>,
     pub is_encrypted: Option<bool>,
     pub encrypted_payload: Option<String>,
+    /// Tokens for searching the donation once it is encrypted.
+    pub blind_index: Option<BlindIndexTokens>,
 }
 
-#[derive(serde::Deserialize)]
+#[derive(serde::Deserialize, utoipa::ToSchema)]
 pub struct ImportCsvRequest {
     pub csv: String,
 }
 
+#[utoipa::path(
+    post,
+    path = "/api/donations/import",
+    tag = "donations",
+    request_body = ImportCsvRequest,
+    responses(
+        (status = 200, description = "Counts of imported and skipped rows: `{imported, skipped}`", body = serde_json::Value),
+    )
+)]
 pub async fn import_donations(
     State(state): State<AppState>,
     user: AuthenticatedUser,
@@ -129,6 +140,7 @@ pub async fn import_donations(
                                 tracing::error!("Import charity create failed: {}", e);
                                 continue;
                             }
+                            state.events.publish(&user.id, "charities", &new_id, "upsert");
                             new_id
                         }
                         Err(e) => {
@@ -149,12 +161,14 @@ pub async fn import_donations(
                     notes: notes.clone(),
                     is_encrypted: None,
                     encrypted_payload: None,
+                    blind_index: None,
                     created_at: now,
                 };
                 if let Err(e) = crate::db::donations::add_donation(&state.db, &new_donation).await {
                     tracing::error!("Import add_donation failed: {}", e);
                 } else {
                     imported += 1;
+                    state.events.publish(&user.id, "donations", &id, "upsert");
                     let audit_id = Uuid::new_v4().to_string();
                     let details = Some(format!("Imported donation id={}", id));
                     let _ = crate::db::audit::log_audit(
@@ -180,7 +194,7 @@ pub async fn import_donations(
         .into_response()
 }
 
-#[derive(Deserialize)]
+#[derive(Deserialize, utoipa::ToSchema)]
 pub struct UpdateDonationRequest {
     pub date: Option<String>, // YYYY-MM-DD
     pub charity_id: Option<String>,
@@ -190,15 +204,23 @@ pub struct UpdateDonationRequest {
     pub updated_at: Option<String>, // RFC3339
     pub is_encrypted: Option<bool>,
     pub encrypted_payload: Option<String>,
+    /// Replaces the donation's tokens when present.
+    pub blind_index: Option<BlindIndexTokens>,
 }
 
-#[derive(Deserialize)]
+#[derive(Deserialize, utoipa::IntoParams)]
+#[into_params(parameter_in = Query)]
 pub struct ListParams {
     year: Option<i32>,
+    /// RFC3339 timestamp; only donations updated after it are returned.
     pub since: Option<String>,
 }
 
 fn validate_create_donation_request(req: &CreateDonationRequest) -> Result<(), &'static str> {
+    if let Some(tokens) = &req.blind_index {
+        crate::routes::blind_index::validate_blind_index(tokens)?;
+    }
+
     if req.is_encrypted.unwrap_or(false) {
         return Ok(());
     }
@@ -232,6 +254,10 @@ fn validate_create_donation_request(req: &CreateDonationRequest) -> Result<(), &
 }
 
 fn validate_update_donation_request(req: &UpdateDonationRequest) -> Result<(), &'static str> {
+    if let Some(tokens) = &req.blind_index {
+        crate::routes::blind_index::validate_blind_index(tokens)?;
+    }
+
     if req.is_encrypted.unwrap_or(false) {
         return Ok(());
     }
@@ -257,6 +283,16 @@ fn validate_update_donation_request(req: &UpdateDonationRequest) -> Result<(), &
     Ok(())
 }
 
+#[utoipa::path(
+    post,
+    path = "/api/donations",
+    tag = "donations",
+    request_body = CreateDonationRequest,
+    responses(
+        (status = 201, description = "Created: `{status, id, charity_id}`", body = serde_json::Value),
+        (status = 400, description = "Validation failed", body = String),
+    )
+)]
 pub async fn create_donation(
     State(state): State<AppState>,
     user: AuthenticatedUser,
@@ -342,6 +378,7 @@ pub async fn create_donation(
                     tracing::error!("Charity create failed: {}", e);
                     return (StatusCode::INTERNAL_SERVER_ERROR, "Database Error").into_response();
                 }
+                state.events.publish(&user_id, "charities", &new_id, "upsert");
                 new_id
             }
             Err(e) => {
@@ -362,6 +399,7 @@ pub async fn create_donation(
         notes: req.notes.clone(),
         is_encrypted: req.is_encrypted,
         encrypted_payload: req.encrypted_payload.clone(),
+        blind_index: req.blind_index.clone(),
         created_at: now,
     };
 
@@ -369,6 +407,7 @@ pub async fn create_donation(
         tracing::error!("DB Error: {}", e);
         return (StatusCode::INTERNAL_SERVER_ERROR, "Database Error").into_response();
     }
+    state.events.publish(&user_id, "donations", &id, "upsert");
 
     (
         StatusCode::CREATED,
@@ -377,13 +416,26 @@ pub async fn create_donation(
         .into_response()
 }
 
+#[utoipa::path(
+    delete,
+    path = "/api/donations/{id}",
+    tag = "donations",
+    params(("id" = String, Path, description = "Donation id")),
+    responses(
+        (status = 200, description = "Deleted", body = String),
+        (status = 404, description = "Not found", body = String),
+    )
+)]
 pub async fn delete_donation(
     Path(id): Path<String>,
     State(state): State<AppState>,
     user: AuthenticatedUser,
 ) -> impl IntoResponse {
     match crate::db::donations::soft_delete_donation(&state.db, &user.id, &id).await {
-        Ok(true) => (StatusCode::OK, "Deleted").into_response(),
+        Ok(true) => {
+            state.events.publish(&user.id, "donations", &id, "delete");
+            (StatusCode::OK, "Deleted").into_response()
+        }
         Ok(false) => (StatusCode::NOT_FOUND, "Not found").into_response(),
         Err(e) => {
             tracing::error!("Delete donation error: {}", e);
@@ -392,6 +444,18 @@ pub async fn delete_donation(
     }
 }
 
+#[utoipa::path(
+    put,
+    path = "/api/donations/{id}",
+    tag = "donations",
+    params(("id" = String, Path, description = "Donation id")),
+    request_body = UpdateDonationRequest,
+    responses(
+        (status = 200, description = "Updated: `{status, id}`", body = serde_json::Value),
+        (status = 400, description = "Validation failed", body = String),
+        (status = 409, description = "Stale `updated_at` or not found", body = String),
+    )
+)]
 pub async fn update_donation(
     Path(id): Path<String>,
     State(state): State<AppState>,
@@ -442,15 +506,19 @@ pub async fn update_donation(
         notes: req.notes.clone(),
         is_encrypted: req.is_encrypted,
         encrypted_payload: req.encrypted_payload.clone(),
+        blind_index: req.blind_index.clone(),
         incoming_updated_at,
     };
 
     match crate::db::donations::update_donation(&state.db, &patch).await {
-        Ok(true) => (
-            StatusCode::OK,
-            AxumJson(serde_json::json!({"status":"updated","id": id})),
-        )
-            .into_response(),
+        Ok(true) => {
+            state.events.publish(&user_id, "donations", &id, "upsert");
+            (
+                StatusCode::OK,
+                AxumJson(serde_json::json!({"status":"updated","id": id})),
+            )
+                .into_response()
+        }
         Ok(false) => (StatusCode::CONFLICT, "Not updated (stale or not found)").into_response(),
         Err(e) => {
             tracing::error!("Update donation error: {}", e);
@@ -459,6 +527,15 @@ pub async fn update_donation(
     }
 }
 
+#[utoipa::path(
+    get,
+    path = "/api/donations",
+    tag = "donations",
+    params(ListParams),
+    responses(
+        (status = 200, description = "`{donations: [Donation]}`", body = serde_json::Value),
+    )
+)]
 pub async fn list_donations(
     State(state): State<AppState>,
     user: AuthenticatedUser,
@@ -512,6 +589,7 @@ mod tests {
             id: Some("test".to_string()),
             is_encrypted: Some(true),
             encrypted_payload: Some("payload".to_string()),
+            blind_index: None,
         };
         assert!(validate_create_donation_request(&req).is_ok());
     }
@@ -529,6 +607,7 @@ mod tests {
             id: Some("test".to_string()),
             is_encrypted: Some(false),
             encrypted_payload: None,
+            blind_index: None,
         };
         assert!(validate_create_donation_request(&req).is_err());
     }
@@ -0,0 +1,76 @@
+use crate::auth::AuthenticatedUser;
+use crate::events::EventBus;
+use crate::AppState;
+use axum::{
+    extract::State,
+    response::{
+        sse::{Event, KeepAlive, Sse},
+        IntoResponse,
+    },
+};
+use futures::stream::{self, Stream, StreamExt};
+use std::convert::Infallible;
+use tokio::sync::broadcast::error::RecvError;
+
+fn user_event_stream(
+    bus: &EventBus,
+    user_id: String,
+) -> impl Stream<Item = Result<Event, Infallible>> {
+    stream::unfold(
+        (bus.subscribe(), user_id),
+        |(mut receiver, user_id)| async move {
+            loop {
+                let event = match receiver.recv().await {
+                    Ok(change) if change.user_id == user_id => Event::default()
+                        .event("change")
+                        .json_data(&change)
+                        .unwrap_or_else(|_| Event::default().event("resync").data("{}")),
+                    Ok(_) => continue,
+                    // Missed notifications cannot be replayed; the client re-reads the change feed instead.
+                    Err(RecvError::Lagged(skipped)) => {
+                        tracing::warn!("Event stream lagged by {} messages", skipped);
+                        Event::default().event("resync").data("{}")
+                    }
+                    Err(RecvError::Closed) => return None,
+                };
+                return Some((Ok(event), (receiver, user_id)));
+            }
+        },
+    )
+}
+
+/// Streams the user's change notifications as Server-Sent Events.
+#[utoipa::path(
+    get,
+    path = "/api/events",
+    tag = "sync",
+    responses((status = 200, description = "`ready`, `change` and `resync` events", content_type = "text/event-stream", body = String))
+)]
+pub async fn stream_events(
+    State(state): State<AppState>,
+    user: AuthenticatedUser,
+) -> impl IntoResponse {
+    let ready = stream::once(async { Ok(Event::default().event("ready").data("{}")) });
+    Sse::new(ready.chain(user_event_stream(&state.events, user.id)))
+        .keep_alive(KeepAlive::default())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_user_event_stream_only_yields_own_changes() {
+        let bus = EventBus::new();
+        let mut events = Box::pin(user_event_stream(&bus, "user-1".to_string()));
+
+        bus.publish("user-2", "donations", "don-other", "upsert");
+        bus.publish("user-1", "receipts", "rec-1", "ocr");
+
+        let event = events.next().await.expect("event").expect("infallible");
+        let rendered = format!("{:?}", event);
+        assert!(rendered.contains("rec-1"), "unexpected event: {rendered}");
+        assert!(!rendered.contains("don-other"));
+        assert!(!rendered.contains("user-1"), "user id must not leak into the payload");
+    }
+}
@@ -1,7 +1,14 @@
+pub mod accountants;
+pub mod blind_index;
 pub mod charities;
 pub mod donations;
+pub mod events;
+pub mod openapi;
+pub mod receipt_uploads;
 pub mod receipts;
 pub mod reports;
+pub mod storage;
 pub mod sync;
 pub mod tax;
 pub mod valuations;
+pub mod vault;
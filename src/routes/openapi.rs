@@ -0,0 +1,205 @@
+use axum::response::{IntoResponse, Json as AxumJson};
+use utoipa::openapi::security::{ApiKey, ApiKeyValue, HttpAuthScheme, HttpBuilder, SecurityScheme};
+use utoipa::{Modify, OpenApi};
+
+#[derive(OpenApi)]
+#[openapi(
+    info(
+        title = "Deductible Tracker API",
+        description = "Mutating requests authenticated by cookie must send `X-CSRF-Token` matching the `csrf_token` cookie. \
+                       POST, PUT, PATCH and DELETE accept an `Idempotency-Key` header; retries with the same key replay the first response."
+    ),
+    paths(
+        crate::routes::donations::list_donations,
+        crate::routes::donations::create_donation,
+        crate::routes::donations::update_donation,
+        crate::routes::donations::delete_donation,
+        crate::routes::donations::import_donations,
+        crate::routes::blind_index::search_donations,
+        crate::routes::blind_index::list_year_tokens,
+        crate::routes::blind_index::list_duplicates,
+        crate::routes::charities::handlers::list_charities,
+        crate::routes::charities::handlers::create_charity,
+        crate::routes::charities::handlers::update_charity,
+        crate::routes::charities::handlers::delete_charity,
+        crate::routes::charities::handlers::search_charities,
+        crate::routes::charities::handlers::lookup_charity_by_ein,
+        crate::routes::receipts::generate_upload_url,
+        crate::routes::receipts::generate_upload_form,
+        crate::routes::receipts::generate_read_url,
+        crate::routes::receipts::confirm_receipt,
+        crate::routes::receipt_uploads::start_multipart_upload,
+        crate::routes::receipt_uploads::sign_upload_parts,
+        crate::routes::receipt_uploads::complete_multipart_upload,
+        crate::routes::receipt_uploads::abort_multipart_upload,
+        crate::routes::receipts::ocr_receipt,
+        crate::routes::receipts::list_receipts,
+        crate::routes::receipts::update_receipt,
+        crate::routes::receipts::delete_receipt,
+        crate::routes::valuations::suggest,
+        crate::routes::valuations::seed,
+        crate::routes::valuations::tree,
+        crate::routes::reports::list_available_years,
+        crate::routes::reports::export_csv,
+        crate::routes::reports::export_tax_txf,
+        crate::routes::reports::export_pdf,
+        crate::routes::reports::export_audit_csv,
+        crate::routes::tax::marginal_rate,
+        crate::routes::accountants::list_grants,
+        crate::routes::accountants::create_grant,
+        crate::routes::accountants::revoke_grant,
+        crate::routes::accountants::list_clients,
+        crate::routes::accountants::client_profile,
+        crate::routes::accountants::client_donations,
+        crate::routes::accountants::client_charities,
+        crate::routes::accountants::client_receipts,
+        crate::routes::accountants::client_receipt_url,
+        crate::routes::accountants::client_export_csv,
+        crate::routes::accountants::client_export_txf,
+        crate::routes::sync::batch_sync,
+        crate::routes::sync::list_changes,
+        crate::routes::events::stream_events,
+        crate::auth::me,
+        crate::auth::update_me,
+        crate::auth::delete_me,
+        crate::auth::export_me,
+        crate::auth::import_me,
+        crate::auth::logout_everywhere,
+        crate::auth::list_sessions,
+        crate::auth::revoke_all_sessions,
+        crate::auth::revoke_session,
+        crate::auth::list_access_tokens,
+        crate::auth::create_access_token,
+        crate::auth::revoke_access_token,
+        crate::auth::passkey_registration_options,
+        crate::auth::register_passkey,
+        crate::auth::list_passkeys,
+        crate::auth::delete_passkey,
+        crate::auth::vault_unlock_options,
+        crate::auth::vault_unlock,
+        crate::routes::vault::rotation_status,
+        crate::routes::vault::rotate_vault,
+        crate::auth::get_config,
+        crate::auth::risc_webhook,
+        openapi_json,
+    ),
+    components(schemas(
+        crate::db::models::Donation,
+        crate::db::models::BlindIndexTokens,
+        crate::db::models::BlindIndexDuplicateGroup,
+        crate::db::models::Receipt,
+        crate::db::models::SyncChange,
+        crate::db::models::AccessToken,
+        crate::db::models::PasskeyCredential,
+        crate::auth::SessionResponse,
+        crate::db::models::AccountantGrant,
+        crate::db::models::ClientGrant,
+        crate::db::models::VaultKeyRotation,
+        crate::routes::charities::handlers::CharityResponse,
+    )),
+    modifiers(&SecuritySchemes),
+    security(("bearer" = []), ("cookie" = []))
+)]
+pub struct ApiDoc;
+
+struct SecuritySchemes;
+
+impl Modify for SecuritySchemes {
+    fn modify(&self, openapi: &mut utoipa::openapi::OpenApi) {
+        // The crate has no license field; drop the empty one utoipa fills in from Cargo metadata.
+        openapi.info.license = None;
+        let components = openapi.components.get_or_insert_with(Default::default);
+        components.add_security_scheme(
+            "bearer",
+            SecurityScheme::Http(
+                HttpBuilder::new()
+                    .scheme(HttpAuthScheme::Bearer)
+                    .description(Some("Session JWT, or a personal access token (`dtp_...`) from `/api/me/tokens`"))
+                    .build(),
+            ),
+        );
+        components.add_security_scheme(
+            "cookie",
+            SecurityScheme::ApiKey(ApiKey::Cookie(ApiKeyValue::new("auth_token"))),
+        );
+    }
+}
+
+/// Serves the OpenAPI 3.1 description of the `/api` surface.
+#[utoipa::path(
+    get,
+    path = "/api/openapi.json",
+    tag = "meta",
+    security(()),
+    responses((status = 200, description = "This document", body = serde_json::Value))
+)]
+pub async fn openapi_json() -> impl IntoResponse {
+    AxumJson(ApiDoc::openapi())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const ROUTER_SOURCE: &str = include_str!("../main_sections/bootstrap/server_bootstrap.rs");
+
+    /// (path, method) for every `.route("/api/...", ...)` registered in the router.
+    fn registered_api_routes() -> Vec<(String, String)> {
+        let mut routes = Vec::new();
+        for line in ROUTER_SOURCE.lines() {
+            let Some(rest) = line.trim().strip_prefix(".route(\"") else {
+                continue;
+            };
+            let Some((path, handlers)) = rest.split_once('"') else {
+                continue;
+            };
+            if !path.starts_with("/api/") {
+                continue;
+            }
+            for method in ["get", "post", "put", "delete", "patch"] {
+                let call = format!("{}(", method);
+                let registered = handlers.match_indices(&call).any(|(idx, _)| {
+                    idx == 0 || !handlers[..idx].ends_with(|c: char| c.is_alphanumeric() || c == '_')
+                });
+                if registered {
+                    routes.push((path.to_string(), method.to_string()));
+                }
+            }
+        }
+        routes
+    }
+
+    #[test]
+    fn test_every_api_route_is_documented() {
+        let doc = ApiDoc::openapi();
+        let routes = registered_api_routes();
+        assert!(routes.len() > 30, "router parsing found too few routes: {routes:?}");
+
+        let undocumented: Vec<_> = routes
+            .iter()
+            .filter(|(path, method)| {
+                let Some(item) = doc.paths.paths.get(path) else {
+                    return true;
+                };
+                let operation = match method.as_str() {
+                    "get" => &item.get,
+                    "post" => &item.post,
+                    "put" => &item.put,
+                    "delete" => &item.delete,
+                    _ => &item.patch,
+                };
+                operation.is_none()
+            })
+            .collect();
+        assert!(undocumented.is_empty(), "routes missing from ApiDoc: {undocumented:?}");
+    }
+
+    #[test]
+    fn test_document_is_openapi_3_1_with_request_schemas() {
+        let doc = serde_json::to_value(ApiDoc::openapi()).expect("serialize openapi");
+        assert!(doc["openapi"].as_str().is_some_and(|v| v.starts_with("3.1")));
+        for schema in ["CreateDonationRequest", "UpdateDonationRequest", "OcrResponse", "MarginalRateResponse"] {
+            assert!(doc["components"]["schemas"][schema].is_object(), "missing schema {schema}");
+        }
+    }
+}
@@ -0,0 +1,307 @@
+use crate::auth::AuthenticatedUser;
+use crate::db;
+use crate::db::models::ReceiptUpload;
+use crate::routes::receipts::{allowed_ext_for_content_type, new_receipt_key, PRESIGN_EXPIRATION_SECS};
+use crate::storage::{UploadedPart, MAX_UPLOAD_PARTS};
+use crate::AppState;
+use axum::{
+    extract::{Json, Path, State},
+    http::StatusCode,
+    response::{IntoResponse, Json as AxumJson},
+};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+/// Smallest part size handed out; S3 needs 5 MiB for every part but the last.
+const MIN_PART_SIZE_BYTES: u64 = 8 * 1024 * 1024;
+/// Most part URLs signed in one request.
+const MAX_PART_URLS_PER_REQUEST: usize = 100;
+
+/// Part size and count for an upload of `size` bytes, growing the parts when
+/// the minimum size would need more than S3's part limit.
+fn plan_parts(size: u64) -> (u64, u64) {
+    let part_size = MIN_PART_SIZE_BYTES.max(size.div_ceil(u64::from(MAX_UPLOAD_PARTS)));
+    (part_size, size.div_ceil(part_size))
+}
+
+#[derive(Deserialize, utoipa::ToSchema)]
+pub struct StartMultipartRequest {
+    /// Content type of the bytes being uploaded, e.g. `application/pdf`.
+    file_type: String,
+    size: i64,
+}
+
+#[derive(Serialize, utoipa::ToSchema)]
+pub struct MultipartUploadResponse {
+    id: String,
+    key: String,
+    part_size: i64,
+    part_count: i64,
+}
+
+#[utoipa::path(
+    post,
+    path = "/api/receipts/multipart",
+    tag = "receipts",
+    request_body = StartMultipartRequest,
+    responses(
+        (status = 201, description = "Upload session started", body = MultipartUploadResponse),
+        (status = 400, description = "Unsupported content type, or size above `RECEIPT_MAX_UPLOAD_MB`", body = String),
+    )
+)]
+pub async fn start_multipart_upload(
+    State(state): State<AppState>,
+    user: AuthenticatedUser,
+    Json(req): Json<StartMultipartRequest>,
+) -> impl IntoResponse {
+    let Some(ext) = allowed_ext_for_content_type(&req.file_type) else {
+        return (StatusCode::BAD_REQUEST, "Unsupported file type").into_response();
+    };
+    if req.size <= 0 || req.size > state.max_receipt_bytes {
+        return (StatusCode::BAD_REQUEST, "Invalid or oversized receipt").into_response();
+    }
+
+    let key = new_receipt_key(&user.id, ext);
+    let upload_id = match state.storage.create_multipart_upload(&key, Some(&req.file_type)).await {
+        Ok(upload_id) => upload_id,
+        Err(e) => {
+            tracing::error!("Storage multipart start for {} failed: {}", key, e);
+            return (StatusCode::INTERNAL_SERVER_ERROR, "Storage Error").into_response();
+        }
+    };
+    let (part_size, part_count) = plan_parts(req.size as u64);
+    let upload = ReceiptUpload {
+        id: Uuid::new_v4().to_string(),
+        user_id: user.id.clone(),
+        key,
+        upload_id,
+        content_type: Some(req.file_type),
+        size: req.size,
+        part_size: part_size as i64,
+        part_count: part_count as i64,
+        created_at: chrono::Utc::now(),
+    };
+    if let Err(e) = db::receipt_uploads::create_upload(&state.db, &upload).await {
+        tracing::error!("DB Error recording upload session: {}", e);
+        abort_storage_upload(&state, &upload).await;
+        return (StatusCode::INTERNAL_SERVER_ERROR, "Database Error").into_response();
+    }
+
+    (
+        StatusCode::CREATED,
+        AxumJson(MultipartUploadResponse {
+            id: upload.id,
+            key: upload.key,
+            part_size: upload.part_size,
+            part_count: upload.part_count,
+        }),
+    )
+        .into_response()
+}
+
+async fn find_upload(state: &AppState, user_id: &str, id: &str) -> Result<ReceiptUpload, (StatusCode, &'static str)> {
+    match db::receipt_uploads::get_upload(&state.db, user_id, id).await {
+        Ok(Some(upload)) => Ok(upload),
+        Ok(None) => Err((StatusCode::NOT_FOUND, "Upload not found")),
+        Err(e) => {
+            tracing::error!("DB Error loading upload session: {}", e);
+            Err((StatusCode::INTERNAL_SERVER_ERROR, "Database Error"))
+        }
+    }
+}
+
+#[derive(Deserialize, utoipa::ToSchema)]
+pub struct PartUrlsRequest {
+    part_numbers: Vec<u32>,
+}
+
+#[derive(Serialize, utoipa::ToSchema)]
+pub struct PartUrl {
+    part_number: u32,
+    url: String,
+}
+
+#[derive(Serialize, utoipa::ToSchema)]
+pub struct PartUrlsResponse {
+    parts: Vec<PartUrl>,
+    expires_in: u64,
+}
+
+#[utoipa::path(
+    post,
+    path = "/api/receipts/multipart/{id}/parts",
+    tag = "receipts",
+    params(("id" = String, Path, description = "Upload session id")),
+    request_body = PartUrlsRequest,
+    responses(
+        (status = 200, description = "Presigned PUT URLs; each response's `ETag` is needed to complete", body = PartUrlsResponse),
+        (status = 400, description = "No part numbers, too many, or outside the upload's parts", body = String),
+        (status = 404, description = "Upload not found", body = String),
+    )
+)]
+pub async fn sign_upload_parts(
+    Path(id): Path<String>,
+    State(state): State<AppState>,
+    user: AuthenticatedUser,
+    Json(req): Json<PartUrlsRequest>,
+) -> impl IntoResponse {
+    let upload = match find_upload(&state, &user.id, &id).await {
+        Ok(upload) => upload,
+        Err((status, message)) => return (status, message).into_response(),
+    };
+    if req.part_numbers.is_empty() || req.part_numbers.len() > MAX_PART_URLS_PER_REQUEST {
+        return (StatusCode::BAD_REQUEST, "Request between 1 and 100 parts").into_response();
+    }
+    if req.part_numbers.iter().any(|&n| n == 0 || i64::from(n) > upload.part_count) {
+        return (StatusCode::BAD_REQUEST, "Part number out of range").into_response();
+    }
+
+    let mut parts = Vec::with_capacity(req.part_numbers.len());
+    for part_number in req.part_numbers {
+        match state.storage.presign_part(&upload.key, &upload.upload_id, part_number, PRESIGN_EXPIRATION_SECS) {
+            Ok(url) => parts.push(PartUrl { part_number, url }),
+            Err(e) => {
+                tracing::error!("Storage Presign Part Error: {}", e);
+                return (StatusCode::INTERNAL_SERVER_ERROR, "Storage Error").into_response();
+            }
+        }
+    }
+    AxumJson(PartUrlsResponse {
+        parts,
+        expires_in: PRESIGN_EXPIRATION_SECS,
+    })
+    .into_response()
+}
+
+#[derive(Deserialize, utoipa::ToSchema)]
+pub struct CompletedPart {
+    part_number: u32,
+    etag: String,
+}
+
+#[derive(Deserialize, utoipa::ToSchema)]
+pub struct CompleteMultipartRequest {
+    parts: Vec<CompletedPart>,
+}
+
+#[derive(Serialize, utoipa::ToSchema)]
+pub struct CompletedUploadResponse {
+    /// Key to pass to `/api/receipts/confirm`.
+    key: String,
+}
+
+#[utoipa::path(
+    post,
+    path = "/api/receipts/multipart/{id}/complete",
+    tag = "receipts",
+    params(("id" = String, Path, description = "Upload session id")),
+    request_body = CompleteMultipartRequest,
+    responses(
+        (status = 200, description = "Parts joined; confirm the receipt with the returned key", body = CompletedUploadResponse),
+        (status = 400, description = "Parts missing or not matching their uploads; the session stays open", body = String),
+        (status = 404, description = "Upload not found", body = String),
+    )
+)]
+pub async fn complete_multipart_upload(
+    Path(id): Path<String>,
+    State(state): State<AppState>,
+    user: AuthenticatedUser,
+    Json(req): Json<CompleteMultipartRequest>,
+) -> impl IntoResponse {
+    let upload = match find_upload(&state, &user.id, &id).await {
+        Ok(upload) => upload,
+        Err((status, message)) => return (status, message).into_response(),
+    };
+    let mut parts: Vec<UploadedPart> = req
+        .parts
+        .into_iter()
+        .map(|part| UploadedPart {
+            part_number: part.part_number,
+            etag: part.etag,
+        })
+        .collect();
+    parts.sort_by_key(|part| part.part_number);
+    let numbered_in_order = parts
+        .iter()
+        .enumerate()
+        .all(|(index, part)| part.part_number as usize == index + 1);
+    if !numbered_in_order || parts.len() as i64 != upload.part_count {
+        return (StatusCode::BAD_REQUEST, "Every part must be listed once").into_response();
+    }
+
+    if let Err(e) = state
+        .storage
+        .complete_multipart_upload(&upload.key, &upload.upload_id, &parts)
+        .await
+    {
+        tracing::warn!("Completing upload {} failed: {}", upload.id, e);
+        return (StatusCode::BAD_REQUEST, "Upload could not be completed").into_response();
+    }
+    if let Err(e) = db::receipt_uploads::delete_upload(&state.db, &upload.id).await {
+        tracing::error!("DB Error closing upload session {}: {}", upload.id, e);
+    }
+    AxumJson(CompletedUploadResponse { key: upload.key }).into_response()
+}
+
+#[utoipa::path(
+    delete,
+    path = "/api/receipts/multipart/{id}",
+    tag = "receipts",
+    params(("id" = String, Path, description = "Upload session id")),
+    responses(
+        (status = 204, description = "Upload aborted and its parts discarded"),
+        (status = 404, description = "Upload not found", body = String),
+    )
+)]
+pub async fn abort_multipart_upload(
+    Path(id): Path<String>,
+    State(state): State<AppState>,
+    user: AuthenticatedUser,
+) -> impl IntoResponse {
+    let upload = match find_upload(&state, &user.id, &id).await {
+        Ok(upload) => upload,
+        Err((status, message)) => return (status, message).into_response(),
+    };
+    match discard_upload(&state, &upload).await {
+        Ok(()) => StatusCode::NO_CONTENT.into_response(),
+        Err(e) => {
+            tracing::error!("Aborting upload {} failed: {}", upload.id, e);
+            (StatusCode::INTERNAL_SERVER_ERROR, "Storage Error").into_response()
+        }
+    }
+}
+
+async fn abort_storage_upload(state: &AppState, upload: &ReceiptUpload) {
+    if let Err(e) = state.storage.abort_multipart_upload(&upload.key, &upload.upload_id).await {
+        tracing::error!("Failed to abort storage upload for {}: {}", upload.key, e);
+    }
+}
+
+/// Aborts the upload in storage, then forgets the session. The session is kept
+/// when storage fails, so a later attempt can still find the parts.
+pub(crate) async fn discard_upload(state: &AppState, upload: &ReceiptUpload) -> anyhow::Result<()> {
+    state
+        .storage
+        .abort_multipart_upload(&upload.key, &upload.upload_id)
+        .await?;
+    db::receipt_uploads::delete_upload(&state.db, &upload.id).await?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parts_grow_past_the_part_limit() {
+        const MIB: u64 = 1024 * 1024;
+        assert_eq!(plan_parts(1), (8 * MIB, 1));
+        assert_eq!(plan_parts(8 * MIB), (8 * MIB, 1));
+        assert_eq!(plan_parts(8 * MIB + 1), (8 * MIB, 2));
+        assert_eq!(plan_parts(200 * MIB), (8 * MIB, 25));
+
+        let (part_size, part_count) = plan_parts(100_000 * MIB);
+        assert!(part_count <= u64::from(MAX_UPLOAD_PARTS));
+        assert!(part_size * part_count >= 100_000 * MIB);
+    }
+}
@@ -1,25 +1,25 @@
 use crate::auth::AuthenticatedUser;
 use crate::AppState;
 use axum::{
-    extract::{Json, Query, State},
+    extract::{Json, Path, Query, State},
     http::{StatusCode, HeaderMap},
     response::{IntoResponse, Json as AxumJson},
 };
-use base64::Engine;
 use chrono::Datelike;
 use serde::Deserialize;
 use serde_json::json;
 use uuid::Uuid;
 
 use crate::db;
-use crate::db::models::NewReceipt;
+use crate::db::models::{NewReceipt, ReceiptPatch};
 use crate::ocr;
 use serde::Serialize;
 
-const MAX_RECEIPT_SIZE_BYTES: i64 = 10 * 1024 * 1024;
-const PRESIGN_EXPIRATION_SECS: u64 = 300;
+pub(crate) const PRESIGN_EXPIRATION_SECS: u64 = 300;
+/// Leading bytes read to check an upload's format; enough for a vault object header.
+const SNIFF_LEN: u64 = 32;
 
-fn allowed_ext_for_content_type(content_type: &str) -> Option<&'static str> {
+pub(crate) fn allowed_ext_for_content_type(content_type: &str) -> Option<&'static str> {
     match content_type {
         // Images
         "image/jpeg" => Some("jpg"),
@@ -31,6 +31,9 @@ fn allowed_ext_for_content_type(content_type: &str) -> Option<&'static str> {
         "image/heif" => Some("heif"),
         "image/bmp" => Some("bmp"),
         "image/webp" => Some("webp"),
+        // Videos of donated items
+        "video/mp4" => Some("mp4"),
+        "video/quicktime" => Some("mov"),
         // Documents
         "application/pdf" => Some("pdf"),
         "application/vnd.openxmlformats-officedocument.wordprocessingml.document" => Some("docx"),
@@ -55,12 +58,27 @@ fn allowed_ext_for_content_type(content_type: &str) -> Option<&'static str> {
     }
 }
 
-#[derive(Deserialize)]
+/// A fresh `receipts/<user>/<year>/<uuid>.<ext>` key.
+pub(crate) fn new_receipt_key(user_id: &str, ext: &str) -> String {
+    format!("receipts/{}/{}/{}.{}", user_id, chrono::Utc::now().year(), Uuid::new_v4(), ext)
+}
+
+#[derive(Deserialize, utoipa::ToSchema)]
 pub struct UploadRequest {
     file_type: String, // e.g., "image/jpeg"
     _donation_id: Option<String>,
 }
 
+#[utoipa::path(
+    post,
+    path = "/api/receipts/upload",
+    tag = "receipts",
+    request_body = UploadRequest,
+    responses(
+        (status = 200, description = "Presigned PUT: `{upload_url, key, expires_in}`", body = serde_json::Value),
+        (status = 400, description = "Unsupported content type", body = String),
+    )
+)]
 pub async fn generate_upload_url(
     State(state): State<AppState>,
     user: AuthenticatedUser,
@@ -73,12 +91,9 @@ pub async fn generate_upload_url(
         None => return (StatusCode::BAD_REQUEST, "Unsupported file type").into_response(),
     };
 
-    let now = chrono::Utc::now();
-    let year = now.year();
-    let file_id = Uuid::new_v4();
-    let key = format!("receipts/{}/{}/{}.{}", user_id, year, file_id, ext);
+    let key = new_receipt_key(&user_id, ext);
 
-    match crate::storage::presign_url(&state, "PUT", &key, PRESIGN_EXPIRATION_SECS) {
+    match state.storage.presign("PUT", &key, PRESIGN_EXPIRATION_SECS) {
         Ok(upload_url) => {
             let resp_data = json!({
                 "upload_url": upload_url,
@@ -94,28 +109,114 @@ pub async fn generate_upload_url(
     }
 }
 
-#[derive(Deserialize)]
+#[derive(Deserialize, utoipa::ToSchema)]
+pub struct UploadFormRequest {
+    file_type: String,
+    /// Exact size of the file; when given, the policy accepts no other size.
+    size: Option<i64>,
+}
+
+#[derive(Serialize, utoipa::ToSchema)]
+pub struct UploadFormResponse {
+    /// POST target for a multipart/form-data upload.
+    upload_url: String,
+    /// Form fields to send as they are, before the file in a `file` field.
+    fields: std::collections::BTreeMap<String, String>,
+    key: String,
+    expires_in: u64,
+}
+
+#[utoipa::path(
+    post,
+    path = "/api/receipts/upload-form",
+    tag = "receipts",
+    request_body = UploadFormRequest,
+    responses(
+        (status = 200, description = "Presigned POST whose policy fixes the key prefix, content type and size range", body = UploadFormResponse),
+        (status = 400, description = "Unsupported content type, or size above `RECEIPT_MAX_UPLOAD_MB`", body = String),
+    )
+)]
+pub async fn generate_upload_form(
+    State(state): State<AppState>,
+    user: AuthenticatedUser,
+    Json(req): Json<UploadFormRequest>,
+) -> impl IntoResponse {
+    let Some(ext) = allowed_ext_for_content_type(&req.file_type) else {
+        return (StatusCode::BAD_REQUEST, "Unsupported file type").into_response();
+    };
+    let (min_bytes, max_bytes) = match req.size {
+        Some(size) if size <= 0 || size > state.max_receipt_bytes => {
+            return (StatusCode::BAD_REQUEST, "Invalid or oversized receipt").into_response();
+        }
+        Some(size) => (size as u64, size as u64),
+        None => (1, state.max_receipt_bytes as u64),
+    };
+
+    let key = new_receipt_key(&user.id, ext);
+    let policy = crate::storage::PostPolicy {
+        key: &key,
+        key_prefix: &crate::storage::user_receipt_prefix(&user.id),
+        content_type: &req.file_type,
+        min_bytes,
+        max_bytes,
+        expires_in_secs: PRESIGN_EXPIRATION_SECS,
+    };
+    match state.storage.presign_post(&policy) {
+        Ok(form) => AxumJson(UploadFormResponse {
+            upload_url: form.url,
+            fields: form.fields.into_iter().collect(),
+            key,
+            expires_in: PRESIGN_EXPIRATION_SECS,
+        })
+        .into_response(),
+        Err(e) => {
+            tracing::error!("Storage Presign Form Error: {}", e);
+            (StatusCode::INTERNAL_SERVER_ERROR, "Storage Error").into_response()
+        }
+    }
+}
+
+#[derive(Deserialize, utoipa::ToSchema)]
 pub struct PresignReadRequest {
     key: String,
 }
 
+#[utoipa::path(
+    post,
+    path = "/api/receipts/presign",
+    tag = "receipts",
+    request_body = PresignReadRequest,
+    responses(
+        (status = 200, description = "Presigned GET: `{download_url, key, expires_in, content_hash}`; `content_hash` is the receipt's recorded hex SHA-256, or null when none was recorded. The download comes straight from storage without passing the server, so checking it against the hash is up to the client", body = serde_json::Value),
+        (status = 403, description = "Key outside the user's prefix", body = String),
+    )
+)]
 pub async fn generate_read_url(
     State(state): State<AppState>,
     user: AuthenticatedUser,
     Json(req): Json<PresignReadRequest>,
 ) -> impl IntoResponse {
-    let key = crate::storage::normalize_object_key(&state.bucket_name, &req.key);
+    let key = state.storage.normalize_key(&req.key);
     let user_prefix = crate::storage::user_receipt_prefix(&user.id);
     if !key.starts_with(&user_prefix) {
         return (StatusCode::FORBIDDEN, "Forbidden").into_response();
     }
 
-    match crate::storage::presign_url(&state, "GET", &key, 300) {
+    let content_hash = match db::receipts::content_hash_for_key(&state.db, &user.id, &key).await {
+        Ok(content_hash) => content_hash,
+        Err(e) => {
+            tracing::error!("DB Error loading receipt hash: {}", e);
+            return (StatusCode::INTERNAL_SERVER_ERROR, "Database Error").into_response();
+        }
+    };
+
+    match state.storage.presign("GET", &key, 300) {
         Ok(download_url) => {
             let resp_data = json!({
                 "download_url": download_url,
                 "key": key,
-                "expires_in": 300
+                "expires_in": 300,
+                "content_hash": content_hash
             });
             (StatusCode::OK, AxumJson(resp_data)).into_response()
         }
@@ -126,7 +227,7 @@ pub async fn generate_read_url(
     }
 }
 
-#[derive(Deserialize)]
+#[derive(Deserialize, utoipa::ToSchema)]
 pub struct ConfirmReceiptRequest {
     pub key: String,
     pub file_name: Option<String>,
@@ -137,28 +238,49 @@ pub struct ConfirmReceiptRequest {
     pub encrypted_payload: Option<String>,
 }
 
-#[derive(Serialize)]
+#[derive(Serialize, utoipa::ToSchema)]
 struct CreatedResponse {
     id: String,
+    /// Storage key the receipt points at; the shared object's key for a duplicate.
+    key: String,
+    /// Receipt already holding the same file; the new receipt shares its stored
+    /// object and the duplicate upload is deleted.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    duplicate_of: Option<String>,
 }
 
+#[utoipa::path(
+    post,
+    path = "/api/receipts/confirm",
+    tag = "receipts",
+    request_body = ConfirmReceiptRequest,
+    responses(
+        (status = 201, description = "Receipt recorded", body = CreatedResponse),
+        (status = 400, description = "Invalid key, type or size, or the uploaded object does not match them (it is then deleted)", body = String),
+        (status = 403, description = "Donation not owned by the user", body = String),
+        (status = 409, description = "The same file is already attached to this donation; the upload is deleted", body = String),
+    )
+)]
 pub async fn confirm_receipt(
     State(state): State<AppState>,
     user: AuthenticatedUser,
     Json(mut req): Json<ConfirmReceiptRequest>,
 ) -> impl IntoResponse {
     req.donation_id = req.donation_id.trim().to_string();
-    req.key = crate::storage::normalize_object_key(&state.bucket_name, &req.key);
+    req.key = state.storage.normalize_key(&req.key);
 
     let user_prefix = crate::storage::user_receipt_prefix(&user.id);
     if !req.key.starts_with(&user_prefix) {
         return (StatusCode::FORBIDDEN, "Forbidden").into_response();
     }
 
+    let is_encrypted = req.is_encrypted.unwrap_or(false);
     if let Some(content_type) = req.content_type.as_deref() {
         let Some(expected_ext) = allowed_ext_for_content_type(content_type) else {
             return (StatusCode::BAD_REQUEST, "Unsupported content type").into_response();
         };
+        // An encrypted receipt's type describes the plaintext; the object itself is a `.bin`.
+        let expected_ext = if is_encrypted { "bin" } else { expected_ext };
 
         if let Some(actual_ext) = req.key.rsplit('.').next() {
             if actual_ext != expected_ext {
@@ -172,7 +294,7 @@ pub async fn confirm_receipt(
     }
 
     if let Some(size) = req.size {
-        if size <= 0 || size > MAX_RECEIPT_SIZE_BYTES {
+        if size <= 0 || size > state.max_receipt_bytes {
             return (StatusCode::BAD_REQUEST, "Invalid or oversized receipt").into_response();
         }
     }
@@ -186,34 +308,201 @@ pub async fn confirm_receipt(
         }
     }
 
+    let stored_size = match verify_uploaded_object(&state, &user.id, &req.key, req.size, is_encrypted).await {
+        Ok(size) => size,
+        Err((status, message)) => return (status, message).into_response(),
+    };
+
+    let content_hash = match state.storage.content_hash(&req.key).await {
+        Ok(Some(content_hash)) => content_hash,
+        Ok(None) => return (StatusCode::BAD_REQUEST, "Uploaded receipt not found").into_response(),
+        Err(e) => {
+            tracing::error!("Storage hash for {} failed: {}", req.key, e);
+            return (StatusCode::INTERNAL_SERVER_ERROR, "Storage Error").into_response();
+        }
+    };
+    let same_file = match db::receipts::find_receipts_by_content_hash(&state.db, &user.id, &content_hash).await {
+        Ok(receipts) => receipts,
+        Err(e) => {
+            tracing::error!("DB Error looking up receipt hash: {}", e);
+            return (StatusCode::INTERNAL_SERVER_ERROR, "Database Error").into_response();
+        }
+    };
+    if same_file.iter().any(|receipt| receipt.donation_id == req.donation_id) {
+        discard_duplicate_upload(&state, &req.key, &same_file).await;
+        return (StatusCode::CONFLICT, "This file is already attached to the donation").into_response();
+    }
+
     let id = Uuid::new_v4().to_string();
     let now = chrono::Utc::now();
 
-    let new_receipt = NewReceipt {
+    let mut new_receipt = NewReceipt {
         id: id.clone(),
         donation_id: req.donation_id.clone(),
         key: req.key.clone(),
         file_name: req.file_name.clone(),
         content_type: req.content_type.clone(),
-        size: req.size,
+        size: Some(stored_size),
         is_encrypted: req.is_encrypted,
         encrypted_payload: req.encrypted_payload.clone(),
+        content_hash: Some(content_hash),
         created_at: now,
     };
 
-    if let Err(e) = db::receipts::add_receipt(&state.db, &new_receipt).await {
-        tracing::error!("DB Error adding receipt: {}", e);
-        return (StatusCode::INTERNAL_SERVER_ERROR, "Database Error").into_response();
+    // The upload is only discarded once the receipt sharing the original object has
+    // committed. If the original was deleted meanwhile, the receipt keeps its own upload.
+    let mut duplicate_of = None;
+    if let Some(original) = same_file.first() {
+        let shared = NewReceipt { key: original.key.clone(), ..new_receipt.clone() };
+        match db::receipts::add_shared_receipt(&state.db, &user.id, &shared).await {
+            Ok(true) => {
+                discard_duplicate_upload(&state, &req.key, &same_file).await;
+                new_receipt = shared;
+                duplicate_of = Some(original.id.clone());
+            }
+            Ok(false) => {}
+            Err(e) => {
+                tracing::error!("DB Error adding receipt: {}", e);
+                return (StatusCode::INTERNAL_SERVER_ERROR, "Database Error").into_response();
+            }
+        }
+    }
+    if duplicate_of.is_none() {
+        if let Err(e) = db::receipts::add_receipt(&state.db, &new_receipt).await {
+            tracing::error!("DB Error adding receipt: {}", e);
+            return (StatusCode::INTERNAL_SERVER_ERROR, "Database Error").into_response();
+        }
+    }
+    state.events.publish(&user.id, "receipts", &id, "upsert");
+
+    let response = CreatedResponse {
+        id,
+        key: new_receipt.key,
+        duplicate_of,
+    };
+    (StatusCode::CREATED, AxumJson(response)).into_response()
+}
+
+/// Deletes an upload whose bytes another receipt already stores, unless it is
+/// that receipt's own object (a retried confirm).
+async fn discard_duplicate_upload(state: &AppState, key: &str, same_file: &[crate::db::models::Receipt]) {
+    if same_file.iter().any(|receipt| receipt.key == key) {
+        return;
+    }
+    if let Err(e) = state.storage.delete(key).await {
+        tracing::error!("Failed to delete duplicate receipt upload {}: {}", key, e);
+    }
+}
+
+/// Checks the uploaded object against what the key and the client claim: it
+/// must exist, fit the size limit, match the declared size, carry the content
+/// type its extension names and start with that format's magic bytes. A
+/// rejected object is deleted, so a presigned PUT can't leave it behind, unless
+/// a committed receipt points at it (a retried or replayed confirm).
+/// Returns the stored size.
+async fn verify_uploaded_object(
+    state: &AppState,
+    user_id: &str,
+    key: &str,
+    declared_size: Option<i64>,
+    is_encrypted: bool,
+) -> Result<i64, (StatusCode, &'static str)> {
+    let meta = match state.storage.head(key).await {
+        Ok(Some(meta)) => meta,
+        Ok(None) => return Err((StatusCode::BAD_REQUEST, "Uploaded receipt not found")),
+        Err(e) => {
+            tracing::error!("Storage head for {} failed: {}", key, e);
+            return Err((StatusCode::INTERNAL_SERVER_ERROR, "Storage Error"));
+        }
+    };
+    let ext = key.rsplit_once('.').map(|(_, ext)| ext).unwrap_or_default();
+
+    let rejection = match i64::try_from(meta.size) {
+        Ok(size) if size <= 0 || size > state.max_receipt_bytes => Some("Invalid or oversized receipt"),
+        Err(_) => Some("Invalid or oversized receipt"),
+        Ok(size) if declared_size.is_some_and(|declared| declared != size) => {
+            Some("Receipt size does not match the upload")
+        }
+        _ => None,
+    };
+    let rejection = rejection.or_else(|| {
+        let stored_type = meta.content_type.as_deref()?;
+        let stored_type = stored_type.split(';').next().unwrap_or_default().trim();
+        (allowed_ext_for_content_type(stored_type) != Some(ext))
+            .then_some("Uploaded content type does not match the receipt")
+    });
+    let rejection = match rejection {
+        Some(message) => Some(message),
+        None => match state.storage.get_range(key, 0..SNIFF_LEN).await {
+            Ok(Some(head)) if content_matches_extension(ext, &head, is_encrypted) => None,
+            Ok(Some(_)) => Some("Receipt content does not match its type"),
+            Ok(None) => return Err((StatusCode::BAD_REQUEST, "Uploaded receipt not found")),
+            Err(e) => {
+                tracing::error!("Storage read for {} failed: {}", key, e);
+                return Err((StatusCode::INTERNAL_SERVER_ERROR, "Storage Error"));
+            }
+        },
+    };
+
+    let Some(message) = rejection else {
+        return Ok(meta.size as i64);
+    };
+    tracing::warn!("Rejected uploaded receipt {}: {}", key, message);
+    match db::receipts::receipt_key_in_use(&state.db, user_id, key).await {
+        Ok(false) => {
+            if let Err(e) = state.storage.delete(key).await {
+                tracing::error!("Failed to delete rejected receipt {}: {}", key, e);
+            }
+        }
+        Ok(true) => {}
+        // The orphan sweep removes the object later if nothing uses it.
+        Err(e) => tracing::error!("DB Error checking whether {} is in use: {}", key, e),
     }
+    Err((StatusCode::BAD_REQUEST, message))
+}
 
-    (StatusCode::CREATED, AxumJson(CreatedResponse { id })).into_response()
+/// Whether an object's first bytes fit the format its extension names.
+/// Encrypted receipts must be vault objects; text formats only need to be free
+/// of NUL bytes; `bin` takes anything.
+fn content_matches_extension(ext: &str, head: &[u8], is_encrypted: bool) -> bool {
+    if is_encrypted {
+        return ext == "bin" && crate::vault_envelope::object_key_id(head).is_some();
+    }
+    let starts = |magic: &[u8]| head.starts_with(magic);
+    match ext {
+        "jpg" => starts(&[0xFF, 0xD8, 0xFF]),
+        "png" => starts(b"\x89PNG\r\n\x1a\n"),
+        "gif" => starts(b"GIF87a") || starts(b"GIF89a"),
+        "bmp" => starts(b"BM"),
+        "tiff" => starts(b"II*\0") || starts(b"MM\0*"),
+        "webp" => starts(b"RIFF") && head.get(8..12) == Some(b"WEBP".as_slice()),
+        "avif" | "heic" | "heif" | "mp4" => head.get(4..8) == Some(b"ftyp".as_slice()),
+        // Older QuickTime files open with a movie or padding atom instead.
+        "mov" => matches!(head.get(4..8), Some(b"ftyp" | b"moov" | b"mdat" | b"wide" | b"free")),
+        "pdf" => starts(b"%PDF-"),
+        "docx" | "pptx" | "xlsx" | "epub" | "odt" => starts(b"PK\x03\x04"),
+        "doc" | "ppt" => starts(&[0xD0, 0xCF, 0x11, 0xE0, 0xA1, 0xB1, 0x1A, 0xE1]),
+        "rtf" => starts(b"{\\rtf"),
+        "bin" => true,
+        _ => !head.contains(&0),
+    }
 }
 
-#[derive(Deserialize)]
+#[derive(Deserialize, utoipa::IntoParams)]
+#[into_params(parameter_in = Query)]
 pub struct ListReceiptsParams {
     pub donation_id: Option<String>,
 }
 
+#[utoipa::path(
+    get,
+    path = "/api/receipts",
+    tag = "receipts",
+    params(ListReceiptsParams),
+    responses(
+        (status = 200, description = "`{receipts: [Receipt]}`", body = serde_json::Value),
+    )
+)]
 pub async fn list_receipts(
     State(state): State<AppState>,
     user: AuthenticatedUser,
@@ -230,7 +519,135 @@ pub async fn list_receipts(
     }
 }
 
-#[derive(Deserialize)]
+#[derive(Deserialize, utoipa::ToSchema)]
+pub struct UpdateReceiptRequest {
+    pub file_name: Option<String>,
+    pub donation_id: Option<String>,
+    pub is_encrypted: Option<bool>,
+    pub encrypted_payload: Option<String>,
+}
+
+#[utoipa::path(
+    put,
+    path = "/api/receipts/{id}",
+    tag = "receipts",
+    params(("id" = String, Path, description = "Receipt id")),
+    request_body = UpdateReceiptRequest,
+    responses(
+        (status = 200, description = "Updated: `{status, id}`", body = serde_json::Value),
+        (status = 404, description = "Receipt or donation not found", body = String),
+    )
+)]
+pub async fn update_receipt(
+    Path(id): Path<String>,
+    State(state): State<AppState>,
+    user: AuthenticatedUser,
+    Json(req): Json<UpdateReceiptRequest>,
+) -> impl IntoResponse {
+    let file_name = req.file_name.map(|value| value.trim().to_string());
+    if file_name.as_deref().is_some_and(str::is_empty) {
+        return (StatusCode::BAD_REQUEST, "File name cannot be empty").into_response();
+    }
+    let donation_id = req
+        .donation_id
+        .as_deref()
+        .map(str::trim)
+        .filter(|value| !value.is_empty())
+        .map(ToString::to_string);
+
+    let patch = ReceiptPatch {
+        receipt_id: id.clone(),
+        user_id: user.id.clone(),
+        donation_id,
+        file_name,
+        is_encrypted: req.is_encrypted,
+        encrypted_payload: req.encrypted_payload,
+    };
+
+    match db::receipts::update_receipt(&state.db, &patch).await {
+        Ok(true) => {
+            state.events.publish(&user.id, "receipts", &id, "upsert");
+            (StatusCode::OK, AxumJson(json!({ "status": "updated", "id": id }))).into_response()
+        }
+        Ok(false) => (StatusCode::NOT_FOUND, "Receipt or donation not found").into_response(),
+        Err(e) => {
+            tracing::error!("DB Error updating receipt: {}", e);
+            (StatusCode::INTERNAL_SERVER_ERROR, "Database Error").into_response()
+        }
+    }
+}
+
+#[utoipa::path(
+    delete,
+    path = "/api/receipts/{id}",
+    tag = "receipts",
+    params(("id" = String, Path, description = "Receipt id")),
+    responses(
+        (status = 200, description = "Deleted", body = String),
+        (status = 404, description = "Not found", body = String),
+    )
+)]
+pub async fn delete_receipt(
+    Path(id): Path<String>,
+    State(state): State<AppState>,
+    user: AuthenticatedUser,
+) -> impl IntoResponse {
+    match db::receipts::delete_receipt(&state.db, &user.id, &id).await {
+        Ok(Some(key)) => {
+            remove_receipt_object(&state, &user.id, &id, &key).await;
+            state.events.publish(&user.id, "receipts", &id, "delete");
+            (StatusCode::OK, "Deleted").into_response()
+        }
+        Ok(None) => (StatusCode::NOT_FOUND, "Not found").into_response(),
+        Err(e) => {
+            tracing::error!("DB Error deleting receipt: {}", e);
+            (StatusCode::INTERNAL_SERVER_ERROR, "Database Error").into_response()
+        }
+    }
+}
+
+/// Deletes a receipt's storage object after its row is gone and records the deletion in the audit log.
+/// The object stays while another receipt shares it. Storage failures are logged rather than
+/// surfaced: the row is already removed.
+pub(crate) async fn remove_receipt_object(state: &AppState, user_id: &str, receipt_id: &str, key: &str) {
+    let storage_result = match db::receipts::receipt_key_in_use(&state.db, user_id, key).await {
+        Ok(true) => "kept for other receipts",
+        Ok(false) => match state.storage.delete(key).await {
+            Ok(()) => "deleted",
+            Err(e) => {
+                tracing::error!("Failed to delete file {} from storage: {}", key, e);
+                "storage delete failed"
+            }
+        },
+        Err(e) => {
+            // The orphan sweep removes the object later if nothing uses it.
+            tracing::error!("DB Error checking whether {} is shared: {}", key, e);
+            "kept, sharing unknown"
+        }
+    };
+
+    let audit_id = Uuid::new_v4().to_string();
+    let details = Some(format!("Deleted receipt {}: object={}", receipt_id, storage_result));
+    if let Err(e) = crate::db::audit::log_audit(
+        &state.db,
+        &audit_id,
+        user_id,
+        "delete",
+        "receipts",
+        &Some(receipt_id.to_string()),
+        &details,
+    )
+    .await
+    {
+        tracing::error!(
+            "Failed to write audit log for receipt delete (audit_id={}): {}",
+            audit_id,
+            e
+        );
+    }
+}
+
+#[derive(Deserialize, utoipa::ToSchema)]
 pub struct OcrRequest {
     pub id: Option<String>,
     pub key: Option<String>,
@@ -238,7 +655,7 @@ pub struct OcrRequest {
     pub size: Option<i64>,
 }
 
-#[derive(Serialize)]
+#[derive(Serialize, utoipa::ToSchema)]
 pub struct OcrResponse {
     pub status: String,
     pub id: Option<String>,
@@ -249,6 +666,18 @@ pub struct OcrResponse {
     pub warning: Option<String>,
 }
 
+#[utoipa::path(
+    post,
+    path = "/api/receipts/ocr",
+    tag = "receipts",
+    params(("X-Vault-Key" = Option<String>, Header, description = "Vault key, required for encrypted receipts")),
+    request_body = OcrRequest,
+    responses(
+        (status = 200, description = "OCR result and donation suggestion", body = OcrResponse),
+        (status = 400, description = "Missing id/key or unsupported receipt", body = String),
+        (status = 404, description = "Receipt not found", body = String),
+    )
+)]
 pub async fn ocr_receipt(
     State(state): State<AppState>,
     user: AuthenticatedUser,
@@ -271,7 +700,7 @@ pub async fn ocr_receipt(
             }
         }
     } else if let Some(key) = req.key.clone() {
-        let normalized_key = crate::storage::normalize_object_key(&state.bucket_name, &key);
+        let normalized_key = state.storage.normalize_key(&key);
         let user_prefix = crate::storage::user_receipt_prefix(&user.id);
         if !normalized_key.starts_with(&user_prefix) {
             return (StatusCode::FORBIDDEN, "Forbidden").into_response();
@@ -287,7 +716,7 @@ pub async fn ocr_receipt(
         }
     }
     if let Some(size_value) = size {
-        if size_value <= 0 || size_value > MAX_RECEIPT_SIZE_BYTES {
+        if size_value <= 0 || size_value > state.max_receipt_bytes {
             return (StatusCode::BAD_REQUEST, "Invalid or oversized receipt").into_response();
         }
     }
@@ -298,26 +727,16 @@ pub async fn ocr_receipt(
         };
 
         // Download and decrypt transiently
-        let download_url = match crate::storage::presign_url(&state, "GET", &receipt_key, 300) {
-            Ok(url) => url,
+        let bytes = match state.storage.get_bytes(&receipt_key).await {
+            Ok(Some(bytes)) => bytes,
+            Ok(None) => return (StatusCode::NOT_FOUND, "Receipt file not found").into_response(),
             Err(e) => {
-                tracing::error!("Failed to presign URL for transient OCR: {}", e);
+                tracing::error!("Failed to download receipt for transient OCR: {}", e);
                 return (StatusCode::INTERNAL_SERVER_ERROR, "Storage Error").into_response();
             }
         };
 
-        let client = reqwest::Client::new();
-        let bytes = match client.get(download_url).send().await {
-            Ok(resp) if resp.status().is_success() => match resp.bytes().await {
-                Ok(b) => b,
-                Err(e) => return (StatusCode::INTERNAL_SERVER_ERROR, format!("Failed to read receipt bytes: {}", e)).into_response(),
-            },
-            Ok(resp) => return (StatusCode::INTERNAL_SERVER_ERROR, format!("Failed to download receipt: {}", resp.status())).into_response(),
-            Err(e) => return (StatusCode::INTERNAL_SERVER_ERROR, format!("Download request failed: {}", e)).into_response(),
-        };
-
-        let encrypted_b64 = base64::prelude::BASE64_STANDARD.encode(&bytes);
-        let decrypted_bytes = match crate::auth::decrypt_payload(vault_key, &encrypted_b64) {
+        let decrypted_bytes = match crate::auth::decrypt_object(vault_key, &bytes) {
             Ok(b) => b,
             Err(e) => {
                 tracing::error!("Failed to decrypt receipt for OCR: {}", e);
@@ -362,6 +781,7 @@ pub async fn ocr_receipt(
                     )
                         .into_response();
                 }
+                state.events.publish(&user.id, "receipts", &receipt_id_value, "ocr");
 
                 let audit_id = Uuid::new_v4().to_string();
                 let details = Some(format!(
@@ -418,3 +838,48 @@ pub async fn ocr_receipt(
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_rejected_confirm_keeps_an_object_that_may_be_in_use() {
+        // The test database is unreachable, so whether a receipt holds the key is unknown.
+        let state = crate::http_pipeline_tests::test_state();
+        let key = "receipts/user-1/2026/committed.pdf";
+        state
+            .storage
+            .put(key, axum::body::Bytes::from_static(b"%PDF-1.7\n%receipt"), Some("application/pdf"))
+            .await
+            .unwrap();
+
+        let rejected = verify_uploaded_object(&state, "user-1", key, Some(3), false).await;
+        assert_eq!(rejected, Err((StatusCode::BAD_REQUEST, "Receipt size does not match the upload")));
+        assert!(state.storage.head(key).await.unwrap().is_some());
+    }
+
+    #[test]
+    fn test_content_must_match_extension_magic() {
+        assert!(content_matches_extension("pdf", b"%PDF-1.7\n%", false));
+        assert!(content_matches_extension("jpg", &[0xFF, 0xD8, 0xFF, 0xE0], false));
+        assert!(content_matches_extension("webp", b"RIFF\x10\0\0\0WEBPVP8 ", false));
+        assert!(content_matches_extension("heic", b"\0\0\0\x18ftypheic", false));
+        assert!(content_matches_extension("mp4", b"\0\0\0\x20ftypisom", false));
+        assert!(content_matches_extension("mov", b"\0\0\0\x08wide\0\0", false));
+        assert!(content_matches_extension("csv", b"date,amount\n2026-01-01,5", false));
+
+        // An executable or archive renamed to an image or PDF.
+        assert!(!content_matches_extension("jpg", b"MZ\x90\0\x03", false));
+        assert!(!content_matches_extension("pdf", b"PK\x03\x04", false));
+        assert!(!content_matches_extension("mp4", b"PK\x03\x04\0\0\0\0", false));
+        assert!(!content_matches_extension("txt", b"\x7fELF\x02\x01\x01\0", false));
+
+        // Encrypted receipts must be vault objects stored as `.bin`.
+        let mut sealed = b"DTV1\x01".to_vec();
+        sealed.extend_from_slice(&[7; 27]);
+        assert!(content_matches_extension("bin", &sealed, true));
+        assert!(!content_matches_extension("bin", b"%PDF-1.7 plaintext receipt....", true));
+        assert!(!content_matches_extension("pdf", &sealed, true));
+    }
+}
@@ -1,43 +1,233 @@
 use crate::auth::AuthenticatedUser;
 use crate::db;
+use crate::db::models::Donation;
+use crate::vault_envelope::{self, VaultKey};
 use crate::AppState;
-use axum::http::{header, HeaderValue};
+use axum::http::{header, HeaderMap, HeaderValue, StatusCode};
 use axum::response::Response;
 use axum::{
     extract::{Query, State},
     response::IntoResponse,
 };
+use chrono::{Datelike, NaiveDate};
+use std::collections::HashMap;
+use std::fmt::Write as _;
+use zeroize::{Zeroize, Zeroizing};
 
-#[derive(serde::Deserialize)]
+#[derive(serde::Deserialize, utoipa::IntoParams)]
+#[into_params(parameter_in = Query)]
 pub struct ExportParams {
     pub year: Option<i32>,
 }
 
-#[derive(serde::Serialize)]
+#[derive(serde::Serialize, utoipa::ToSchema)]
 pub struct YearsResponse {
     pub years: Vec<i32>,
 }
 
 fn csv_escape(s: &str) -> String {
-    let mut normalized = s.to_string();
-    if let Some(first) = normalized.chars().next() {
-        if matches!(first, '=' | '+' | '-' | '@') {
-            normalized.insert(0, '\'');
+    let mut out = String::new();
+    push_csv_field(&mut out, s);
+    out
+}
+
+/// Appends `s` to `out` as one CSV field, with no intermediate copy. Takes at
+/// most `2 * s.len() + 3` bytes.
+fn push_csv_field(out: &mut String, s: &str) {
+    let quoted = s.contains([',', '"', '\n']);
+    if quoted {
+        out.push('"');
+    }
+    if s.starts_with(['=', '+', '-', '@']) {
+        out.push('\'');
+    }
+    for c in s.chars() {
+        if c == '"' {
+            out.push('"');
         }
+        out.push(c);
+    }
+    if quoted {
+        out.push('"');
     }
+}
+
+/// Appends `s` to `out` with the characters that would end a TXF line replaced.
+fn push_txf_text(out: &mut String, s: &str) {
+    out.extend(s.chars().map(|c| if matches!(c, '^' | '\r' | '\n') { ' ' } else { c }));
+}
+
+/// A response body whose buffer is zeroed once the response has been sent.
+fn zeroizing_body(bytes: Vec<u8>) -> axum::body::Body {
+    axum::body::Body::from(axum::body::Bytes::from_owner(Zeroizing::new(bytes)))
+}
+
+/// Fields of a donation's `encrypted_payload`, as sealed by the browser.
+#[derive(serde::Deserialize)]
+struct DonationPlaintext {
+    date: Option<String>,
+    category: Option<String>,
+    amount: Option<PlaintextAmount>,
+    notes: Option<String>,
+}
+
+#[derive(serde::Deserialize)]
+#[serde(untagged)]
+enum PlaintextAmount {
+    Number(f64),
+    Text(String),
+}
+
+/// The part of a charity's `encrypted_payload` a report shows.
+#[derive(serde::Deserialize)]
+struct CharityPlaintext {
+    name: Option<String>,
+    ein: Option<String>,
+}
 
-    if normalized.contains(',') || normalized.contains('"') || normalized.contains('\n') {
-        let escaped = normalized.replace('"', "\"\"");
-        format!("\"{}\"", escaped)
-    } else {
-        normalized
+/// Donations for one report, with encrypted payloads opened in memory. The
+/// plaintext is never stored and is zeroed when the report is dropped.
+struct ReportDonations(Vec<Donation>);
+
+impl Drop for ReportDonations {
+    fn drop(&mut self) {
+        for d in &mut self.0 {
+            d.charity_name.zeroize();
+            d.charity_ein.zeroize();
+            d.category.zeroize();
+            d.notes.zeroize();
+            d.amount = None;
+            d.date = NaiveDate::default();
+        }
     }
 }
 
-fn txf_escape_line(s: &str) -> String {
-    s.replace(['^', '\r', '\n'], " ")
+fn vault_key_header(headers: &HeaderMap) -> Result<Option<VaultKey>, (StatusCode, &'static str)> {
+    let Some(value) = headers.get("x-vault-key") else {
+        return Ok(None);
+    };
+    value
+        .to_str()
+        .ok()
+        .and_then(|v| VaultKey::from_base64(v).ok())
+        .map(Some)
+        .ok_or((StatusCode::BAD_REQUEST, "Vault key must be 32 bytes, base64-encoded"))
+}
+
+fn open_json<T: serde::de::DeserializeOwned>(key: &VaultKey, payload: &str) -> anyhow::Result<T> {
+    let plaintext = Zeroizing::new(vault_envelope::decrypt_payload(key, payload)?);
+    Ok(serde_json::from_slice(&plaintext)?)
 }
 
+fn open_donation(key: &VaultKey, d: &mut Donation) -> anyhow::Result<()> {
+    let Some(payload) = d.encrypted_payload.as_deref() else {
+        return Ok(());
+    };
+    let mut plain: DonationPlaintext = open_json(key, payload)?;
+    if let Some(date) = plain.date.as_deref().and_then(|v| NaiveDate::parse_from_str(v, "%Y-%m-%d").ok()) {
+        d.date = date;
+        d.year = date.year();
+    }
+    d.amount = match plain.amount.take() {
+        Some(PlaintextAmount::Number(amount)) => Some(amount),
+        Some(PlaintextAmount::Text(mut text)) => {
+            let amount = text.trim().parse().ok();
+            text.zeroize();
+            amount
+        }
+        None => None,
+    };
+    d.category = plain.category.take().or(d.category.take());
+    d.notes = plain.notes.take();
+    plain.date.zeroize();
+    Ok(())
+}
+
+/// The user's donations for a report. Encrypted donations need the vault key
+/// in `X-Vault-Key`; with it, every year is read and filtered on the opened
+/// date, since the server-side year of an encrypted donation is a guess.
+async fn report_donations(
+    state: &AppState,
+    user_id: &str,
+    year: Option<i32>,
+    headers: &HeaderMap,
+) -> Result<ReportDonations, (StatusCode, &'static str)> {
+    let key = vault_key_header(headers)?;
+    let query_year = if key.is_some() { None } else { year };
+    let list = db::donations::list_donations(&state.db, user_id, query_year)
+        .await
+        .map_err(|e| {
+            tracing::error!("Failed to load donations for report: {}", e);
+            (StatusCode::INTERNAL_SERVER_ERROR, "Database Error")
+        })?;
+    let mut report = ReportDonations(list);
+    let encrypted = |d: &Donation| d.is_encrypted.unwrap_or(false);
+    if !report.0.iter().any(encrypted) {
+        if key.is_some() {
+            report.0.retain(|d| year.is_none_or(|y| d.year == y));
+        }
+        return Ok(report);
+    }
+    let Some(key) = key else {
+        return Err((StatusCode::BAD_REQUEST, "Vault key required for encrypted donations"));
+    };
+
+    let charities = db::charities::list_charities(&state.db, user_id).await.map_err(|e| {
+        tracing::error!("Failed to load charities for report: {}", e);
+        (StatusCode::INTERNAL_SERVER_ERROR, "Database Error")
+    })?;
+    let mut names: HashMap<String, CharityPlaintext> = HashMap::new();
+    let mut result = Ok(());
+    for charity in charities.iter().filter(|c| c.is_encrypted.unwrap_or(false)) {
+        let Some(payload) = charity.encrypted_payload.as_deref() else {
+            continue;
+        };
+        match open_json(&key, payload) {
+            Ok(plain) => {
+                names.insert(charity.id.clone(), plain);
+            }
+            Err(e) => {
+                tracing::warn!("Failed to open charity {} for report: {}", charity.id, e);
+                result = Err((StatusCode::UNAUTHORIZED, "Invalid vault key or corrupted data"));
+                break;
+            }
+        }
+    }
+    if result.is_ok() {
+        for d in report.0.iter_mut().filter(|d| encrypted(d)) {
+            if let Err(e) = open_donation(&key, d) {
+                tracing::warn!("Failed to open donation {} for report: {}", d.id, e);
+                result = Err((StatusCode::UNAUTHORIZED, "Invalid vault key or corrupted data"));
+                break;
+            }
+        }
+    }
+    for d in report.0.iter_mut() {
+        if let Some(plain) = names.get(&d.charity_id) {
+            if let Some(name) = &plain.name {
+                d.charity_name.zeroize();
+                d.charity_name = name.clone();
+            }
+            if plain.ein.is_some() {
+                d.charity_ein = plain.ein.clone();
+            }
+        }
+    }
+    for plain in names.values_mut() {
+        plain.name.zeroize();
+        plain.ein.zeroize();
+    }
+    result?;
+    report.0.retain(|d| year.is_none_or(|y| d.year == y));
+    Ok(report)
+}
+
+#[utoipa::path(
+    get,
+    path = "/api/reports/years",
+    tag = "reports",
+    responses((status = 200, description = "Years with donations", body = YearsResponse))
+)]
 pub async fn list_available_years(
     State(state): State<AppState>,
     user: AuthenticatedUser,
@@ -52,116 +242,231 @@ pub async fn list_available_years(
     }
 }
 
+/// Renders donations as the CSV download served by `/api/reports/export`.
+/// Rows are written straight into one buffer sized up front, so it never
+/// reallocates, and the body is zeroed after it is sent.
+pub(crate) fn donations_csv_response(list: &[Donation]) -> Response {
+    const HEADER: &str = "id,date,category,amount,charity_name,charity_id,notes\n";
+    let escaped = |s: &str| 2 * s.len() + 3;
+    let capacity = HEADER.len()
+        + list
+            .iter()
+            .map(|d| {
+                escaped(&d.id)
+                    + escaped(d.category.as_deref().unwrap_or_default())
+                    + escaped(&d.charity_name)
+                    + escaped(&d.charity_id)
+                    + escaped(d.notes.as_deref().unwrap_or_default())
+                    // Date, amount, separators and newline.
+                    + 64
+            })
+            .sum::<usize>();
+    let mut w = Zeroizing::new(String::with_capacity(capacity));
+    w.push_str(HEADER);
+    for d in list {
+        push_csv_field(&mut w, &d.id);
+        let _ = write!(w, ",{},", d.date.format("%Y-%m-%d"));
+        push_csv_field(&mut w, d.category.as_deref().unwrap_or_default());
+        let amount = d.amount.unwrap_or(0.0);
+        // Same formula guard as push_csv_field applies to a leading '-'.
+        w.push_str(if amount.is_sign_negative() { ",'" } else { "," });
+        let _ = write!(w, "{:.2},", amount);
+        push_csv_field(&mut w, &d.charity_name);
+        w.push(',');
+        push_csv_field(&mut w, &d.charity_id);
+        w.push(',');
+        push_csv_field(&mut w, d.notes.as_deref().unwrap_or_default());
+        w.push('\n');
+    }
+
+    let mut resp = Response::new(zeroizing_body(std::mem::take(&mut *w).into_bytes()));
+    let headers = resp.headers_mut();
+    headers.insert(
+        header::CONTENT_TYPE,
+        HeaderValue::from_static("text/csv; charset=utf-8"),
+    );
+    headers.insert(
+        header::CONTENT_DISPOSITION,
+        HeaderValue::from_static("attachment; filename=donations.csv"),
+    );
+    resp
+}
+
+/// Renders donations as the TXF download served by `/api/reports/export/txf`.
+/// Like the CSV, it is written into one presized buffer that is zeroed after sending.
+pub(crate) fn donations_txf_response(list: &[Donation]) -> Response {
+    let capacity = 64
+        + list
+            .iter()
+            .map(|d| {
+                d.id.len()
+                    + d.charity_name.len()
+                    + d.charity_ein.as_deref().map_or(0, str::len)
+                    + d.notes.as_deref().map_or(0, str::len)
+                    // Record lines, date, amount and memo labels.
+                    + 160
+            })
+            .sum::<usize>();
+    let mut out = Zeroizing::new(String::with_capacity(capacity));
+    out.push_str("V042\n");
+    out.push_str("ADeductible Tracker\n");
+    let _ = writeln!(out, "D{}", chrono::Utc::now().format("%m/%d/%Y"));
+    out.push_str("^\n");
+
+    for d in list {
+        let ein = d.charity_ein.as_deref().unwrap_or_default().trim();
+        let notes = d.notes.as_deref().unwrap_or_default().trim();
+
+        out.push_str("TD\n");
+        out.push_str("N323\n");
+        out.push_str("C1\n");
+        out.push_str("LCharitable contributions\n");
+        out.push('P');
+        push_txf_text(&mut out, &d.charity_name);
+        let _ = writeln!(out, "\nD{}", d.date.format("%Y-%m-%d"));
+        let _ = writeln!(out, "${:.2}", d.amount.unwrap_or(0.0));
+        out.push_str("MDonation ID: ");
+        push_txf_text(&mut out, &d.id);
+        if !ein.is_empty() {
+            out.push_str(" | EIN: ");
+            push_txf_text(&mut out, ein);
+        }
+        if !notes.is_empty() {
+            out.push_str(" | Notes: ");
+            push_txf_text(&mut out, notes);
+        }
+        out.push('\n');
+        out.push_str("^\n");
+    }
+
+    let mut resp = Response::new(zeroizing_body(std::mem::take(&mut *out).into_bytes()));
+    let headers = resp.headers_mut();
+    headers.insert(
+        header::CONTENT_TYPE,
+        HeaderValue::from_static("application/octet-stream"),
+    );
+    headers.insert(
+        header::CONTENT_DISPOSITION,
+        HeaderValue::from_static("attachment; filename=donations-tax-export.txf"),
+    );
+    resp
+}
+
+/// Renders donations as the PDF summary served by `/api/reports/export/pdf`.
+pub(crate) fn donations_pdf_response(list: &[Donation], year: Option<i32>) -> Response {
+    let title = match year {
+        Some(year) => format!("Charitable contributions {}", year),
+        None => "Charitable contributions".to_string(),
+    };
+    let mut lines = Zeroizing::new(Vec::with_capacity(list.len() + 2));
+    for d in list {
+        let mut line = format!(
+            "{}   ${:.2}   {}",
+            d.date.format("%Y-%m-%d"),
+            d.amount.unwrap_or(0.0),
+            d.charity_name
+        );
+        if let Some(category) = d.category.as_deref().filter(|c| !c.is_empty()) {
+            line.push_str(&format!(" ({})", category));
+        }
+        lines.push(line);
+    }
+    let total: f64 = list.iter().filter_map(|d| d.amount).sum();
+    lines.push(String::new());
+    lines.push(format!("Total: ${:.2} in {} donations", total, list.len()));
+
+    let mut resp = Response::new(zeroizing_body(crate::pdf::render_text_pdf(&title, &lines)));
+    let headers = resp.headers_mut();
+    headers.insert(header::CONTENT_TYPE, HeaderValue::from_static("application/pdf"));
+    headers.insert(
+        header::CONTENT_DISPOSITION,
+        HeaderValue::from_static("attachment; filename=donations.pdf"),
+    );
+    resp
+}
+
+#[utoipa::path(
+    get,
+    path = "/api/reports/export",
+    tag = "reports",
+    params(ExportParams, ("X-Vault-Key" = Option<String>, Header, description = "Vault key, required when donations are encrypted")),
+    responses(
+        (status = 200, description = "Donations as CSV", content_type = "text/csv", body = String),
+        (status = 400, description = "Encrypted donations and no vault key", body = String),
+        (status = 401, description = "Vault key does not open the donations", body = String),
+    )
+)]
 pub async fn export_csv(
     State(state): State<AppState>,
     user: AuthenticatedUser,
+    headers: HeaderMap,
     Query(params): Query<ExportParams>,
 ) -> impl IntoResponse {
-    match db::donations::list_donations(&state.db, &user.id, params.year).await {
-        Ok(list) => {
-            let mut w = String::new();
-            w.push_str("id,date,category,amount,charity_name,charity_id,notes\n");
-            for d in list {
-                let date = d.date.format("%Y-%m-%d").to_string();
-                let category = d.category.clone().unwrap_or_default();
-                let amount = format!("{:.2}", d.amount.unwrap_or(0.0));
-                let notes = d.notes.clone().unwrap_or_default();
-                w.push_str(&format!(
-                    "{},{},{},{},{},{},{}\n",
-                    csv_escape(&d.id),
-                    csv_escape(&date),
-                    csv_escape(&category),
-                    csv_escape(&amount),
-                    csv_escape(&d.charity_name),
-                    csv_escape(&d.charity_id),
-                    csv_escape(&notes),
-                ));
-            }
-
-            let mut resp = Response::new(w.into());
-            let headers = resp.headers_mut();
-            headers.insert(
-                header::CONTENT_TYPE,
-                HeaderValue::from_static("text/csv; charset=utf-8"),
-            );
-            headers.insert(
-                header::CONTENT_DISPOSITION,
-                HeaderValue::from_static("attachment; filename=donations.csv"),
-            );
-            resp
-        }
-        Err(_) => (
-            axum::http::StatusCode::INTERNAL_SERVER_ERROR,
-            "Database Error",
-        )
-            .into_response(),
+    match report_donations(&state, &user.id, params.year, &headers).await {
+        Ok(report) => donations_csv_response(&report.0),
+        Err(e) => e.into_response(),
     }
 }
 
+#[utoipa::path(
+    get,
+    path = "/api/reports/export/txf",
+    tag = "reports",
+    params(ExportParams, ("X-Vault-Key" = Option<String>, Header, description = "Vault key, required when donations are encrypted")),
+    responses(
+        (status = 200, description = "Donations in TXF for tax software", content_type = "application/octet-stream", body = String),
+        (status = 400, description = "Encrypted donations and no vault key", body = String),
+        (status = 401, description = "Vault key does not open the donations", body = String),
+    )
+)]
 pub async fn export_tax_txf(
     State(state): State<AppState>,
     user: AuthenticatedUser,
+    headers: HeaderMap,
     Query(params): Query<ExportParams>,
 ) -> impl IntoResponse {
-    match db::donations::list_donations(&state.db, &user.id, params.year).await {
-        Ok(list) => {
-            let mut out = String::new();
-            out.push_str("V042\n");
-            out.push_str("ADeductible Tracker\n");
-            out.push_str(&format!("D{}\n", chrono::Utc::now().format("%m/%d/%Y")));
-            out.push_str("^\n");
-
-            for d in list {
-                let date = d.date.format("%Y-%m-%d").to_string();
-                let ein = d.charity_ein.unwrap_or_default();
-                let notes = d.notes.unwrap_or_default();
-                let amount = d.amount.unwrap_or(0.0);
-                let mut memo_parts = Vec::new();
-                memo_parts.push(format!("Donation ID: {}", d.id));
-                if !ein.trim().is_empty() {
-                    memo_parts.push(format!("EIN: {}", ein.trim()));
-                }
-                if !notes.trim().is_empty() {
-                    memo_parts.push(format!("Notes: {}", notes.trim()));
-                }
-                let memo = memo_parts.join(" | ");
-
-                out.push_str("TD\n");
-                out.push_str("N323\n");
-                out.push_str("C1\n");
-                out.push_str("LCharitable contributions\n");
-                out.push_str(&format!("P{}\n", txf_escape_line(&d.charity_name)));
-                out.push_str(&format!("D{}\n", txf_escape_line(&date)));
-                out.push_str(&format!("${:.2}\n", amount));
-                out.push_str(&format!("M{}\n", txf_escape_line(&memo)));
-                out.push_str("^\n");
-            }
+    match report_donations(&state, &user.id, params.year, &headers).await {
+        Ok(report) => donations_txf_response(&report.0),
+        Err(e) => e.into_response(),
+    }
+}
 
-            let mut resp = Response::new(out.into());
-            let headers = resp.headers_mut();
-            headers.insert(
-                header::CONTENT_TYPE,
-                HeaderValue::from_static("application/octet-stream"),
-            );
-            headers.insert(
-                header::CONTENT_DISPOSITION,
-                HeaderValue::from_static("attachment; filename=donations-tax-export.txf"),
-            );
-            resp
-        }
-        Err(_) => (
-            axum::http::StatusCode::INTERNAL_SERVER_ERROR,
-            "Database Error",
-        )
-            .into_response(),
+#[utoipa::path(
+    get,
+    path = "/api/reports/export/pdf",
+    tag = "reports",
+    params(ExportParams, ("X-Vault-Key" = Option<String>, Header, description = "Vault key, required when donations are encrypted")),
+    responses(
+        (status = 200, description = "Donations as a printable PDF summary", content_type = "application/pdf", body = String),
+        (status = 400, description = "Encrypted donations and no vault key", body = String),
+        (status = 401, description = "Vault key does not open the donations", body = String),
+    )
+)]
+pub async fn export_pdf(
+    State(state): State<AppState>,
+    user: AuthenticatedUser,
+    headers: HeaderMap,
+    Query(params): Query<ExportParams>,
+) -> impl IntoResponse {
+    match report_donations(&state, &user.id, params.year, &headers).await {
+        Ok(report) => donations_pdf_response(&report.0, params.year),
+        Err(e) => e.into_response(),
     }
 }
 
-#[derive(serde::Deserialize)]
+#[derive(serde::Deserialize, utoipa::IntoParams)]
+#[into_params(parameter_in = Query)]
 pub struct AuditExportParams {
     pub since: Option<String>,
 }
 
+#[utoipa::path(
+    get,
+    path = "/api/reports/audit",
+    tag = "reports",
+    params(AuditExportParams),
+    responses((status = 200, description = "Audit log as CSV", content_type = "text/csv", body = String))
+)]
 pub async fn export_audit_csv(
     State(state): State<AppState>,
     user: AuthenticatedUser,
@@ -211,3 +516,83 @@ pub async fn export_audit_csv(
             .into_response(),
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    async fn body_text(resp: Response) -> String {
+        let bytes = axum::body::to_bytes(resp.into_body(), usize::MAX).await.unwrap();
+        String::from_utf8(bytes.to_vec()).unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_csv_and_txf_exports_escape_opened_fields() {
+        let mut donation = encrypted_donation("sealed".to_string());
+        donation.charity_name = "Food, \"Bank\"".to_string();
+        donation.category = Some("=cmd".to_string());
+        donation.amount = Some(-5.0);
+        donation.notes = Some("line^one\nline two".to_string());
+        donation.charity_ein = Some(" 12-3456789 ".to_string());
+        let list = vec![donation];
+
+        let csv = body_text(donations_csv_response(&list)).await;
+        assert_eq!(
+            csv,
+            "id,date,category,amount,charity_name,charity_id,notes\n\
+             d1,2026-01-02,'=cmd,'-5.00,\"Food, \"\"Bank\"\"\",c1,\"line^one\nline two\"\n"
+        );
+        assert_eq!(csv_escape("-1"), "'-1");
+
+        let txf = body_text(donations_txf_response(&list)).await;
+        assert!(txf.contains("\nPFood, \"Bank\"\nD2026-01-02\n$-5.00\nMDonation ID: d1 | EIN: 12-3456789 | Notes: line one line two\n^\n"));
+    }
+
+    fn encrypted_donation(payload: String) -> Donation {
+        Donation {
+            id: "d1".to_string(),
+            user_id: "u1".to_string(),
+            year: 2026,
+            date: NaiveDate::from_ymd_opt(2026, 1, 2).unwrap(),
+            category: None,
+            amount: None,
+            charity_id: "c1".to_string(),
+            charity_name: "encrypted-c1".to_string(),
+            charity_ein: None,
+            notes: None,
+            is_encrypted: Some(true),
+            encrypted_payload: Some(payload),
+            shared_with: None,
+            created_at: chrono::Utc::now(),
+            updated_at: chrono::Utc::now(),
+            deleted: false,
+        }
+    }
+
+    #[test]
+    fn test_open_donation_fills_fields_from_the_payload() {
+        let key = VaultKey::from_bytes(&[9u8; 32]).unwrap();
+        let payload = vault_envelope::encrypt_payload(
+            &key,
+            br#"{"date":"2025-12-30","category":"money","amount":"125.5","notes":"year-end"}"#,
+        )
+        .unwrap();
+        let mut donation = encrypted_donation(payload);
+        open_donation(&key, &mut donation).unwrap();
+        assert_eq!(donation.date, NaiveDate::from_ymd_opt(2025, 12, 30).unwrap());
+        assert_eq!(donation.year, 2025);
+        assert_eq!(donation.amount, Some(125.5));
+        assert_eq!(donation.notes.as_deref(), Some("year-end"));
+
+        let other = VaultKey::from_bytes(&[8u8; 32]).unwrap();
+        assert!(open_donation(&other, &mut donation).is_err());
+    }
+
+    #[test]
+    fn test_vault_key_header_is_optional_but_must_be_valid() {
+        let mut headers = HeaderMap::new();
+        assert!(vault_key_header(&headers).unwrap().is_none());
+        headers.insert("x-vault-key", HeaderValue::from_static("not-a-key"));
+        assert_eq!(vault_key_header(&headers).err().map(|e| e.0), Some(StatusCode::BAD_REQUEST));
+    }
+}
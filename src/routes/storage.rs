@@ -0,0 +1,180 @@
+use crate::AppState;
+use axum::{
+    body::Body,
+    extract::{Multipart, Path, Query, State},
+    http::{header, Method, StatusCode},
+    response::{IntoResponse, Response},
+};
+use serde::Deserialize;
+
+/// Room a presigned form needs besides its file: the policy, signature and key fields.
+const FORM_FIELDS_ALLOWANCE: usize = 64 * 1024;
+
+/// Request body limit of the `/storage` form route: one receipt at the
+/// `RECEIPT_MAX_UPLOAD_MB` ceiling plus its policy fields.
+pub(crate) fn local_form_body_limit(max_receipt_bytes: i64) -> usize {
+    usize::try_from(max_receipt_bytes).unwrap_or(0).saturating_add(FORM_FIELDS_ALLOWANCE)
+}
+
+/// The stored type when it is one a browser cannot script (raster images, video, PDF);
+/// anything else, such as HTML, SVG or XML, is served as opaque bytes.
+fn served_content_type(stored: Option<&str>) -> &str {
+    match stored.map(str::trim) {
+        Some(t @ ("image/jpeg" | "image/png" | "image/gif" | "image/webp" | "image/avif" | "image/bmp"
+            | "image/tiff" | "image/heic" | "image/heif" | "video/mp4" | "video/quicktime" | "application/pdf")) => t,
+        _ => "application/octet-stream",
+    }
+}
+
+#[derive(Deserialize)]
+pub struct LocalObjectQuery {
+    expires: i64,
+    signature: String,
+}
+
+/// Serves the presigned `/storage/<key>` URLs of the filesystem and in-memory
+/// backends. The signature is the only credential, as with S3 presigned URLs,
+/// so this sits outside `/api` and its session checks.
+pub async fn serve_local_object(
+    State(state): State<AppState>,
+    method: Method,
+    Path(key): Path<String>,
+    Query(query): Query<LocalObjectQuery>,
+    headers: axum::http::HeaderMap,
+    body: Body,
+) -> Response {
+    let Some(signer) = state.storage.local_signer() else {
+        return (StatusCode::NOT_FOUND, "Not found").into_response();
+    };
+    let key = state.storage.normalize_key(&key);
+    let signed_method = if method == Method::HEAD { "GET" } else { method.as_str() };
+    if key.is_empty() || !signer.verify(signed_method, &key, query.expires, &query.signature) {
+        return (StatusCode::FORBIDDEN, "Invalid or expired signature").into_response();
+    }
+
+    match method {
+        Method::GET | Method::HEAD => {
+            let meta = match state.storage.head(&key).await {
+                Ok(Some(meta)) => meta,
+                Ok(None) => return (StatusCode::NOT_FOUND, "Not found").into_response(),
+                Err(e) => {
+                    tracing::error!("Local storage head for {} failed: {}", key, e);
+                    return (StatusCode::INTERNAL_SERVER_ERROR, "Storage Error").into_response();
+                }
+            };
+            let stream = match state.storage.get(&key).await {
+                Ok(Some(stream)) => stream,
+                Ok(None) => return (StatusCode::NOT_FOUND, "Not found").into_response(),
+                Err(e) => {
+                    tracing::error!("Local storage read for {} failed: {}", key, e);
+                    return (StatusCode::INTERNAL_SERVER_ERROR, "Storage Error").into_response();
+                }
+            };
+            // Objects come from this origin but their bytes and type are the uploader's, so
+            // they are never rendered as a document here.
+            (
+                [
+                    (header::CONTENT_TYPE, served_content_type(meta.content_type.as_deref()).to_string()),
+                    (header::CONTENT_LENGTH, meta.size.to_string()),
+                    (header::CACHE_CONTROL, "private, no-store".to_string()),
+                    (header::CONTENT_DISPOSITION, "attachment".to_string()),
+                    (header::CONTENT_SECURITY_POLICY, "sandbox; default-src 'none'".to_string()),
+                ],
+                Body::from_stream(stream),
+            )
+                .into_response()
+        }
+        Method::PUT => {
+            // A whole receipt or one part of it, so never more than the receipt ceiling.
+            let limit = usize::try_from(state.max_receipt_bytes).unwrap_or(0);
+            let bytes = match axum::body::to_bytes(body, limit).await {
+                Ok(bytes) => bytes,
+                Err(_) => return (StatusCode::PAYLOAD_TOO_LARGE, "Object too large").into_response(),
+            };
+            let content_type = headers.get(header::CONTENT_TYPE).and_then(|value| value.to_str().ok());
+            // Multipart uploads hand this back when they complete.
+            let etag = crate::storage::local_etag(&bytes);
+            match state.storage.put(&key, bytes, content_type).await {
+                Ok(()) => (StatusCode::OK, [(header::ETAG, etag)]).into_response(),
+                Err(e) => {
+                    tracing::error!("Local storage write for {} failed: {}", key, e);
+                    (StatusCode::INTERNAL_SERVER_ERROR, "Storage Error").into_response()
+                }
+            }
+        }
+        Method::DELETE => match state.storage.delete(&key).await {
+            Ok(()) => StatusCode::NO_CONTENT.into_response(),
+            Err(e) => {
+                tracing::error!("Local storage delete for {} failed: {}", key, e);
+                (StatusCode::INTERNAL_SERVER_ERROR, "Storage Error").into_response()
+            }
+        },
+        _ => StatusCode::METHOD_NOT_ALLOWED.into_response(),
+    }
+}
+
+/// Accepts the presigned POST forms of the filesystem and in-memory backends.
+/// As on S3, fields after `file` are ignored and the signed policy decides the
+/// key, content type and allowed size.
+pub async fn accept_local_form(State(state): State<AppState>, mut form: Multipart) -> Response {
+    let Some(signer) = state.storage.local_signer() else {
+        return (StatusCode::NOT_FOUND, "Not found").into_response();
+    };
+    let mut fields = Vec::new();
+    let mut file = None;
+    loop {
+        let field = match form.next_field().await {
+            Ok(Some(field)) => field,
+            Ok(None) => break,
+            Err(_) => return (StatusCode::BAD_REQUEST, "Invalid form").into_response(),
+        };
+        let name = field.name().unwrap_or_default().to_string();
+        let value = match field.bytes().await {
+            Ok(value) => value,
+            Err(_) => return (StatusCode::PAYLOAD_TOO_LARGE, "Object too large").into_response(),
+        };
+        if name == "file" {
+            file = Some(value);
+            break;
+        }
+        fields.push((name, String::from_utf8_lossy(&value).into_owned()));
+    }
+    let Some(file) = file else {
+        return (StatusCode::BAD_REQUEST, "Missing file").into_response();
+    };
+
+    let key = match signer.verify_post(&fields, file.len() as u64) {
+        Ok(key) => state.storage.normalize_key(&key),
+        Err(e) => {
+            tracing::warn!("Rejected local form upload: {}", e);
+            return (StatusCode::FORBIDDEN, "Upload does not satisfy its policy").into_response();
+        }
+    };
+    let content_type = fields
+        .iter()
+        .find(|(name, _)| name.eq_ignore_ascii_case("content-type"))
+        .map(|(_, value)| value.as_str());
+    match state.storage.put(&key, file, content_type).await {
+        Ok(()) => StatusCode::NO_CONTENT.into_response(),
+        Err(e) => {
+            tracing::error!("Local storage form write for {} failed: {}", key, e);
+            (StatusCode::INTERNAL_SERVER_ERROR, "Storage Error").into_response()
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_only_inert_content_types_are_served_as_stored() {
+        assert_eq!(served_content_type(Some("image/png")), "image/png");
+        assert_eq!(served_content_type(Some("application/pdf")), "application/pdf");
+        assert_eq!(served_content_type(Some("text/html")), "application/octet-stream");
+        assert_eq!(served_content_type(Some("image/svg+xml")), "application/octet-stream");
+        assert_eq!(served_content_type(Some("application/xml")), "application/octet-stream");
+        assert_eq!(served_content_type(Some("IMAGE/PNG")), "application/octet-stream");
+        assert_eq!(served_content_type(None), "application/octet-stream");
+    }
+}
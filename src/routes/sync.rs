@@ -1,12 +1,89 @@
 use crate::auth::AuthenticatedUser;
 use crate::db;
-use crate::db::models::{BatchSyncRequest, DonationSyncItem, ReceiptSyncItem};
+use crate::db::models::{
+    BatchSyncFailure, BatchSyncRequest, CharitySyncItem, DonationSyncItem, ProfileSyncItem,
+    ReceiptSyncItem, SyncChange,
+};
 use crate::AppState;
 use axum::{
-    extract::{Json, State},
+    extract::{Json, Query, State},
     http::StatusCode,
-    response::IntoResponse,
+    response::{IntoResponse, Json as AxumJson},
 };
+use base64::Engine;
+use serde::Deserialize;
+use serde_json::json;
+
+const CHANGE_FEED_PAGE_SIZE: usize = 500;
+const CHANGE_CURSOR_PREFIX: &str = "v1:";
+
+#[derive(Deserialize, utoipa::IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct ChangesParams {
+    /// Opaque `next_cursor` from the previous page; omit to start from the beginning.
+    pub cursor: Option<String>,
+}
+
+fn encode_change_cursor(seq: i64) -> String {
+    base64::prelude::BASE64_URL_SAFE_NO_PAD.encode(format!("{}{}", CHANGE_CURSOR_PREFIX, seq))
+}
+
+/// Cursors are opaque to clients; an absent or empty cursor starts from the beginning of the feed.
+fn decode_change_cursor(cursor: Option<&str>) -> Option<i64> {
+    let cursor = cursor.map(str::trim).unwrap_or_default();
+    if cursor.is_empty() {
+        return Some(0);
+    }
+    let decoded = base64::prelude::BASE64_URL_SAFE_NO_PAD.decode(cursor).ok()?;
+    let decoded = String::from_utf8(decoded).ok()?;
+    decoded
+        .strip_prefix(CHANGE_CURSOR_PREFIX)?
+        .parse::<i64>()
+        .ok()
+        .filter(|seq| *seq >= 0)
+}
+
+fn validate_profile_sync_item(item: &ProfileSyncItem) -> Result<(), &'static str> {
+    if item.name.as_deref().is_some_and(|value| value.trim().is_empty()) {
+        return Err("Name is required");
+    }
+
+    if let Some(agi) = item.agi {
+        if !agi.is_finite() || agi < 0.0 {
+            return Err("AGI must be a non-negative number");
+        }
+    }
+
+    if let Some(rate) = item.marginal_tax_rate {
+        if !rate.is_finite() || !(0.0..=1.0).contains(&rate) {
+            return Err("Marginal tax rate must be between 0 and 1");
+        }
+    }
+
+    Ok(())
+}
+
+fn validate_charity_sync_item(item: &CharitySyncItem) -> Result<(), &'static str> {
+    let action = item.action.trim();
+    if !matches!(action, "create" | "update" | "delete") {
+        return Err("Invalid charity sync action");
+    }
+
+    if item.id.trim().is_empty() {
+        return Err("Charity id is required for sync");
+    }
+
+    if action == "delete" {
+        return Ok(());
+    }
+
+    // Encrypted charities still carry a placeholder name for the unique (user_id, name) index.
+    if item.name.as_deref().map(str::trim).unwrap_or_default().is_empty() {
+        return Err("Charity name is required for sync");
+    }
+
+    Ok(())
+}
 
 fn validate_donation_sync_item(item: &DonationSyncItem) -> Result<(), &'static str> {
     let action = item.action.trim();
@@ -22,8 +99,12 @@ fn validate_donation_sync_item(item: &DonationSyncItem) -> Result<(), &'static s
         return Ok(());
     }
 
+    if let Some(tokens) = &item.blind_index {
+        crate::routes::blind_index::validate_blind_index(tokens)?;
+    }
+
     // Encrypted donations still require a valid charity_id for DB foreign key constraints.
-    // The client should have created an encrypted charity first if needed.
+    // The charity may be created earlier in the same batch.
     if item.charity_id.trim().is_empty() {
         return Err("Donation charity_id is required even for encrypted sync");
     }
@@ -53,8 +134,10 @@ fn validate_donation_sync_item(item: &DonationSyncItem) -> Result<(), &'static s
     Ok(())
 }
 
-fn validate_receipt_sync_item(item: &ReceiptSyncItem) -> Result<(), &'static str> {
-    if item.action.trim() != "create" {
+/// Receipt keys are expected already normalized, as `batch_sync` does before this.
+fn validate_receipt_sync_item(item: &ReceiptSyncItem, user_id: &str) -> Result<(), &'static str> {
+    let action = item.action.trim();
+    if !matches!(action, "create" | "update" | "delete") {
         return Err("Invalid receipt sync action");
     }
 
@@ -62,40 +145,143 @@ fn validate_receipt_sync_item(item: &ReceiptSyncItem) -> Result<(), &'static str
         return Err("Receipt id is required for sync");
     }
 
-    if item.donation_id.trim().is_empty() {
-        return Err("Receipt donation_id is required for sync");
-    }
+    match action {
+        "create" => {
+            if item.donation_id.trim().is_empty() {
+                return Err("Receipt donation_id is required for sync");
+            }
+
+            if item.key.trim().is_empty() {
+                return Err("Receipt key is required for sync");
+            }
+
+            if !item.key.starts_with(&crate::storage::user_receipt_prefix(user_id)) {
+                return Err("Receipt key is outside the user's storage");
+            }
+        }
+        "update" => {
+            if item.file_name.as_deref().is_some_and(|value| value.trim().is_empty()) {
+                return Err("Receipt file_name cannot be empty");
+            }
 
-    if item.key.trim().is_empty() {
-        return Err("Receipt key is required for sync");
+            if item.donation_id.trim().is_empty()
+                && item.file_name.is_none()
+                && item.encrypted_payload.is_none()
+            {
+                return Err("Receipt update has no changes");
+            }
+        }
+        _ => {}
     }
 
     Ok(())
 }
 
-fn validate_batch_sync_request(req: &BatchSyncRequest) -> Result<(), &'static str> {
-    for donation in &req.donations {
-        validate_donation_sync_item(donation)?;
+/// Splits a batch into the items that pass validation and per-item failures for the rest,
+/// so one malformed offline record does not block everything queued behind it.
+fn partition_batch_sync_request(
+    user_id: &str,
+    req: BatchSyncRequest,
+) -> (BatchSyncRequest, Vec<BatchSyncFailure>) {
+    let mut failures = Vec::new();
+    let mut valid = BatchSyncRequest {
+        profile: None,
+        charities: Vec::with_capacity(req.charities.len()),
+        donations: Vec::with_capacity(req.donations.len()),
+        receipts: Vec::with_capacity(req.receipts.len()),
+    };
+
+    if let Some(mut profile) = req.profile {
+        match validate_profile_sync_item(&profile) {
+            Ok(()) => {
+                profile.name = profile.name.map(|value| value.trim().to_string());
+                profile.filing_status = profile
+                    .filing_status
+                    .map(|value| value.trim().to_lowercase())
+                    .filter(|value| !value.is_empty());
+                valid.profile = Some(profile);
+            }
+            Err(reason) => failures.push(BatchSyncFailure {
+                table: "users".to_string(),
+                id: user_id.to_string(),
+                action: "update".to_string(),
+                reason: reason.to_string(),
+                retryable: false,
+            }),
+        }
     }
 
-    for receipt in &req.receipts {
-        validate_receipt_sync_item(receipt)?;
+    for charity in req.charities {
+        match validate_charity_sync_item(&charity) {
+            Ok(()) => valid.charities.push(charity),
+            Err(reason) => failures.push(BatchSyncFailure {
+                table: "charities".to_string(),
+                id: charity.id,
+                action: charity.action,
+                reason: reason.to_string(),
+                retryable: false,
+            }),
+        }
     }
 
-    Ok(())
+    for donation in req.donations {
+        match validate_donation_sync_item(&donation) {
+            Ok(()) => valid.donations.push(donation),
+            Err(reason) => failures.push(BatchSyncFailure {
+                table: "donations".to_string(),
+                id: donation.id,
+                action: donation.action,
+                reason: reason.to_string(),
+                retryable: false,
+            }),
+        }
+    }
+
+    for receipt in req.receipts {
+        match validate_receipt_sync_item(&receipt, user_id) {
+            Ok(()) => valid.receipts.push(receipt),
+            Err(reason) => failures.push(BatchSyncFailure {
+                table: "receipts".to_string(),
+                id: receipt.id,
+                action: receipt.action,
+                reason: reason.to_string(),
+                retryable: false,
+            }),
+        }
+    }
+
+    (valid, failures)
 }
 
+#[utoipa::path(
+    post,
+    path = "/api/sync/batch",
+    tag = "sync",
+    request_body = BatchSyncRequest,
+    responses((status = 200, description = "Applied count and per-item failures", body = crate::db::models::BatchSyncResult))
+)]
 pub async fn batch_sync(
     State(state): State<AppState>,
     user: AuthenticatedUser,
-    Json(req): Json<BatchSyncRequest>,
+    Json(mut req): Json<BatchSyncRequest>,
 ) -> impl IntoResponse {
-    if let Err(message) = validate_batch_sync_request(&req) {
-        return (StatusCode::BAD_REQUEST, message).into_response();
+    for receipt in &mut req.receipts {
+        receipt.key = state.storage.normalize_key(&receipt.key);
     }
+    let (valid, mut failures) = partition_batch_sync_request(&user.id, req);
 
-    match db::batch_sync(&state.db, &user.id, req).await {
-        Ok(_) => StatusCode::OK.into_response(),
+    match db::batch_sync(&state.db, &user.id, valid).await {
+        Ok(mut result) => {
+            for (receipt_id, key) in std::mem::take(&mut result.deleted_receipts) {
+                crate::routes::receipts::remove_receipt_object(&state, &user.id, &receipt_id, &key).await;
+            }
+            for (table, id, op) in std::mem::take(&mut result.applied_changes) {
+                state.events.publish(&user.id, &table, &id, &op);
+            }
+            failures.append(&mut result.failures);
+            result.failures = failures;
+            AxumJson(result).into_response()
+        }
         Err(e) => {
             tracing::error!("Batch sync error: {}", e);
             (StatusCode::INTERNAL_SERVER_ERROR, "Database Error").into_response()
@@ -103,6 +289,65 @@ pub async fn batch_sync(
     }
 }
 
+/// The vault credential carried by the caller's own profile entry, if the page has one.
+fn feed_vault_credential<'a>(changes: &'a [SyncChange], user_id: &str) -> Option<&'a str> {
+    changes
+        .iter()
+        .find(|change| change.table == "users" && change.id == user_id)
+        .and_then(|change| change.data.as_ref())
+        .and_then(|data| data["vault_credential_id"].as_str())
+}
+
+/// Nulls a passkey-gated vault credential, as `/api/me` does; it is released only by an unlock.
+fn clear_feed_vault_credential(changes: &mut [SyncChange], user_id: &str) {
+    for change in changes.iter_mut().filter(|change| change.table == "users" && change.id == user_id) {
+        if let Some(data) = change.data.as_mut().and_then(|data| data.as_object_mut()) {
+            data.insert("vault_credential_id".to_string(), serde_json::Value::Null);
+        }
+    }
+}
+
+#[utoipa::path(
+    get,
+    path = "/api/sync/changes",
+    tag = "sync",
+    params(ChangesParams),
+    responses(
+        (status = 200, description = "`{changes: [SyncChange], next_cursor, has_more}`", body = serde_json::Value),
+        (status = 400, description = "Invalid cursor", body = String),
+    )
+)]
+pub async fn list_changes(
+    State(state): State<AppState>,
+    user: AuthenticatedUser,
+    Query(params): Query<ChangesParams>,
+) -> impl IntoResponse {
+    let Some(after_seq) = decode_change_cursor(params.cursor.as_deref()) else {
+        return (StatusCode::BAD_REQUEST, "Invalid cursor").into_response();
+    };
+
+    match db::list_sync_changes(&state.db, &user.id, after_seq, CHANGE_FEED_PAGE_SIZE).await {
+        Ok(mut page) => {
+            let vault_credential_id = feed_vault_credential(&page.changes, &user.id).map(str::to_string);
+            if crate::auth::vault_credential_is_gated(&state.db, &user.id, vault_credential_id.as_deref()).await {
+                clear_feed_vault_credential(&mut page.changes, &user.id);
+            }
+            // With nothing new, hand back the same position so the client can poll from it.
+            let next_seq = page.last_seq.unwrap_or(after_seq);
+            AxumJson(json!({
+                "changes": page.changes,
+                "next_cursor": encode_change_cursor(next_seq),
+                "has_more": page.has_more,
+            }))
+            .into_response()
+        }
+        Err(e) => {
+            tracing::error!("Change feed error: {}", e);
+            (StatusCode::INTERNAL_SERVER_ERROR, "Database Error").into_response()
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -121,6 +366,7 @@ mod tests {
             notes: None,
             is_encrypted: Some(true),
             encrypted_payload: Some("payload".to_string()),
+            blind_index: None,
             updated_at: None,
         };
 
@@ -148,9 +394,181 @@ mod tests {
             notes: None,
             is_encrypted: Some(false),
             encrypted_payload: None,
+            blind_index: None,
             updated_at: None,
         };
 
         assert!(validate_donation_sync_item(&item).is_err());
     }
+
+    #[test]
+    fn test_partition_batch_sync_request_reports_invalid_items_individually() {
+        let valid = DonationSyncItem {
+            action: "create".to_string(),
+            id: "good-id".to_string(),
+            date: None,
+            year: None,
+            category: Some("money".to_string()),
+            amount: Some(10.0),
+            charity_id: "char-123".to_string(),
+            notes: None,
+            is_encrypted: Some(false),
+            encrypted_payload: None,
+            blind_index: None,
+            updated_at: None,
+        };
+        let invalid = DonationSyncItem {
+            id: "bad-id".to_string(),
+            amount: Some(-5.0),
+            ..valid.clone()
+        };
+        let receipt = ReceiptSyncItem {
+            action: "attach".to_string(),
+            id: "receipt-id".to_string(),
+            donation_id: "good-id".to_string(),
+            key: "receipts/user/2026/file.png".to_string(),
+            file_name: None,
+            content_type: None,
+            size: None,
+            is_encrypted: None,
+            encrypted_payload: None,
+        };
+
+        let (accepted, failures) = partition_batch_sync_request("user-1", BatchSyncRequest {
+            profile: None,
+            charities: Vec::new(),
+            donations: vec![invalid, valid],
+            receipts: vec![receipt],
+        });
+
+        assert_eq!(accepted.donations.len(), 1);
+        assert_eq!(accepted.donations[0].id, "good-id");
+        assert!(accepted.receipts.is_empty());
+        assert_eq!(failures.len(), 2);
+        assert_eq!(failures[0].id, "bad-id");
+        assert_eq!(failures[0].reason, "Donation amount cannot be negative");
+        assert!(!failures[0].retryable);
+        assert_eq!(failures[1].table, "receipts");
+    }
+
+    #[test]
+    fn test_receipt_sync_create_must_stay_in_the_users_prefix() {
+        let receipt = ReceiptSyncItem {
+            action: "create".to_string(),
+            id: "receipt-id".to_string(),
+            donation_id: "donation-id".to_string(),
+            key: "receipts/user-1/2026/file.png".to_string(),
+            file_name: None,
+            content_type: None,
+            size: None,
+            is_encrypted: None,
+            encrypted_payload: None,
+        };
+        assert!(validate_receipt_sync_item(&receipt, "user-1").is_ok());
+        assert_eq!(
+            validate_receipt_sync_item(&receipt, "user-2"),
+            Err("Receipt key is outside the user's storage")
+        );
+        // Prefix matching is on whole path segments.
+        assert!(validate_receipt_sync_item(&receipt, "user-").is_err());
+
+        let delete = ReceiptSyncItem {
+            action: "delete".to_string(),
+            ..receipt
+        };
+        assert!(validate_receipt_sync_item(&delete, "user-2").is_ok());
+    }
+
+    #[test]
+    fn test_validate_charity_and_profile_sync_items() {
+        let charity = CharitySyncItem {
+            action: "create".to_string(),
+            id: "char-1".to_string(),
+            name: Some("Food Bank".to_string()),
+            ein: None,
+            category: None,
+            status: None,
+            classification: None,
+            nonprofit_type: None,
+            deductibility: None,
+            street: None,
+            city: None,
+            state: None,
+            zip: None,
+            is_encrypted: None,
+            encrypted_payload: None,
+            updated_at: None,
+        };
+        assert!(validate_charity_sync_item(&charity).is_ok());
+
+        let unnamed = CharitySyncItem {
+            name: Some("  ".to_string()),
+            ..charity.clone()
+        };
+        assert!(validate_charity_sync_item(&unnamed).is_err());
+
+        let delete = CharitySyncItem {
+            action: "delete".to_string(),
+            name: None,
+            ..charity
+        };
+        assert!(validate_charity_sync_item(&delete).is_ok());
+
+        let profile = ProfileSyncItem {
+            agi: Some(50_000.0),
+            marginal_tax_rate: Some(0.22),
+            ..Default::default()
+        };
+        assert!(validate_profile_sync_item(&profile).is_ok());
+
+        let bad_rate = ProfileSyncItem {
+            marginal_tax_rate: Some(22.0),
+            ..profile
+        };
+        assert!(validate_profile_sync_item(&bad_rate).is_err());
+    }
+
+    #[test]
+    fn test_change_cursor_round_trips_and_rejects_garbage() {
+        assert_eq!(decode_change_cursor(None), Some(0));
+        assert_eq!(decode_change_cursor(Some("")), Some(0));
+
+        let cursor = encode_change_cursor(42);
+        assert_ne!(cursor, "42");
+        assert_eq!(decode_change_cursor(Some(&cursor)), Some(42));
+
+        assert_eq!(decode_change_cursor(Some("42")), None);
+        assert_eq!(decode_change_cursor(Some("not base64!")), None);
+        let negative = base64::prelude::BASE64_URL_SAFE_NO_PAD.encode("v1:-1");
+        assert_eq!(decode_change_cursor(Some(&negative)), None);
+    }
+
+    #[test]
+    fn test_gated_vault_credential_is_nulled_in_feed_profile() {
+        let mut changes = vec![
+            SyncChange {
+                seq: 1,
+                table: "users".to_string(),
+                id: "user-1".to_string(),
+                op: "upsert".to_string(),
+                data: Some(json!({ "name": "Ada", "vault_credential_id": "cred-1" })),
+            },
+            SyncChange {
+                seq: 2,
+                table: "donations".to_string(),
+                id: "donation-1".to_string(),
+                op: "upsert".to_string(),
+                data: Some(json!({ "vault_credential_id": "not-a-profile" })),
+            },
+        ];
+        assert_eq!(feed_vault_credential(&changes, "user-1"), Some("cred-1"));
+        assert_eq!(feed_vault_credential(&changes, "user-2"), None);
+
+        clear_feed_vault_credential(&mut changes, "user-1");
+        let profile = changes[0].data.as_ref().unwrap();
+        assert!(profile["vault_credential_id"].is_null());
+        assert_eq!(profile["name"], "Ada");
+        assert_eq!(changes[1].data.as_ref().unwrap()["vault_credential_id"], "not-a-profile");
+        assert_eq!(feed_vault_credential(&changes, "user-1"), None);
+    }
 }
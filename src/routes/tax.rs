@@ -6,20 +6,21 @@ use axum::{
 };
 use serde::{Deserialize, Serialize};
 
-#[derive(Deserialize)]
+#[derive(Deserialize, utoipa::IntoParams)]
+#[into_params(parameter_in = Query)]
 pub struct MarginalRateQuery {
     pub filing_status: Option<String>,
     pub agi: Option<f64>,
 }
 
-#[derive(Serialize, Clone, Copy)]
+#[derive(Serialize, Clone, Copy, utoipa::ToSchema)]
 pub struct TaxBracket {
     pub rate: f64,
     pub min: f64,
     pub max: Option<f64>,
 }
 
-#[derive(Serialize)]
+#[derive(Serialize, utoipa::ToSchema)]
 pub struct MarginalRateResponse {
     pub filing_status: String,
     pub agi: Option<f64>,
@@ -209,6 +210,13 @@ fn marginal_rate_from_brackets(brackets: &[TaxBracket], agi: f64) -> Option<f64>
     brackets.last().map(|b| b.rate)
 }
 
+#[utoipa::path(
+    get,
+    path = "/api/tax/marginal-rate",
+    tag = "tax",
+    params(MarginalRateQuery),
+    responses((status = 200, description = "Federal brackets and the rate for the given AGI", body = MarginalRateResponse))
+)]
 pub async fn marginal_rate(
     _user: AuthenticatedUser,
     Query(query): Query<MarginalRateQuery>,
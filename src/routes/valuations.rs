@@ -6,11 +6,18 @@ use axum::{
 };
 use serde::Deserialize;
 
-#[derive(Deserialize)]
+#[derive(Deserialize, utoipa::ToSchema)]
 pub struct ValRequest {
     pub query: String,
 }
 
+#[utoipa::path(
+    post,
+    path = "/api/valuations/suggest",
+    tag = "valuations",
+    request_body = ValRequest,
+    responses((status = 200, description = "`{suggestions: [{name, min, max}]}`", body = serde_json::Value))
+)]
 pub async fn suggest(
     State(state): State<AppState>,
     Json(req): Json<ValRequest>,
@@ -30,6 +37,12 @@ pub async fn suggest(
     }
 }
 
+#[utoipa::path(
+    post,
+    path = "/api/valuations/seed",
+    tag = "valuations",
+    responses((status = 200, description = "Seeded", body = String))
+)]
 pub async fn seed(State(state): State<AppState>) -> impl IntoResponse {
     match crate::db::valuations::seed_valuations(&state.db).await {
         Ok(_) => (axum::http::StatusCode::OK, "seeded").into_response(),
@@ -40,6 +53,12 @@ pub async fn seed(State(state): State<AppState>) -> impl IntoResponse {
     }
 }
 
+#[utoipa::path(
+    get,
+    path = "/api/valuations/tree",
+    tag = "valuations",
+    responses((status = 200, description = "Valuation categories with their items", body = serde_json::Value))
+)]
 pub async fn tree(State(state): State<AppState>) -> impl IntoResponse {
     match crate::db::valuations::list_valuation_tree(&state.db).await {
         Ok(t) => AxumJson(t).into_response(),
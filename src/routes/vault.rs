@@ -0,0 +1,343 @@
+use crate::auth::AuthenticatedUser;
+use crate::vault_envelope::{self, VaultKey};
+use crate::AppState;
+use axum::{
+    extract::{Json, State},
+    http::{HeaderMap, StatusCode},
+    response::{IntoResponse, Json as AxumJson},
+};
+use serde::Deserialize;
+use serde_json::json;
+
+use crate::db;
+use crate::db::models::{NewVaultKeyRotation, VaultKeyRotation, VaultRotationItem};
+
+const DEFAULT_BATCH_SIZE: i64 = 25;
+const MAX_BATCH_SIZE: i64 = 200;
+const MAX_CREDENTIAL_ID_LEN: usize = 512;
+
+#[derive(Deserialize, Default, utoipa::ToSchema)]
+pub struct RotateVaultRequest {
+    /// Passkey the new key is derived from. It becomes the vault credential once
+    /// every item is re-encrypted; only read when a run starts.
+    pub vault_credential_id: Option<String>,
+    /// Items to re-encrypt in this call: 25 by default, at most 200.
+    pub batch_size: Option<i64>,
+}
+
+/// Why an item could not be rotated. Only `Undecryptable` fails the run; the
+/// others leave it running so the same call can be retried.
+enum RotationError {
+    Undecryptable(String),
+    Unavailable(StatusCode, &'static str),
+}
+
+fn vault_key_header(headers: &HeaderMap, name: &str) -> Result<VaultKey, (StatusCode, &'static str)> {
+    let value = headers
+        .get(name)
+        .and_then(|v| v.to_str().ok())
+        .ok_or((StatusCode::BAD_REQUEST, "X-Vault-Key and X-New-Vault-Key are required"))?;
+    VaultKey::from_base64(value).map_err(|_| (StatusCode::BAD_REQUEST, "Vault keys must be 32 bytes, base64-encoded"))
+}
+
+/// The run these keys continue, or a new one. A failed run with the same keys
+/// is resumed; a running one with other keys blocks a new run.
+async fn current_run(
+    state: &AppState,
+    user: &AuthenticatedUser,
+    old_key: &VaultKey,
+    new_key: &VaultKey,
+    req: &RotateVaultRequest,
+) -> Result<VaultKeyRotation, (StatusCode, &'static str)> {
+    let latest = db::vault_rotations::latest_rotation(&state.db, &user.id).await.map_err(|e| {
+        tracing::error!("Failed to load vault key rotation: {}", e);
+        (StatusCode::INTERNAL_SERVER_ERROR, "Database Error")
+    })?;
+    if let Some(run) = latest.filter(|run| run.status != "completed") {
+        let same_keys = run.old_key_id == old_key.id() && run.new_key_id == new_key.id();
+        if same_keys && run.status == "failed" {
+            return db::vault_rotations::resume_rotation(&state.db, &run.id).await.map_err(|e| {
+                tracing::error!("Failed to resume vault key rotation: {}", e);
+                (StatusCode::INTERNAL_SERVER_ERROR, "Database Error")
+            });
+        }
+        if same_keys {
+            return Ok(run);
+        }
+        if run.status == "running" {
+            return Err((StatusCode::CONFLICT, "A rotation to a different key is in progress"));
+        }
+    }
+
+    let new_credential_id = req
+        .vault_credential_id
+        .as_deref()
+        .map(str::trim)
+        .filter(|id| !id.is_empty())
+        .map(str::to_string);
+    if new_credential_id.as_ref().is_some_and(|id| id.len() > MAX_CREDENTIAL_ID_LEN) {
+        return Err((StatusCode::BAD_REQUEST, "Invalid vault credential id"));
+    }
+    let rotated_prefix = vault_envelope::payload_prefix(&new_key.id());
+    let total_items = db::vault_rotations::count_pending_items(&state.db, &user.id, &rotated_prefix)
+        .await
+        .map_err(|e| {
+            tracing::error!("Failed to count vault items: {}", e);
+            (StatusCode::INTERNAL_SERVER_ERROR, "Database Error")
+        })?;
+    let input = NewVaultKeyRotation {
+        id: uuid::Uuid::new_v4().to_string(),
+        user_id: user.id.clone(),
+        old_key_id: old_key.id(),
+        new_key_id: new_key.id(),
+        new_credential_id,
+        total_items,
+    };
+    let run = db::vault_rotations::create_rotation(&state.db, &input).await.map_err(|e| {
+        tracing::error!("Failed to start vault key rotation: {}", e);
+        (StatusCode::INTERNAL_SERVER_ERROR, "Database Error")
+    })?;
+    audit_rotation(state, &user.id, "vault_key_rotation_started", &run).await;
+    Ok(run)
+}
+
+async fn audit_rotation(state: &AppState, user_id: &str, action: &str, run: &VaultKeyRotation) {
+    let details = json!({
+        "old_key_id": run.old_key_id,
+        "new_key_id": run.new_key_id,
+        "total_items": run.total_items,
+        "processed_items": run.processed_items,
+        "failed_table": run.failed_table,
+        "failed_record_id": run.failed_record_id,
+    })
+    .to_string();
+    if let Err(e) = db::audit::log_audit(
+        &state.db,
+        &uuid::Uuid::new_v4().to_string(),
+        user_id,
+        action,
+        "vault_key_rotations",
+        &Some(run.id.clone()),
+        &Some(details),
+    )
+    .await
+    {
+        tracing::error!("Failed to audit vault key rotation: {}", e);
+    }
+}
+
+async fn download_object(state: &AppState, key: &str) -> Result<Option<Vec<u8>>, RotationError> {
+    state.storage.get_bytes(key).await.map_err(|e| {
+        tracing::error!("Receipt download for {} failed: {}", key, e);
+        RotationError::Unavailable(StatusCode::SERVICE_UNAVAILABLE, "Storage Error")
+    })
+}
+
+async fn upload_object(state: &AppState, key: &str, bytes: Vec<u8>) -> Result<(), RotationError> {
+    state
+        .storage
+        .put(key, bytes.into(), Some("application/octet-stream"))
+        .await
+        .map_err(|e| {
+            tracing::error!("Receipt upload for {} failed: {}", key, e);
+            RotationError::Unavailable(StatusCode::SERVICE_UNAVAILABLE, "Storage Error")
+        })
+}
+
+/// Re-encrypts a receipt's stored object unless it is already under the new
+/// key, then records the object's new content hash. The object goes before the
+/// row, so a row under the new key always has its object there too.
+async fn rotate_object(
+    state: &AppState,
+    user_id: &str,
+    key: &str,
+    old_key: &VaultKey,
+    new_key: &VaultKey,
+) -> Result<(), RotationError> {
+    let key = state.storage.normalize_key(key);
+    if !key.starts_with(&crate::storage::user_receipt_prefix(user_id)) {
+        return Err(RotationError::Undecryptable("receipt object is outside the user's storage".to_string()));
+    }
+    let Some(stored) = download_object(state, &key).await? else {
+        tracing::warn!("Receipt object {} is missing; rotating its metadata only", key);
+        return Ok(());
+    };
+    let already_rotated = vault_envelope::object_key_id(&stored).as_deref() == Some(new_key.id().as_str())
+        && vault_envelope::decrypt_object(new_key, &stored).is_ok();
+    let sealed = if already_rotated {
+        None
+    } else {
+        let plaintext = vault_envelope::decrypt_object(old_key, &stored)
+            .map_err(|e| RotationError::Undecryptable(e.to_string()))?;
+        let sealed = vault_envelope::encrypt_object(new_key, &plaintext)
+            .map_err(|e| RotationError::Undecryptable(e.to_string()))?;
+        Some(sealed)
+    };
+    let mut hasher = crate::storage::ContentHasher::default();
+    hasher.update(sealed.as_deref().unwrap_or(&stored));
+    if let Some(sealed) = sealed {
+        upload_object(state, &key, sealed).await?;
+    }
+    // Also on the already-rotated path, in case an earlier attempt stopped after the upload.
+    db::receipts::set_content_hash_for_key(&state.db, &key, &hasher.finish())
+        .await
+        .map_err(|e| {
+            tracing::error!("Failed to record content hash for {}: {}", key, e);
+            RotationError::Unavailable(StatusCode::INTERNAL_SERVER_ERROR, "Database Error")
+        })
+}
+
+/// Re-encrypts one item; false when the client rewrote it in the meantime, in
+/// which case it is picked up again by a later batch.
+async fn rotate_item(
+    state: &AppState,
+    user_id: &str,
+    item: &VaultRotationItem,
+    old_key: &VaultKey,
+    new_key: &VaultKey,
+) -> Result<bool, RotationError> {
+    let plaintext = vault_envelope::decrypt_payload(old_key, &item.payload)
+        .map_err(|e| RotationError::Undecryptable(e.to_string()))?;
+    if let Some(object_key) = item.object_key.as_deref() {
+        rotate_object(state, user_id, object_key, old_key, new_key).await?;
+    }
+    let payload = vault_envelope::encrypt_payload(new_key, &plaintext)
+        .map_err(|e| RotationError::Undecryptable(e.to_string()))?;
+    db::vault_rotations::replace_item_payload(&state.db, user_id, item, &payload)
+        .await
+        .map_err(|e| {
+            tracing::error!("Failed to store re-encrypted {} {}: {}", item.table, item.id, e);
+            RotationError::Unavailable(StatusCode::INTERNAL_SERVER_ERROR, "Database Error")
+        })
+}
+
+#[utoipa::path(
+    get,
+    path = "/api/me/vault/rotation",
+    tag = "vault",
+    responses((status = 200, description = "`{rotation: VaultKeyRotation | null}`, the latest key rotation", body = serde_json::Value))
+)]
+pub async fn rotation_status(
+    State(state): State<AppState>,
+    user: AuthenticatedUser,
+) -> impl IntoResponse {
+    match db::vault_rotations::latest_rotation(&state.db, &user.id).await {
+        Ok(rotation) => AxumJson(json!({ "rotation": rotation })).into_response(),
+        Err(e) => {
+            tracing::error!("Failed to load vault key rotation: {}", e);
+            (StatusCode::INTERNAL_SERVER_ERROR, "Database Error").into_response()
+        }
+    }
+}
+
+/// Re-encrypts the next batch of the vault from the key in `X-Vault-Key` to the
+/// one in `X-New-Vault-Key`, both base64 of the raw 32 bytes. Call until the
+/// status is `completed`; items already under the new key are skipped, so an
+/// interrupted run resumes where it stopped.
+#[utoipa::path(
+    post,
+    path = "/api/me/vault/rotation",
+    tag = "vault",
+    request_body = RotateVaultRequest,
+    params(
+        ("X-Vault-Key" = String, Header, description = "Current vault key"),
+        ("X-New-Vault-Key" = String, Header, description = "Key to re-encrypt to"),
+    ),
+    responses(
+        (status = 200, description = "Progress after this batch; `completed` once nothing is left under another key", body = crate::db::models::VaultKeyRotation),
+        (status = 400, description = "Missing or malformed keys", body = String),
+        (status = 409, description = "A rotation to a different key is in progress", body = String),
+        (status = 422, description = "An item could not be decrypted with the current key; the run is `failed` and names it", body = crate::db::models::VaultKeyRotation),
+    )
+)]
+pub async fn rotate_vault(
+    State(state): State<AppState>,
+    user: AuthenticatedUser,
+    headers: HeaderMap,
+    body: Option<Json<RotateVaultRequest>>,
+) -> impl IntoResponse {
+    let req = body.map(|Json(req)| req).unwrap_or_default();
+    let keys = vault_key_header(&headers, "x-vault-key")
+        .and_then(|old| vault_key_header(&headers, "x-new-vault-key").map(|new| (old, new)));
+    let (old_key, new_key) = match keys {
+        Ok(keys) => keys,
+        Err(rejection) => return rejection.into_response(),
+    };
+    if old_key.id() == new_key.id() {
+        return (StatusCode::BAD_REQUEST, "The new key must differ from the current key").into_response();
+    }
+    let run = match current_run(&state, &user, &old_key, &new_key, &req).await {
+        Ok(run) => run,
+        Err(rejection) => return rejection.into_response(),
+    };
+
+    let batch_size = req.batch_size.unwrap_or(DEFAULT_BATCH_SIZE).clamp(1, MAX_BATCH_SIZE);
+    let rotated_prefix = vault_envelope::payload_prefix(&new_key.id());
+    let items = match db::vault_rotations::pending_items(&state.db, &user.id, &rotated_prefix, batch_size).await {
+        Ok(items) => items,
+        Err(e) => {
+            tracing::error!("Failed to load vault items: {}", e);
+            return (StatusCode::INTERNAL_SERVER_ERROR, "Database Error").into_response();
+        }
+    };
+
+    let mut processed = 0;
+    let mut outcome = Ok(());
+    for item in &items {
+        match rotate_item(&state, &user.id, item, &old_key, &new_key).await {
+            Ok(replaced) => processed += i64::from(replaced),
+            Err(e) => {
+                outcome = Err((item, e));
+                break;
+            }
+        }
+    }
+
+    let run = match db::vault_rotations::record_progress(&state.db, &run.id, processed).await {
+        Ok(run) => run,
+        Err(e) => {
+            tracing::error!("Failed to record vault key rotation progress: {}", e);
+            return (StatusCode::INTERNAL_SERVER_ERROR, "Database Error").into_response();
+        }
+    };
+    match outcome {
+        Err((_, RotationError::Unavailable(status, msg))) => return (status, msg).into_response(),
+        Err((item, RotationError::Undecryptable(error))) => {
+            tracing::warn!("Vault key rotation {} stopped at {} {}: {}", run.id, item.table, item.id, error);
+            return match db::vault_rotations::fail_rotation(&state.db, &run.id, &item.table, &item.id, &error).await {
+                Ok(run) => {
+                    audit_rotation(&state, &user.id, "vault_key_rotation_failed", &run).await;
+                    (StatusCode::UNPROCESSABLE_ENTITY, AxumJson(run)).into_response()
+                }
+                Err(e) => {
+                    tracing::error!("Failed to record vault key rotation failure: {}", e);
+                    (StatusCode::INTERNAL_SERVER_ERROR, "Database Error").into_response()
+                }
+            };
+        }
+        Ok(()) => {}
+    }
+
+    if (items.len() as i64) == batch_size {
+        return AxumJson(run).into_response();
+    }
+    match db::vault_rotations::count_pending_items(&state.db, &user.id, &rotated_prefix).await {
+        Ok(0) => {}
+        Ok(_) => return AxumJson(run).into_response(),
+        Err(e) => {
+            tracing::error!("Failed to count vault items: {}", e);
+            return (StatusCode::INTERNAL_SERVER_ERROR, "Database Error").into_response();
+        }
+    }
+    match db::vault_rotations::complete_rotation(&state.db, &user.id, &run.id).await {
+        Ok(run) => {
+            audit_rotation(&state, &user.id, "vault_key_rotation_completed", &run).await;
+            state.events.publish(&user.id, "users", &user.id, "upsert");
+            AxumJson(run).into_response()
+        }
+        Err(e) => {
+            tracing::error!("Failed to complete vault key rotation: {}", e);
+            (StatusCode::INTERNAL_SERVER_ERROR, "Database Error").into_response()
+        }
+    }
+}
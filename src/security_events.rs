@@ -0,0 +1,172 @@
+// Security Event Tokens (RFC 8417) as delivered by Google's Cross-Account
+// Protection (RISC): signature and claim verification, and what each event
+// asks us to do about the affected account.
+
+use anyhow::{anyhow, Context};
+use chrono::Utc;
+use jsonwebtoken::{Algorithm, DecodingKey, Validation};
+use serde::Deserialize;
+use serde_json::Value;
+
+pub(crate) const GOOGLE_RISC_JWKS_URI: &str = "https://www.googleapis.com/oauth2/v3/certs";
+/// Google documents the trailing slash; older deliveries omit it.
+const GOOGLE_RISC_ISSUERS: [&str; 2] = ["https://accounts.google.com/", "https://accounts.google.com"];
+
+/// Events older than this are rejected, which bounds how long jtis must be kept for replay protection.
+pub(crate) const MAX_EVENT_AGE_SECONDS: i64 = 7 * 24 * 3600;
+const CLOCK_SKEW_SECONDS: i64 = 300;
+
+pub(crate) const EVENT_VERIFICATION: &str = "https://schemas.openid.net/secevent/risc/event-type/verification";
+pub(crate) const EVENT_SESSIONS_REVOKED: &str = "https://schemas.openid.net/secevent/risc/event-type/sessions-revoked";
+pub(crate) const EVENT_TOKENS_REVOKED: &str = "https://schemas.openid.net/secevent/oauth/event-type/tokens-revoked";
+pub(crate) const EVENT_TOKEN_REVOKED: &str = "https://schemas.openid.net/secevent/oauth/event-type/token-revoked";
+pub(crate) const EVENT_ACCOUNT_DISABLED: &str = "https://schemas.openid.net/secevent/risc/event-type/account-disabled";
+pub(crate) const EVENT_ACCOUNT_ENABLED: &str = "https://schemas.openid.net/secevent/risc/event-type/account-enabled";
+pub(crate) const EVENT_CREDENTIAL_CHANGE_REQUIRED: &str =
+    "https://schemas.openid.net/secevent/risc/event-type/account-credential-change-required";
+/// Pre-standard RISC draft URIs that older deliveries still use.
+const LEGACY_EVENT_VERIFICATION: &str = "http://schemas.openid.net/event/risc/v1/verification";
+const LEGACY_EVENT_TOKEN_REVOKED: &str = "http://schemas.openid.net/event/risc/v1/token-revoked";
+const LEGACY_EVENT_ACCOUNT_DISABLED: &str = "http://schemas.openid.net/event/risc/v1/account-disabled";
+
+/// Where to fetch signing keys and which `iss`/`aud` values to accept.
+#[derive(Debug, Clone)]
+pub(crate) struct SecurityEventConfig {
+    pub jwks_uri: String,
+    pub issuers: Vec<String>,
+    pub audiences: Vec<String>,
+}
+
+impl SecurityEventConfig {
+    /// Google RISC settings. Audiences are our OAuth client ids (`GOOGLE_CLIENT_ID`,
+    /// comma-separated when several clients share the receiver).
+    pub(crate) fn google_from_env() -> anyhow::Result<Self> {
+        let audiences: Vec<String> = std::env::var("GOOGLE_CLIENT_ID")
+            .unwrap_or_default()
+            .split(',')
+            .map(|s| s.trim().to_string())
+            .filter(|s| !s.is_empty())
+            .collect();
+        if audiences.is_empty() {
+            return Err(anyhow!("GOOGLE_CLIENT_ID is required to verify security events"));
+        }
+        Ok(SecurityEventConfig {
+            jwks_uri: GOOGLE_RISC_JWKS_URI.to_string(),
+            issuers: GOOGLE_RISC_ISSUERS.iter().map(|s| s.to_string()).collect(),
+            audiences,
+        })
+    }
+}
+
+#[derive(Deserialize)]
+struct SecurityEventClaims {
+    iss: String,
+    iat: i64,
+    jti: String,
+    sub: Option<String>,
+    events: serde_json::Map<String, Value>,
+}
+
+/// A verified event token. Each entry in `events` keeps its type URI and body.
+#[derive(Debug, Clone)]
+pub(crate) struct SecurityEvent {
+    pub jti: String,
+    pub issuer: String,
+    pub issued_at: i64,
+    subject: Option<String>,
+    pub events: Vec<(String, Value)>,
+}
+
+impl SecurityEvent {
+    /// The provider account an event is about: the event's own `subject`
+    /// (`iss-sub` or `id_token_claims` format) or the token-level `sub`.
+    pub(crate) fn subject_for(&self, event: &Value) -> Option<String> {
+        event
+            .get("subject")
+            .and_then(|s| s.get("sub").or_else(|| s.get("id_token_claims").and_then(|c| c.get("sub"))))
+            .and_then(Value::as_str)
+            .map(str::to_string)
+            .or_else(|| self.subject.clone())
+    }
+}
+
+/// What an event asks us to do to the matching account.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) enum SecurityAction {
+    Verification,
+    RevokeSessions,
+    RevokeSessionsAndLock,
+    Unlock,
+    Ignore,
+}
+
+/// `lock_accounts` opts in to locking when the provider disables an account;
+/// sessions are revoked either way.
+pub(crate) fn action_for(event_type: &str, lock_accounts: bool) -> SecurityAction {
+    match event_type {
+        EVENT_VERIFICATION | LEGACY_EVENT_VERIFICATION => SecurityAction::Verification,
+        EVENT_SESSIONS_REVOKED
+        | EVENT_TOKENS_REVOKED
+        | EVENT_TOKEN_REVOKED
+        | EVENT_CREDENTIAL_CHANGE_REQUIRED
+        | LEGACY_EVENT_TOKEN_REVOKED => SecurityAction::RevokeSessions,
+        EVENT_ACCOUNT_DISABLED | LEGACY_EVENT_ACCOUNT_DISABLED if lock_accounts => SecurityAction::RevokeSessionsAndLock,
+        EVENT_ACCOUNT_DISABLED | LEGACY_EVENT_ACCOUNT_DISABLED => SecurityAction::RevokeSessions,
+        // Google re-enables accounts after a hijacking is resolved; lifting our
+        // lock then is only right if the lock came from a security event.
+        EVENT_ACCOUNT_ENABLED if lock_accounts => SecurityAction::Unlock,
+        _ => SecurityAction::Ignore,
+    }
+}
+
+/// Short reason string for audit records, e.g. `hijacking` or `bulk-account`.
+pub(crate) fn event_reason(event: &Value) -> Option<String> {
+    event.get("reason").and_then(Value::as_str).map(str::to_string)
+}
+
+pub(crate) async fn verify_security_event(
+    client: &reqwest::Client,
+    config: &SecurityEventConfig,
+    token: &str,
+) -> anyhow::Result<SecurityEvent> {
+    let header = jsonwebtoken::decode_header(token).context("security event header is invalid")?;
+    if matches!(header.alg, Algorithm::HS256 | Algorithm::HS384 | Algorithm::HS512) {
+        return Err(anyhow!("security events must be signed with an asymmetric algorithm"));
+    }
+    let kid = header.kid.as_deref().ok_or_else(|| anyhow!("security event has no kid"))?;
+    let jwk = crate::oidc::signing_key(client, &config.jwks_uri, Some(kid)).await?;
+    let decoding_key = DecodingKey::from_jwk(&jwk).context("security event signing key is unusable")?;
+
+    let mut validation = Validation::new(header.alg);
+    // SETs carry no exp (RFC 8417 §2.2); freshness is enforced on iat below.
+    validation.validate_exp = false;
+    validation.set_required_spec_claims(&["iss", "aud"]);
+    validation.set_issuer(&config.issuers);
+    validation.set_audience(&config.audiences);
+
+    let claims = jsonwebtoken::decode::<SecurityEventClaims>(token, &decoding_key, &validation)
+        .context("security event verification failed")?
+        .claims;
+
+    let now = Utc::now().timestamp();
+    if claims.iat > now + CLOCK_SKEW_SECONDS {
+        return Err(anyhow!("security event is issued in the future"));
+    }
+    if claims.iat < now - MAX_EVENT_AGE_SECONDS {
+        return Err(anyhow!("security event is too old"));
+    }
+    if claims.jti.trim().is_empty() || claims.jti.len() > 255 {
+        return Err(anyhow!("security event jti is missing or too long"));
+    }
+    if claims.events.is_empty() {
+        return Err(anyhow!("security event has no events"));
+    }
+
+    Ok(SecurityEvent {
+        jti: claims.jti,
+        issuer: claims.iss,
+        issued_at: claims.iat,
+        subject: claims.sub,
+        events: claims.events.into_iter().collect(),
+    })
+}
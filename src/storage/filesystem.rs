@@ -0,0 +1,227 @@
+// Local filesystem backend: each object is a file under a root directory, at
+// the path its key names. Writes go to a hidden temporary file that is renamed
+// into place, so readers never see a partial object. Content types are not
+// stored; they are inferred from the key's extension.
+
+use anyhow::{anyhow, Context, Result};
+use async_trait::async_trait;
+use axum::body::Bytes;
+use chrono::{DateTime, Utc};
+use futures::StreamExt;
+use std::io::{ErrorKind, SeekFrom};
+use std::ops::Range;
+use std::path::{Path, PathBuf};
+use tokio::io::{AsyncReadExt, AsyncSeekExt, AsyncWriteExt};
+
+use super::{require_key, ByteStream, LocalUrlSigner, ObjectMeta, ObjectStore};
+
+pub struct FsStore {
+    root: PathBuf,
+    bucket_name: String,
+    signer: LocalUrlSigner,
+}
+
+impl FsStore {
+    /// Creates `root` if it does not exist yet.
+    pub fn new(root: impl Into<PathBuf>, bucket_name: &str, signer: LocalUrlSigner) -> Result<Self> {
+        let root = root.into();
+        std::fs::create_dir_all(&root).with_context(|| format!("creating storage directory {}", root.display()))?;
+        Ok(Self {
+            root,
+            bucket_name: bucket_name.to_string(),
+            signer,
+        })
+    }
+
+    /// The key and its file. Empty and hidden path segments are refused, which
+    /// keeps keys inside the root and away from in-flight temporary files.
+    fn object_path(&self, key: &str) -> Result<(String, PathBuf)> {
+        let key = require_key(&self.bucket_name, key)?;
+        let mut path = self.root.clone();
+        for segment in key.split('/') {
+            if segment.is_empty() || segment.starts_with('.') || segment.contains('\\') {
+                return Err(anyhow!("invalid storage key {:?}", key));
+            }
+            path.push(segment);
+        }
+        Ok((key, path))
+    }
+
+    async fn meta(&self, key: String, path: &Path) -> Result<Option<ObjectMeta>> {
+        match tokio::fs::metadata(path).await {
+            Ok(metadata) if metadata.is_file() => Ok(Some(ObjectMeta {
+                size: metadata.len(),
+                content_type: content_type_for_key(&key).map(str::to_string),
+                last_modified: metadata.modified().ok().map(DateTime::<Utc>::from),
+                key,
+            })),
+            Ok(_) => Ok(None),
+            Err(e) if e.kind() == ErrorKind::NotFound => Ok(None),
+            Err(e) => Err(e).with_context(|| format!("inspecting {}", key)),
+        }
+    }
+}
+
+#[async_trait]
+impl ObjectStore for FsStore {
+    fn bucket(&self) -> &str {
+        &self.bucket_name
+    }
+
+    async fn put(&self, key: &str, body: Bytes, _content_type: Option<&str>) -> Result<()> {
+        let (key, path) = self.object_path(key)?;
+        let parent = path.parent().ok_or_else(|| anyhow!("invalid storage key {:?}", key))?;
+        tokio::fs::create_dir_all(parent)
+            .await
+            .with_context(|| format!("creating directory for {}", key))?;
+        let temp = parent.join(format!(".upload-{}", uuid::Uuid::new_v4()));
+        if let Err(e) = tokio::fs::write(&temp, &body).await {
+            let _ = tokio::fs::remove_file(&temp).await;
+            return Err(e).with_context(|| format!("writing {}", key));
+        }
+        tokio::fs::rename(&temp, &path)
+            .await
+            .with_context(|| format!("moving {} into place", key))
+    }
+
+    async fn put_stream(&self, key: &str, mut body: ByteStream, _content_type: Option<&str>) -> Result<()> {
+        let (key, path) = self.object_path(key)?;
+        let parent = path.parent().ok_or_else(|| anyhow!("invalid storage key {:?}", key))?;
+        tokio::fs::create_dir_all(parent)
+            .await
+            .with_context(|| format!("creating directory for {}", key))?;
+        let temp = parent.join(format!(".upload-{}", uuid::Uuid::new_v4()));
+        let written: Result<()> = async {
+            let mut file = tokio::fs::File::create(&temp).await?;
+            while let Some(chunk) = body.next().await {
+                file.write_all(&chunk?).await?;
+            }
+            file.flush().await?;
+            Ok(())
+        }
+        .await;
+        if let Err(e) = written {
+            let _ = tokio::fs::remove_file(&temp).await;
+            return Err(e).with_context(|| format!("writing {}", key));
+        }
+        tokio::fs::rename(&temp, &path)
+            .await
+            .with_context(|| format!("moving {} into place", key))
+    }
+
+    async fn get(&self, key: &str) -> Result<Option<ByteStream>> {
+        let (key, path) = self.object_path(key)?;
+        match tokio::fs::File::open(&path).await {
+            Ok(file) => Ok(Some(tokio_util::io::ReaderStream::new(file).boxed())),
+            Err(e) if e.kind() == ErrorKind::NotFound => Ok(None),
+            Err(e) => Err(e).with_context(|| format!("opening {}", key)),
+        }
+    }
+
+    async fn head(&self, key: &str) -> Result<Option<ObjectMeta>> {
+        let (key, path) = self.object_path(key)?;
+        self.meta(key, &path).await
+    }
+
+    async fn get_range(&self, key: &str, range: Range<u64>) -> Result<Option<Bytes>> {
+        let (key, path) = self.object_path(key)?;
+        let mut file = match tokio::fs::File::open(&path).await {
+            Ok(file) => file,
+            Err(e) if e.kind() == ErrorKind::NotFound => return Ok(None),
+            Err(e) => return Err(e).with_context(|| format!("opening {}", key)),
+        };
+        file.seek(SeekFrom::Start(range.start))
+            .await
+            .with_context(|| format!("seeking in {}", key))?;
+        let mut bytes = Vec::new();
+        file.take(range.end.saturating_sub(range.start))
+            .read_to_end(&mut bytes)
+            .await
+            .with_context(|| format!("reading {}", key))?;
+        Ok(Some(Bytes::from(bytes)))
+    }
+
+    async fn delete(&self, key: &str) -> Result<()> {
+        let (key, path) = self.object_path(key)?;
+        match tokio::fs::remove_file(&path).await {
+            Ok(()) => Ok(()),
+            Err(e) if e.kind() == ErrorKind::NotFound => Ok(()),
+            Err(e) => Err(e).with_context(|| format!("deleting {}", key)),
+        }
+    }
+
+    async fn list(&self, prefix: &str) -> Result<Vec<ObjectMeta>> {
+        let prefix = prefix.trim_start_matches('/');
+        // Only walk the deepest directory the prefix names in full.
+        let start_dir = match prefix.rfind('/') {
+            Some(end) => prefix[..end].to_string(),
+            None => String::new(),
+        };
+        if start_dir.split('/').any(|segment| segment.starts_with('.')) {
+            return Ok(Vec::new());
+        }
+
+        let mut objects = Vec::new();
+        let mut pending = vec![start_dir];
+        while let Some(dir) = pending.pop() {
+            let dir_path = if dir.is_empty() { self.root.clone() } else { self.root.join(&dir) };
+            let mut entries = match tokio::fs::read_dir(&dir_path).await {
+                Ok(entries) => entries,
+                Err(e) if e.kind() == ErrorKind::NotFound => continue,
+                Err(e) => return Err(e).with_context(|| format!("listing {}", dir_path.display())),
+            };
+            while let Some(entry) = entries.next_entry().await? {
+                let Ok(name) = entry.file_name().into_string() else {
+                    continue;
+                };
+                if name.starts_with('.') {
+                    continue;
+                }
+                let key = if dir.is_empty() { name } else { format!("{}/{}", dir, name) };
+                let file_type = entry.file_type().await?;
+                if file_type.is_dir() {
+                    if key.starts_with(prefix) || prefix.starts_with(&format!("{}/", key)) {
+                        pending.push(key);
+                    }
+                } else if file_type.is_file() && key.starts_with(prefix) {
+                    if let Some(meta) = self.meta(key, &entry.path()).await? {
+                        objects.push(meta);
+                    }
+                }
+            }
+        }
+        objects.sort_by(|a, b| a.key.cmp(&b.key));
+        Ok(objects)
+    }
+
+    fn presign(&self, method: &str, key: &str, expires_in_secs: u64) -> Result<String> {
+        let (key, _) = self.object_path(key)?;
+        self.signer.sign_url(method, &key, expires_in_secs)
+    }
+
+    fn local_signer(&self) -> Option<&LocalUrlSigner> {
+        Some(&self.signer)
+    }
+}
+
+/// Content type for the extensions receipts are stored under.
+fn content_type_for_key(key: &str) -> Option<&'static str> {
+    let ext = key.rsplit_once('.')?.1.to_ascii_lowercase();
+    Some(match ext.as_str() {
+        "jpg" | "jpeg" => "image/jpeg",
+        "png" => "image/png",
+        "gif" => "image/gif",
+        "webp" => "image/webp",
+        "avif" => "image/avif",
+        "heic" => "image/heic",
+        "heif" => "image/heif",
+        "bmp" => "image/bmp",
+        "tiff" => "image/tiff",
+        "mp4" => "video/mp4",
+        "mov" => "video/quicktime",
+        "pdf" => "application/pdf",
+        "txt" => "text/plain",
+        "csv" => "text/csv",
+        _ => "application/octet-stream",
+    })
+}
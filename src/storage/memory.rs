@@ -0,0 +1,115 @@
+// In-memory backend for tests and throwaway instances. Objects are lost when
+// the store is dropped. Presigned URLs are app-served like the filesystem
+// backend's, so a server running on it still handles browser uploads.
+
+use anyhow::Result;
+use async_trait::async_trait;
+use axum::body::Bytes;
+use chrono::{DateTime, Utc};
+use futures::StreamExt;
+use std::collections::BTreeMap;
+use std::ops::Range;
+use std::sync::Mutex;
+
+use super::{require_key, ByteStream, LocalUrlSigner, ObjectMeta, ObjectStore};
+
+struct StoredObject {
+    body: Bytes,
+    content_type: Option<String>,
+    last_modified: DateTime<Utc>,
+}
+
+impl StoredObject {
+    fn meta(&self, key: &str) -> ObjectMeta {
+        ObjectMeta {
+            key: key.to_string(),
+            size: self.body.len() as u64,
+            content_type: self.content_type.clone(),
+            last_modified: Some(self.last_modified),
+        }
+    }
+}
+
+pub struct MemoryStore {
+    bucket_name: String,
+    signer: LocalUrlSigner,
+    objects: Mutex<BTreeMap<String, StoredObject>>,
+}
+
+impl MemoryStore {
+    pub fn new(bucket_name: &str, signer: LocalUrlSigner) -> Self {
+        Self {
+            bucket_name: bucket_name.to_string(),
+            signer,
+            objects: Mutex::new(BTreeMap::new()),
+        }
+    }
+
+    fn objects(&self) -> std::sync::MutexGuard<'_, BTreeMap<String, StoredObject>> {
+        self.objects.lock().unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+}
+
+#[async_trait]
+impl ObjectStore for MemoryStore {
+    fn bucket(&self) -> &str {
+        &self.bucket_name
+    }
+
+    async fn put(&self, key: &str, body: Bytes, content_type: Option<&str>) -> Result<()> {
+        let key = require_key(&self.bucket_name, key)?;
+        self.objects().insert(
+            key,
+            StoredObject {
+                body,
+                content_type: content_type.map(str::to_string),
+                last_modified: Utc::now(),
+            },
+        );
+        Ok(())
+    }
+
+    async fn get(&self, key: &str) -> Result<Option<ByteStream>> {
+        let key = require_key(&self.bucket_name, key)?;
+        let body = self.objects().get(&key).map(|object| object.body.clone());
+        Ok(body.map(|body| futures::stream::once(async move { Ok(body) }).boxed()))
+    }
+
+    async fn head(&self, key: &str) -> Result<Option<ObjectMeta>> {
+        let key = require_key(&self.bucket_name, key)?;
+        Ok(self.objects().get(&key).map(|object| object.meta(&key)))
+    }
+
+    async fn get_range(&self, key: &str, range: Range<u64>) -> Result<Option<Bytes>> {
+        let key = require_key(&self.bucket_name, key)?;
+        Ok(self.objects().get(&key).map(|object| {
+            let len = object.body.len() as u64;
+            object.body.slice(range.start.min(len) as usize..range.end.clamp(range.start.min(len), len) as usize)
+        }))
+    }
+
+    async fn delete(&self, key: &str) -> Result<()> {
+        let key = require_key(&self.bucket_name, key)?;
+        self.objects().remove(&key);
+        Ok(())
+    }
+
+    async fn list(&self, prefix: &str) -> Result<Vec<ObjectMeta>> {
+        let prefix = prefix.trim_start_matches('/');
+        Ok(self
+            .objects()
+            .range(prefix.to_string()..)
+            .take_while(|(key, _)| key.starts_with(prefix))
+            .map(|(key, object)| object.meta(key))
+            .collect())
+    }
+
+    fn presign(&self, method: &str, key: &str, expires_in_secs: u64) -> Result<String> {
+        let key = require_key(&self.bucket_name, key)?;
+        self.signer.sign_url(method, &key, expires_in_secs)
+    }
+
+    fn local_signer(&self) -> Option<&LocalUrlSigner> {
+        Some(&self.signer)
+    }
+}
@@ -0,0 +1,482 @@
+// S3-compatible backend (OCI Object Storage, MinIO, AWS) using path-style
+// addressing. Every request, including the app's own, goes through a SigV4
+// presigned URL, so no request signing beyond query-string auth is needed.
+
+use anyhow::{anyhow, Context, Result};
+use async_trait::async_trait;
+use axum::body::Bytes;
+use chrono::{DateTime, Utc};
+use futures::StreamExt;
+use sha2::{Digest, Sha256};
+use std::ops::Range;
+use url::Url;
+
+use super::{
+    check_part_number, check_part_order, encode_path, encode_query_component, hex_encode, hmac_sha256, require_key,
+    ByteStream, ObjectMeta, ObjectStore, PostPolicy, PresignedPost, UploadedPart,
+};
+
+/// Lifetime of the URLs the app signs for its own requests.
+const INTERNAL_URL_EXPIRATION_SECS: u64 = 300;
+
+pub struct S3Store {
+    endpoint: Url,
+    bucket_name: String,
+    region: String,
+    access_key_id: String,
+    secret_access_key: String,
+    client: reqwest::Client,
+}
+
+impl S3Store {
+    pub fn new(
+        endpoint: &str,
+        bucket_name: &str,
+        region: &str,
+        access_key_id: &str,
+        secret_access_key: &str,
+    ) -> Result<Self> {
+        let endpoint = Url::parse(endpoint).context("invalid storage endpoint")?;
+        if endpoint.host_str().is_none() {
+            return Err(anyhow!("storage endpoint is missing a host"));
+        }
+        Ok(Self {
+            endpoint,
+            bucket_name: bucket_name.to_string(),
+            region: region.to_string(),
+            access_key_id: access_key_id.to_string(),
+            secret_access_key: secret_access_key.to_string(),
+            client: reqwest::Client::new(),
+        })
+    }
+
+    /// `<endpoint path>/<bucket>[/<key>]`, without a leading slash.
+    fn bucket_path(&self, key: Option<&str>) -> String {
+        let base_path = self.endpoint.path().trim_matches('/');
+        let bucket_path = if base_path.is_empty() {
+            self.bucket_name.clone()
+        } else {
+            format!("{}/{}", base_path, self.bucket_name)
+        };
+        match key {
+            Some(key) => format!("{}/{}", bucket_path, key),
+            None => bucket_path,
+        }
+    }
+
+    /// Query-string SigV4 URL for `method` on `path`, signing `extra_query` too.
+    fn signed_url(
+        &self,
+        method: &str,
+        path: &str,
+        extra_query: &[(&str, &str)],
+        expires_in_secs: u64,
+    ) -> Result<Url> {
+        let host = self
+            .endpoint
+            .host_str()
+            .ok_or_else(|| anyhow!("storage endpoint is missing a host"))?;
+        let host = match self.endpoint.port() {
+            Some(port) => format!("{}:{}", host, port),
+            None => host.to_string(),
+        };
+        let canonical_uri = format!("/{}", encode_path(path));
+
+        let now = Utc::now();
+        let amz_date = now.format("%Y%m%dT%H%M%SZ").to_string();
+        let date_stamp = now.format("%Y%m%d").to_string();
+        let credential_scope = format!("{}/{}/s3/aws4_request", date_stamp, self.region);
+        let credential = format!("{}/{}", self.access_key_id, credential_scope);
+
+        let mut query_params = vec![
+            (
+                "X-Amz-Algorithm".to_string(),
+                "AWS4-HMAC-SHA256".to_string(),
+            ),
+            ("X-Amz-Credential".to_string(), credential),
+            ("X-Amz-Date".to_string(), amz_date.clone()),
+            ("X-Amz-Expires".to_string(), expires_in_secs.to_string()),
+            ("X-Amz-SignedHeaders".to_string(), "host".to_string()),
+        ];
+        query_params.extend(extra_query.iter().map(|(k, v)| (k.to_string(), v.to_string())));
+        query_params.sort_by(|a, b| a.0.cmp(&b.0).then_with(|| a.1.cmp(&b.1)));
+
+        let canonical_query = query_params
+            .iter()
+            .map(|(k, v)| {
+                format!(
+                    "{}={}",
+                    encode_query_component(k),
+                    encode_query_component(v)
+                )
+            })
+            .collect::<Vec<_>>()
+            .join("&");
+
+        let canonical_headers = format!("host:{}\n", host);
+        let canonical_request = format!(
+            "{}\n{}\n{}\n{}\nhost\nUNSIGNED-PAYLOAD",
+            method, canonical_uri, canonical_query, canonical_headers,
+        );
+        let canonical_request_hash = hex_encode(&sha256_hash(canonical_request.as_bytes()));
+        let string_to_sign = format!(
+            "AWS4-HMAC-SHA256\n{}\n{}\n{}",
+            amz_date, credential_scope, canonical_request_hash,
+        );
+
+        let signing_key = signing_key(
+            &self.secret_access_key,
+            &date_stamp,
+            &self.region,
+            "s3",
+        )?;
+        let signature = hex_encode(&hmac_sha256(&signing_key, string_to_sign.as_bytes())?);
+
+        let mut final_url = self.endpoint.clone();
+        final_url.set_path(path);
+        final_url.set_query(Some(&format!(
+            "{}&X-Amz-Signature={}",
+            canonical_query, signature
+        )));
+        Ok(final_url)
+    }
+
+    fn object_url(&self, method: &str, key: &str) -> Result<(String, Url)> {
+        self.object_url_with_query(method, key, &[])
+    }
+
+    fn object_url_with_query(&self, method: &str, key: &str, query: &[(&str, &str)]) -> Result<(String, Url)> {
+        let key = require_key(&self.bucket_name, key)?;
+        let url = self.signed_url(method, &self.bucket_path(Some(&key)), query, INTERNAL_URL_EXPIRATION_SECS)?;
+        Ok((key, url))
+    }
+}
+
+#[async_trait]
+impl ObjectStore for S3Store {
+    fn bucket(&self) -> &str {
+        &self.bucket_name
+    }
+
+    async fn put(&self, key: &str, body: Bytes, content_type: Option<&str>) -> Result<()> {
+        let (key, url) = self.object_url("PUT", key)?;
+        let resp = self
+            .client
+            .put(url)
+            .header(
+                reqwest::header::CONTENT_TYPE,
+                content_type.unwrap_or("application/octet-stream"),
+            )
+            .body(body)
+            .send()
+            .await
+            .with_context(|| format!("uploading {}", key))?;
+        if !resp.status().is_success() {
+            return Err(anyhow!("storage upload of {} returned {}", key, resp.status()));
+        }
+        Ok(())
+    }
+
+    async fn get(&self, key: &str) -> Result<Option<ByteStream>> {
+        let (key, url) = self.object_url("GET", key)?;
+        let resp = self
+            .client
+            .get(url)
+            .send()
+            .await
+            .with_context(|| format!("downloading {}", key))?;
+        if resp.status() == reqwest::StatusCode::NOT_FOUND {
+            return Ok(None);
+        }
+        if !resp.status().is_success() {
+            return Err(anyhow!("storage download of {} returned {}", key, resp.status()));
+        }
+        Ok(Some(resp.bytes_stream().map(|chunk| chunk.map_err(std::io::Error::other)).boxed()))
+    }
+
+    async fn head(&self, key: &str) -> Result<Option<ObjectMeta>> {
+        let (key, url) = self.object_url("HEAD", key)?;
+        let resp = self
+            .client
+            .head(url)
+            .send()
+            .await
+            .with_context(|| format!("inspecting {}", key))?;
+        if resp.status() == reqwest::StatusCode::NOT_FOUND {
+            return Ok(None);
+        }
+        if !resp.status().is_success() {
+            return Err(anyhow!("storage head of {} returned {}", key, resp.status()));
+        }
+        let header = |name: reqwest::header::HeaderName| {
+            resp.headers().get(name).and_then(|value| value.to_str().ok()).map(str::to_string)
+        };
+        Ok(Some(ObjectMeta {
+            size: header(reqwest::header::CONTENT_LENGTH)
+                .and_then(|value| value.parse().ok())
+                .unwrap_or(0),
+            content_type: header(reqwest::header::CONTENT_TYPE),
+            last_modified: header(reqwest::header::LAST_MODIFIED)
+                .and_then(|value| DateTime::parse_from_rfc2822(&value).ok())
+                .map(|value| value.with_timezone(&Utc)),
+            key,
+        }))
+    }
+
+    async fn get_range(&self, key: &str, range: Range<u64>) -> Result<Option<Bytes>> {
+        if range.end <= range.start {
+            return Ok(self.head(key).await?.map(|_| Bytes::new()));
+        }
+        let (key, url) = self.object_url("GET", key)?;
+        let resp = self
+            .client
+            .get(url)
+            .header(reqwest::header::RANGE, format!("bytes={}-{}", range.start, range.end - 1))
+            .send()
+            .await
+            .with_context(|| format!("downloading part of {}", key))?;
+        match resp.status() {
+            reqwest::StatusCode::NOT_FOUND => return Ok(None),
+            // The range starts past the end of the object.
+            reqwest::StatusCode::RANGE_NOT_SATISFIABLE => return Ok(Some(Bytes::new())),
+            status if !status.is_success() => {
+                return Err(anyhow!("storage download of {} returned {}", key, status));
+            }
+            _ => {}
+        }
+        let full_body = resp.status() != reqwest::StatusCode::PARTIAL_CONTENT;
+        let mut bytes = resp.bytes().await.with_context(|| format!("reading {}", key))?;
+        // A server that ignores Range sends the whole object.
+        if full_body {
+            let len = bytes.len() as u64;
+            bytes = bytes.slice(range.start.min(len) as usize..range.end.min(len) as usize);
+        }
+        Ok(Some(bytes))
+    }
+
+    async fn delete(&self, key: &str) -> Result<()> {
+        let (key, url) = self.object_url("DELETE", key)?;
+        let resp = self
+            .client
+            .delete(url)
+            .send()
+            .await
+            .with_context(|| format!("deleting {}", key))?;
+        if !resp.status().is_success() && resp.status() != reqwest::StatusCode::NOT_FOUND {
+            return Err(anyhow!("storage delete of {} returned {}", key, resp.status()));
+        }
+        Ok(())
+    }
+
+    async fn list(&self, prefix: &str) -> Result<Vec<ObjectMeta>> {
+        let prefix = prefix.trim_start_matches('/');
+        let mut objects = Vec::new();
+        let mut continuation: Option<String> = None;
+        loop {
+            let mut query = vec![("list-type", "2"), ("prefix", prefix)];
+            if let Some(token) = continuation.as_deref() {
+                query.push(("continuation-token", token));
+            }
+            let url = self.signed_url("GET", &self.bucket_path(None), &query, INTERNAL_URL_EXPIRATION_SECS)?;
+            let resp = self.client.get(url).send().await.context("listing objects")?;
+            if !resp.status().is_success() {
+                return Err(anyhow!("storage list of {} returned {}", prefix, resp.status()));
+            }
+            let body = resp.text().await.context("reading object listing")?;
+            let (page, next) = parse_list_response(&body);
+            objects.extend(page);
+            match next {
+                Some(token) => continuation = Some(token),
+                None => break,
+            }
+        }
+        objects.sort_by(|a, b| a.key.cmp(&b.key));
+        Ok(objects)
+    }
+
+    fn presign(&self, method: &str, key: &str, expires_in_secs: u64) -> Result<String> {
+        let key = require_key(&self.bucket_name, key)?;
+        Ok(self
+            .signed_url(method, &self.bucket_path(Some(&key)), &[], expires_in_secs)?
+            .to_string())
+    }
+
+    fn presign_post(&self, policy: &PostPolicy<'_>) -> Result<PresignedPost> {
+        let key = require_key(&self.bucket_name, policy.key)?;
+        let now = Utc::now();
+        let amz_date = now.format("%Y%m%dT%H%M%SZ").to_string();
+        let date_stamp = now.format("%Y%m%d").to_string();
+        let credential = format!("{}/{}/{}/s3/aws4_request", self.access_key_id, date_stamp, self.region);
+        let encoded = PostPolicy { key: &key, ..policy.clone() }.encode(vec![
+            serde_json::json!({ "bucket": self.bucket_name }),
+            serde_json::json!({ "x-amz-algorithm": "AWS4-HMAC-SHA256" }),
+            serde_json::json!({ "x-amz-credential": credential }),
+            serde_json::json!({ "x-amz-date": amz_date }),
+        ])?;
+        // A POST policy is signed as the string to sign itself.
+        let signing_key = signing_key(&self.secret_access_key, &date_stamp, &self.region, "s3")?;
+        let signature = hex_encode(&hmac_sha256(&signing_key, encoded.as_bytes())?);
+
+        let mut url = self.endpoint.clone();
+        url.set_path(&self.bucket_path(None));
+        url.set_query(None);
+        Ok(PresignedPost {
+            url: url.to_string(),
+            fields: vec![
+                ("key".to_string(), key),
+                ("Content-Type".to_string(), policy.content_type.to_string()),
+                ("x-amz-algorithm".to_string(), "AWS4-HMAC-SHA256".to_string()),
+                ("x-amz-credential".to_string(), credential),
+                ("x-amz-date".to_string(), amz_date),
+                ("policy".to_string(), encoded),
+                ("x-amz-signature".to_string(), signature),
+            ],
+        })
+    }
+
+    async fn create_multipart_upload(&self, key: &str, content_type: Option<&str>) -> Result<String> {
+        let (key, url) = self.object_url_with_query("POST", key, &[("uploads", "")])?;
+        let resp = self
+            .client
+            .post(url)
+            .header(
+                reqwest::header::CONTENT_TYPE,
+                content_type.unwrap_or("application/octet-stream"),
+            )
+            .send()
+            .await
+            .with_context(|| format!("starting multipart upload of {}", key))?;
+        if !resp.status().is_success() {
+            return Err(anyhow!("starting multipart upload of {} returned {}", key, resp.status()));
+        }
+        let body = resp.text().await.context("reading multipart upload response")?;
+        xml_text(&body, "UploadId").ok_or_else(|| anyhow!("multipart upload response for {} has no UploadId", key))
+    }
+
+    fn presign_part(&self, key: &str, upload_id: &str, part_number: u32, expires_in_secs: u64) -> Result<String> {
+        check_part_number(part_number)?;
+        let key = require_key(&self.bucket_name, key)?;
+        let part_number = part_number.to_string();
+        Ok(self
+            .signed_url(
+                "PUT",
+                &self.bucket_path(Some(&key)),
+                &[("partNumber", &part_number), ("uploadId", upload_id)],
+                expires_in_secs,
+            )?
+            .to_string())
+    }
+
+    async fn complete_multipart_upload(&self, key: &str, upload_id: &str, parts: &[UploadedPart]) -> Result<()> {
+        check_part_order(parts)?;
+        let (key, url) = self.object_url_with_query("POST", key, &[("uploadId", upload_id)])?;
+        let mut body = String::from("<CompleteMultipartUpload>");
+        for part in parts {
+            body.push_str(&format!(
+                "<Part><PartNumber>{}</PartNumber><ETag>{}</ETag></Part>",
+                part.part_number,
+                xml_escape(&part.etag)
+            ));
+        }
+        body.push_str("</CompleteMultipartUpload>");
+        let resp = self
+            .client
+            .post(url)
+            .header(reqwest::header::CONTENT_TYPE, "application/xml")
+            .body(body)
+            .send()
+            .await
+            .with_context(|| format!("completing multipart upload of {}", key))?;
+        let status = resp.status();
+        let body = resp.text().await.unwrap_or_default();
+        // S3 can report a failed completion in the body of a 200 response.
+        if !status.is_success() || body.contains("<Error>") {
+            let code = xml_text(&body, "Code").unwrap_or_else(|| status.to_string());
+            return Err(anyhow!("completing multipart upload of {} failed: {}", key, code));
+        }
+        Ok(())
+    }
+
+    async fn abort_multipart_upload(&self, key: &str, upload_id: &str) -> Result<()> {
+        let (key, url) = self.object_url_with_query("DELETE", key, &[("uploadId", upload_id)])?;
+        let resp = self
+            .client
+            .delete(url)
+            .send()
+            .await
+            .with_context(|| format!("aborting multipart upload of {}", key))?;
+        if !resp.status().is_success() && resp.status() != reqwest::StatusCode::NOT_FOUND {
+            return Err(anyhow!("aborting multipart upload of {} returned {}", key, resp.status()));
+        }
+        Ok(())
+    }
+}
+
+/// Objects in a ListObjectsV2 response, and the continuation token if it was truncated.
+fn parse_list_response(xml: &str) -> (Vec<ObjectMeta>, Option<String>) {
+    let objects = xml
+        .split("<Contents>")
+        .skip(1)
+        .filter_map(|entry| {
+            let entry = entry.split("</Contents>").next().unwrap_or(entry);
+            Some(ObjectMeta {
+                key: xml_text(entry, "Key")?,
+                size: xml_text(entry, "Size").and_then(|size| size.parse().ok()).unwrap_or(0),
+                content_type: None,
+                last_modified: xml_text(entry, "LastModified")
+                    .and_then(|value| DateTime::parse_from_rfc3339(&value).ok())
+                    .map(|value| value.with_timezone(&Utc)),
+            })
+        })
+        .collect();
+    let next = if xml_text(xml, "IsTruncated").as_deref() == Some("true") {
+        xml_text(xml, "NextContinuationToken")
+    } else {
+        None
+    };
+    (objects, next)
+}
+
+/// Unescaped text of the first `<tag>` element in `xml`.
+fn xml_text(xml: &str, tag: &str) -> Option<String> {
+    let open = format!("<{}>", tag);
+    let close = format!("</{}>", tag);
+    let start = xml.find(&open)? + open.len();
+    let end = start + xml[start..].find(&close)?;
+    Some(
+        xml[start..end]
+            .replace("&lt;", "<")
+            .replace("&gt;", ">")
+            .replace("&quot;", "\"")
+            .replace("&apos;", "'")
+            .replace("&amp;", "&"),
+    )
+}
+
+fn xml_escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+fn signing_key(
+    secret_access_key: &str,
+    date_stamp: &str,
+    region: &str,
+    service: &str,
+) -> Result<Vec<u8>> {
+    let k_date = hmac_sha256(
+        format!("AWS4{}", secret_access_key).as_bytes(),
+        date_stamp.as_bytes(),
+    )?;
+    let k_region = hmac_sha256(&k_date, region.as_bytes())?;
+    let k_service = hmac_sha256(&k_region, service.as_bytes())?;
+    hmac_sha256(&k_service, b"aws4_request")
+}
+
+fn sha256_hash(data: &[u8]) -> Vec<u8> {
+    let mut hasher = Sha256::new();
+    hasher.update(data);
+    hasher.finalize().to_vec()
+}
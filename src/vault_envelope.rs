@@ -0,0 +1,172 @@
+// Versioned envelopes for vault-encrypted data.
+//
+// Text payloads (`encrypted_payload` columns) are `v1:A256GCM:<key id>:<base64 iv||ciphertext>`;
+// receipt objects start with `DTV1`, an algorithm byte and the 8-byte key id,
+// followed by the IV and ciphertext. The header is authenticated as AAD, so it
+// cannot be altered without failing decryption. Data written before envelopes
+// existed is bare `iv||ciphertext` and is still read.
+
+use aes_gcm::aead::{Aead, AeadCore, KeyInit, OsRng, Payload};
+use aes_gcm::{Aes256Gcm, Nonce};
+use anyhow::{anyhow, bail};
+use base64::prelude::{Engine as _, BASE64_STANDARD};
+use sha2::{Digest, Sha256};
+
+const ENVELOPE_VERSION: &str = "v1";
+const ALG_A256GCM: &str = "A256GCM";
+
+const OBJECT_MAGIC: &[u8; 4] = b"DTV1";
+const OBJECT_ALG_A256GCM: u8 = 1;
+const KEY_ID_LEN: usize = 8;
+const OBJECT_HEADER_LEN: usize = OBJECT_MAGIC.len() + 1 + KEY_ID_LEN;
+const NONCE_LEN: usize = 12;
+
+/// A 256-bit vault key and its id: the first 8 bytes of its SHA-256, as hex.
+pub(crate) struct VaultKey {
+    cipher: Aes256Gcm,
+    id: [u8; KEY_ID_LEN],
+}
+
+impl VaultKey {
+    pub(crate) fn from_bytes(bytes: &[u8]) -> anyhow::Result<Self> {
+        if bytes.len() != 32 {
+            bail!("Invalid key length. Expected 32 bytes, got {}", bytes.len());
+        }
+        let cipher = Aes256Gcm::new_from_slice(bytes).map_err(|e| anyhow!("Cipher init error: {}", e))?;
+        let digest = Sha256::digest(bytes);
+        let mut id = [0u8; KEY_ID_LEN];
+        id.copy_from_slice(&digest[..KEY_ID_LEN]);
+        Ok(Self { cipher, id })
+    }
+
+    /// Parses a key as sent in `X-Vault-Key`: standard base64 of the raw 32 bytes.
+    pub(crate) fn from_base64(encoded: &str) -> anyhow::Result<Self> {
+        let bytes = BASE64_STANDARD
+            .decode(encoded.trim())
+            .map_err(|e| anyhow!("Failed to decode key: {}", e))?;
+        Self::from_bytes(&bytes)
+    }
+
+    pub(crate) fn id(&self) -> String {
+        hex(&self.id)
+    }
+
+    fn seal(&self, plaintext: &[u8], aad: &[u8]) -> anyhow::Result<Vec<u8>> {
+        let nonce = Aes256Gcm::generate_nonce(&mut OsRng);
+        let ciphertext = self
+            .cipher
+            .encrypt(&nonce, Payload { msg: plaintext, aad })
+            .map_err(|e| anyhow!("Encryption error: {}", e))?;
+        let mut sealed = Vec::with_capacity(NONCE_LEN + ciphertext.len());
+        sealed.extend_from_slice(&nonce);
+        sealed.extend_from_slice(&ciphertext);
+        Ok(sealed)
+    }
+
+    fn open(&self, sealed: &[u8], aad: &[u8]) -> anyhow::Result<Vec<u8>> {
+        if sealed.len() < NONCE_LEN {
+            bail!("Payload too short");
+        }
+        let (iv, ciphertext) = sealed.split_at(NONCE_LEN);
+        self.cipher
+            .decrypt(Nonce::from_slice(iv), Payload { msg: ciphertext, aad })
+            .map_err(|e| anyhow!("Decryption error: {}", e))
+    }
+}
+
+fn hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+/// Key id and sealed bytes of an enveloped text payload; None for a legacy one.
+fn parse_payload(payload: &str) -> anyhow::Result<Option<(&str, &str)>> {
+    let Some(rest) = payload.strip_prefix(ENVELOPE_VERSION).and_then(|r| r.strip_prefix(':')) else {
+        // Base64 never contains ':', so anything else is a legacy payload.
+        if payload.contains(':') {
+            bail!("Unsupported payload envelope");
+        }
+        return Ok(None);
+    };
+    let mut parts = rest.splitn(3, ':');
+    let (Some(alg), Some(kid), Some(body)) = (parts.next(), parts.next(), parts.next()) else {
+        bail!("Malformed payload envelope");
+    };
+    if alg != ALG_A256GCM {
+        bail!("Unsupported payload algorithm {}", alg);
+    }
+    Ok(Some((kid, body)))
+}
+
+fn payload_aad(kid: &str) -> String {
+    format!("{ENVELOPE_VERSION}:{ALG_A256GCM}:{kid}")
+}
+
+/// The `encrypted_payload` prefix shared by every payload sealed with `key_id`.
+pub(crate) fn payload_prefix(key_id: &str) -> String {
+    format!("{}:", payload_aad(key_id))
+}
+
+pub(crate) fn encrypt_payload(key: &VaultKey, plaintext: &[u8]) -> anyhow::Result<String> {
+    let aad = payload_aad(&key.id());
+    let sealed = key.seal(plaintext, aad.as_bytes())?;
+    Ok(format!("{}:{}", aad, BASE64_STANDARD.encode(sealed)))
+}
+
+pub(crate) fn decrypt_payload(key: &VaultKey, payload: &str) -> anyhow::Result<Vec<u8>> {
+    let decode = |body: &str| {
+        BASE64_STANDARD
+            .decode(body)
+            .map_err(|e| anyhow!("Failed to decode encrypted payload: {}", e))
+    };
+    match parse_payload(payload)? {
+        Some((kid, body)) => {
+            if kid != key.id() {
+                bail!("Payload was encrypted with key {}, not {}", kid, key.id());
+            }
+            key.open(&decode(body)?, payload_aad(kid).as_bytes())
+        }
+        None => key.open(&decode(payload)?, &[]),
+    }
+}
+
+fn object_header(key: &VaultKey) -> [u8; OBJECT_HEADER_LEN] {
+    let mut header = [0u8; OBJECT_HEADER_LEN];
+    header[..OBJECT_MAGIC.len()].copy_from_slice(OBJECT_MAGIC);
+    header[OBJECT_MAGIC.len()] = OBJECT_ALG_A256GCM;
+    header[OBJECT_MAGIC.len() + 1..].copy_from_slice(&key.id);
+    header
+}
+
+/// Key id in a receipt object's header; None for a legacy object. A legacy IV
+/// can begin with the magic by chance, so a match is a hint, not proof.
+pub(crate) fn object_key_id(bytes: &[u8]) -> Option<String> {
+    if bytes.len() < OBJECT_HEADER_LEN + NONCE_LEN
+        || &bytes[..OBJECT_MAGIC.len()] != OBJECT_MAGIC
+        || bytes[OBJECT_MAGIC.len()] != OBJECT_ALG_A256GCM
+    {
+        return None;
+    }
+    Some(hex(&bytes[OBJECT_MAGIC.len() + 1..OBJECT_HEADER_LEN]))
+}
+
+pub(crate) fn encrypt_object(key: &VaultKey, plaintext: &[u8]) -> anyhow::Result<Vec<u8>> {
+    let header = object_header(key);
+    let sealed = key.seal(plaintext, &header)?;
+    let mut object = Vec::with_capacity(header.len() + sealed.len());
+    object.extend_from_slice(&header);
+    object.extend_from_slice(&sealed);
+    Ok(object)
+}
+
+pub(crate) fn decrypt_object(key: &VaultKey, bytes: &[u8]) -> anyhow::Result<Vec<u8>> {
+    match object_key_id(bytes) {
+        Some(kid) if kid == key.id() => {
+            let (header, sealed) = bytes.split_at(OBJECT_HEADER_LEN);
+            key.open(sealed, header).or_else(|e| key.open(bytes, &[]).map_err(|_| e))
+        }
+        Some(kid) => key
+            .open(bytes, &[])
+            .map_err(|_| anyhow!("Object was encrypted with key {}, not {}", kid, key.id())),
+        None => key.open(bytes, &[]),
+    }
+}
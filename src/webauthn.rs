@@ -0,0 +1,432 @@
+use anyhow::{anyhow, bail, Context};
+use base64::prelude::{Engine as _, BASE64_URL_SAFE_NO_PAD};
+use jsonwebtoken::{Algorithm, DecodingKey};
+use serde::Deserialize;
+use sha2::{Digest, Sha256};
+use std::env;
+
+/// COSE algorithm identifiers we accept (RFC 9053): ES256 and RS256.
+pub(crate) const COSE_ALG_ES256: i64 = -7;
+pub(crate) const COSE_ALG_RS256: i64 = -257;
+
+const FLAG_USER_PRESENT: u8 = 0x01;
+const FLAG_USER_VERIFIED: u8 = 0x04;
+const FLAG_ATTESTED_CREDENTIAL: u8 = 0x40;
+const MAX_CREDENTIAL_ID_LEN: usize = 1023;
+const MAX_CBOR_DEPTH: usize = 16;
+
+/// Where ceremonies are valid: the RP ID (a registrable domain) and the exact
+/// origins the browser may report in clientDataJSON.
+#[derive(Debug, Clone)]
+pub(crate) struct RelyingParty {
+    pub id: String,
+    pub name: String,
+    pub origins: Vec<String>,
+}
+
+impl RelyingParty {
+    /// `WEBAUTHN_RP_ID` is required in production; development defaults to
+    /// `localhost`. Origins come from `WEBAUTHN_ORIGINS`, then `ALLOWED_ORIGINS`.
+    pub(crate) fn from_env() -> anyhow::Result<Self> {
+        let production = env::var("RUST_ENV").map(|v| v == "production").unwrap_or(false);
+        let id = match env::var("WEBAUTHN_RP_ID").ok().filter(|v| !v.trim().is_empty()) {
+            Some(id) => id.trim().to_lowercase(),
+            None if production => bail!("WEBAUTHN_RP_ID must be set in production"),
+            None => "localhost".to_string(),
+        };
+        let name = env::var("WEBAUTHN_RP_NAME").unwrap_or_else(|_| "Deductible Tracker".to_string());
+        let origins: Vec<String> = env::var("WEBAUTHN_ORIGINS")
+            .or_else(|_| env::var("ALLOWED_ORIGINS"))
+            .unwrap_or_else(|_| "http://localhost:3000,http://localhost:8080".to_string())
+            .split(',')
+            .map(|s| s.trim().trim_end_matches('/').to_string())
+            .filter(|s| !s.is_empty())
+            .collect();
+        if origins.is_empty() {
+            bail!("WEBAUTHN_ORIGINS must list at least one origin");
+        }
+        Ok(Self { id, name, origins })
+    }
+}
+
+/// A credential accepted by a registration ceremony.
+#[derive(Debug, Clone)]
+pub(crate) struct RegisteredCredential {
+    pub credential_id: Vec<u8>,
+    /// The COSE_Key exactly as the authenticator encoded it.
+    pub public_key: Vec<u8>,
+    pub algorithm: i64,
+    pub sign_count: u32,
+}
+
+#[derive(Debug, Clone)]
+pub(crate) struct VerifiedAssertion {
+    pub sign_count: u32,
+}
+
+pub(crate) fn new_challenge() -> Vec<u8> {
+    let mut challenge = Vec::with_capacity(32);
+    challenge.extend_from_slice(uuid::Uuid::new_v4().as_bytes());
+    challenge.extend_from_slice(uuid::Uuid::new_v4().as_bytes());
+    challenge
+}
+
+pub(crate) fn encode_b64url(bytes: &[u8]) -> String {
+    BASE64_URL_SAFE_NO_PAD.encode(bytes)
+}
+
+/// Browsers send base64url; tolerate padding and the standard alphabet too.
+pub(crate) fn decode_b64url(value: &str) -> anyhow::Result<Vec<u8>> {
+    let normalized: String = value
+        .trim_end_matches('=')
+        .chars()
+        .map(|c| match c {
+            '+' => '-',
+            '/' => '_',
+            other => other,
+        })
+        .collect();
+    BASE64_URL_SAFE_NO_PAD
+        .decode(normalized)
+        .context("invalid base64url value")
+}
+
+// --- Minimal CBOR (RFC 8949) reader: definite lengths, no floats or tags. ---
+
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) enum Cbor {
+    Int(i128),
+    Bytes(Vec<u8>),
+    Text(String),
+    Array(Vec<Cbor>),
+    Map(Vec<(Cbor, Cbor)>),
+    Bool(bool),
+    Null,
+}
+
+impl Cbor {
+    fn map_get(&self, key: &Cbor) -> Option<&Cbor> {
+        match self {
+            Cbor::Map(entries) => entries.iter().find(|(k, _)| k == key).map(|(_, v)| v),
+            _ => None,
+        }
+    }
+
+    fn get_int_key(&self, key: i128) -> Option<&Cbor> {
+        self.map_get(&Cbor::Int(key))
+    }
+
+    fn get_text_key(&self, key: &str) -> Option<&Cbor> {
+        self.map_get(&Cbor::Text(key.to_string()))
+    }
+}
+
+/// Decodes one CBOR item and returns it with the number of bytes consumed.
+pub(crate) fn decode_cbor(input: &[u8]) -> anyhow::Result<(Cbor, usize)> {
+    let mut pos = 0;
+    let value = decode_item(input, &mut pos, 0)?;
+    Ok((value, pos))
+}
+
+fn take<'a>(input: &'a [u8], pos: &mut usize, len: usize) -> anyhow::Result<&'a [u8]> {
+    let end = pos.checked_add(len).ok_or_else(|| anyhow!("CBOR length overflow"))?;
+    let slice = input.get(*pos..end).ok_or_else(|| anyhow!("truncated CBOR"))?;
+    *pos = end;
+    Ok(slice)
+}
+
+fn read_argument(input: &[u8], pos: &mut usize, info: u8) -> anyhow::Result<u64> {
+    Ok(match info {
+        0..=23 => info as u64,
+        24 => take(input, pos, 1)?[0] as u64,
+        25 => u16::from_be_bytes(take(input, pos, 2)?.try_into()?) as u64,
+        26 => u32::from_be_bytes(take(input, pos, 4)?.try_into()?) as u64,
+        27 => u64::from_be_bytes(take(input, pos, 8)?.try_into()?),
+        _ => bail!("unsupported CBOR length encoding"),
+    })
+}
+
+fn decode_item(input: &[u8], pos: &mut usize, depth: usize) -> anyhow::Result<Cbor> {
+    if depth > MAX_CBOR_DEPTH {
+        bail!("CBOR nested too deeply");
+    }
+    let initial = take(input, pos, 1)?[0];
+    let major = initial >> 5;
+    let info = initial & 0x1f;
+    if major == 7 {
+        return match info {
+            20 => Ok(Cbor::Bool(false)),
+            21 => Ok(Cbor::Bool(true)),
+            22 => Ok(Cbor::Null),
+            _ => bail!("unsupported CBOR simple value"),
+        };
+    }
+    let arg = read_argument(input, pos, info)?;
+    // Every element takes at least one byte, which bounds pre-allocation.
+    let remaining = input.len().saturating_sub(*pos) as u64;
+    match major {
+        0 => Ok(Cbor::Int(arg as i128)),
+        1 => Ok(Cbor::Int(-1 - arg as i128)),
+        2 => Ok(Cbor::Bytes(take(input, pos, usize::try_from(arg)?)?.to_vec())),
+        3 => {
+            let bytes = take(input, pos, usize::try_from(arg)?)?;
+            Ok(Cbor::Text(String::from_utf8(bytes.to_vec()).context("invalid CBOR text")?))
+        }
+        4 => {
+            if arg > remaining {
+                bail!("truncated CBOR");
+            }
+            let mut items = Vec::with_capacity(arg as usize);
+            for _ in 0..arg {
+                items.push(decode_item(input, pos, depth + 1)?);
+            }
+            Ok(Cbor::Array(items))
+        }
+        5 => {
+            if arg.saturating_mul(2) > remaining {
+                bail!("truncated CBOR");
+            }
+            let mut entries = Vec::with_capacity(arg as usize);
+            for _ in 0..arg {
+                let key = decode_item(input, pos, depth + 1)?;
+                let value = decode_item(input, pos, depth + 1)?;
+                entries.push((key, value));
+            }
+            Ok(Cbor::Map(entries))
+        }
+        _ => bail!("unsupported CBOR major type {}", major),
+    }
+}
+
+// --- COSE keys ---
+
+fn cose_bytes(key: &Cbor, label: i128) -> anyhow::Result<&[u8]> {
+    match key.get_int_key(label) {
+        Some(Cbor::Bytes(b)) => Ok(b),
+        _ => bail!("COSE key is missing parameter {}", label),
+    }
+}
+
+/// Parses a COSE_Key into its algorithm and a verification key.
+pub(crate) fn parse_cose_key(bytes: &[u8]) -> anyhow::Result<(i64, DecodingKey)> {
+    let (key, _) = decode_cbor(bytes)?;
+    let kty = match key.get_int_key(1) {
+        Some(Cbor::Int(v)) => *v,
+        _ => bail!("COSE key has no kty"),
+    };
+    let alg = match key.get_int_key(3) {
+        Some(Cbor::Int(v)) => i64::try_from(*v)?,
+        _ => bail!("COSE key has no alg"),
+    };
+    match (kty, alg) {
+        // EC2 on P-256
+        (2, COSE_ALG_ES256) => {
+            if key.get_int_key(-1) != Some(&Cbor::Int(1)) {
+                bail!("ES256 keys must use the P-256 curve");
+            }
+            let x = cose_bytes(&key, -2)?;
+            let y = cose_bytes(&key, -3)?;
+            if x.len() != 32 || y.len() != 32 {
+                bail!("invalid P-256 coordinates");
+            }
+            let decoding = DecodingKey::from_ec_components(&encode_b64url(x), &encode_b64url(y))?;
+            Ok((alg, decoding))
+        }
+        (3, COSE_ALG_RS256) => {
+            let n = cose_bytes(&key, -1)?;
+            let e = cose_bytes(&key, -2)?;
+            if n.len() < 256 {
+                bail!("RSA keys must be at least 2048 bits");
+            }
+            Ok((alg, DecodingKey::from_rsa_raw_components(n, e)))
+        }
+        _ => bail!("unsupported COSE key type {} / algorithm {}", kty, alg),
+    }
+}
+
+/// WebAuthn ECDSA signatures are ASN.1 DER; JOSE verifiers expect r || s.
+fn ecdsa_der_to_raw(der: &[u8]) -> anyhow::Result<Vec<u8>> {
+    fn read_int<'a>(der: &'a [u8], pos: &mut usize) -> anyhow::Result<&'a [u8]> {
+        if der.get(*pos) != Some(&0x02) {
+            bail!("invalid ECDSA signature");
+        }
+        let len = *der.get(*pos + 1).ok_or_else(|| anyhow!("invalid ECDSA signature"))? as usize;
+        let start = *pos + 2;
+        let int = der.get(start..start + len).ok_or_else(|| anyhow!("invalid ECDSA signature"))?;
+        *pos = start + len;
+        Ok(int)
+    }
+    if der.len() < 8 || der[0] != 0x30 || der[1] as usize != der.len() - 2 {
+        bail!("invalid ECDSA signature");
+    }
+    let mut pos = 2;
+    let r = read_int(der, &mut pos)?;
+    let s = read_int(der, &mut pos)?;
+    if pos != der.len() {
+        bail!("invalid ECDSA signature");
+    }
+    let mut raw = vec![0u8; 64];
+    for (int, offset) in [(r, 0), (s, 32)] {
+        let trimmed: &[u8] = {
+            let first_nonzero = int.iter().position(|b| *b != 0).unwrap_or(int.len());
+            &int[first_nonzero..]
+        };
+        if trimmed.len() > 32 {
+            bail!("invalid ECDSA signature");
+        }
+        raw[offset + 32 - trimmed.len()..offset + 32].copy_from_slice(trimmed);
+    }
+    Ok(raw)
+}
+
+pub(crate) fn verify_signature(public_key: &[u8], message: &[u8], signature: &[u8]) -> anyhow::Result<()> {
+    let (alg, key) = parse_cose_key(public_key)?;
+    let (jose_alg, raw_signature) = match alg {
+        COSE_ALG_ES256 => (Algorithm::ES256, ecdsa_der_to_raw(signature)?),
+        COSE_ALG_RS256 => (Algorithm::RS256, signature.to_vec()),
+        other => bail!("unsupported algorithm {}", other),
+    };
+    let valid = jsonwebtoken::crypto::verify(&encode_b64url(&raw_signature), message, &key, jose_alg)
+        .unwrap_or(false);
+    if !valid {
+        bail!("signature verification failed");
+    }
+    Ok(())
+}
+
+// --- Ceremony checks ---
+
+#[derive(Deserialize)]
+struct ClientData {
+    #[serde(rename = "type")]
+    kind: String,
+    challenge: String,
+    origin: String,
+    #[serde(default, rename = "crossOrigin")]
+    cross_origin: Option<bool>,
+}
+
+fn verify_client_data(rp: &RelyingParty, client_data_json: &[u8], kind: &str, challenge: &[u8]) -> anyhow::Result<()> {
+    let client_data: ClientData = serde_json::from_slice(client_data_json).context("invalid clientDataJSON")?;
+    if client_data.kind != kind {
+        bail!("unexpected ceremony type {}", client_data.kind);
+    }
+    if decode_b64url(&client_data.challenge)? != challenge {
+        bail!("challenge mismatch");
+    }
+    if !rp.origins.iter().any(|o| o == &client_data.origin) {
+        bail!("origin {} is not allowed", client_data.origin);
+    }
+    if client_data.cross_origin == Some(true) {
+        bail!("cross-origin ceremonies are not allowed");
+    }
+    Ok(())
+}
+
+struct AuthenticatorData<'a> {
+    flags: u8,
+    sign_count: u32,
+    rest: &'a [u8],
+}
+
+fn parse_authenticator_data<'a>(rp: &RelyingParty, data: &'a [u8], require_user_verification: bool) -> anyhow::Result<AuthenticatorData<'a>> {
+    if data.len() < 37 {
+        bail!("authenticator data too short");
+    }
+    if data[..32] != Sha256::digest(rp.id.as_bytes())[..] {
+        bail!("RP ID hash mismatch");
+    }
+    let flags = data[32];
+    if flags & FLAG_USER_PRESENT == 0 {
+        bail!("user presence flag not set");
+    }
+    if require_user_verification && flags & FLAG_USER_VERIFIED == 0 {
+        bail!("user verification required");
+    }
+    let sign_count = u32::from_be_bytes(data[33..37].try_into()?);
+    Ok(AuthenticatorData { flags, sign_count, rest: &data[37..] })
+}
+
+/// Verifies a `navigator.credentials.create()` response. Attestation is
+/// requested as "none", so any statement present is not evaluated.
+pub(crate) fn verify_registration(
+    rp: &RelyingParty,
+    challenge: &[u8],
+    client_data_json: &[u8],
+    attestation_object: &[u8],
+    require_user_verification: bool,
+) -> anyhow::Result<RegisteredCredential> {
+    verify_client_data(rp, client_data_json, "webauthn.create", challenge)?;
+
+    let (attestation, _) = decode_cbor(attestation_object)?;
+    let auth_data = match attestation.get_text_key("authData") {
+        Some(Cbor::Bytes(b)) => b.as_slice(),
+        _ => bail!("attestation object has no authData"),
+    };
+    if !matches!(attestation.get_text_key("fmt"), Some(Cbor::Text(_))) {
+        bail!("attestation object has no fmt");
+    }
+
+    let parsed = parse_authenticator_data(rp, auth_data, require_user_verification)?;
+    if parsed.flags & FLAG_ATTESTED_CREDENTIAL == 0 {
+        bail!("no attested credential data");
+    }
+    // aaguid (16) || credentialIdLength (2) || credentialId || credentialPublicKey
+    let rest = parsed.rest;
+    if rest.len() < 18 {
+        bail!("attested credential data too short");
+    }
+    let id_len = u16::from_be_bytes(rest[16..18].try_into()?) as usize;
+    if id_len == 0 || id_len > MAX_CREDENTIAL_ID_LEN {
+        bail!("invalid credential id length");
+    }
+    let credential_id = rest
+        .get(18..18 + id_len)
+        .ok_or_else(|| anyhow!("credential id truncated"))?
+        .to_vec();
+    let key_bytes = &rest[18 + id_len..];
+    let (_, key_len) = decode_cbor(key_bytes)?;
+    let public_key = key_bytes[..key_len].to_vec();
+    let (algorithm, _) = parse_cose_key(&public_key)?;
+
+    Ok(RegisteredCredential {
+        credential_id,
+        public_key,
+        algorithm,
+        sign_count: parsed.sign_count,
+    })
+}
+
+/// Verifies a `navigator.credentials.get()` response against the stored key
+/// and counter. A counter that fails to increase suggests a cloned
+/// authenticator; authenticators that always report zero are allowed.
+#[allow(clippy::too_many_arguments)]
+pub(crate) fn verify_assertion(
+    rp: &RelyingParty,
+    challenge: &[u8],
+    client_data_json: &[u8],
+    authenticator_data: &[u8],
+    signature: &[u8],
+    public_key: &[u8],
+    stored_sign_count: u32,
+    require_user_verification: bool,
+) -> anyhow::Result<VerifiedAssertion> {
+    verify_client_data(rp, client_data_json, "webauthn.get", challenge)?;
+    let parsed = parse_authenticator_data(rp, authenticator_data, require_user_verification)?;
+
+    let mut message = authenticator_data.to_vec();
+    message.extend_from_slice(&Sha256::digest(client_data_json));
+    verify_signature(public_key, &message, signature)?;
+
+    if (parsed.sign_count != 0 || stored_sign_count != 0) && parsed.sign_count <= stored_sign_count {
+        bail!(
+            "signature counter did not increase ({} <= {}); the authenticator may be cloned",
+            parsed.sign_count,
+            stored_sign_count
+        );
+    }
+
+    Ok(VerifiedAssertion {
+        sign_count: parsed.sign_count,
+    })
+}
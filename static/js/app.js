@@ -28,10 +28,12 @@ import {
 } from './services/current-user.js';
 import { iconSvg } from './services/icons.js';
 import { escapeHtml } from './utils/html.js';
-import { registerVaultKey, unlockVaultKey, encryptData, decryptData, isWebAuthnSupported } from './services/crypto.js';
+import { registerVaultKey, unlockVaultKey, rotateVaultKey, encryptData, decryptData, isWebAuthnSupported } from './services/crypto.js';
+import { registerPasskey, listPasskeys, deletePasskey } from './services/passkeys.js';
 
 // Extracted modules
 import {
+  isAuthenticated,
   setAuthenticated,
   setReturnTo,
   checkAuthCached,
@@ -120,6 +122,10 @@ function buildRouteDeps() {
     deleteDonationOnServer,
     registerVaultKey,
     unlockVaultKey,
+    rotateVaultKey,
+    registerPasskey,
+    listPasskeys,
+    deletePasskey,
     encryptData,
     decryptData,
     isWebAuthnSupported,
@@ -193,6 +199,7 @@ function renderLogin() {
 }
 
 async function handleLogout() {
+  Sync.disconnectServerEvents();
   await authHandleLogout({ clearUserCaches, renderLogin });
 }
 
@@ -338,6 +345,9 @@ async function init() {
     const isOnline = navigator.onLine;
     if (isOnline) {
       Sync.pushChanges().catch((err) => console.error('Initial sync failed:', err));
+      if (isAuthenticated()) Sync.connectServerEvents();
+    } else {
+      Sync.disconnectServerEvents();
     }
     await updateSyncStatus();
   };
@@ -345,6 +355,15 @@ async function init() {
   window.addEventListener('online', updateStatus);
   window.addEventListener('offline', updateStatus);
   window.addEventListener('sync-queue-changed', updateSyncStatus);
+  // Re-render list views when another device changed something; forms are left alone.
+  window.addEventListener('sync-remote-change', () => {
+    const path = window.location.pathname;
+    if (['/', '/donations', '/charities'].includes(path)) {
+      navigate(path, { pushState: false }).catch((err) =>
+        console.error('Refresh after remote change failed:', err)
+      );
+    }
+  });
   updateStatus();
 
   function closeMobileMenu() {
@@ -454,6 +473,7 @@ async function init() {
       const initialRoute =
         location.pathname === '/index.html' || location.pathname === '/' ? '/' : location.pathname;
       await navigate(initialRoute);
+      if (navigator.onLine) Sync.connectServerEvents();
     }
   } catch (err) {
     console.error('Initialization failed:', err);
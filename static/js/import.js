@@ -5,6 +5,7 @@ export async function importCSV(csvString) {
     method: 'POST',
     headers: { 'Content-Type': 'application/json' },
     body: JSON.stringify({ csv: csvString }),
+    idempotent: true,
   });
   if (!res.ok) throw new Error('Import failed');
   return data;
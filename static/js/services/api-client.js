@@ -80,6 +80,7 @@ export async function createDonationOnServer(payload) {
     method: 'POST',
     headers: { 'Content-Type': 'application/json' },
     body: JSON.stringify(finalPayload),
+    idempotent: true,
   });
   if (!res.ok) {
     throw new Error(typeof data === 'string' ? data : 'Failed to create donation');
@@ -5,7 +5,7 @@
  * profile hydration, and logout flow.
  */
 
-import { apiJson } from './http.js';
+import { apiJson, refreshSession } from './http.js';
 import {
   clearCurrentUser,
   getCurrentUserId,
@@ -49,7 +49,10 @@ export async function checkAuthCached() {
   if (now - lastAuthCheckAt < ttlMs) return lastAuthResult;
   authCheckInFlight = (async () => {
     try {
-      const res = await fetch('/api/me', { credentials: 'include' });
+      let res = await fetch('/api/me', { credentials: 'include' });
+      if (res.status === 401 && (await refreshSession())) {
+        res = await fetch('/api/me', { credentials: 'include' });
+      }
       // Treat 429 as "unknown" and keep the last known result
       if (res.status === 429) return lastAuthResult;
 
@@ -1,5 +1,7 @@
 // static/js/services/crypto.js
 import { getCurrentUser } from './current-user.js';
+import { apiJson } from './http.js';
+import { registerPasskey, unlockVaultWithPasskey } from './passkeys.js';
 
 const VAULT_CHALLENGE = "Deductible Tracker Vault Challenge - Do Not Share";
 let cachedVaultKey = null;
@@ -11,10 +13,18 @@ export async function ensureVaultKey(userId) {
     if (user.vault_credential_id) {
       cachedVaultKey = await unlockVaultKey(userId, user.vault_credential_id);
     } else {
-      // Fallback for transition - if no ID stored, we must re-register or fail
-      console.warn('Vault enabled but no credential ID found. Re-registration may be required.');
-      const result = await registerVaultKey(userId);
-      cachedVaultKey = result.key;
+      // The server withholds the credential id of a passkey-gated vault until
+      // it has verified an assertion made with that passkey.
+      try {
+        const { rawId } = await unlockVaultWithPasskey();
+        cachedVaultKey = await deriveKeyFromRawId(rawId);
+      } catch (e) {
+        if (e.status !== 404) throw e;
+        // Fallback for transition - if no ID stored, we must re-register or fail
+        console.warn('Vault enabled but no credential ID found. Re-registration may be required.');
+        const result = await registerVaultKey(userId);
+        cachedVaultKey = result.key;
+      }
     }
   }
   return cachedVaultKey;
@@ -29,6 +39,17 @@ export async function registerVaultKey(userId) {
     throw new Error('WebAuthn is not supported in this browser.');
   }
 
+  // Prefer a server-verified passkey so the vault can later be gated on it.
+  try {
+    const { rawId } = await registerPasskey('Encrypted vault');
+    const key = await deriveKeyFromRawId(rawId);
+    const credentialId = btoa(String.fromCharCode(...new Uint8Array(rawId)));
+    return { key, credentialId };
+  } catch (e) {
+    // 503: WebAuthn is not configured on this server.
+    if (e.status !== 503) throw e;
+  }
+
   const challenge = new TextEncoder().encode(VAULT_CHALLENGE);
   const userBuffer = new TextEncoder().encode(userId);
 
@@ -92,51 +113,217 @@ export async function unlockVaultKey(userId, credentialIdB64) {
 
 async function deriveKeyFromRawId(rawId) {
   const hash = await crypto.subtle.digest('SHA-256', rawId);
-  return await crypto.subtle.importKey(
+  // Extractable: OCR and key rotation send the raw key in X-Vault-Key.
+  const key = await crypto.subtle.importKey(
     'raw',
     hash,
     { name: 'AES-GCM' },
-    false,
+    true,
     ['encrypt', 'decrypt']
   );
+  vaultKeyIds.set(key, await keyIdFromRaw(hash));
+  return key;
 }
 
-export async function encryptData(key, data) {
-  const encoded = new TextEncoder().encode(JSON.stringify(data));
-  return encryptBinaryData(key, encoded);
+// Payloads are sealed as `v1:A256GCM:<key id>:<base64 iv||ciphertext>` and
+// receipt objects as `DTV1`, an algorithm byte and the 8-byte key id, then
+// iv||ciphertext; the header is the AES-GCM additional data. Data written
+// before envelopes is bare iv||ciphertext. Must match src/vault_envelope.rs.
+const ENVELOPE_HEADER = 'v1:A256GCM:';
+const OBJECT_MAGIC = [0x44, 0x54, 0x56, 0x31]; // "DTV1"
+const OBJECT_ALG_A256GCM = 1;
+const KEY_ID_BYTES = 8;
+const vaultKeyIds = new WeakMap();
+const BLIND_INDEX_INFO = 'deductible-tracker blind index v1';
+const blindIndexKeys = new WeakMap();
+
+function toHex(bytes) {
+  return Array.from(bytes, (b) => b.toString(16).padStart(2, '0')).join('');
 }
 
-export async function encryptBinaryData(key, bytes) {
+function toBase64(bytes) {
+  let binary = '';
+  for (let i = 0; i < bytes.length; i += 0x8000) {
+    binary += String.fromCharCode(...bytes.subarray(i, i + 0x8000));
+  }
+  return btoa(binary);
+}
+
+function fromBase64(value) {
+  return Uint8Array.from(atob(value), (c) => c.charCodeAt(0));
+}
+
+async function keyIdFromRaw(raw) {
+  const digest = new Uint8Array(await crypto.subtle.digest('SHA-256', raw));
+  return toHex(digest.slice(0, KEY_ID_BYTES));
+}
+
+/** Id of a vault key: the first 8 bytes of SHA-256 of the raw key, as hex. */
+export async function vaultKeyId(key) {
+  if (!vaultKeyIds.has(key)) {
+    vaultKeyIds.set(key, await keyIdFromRaw(await crypto.subtle.exportKey('raw', key)));
+  }
+  return vaultKeyIds.get(key);
+}
+
+export async function exportVaultKey(key) {
+  return toBase64(new Uint8Array(await crypto.subtle.exportKey('raw', key)));
+}
+
+async function seal(key, bytes, additionalData) {
   const iv = crypto.getRandomValues(new Uint8Array(12));
-  
   const ciphertext = await crypto.subtle.encrypt(
-    { name: 'AES-GCM', iv },
+    { name: 'AES-GCM', iv, additionalData },
     key,
     bytes
   );
+  const sealed = new Uint8Array(iv.length + ciphertext.byteLength);
+  sealed.set(iv, 0);
+  sealed.set(new Uint8Array(ciphertext), iv.length);
+  return sealed;
+}
 
-  // Return base64 combined IV + Ciphertext
-  const combined = new Uint8Array(iv.length + ciphertext.byteLength);
-  combined.set(iv, 0);
-  combined.set(new Uint8Array(ciphertext), iv.length);
-  
-  return btoa(String.fromCharCode(...combined));
+async function unseal(key, sealed, additionalData) {
+  const params = { name: 'AES-GCM', iv: sealed.slice(0, 12) };
+  if (additionalData) params.additionalData = additionalData;
+  return crypto.subtle.decrypt(params, key, sealed.slice(12));
 }
 
-export async function decryptData(key, base64Data) {
-  const combined = new Uint8Array(atob(base64Data).split('').map(c => c.charCodeAt(0)));
-  const iv = combined.slice(0, 12);
-  const ciphertext = combined.slice(12);
+export async function encryptData(key, data) {
+  const encoded = new TextEncoder().encode(JSON.stringify(data));
+  const header = `${ENVELOPE_HEADER}${await vaultKeyId(key)}`;
+  const sealed = await seal(key, encoded, new TextEncoder().encode(header));
+  return `${header}:${toBase64(sealed)}`;
+}
 
-  const decrypted = await crypto.subtle.decrypt(
-    { name: 'AES-GCM', iv },
-    key,
-    ciphertext
-  );
+/** Seals a receipt file; returns the stored object, base64-encoded. */
+export async function encryptBinaryData(key, bytes) {
+  const header = new Uint8Array(OBJECT_MAGIC.length + 1 + KEY_ID_BYTES);
+  header.set(OBJECT_MAGIC, 0);
+  header[OBJECT_MAGIC.length] = OBJECT_ALG_A256GCM;
+  const keyId = await vaultKeyId(key);
+  for (let i = 0; i < KEY_ID_BYTES; i++) {
+    header[OBJECT_MAGIC.length + 1 + i] = parseInt(keyId.slice(i * 2, i * 2 + 2), 16);
+  }
+  const sealed = await seal(key, bytes, header);
+  const object = new Uint8Array(header.length + sealed.length);
+  object.set(header, 0);
+  object.set(sealed, header.length);
+  return toBase64(object);
+}
 
+export async function decryptData(key, payload) {
+  let decrypted;
+  if (payload.startsWith(ENVELOPE_HEADER)) {
+    const separator = payload.indexOf(':', ENVELOPE_HEADER.length);
+    const header = payload.slice(0, separator);
+    const keyId = header.slice(ENVELOPE_HEADER.length);
+    if (keyId !== (await vaultKeyId(key))) {
+      throw new Error(`Encrypted with another vault key (${keyId})`);
+    }
+    decrypted = await unseal(key, fromBase64(payload.slice(separator + 1)), new TextEncoder().encode(header));
+  } else {
+    decrypted = await unseal(key, fromBase64(payload));
+  }
   return JSON.parse(new TextDecoder().decode(decrypted));
 }
 
+async function blindIndexKey(key) {
+  if (!blindIndexKeys.has(key)) {
+    const raw = await crypto.subtle.exportKey('raw', key);
+    const base = await crypto.subtle.importKey('raw', raw, 'HKDF', false, ['deriveKey']);
+    const info = new TextEncoder().encode(BLIND_INDEX_INFO);
+    blindIndexKeys.set(
+      key,
+      await crypto.subtle.deriveKey(
+        { name: 'HKDF', hash: 'SHA-256', salt: new Uint8Array(0), info },
+        base,
+        { name: 'HMAC', hash: 'SHA-256', length: 256 },
+        false,
+        ['sign']
+      )
+    );
+  }
+  return blindIndexKeys.get(key);
+}
+
+/** HMAC of one normalized value under a key derived from the vault key, base64url. */
+export async function blindIndexToken(key, kind, value) {
+  const message = new TextEncoder().encode(`${kind}:${String(value).trim().toLowerCase()}`);
+  const mac = new Uint8Array(await crypto.subtle.sign('HMAC', await blindIndexKey(key), message));
+  return toBase64(mac).replace(/\+/g, '-').replace(/\//g, '_').replace(/=+$/, '');
+}
+
+/**
+ * Tokens the server stores beside an encrypted donation so it can filter
+ * and find duplicates without seeing the values. Missing fields get none.
+ */
+export async function donationBlindIndex(key, donation) {
+  const present = (value) => value !== null && value !== undefined && value !== '';
+  const token = (kind, value) => (present(value) ? blindIndexToken(key, kind, value) : null);
+  const year = donation.year ?? (present(donation.date) ? String(donation.date).slice(0, 4) : null);
+  const amount = present(donation.amount) ? Number(donation.amount).toFixed(2) : '';
+  const dedupe = present(donation.date)
+    ? [donation.charity_id, donation.date, donation.category || '', amount].map((v) => String(v).trim()).join('|')
+    : null;
+  const [charity, yearToken, category, dedupeToken] = await Promise.all([
+    token('charity', donation.charity_id),
+    token('year', year),
+    token('category', donation.category),
+    token('dedupe', dedupe),
+  ]);
+  return { charity, year: yearToken, category, dedupe: dedupeToken };
+}
+
+/**
+ * Re-encrypts the vault under a new passkey. The server does the work in
+ * batches; an interrupted rotation is resumed with the passkey it started with.
+ */
+export async function rotateVaultKey(userId, onProgress = () => {}) {
+  const oldKey = await ensureVaultKey(userId);
+  if (!oldKey) throw new Error('Encryption is not enabled.');
+
+  const { data: status } = await apiJson('/api/me/vault/rotation');
+  const unfinished = status && status.rotation;
+  let newKey;
+  let credentialId;
+  if (
+    unfinished &&
+    unfinished.status !== 'completed' &&
+    unfinished.new_credential_id &&
+    unfinished.old_key_id === (await vaultKeyId(oldKey))
+  ) {
+    credentialId = unfinished.new_credential_id;
+    newKey = await unlockVaultKey(userId, credentialId);
+  } else {
+    ({ key: newKey, credentialId } = await registerVaultKey(userId));
+  }
+
+  const headers = {
+    'Content-Type': 'application/json',
+    'X-Vault-Key': await exportVaultKey(oldKey),
+    'X-New-Vault-Key': await exportVaultKey(newKey),
+  };
+  for (;;) {
+    const { res, data } = await apiJson('/api/me/vault/rotation', {
+      method: 'POST',
+      headers,
+      body: JSON.stringify({ vault_credential_id: credentialId }),
+    });
+    if (!res.ok) {
+      const reason = data && data.failed_table
+        ? `Could not decrypt ${data.failed_table} ${data.failed_record_id} with the current key.`
+        : typeof data === 'string' ? data : 'Key rotation failed';
+      throw new Error(reason);
+    }
+    onProgress(data);
+    if (data.status === 'completed') {
+      cachedVaultKey = newKey;
+      return data;
+    }
+  }
+}
+
 export function isWebAuthnSupported() {
   return !!(window.PublicKeyCredential && 
             PublicKeyCredential.isUserVerifyingPlatformAuthenticatorAvailable && 
@@ -6,7 +6,7 @@
  * logic in every call-site.
  */
 
-import { encryptData, decryptData, ensureVaultKey } from './crypto.js';
+import { encryptData, decryptData, donationBlindIndex, ensureVaultKey } from './crypto.js';
 import { getCurrentUserId } from './current-user.js';
 
 const CHARITY_SENSITIVE_FIELDS = ['name', 'ein', 'street', 'city', 'state', 'zip'];
@@ -48,6 +48,21 @@ export async function encryptPayloadFields(vaultKey, payload, fields, placeholde
   return result;
 }
 
+/**
+ * Encrypt a donation payload and attach its blind index tokens, computed
+ * from the plaintext before it is nullified.
+ *
+ * @param {CryptoKey|null} vaultKey
+ * @param {object} payload
+ * @returns {Promise<object>}
+ */
+export async function encryptDonationFields(vaultKey, payload) {
+  if (!vaultKey) return payload;
+  const blindIndex = await donationBlindIndex(vaultKey, payload);
+  const encrypted = await encryptPayloadFields(vaultKey, payload, DONATION_SENSITIVE_FIELDS);
+  return { ...encrypted, blind_index: blindIndex };
+}
+
 /**
  * Decrypt `encrypted_payload` on items that have `is_encrypted` set.
  * Mutates items in-place and returns the array.
@@ -89,7 +104,7 @@ export async function encryptCharityPayload(payload, label) {
 export async function encryptDonationPayload(payload) {
   const userId = getCurrentUserId();
   const vaultKey = await ensureVaultKey(userId);
-  return encryptPayloadFields(vaultKey, payload, DONATION_SENSITIVE_FIELDS);
+  return encryptDonationFields(vaultKey, payload);
 }
 
 /**
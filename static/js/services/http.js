@@ -1,6 +1,46 @@
+const IDEMPOTENT_RETRY_DELAYS_MS = [500, 2000];
+
+let refreshInFlight = null;
+
+/**
+ * Trades the refresh cookie for fresh session cookies. A refresh token works
+ * once and presenting it twice ends the session, so callers in this tab share
+ * one request and tabs take turns through a lock. A tab that waited on the
+ * lock sees the CSRF cookie change when another tab has already refreshed.
+ */
+export function refreshSession() {
+  if (!refreshInFlight) {
+    const csrfBefore = getCookie('csrf_token');
+    const run = async () => {
+      if (csrfBefore && getCookie('csrf_token') !== csrfBefore) return true;
+      try {
+        const res = await fetch('/auth/refresh', { method: 'POST', credentials: 'include' });
+        return res.ok;
+      } catch (e) {
+        return false;
+      }
+    };
+    const locks = typeof navigator !== 'undefined' ? navigator.locks : undefined;
+    refreshInFlight = (locks ? locks.request('session-refresh', run) : run()).finally(() => {
+      refreshInFlight = null;
+    });
+  }
+  return refreshInFlight;
+}
+
 export async function apiJson(path, options = {}) {
-  const method = (options.method || 'GET').toUpperCase();
-  const headers = { ...options.headers };
+  const res = await apiFetch(path, options);
+  // Access cookies are short-lived; renew once and retry before reporting 401.
+  if (res.status === 401 && isApiPath(path) && (await refreshSession())) {
+    return readResponse(await apiFetch(path, options));
+  }
+  return readResponse(res);
+}
+
+async function apiFetch(path, options) {
+  const { idempotent, ...fetchOptions } = options;
+  const method = (fetchOptions.method || 'GET').toUpperCase();
+  const headers = { ...fetchOptions.headers };
 
   if (isApiMutationRequest(path, method)) {
     const token = getCookie('csrf_token');
@@ -9,12 +49,19 @@ export async function apiJson(path, options = {}) {
     }
   }
 
-  const res = await fetch(path, {
+  // Retried requests reuse one key so the server replays instead of repeating the write.
+  if (idempotent && isApiMutationRequest(path, method)) {
+    headers['Idempotency-Key'] = crypto.randomUUID();
+  }
+
+  return fetchWithRetry(path, {
     credentials: 'include',
-    ...options,
+    ...fetchOptions,
     headers,
-  });
+  }, idempotent ? IDEMPOTENT_RETRY_DELAYS_MS : []);
+}
 
+async function readResponse(res) {
   let data = null;
   const contentType = res.headers.get('content-type') || '';
   if (contentType.includes('application/json')) {
@@ -34,8 +81,23 @@ export async function apiJson(path, options = {}) {
   return { res, data };
 }
 
+async function fetchWithRetry(path, init, retryDelays) {
+  for (let attempt = 0; ; attempt++) {
+    try {
+      return await fetch(path, init);
+    } catch (e) {
+      if (attempt >= retryDelays.length) throw e;
+      await new Promise((resolve) => setTimeout(resolve, retryDelays[attempt]));
+    }
+  }
+}
+
 function isApiMutationRequest(path, method) {
   if (!['POST', 'PUT', 'DELETE', 'PATCH'].includes(method)) return false;
+  return isApiPath(path);
+}
+
+function isApiPath(path) {
   if (typeof path !== 'string') return false;
 
   // Handle both relative paths and full URLs
@@ -0,0 +1,138 @@
+// static/js/services/passkeys.js
+// Server-verified WebAuthn ceremonies. The server hands out options with
+// binary fields base64url-encoded and expects the same encoding back.
+import { apiJson } from './http.js';
+
+export function toBase64Url(buffer) {
+  const bytes = new Uint8Array(buffer);
+  let binary = '';
+  for (const b of bytes) binary += String.fromCharCode(b);
+  return btoa(binary).replace(/\+/g, '-').replace(/\//g, '_').replace(/=+$/, '');
+}
+
+export function fromBase64Url(value) {
+  const base64 = String(value).replace(/-/g, '+').replace(/_/g, '/');
+  const padded = base64 + '='.repeat((4 - (base64.length % 4)) % 4);
+  return Uint8Array.from(atob(padded), (c) => c.charCodeAt(0));
+}
+
+function decodeDescriptors(list) {
+  return (Array.isArray(list) ? list : []).map((d) => ({ ...d, id: fromBase64Url(d.id) }));
+}
+
+function decodeCreationOptions(publicKey) {
+  return {
+    ...publicKey,
+    challenge: fromBase64Url(publicKey.challenge),
+    user: { ...publicKey.user, id: fromBase64Url(publicKey.user.id) },
+    excludeCredentials: decodeDescriptors(publicKey.excludeCredentials),
+  };
+}
+
+function decodeRequestOptions(publicKey) {
+  return {
+    ...publicKey,
+    challenge: fromBase64Url(publicKey.challenge),
+    allowCredentials: decodeDescriptors(publicKey.allowCredentials),
+  };
+}
+
+function encodeRegistration(credential) {
+  const response = credential.response;
+  return {
+    id: toBase64Url(credential.rawId),
+    response: {
+      clientDataJSON: toBase64Url(response.clientDataJSON),
+      attestationObject: toBase64Url(response.attestationObject),
+      transports: typeof response.getTransports === 'function' ? response.getTransports() : [],
+    },
+  };
+}
+
+function encodeAssertion(credential) {
+  const response = credential.response;
+  return {
+    id: toBase64Url(credential.rawId),
+    response: {
+      clientDataJSON: toBase64Url(response.clientDataJSON),
+      authenticatorData: toBase64Url(response.authenticatorData),
+      signature: toBase64Url(response.signature),
+      userHandle: response.userHandle ? toBase64Url(response.userHandle) : null,
+    },
+  };
+}
+
+/** Error carrying the HTTP status of a failed ceremony step. */
+export class PasskeyError extends Error {
+  constructor(message, status) {
+    super(message);
+    this.status = status;
+  }
+}
+
+async function postJson(path, body) {
+  const { res, data } = await apiJson(path, {
+    method: 'POST',
+    headers: { 'Content-Type': 'application/json' },
+    body: body === undefined ? undefined : JSON.stringify(body),
+  });
+  if (!res.ok) {
+    throw new PasskeyError(typeof data === 'string' && data ? data : `Request failed (${res.status})`, res.status);
+  }
+  return data;
+}
+
+async function getAssertion(optionsPath, verifyPath) {
+  const options = await postJson(optionsPath);
+  const assertion = await navigator.credentials.get({ publicKey: decodeRequestOptions(options.publicKey) });
+  if (!assertion) throw new PasskeyError('Passkey prompt was dismissed', 0);
+  const data = await postJson(verifyPath, {
+    challenge_id: options.challenge_id,
+    credential: encodeAssertion(assertion),
+  });
+  return { data, rawId: assertion.rawId };
+}
+
+/**
+ * Registers a new passkey for the signed-in user.
+ * Returns { passkey, rawId }.
+ */
+export async function registerPasskey(name) {
+  const options = await postJson('/api/me/passkeys/options');
+  const credential = await navigator.credentials.create({ publicKey: decodeCreationOptions(options.publicKey) });
+  if (!credential) throw new PasskeyError('Passkey prompt was dismissed', 0);
+  const passkey = await postJson('/api/me/passkeys', {
+    challenge_id: options.challenge_id,
+    name: name || null,
+    credential: encodeRegistration(credential),
+  });
+  return { passkey, rawId: credential.rawId };
+}
+
+/** Usernameless sign-in. Resolves to the `{ user }` auth response. */
+export async function signInWithPasskey() {
+  const { data } = await getAssertion('/auth/passkey/options', '/auth/passkey/login');
+  return data;
+}
+
+/**
+ * Proves possession of the vault passkey to the server.
+ * Returns { rawId, vaultCredentialId }.
+ */
+export async function unlockVaultWithPasskey() {
+  const { data, rawId } = await getAssertion('/api/me/vault/options', '/api/me/vault/unlock');
+  return { rawId, vaultCredentialId: data.vault_credential_id };
+}
+
+export async function listPasskeys() {
+  const { res, data } = await apiJson('/api/me/passkeys');
+  if (!res.ok || !data) throw new PasskeyError('Failed to load passkeys', res.status);
+  return Array.isArray(data.passkeys) ? data.passkeys : [];
+}
+
+export async function deletePasskey(id) {
+  const { res, data } = await apiJson(`/api/me/passkeys/${encodeURIComponent(id)}`, { method: 'DELETE' });
+  if (!res.ok) {
+    throw new PasskeyError(typeof data === 'string' && data ? data : 'Failed to remove passkey', res.status);
+  }
+}
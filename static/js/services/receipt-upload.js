@@ -1,7 +1,13 @@
 import { apiJson } from './http.js';
-import { ensureVaultKey, encryptData, encryptBinaryData } from './crypto.js';
+import { ensureVaultKey, encryptData, encryptBinaryData, exportVaultKey } from './crypto.js';
 import { getCurrentUserId } from './current-user.js';
 
+// Larger files go up in parts, so a dropped mobile connection only costs one part.
+export const MULTIPART_THRESHOLD_BYTES = 8 * 1024 * 1024;
+const PART_UPLOAD_ATTEMPTS = 3;
+// The server signs at most this many part URLs per request.
+const PART_URL_BATCH = 100;
+
 export function isImageReceipt(contentType) {
   return contentType === 'image/jpeg' || contentType === 'image/png';
 }
@@ -19,6 +25,37 @@ export async function requestReceiptUpload(fileType) {
   return data;
 }
 
+// A presigned POST whose policy pins the key prefix, content type and size.
+export async function requestReceiptUploadForm(fileType, size) {
+  const { res, data } = await apiJson('/api/receipts/upload-form', {
+    method: 'POST',
+    headers: { 'Content-Type': 'application/json' },
+    body: JSON.stringify({ file_type: fileType, size }),
+  });
+
+  if (!res.ok) {
+    throw new Error(typeof data === 'string' ? data : 'Failed to request upload form');
+  }
+  return data;
+}
+
+// Storage ignores fields after the file, so it goes last.
+export function buildReceiptUploadForm(fields, file) {
+  const form = new FormData();
+  for (const [name, value] of Object.entries(fields)) {
+    form.append(name, value);
+  }
+  form.append('file', file);
+  return form;
+}
+
+export async function postReceiptForm(uploadUrl, fields, file) {
+  const res = await fetch(uploadUrl, { method: 'POST', body: buildReceiptUploadForm(fields, file) });
+  if (!res.ok) {
+    throw new Error('Receipt upload failed');
+  }
+}
+
 export async function uploadReceiptBinary(uploadUrl, file) {
   const res = await fetch(uploadUrl, {
     method: 'PUT',
@@ -31,6 +68,81 @@ export async function uploadReceiptBinary(uploadUrl, file) {
   }
 }
 
+// Byte ranges of each part, numbered from 1 as the storage API expects.
+export function planUploadParts(size, partSize) {
+  const parts = [];
+  for (let start = 0, partNumber = 1; start < size; start += partSize, partNumber += 1) {
+    parts.push({ partNumber, start, end: Math.min(start + partSize, size) });
+  }
+  return parts;
+}
+
+async function uploadPart(url, blob) {
+  let lastError = null;
+  for (let attempt = 0; attempt < PART_UPLOAD_ATTEMPTS; attempt += 1) {
+    try {
+      const res = await fetch(url, { method: 'PUT', body: blob });
+      const etag = res.headers.get('ETag');
+      if (res.ok && etag) return etag;
+      lastError = new Error(res.ok ? 'Storage did not return an ETag' : 'Receipt part upload failed');
+    } catch (err) {
+      lastError = err;
+    }
+  }
+  throw lastError;
+}
+
+async function multipartRequest(path, options, failure) {
+  const { res, data } = await apiJson(path, {
+    headers: { 'Content-Type': 'application/json' },
+    ...options,
+  });
+  if (!res.ok) {
+    throw new Error(typeof data === 'string' ? data : failure);
+  }
+  return data;
+}
+
+export async function uploadReceiptMultipart(file) {
+  const upload = await multipartRequest(
+    '/api/receipts/multipart',
+    {
+      method: 'POST',
+      body: JSON.stringify({ file_type: file.type || 'application/octet-stream', size: file.size }),
+    },
+    'Failed to start upload',
+  );
+  const uploadPath = `/api/receipts/multipart/${encodeURIComponent(upload.id)}`;
+
+  try {
+    const ranges = planUploadParts(file.size, upload.part_size);
+    const completed = [];
+    for (let i = 0; i < ranges.length; i += PART_URL_BATCH) {
+      const batch = ranges.slice(i, i + PART_URL_BATCH);
+      const signed = await multipartRequest(
+        `${uploadPath}/parts`,
+        { method: 'POST', body: JSON.stringify({ part_numbers: batch.map((part) => part.partNumber) }) },
+        'Failed to request part upload URLs',
+      );
+      const urls = new Map(signed.parts.map((part) => [part.part_number, part.url]));
+      for (const part of batch) {
+        const etag = await uploadPart(urls.get(part.partNumber), file.slice(part.start, part.end));
+        completed.push({ part_number: part.partNumber, etag });
+      }
+    }
+    const result = await multipartRequest(
+      `${uploadPath}/complete`,
+      { method: 'POST', body: JSON.stringify({ parts: completed }) },
+      'Failed to complete upload',
+    );
+    return result.key;
+  } catch (err) {
+    // Best effort: the server aborts stale uploads on its own too.
+    apiJson(uploadPath, { method: 'DELETE' }).catch(() => {});
+    throw err;
+  }
+}
+
 export async function uploadReceiptToStorage(file) {
   const userId = getCurrentUserId();
   const vaultKey = await ensureVaultKey(userId);
@@ -51,11 +163,17 @@ export async function uploadReceiptToStorage(file) {
     encryptedPayload = await encryptData(vaultKey, { file_name: file.name });
   }
 
-  const uploadData = await requestReceiptUpload(uploadFile.type || 'application/octet-stream');
-  await uploadReceiptBinary(uploadData.upload_url, uploadFile);
-  
+  let key;
+  if (uploadFile.size > MULTIPART_THRESHOLD_BYTES) {
+    key = await uploadReceiptMultipart(uploadFile);
+  } else {
+    const form = await requestReceiptUploadForm(uploadFile.type || 'application/octet-stream', uploadFile.size);
+    await postReceiptForm(form.upload_url, form.fields, uploadFile);
+    key = form.key;
+  }
+
   return {
-    key: uploadData.key,
+    key,
     file_name: isEncrypted ? null : file.name,
     content_type: file.type,
     size: uploadFile.size,
@@ -77,19 +195,54 @@ export async function confirmReceiptUpload(uploadedReceipt, donationId) {
       is_encrypted: uploadedReceipt.is_encrypted,
       encrypted_payload: uploadedReceipt.encrypted_payload,
     }),
+    idempotent: true,
   });
 
   if (!res.ok) {
     throw new Error(typeof data === 'string' ? data : 'Failed to confirm receipt');
   }
 
+  // A file the user already attached elsewhere is stored once; the receipt
+  // then points at the existing object.
   return {
     ...uploadedReceipt,
     donation_id: donationId,
     id: data && data.id ? data.id : null,
+    key: data && data.key ? data.key : uploadedReceipt.key,
+    duplicate_of: data && data.duplicate_of ? data.duplicate_of : null,
   };
 }
 
+export async function sha256Hex(buffer) {
+  const digest = new Uint8Array(await crypto.subtle.digest('SHA-256', buffer));
+  return Array.from(digest, (byte) => byte.toString(16).padStart(2, '0')).join('');
+}
+
+// Downloads a receipt and checks it against the hash recorded when it was
+// confirmed; receipts from before hashing have none and are not checked.
+// Returns an object URL for the verified bytes.
+export async function fetchVerifiedReceipt(key) {
+  const { res, data } = await apiJson('/api/receipts/presign', {
+    method: 'POST',
+    headers: { 'Content-Type': 'application/json' },
+    body: JSON.stringify({ key, action: 'download' }),
+  });
+  if (!res.ok) {
+    throw new Error(typeof data === 'string' ? data : 'Failed to request download URL');
+  }
+
+  const download = await fetch(data.download_url);
+  if (!download.ok) {
+    throw new Error('Receipt download failed');
+  }
+  const bytes = await download.arrayBuffer();
+  if (data.content_hash && (await sha256Hex(bytes)) !== data.content_hash) {
+    throw new Error('Receipt does not match the file that was uploaded');
+  }
+  const type = download.headers.get('Content-Type') || 'application/octet-stream';
+  return URL.createObjectURL(new Blob([bytes], { type }));
+}
+
 export function normalizeReceiptAnalysis(data) {
   if (!data || typeof data !== 'object') return null;
 
@@ -130,9 +283,7 @@ export async function analyzeReceipt(payload) {
   const headers = { 'Content-Type': 'application/json' };
 
   if (vaultKey) {
-    // Export vault key to base64 for header
-    const rawKey = await crypto.subtle.exportKey('raw', vaultKey);
-    headers['X-Vault-Key'] = btoa(String.fromCharCode(...new Uint8Array(rawKey)));
+    headers['X-Vault-Key'] = await exportVaultKey(vaultKey);
   }
 
   const { res, data } = await apiJson('/api/receipts/ocr', {
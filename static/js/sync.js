@@ -1,10 +1,11 @@
 import db from './db.js';
 import { getCurrentUser, getCurrentUserId, setCurrentUser } from './services/current-user.js';
-import { apiJson } from './services/http.js';
+import { apiJson, refreshSession } from './services/http.js';
 import { decryptData, ensureVaultKey } from './services/crypto.js';
 import {
+  encryptDonationFields,
   encryptPayloadFields,
-  DONATION_SENSITIVE_FIELDS,
+  CHARITY_SENSITIVE_FIELDS,
 } from './services/encrypt-transport.js';
 
 const API_BASE = '/api';
@@ -113,10 +114,12 @@ export const Sync = {
     const vaultKey = await ensureVaultKey(userId);
 
     const batch = {
+      charities: [],
       donations: [],
       receipts: [],
     };
     const taskIds = [];
+    const taskKeys = new Map();
     const donationUpdates = [];
     const invalidTaskIds = [];
 
@@ -137,7 +140,40 @@ export const Sync = {
           continue;
         }
 
-        if (task.table === 'donations') {
+        if (task.table === 'charities') {
+          const charity = await db.charities.get(itemId);
+          if (charity || action === 'delete') {
+            let item = {
+              action,
+              id: itemId,
+              name: charity ? charity.name : null,
+              ein: charity ? charity.ein || null : null,
+              category: charity ? charity.category || null : null,
+              status: charity ? charity.status || null : null,
+              classification: charity ? charity.classification || null : null,
+              nonprofit_type: charity ? charity.nonprofit_type || null : null,
+              deductibility: charity ? charity.deductibility || null : null,
+              street: charity ? charity.street || null : null,
+              city: charity ? charity.city || null : null,
+              state: charity ? charity.state || null : null,
+              zip: charity ? charity.zip || null : null,
+              updated_at: charity ? charity.updated_at || null : null,
+            };
+
+            if (vaultKey && charity) {
+              item = await encryptPayloadFields(
+                vaultKey,
+                item,
+                CHARITY_SENSITIVE_FIELDS,
+                `Encrypted Charity (${itemId})`
+              );
+            }
+
+            batch.charities.push(item);
+            taskIds.push(task.id);
+            taskKeys.set(task.id, `charities:${itemId}`);
+          }
+        } else if (task.table === 'donations') {
           const donation = await db.donations.get(itemId);
           if (donation || action === 'delete') {
             const item = {
@@ -153,32 +189,41 @@ export const Sync = {
             };
 
             if (vaultKey && donation) {
-              const encrypted = await encryptPayloadFields(
-                vaultKey,
-                item,
-                DONATION_SENSITIVE_FIELDS
-              );
+              const encrypted = await encryptDonationFields(vaultKey, item);
               Object.assign(item, encrypted);
             }
 
             batch.donations.push(item);
             taskIds.push(task.id);
+            taskKeys.set(task.id, `donations:${itemId}`);
             if (action !== 'delete') {
               donationUpdates.push(itemId);
             }
           }
-        } else if (task.table === 'receipts' && action === 'create') {
+        } else if (task.table === 'receipts' && action === 'delete') {
+          batch.receipts.push({ action: 'delete', id: itemId });
+          taskIds.push(task.id);
+          taskKeys.set(task.id, `receipts:${itemId}`);
+        } else if (task.table === 'receipts' && (action === 'create' || action === 'update')) {
           const receipt = await db.receipts.get(itemId);
           if (receipt && receipt.donation_id) {
-            const item = {
-              action: 'create',
-              id: receipt.id,
-              donation_id: receipt.donation_id,
-              key: receipt.key,
-              file_name: receipt.file_name,
-              content_type: receipt.content_type,
-              size: receipt.size,
-            };
+            const item =
+              action === 'create'
+                ? {
+                    action: 'create',
+                    id: receipt.id,
+                    donation_id: receipt.donation_id,
+                    key: receipt.key,
+                    file_name: receipt.file_name,
+                    content_type: receipt.content_type,
+                    size: receipt.size,
+                  }
+                : {
+                    action: 'update',
+                    id: receipt.id,
+                    donation_id: receipt.donation_id,
+                    file_name: receipt.file_name,
+                  };
 
             if (vaultKey) {
               const receiptSensitiveFields = ['file_name', 'ocr_text', 'ocr_date', 'ocr_amount'];
@@ -192,6 +237,7 @@ export const Sync = {
 
             batch.receipts.push(item);
             taskIds.push(task.id);
+            taskKeys.set(task.id, `receipts:${receipt.id}`);
           }
         }
       } catch (e) {
@@ -203,7 +249,11 @@ export const Sync = {
       await db.sync_queue.bulkDelete(invalidTaskIds);
     }
 
-    if (batch.donations.length === 0 && batch.receipts.length === 0) {
+    if (
+      batch.charities.length === 0 &&
+      batch.donations.length === 0 &&
+      batch.receipts.length === 0
+    ) {
       if (invalidTaskIds.length > 0) {
         try {
           window.dispatchEvent(new CustomEvent('sync-queue-changed'));
@@ -215,17 +265,32 @@ export const Sync = {
     }
 
     try {
-      const { res } = await apiJson(`${API_BASE}/sync/batch`, {
+      const { res, data } = await apiJson(`${API_BASE}/sync/batch`, {
         method: 'POST',
         headers: { 'Content-Type': 'application/json' },
         body: JSON.stringify(batch),
       });
 
       if (res.ok) {
-        // Success: Clear all task IDs from the queue
-        await db.sync_queue.bulkDelete(taskIds);
+        // Items that failed transiently stay queued; rejected items are dropped so they
+        // do not block everything queued behind them.
+        const failures = data && Array.isArray(data.failures) ? data.failures : [];
+        const failedKeys = new Set();
+        const retryKeys = new Set();
+        for (const failure of failures) {
+          const key = `${failure.table}:${failure.id}`;
+          failedKeys.add(key);
+          if (failure.retryable) {
+            retryKeys.add(key);
+          } else {
+            console.warn('Sync item rejected by server', key, failure.reason);
+          }
+        }
+        const completedTaskIds = taskIds.filter((id) => !retryKeys.has(taskKeys.get(id)));
+        await db.sync_queue.bulkDelete(completedTaskIds);
         // Mark donations as synced
         for (const id of donationUpdates) {
+          if (failedKeys.has(`donations:${id}`)) continue;
           await db.donations.update(id, { sync_status: 'synced' });
         }
       } else {
@@ -250,63 +315,42 @@ export const Sync = {
     console.log('Pulling changes...');
     const userId = getCurrentUserId();
     if (!userId) return;
-    const lastKey = `last_sync_${userId}`;
-    const lastSync = localStorage.getItem(lastKey);
-    const url = lastSync
-      ? `${API_BASE}/donations?since=${encodeURIComponent(lastSync)}`
-      : `${API_BASE}/donations`;
+    const cursorKey = `sync_cursor_${userId}`;
+    let cursor = localStorage.getItem(cursorKey) || '';
     try {
-      const { res, data } = await apiJson(url);
-      if (!res.ok) {
-        if (res.status === 401) {
-          console.warn('Unauthorized during pull');
+      for (;;) {
+        const url = cursor
+          ? `${API_BASE}/sync/changes?cursor=${encodeURIComponent(cursor)}`
+          : `${API_BASE}/sync/changes`;
+        const { res, data } = await apiJson(url);
+        if (!res.ok) {
+          if (res.status === 401) {
+            console.warn('Unauthorized during pull');
+            return;
+          }
+          if (res.status === 400 && cursor) {
+            // Cursor from an older feed format; start over from the beginning.
+            localStorage.removeItem(cursorKey);
+            cursor = '';
+            continue;
+          }
+          console.warn('Pull failed', res.status);
           return;
         }
-        console.warn('Pull failed', res.status);
-        return;
-      }
-      if (data && Array.isArray(data.donations)) {
-        for (let remote of data.donations) {
+        const changes = data && Array.isArray(data.changes) ? data.changes : [];
+        for (const change of changes) {
           try {
-            if (remote.is_encrypted && remote.encrypted_payload) {
-              const vaultKey = await ensureVaultKey(userId);
-              if (vaultKey) {
-                try {
-                  const decrypted = await decryptData(vaultKey, remote.encrypted_payload);
-                  remote = { ...remote, ...decrypted };
-                } catch (e) {
-                  console.error('Failed to decrypt remote donation', remote.id, e);
-                  continue;
-                }
-              }
-            }
-
-            if (remote.deleted) {
-              await db.donations.delete(remote.id);
-            } else {
-              // Normalize date field if needed
-              const local = {
-                id: remote.id,
-                user_id: remote.user_id,
-                year: remote.year,
-                date: remote.date,
-                category: remote.category || 'money',
-                amount: remote.amount ?? 0,
-                charity_id: remote.charity_id,
-                notes: remote.notes || null,
-                sync_status: 'synced',
-                updated_at: remote.updated_at || null,
-                created_at: remote.created_at || null,
-              };
-              await db.donations.put(local);
-            }
+            await this.applyRemoteChange(userId, change);
           } catch (e) {
-            console.error('Failed to merge remote donation', remote.id, e);
+            console.error('Failed to merge remote change', change.table, change.id, e);
           }
         }
+        if (data && data.next_cursor) {
+          cursor = data.next_cursor;
+          localStorage.setItem(cursorKey, cursor);
+        }
+        if (!data || !data.has_more) break;
       }
-      // update last sync time
-      localStorage.setItem(lastKey, new Date().toISOString());
       try {
         window.dispatchEvent(new CustomEvent('sync-queue-changed'));
       } catch (e) {
@@ -317,6 +361,133 @@ export const Sync = {
     }
   },
 
+  // Coalesces bursts of server notifications into one pull, plus one more if
+  // anything arrived while it was running.
+  schedulePull() {
+    if (this.pullInFlight) {
+      this.pullQueued = true;
+      return this.pullInFlight;
+    }
+    this.pullInFlight = (async () => {
+      do {
+        this.pullQueued = false;
+        await this.pullChanges();
+      } while (this.pullQueued);
+    })().finally(() => {
+      this.pullInFlight = null;
+    });
+    return this.pullInFlight;
+  },
+
+  connectServerEvents() {
+    if (this.eventSource || typeof EventSource === 'undefined') return;
+    const source = new EventSource(`${API_BASE}/events`);
+    this.eventSource = source;
+    source.addEventListener('change', async (event) => {
+      let change = null;
+      try {
+        change = JSON.parse(event.data);
+      } catch (e) {
+        /* ignore */
+      }
+      await this.schedulePull();
+      try {
+        window.dispatchEvent(new CustomEvent('sync-remote-change', { detail: change }));
+      } catch (e) {
+        /* ignore */
+      }
+    });
+    // Sent when the server dropped notifications for this stream.
+    source.addEventListener('resync', () => this.schedulePull());
+    // Catch up on anything missed while disconnected; EventSource reconnects on its own.
+    source.addEventListener('ready', () => this.schedulePull());
+    // Except after a 401 once the access cookie has expired: renew it and reopen.
+    source.addEventListener('error', async () => {
+      if (source.readyState !== EventSource.CLOSED || this.eventSource !== source) return;
+      this.eventSource = null;
+      if (await refreshSession()) this.connectServerEvents();
+    });
+  },
+
+  disconnectServerEvents() {
+    if (!this.eventSource) return;
+    this.eventSource.close();
+    this.eventSource = null;
+  },
+
+  async applyRemoteChange(userId, change) {
+    if (!change || !change.table || !change.id) return;
+    if (change.table === 'users') {
+      // A queued local edit wins until it has been pushed.
+      if (change.op === 'upsert' && change.data && !loadPendingProfileUpdate(userId)) {
+        setCurrentUser({ ...(getCurrentUser() || {}), ...change.data, id: userId });
+      }
+      return;
+    }
+    if (!['donations', 'charities', 'receipts'].includes(change.table)) return;
+    if (change.op === 'delete' || !change.data) {
+      await db.table(change.table).delete(change.id);
+      return;
+    }
+
+    let remote = change.data;
+    if (remote.is_encrypted && remote.encrypted_payload) {
+      const vaultKey = await ensureVaultKey(userId);
+      if (vaultKey) {
+        try {
+          const decrypted = await decryptData(vaultKey, remote.encrypted_payload);
+          remote = { ...remote, ...decrypted };
+        } catch (e) {
+          console.error('Failed to decrypt remote change', change.table, remote.id, e);
+          return;
+        }
+      }
+    }
+
+    if (change.table === 'donations') {
+      await db.donations.put({
+        id: remote.id,
+        user_id: remote.user_id,
+        year: remote.year,
+        date: remote.date,
+        category: remote.category || 'money',
+        amount: remote.amount ?? 0,
+        charity_id: remote.charity_id,
+        notes: remote.notes || null,
+        sync_status: 'synced',
+        updated_at: remote.updated_at || null,
+        created_at: remote.created_at || null,
+      });
+    } else if (change.table === 'charities') {
+      await db.charities.put({
+        id: remote.id,
+        user_id: userId,
+        name: remote.name,
+        ein: remote.ein || '',
+        category: remote.category || null,
+        status: remote.status || null,
+        classification: remote.classification || null,
+        nonprofit_type: remote.nonprofit_type || null,
+        deductibility: remote.deductibility || null,
+        street: remote.street || null,
+        city: remote.city || null,
+        state: remote.state || null,
+        zip: remote.zip || null,
+        cached_at: Date.now(),
+      });
+    } else {
+      await db.receipts.put({
+        id: remote.id,
+        key: remote.key,
+        file_name: remote.file_name || null,
+        content_type: remote.content_type || null,
+        size: remote.size || null,
+        donation_id: remote.donation_id,
+        uploaded_at: remote.created_at || new Date().toISOString(),
+      });
+    }
+  },
+
   async queueAction(table, item, action) {
     if (!item || typeof item !== 'object') {
       console.warn('Skipping sync queue action with invalid item', table, action, item);
@@ -1,10 +1,12 @@
 /**
- * Login view — renders the sign-in page (Google + optional dev login)
+ * Login view — renders the sign-in page (Google, OpenID Connect providers,
+ * passkeys and optional dev login)
  * and handles post-login bootstrapping.
  */
 
 import { apiJson } from '../services/http.js';
 import { getCurrentUserId, setCurrentUser } from '../services/current-user.js';
+import { signInWithPasskey } from '../services/passkeys.js';
 import {
   setAuthenticated,
   consumeReturnTo,
@@ -48,6 +50,12 @@ export async function renderLoginView(deps) {
                     </div>
                   </div>
 
+                  <div id="oidc-login-buttons" class="hidden w-full max-w-[320px] space-y-3"></div>
+
+                  <div id="passkey-login-section" class="hidden w-full max-w-[320px]">
+                    <button id="passkey-login-btn" type="button" class="dt-btn-secondary flex w-full justify-center">Sign in with a passkey</button>
+                  </div>
+
                     <div id="traditional-login-section" class="hidden w-full space-y-6">
                         <div class="relative w-full">
                             <div class="absolute inset-0 flex items-center" aria-hidden="true">
@@ -99,6 +107,23 @@ export async function renderLoginView(deps) {
         document.getElementById('traditional-login-section').classList.remove('hidden');
       }
 
+      const oidcProviders = Array.isArray(config.oidc_providers) ? config.oidc_providers : [];
+      if (oidcProviders.length > 0) {
+        const container = document.getElementById('oidc-login-buttons');
+        for (const provider of oidcProviders) {
+          const link = document.createElement('a');
+          link.className = 'dt-btn-secondary flex w-full justify-center';
+          link.href = `/auth/login/${encodeURIComponent(provider.id)}`;
+          link.textContent = `Sign in with ${provider.name || provider.id}`;
+          container.appendChild(link);
+        }
+        container.classList.remove('hidden');
+      }
+
+      if (config.passkeys_enabled && window.PublicKeyCredential) {
+        document.getElementById('passkey-login-section').classList.remove('hidden');
+      }
+
       const gIdOnload = document.getElementById('g_id_onload');
       const gIdSignin = document.querySelector('.g_id_signin');
       if (config.google_enabled && config.google_client_id) {
@@ -156,6 +181,52 @@ export async function renderLoginView(deps) {
     console.warn('Could not fetch config', e);
   }
 
+  const completeLogin = async (data) => {
+    const profile = data && data.user ? data.user : data;
+
+    const previousUserId = getCurrentUserId();
+    if (profile) setCurrentUser(profile);
+    const nextUserId = profile && profile.id ? profile.id : null;
+    if (!previousUserId || (nextUserId && nextUserId !== previousUserId)) {
+      await clearUserCaches();
+    }
+    setAuthenticated(true);
+    restoreAppShellIfNeeded();
+    document.getElementById('nav-container').classList.remove('hidden', 'sm:hidden');
+    document.getElementById('auth-actions').classList.remove('hidden');
+
+    try {
+      await Sync.pushChanges();
+    } catch (err) {
+      console.warn('Initial push changes failed', err);
+    }
+    try {
+      await refreshCharitiesCache();
+    } catch (err) {
+      console.warn('Failed to refresh charities on login', err);
+    }
+    try {
+      await refreshDonationsFromServer();
+    } catch (err) {
+      console.warn('Failed to refresh donations on login', err);
+    }
+
+    await updateTotals();
+
+    const goto = consumeReturnTo() || '/';
+    await navigate(goto);
+  };
+
+  document.getElementById('passkey-login-btn').addEventListener('click', async () => {
+    try {
+      await completeLogin(await signInWithPasskey());
+    } catch (err) {
+      if (err && err.name === 'NotAllowedError') return;
+      console.error(err);
+      alert('Passkey sign-in failed');
+    }
+  });
+
   document.getElementById('login-form').addEventListener('submit', async (e) => {
     e.preventDefault();
     const username = e.target.username.value;
@@ -169,39 +240,7 @@ export async function renderLoginView(deps) {
       });
 
       if (res.ok) {
-        const profile = data && data.user ? data.user : data;
-
-        const previousUserId = getCurrentUserId();
-        if (profile) setCurrentUser(profile);
-        const nextUserId = profile && profile.id ? profile.id : null;
-        if (!previousUserId || (nextUserId && nextUserId !== previousUserId)) {
-          await clearUserCaches();
-        }
-        setAuthenticated(true);
-        restoreAppShellIfNeeded();
-        document.getElementById('nav-container').classList.remove('hidden', 'sm:hidden');
-        document.getElementById('auth-actions').classList.remove('hidden');
-
-        try {
-          await Sync.pushChanges();
-        } catch (err) {
-          console.warn('Initial push changes failed', err);
-        }
-        try {
-          await refreshCharitiesCache();
-        } catch (err) {
-          console.warn('Failed to refresh charities on login', err);
-        }
-        try {
-          await refreshDonationsFromServer();
-        } catch (err) {
-          console.warn('Failed to refresh donations on login', err);
-        }
-
-        await updateTotals();
-
-        const goto = consumeReturnTo() || '/';
-        await navigate(goto);
+        await completeLogin(data);
       } else {
         alert('Login failed');
       }
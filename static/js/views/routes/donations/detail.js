@@ -1,17 +1,4 @@
-async function getReceiptDownloadUrl(key, deps) {
-  const { getCookie } = deps;
-  const res = await fetch('/api/receipts/presign', {
-    method: 'POST',
-    headers: {
-      'Content-Type': 'application/json',
-      'X-CSRF-Token': getCookie('csrf_token'),
-    },
-    credentials: 'include',
-    body: JSON.stringify({ key, action: 'download' }),
-  });
-  const data = await res.json();
-  return data.download_url;
-}
+import { fetchVerifiedReceipt } from '../../../services/receipt-upload.js';
 
 export async function renderDonationViewRoute(donationId, deps) {
   const { db, escapeHtml, formatCurrency, navigate, calculateTaxEstimates, getCurrentUser } = deps;
@@ -116,10 +103,10 @@ export async function renderDonationViewRoute(donationId, deps) {
     btn.addEventListener('click', async (e) => {
       const key = e.currentTarget.dataset.key;
       try {
-        const url = await getReceiptDownloadUrl(key, deps);
+        const url = await fetchVerifiedReceipt(key);
         window.open(url, '_blank');
       } catch (err) {
-        alert('Failed to preview receipt');
+        alert(err.message || 'Failed to preview receipt');
       }
     });
   });
@@ -2,25 +2,11 @@ import {
   analyzeConfirmedReceipt,
   analyzeUploadedReceipt,
   confirmReceiptUpload,
+  fetchVerifiedReceipt,
   mapReceiptSuggestionToDonationDraft,
   uploadReceiptToStorage,
 } from '../../../services/receipt-upload.js';
 
-async function getReceiptDownloadUrl(key, deps) {
-  const { getCookie } = deps;
-  const res = await fetch('/api/receipts/presign', {
-    method: 'POST',
-    headers: {
-      'Content-Type': 'application/json',
-      'X-CSRF-Token': getCookie('csrf_token'),
-    },
-    credentials: 'include',
-    body: JSON.stringify({ key, action: 'download' }),
-  });
-  const data = await res.json();
-  return data.download_url;
-}
-
 function buildDonationFormHtml(
   { title, desc, submitLabel, categoryPrefill = 'money', existing = null },
   deps
@@ -306,10 +292,10 @@ async function bindDonationFormHandlers({ userId, charities, existingDonation },
       btn.addEventListener('click', async (e) => {
         const key = e.currentTarget.dataset.key;
         try {
-          const url = await getReceiptDownloadUrl(key, deps);
+          const url = await fetchVerifiedReceipt(key);
           window.open(url, '_blank');
         } catch (err) {
-          alert('Failed to preview receipt');
+          alert(err.message || 'Failed to preview receipt');
         }
       });
     });
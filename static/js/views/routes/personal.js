@@ -49,6 +49,193 @@ function renderRateOptionsRoute(brackets, currentRate) {
     .join('');
 }
 
+const ACCESS_TOKEN_SCOPES = [
+  { value: 'read', label: 'Read all data' },
+  { value: 'reports', label: 'Read reports' },
+  { value: 'import', label: 'Import donations' },
+  { value: 'write', label: 'Modify data' },
+];
+
+function renderAccessTokenRows(tokens, deps) {
+  if (!tokens.length) {
+    return '<p class="text-sm text-slate-500 dark:text-slate-400">No access tokens yet.</p>';
+  }
+  const now = Date.now();
+  return tokens
+    .map((t) => {
+      const expired = new Date(t.expires_at).getTime() <= now;
+      const state = t.revoked_at ? 'Revoked' : expired ? 'Expired' : 'Active';
+      const lastUsed = t.last_used_at ? new Date(t.last_used_at).toLocaleString() : 'Never used';
+      return `<div class="flex items-center justify-between gap-4 border-b border-slate-100 py-2 dark:border-slate-800">
+                <div class="text-sm">
+                  <p class="font-medium text-slate-900 dark:text-slate-100">${deps.escapeHtml(t.name)} <span class="text-xs text-slate-500">(${deps.escapeHtml(t.scopes.join(', '))})</span></p>
+                  <p class="text-xs text-slate-500 dark:text-slate-400">${state} · expires ${new Date(t.expires_at).toLocaleDateString()} · ${lastUsed}</p>
+                </div>
+                ${state === 'Active' ? `<button type="button" data-revoke-token="${deps.escapeHtml(t.id)}" class="dt-btn-secondary text-xs">Revoke</button>` : ''}
+              </div>`;
+    })
+    .join('');
+}
+
+async function loadAccessTokens(deps) {
+  const list = document.getElementById('access-token-list');
+  if (!list || !navigator.onLine) return;
+  try {
+    const { res, data } = await deps.apiJson('/api/me/tokens');
+    if (!res.ok || !data) throw new Error('Failed to load tokens');
+    list.innerHTML = renderAccessTokenRows(Array.isArray(data.tokens) ? data.tokens : [], deps);
+  } catch (e) {
+    console.warn('Failed to load access tokens', e);
+    list.innerHTML = '<p class="text-sm text-red-600">Could not load access tokens.</p>';
+  }
+}
+
+function renderAccountantRows(grants, deps) {
+  if (!grants.length) {
+    return '<p class="text-sm text-slate-500 dark:text-slate-400">No accountants invited.</p>';
+  }
+  return grants
+    .map((g) => {
+      const state = g.revoked_at ? 'Revoked' : g.accepted_at ? 'Active' : 'Invited';
+      const agi = g.allow_agi ? ' · AGI shared' : '';
+      return `<div class="flex items-center justify-between gap-4 border-b border-slate-100 py-2 dark:border-slate-800">
+                <div class="text-sm">
+                  <p class="font-medium text-slate-900 dark:text-slate-100">${deps.escapeHtml(g.accountant_email)}</p>
+                  <p class="text-xs text-slate-500 dark:text-slate-400">${state} · tax years ${deps.escapeHtml(g.tax_years.join(', '))}${agi}</p>
+                </div>
+                ${g.revoked_at ? '' : `<button type="button" data-revoke-grant="${deps.escapeHtml(g.id)}" class="dt-btn-secondary text-xs">Revoke</button>`}
+              </div>`;
+    })
+    .join('');
+}
+
+async function loadAccountants(deps) {
+  const list = document.getElementById('accountant-list');
+  if (!list || !navigator.onLine) return;
+  try {
+    const { res, data } = await deps.apiJson('/api/me/accountants');
+    if (!res.ok || !data) throw new Error('Failed to load accountants');
+    list.innerHTML = renderAccountantRows(Array.isArray(data.grants) ? data.grants : [], deps);
+  } catch (e) {
+    console.warn('Failed to load accountants', e);
+    list.innerHTML = '<p class="text-sm text-red-600">Could not load accountant access.</p>';
+  }
+}
+
+function renderClientRows(clients, deps) {
+  return clients
+    .map((c) => {
+      const exports = c.tax_years
+        .map(
+          (year) => `<span class="inline-flex gap-1 text-xs">${year}:
+                      <button type="button" data-client-export="${deps.escapeHtml(c.id)}" data-year="${year}" data-format="csv" class="underline">CSV</button>
+                      <button type="button" data-client-export="${deps.escapeHtml(c.id)}" data-year="${year}" data-format="txf" class="underline">TXF</button>
+                    </span>`
+        )
+        .join(' ');
+      return `<div class="border-b border-slate-100 py-2 text-sm dark:border-slate-800">
+                <p class="font-medium text-slate-900 dark:text-slate-100">${deps.escapeHtml(c.owner_name)} <span class="text-xs text-slate-500">${deps.escapeHtml(c.owner_email)}</span></p>
+                <div class="mt-1 flex flex-wrap gap-3 text-slate-600 dark:text-slate-300">${exports}</div>
+              </div>`;
+    })
+    .join('');
+}
+
+async function loadClients(deps) {
+  const panel = document.getElementById('client-panel');
+  const list = document.getElementById('client-list');
+  if (!panel || !list || !navigator.onLine) return;
+  try {
+    const { res, data } = await deps.apiJson('/api/clients');
+    if (!res.ok || !data) throw new Error('Failed to load clients');
+    const clients = Array.isArray(data.clients) ? data.clients : [];
+    // Most users are nobody's accountant; only show the panel when someone shared with them.
+    panel.classList.toggle('hidden', clients.length === 0);
+    list.innerHTML = renderClientRows(clients, deps);
+  } catch (e) {
+    console.warn('Failed to load clients', e);
+  }
+}
+
+async function downloadFile(url, filename) {
+  const res = await fetch(url, { credentials: 'include' });
+  if (!res.ok) throw new Error(`Download failed (${res.status})`);
+  const blobUrl = window.URL.createObjectURL(await res.blob());
+  const a = document.createElement('a');
+  a.href = blobUrl;
+  a.download = filename;
+  document.body.appendChild(a);
+  a.click();
+  window.URL.revokeObjectURL(blobUrl);
+  a.remove();
+}
+
+function renderSessionRows(sessions, deps) {
+  if (!sessions.length) {
+    return '<p class="text-sm text-slate-500 dark:text-slate-400">No active sessions.</p>';
+  }
+  return sessions
+    .map((s) => {
+      const seen = s.last_seen_at ? `last active ${new Date(s.last_seen_at).toLocaleString()}` : 'Not used yet';
+      const where = s.ip_address ? ` · ${deps.escapeHtml(s.ip_address)}` : '';
+      const current = s.current ? ' <span class="text-xs text-green-600 dark:text-green-400">(this device)</span>' : '';
+      return `<div class="flex items-center justify-between gap-4 border-b border-slate-100 py-2 dark:border-slate-800">
+                <div class="text-sm">
+                  <p class="font-medium text-slate-900 dark:text-slate-100" title="${deps.escapeHtml(s.user_agent || '')}">${deps.escapeHtml(s.device)}${current}</p>
+                  <p class="text-xs text-slate-500 dark:text-slate-400">Signed in ${new Date(s.created_at).toLocaleString()}${where} · ${seen}</p>
+                </div>
+                <button type="button" data-revoke-session="${deps.escapeHtml(s.id)}" data-current="${s.current ? 'true' : 'false'}" class="dt-btn-secondary text-xs">Sign out</button>
+              </div>`;
+    })
+    .join('');
+}
+
+async function loadSessions(deps) {
+  const list = document.getElementById('session-list');
+  if (!list || !navigator.onLine) return;
+  try {
+    const { res, data } = await deps.apiJson('/api/me/sessions');
+    if (!res.ok || !data) throw new Error('Failed to load sessions');
+    list.innerHTML = renderSessionRows(Array.isArray(data.sessions) ? data.sessions : [], deps);
+  } catch (e) {
+    console.warn('Failed to load sessions', e);
+    list.innerHTML = '<p class="text-sm text-red-600">Could not load sessions.</p>';
+  }
+}
+
+function renderPasskeyRows(passkeys, deps) {
+  if (!passkeys.length) {
+    return '<p class="text-sm text-slate-500 dark:text-slate-400">No passkeys yet.</p>';
+  }
+  return passkeys
+    .map((p) => {
+      const lastUsed = p.last_used_at ? `last used ${new Date(p.last_used_at).toLocaleString()}` : 'Never used';
+      return `<div class="flex items-center justify-between gap-4 border-b border-slate-100 py-2 dark:border-slate-800">
+                <div class="text-sm">
+                  <p class="font-medium text-slate-900 dark:text-slate-100">${deps.escapeHtml(p.name)}</p>
+                  <p class="text-xs text-slate-500 dark:text-slate-400">Added ${new Date(p.created_at).toLocaleDateString()} · ${lastUsed}</p>
+                </div>
+                ${
+                  p.id
+                    ? `<button type="button" data-remove-passkey="${deps.escapeHtml(p.id)}" class="dt-btn-secondary text-xs">Remove</button>`
+                    : '<span class="text-xs text-slate-500 dark:text-slate-400">Unlocks your vault</span>'
+                }
+              </div>`;
+    })
+    .join('');
+}
+
+async function loadPasskeys(deps) {
+  const list = document.getElementById('passkey-list');
+  if (!list || !navigator.onLine) return;
+  try {
+    list.innerHTML = renderPasskeyRows(await deps.listPasskeys(), deps);
+  } catch (e) {
+    console.warn('Failed to load passkeys', e);
+    list.innerHTML = '<p class="text-sm text-red-600">Could not load passkeys.</p>';
+  }
+}
+
 export async function renderPersonalInfoRoute(deps) {
   const root = document.getElementById('route-content') || document.getElementById('app');
   let profile = {
@@ -123,7 +310,8 @@ export async function renderPersonalInfoRoute(deps) {
                     </div>
                     <div>
                         <label class="dt-label">Email</label>
-                        <input id="profile-email" type="email" value="${deps.escapeHtml(profile.email)}" class="dt-input" />
+                        <input id="profile-email" type="email" value="${deps.escapeHtml(profile.email)}" class="dt-input" readonly aria-describedby="profile-email-hint" />
+                        <p id="profile-email-hint" class="mt-1 text-xs text-slate-500">Set by your sign-in provider.</p>
                     </div>
                     <div class="grid gap-4 sm:grid-cols-2">
                         <div>
@@ -172,7 +360,10 @@ export async function renderPersonalInfoRoute(deps) {
                         ? `<p class="flex items-center gap-2 text-sm font-medium text-green-600 dark:text-green-400">
                             ${iconSvg('lock', 'h-4 w-4')}
                             Your vault is locked and secure.
-                           </p>`
+                           </p>
+                           <button id="rotate-vault-key-btn" class="dt-btn-secondary mt-4">Rotate vault key</button>
+                           <p class="mt-2 text-xs text-slate-500">Re-encrypts everything under a new Passkey. Do this if your current Passkey may be compromised.</p>
+                           <p id="vault-rotation-status" class="mt-2 text-sm text-slate-600 dark:text-slate-300"></p>`
                         : `<button id="enable-privacy-btn" class="dt-btn-secondary flex items-center gap-2">
                             ${iconSvg('shield', 'h-4 w-4')}
                             Enable Full Privacy
@@ -182,6 +373,89 @@ export async function renderPersonalInfoRoute(deps) {
                     </div>
                     </div>
 
+                    <div class="dt-panel p-6">
+                    <h2 class="text-lg font-medium text-slate-900 dark:text-slate-100">Sessions</h2>
+                    <p class="mt-1 text-sm text-slate-600 dark:text-slate-300">Browsers and devices where you are signed in. Sign out of one, or of every session including this one.</p>
+                    <div id="session-list" class="mt-4"></div>
+                    <div class="mt-4">
+                    <button id="logout-everywhere-btn" class="dt-btn-secondary">Sign out everywhere</button>
+                    </div>
+                    </div>
+
+                    <div class="dt-panel p-6">
+                    <h2 class="text-lg font-medium text-slate-900 dark:text-slate-100">Passkeys</h2>
+                    <p class="mt-1 text-sm text-slate-600 dark:text-slate-300">Sign in with Touch ID, Face ID, Windows Hello or a security key instead of your identity provider.</p>
+                    <form id="passkey-form" class="mt-4 flex flex-wrap items-end gap-3">
+                    <div class="grow">
+                        <label class="dt-label">Passkey name</label>
+                        <input id="passkey-name" type="text" maxlength="255" class="dt-input" placeholder="My laptop" />
+                    </div>
+                    <button type="submit" class="dt-btn-secondary">Add passkey</button>
+                    </form>
+                    <div id="passkey-list" class="mt-4"></div>
+                    </div>
+
+                    <div class="dt-panel p-6">
+                    <h2 class="text-lg font-medium text-slate-900 dark:text-slate-100">API Access Tokens</h2>
+                    <p class="mt-1 text-sm text-slate-600 dark:text-slate-300">Create tokens for scripts and integrations. Send them as <code>Authorization: Bearer</code>; each token is shown only once.</p>
+                    <form id="access-token-form" class="mt-4 space-y-3">
+                    <div class="grid gap-4 sm:grid-cols-2">
+                        <div>
+                            <label class="dt-label">Token name</label>
+                            <input id="access-token-name" type="text" maxlength="255" class="dt-input" placeholder="Spreadsheet sync" />
+                        </div>
+                        <div>
+                            <label class="dt-label">Expires in (days)</label>
+                            <input id="access-token-expiry" type="number" min="1" max="365" value="90" class="dt-input" />
+                        </div>
+                    </div>
+                    <div class="flex flex-wrap gap-4">
+                        ${ACCESS_TOKEN_SCOPES.map(
+                          (scope) => `<label class="inline-flex items-center gap-2 text-sm text-slate-700 dark:text-slate-300">
+                            <input type="checkbox" name="access-token-scope" value="${scope.value}" class="h-4 w-4 rounded border-slate-300" />
+                            ${scope.label}
+                          </label>`
+                        ).join('')}
+                    </div>
+                    <div class="flex justify-end">
+                        <button type="submit" class="dt-btn-secondary">Create token</button>
+                    </div>
+                    </form>
+                    <div id="access-token-created" class="mt-3 hidden rounded-lg bg-amber-50 p-3 text-sm text-amber-900 dark:bg-amber-950 dark:text-amber-100"></div>
+                    <div id="access-token-list" class="mt-4"></div>
+                    </div>
+
+                    <div class="dt-panel p-6">
+                    <h2 class="text-lg font-medium text-slate-900 dark:text-slate-100">Accountant Access</h2>
+                    <p class="mt-1 text-sm text-slate-600 dark:text-slate-300">Give your accountant read-only access to chosen tax years. They sign in with the email you enter; every access is recorded in your audit log.</p>
+                    <form id="accountant-form" class="mt-4 space-y-3">
+                    <div class="grid gap-4 sm:grid-cols-2">
+                        <div>
+                            <label class="dt-label">Accountant email</label>
+                            <input id="accountant-email" type="email" maxlength="255" class="dt-input" placeholder="cpa@example.com" />
+                        </div>
+                        <div>
+                            <label class="dt-label">Tax years</label>
+                            <input id="accountant-years" type="text" class="dt-input" placeholder="${new Date().getFullYear() - 1}" />
+                        </div>
+                    </div>
+                    <label class="inline-flex items-center gap-2 text-sm text-slate-700 dark:text-slate-300">
+                        <input id="accountant-allow-agi" type="checkbox" class="h-4 w-4 rounded border-slate-300" />
+                        Share my AGI
+                    </label>
+                    <div class="flex justify-end">
+                        <button type="submit" class="dt-btn-secondary">Invite accountant</button>
+                    </div>
+                    </form>
+                    <div id="accountant-list" class="mt-4"></div>
+                    </div>
+
+                    <div id="client-panel" class="dt-panel hidden p-6">
+                    <h2 class="text-lg font-medium text-slate-900 dark:text-slate-100">Shared With Me</h2>
+                    <p class="mt-1 text-sm text-slate-600 dark:text-slate-300">Clients who gave you read-only access to their donations. Download a year's report for your tax software.</p>
+                    <div id="client-list" class="mt-4"></div>
+                    </div>
+
                     <div class="dt-panel p-6">
                     <h2 class="text-lg font-medium text-slate-900 dark:text-slate-100">Data Management</h2>
                     <p class="mt-1 text-sm text-slate-600 dark:text-slate-300">Backup your data to a restorable ZIP file or restore from a previous backup.</p>
@@ -216,6 +490,165 @@ export async function renderPersonalInfoRoute(deps) {
   const marginalRateEl = document.getElementById('profile-marginal-rate');
   let rateRequestCounter = 0;
 
+  loadAccessTokens(deps);
+  loadPasskeys(deps);
+  loadSessions(deps);
+  loadAccountants(deps);
+  loadClients(deps);
+
+  document.getElementById('logout-everywhere-btn')?.addEventListener('click', async () => {
+    if (!confirm('Sign out of all sessions on every device?')) return;
+    const { res } = await deps.apiJson('/api/me/logout-all', { method: 'POST' });
+    if (!res.ok) {
+      alert('Failed to sign out other sessions');
+      return;
+    }
+    await deps.handleLogout();
+  });
+
+  document.getElementById('session-list')?.addEventListener('click', async (e) => {
+    const sessionId = e.target?.dataset?.revokeSession;
+    if (!sessionId) return;
+    const isCurrent = e.target.dataset.current === 'true';
+    if (!confirm(isCurrent ? 'Sign out of this device?' : 'Sign out of this session?')) return;
+    const { res } = await deps.apiJson(`/api/me/sessions/${encodeURIComponent(sessionId)}`, {
+      method: 'DELETE',
+    });
+    if (!res.ok) {
+      alert('Failed to sign out session');
+    } else if (isCurrent) {
+      await deps.handleLogout();
+      return;
+    }
+    await loadSessions(deps);
+  });
+
+  document.getElementById('passkey-form')?.addEventListener('submit', async (e) => {
+    e.preventDefault();
+    if (!window.PublicKeyCredential) {
+      alert('Your browser does not support Passkeys (WebAuthn).');
+      return;
+    }
+    try {
+      await deps.registerPasskey(document.getElementById('passkey-name').value.trim());
+      e.target.reset();
+    } catch (err) {
+      if (err && err.name === 'NotAllowedError') return;
+      console.warn('Passkey registration failed', err);
+      alert(err && err.status === 409 ? 'That passkey is already registered.' : 'Failed to add passkey');
+    }
+    await loadPasskeys(deps);
+  });
+
+  document.getElementById('passkey-list')?.addEventListener('click', async (e) => {
+    const passkeyId = e.target?.dataset?.removePasskey;
+    if (!passkeyId || !confirm('Remove this passkey? You will no longer be able to sign in with it.')) return;
+    try {
+      await deps.deletePasskey(passkeyId);
+    } catch (err) {
+      alert(err && err.status === 409 ? 'This passkey unlocks your encrypted vault and cannot be removed.' : 'Failed to remove passkey');
+    }
+    await loadPasskeys(deps);
+  });
+
+  document.getElementById('access-token-form')?.addEventListener('submit', async (e) => {
+    e.preventDefault();
+    const name = document.getElementById('access-token-name').value.trim();
+    const scopes = Array.from(
+      document.querySelectorAll('input[name="access-token-scope"]:checked')
+    ).map((el) => el.value);
+    const expiresInDays = parseInt(document.getElementById('access-token-expiry').value, 10);
+    if (!name || scopes.length === 0) {
+      alert('Give the token a name and at least one scope.');
+      return;
+    }
+
+    const { res, data } = await deps.apiJson('/api/me/tokens', {
+      method: 'POST',
+      headers: { 'Content-Type': 'application/json' },
+      body: JSON.stringify({
+        name,
+        scopes,
+        expires_in_days: Number.isFinite(expiresInDays) ? expiresInDays : null,
+      }),
+    });
+    if (!res.ok || !data) {
+      alert(typeof data === 'string' ? data : 'Failed to create token');
+      return;
+    }
+
+    const created = document.getElementById('access-token-created');
+    created.innerHTML = `Copy this token now; it will not be shown again:<br><code class="break-all">${deps.escapeHtml(data.token)}</code>`;
+    created.classList.remove('hidden');
+    e.target.reset();
+    await loadAccessTokens(deps);
+  });
+
+  document.getElementById('access-token-list')?.addEventListener('click', async (e) => {
+    const tokenId = e.target?.dataset?.revokeToken;
+    if (!tokenId || !confirm('Revoke this token? Scripts using it will stop working.')) return;
+    const { res } = await deps.apiJson(`/api/me/tokens/${encodeURIComponent(tokenId)}`, {
+      method: 'DELETE',
+    });
+    if (!res.ok) alert('Failed to revoke token');
+    await loadAccessTokens(deps);
+  });
+
+  document.getElementById('accountant-form')?.addEventListener('submit', async (e) => {
+    e.preventDefault();
+    const email = document.getElementById('accountant-email').value.trim();
+    const taxYears = document
+      .getElementById('accountant-years')
+      .value.split(/[\s,]+/)
+      .map((y) => parseInt(y, 10))
+      .filter(Number.isFinite);
+    if (!email || taxYears.length === 0) {
+      alert('Enter the accountant\'s email and at least one tax year.');
+      return;
+    }
+    const { res, data } = await deps.apiJson('/api/me/accountants', {
+      method: 'POST',
+      headers: { 'Content-Type': 'application/json' },
+      body: JSON.stringify({
+        email,
+        tax_years: taxYears,
+        allow_agi: document.getElementById('accountant-allow-agi').checked,
+      }),
+    });
+    if (!res.ok) {
+      alert(typeof data === 'string' && data ? data : 'Failed to invite accountant');
+      return;
+    }
+    e.target.reset();
+    await loadAccountants(deps);
+  });
+
+  document.getElementById('accountant-list')?.addEventListener('click', async (e) => {
+    const grantId = e.target?.dataset?.revokeGrant;
+    if (!grantId || !confirm('Revoke this accountant\'s access?')) return;
+    const { res } = await deps.apiJson(`/api/me/accountants/${encodeURIComponent(grantId)}`, {
+      method: 'DELETE',
+    });
+    if (!res.ok) alert('Failed to revoke access');
+    await loadAccountants(deps);
+  });
+
+  document.getElementById('client-list')?.addEventListener('click', async (e) => {
+    const grantId = e.target?.dataset?.clientExport;
+    if (!grantId) return;
+    const { year, format } = e.target.dataset;
+    const path = format === 'txf' ? 'reports/export/txf' : 'reports/export';
+    try {
+      await downloadFile(
+        `/api/clients/${encodeURIComponent(grantId)}/${path}?year=${encodeURIComponent(year)}`,
+        `client-donations-${year}.${format}`
+      );
+    } catch (err) {
+      console.warn('Client export failed', err);
+      alert('Failed to download report');
+    }
+  });
+
   document.getElementById('backup-btn')?.addEventListener('click', async () => {
     try {
       const res = await fetch('/api/me/export', { credentials: 'include' });
@@ -335,7 +768,6 @@ export async function renderPersonalInfoRoute(deps) {
     e.preventDefault();
     const updated = {
       name: document.getElementById('profile-name').value.trim(),
-      email: document.getElementById('profile-email').value.trim(),
       filing_status: filingStatusEl.value,
       agi: parseFloat(agiEl.value || ''),
       marginal_tax_rate: parseFloat(marginalRateEl.value || ''),
@@ -364,6 +796,26 @@ export async function renderPersonalInfoRoute(deps) {
     }
   });
 
+  document.getElementById('rotate-vault-key-btn')?.addEventListener('click', async (event) => {
+    if (!confirm('Rotate your vault key? You will create a new Passkey, and everything is re-encrypted with it. Keep this page open until it finishes.')) {
+      return;
+    }
+    const button = event.currentTarget;
+    const status = document.getElementById('vault-rotation-status');
+    button.disabled = true;
+    try {
+      await deps.rotateVaultKey(deps.getCurrentUserId(), (rotation) => {
+        status.textContent = `Re-encrypted ${rotation.processed_items} of ${rotation.total_items} items…`;
+      });
+      alert('Vault key rotated. Use your new Passkey from now on.');
+      window.location.reload();
+    } catch (e) {
+      console.error('Vault key rotation failed', e);
+      status.textContent = `${e.message} Run it again to resume.`;
+      button.disabled = false;
+    }
+  });
+
   document.getElementById('enable-privacy-btn')?.addEventListener('click', async () => {
     if (!deps.isWebAuthnSupported()) {
       alert('Your browser does not support Passkeys (WebAuthn). Encryption cannot be enabled.');
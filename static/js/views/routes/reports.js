@@ -1,3 +1,6 @@
+import { ensureVaultKey, exportVaultKey } from '../../services/crypto.js';
+import { getCurrentUserId } from '../../services/current-user.js';
+
 export async function renderReportsRoute(deps) {
   const { apiJson } = deps;
   const root = document.getElementById('route-content') || document.getElementById('app');
@@ -30,10 +33,11 @@ export async function renderReportsRoute(deps) {
                     </div>
                     <button id="btn-export-csv" class="dt-btn-primary">Export CSV</button>
                     <button id="btn-export-tax-txf" class="dt-btn-secondary">Export TXF</button>
+                    <button id="btn-export-pdf" class="dt-btn-secondary">Export PDF</button>
                 </div>
             </div>
             <div class="dt-panel p-6">
-                <p class="text-sm text-slate-600 dark:text-slate-300">Select a donation year and export donations as CSV or TXF.</p>
+                <p class="text-sm text-slate-600 dark:text-slate-300">Select a donation year and export donations as CSV, TXF or PDF.</p>
             </div>
         </div>
     `;
@@ -41,17 +45,26 @@ export async function renderReportsRoute(deps) {
   const yearEl = document.getElementById('export-year');
   const csvBtn = document.getElementById('btn-export-csv');
   const taxTxfBtn = document.getElementById('btn-export-tax-txf');
+  const pdfBtn = document.getElementById('btn-export-pdf');
 
   if (!hasDonationYears) {
     csvBtn.disabled = true;
     taxTxfBtn.disabled = true;
+    pdfBtn.disabled = true;
   }
 
   const downloadReport = async (endpoint, extension) => {
     const year = yearEl.value;
     try {
+      // Encrypted donations are opened on the server for this one request.
+      const headers = {};
+      const vaultKey = await ensureVaultKey(getCurrentUserId());
+      if (vaultKey) {
+        headers['X-Vault-Key'] = await exportVaultKey(vaultKey);
+      }
       const res = await fetch(`${endpoint}?year=${encodeURIComponent(year)}`, {
         credentials: 'include',
+        headers,
       });
       if (!res.ok) throw new Error('Export failed');
       const blob = await res.blob();
@@ -76,4 +89,8 @@ export async function renderReportsRoute(deps) {
   taxTxfBtn.addEventListener('click', async () => {
     await downloadReport('/api/reports/export/txf', 'txf');
   });
+
+  pdfBtn.addEventListener('click', async () => {
+    await downloadReport('/api/reports/export/pdf', 'pdf');
+  });
 }
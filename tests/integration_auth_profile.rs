@@ -1,5 +1,5 @@
 use deductible_tracker::db;
-use deductible_tracker::db::models::UserProfileUpsert;
+use deductible_tracker::db::models::{NewAccessToken, NewPasskeyCredential, NewWebauthnChallenge, RefreshRotation, UserProfileUpsert};
 use oracle_rs::Value;
 use std::sync::OnceLock;
 use uuid::Uuid;
@@ -117,3 +117,871 @@ async fn oracle_repeated_profile_text_query_stays_stable() {
         .await
         .expect("delete repeated-query test data");
 }
+
+#[tokio::test]
+async fn oracle_access_tokens_resolve_until_revoked() {
+    let _guard = auth_profile_test_mutex().lock().await;
+    std::env::set_var("RUST_ENV", "development");
+
+    let pool = db::init_pool().await.expect("init pool");
+
+    let suffix = Uuid::new_v4().to_string();
+    let user_id = format!("auth-profile-pat-{suffix}");
+    let input = UserProfileUpsert {
+        user_id: user_id.clone(),
+        email: format!("pat-{suffix}@example.test"),
+        name: "Access Token Test".to_string(),
+        provider: "local".to_string(),
+        filing_status: None,
+        agi: None,
+        marginal_tax_rate: None,
+        itemize_deductions: None,
+        is_encrypted: None,
+        encrypted_payload: None,
+        vault_credential_id: None,
+    };
+    db::users::upsert_user_profile(&pool, &input)
+        .await
+        .expect("upsert user profile");
+
+    let token_id = Uuid::new_v4().simple().to_string();
+    let created = db::access_tokens::create_token(
+        &pool,
+        &NewAccessToken {
+            id: token_id.clone(),
+            user_id: user_id.clone(),
+            name: "reports script".to_string(),
+            scopes: vec!["read".to_string(), "reports".to_string()],
+            token_hash: "$argon2id$test-hash".to_string(),
+            expires_in_days: 30,
+        },
+    )
+    .await
+    .expect("create access token");
+    assert_eq!(created.scopes, vec!["read".to_string(), "reports".to_string()]);
+    assert!(created.expires_at > chrono::Utc::now() + chrono::Duration::days(29));
+    assert!(created.last_used_at.is_none());
+
+    let active = db::access_tokens::find_active_token(&pool, &token_id)
+        .await
+        .expect("find active token")
+        .expect("token is active");
+    assert_eq!(active.user_id, user_id);
+    assert_eq!(active.email, input.email);
+    assert_eq!(active.token_hash, "$argon2id$test-hash");
+
+    db::access_tokens::touch_token(&pool, &token_id)
+        .await
+        .expect("touch token");
+    let listed = db::access_tokens::list_tokens(&pool, &user_id)
+        .await
+        .expect("list tokens");
+    assert_eq!(listed.len(), 1);
+    assert!(listed[0].last_used_at.is_some());
+
+    assert!(!db::access_tokens::revoke_token(&pool, "someone-else", &token_id)
+        .await
+        .expect("revoke as another user"));
+    assert!(db::access_tokens::revoke_token(&pool, &user_id, &token_id)
+        .await
+        .expect("revoke token"));
+    assert!(db::access_tokens::find_active_token(&pool, &token_id)
+        .await
+        .expect("find revoked token")
+        .is_none());
+
+    // A security event revokes every token the user still has, and only those.
+    let second_id = Uuid::new_v4().simple().to_string();
+    db::access_tokens::create_token(
+        &pool,
+        &NewAccessToken {
+            id: second_id.clone(),
+            user_id: user_id.clone(),
+            name: "sync script".to_string(),
+            scopes: vec!["read".to_string()],
+            token_hash: "$argon2id$second-hash".to_string(),
+            expires_in_days: 30,
+        },
+    )
+    .await
+    .expect("create second access token");
+    assert_eq!(
+        db::access_tokens::revoke_all_tokens(&pool, &user_id)
+            .await
+            .expect("revoke all tokens"),
+        1
+    );
+    assert!(db::access_tokens::find_active_token(&pool, &second_id)
+        .await
+        .expect("find revoked token")
+        .is_none());
+
+    db::users::delete_user_data(&pool, &user_id)
+        .await
+        .expect("delete access token test data");
+}
+
+#[tokio::test]
+async fn oracle_passkeys_consume_challenges_and_advance_counters() {
+    let _guard = auth_profile_test_mutex().lock().await;
+    std::env::set_var("RUST_ENV", "development");
+
+    let pool = db::init_pool().await.expect("init pool");
+
+    let suffix = Uuid::new_v4().to_string();
+    let user_id = format!("auth-profile-passkey-{suffix}");
+    let input = UserProfileUpsert {
+        user_id: user_id.clone(),
+        email: format!("passkey-{suffix}@example.test"),
+        name: "Passkey Test".to_string(),
+        provider: "local".to_string(),
+        filing_status: None,
+        agi: None,
+        marginal_tax_rate: None,
+        itemize_deductions: None,
+        is_encrypted: None,
+        encrypted_payload: None,
+        vault_credential_id: None,
+    };
+    db::users::upsert_user_profile(&pool, &input)
+        .await
+        .expect("upsert user profile");
+
+    let challenge_id = Uuid::new_v4().simple().to_string();
+    db::passkeys::create_challenge(
+        &pool,
+        &NewWebauthnChallenge {
+            id: challenge_id.clone(),
+            user_id: Some(user_id.clone()),
+            purpose: "register".to_string(),
+            challenge: "Y2hhbGxlbmdl".to_string(),
+            ttl_seconds: 300,
+        },
+    )
+    .await
+    .expect("create challenge");
+    assert!(db::passkeys::take_challenge(&pool, &challenge_id, "login")
+        .await
+        .expect("take challenge for another purpose")
+        .is_none());
+    // The mismatched lookup above still consumed the challenge.
+    assert!(db::passkeys::take_challenge(&pool, &challenge_id, "register")
+        .await
+        .expect("take consumed challenge")
+        .is_none());
+
+    let credential_id = format!("cred-{}", Uuid::new_v4().simple());
+    let credential = NewPasskeyCredential {
+        id: credential_id.clone(),
+        user_id: user_id.clone(),
+        name: "Laptop".to_string(),
+        public_key: "cose-key".to_string(),
+        algorithm: -7,
+        sign_count: 3,
+        transports: vec!["internal".to_string(), "hybrid".to_string()],
+    };
+    assert!(db::passkeys::insert_credential(&pool, &credential)
+        .await
+        .expect("insert credential"));
+    assert!(!db::passkeys::insert_credential(&pool, &credential)
+        .await
+        .expect("insert duplicate credential"));
+
+    let listed = db::passkeys::list_credentials(&pool, &user_id)
+        .await
+        .expect("list credentials");
+    assert_eq!(listed.len(), 1);
+    assert_eq!(listed[0].transports, credential.transports);
+    assert!(listed[0].last_used_at.is_none());
+
+    assert!(db::passkeys::record_assertion(&pool, &credential_id, 3, 4)
+        .await
+        .expect("record assertion"));
+    // A second use racing on the same previous counter loses.
+    assert!(!db::passkeys::record_assertion(&pool, &credential_id, 3, 5)
+        .await
+        .expect("record stale assertion"));
+    let stored = db::passkeys::find_credential(&pool, &credential_id)
+        .await
+        .expect("find credential")
+        .expect("credential exists");
+    assert_eq!(stored.user_id, user_id);
+    assert_eq!(stored.sign_count, 4);
+
+    assert!(!db::passkeys::delete_credential(&pool, "someone-else", &credential_id)
+        .await
+        .expect("delete as another user"));
+    assert!(db::passkeys::delete_credential(&pool, &user_id, &credential_id)
+        .await
+        .expect("delete credential"));
+
+    db::users::delete_user_data(&pool, &user_id)
+        .await
+        .expect("delete passkey test data");
+}
+
+#[tokio::test]
+async fn oracle_session_revocations_persist_and_watermark_only_advances() {
+    let _guard = auth_profile_test_mutex().lock().await;
+    std::env::set_var("RUST_ENV", "development");
+
+    let pool = db::init_pool().await.expect("init pool");
+
+    let suffix = Uuid::new_v4().to_string();
+    let user_id = format!("auth-profile-revoke-{suffix}");
+    let input = UserProfileUpsert {
+        user_id: user_id.clone(),
+        email: format!("revoke-{suffix}@example.test"),
+        name: "Revocation Test".to_string(),
+        provider: "local".to_string(),
+        filing_status: None,
+        agi: None,
+        marginal_tax_rate: None,
+        itemize_deductions: None,
+        is_encrypted: None,
+        encrypted_payload: None,
+        vault_credential_id: None,
+    };
+    db::users::upsert_user_profile(&pool, &input)
+        .await
+        .expect("upsert user profile");
+
+    let jti = Uuid::new_v4().to_string();
+    let status = db::revocations::token_status(&pool, &jti, &jti, &user_id)
+        .await
+        .expect("status before revocation");
+    assert!(!status.revoked);
+    assert!(status.tokens_valid_after.is_none());
+
+    let expires_at = chrono::Utc::now().timestamp() + 3600;
+    db::revocations::revoke_token(&pool, &jti, &user_id, expires_at)
+        .await
+        .expect("revoke token");
+    db::revocations::revoke_token(&pool, &jti, &user_id, expires_at)
+        .await
+        .expect("revoke token twice");
+    assert!(db::revocations::token_status(&pool, &jti, &jti, &user_id)
+        .await
+        .expect("status after revocation")
+        .revoked);
+
+    let watermark = chrono::Utc::now().timestamp();
+    db::revocations::revoke_tokens_issued_before(&pool, &user_id, watermark)
+        .await
+        .expect("set watermark");
+    db::revocations::revoke_tokens_issued_before(&pool, &user_id, watermark - 600)
+        .await
+        .expect("older watermark is ignored");
+    let fresh_jti = Uuid::new_v4().to_string();
+    let status = db::revocations::token_status(&pool, &fresh_jti, &fresh_jti, &user_id)
+        .await
+        .expect("status with watermark");
+    assert!(!status.revoked);
+    assert_eq!(status.tokens_valid_after.map(|t| t.timestamp()), Some(watermark));
+
+    db::users::delete_user_data(&pool, &user_id)
+        .await
+        .expect("delete revocation test data");
+}
+
+#[tokio::test]
+async fn oracle_security_events_link_identities_lock_accounts_and_block_replays() {
+    let _guard = auth_profile_test_mutex().lock().await;
+    std::env::set_var("RUST_ENV", "development");
+
+    let pool = db::init_pool().await.expect("init pool");
+
+    let suffix = Uuid::new_v4().to_string();
+    let user_id = format!("auth-profile-risc-{suffix}");
+    let input = UserProfileUpsert {
+        user_id: user_id.clone(),
+        email: format!("risc-{suffix}@example.test"),
+        name: "Security Event Test".to_string(),
+        provider: "github".to_string(),
+        filing_status: None,
+        agi: None,
+        marginal_tax_rate: None,
+        itemize_deductions: None,
+        is_encrypted: None,
+        encrypted_payload: None,
+        vault_credential_id: None,
+    };
+    db::users::upsert_user_profile(&pool, &input)
+        .await
+        .expect("upsert user profile");
+
+    // Signed up with GitHub, later signed in with Google under the same email.
+    let google_sub = format!("google-{suffix}");
+    assert_eq!(
+        db::account_security::find_user_by_identity(&pool, "google", &google_sub)
+            .await
+            .expect("lookup before link"),
+        None
+    );
+    db::account_security::link_identity(&pool, "google", &google_sub, &user_id)
+        .await
+        .expect("link identity");
+    db::account_security::link_identity(&pool, "google", &google_sub, &user_id)
+        .await
+        .expect("link identity again");
+    assert_eq!(
+        db::account_security::find_user_by_identity(&pool, "google", &google_sub)
+            .await
+            .expect("lookup after link")
+            .as_deref(),
+        Some(user_id.as_str())
+    );
+    assert_eq!(
+        db::account_security::find_user_by_identity(&pool, "github", &user_id)
+            .await
+            .expect("lookup by legacy provider id")
+            .as_deref(),
+        Some(user_id.as_str())
+    );
+
+    assert!(!db::account_security::is_user_locked(&pool, &user_id).await.expect("lock state"));
+    db::account_security::lock_user(&pool, &user_id, "admin:manual")
+        .await
+        .expect("manual lock");
+    assert!(!db::account_security::unlock_user(&pool, &user_id, "risc:")
+        .await
+        .expect("unlock with other prefix"));
+    let fresh_jti = Uuid::new_v4().to_string();
+    assert!(db::revocations::token_status(&pool, &fresh_jti, &fresh_jti, &user_id)
+        .await
+        .expect("status while locked")
+        .locked);
+    db::account_security::lock_user(&pool, &user_id, "risc:hijacking")
+        .await
+        .expect("security event lock");
+    assert!(db::account_security::unlock_user(&pool, &user_id, "risc:")
+        .await
+        .expect("unlock"));
+    assert!(!db::account_security::is_user_locked(&pool, &user_id).await.expect("lock state after unlock"));
+
+    let jti = format!("risc-{suffix}");
+    let expires_at = chrono::Utc::now().timestamp() + 3600;
+    assert!(db::account_security::record_security_event(&pool, &jti, "https://accounts.google.com/", "sessions-revoked", expires_at)
+        .await
+        .expect("record event"));
+    assert!(!db::account_security::record_security_event(&pool, &jti, "https://accounts.google.com/", "sessions-revoked", expires_at)
+        .await
+        .expect("replayed event"));
+    db::account_security::forget_security_event(&pool, &jti)
+        .await
+        .expect("forget event");
+    assert!(db::account_security::record_security_event(&pool, &jti, "https://accounts.google.com/", "sessions-revoked", expires_at)
+        .await
+        .expect("record event after forget"));
+    db::account_security::forget_security_event(&pool, &jti)
+        .await
+        .expect("clean up event");
+
+    db::users::delete_user_data(&pool, &user_id)
+        .await
+        .expect("delete security event test data");
+    assert_eq!(
+        db::account_security::find_user_by_identity(&pool, "google", &google_sub)
+            .await
+            .expect("lookup after delete"),
+        None
+    );
+}
+
+#[tokio::test]
+async fn oracle_sessions_list_until_revoked_or_watermarked() {
+    let _guard = auth_profile_test_mutex().lock().await;
+    std::env::set_var("RUST_ENV", "development");
+
+    let pool = db::init_pool().await.expect("init pool");
+
+    let suffix = Uuid::new_v4().to_string();
+    let user_id = format!("auth-profile-sessions-{suffix}");
+    let input = UserProfileUpsert {
+        user_id: user_id.clone(),
+        email: format!("sessions-{suffix}@example.test"),
+        name: "Session Test".to_string(),
+        provider: "local".to_string(),
+        filing_status: None,
+        agi: None,
+        marginal_tax_rate: None,
+        itemize_deductions: None,
+        is_encrypted: None,
+        encrypted_payload: None,
+        vault_credential_id: None,
+    };
+    db::users::upsert_user_profile(&pool, &input)
+        .await
+        .expect("upsert user profile");
+
+    let now = chrono::Utc::now().timestamp();
+    let session = |jti: &str, issued_at: i64| db::models::NewUserSession {
+        jti: jti.to_string(),
+        user_id: user_id.clone(),
+        user_agent: Some("Mozilla/5.0 (X11; Linux x86_64) Firefox/128.0".to_string()),
+        ip_address: Some("203.0.113.7".to_string()),
+        issued_at,
+        expires_at: issued_at + 3600,
+    };
+    let laptop = Uuid::new_v4().to_string();
+    let phone = Uuid::new_v4().to_string();
+    db::sessions::create_session(&pool, &session(&laptop, now - 120))
+        .await
+        .expect("create laptop session");
+    db::sessions::create_session(&pool, &session(&phone, now - 60))
+        .await
+        .expect("create phone session");
+    db::sessions::touch_session(&pool, &phone).await.expect("touch session");
+
+    let sessions = db::sessions::list_sessions(&pool, &user_id).await.expect("list sessions");
+    assert_eq!(sessions.len(), 2);
+    assert!(sessions.iter().all(|s| s.ip_address.as_deref() == Some("203.0.113.7")));
+    let laptop_row = sessions.iter().find(|s| s.id == laptop).expect("laptop listed");
+    assert_eq!(laptop_row.created_at.timestamp(), now - 120);
+    assert_eq!(laptop_row.expires_at.timestamp(), now - 120 + 3600);
+
+    assert_eq!(
+        db::sessions::active_session_expiry(&pool, &user_id, &laptop)
+            .await
+            .expect("laptop expiry"),
+        Some(now - 120 + 3600)
+    );
+    assert_eq!(
+        db::sessions::active_session_expiry(&pool, "someone-else", &laptop)
+            .await
+            .expect("foreign expiry"),
+        None
+    );
+
+    db::revocations::revoke_token(&pool, &laptop, &user_id, now + 3600)
+        .await
+        .expect("revoke laptop");
+    let sessions = db::sessions::list_sessions(&pool, &user_id).await.expect("list after revoke");
+    assert_eq!(sessions.iter().map(|s| s.id.as_str()).collect::<Vec<_>>(), vec![phone.as_str()]);
+
+    db::revocations::revoke_tokens_issued_before(&pool, &user_id, now)
+        .await
+        .expect("sign out everywhere");
+    assert!(db::sessions::list_sessions(&pool, &user_id)
+        .await
+        .expect("list after watermark")
+        .is_empty());
+
+    db::users::delete_user_data(&pool, &user_id)
+        .await
+        .expect("delete session test data");
+}
+
+#[tokio::test]
+async fn oracle_refresh_tokens_rotate_once_and_revoke_family_on_reuse() {
+    let _guard = auth_profile_test_mutex().lock().await;
+    std::env::set_var("RUST_ENV", "development");
+
+    let pool = db::init_pool().await.expect("init pool");
+
+    let suffix = Uuid::new_v4().to_string();
+    let user_id = format!("auth-profile-refresh-{suffix}");
+    let input = UserProfileUpsert {
+        user_id: user_id.clone(),
+        email: format!("refresh-{suffix}@example.test"),
+        name: "Refresh Test".to_string(),
+        provider: "local".to_string(),
+        filing_status: None,
+        agi: None,
+        marginal_tax_rate: None,
+        itemize_deductions: None,
+        is_encrypted: None,
+        encrypted_payload: None,
+        vault_credential_id: None,
+    };
+    db::users::upsert_user_profile(&pool, &input)
+        .await
+        .expect("upsert user profile");
+
+    let now = chrono::Utc::now().timestamp();
+    let family = Uuid::new_v4().to_string();
+    db::sessions::create_session(
+        &pool,
+        &db::models::NewUserSession {
+            jti: family.clone(),
+            user_id: user_id.clone(),
+            user_agent: None,
+            ip_address: None,
+            issued_at: now,
+            expires_at: now + 3600,
+        },
+    )
+    .await
+    .expect("create session");
+    let first = Uuid::new_v4().simple().to_string();
+    db::refresh_tokens::create_token(
+        &pool,
+        &db::models::NewRefreshToken {
+            token_hash: first.clone(),
+            family_id: family.clone(),
+            user_id: user_id.clone(),
+            expires_at: now + 3600,
+        },
+    )
+    .await
+    .expect("create refresh token");
+
+    let rotated = RefreshRotation::Rotated { user_id: user_id.clone(), family_id: family.clone() };
+    let second = Uuid::new_v4().simple().to_string();
+    assert_eq!(
+        db::refresh_tokens::rotate_token(&pool, &first, &second, now + 7200).await.expect("rotate first"),
+        rotated
+    );
+    let sessions = db::sessions::list_sessions(&pool, &user_id).await.expect("list sessions");
+    assert_eq!(sessions[0].expires_at.timestamp(), now + 7200);
+
+    // Replaying the spent token is reported, and revoking the family kills its live successor.
+    let third = Uuid::new_v4().simple().to_string();
+    assert_eq!(
+        db::refresh_tokens::rotate_token(&pool, &first, &third, now + 7200).await.expect("replay first"),
+        RefreshRotation::Reused { user_id: user_id.clone(), family_id: family.clone() }
+    );
+    assert_eq!(
+        db::refresh_tokens::find_family(&pool, &second).await.expect("find family"),
+        Some((user_id.clone(), family.clone()))
+    );
+    db::revocations::revoke_token(&pool, &family, &user_id, now + 3600)
+        .await
+        .expect("revoke family");
+    assert_eq!(
+        db::refresh_tokens::rotate_token(&pool, &second, &third, now + 7200).await.expect("rotate revoked"),
+        RefreshRotation::Invalid
+    );
+    assert!(db::revocations::token_status(&pool, &Uuid::new_v4().to_string(), &family, &user_id)
+        .await
+        .expect("status of a token in the family")
+        .revoked);
+    assert_eq!(
+        db::refresh_tokens::rotate_token(&pool, "unknown", &third, now + 7200).await.expect("rotate unknown"),
+        RefreshRotation::Invalid
+    );
+
+    // "Log out everywhere" revokes refresh tokens as well.
+    let other_family = Uuid::new_v4().to_string();
+    let fourth = Uuid::new_v4().simple().to_string();
+    db::refresh_tokens::create_token(
+        &pool,
+        &db::models::NewRefreshToken {
+            token_hash: fourth.clone(),
+            family_id: other_family,
+            user_id: user_id.clone(),
+            expires_at: now + 3600,
+        },
+    )
+    .await
+    .expect("create second family");
+    db::revocations::revoke_tokens_issued_before(&pool, &user_id, chrono::Utc::now().timestamp() + 1)
+        .await
+        .expect("set watermark");
+    assert_eq!(
+        db::refresh_tokens::rotate_token(&pool, &fourth, &third, now + 7200).await.expect("rotate after watermark"),
+        RefreshRotation::Invalid
+    );
+
+    db::users::delete_user_data(&pool, &user_id)
+        .await
+        .expect("delete refresh test data");
+}
+
+#[tokio::test]
+async fn oracle_accountant_grants_resolve_by_email_until_revoked() {
+    let _guard = auth_profile_test_mutex().lock().await;
+    std::env::set_var("RUST_ENV", "development");
+
+    let pool = db::init_pool().await.expect("init pool");
+
+    let suffix = Uuid::new_v4().to_string();
+    let owner_id = format!("auth-profile-grant-owner-{suffix}");
+    let accountant_id = format!("auth-profile-grant-cpa-{suffix}");
+    let accountant_email = format!("cpa-{suffix}@example.test");
+    for (user_id, email, name) in [
+        (&owner_id, format!("owner-{suffix}@example.test"), "Grant Owner"),
+        (&accountant_id, accountant_email.clone(), "Grant Accountant"),
+    ] {
+        db::users::upsert_user_profile(
+            &pool,
+            &UserProfileUpsert {
+                user_id: user_id.clone(),
+                email,
+                name: name.to_string(),
+                provider: "local".to_string(),
+                filing_status: None,
+                agi: None,
+                marginal_tax_rate: None,
+                itemize_deductions: None,
+                is_encrypted: None,
+                encrypted_payload: None,
+                vault_credential_id: None,
+            },
+        )
+        .await
+        .expect("upsert user profile");
+    }
+
+    let input = db::models::NewAccountantGrant {
+        id: Uuid::new_v4().to_string(),
+        owner_user_id: owner_id.clone(),
+        accountant_email: accountant_email.clone(),
+        tax_years: vec![2024, 2025],
+        allow_agi: false,
+    };
+    let grant = db::accountant_grants::create_grant(&pool, &input)
+        .await
+        .expect("create grant")
+        .expect("first grant is created");
+    assert_eq!(grant.tax_years, vec![2024, 2025]);
+    assert!(!grant.allow_agi);
+    assert!(grant.accepted_at.is_none());
+    let duplicate = db::models::NewAccountantGrant { id: Uuid::new_v4().to_string(), ..input.clone() };
+    assert!(db::accountant_grants::create_grant(&pool, &duplicate)
+        .await
+        .expect("duplicate grant")
+        .is_none());
+
+    // Pending grants only open to an account whose provider verified the invited address.
+    assert!(db::accountant_grants::list_client_grants(&pool, &accountant_email, &accountant_id)
+        .await
+        .expect("list clients while unverified")
+        .is_empty());
+    db::account_security::set_email_verified(&pool, &accountant_id, true)
+        .await
+        .expect("verify accountant email");
+
+    let clients = db::accountant_grants::list_client_grants(&pool, &accountant_email, &accountant_id)
+        .await
+        .expect("list clients");
+    assert_eq!(clients.len(), 1);
+    assert_eq!(clients[0].owner_user_id, owner_id);
+    assert_eq!(clients[0].owner_name, "Grant Owner");
+    assert!(db::accountant_grants::find_client_grant(&pool, &grant.id, "someone@example.test", "someone")
+        .await
+        .expect("find as stranger")
+        .is_none());
+
+    db::accountant_grants::mark_accepted(&pool, &grant.id, &accountant_id)
+        .await
+        .expect("accept grant");
+    let grants = db::accountant_grants::list_grants(&pool, &owner_id).await.expect("list grants");
+    assert!(grants[0].accepted_at.is_some());
+
+    assert!(db::accountant_grants::revoke_grant(&pool, &owner_id, &grant.id)
+        .await
+        .expect("revoke grant"));
+    assert!(db::accountant_grants::find_client_grant(&pool, &grant.id, &accountant_email, &accountant_id)
+        .await
+        .expect("find after revoke")
+        .is_none());
+
+    for user_id in [&owner_id, &accountant_id] {
+        db::users::delete_user_data(&pool, user_id)
+            .await
+            .expect("delete grant test data");
+    }
+}
+
+#[tokio::test]
+async fn oracle_accountant_grants_refuse_an_email_changed_to_the_grantee() {
+    let _guard = auth_profile_test_mutex().lock().await;
+    std::env::set_var("RUST_ENV", "development");
+
+    let pool = db::init_pool().await.expect("init pool");
+
+    let suffix = Uuid::new_v4().to_string();
+    let owner_id = format!("auth-profile-takeover-owner-{suffix}");
+    let accountant_id = format!("auth-profile-takeover-cpa-{suffix}");
+    let intruder_id = format!("auth-profile-takeover-intruder-{suffix}");
+    let accountant_email = format!("cpa-{suffix}@example.test");
+    let profile = |user_id: &str, email: String| UserProfileUpsert {
+        user_id: user_id.to_string(),
+        email,
+        name: "Takeover Test".to_string(),
+        provider: "local".to_string(),
+        filing_status: None,
+        agi: None,
+        marginal_tax_rate: None,
+        itemize_deductions: None,
+        is_encrypted: None,
+        encrypted_payload: None,
+        vault_credential_id: None,
+    };
+    for (user_id, email) in [
+        (&owner_id, format!("owner-{suffix}@example.test")),
+        (&accountant_id, accountant_email.clone()),
+        (&intruder_id, format!("intruder-{suffix}@example.test")),
+    ] {
+        db::users::upsert_user_profile(&pool, &profile(user_id, email))
+            .await
+            .expect("upsert user profile");
+        db::account_security::set_email_verified(&pool, user_id, true)
+            .await
+            .expect("verify email");
+    }
+
+    let grant = db::accountant_grants::create_grant(
+        &pool,
+        &db::models::NewAccountantGrant {
+            id: Uuid::new_v4().to_string(),
+            owner_user_id: owner_id.clone(),
+            accountant_email: accountant_email.clone(),
+            tax_years: vec![2025],
+            allow_agi: true,
+        },
+    )
+    .await
+    .expect("create grant")
+    .expect("grant is created");
+
+    // Before acceptance: an address the provider never vouched for opens nothing.
+    let intruder_email = accountant_email.to_uppercase();
+    db::users::upsert_user_profile(&pool, &profile(&intruder_id, intruder_email.clone()))
+        .await
+        .expect("intruder takes the grantee's address in another case");
+    db::account_security::set_email_verified(&pool, &intruder_id, false)
+        .await
+        .expect("intruder email is unverified");
+    assert!(db::accountant_grants::find_client_grant(&pool, &grant.id, &intruder_email.to_lowercase(), &intruder_id)
+        .await
+        .expect("find as unverified intruder")
+        .is_none());
+
+    db::accountant_grants::find_client_grant(&pool, &grant.id, &accountant_email, &accountant_id)
+        .await
+        .expect("find as accountant")
+        .expect("accountant sees the grant");
+    db::accountant_grants::mark_accepted(&pool, &grant.id, &accountant_id)
+        .await
+        .expect("accept grant");
+
+    // After acceptance: the grant belongs to the accountant's account, whatever the email says.
+    db::account_security::set_email_verified(&pool, &intruder_id, true)
+        .await
+        .expect("intruder email verified");
+    assert!(db::accountant_grants::find_client_grant(&pool, &grant.id, &accountant_email, &intruder_id)
+        .await
+        .expect("find as intruder after acceptance")
+        .is_none());
+    assert!(db::accountant_grants::list_client_grants(&pool, &accountant_email, &intruder_id)
+        .await
+        .expect("list as intruder")
+        .is_empty());
+    assert!(db::accountant_grants::find_client_grant(&pool, &grant.id, &accountant_email, &accountant_id)
+        .await
+        .expect("find as accountant after acceptance")
+        .is_some());
+
+    for user_id in [&owner_id, &accountant_id, &intruder_id] {
+        db::users::delete_user_data(&pool, user_id)
+            .await
+            .expect("delete takeover test data");
+    }
+}
+
+#[tokio::test]
+async fn oracle_vault_rotations_track_progress_and_switch_credential() {
+    let _guard = auth_profile_test_mutex().lock().await;
+    std::env::set_var("RUST_ENV", "development");
+
+    let pool = db::init_pool().await.expect("init pool");
+
+    let user_id = format!("auth-profile-vault-{}", Uuid::new_v4());
+    let legacy_payload = "bGVnYWN5LXBheWxvYWQ=".to_string();
+    db::users::upsert_user_profile(
+        &pool,
+        &UserProfileUpsert {
+            user_id: user_id.clone(),
+            email: format!("{user_id}@example.test"),
+            name: "Vault Rotation".to_string(),
+            provider: "local".to_string(),
+            filing_status: None,
+            agi: None,
+            marginal_tax_rate: None,
+            itemize_deductions: None,
+            is_encrypted: Some(true),
+            encrypted_payload: Some(legacy_payload.clone()),
+            vault_credential_id: Some("old-credential".to_string()),
+        },
+    )
+    .await
+    .expect("upsert user profile");
+
+    let rotated_prefix = "v1:A256GCM:00112233aabbccdd:";
+    assert_eq!(
+        db::vault_rotations::count_pending_items(&pool, &user_id, rotated_prefix)
+            .await
+            .expect("count pending"),
+        1
+    );
+    let run = db::vault_rotations::create_rotation(
+        &pool,
+        &db::models::NewVaultKeyRotation {
+            id: Uuid::new_v4().to_string(),
+            user_id: user_id.clone(),
+            old_key_id: "ffeeddccbbaa9988".to_string(),
+            new_key_id: "00112233aabbccdd".to_string(),
+            new_credential_id: Some("new-credential".to_string()),
+            total_items: 1,
+        },
+    )
+    .await
+    .expect("create rotation");
+    assert_eq!(run.status, "running");
+
+    let items = db::vault_rotations::pending_items(&pool, &user_id, rotated_prefix, 10)
+        .await
+        .expect("pending items");
+    assert_eq!(items.len(), 1);
+    assert_eq!(items[0].table, "users");
+    assert_eq!(items[0].payload, legacy_payload);
+
+    let stale = db::models::VaultRotationItem { payload: "c3RhbGU=".to_string(), ..items[0].clone() };
+    let rotated = format!("{rotated_prefix}bmV3LXBheWxvYWQ=");
+    assert!(!db::vault_rotations::replace_item_payload(&pool, &user_id, &stale, &rotated)
+        .await
+        .expect("replace stale payload"));
+    assert!(db::vault_rotations::replace_item_payload(&pool, &user_id, &items[0], &rotated)
+        .await
+        .expect("replace payload"));
+    assert_eq!(
+        db::vault_rotations::count_pending_items(&pool, &user_id, rotated_prefix)
+            .await
+            .expect("count after rotation"),
+        0
+    );
+
+    let run = db::vault_rotations::record_progress(&pool, &run.id, 1).await.expect("record progress");
+    assert_eq!(run.processed_items, 1);
+    let run = db::vault_rotations::fail_rotation(&pool, &run.id, "users", &user_id, "Decryption error")
+        .await
+        .expect("fail rotation");
+    assert_eq!(run.status, "failed");
+    assert_eq!(run.failed_table.as_deref(), Some("users"));
+    let run = db::vault_rotations::resume_rotation(&pool, &run.id).await.expect("resume rotation");
+    assert_eq!(run.status, "running");
+    assert!(run.error.is_none());
+
+    let run = db::vault_rotations::complete_rotation(&pool, &user_id, &run.id)
+        .await
+        .expect("complete rotation");
+    assert_eq!(run.status, "completed");
+    assert!(run.completed_at.is_some());
+    let latest = db::vault_rotations::latest_rotation(&pool, &user_id)
+        .await
+        .expect("latest rotation")
+        .expect("rotation exists");
+    assert_eq!(latest.id, run.id);
+    let profile = db::users::get_user_profile(&pool, &user_id)
+        .await
+        .expect("load profile")
+        .expect("profile exists");
+    assert_eq!(profile.8.as_deref(), Some(rotated.as_str()));
+    assert_eq!(profile.9.as_deref(), Some("new-credential"));
+
+    db::users::delete_user_data(&pool, &user_id)
+        .await
+        .expect("delete vault rotation test data");
+}
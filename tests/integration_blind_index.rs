@@ -0,0 +1,150 @@
+use deductible_tracker::db;
+use deductible_tracker::db::models::{BlindIndexTokens, DonationPatch, NewCharity, NewDonation, UserProfileUpsert};
+use uuid::Uuid;
+
+fn tokens(charity: &str, year: &str, dedupe: &str) -> BlindIndexTokens {
+    BlindIndexTokens {
+        charity: Some(charity.to_string()),
+        year: Some(year.to_string()),
+        category: Some("cat-money".to_string()),
+        dedupe: Some(dedupe.to_string()),
+    }
+}
+
+async fn add_encrypted_donation(pool: &db::DbPool, user_id: &str, charity_id: &str, blind_index: BlindIndexTokens) -> String {
+    let id = Uuid::new_v4().to_string();
+    db::donations::add_donation(
+        pool,
+        &NewDonation {
+            id: id.clone(),
+            user_id: user_id.to_string(),
+            year: 2026,
+            date: chrono::Utc::now().date_naive(),
+            category: None,
+            charity_id: charity_id.to_string(),
+            amount: None,
+            notes: None,
+            is_encrypted: Some(true),
+            encrypted_payload: Some("v1:A256GCM:0000000000000000:c2VhbGVk".to_string()),
+            blind_index: Some(blind_index),
+            created_at: chrono::Utc::now(),
+        },
+    )
+    .await
+    .expect("add donation");
+    id
+}
+
+#[tokio::test]
+async fn oracle_blind_index_filters_lists_years_and_finds_duplicates() {
+    std::env::set_var("RUST_ENV", "development");
+    let pool = db::init_pool().await.expect("init pool");
+
+    let user_id = format!("blind-index-{}", Uuid::new_v4());
+    db::users::upsert_user_profile(
+        &pool,
+        &UserProfileUpsert {
+            user_id: user_id.clone(),
+            email: format!("{user_id}@example.test"),
+            name: "Blind Index".to_string(),
+            provider: "local".to_string(),
+            filing_status: None,
+            agi: None,
+            marginal_tax_rate: None,
+            itemize_deductions: None,
+            is_encrypted: Some(true),
+            encrypted_payload: None,
+            vault_credential_id: None,
+        },
+    )
+    .await
+    .expect("upsert user profile");
+    let charity_id = Uuid::new_v4().to_string();
+    db::charities::create_charity(
+        &pool,
+        &NewCharity {
+            id: charity_id.clone(),
+            user_id: user_id.clone(),
+            name: format!("encrypted-{charity_id}"),
+            ein: None,
+            category: None,
+            status: None,
+            classification: None,
+            nonprofit_type: None,
+            deductibility: None,
+            street: None,
+            city: None,
+            state: None,
+            zip: None,
+            is_encrypted: Some(true),
+            encrypted_payload: None,
+            created_at: chrono::Utc::now(),
+        },
+    )
+    .await
+    .expect("create charity");
+
+    let first = add_encrypted_donation(&pool, &user_id, &charity_id, tokens("ch-a", "yr-2025", "dup-1")).await;
+    let second = add_encrypted_donation(&pool, &user_id, &charity_id, tokens("ch-a", "yr-2025", "dup-1")).await;
+    let third = add_encrypted_donation(&pool, &user_id, &charity_id, tokens("ch-b", "yr-2024", "dup-2")).await;
+
+    let by_charity_and_year = db::blind_index::find_donations(
+        &pool,
+        &user_id,
+        &BlindIndexTokens { charity: Some("ch-a".to_string()), year: Some("yr-2025".to_string()), ..Default::default() },
+    )
+    .await
+    .expect("search by tokens");
+    let mut ids: Vec<_> = by_charity_and_year.iter().map(|d| d.id.clone()).collect();
+    ids.sort();
+    let mut expected = vec![first.clone(), second.clone()];
+    expected.sort();
+    assert_eq!(ids, expected);
+
+    let years = db::blind_index::list_year_tokens(&pool, &user_id).await.expect("year tokens");
+    assert_eq!(years, vec!["yr-2024".to_string(), "yr-2025".to_string()]);
+
+    let duplicates = db::blind_index::list_duplicates(&pool, &user_id).await.expect("duplicates");
+    assert_eq!(duplicates.len(), 1);
+    assert_eq!(duplicates[0].token, "dup-1");
+    assert_eq!(duplicates[0].donation_ids, vec![first.clone(), second.clone()]);
+
+    // Re-saving with new tokens replaces the old ones.
+    let updated = db::donations::update_donation(
+        &pool,
+        &DonationPatch {
+            user_id: user_id.clone(),
+            donation_id: third.clone(),
+            date_opt: None,
+            year_opt: None,
+            category_opt: None,
+            charity_id_opt: None,
+            amount_opt: None,
+            notes: None,
+            is_encrypted: Some(true),
+            encrypted_payload: None,
+            blind_index: Some(tokens("ch-a", "yr-2025", "dup-3")),
+            incoming_updated_at: None,
+        },
+    )
+    .await
+    .expect("update donation");
+    assert!(updated);
+    let years = db::blind_index::list_year_tokens(&pool, &user_id).await.expect("year tokens");
+    assert_eq!(years, vec!["yr-2025".to_string()]);
+
+    // Deleted donations drop out of every lookup.
+    assert!(db::donations::soft_delete_donation(&pool, &user_id, &second).await.expect("soft delete"));
+    let duplicates = db::blind_index::list_duplicates(&pool, &user_id).await.expect("duplicates");
+    assert!(duplicates.is_empty());
+    let by_charity = db::blind_index::find_donations(
+        &pool,
+        &user_id,
+        &BlindIndexTokens { charity: Some("ch-a".to_string()), ..Default::default() },
+    )
+    .await
+    .expect("search by charity");
+    assert_eq!(by_charity.len(), 2);
+
+    db::users::delete_user_data(&pool, &user_id).await.expect("delete test data");
+}
@@ -0,0 +1,53 @@
+mod field_crypto_test {
+    #![allow(dead_code)]
+
+    include!("../src/db/field_crypto.rs");
+
+    fn master(fill: u8) -> MasterKey {
+        MasterKey::from_base64(&BASE64_STANDARD.encode([fill; 32])).unwrap()
+    }
+
+    #[test]
+    fn master_keys_must_be_32_bytes_of_base64() {
+        assert!(MasterKey::from_base64("not base64!").is_err());
+        assert!(MasterKey::from_base64(&BASE64_STANDARD.encode([0u8; 16])).is_err());
+        assert_eq!(master(0).id(), "66687aadf862bd77");
+    }
+
+    #[test]
+    fn data_keys_unwrap_only_for_their_user_and_master() {
+        let (key, wrapped) = master(1).generate_data_key("user-a").unwrap();
+        let sealed = key.seal(DONATION_NOTES, "tithe").unwrap();
+
+        let unwrapped = master(1).unwrap_data_key("user-a", &wrapped, master(1).id()).unwrap();
+        assert_eq!(unwrapped.open(DONATION_NOTES, &sealed).unwrap(), "tithe");
+
+        assert!(master(1).unwrap_data_key("user-b", &wrapped, master(1).id()).is_err());
+        let err = master(2)
+            .unwrap_data_key("user-a", &wrapped, master(1).id())
+            .err()
+            .unwrap()
+            .to_string();
+        assert!(err.contains(master(1).id()), "{err}");
+    }
+
+    #[test]
+    fn sealed_values_are_prefixed_and_bound_to_their_column() {
+        let (key, _) = master(3).generate_data_key("user-a").unwrap();
+        let sealed = key.seal(CHARITY_STREET, "1 Main St").unwrap();
+        assert!(is_sealed(&sealed));
+        assert!(!sealed.contains("Main"));
+        assert_ne!(sealed, key.seal(CHARITY_STREET, "1 Main St").unwrap());
+
+        assert_eq!(key.open(CHARITY_STREET, &sealed).unwrap(), "1 Main St");
+        assert!(key.open(CHARITY_CITY, &sealed).is_err());
+    }
+
+    #[test]
+    fn plaintext_from_before_encryption_passes_through() {
+        let (key, _) = master(4).generate_data_key("user-a").unwrap();
+        assert!(!is_sealed("Springfield"));
+        assert_eq!(key.open(CHARITY_CITY, "Springfield").unwrap(), "Springfield");
+        assert!(key.open(USER_AGI, "enc:v1:bm90IHNlYWxlZA==").is_err());
+    }
+}
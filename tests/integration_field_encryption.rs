@@ -0,0 +1,148 @@
+use base64::prelude::{Engine as _, BASE64_STANDARD};
+use deductible_tracker::db;
+use deductible_tracker::db::models::{NewCharity, NewDonation, UserProfileUpsert};
+use oracle_rs::Value;
+use uuid::Uuid;
+
+// The master key is read once per process, so every test in this file sets the same one.
+fn enable_field_encryption() {
+    std::env::set_var("RUST_ENV", "development");
+    std::env::set_var("DATA_ENCRYPTION_KEY", BASE64_STANDARD.encode([42u8; 32]));
+}
+
+fn profile(user_id: &str, agi: Option<f64>) -> UserProfileUpsert {
+    UserProfileUpsert {
+        user_id: user_id.to_string(),
+        email: format!("{user_id}@example.test"),
+        name: "Field Encryption".to_string(),
+        provider: "local".to_string(),
+        filing_status: Some("single".to_string()),
+        agi,
+        marginal_tax_rate: Some(0.22),
+        itemize_deductions: Some(true),
+        is_encrypted: None,
+        encrypted_payload: None,
+        vault_credential_id: None,
+    }
+}
+
+#[tokio::test]
+async fn oracle_sensitive_columns_are_sealed_at_rest_and_migrated_in_place() {
+    enable_field_encryption();
+    let pool = db::init_pool().await.expect("init pool");
+    let db::DbPoolEnum::Oracle(oracle_pool) = &*pool;
+
+    let user_id = format!("field-encryption-{}", Uuid::new_v4());
+    db::users::upsert_user_profile(&pool, &profile(&user_id, Some(98_765.43)))
+        .await
+        .expect("upsert user profile");
+    let charity_id = Uuid::new_v4().to_string();
+    db::charities::create_charity(
+        &pool,
+        &NewCharity {
+            id: charity_id.clone(),
+            user_id: user_id.clone(),
+            name: "Sealed Address Fund".to_string(),
+            ein: None,
+            category: None,
+            status: None,
+            classification: None,
+            nonprofit_type: None,
+            deductibility: None,
+            street: Some("1 Main St".to_string()),
+            city: Some("Springfield".to_string()),
+            state: Some("IL".to_string()),
+            zip: Some("62701".to_string()),
+            is_encrypted: None,
+            encrypted_payload: None,
+            created_at: chrono::Utc::now(),
+        },
+    )
+    .await
+    .expect("create charity");
+    let donation_id = Uuid::new_v4().to_string();
+    db::donations::add_donation(
+        &pool,
+        &NewDonation {
+            id: donation_id.clone(),
+            user_id: user_id.clone(),
+            year: 2025,
+            date: chrono::NaiveDate::from_ymd_opt(2025, 3, 1).unwrap(),
+            category: Some("money".to_string()),
+            charity_id: charity_id.clone(),
+            amount: Some(50.0),
+            notes: Some("in memory of grandma".to_string()),
+            is_encrypted: None,
+            encrypted_payload: None,
+            blind_index: None,
+            created_at: chrono::Utc::now(),
+        },
+    )
+    .await
+    .expect("add donation");
+
+    // The API sees plaintext...
+    let stored = db::users::get_user_profile(&pool, &user_id).await.expect("get profile").expect("profile");
+    assert_eq!(stored.4, Some(98_765.43));
+    let charities = db::charities::list_charities(&pool, &user_id).await.expect("list charities");
+    assert_eq!(charities[0].street.as_deref(), Some("1 Main St"));
+    assert_eq!(charities[0].zip.as_deref(), Some("62701"));
+    let donations = db::donations::list_donations(&pool, &user_id, Some(2025)).await.expect("list donations");
+    assert_eq!(donations[0].notes.as_deref(), Some("in memory of grandma"));
+
+    // ...while Oracle holds only sealed values.
+    let conn = oracle_pool.get().await.expect("checkout oracle connection");
+    let rows = conn
+        .query(
+            "SELECT NVL2(u.agi, 'plain', 'empty'), u.agi_ciphertext, c.street, c.city, c.state, c.zip, d.notes FROM users u JOIN charities c ON c.user_id = u.id JOIN donations d ON d.user_id = u.id WHERE u.id = :1",
+            &[Value::from(user_id.clone())],
+        )
+        .await
+        .expect("read raw columns");
+    let row = rows.first().expect("raw row");
+    assert_eq!(row.get_string(0), Some("empty"));
+    for column in 1..=6 {
+        assert!(row.get_string(column).unwrap_or_default().starts_with("enc:v1:"), "column {column}");
+    }
+
+    // Rows written before the key was configured are sealed in place by the migration.
+    conn.execute(
+        "UPDATE users SET agi = 1234.5, agi_ciphertext = NULL WHERE id = :1",
+        &[Value::from(user_id.clone())],
+    )
+    .await
+    .expect("write legacy agi");
+    conn.execute(
+        "UPDATE donations SET notes = 'legacy note' WHERE id = :1",
+        &[Value::from(donation_id.clone())],
+    )
+    .await
+    .expect("write legacy note");
+    conn.commit().await.expect("commit legacy values");
+
+    let legacy = db::users::get_user_profile(&pool, &user_id).await.expect("get profile").expect("profile");
+    assert_eq!(legacy.4, Some(1234.5));
+
+    let report = db::oracle::encrypt_existing_columns(&conn).await.expect("encrypt existing columns");
+    assert!(report.agi_values >= 1);
+    assert!(report.donation_notes >= 1);
+
+    let rows = conn
+        .query(
+            "SELECT NVL2(agi, 'plain', 'empty'), agi_ciphertext FROM users WHERE id = :1",
+            &[Value::from(user_id.clone())],
+        )
+        .await
+        .expect("read migrated agi");
+    let row = rows.first().expect("migrated row");
+    assert_eq!(row.get_string(0), Some("empty"));
+    assert!(row.get_string(1).unwrap_or_default().starts_with("enc:v1:"));
+
+    let migrated = db::users::get_user_profile(&pool, &user_id).await.expect("get profile").expect("profile");
+    assert_eq!(migrated.4, Some(1234.5));
+    let donations = db::donations::list_donations(&pool, &user_id, Some(2025)).await.expect("list donations");
+    assert_eq!(donations[0].notes.as_deref(), Some("legacy note"));
+
+    drop(conn);
+    db::users::delete_user_data(&pool, &user_id).await.expect("delete test data");
+}
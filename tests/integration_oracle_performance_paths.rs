@@ -1,8 +1,8 @@
 use chrono::{Duration, NaiveDate, Utc};
 use deductible_tracker::db;
 use deductible_tracker::db::models::{
-    BatchSyncRequest, DonationSyncItem, NewCharity, NewDonation, NewReceipt, ReceiptSyncItem,
-    UserProfileUpsert,
+    BatchSyncRequest, CharitySyncItem, DonationSyncItem, IdempotencyClaim, NewCharity,
+    NewDonation, NewReceipt, ProfileSyncItem, ReceiptSyncItem, StoredResponse, UserProfileUpsert,
 };
 use oracle_rs::Value;
 use uuid::Uuid;
@@ -106,6 +106,8 @@ async fn batch_sync_merges_updates_and_receipts() {
         &pool,
         &user_id,
         BatchSyncRequest {
+            profile: None,
+            charities: Vec::new(),
             donations: vec![DonationSyncItem {
                 action: "create".to_string(),
                 id: donation_id.clone(),
@@ -115,6 +117,7 @@ async fn batch_sync_merges_updates_and_receipts() {
                 amount: Some(25.0),
                 charity_id: charity_id.clone(),
                 notes: Some("first sync".to_string()),
+                blind_index: None,
                 updated_at: Some(first_updated_at),
                 is_encrypted: None,
                 encrypted_payload: None,
@@ -139,6 +142,8 @@ async fn batch_sync_merges_updates_and_receipts() {
         &pool,
         &user_id,
         BatchSyncRequest {
+            profile: None,
+            charities: Vec::new(),
             donations: vec![DonationSyncItem {
                 action: "update".to_string(),
                 id: donation_id.clone(),
@@ -148,6 +153,7 @@ async fn batch_sync_merges_updates_and_receipts() {
                 amount: Some(99.0),
                 charity_id: charity_id.clone(),
                 notes: Some("older sync".to_string()),
+                blind_index: None,
                 updated_at: Some(older_updated_at),
                 is_encrypted: None,
                 encrypted_payload: None,
@@ -182,6 +188,8 @@ async fn batch_sync_merges_updates_and_receipts() {
         &pool,
         &user_id,
         BatchSyncRequest {
+            profile: None,
+            charities: Vec::new(),
             donations: vec![DonationSyncItem {
                 action: "update".to_string(),
                 id: donation_id.clone(),
@@ -191,6 +199,7 @@ async fn batch_sync_merges_updates_and_receipts() {
                 amount: Some(55.0),
                 charity_id: charity_id.clone(),
                 notes: Some("newer sync".to_string()),
+                blind_index: None,
                 updated_at: Some(newer_updated_at),
                 is_encrypted: None,
                 encrypted_payload: None,
@@ -235,6 +244,485 @@ async fn batch_sync_merges_updates_and_receipts() {
         .expect("cleanup test user data");
 }
 
+#[tokio::test]
+async fn batch_sync_applies_valid_items_when_one_fails() {
+    let pool = init_test_pool().await;
+    let (user_id, _) = create_test_user(&pool).await;
+    let suffix = Uuid::new_v4().to_string();
+    let charity_id = create_test_charity(&pool, &user_id, &suffix).await;
+    let good_donation_id = format!("oracle-partial-good-{suffix}");
+    let bad_donation_id = format!("oracle-partial-bad-{suffix}");
+    let orphan_receipt_id = format!("oracle-partial-receipt-{suffix}");
+
+    let donation = |id: &str, charity_id: &str| DonationSyncItem {
+        action: "create".to_string(),
+        id: id.to_string(),
+        date: Some(NaiveDate::from_ymd_opt(2026, 5, 1).expect("valid date")),
+        year: Some(2026),
+        category: Some("money".to_string()),
+        amount: Some(40.0),
+        charity_id: charity_id.to_string(),
+        notes: None,
+        blind_index: None,
+        updated_at: Some(Utc::now()),
+        is_encrypted: None,
+        encrypted_payload: None,
+    };
+
+    let result = db::batch_sync(
+        &pool,
+        &user_id,
+        BatchSyncRequest {
+            profile: None,
+            charities: Vec::new(),
+            donations: vec![
+                donation(&bad_donation_id, &format!("missing-charity-{suffix}")),
+                donation(&good_donation_id, &charity_id),
+            ],
+            receipts: vec![ReceiptSyncItem {
+                action: "create".to_string(),
+                id: orphan_receipt_id.clone(),
+                donation_id: format!("missing-donation-{suffix}"),
+                key: format!("key-{suffix}"),
+                file_name: None,
+                content_type: None,
+                size: None,
+                is_encrypted: None,
+                encrypted_payload: None,
+            }],
+        },
+    )
+    .await
+    .expect("partial batch sync");
+
+    assert_eq!(result.applied, 1);
+    let failed_ids: Vec<&str> = result.failures.iter().map(|f| f.id.as_str()).collect();
+    assert_eq!(failed_ids, vec![bad_donation_id.as_str(), orphan_receipt_id.as_str()]);
+    assert!(result.failures.iter().all(|f| f.retryable));
+
+    let donations = db::donations::list_donations(&pool, &user_id, Some(2026))
+        .await
+        .expect("list donations after partial sync");
+    assert_eq!(donations.len(), 1);
+    assert_eq!(donations[0].id, good_donation_id);
+
+    db::users::delete_user_data(&pool, &user_id)
+        .await
+        .expect("cleanup test user data");
+}
+
+#[tokio::test]
+async fn batch_sync_applies_offline_charity_and_profile_in_dependency_order() {
+    let pool = init_test_pool().await;
+    let (user_id, _) = create_test_user(&pool).await;
+    let suffix = Uuid::new_v4().to_string();
+    let charity_id = format!("oracle-offline-charity-{suffix}");
+    let donation_id = format!("oracle-offline-donation-{suffix}");
+
+    let result = db::batch_sync(
+        &pool,
+        &user_id,
+        BatchSyncRequest {
+            profile: Some(ProfileSyncItem {
+                filing_status: Some("married_joint".to_string()),
+                agi: Some(120_000.0),
+                ..Default::default()
+            }),
+            charities: vec![CharitySyncItem {
+                action: "create".to_string(),
+                id: charity_id.clone(),
+                name: Some(format!("Offline Shelter {suffix}")),
+                ein: None,
+                category: None,
+                status: None,
+                classification: None,
+                nonprofit_type: None,
+                deductibility: None,
+                street: None,
+                city: None,
+                state: None,
+                zip: None,
+                is_encrypted: None,
+                encrypted_payload: None,
+                updated_at: Some(Utc::now()),
+            }],
+            donations: vec![DonationSyncItem {
+                action: "create".to_string(),
+                id: donation_id.clone(),
+                date: Some(NaiveDate::from_ymd_opt(2026, 6, 1).expect("valid date")),
+                year: Some(2026),
+                category: Some("money".to_string()),
+                amount: Some(75.0),
+                charity_id: charity_id.clone(),
+                notes: None,
+                blind_index: None,
+                updated_at: Some(Utc::now()),
+                is_encrypted: None,
+                encrypted_payload: None,
+            }],
+            receipts: Vec::new(),
+        },
+    )
+    .await
+    .expect("offline batch sync");
+
+    assert!(result.failures.is_empty(), "unexpected failures: {:?}", result.failures);
+    assert_eq!(result.applied, 3);
+
+    let donations = db::donations::list_donations(&pool, &user_id, Some(2026))
+        .await
+        .expect("list donations after offline sync");
+    assert_eq!(donations.len(), 1);
+    assert_eq!(donations[0].charity_id, charity_id);
+
+    let profile = db::users::get_user_profile(&pool, &user_id)
+        .await
+        .expect("get profile")
+        .expect("profile exists");
+    assert_eq!(profile.3.as_deref(), Some("married_joint"));
+    assert_eq!(profile.4, Some(120_000.0));
+
+    db::users::delete_user_data(&pool, &user_id)
+        .await
+        .expect("cleanup test user data");
+}
+
+#[tokio::test]
+async fn batch_sync_profile_cannot_change_email_or_vault_credential() {
+    let pool = init_test_pool().await;
+    let (user_id, email) = create_test_user(&pool).await;
+
+    let profile: ProfileSyncItem = serde_json::from_value(serde_json::json!({
+        "name": "Renamed Offline",
+        "email": "cpa@firm.example",
+        "vault_credential_id": "attacker-credential",
+    }))
+    .expect("profile sync item");
+    let result = db::batch_sync(
+        &pool,
+        &user_id,
+        BatchSyncRequest {
+            profile: Some(profile),
+            charities: Vec::new(),
+            donations: Vec::new(),
+            receipts: Vec::new(),
+        },
+    )
+    .await
+    .expect("profile batch sync");
+    assert!(result.failures.is_empty(), "unexpected failures: {:?}", result.failures);
+
+    let profile = db::users::get_user_profile(&pool, &user_id)
+        .await
+        .expect("get profile")
+        .expect("profile exists");
+    assert_eq!(profile.1, "Renamed Offline");
+    assert_eq!(profile.0, email);
+    assert_eq!(profile.9, None);
+
+    db::users::delete_user_data(&pool, &user_id)
+        .await
+        .expect("cleanup test user data");
+}
+
+#[tokio::test]
+async fn batch_sync_cannot_attach_receipts_to_another_users_donation() {
+    let pool = init_test_pool().await;
+    let (owner_id, _) = create_test_user(&pool).await;
+    let (intruder_id, _) = create_test_user(&pool).await;
+    let suffix = Uuid::new_v4().to_string();
+    let charity_id = create_test_charity(&pool, &owner_id, &suffix).await;
+    let donation_id = format!("oracle-owned-donation-{suffix}");
+    let receipt_id = format!("oracle-intruder-receipt-{suffix}");
+    db::donations::add_donation(
+        &pool,
+        &NewDonation {
+            id: donation_id.clone(),
+            user_id: owner_id.clone(),
+            year: 2026,
+            date: NaiveDate::from_ymd_opt(2026, 7, 1).expect("valid date"),
+            category: Some("money".to_string()),
+            charity_id,
+            amount: Some(15.0),
+            notes: None,
+            is_encrypted: None,
+            encrypted_payload: None,
+            blind_index: None,
+            created_at: Utc::now(),
+        },
+    )
+    .await
+    .expect("add owner's donation");
+
+    let result = db::batch_sync(
+        &pool,
+        &intruder_id,
+        BatchSyncRequest {
+            profile: None,
+            charities: Vec::new(),
+            donations: Vec::new(),
+            receipts: vec![ReceiptSyncItem {
+                action: "create".to_string(),
+                id: receipt_id.clone(),
+                donation_id: donation_id.clone(),
+                key: format!("receipts/{intruder_id}/2026/{suffix}.pdf"),
+                file_name: None,
+                content_type: None,
+                size: None,
+                is_encrypted: None,
+                encrypted_payload: None,
+            }],
+        },
+    )
+    .await
+    .expect("intruder batch sync");
+
+    assert_eq!(result.applied, 0);
+    assert_eq!(result.failures.len(), 1);
+    assert_eq!(result.failures[0].reason, "Donation not found for user");
+    assert!(!result.failures[0].retryable);
+    let receipts = db::receipts::list_receipts(&pool, &owner_id, Some(donation_id))
+        .await
+        .expect("list owner's receipts");
+    assert!(receipts.iter().all(|receipt| receipt.id != receipt_id));
+
+    for user_id in [&intruder_id, &owner_id] {
+        db::users::delete_user_data(&pool, user_id)
+            .await
+            .expect("cleanup test user data");
+    }
+}
+
+#[tokio::test]
+async fn change_feed_reports_upserts_and_tombstones_in_sequence() {
+    let pool = init_test_pool().await;
+    let (user_id, _) = create_test_user(&pool).await;
+    let suffix = Uuid::new_v4().to_string();
+    let charity_id = create_test_charity(&pool, &user_id, &suffix).await;
+    let kept_id = format!("oracle-feed-kept-{suffix}");
+    let removed_id = format!("oracle-feed-removed-{suffix}");
+    let receipt_id = format!("oracle-feed-receipt-{suffix}");
+
+    for donation_id in [&kept_id, &removed_id] {
+        db::donations::add_donation(
+            &pool,
+            &NewDonation {
+                id: donation_id.clone(),
+                user_id: user_id.clone(),
+                year: 2026,
+                date: NaiveDate::from_ymd_opt(2026, 4, 1).expect("valid date"),
+                category: Some("money".to_string()),
+                charity_id: charity_id.clone(),
+                amount: Some(20.0),
+                notes: None,
+                is_encrypted: None,
+                encrypted_payload: None,
+                blind_index: None,
+                created_at: Utc::now(),
+            },
+        )
+        .await
+        .expect("add donation");
+    }
+    db::receipts::add_receipt(
+        &pool,
+        &NewReceipt {
+            id: receipt_id.clone(),
+            donation_id: removed_id.clone(),
+            key: format!("key-{suffix}"),
+            file_name: Some("feed.png".to_string()),
+            content_type: Some("image/png".to_string()),
+            size: Some(10),
+            is_encrypted: None,
+            encrypted_payload: None,
+            content_hash: None,
+            created_at: Utc::now(),
+        },
+    )
+    .await
+    .expect("add receipt");
+    db::donations::soft_delete_donation(&pool, &user_id, &removed_id)
+        .await
+        .expect("soft delete donation");
+
+    let page = db::list_sync_changes(&pool, &user_id, 0, 500)
+        .await
+        .expect("list changes");
+    assert!(!page.has_more);
+    let seqs: Vec<i64> = page.changes.iter().map(|change| change.seq).collect();
+    assert!(seqs.windows(2).all(|pair| pair[0] < pair[1]), "changes out of order: {seqs:?}");
+
+    let find = |table: &str, id: &str| {
+        page.changes
+            .iter()
+            .find(|change| change.table == table && change.id == id)
+            .unwrap_or_else(|| panic!("missing {table} {id}"))
+    };
+    assert_eq!(find("users", &user_id).op, "upsert");
+    assert_eq!(find("charities", &charity_id).op, "upsert");
+    let kept = find("donations", &kept_id);
+    assert_eq!(kept.op, "upsert");
+    assert_eq!(kept.data.as_ref().and_then(|data| data["amount"].as_f64()), Some(20.0));
+    let removed = find("donations", &removed_id);
+    assert_eq!(removed.op, "delete");
+    assert!(removed.data.is_none());
+    assert_eq!(find("receipts", &receipt_id).op, "delete");
+    assert_eq!(
+        page.changes.iter().filter(|change| change.id == removed_id).count(),
+        1,
+        "superseded entries should collapse to the latest"
+    );
+
+    let next = db::list_sync_changes(&pool, &user_id, page.last_seq.expect("last seq"), 500)
+        .await
+        .expect("list changes after cursor");
+    assert!(next.changes.is_empty());
+
+    db::users::delete_user_data(&pool, &user_id)
+        .await
+        .expect("cleanup test user data");
+}
+
+#[tokio::test]
+async fn change_feed_writers_commit_in_sequence_order() {
+    let pool = init_test_pool().await;
+    let (user_id, _) = create_test_user(&pool).await;
+    let suffix = Uuid::new_v4().to_string();
+    let charity_id = create_test_charity(&pool, &user_id, &suffix).await;
+    let donation_id = format!("oracle-feed-order-{suffix}");
+    let held_id = format!("oracle-feed-held-{suffix}");
+    let baseline = db::list_sync_changes(&pool, &user_id, 0, 500)
+        .await
+        .expect("list changes")
+        .last_seq
+        .expect("baseline seq");
+
+    // An open transaction that has drawn a sequence number but not yet committed.
+    let db::DbPoolEnum::Oracle(oracle_pool) = &*pool;
+    let held = oracle_pool.get().await.expect("checkout oracle connection");
+    held.query(
+        "SELECT id FROM users WHERE id = :1 FOR UPDATE",
+        &[Value::from(user_id.clone())],
+    )
+    .await
+    .expect("lock user feed");
+    held.execute(
+        "INSERT INTO sync_changes (seq, user_id, table_name, record_id, operation, created_at) VALUES (sync_change_seq.NEXTVAL, :1, 'donations', :2, 'delete', SYSTIMESTAMP)",
+        &[Value::from(user_id.clone()), Value::from(held_id.clone())],
+    )
+    .await
+    .expect("record held change");
+
+    let writer = {
+        let pool = pool.clone();
+        let user_id = user_id.clone();
+        let donation_id = donation_id.clone();
+        tokio::spawn(async move {
+            db::donations::add_donation(
+                &pool,
+                &NewDonation {
+                    id: donation_id,
+                    user_id,
+                    year: 2026,
+                    date: NaiveDate::from_ymd_opt(2026, 5, 1).expect("valid date"),
+                    category: Some("money".to_string()),
+                    charity_id,
+                    amount: Some(5.0),
+                    notes: None,
+                    is_encrypted: None,
+                    encrypted_payload: None,
+                    blind_index: None,
+                    created_at: Utc::now(),
+                },
+            )
+            .await
+        })
+    };
+    tokio::time::sleep(std::time::Duration::from_secs(1)).await;
+    assert!(!writer.is_finished(), "a second writer must wait for the in-flight one");
+    let page = db::list_sync_changes(&pool, &user_id, baseline, 500)
+        .await
+        .expect("list changes while in flight");
+    assert!(page.changes.is_empty(), "nothing may be served past an in-flight entry");
+
+    held.commit().await.expect("commit held change");
+    drop(held);
+    writer.await.expect("join writer").expect("add donation");
+
+    let page = db::list_sync_changes(&pool, &user_id, baseline, 500)
+        .await
+        .expect("list changes");
+    let ids: Vec<&str> = page.changes.iter().map(|change| change.id.as_str()).collect();
+    assert_eq!(ids, vec![held_id.as_str(), donation_id.as_str()]);
+
+    db::users::delete_user_data(&pool, &user_id)
+        .await
+        .expect("cleanup test user data");
+}
+
+#[tokio::test]
+async fn idempotency_keys_replay_stored_responses_and_reject_other_requests() {
+    let pool = init_test_pool().await;
+    let (user_id, _) = create_test_user(&pool).await;
+    let key = format!("idem-{}", Uuid::new_v4());
+
+    let first = db::idempotency::claim_key(&pool, &user_id, &key, "hash-a", 3600)
+        .await
+        .expect("claim key");
+    assert!(matches!(first, IdempotencyClaim::Claimed));
+
+    let concurrent = db::idempotency::claim_key(&pool, &user_id, &key, "hash-a", 3600)
+        .await
+        .expect("claim pending key");
+    assert!(matches!(concurrent, IdempotencyClaim::InProgress));
+
+    db::idempotency::store_response(
+        &pool,
+        &user_id,
+        &key,
+        &StoredResponse {
+            status_code: 201,
+            content_type: Some("application/json".to_string()),
+            body: r#"{"id":"donation-1"}"#.to_string(),
+        },
+    )
+    .await
+    .expect("store response");
+
+    match db::idempotency::claim_key(&pool, &user_id, &key, "hash-a", 3600)
+        .await
+        .expect("claim completed key")
+    {
+        IdempotencyClaim::Replay(stored) => {
+            assert_eq!(stored.status_code, 201);
+            assert_eq!(stored.content_type.as_deref(), Some("application/json"));
+            assert_eq!(stored.body, r#"{"id":"donation-1"}"#);
+        }
+        other => panic!("expected replay, got {other:?}"),
+    }
+
+    let reused = db::idempotency::claim_key(&pool, &user_id, &key, "hash-b", 3600)
+        .await
+        .expect("claim with different fingerprint");
+    assert!(matches!(reused, IdempotencyClaim::Mismatch));
+
+    let released_key = format!("idem-released-{}", Uuid::new_v4());
+    db::idempotency::claim_key(&pool, &user_id, &released_key, "hash-c", 3600)
+        .await
+        .expect("claim key to release");
+    db::idempotency::release_key(&pool, &user_id, &released_key)
+        .await
+        .expect("release key");
+    let retried = db::idempotency::claim_key(&pool, &user_id, &released_key, "hash-c", 3600)
+        .await
+        .expect("claim released key");
+    assert!(matches!(retried, IdempotencyClaim::Claimed));
+
+    db::users::delete_user_data(&pool, &user_id)
+        .await
+        .expect("cleanup test user data");
+}
+
 #[tokio::test]
 async fn clob_updates_round_trip_large_payloads() {
     let pool = init_test_pool().await;
@@ -258,6 +746,7 @@ async fn clob_updates_round_trip_large_payloads() {
             notes: Some("large clob test".to_string()),
             is_encrypted: None,
             encrypted_payload: None,
+            blind_index: None,
             created_at: now,
         },
     )
@@ -275,6 +764,7 @@ async fn clob_updates_round_trip_large_payloads() {
             size: Some(4096),
             is_encrypted: None,
             encrypted_payload: None,
+            content_hash: None,
             created_at: now,
         },
     )
@@ -0,0 +1,38 @@
+mod pdf_test {
+    #![allow(dead_code)]
+
+    include!("../src/pdf.rs");
+
+    fn text(pdf: &[u8]) -> String {
+        String::from_utf8(pdf.to_vec()).expect("report PDFs are ASCII")
+    }
+
+    #[test]
+    fn documents_have_a_valid_cross_reference_table() {
+        let pdf = text(&render_text_pdf("Charitable contributions 2025", &["2025-03-01   $50.00   Food Bank".to_string()]));
+        assert!(pdf.starts_with("%PDF-1.4\n"));
+        assert!(pdf.ends_with("%%EOF\n"));
+
+        let startxref: usize = pdf.rsplit("startxref\n").next().unwrap().lines().next().unwrap().parse().unwrap();
+        assert!(pdf[startxref..].starts_with("xref\n0 6\n"));
+        for (i, entry) in pdf[startxref..].lines().skip(3).take(5).enumerate() {
+            let offset: usize = entry[..10].parse().unwrap();
+            assert!(pdf[offset..].starts_with(&format!("{} 0 obj\n", i + 1)), "object {}", i + 1);
+        }
+    }
+
+    #[test]
+    fn long_reports_continue_on_numbered_pages() {
+        let lines: Vec<String> = (0..LINES_PER_PAGE * 2 + 1).map(|i| format!("line {i}")).collect();
+        let pdf = text(&render_text_pdf("Report", &lines));
+        assert!(pdf.contains("/Count 3"));
+        assert!(pdf.contains("(Page 3 of 3) Tj"));
+        assert!(pdf.contains(&format!("(line {}) Tj", LINES_PER_PAGE * 2)));
+    }
+
+    #[test]
+    fn text_is_escaped_for_pdf_strings() {
+        let pdf = text(&render_text_pdf("Report", &["Gift (cash) \\ café ✓".to_string()]));
+        assert!(pdf.contains("(Gift \\(cash\\) \\\\ caf\\351 ?) Tj"));
+    }
+}
@@ -1,5 +1,5 @@
 use deductible_tracker::db;
-use deductible_tracker::db::models::{NewCharity, NewDonation, NewReceipt};
+use deductible_tracker::db::models::{NewCharity, NewDonation, NewReceipt, ReceiptPatch};
 use uuid::Uuid;
 
 #[tokio::test]
@@ -50,6 +50,7 @@ async fn receipt_ocr_and_audit_flow() {
         notes: Some("integration test".to_string()),
         is_encrypted: None,
         encrypted_payload: None,
+        blind_index: None,
         created_at: now,
     };
     db::add_donation(&pool, &donation)
@@ -65,6 +66,7 @@ async fn receipt_ocr_and_audit_flow() {
         size: Some(123i64),
         is_encrypted: None,
         encrypted_payload: None,
+        content_hash: None,
         created_at: now,
     };
     db::add_receipt(&pool, &receipt).await.expect("add_receipt");
@@ -161,6 +163,7 @@ async fn receipt_summary_listing_avoids_ocr_clob_decode_failures() {
         notes: Some("summary test".to_string()),
         is_encrypted: None,
         encrypted_payload: None,
+        blind_index: None,
         created_at: now,
     };
     db::add_donation(&pool, &donation)
@@ -176,6 +179,7 @@ async fn receipt_summary_listing_avoids_ocr_clob_decode_failures() {
         size: Some(321),
         is_encrypted: None,
         encrypted_payload: None,
+        content_hash: None,
         created_at: now,
     };
     db::add_receipt(&pool, &receipt).await.expect("add_receipt");
@@ -210,3 +214,249 @@ async fn receipt_summary_listing_avoids_ocr_clob_decode_failures() {
         .expect("receipt detail present");
     assert_eq!(detail.ocr_text, large_ocr_text);
 }
+
+async fn create_charity_and_donation(pool: &db::DbPool, user_id: &str, label: &str) -> String {
+    let now = chrono::Utc::now();
+    let charity_id = format!("test-charity-{label}-{}", Uuid::new_v4());
+    let charity = NewCharity {
+        id: charity_id.clone(),
+        user_id: user_id.to_string(),
+        name: format!("Test Charity {label} {}", Uuid::new_v4()),
+        ein: None,
+        category: None,
+        status: None,
+        classification: None,
+        nonprofit_type: None,
+        deductibility: None,
+        street: None,
+        city: None,
+        state: None,
+        zip: None,
+        is_encrypted: None,
+        encrypted_payload: None,
+        created_at: now,
+    };
+    db::create_charity(pool, &charity)
+        .await
+        .expect("create_charity");
+
+    let donation_id = format!("test-donation-{label}-{}", Uuid::new_v4());
+    let donation = NewDonation {
+        id: donation_id.clone(),
+        user_id: user_id.to_string(),
+        year: 2026,
+        date: chrono::NaiveDate::from_ymd_opt(2026, 2, 18).expect("valid date"),
+        category: Some("money".to_string()),
+        charity_id,
+        amount: Some(10.0),
+        notes: None,
+        is_encrypted: None,
+        encrypted_payload: None,
+        blind_index: None,
+        created_at: now,
+    };
+    db::add_donation(pool, &donation)
+        .await
+        .expect("add_donation");
+    donation_id
+}
+
+#[tokio::test]
+async fn receipt_rename_reassign_and_delete() {
+    std::env::set_var("RUST_ENV", "development");
+    let pool = db::init_pool().await.expect("init pool");
+    let user_id = "dev-1".to_string();
+
+    let first_donation = create_charity_and_donation(&pool, &user_id, "receipt-move-a").await;
+    let second_donation = create_charity_and_donation(&pool, &user_id, "receipt-move-b").await;
+
+    let receipt_id = format!("test-receipt-move-{}", Uuid::new_v4());
+    let receipt = NewReceipt {
+        id: receipt_id.clone(),
+        donation_id: first_donation.clone(),
+        key: format!("receipts/{user_id}/2026/{receipt_id}.png"),
+        file_name: Some("wrong.png".to_string()),
+        content_type: Some("image/png".to_string()),
+        size: Some(64),
+        is_encrypted: None,
+        encrypted_payload: None,
+        content_hash: None,
+        created_at: chrono::Utc::now(),
+    };
+    db::add_receipt(&pool, &receipt).await.expect("add_receipt");
+
+    let updated = db::receipts::update_receipt(
+        &pool,
+        &ReceiptPatch {
+            receipt_id: receipt_id.clone(),
+            user_id: user_id.clone(),
+            donation_id: Some(second_donation.clone()),
+            file_name: Some("right.png".to_string()),
+            is_encrypted: None,
+            encrypted_payload: None,
+        },
+    )
+    .await
+    .expect("update_receipt");
+    assert!(updated);
+
+    let moved = db::get_receipt(&pool, &user_id, &receipt_id)
+        .await
+        .expect("get_receipt")
+        .expect("receipt present");
+    assert_eq!(moved.donation_id, second_donation);
+    assert_eq!(moved.file_name.as_deref(), Some("right.png"));
+
+    let foreign = db::receipts::update_receipt(
+        &pool,
+        &ReceiptPatch {
+            receipt_id: receipt_id.clone(),
+            user_id: user_id.clone(),
+            donation_id: Some(format!("missing-donation-{}", Uuid::new_v4())),
+            file_name: None,
+            is_encrypted: None,
+            encrypted_payload: None,
+        },
+    )
+    .await
+    .expect("update_receipt to unknown donation");
+    assert!(!foreign, "reassigning to an unknown donation must be refused");
+
+    let deleted_key = db::receipts::delete_receipt(&pool, &user_id, &receipt_id)
+        .await
+        .expect("delete_receipt");
+    assert_eq!(deleted_key, Some(receipt.key.clone()));
+    assert!(db::get_receipt(&pool, &user_id, &receipt_id)
+        .await
+        .expect("get_receipt after delete")
+        .is_none());
+
+    let deleted_again = db::receipts::delete_receipt(&pool, &user_id, &receipt_id)
+        .await
+        .expect("delete_receipt twice");
+    assert_eq!(deleted_again, None);
+}
+
+#[tokio::test]
+async fn receipt_content_hashes_find_shared_files() {
+    std::env::set_var("RUST_ENV", "development");
+    let pool = db::init_pool().await.expect("init pool");
+    let user_id = "dev-1".to_string();
+    let now = chrono::Utc::now();
+
+    let charity_id = format!("test-charity-{}", Uuid::new_v4());
+    let charity = NewCharity {
+        id: charity_id.clone(),
+        user_id: user_id.clone(),
+        name: format!("Test Charity {}", Uuid::new_v4()),
+        ein: None,
+        category: None,
+        status: None,
+        classification: None,
+        nonprofit_type: None,
+        deductibility: None,
+        street: None,
+        city: None,
+        state: None,
+        zip: None,
+        is_encrypted: None,
+        encrypted_payload: None,
+        created_at: now,
+    };
+    db::create_charity(&pool, &charity).await.expect("create_charity");
+
+    let mut donation_ids = Vec::new();
+    for _ in 0..2 {
+        let donation_id = format!("test-donation-{}", Uuid::new_v4());
+        let donation = NewDonation {
+            id: donation_id.clone(),
+            user_id: user_id.clone(),
+            year: 2026,
+            date: chrono::NaiveDate::from_ymd_opt(2026, 3, 1).expect("valid date"),
+            category: Some("money".to_string()),
+            charity_id: charity_id.clone(),
+            amount: Some(10.0),
+            notes: None,
+            is_encrypted: None,
+            encrypted_payload: None,
+            blind_index: None,
+            created_at: now,
+        };
+        db::add_donation(&pool, &donation).await.expect("add_donation");
+        donation_ids.push(donation_id);
+    }
+
+    // Random per run so earlier runs' rows don't match.
+    let content_hash = format!("{:064x}", Uuid::new_v4().as_u128());
+    let key = format!("receipts/{}/2026/{}.pdf", user_id, Uuid::new_v4());
+    let mut receipt_ids = Vec::new();
+    for donation_id in &donation_ids {
+        let receipt_id = format!("test-receipt-{}", Uuid::new_v4());
+        let receipt = NewReceipt {
+            id: receipt_id.clone(),
+            donation_id: donation_id.clone(),
+            key: key.clone(),
+            file_name: Some("same.pdf".to_string()),
+            content_type: Some("application/pdf".to_string()),
+            size: Some(8),
+            is_encrypted: None,
+            encrypted_payload: None,
+            content_hash: Some(content_hash.clone()),
+            created_at: now,
+        };
+        if receipt_ids.is_empty() {
+            db::add_receipt(&pool, &receipt).await.expect("add_receipt");
+        } else {
+            assert!(db::add_shared_receipt(&pool, &user_id, &receipt).await.expect("add_shared_receipt"));
+        }
+        receipt_ids.push(receipt_id);
+    }
+
+    let same_file = db::find_receipts_by_content_hash(&pool, &user_id, &content_hash)
+        .await
+        .expect("find_receipts_by_content_hash");
+    assert_eq!(same_file.len(), 2);
+    assert!(same_file.iter().all(|receipt| receipt.key == key));
+    assert!(db::find_receipts_by_content_hash(&pool, "someone-else", &content_hash)
+        .await
+        .expect("find_receipts_by_content_hash")
+        .is_empty());
+    assert_eq!(
+        db::content_hash_for_key(&pool, &user_id, &key).await.expect("content_hash_for_key"),
+        Some(content_hash.clone())
+    );
+
+    // Rewriting the shared object updates every receipt pointing at it.
+    let rotated_hash = format!("{:064x}", Uuid::new_v4().as_u128());
+    db::set_content_hash_for_key(&pool, &key, &rotated_hash)
+        .await
+        .expect("set_content_hash_for_key");
+    let fetched = db::get_receipt(&pool, &user_id, &receipt_ids[1])
+        .await
+        .expect("get_receipt")
+        .expect("receipt exists");
+    assert_eq!(fetched.content_hash, Some(rotated_hash));
+
+    // The object stays in use until the last receipt sharing it is gone.
+    db::delete_receipt(&pool, &user_id, &receipt_ids[0]).await.expect("delete_receipt");
+    assert!(db::receipt_key_in_use(&pool, &user_id, &key).await.expect("receipt_key_in_use"));
+    db::delete_receipt(&pool, &user_id, &receipt_ids[1]).await.expect("delete_receipt");
+    assert!(!db::receipt_key_in_use(&pool, &user_id, &key).await.expect("receipt_key_in_use"));
+
+    // Once nothing holds the object, a receipt can no longer be pointed at it.
+    let late_id = format!("test-receipt-{}", Uuid::new_v4());
+    let late = NewReceipt {
+        id: late_id.clone(),
+        donation_id: donation_ids[0].clone(),
+        key: key.clone(),
+        file_name: Some("same.pdf".to_string()),
+        content_type: Some("application/pdf".to_string()),
+        size: Some(8),
+        is_encrypted: None,
+        encrypted_payload: None,
+        content_hash: Some(content_hash.clone()),
+        created_at: now,
+    };
+    assert!(!db::add_shared_receipt(&pool, &user_id, &late).await.expect("add_shared_receipt"));
+    assert!(db::get_receipt(&pool, &user_id, &late_id).await.expect("get_receipt").is_none());
+}
@@ -0,0 +1,498 @@
+#[allow(dead_code)]
+#[path = "../src/storage/mod.rs"]
+mod storage;
+
+use axum::body::Bytes;
+use axum::extract::{Multipart, Query, State};
+use axum::http::{header, Method, StatusCode, Uri};
+use axum::response::{IntoResponse, Response};
+use axum::routing::post;
+use axum::Router;
+use std::collections::{BTreeMap, HashMap};
+use std::sync::{Arc, Mutex};
+use base64::{prelude::BASE64_STANDARD, Engine as _};
+use storage::{FsStore, LocalUrlSigner, MemoryStore, ObjectStore, PostPolicy, S3Store, UploadedPart};
+
+fn signer() -> LocalUrlSigner {
+    LocalUrlSigner::new("", b"local-signing-secret")
+}
+
+/// Put, head, get, list and delete behave the same on every backend.
+async fn exercise_store(store: &dyn ObjectStore) {
+    let key = "receipts/user-1/2026/a.pdf";
+    assert!(store.get(key).await.unwrap().is_none());
+    assert!(store.head(key).await.unwrap().is_none());
+
+    store.put(key, Bytes::from_static(b"%PDF-1.4 receipt"), Some("application/pdf")).await.unwrap();
+    store.put("receipts/user-1/2026/b.png", Bytes::from_static(b"png"), Some("image/png")).await.unwrap();
+    store.put("receipts/user-2/2026/c.png", Bytes::from_static(b"other"), Some("image/png")).await.unwrap();
+
+    let meta = store.head(key).await.unwrap().expect("object exists");
+    assert_eq!(meta.key, key);
+    assert_eq!(meta.size, 16);
+    assert_eq!(meta.content_type.as_deref(), Some("application/pdf"));
+    assert!(meta.last_modified.is_some());
+    assert_eq!(store.get_bytes(key).await.unwrap().unwrap(), b"%PDF-1.4 receipt");
+    assert_eq!(store.get_range(key, 0..5).await.unwrap().unwrap(), Bytes::from_static(b"%PDF-"));
+    assert_eq!(store.get_range(key, 9..64).await.unwrap().unwrap(), Bytes::from_static(b"receipt"));
+    assert!(store.get_range("receipts/user-1/missing.pdf", 0..5).await.unwrap().is_none());
+
+    // Keys prefixed with the bucket name address the same object.
+    let bucket_key = format!("/{}/{}", store.bucket(), key);
+    assert_eq!(store.get_bytes(&bucket_key).await.unwrap().unwrap(), b"%PDF-1.4 receipt");
+
+    let listed: Vec<String> = store.list("receipts/user-1/").await.unwrap().into_iter().map(|m| m.key).collect();
+    assert_eq!(listed, vec![key.to_string(), "receipts/user-1/2026/b.png".to_string()]);
+    assert_eq!(store.list("receipts/").await.unwrap().len(), 3);
+    assert!(store.list("receipts/user-3/").await.unwrap().is_empty());
+
+    // SHA-256 of the stored bytes, streamed; it follows the object when rewritten.
+    assert_eq!(
+        store.content_hash("receipts/user-1/2026/b.png").await.unwrap().as_deref(),
+        Some("8f8cbb7dcf46e0bc7d53265749a6c17d116093a6ba95e442764060c76fd4a86c")
+    );
+    let original_hash = store.content_hash(key).await.unwrap().expect("object exists");
+    assert!(store.content_hash("receipts/user-1/missing.pdf").await.unwrap().is_none());
+
+    store.put(key, Bytes::from_static(b"replaced"), Some("application/pdf")).await.unwrap();
+    assert_eq!(store.get_bytes(key).await.unwrap().unwrap(), b"replaced");
+    assert_ne!(store.content_hash(key).await.unwrap().unwrap(), original_hash);
+
+    store.delete(key).await.unwrap();
+    store.delete(key).await.unwrap();
+    assert!(store.get(key).await.unwrap().is_none());
+    assert_eq!(store.list("receipts/user-1/").await.unwrap().len(), 1);
+
+    assert!(store.put("../escape", Bytes::from_static(b"x"), None).await.is_err());
+    assert!(store.presign("GET", "", 60).is_err());
+}
+
+/// PUTs a part to its presigned URL and returns the ETag. App-served URLs are
+/// checked and written the way the `/storage/` route does it.
+async fn upload_part(store: &dyn ObjectStore, url: &str, body: &'static [u8]) -> String {
+    let Some(signer) = store.local_signer() else {
+        let resp = reqwest::Client::new().put(url).body(body).send().await.unwrap();
+        assert!(resp.status().is_success(), "part upload returned {}", resp.status());
+        return resp.headers()[header::ETAG].to_str().unwrap().to_string();
+    };
+    let key = url.split_once(storage::LOCAL_URL_PATH).unwrap().1.split('?').next().unwrap();
+    let expires: i64 = query_param(url, "expires").parse().unwrap();
+    assert!(signer.verify("PUT", key, expires, &query_param(url, "signature")));
+    store.put(key, Bytes::from_static(body), None).await.unwrap();
+    storage::local_etag(body)
+}
+
+/// Parts uploaded out of order are joined by part number; bad ETags and
+/// aborted uploads can't be completed.
+async fn exercise_multipart(store: &dyn ObjectStore) {
+    let key = "receipts/user-1/2026/appraisal.pdf";
+    let upload_id = store.create_multipart_upload(key, Some("application/pdf")).await.unwrap();
+    let second = upload_part(store, &store.presign_part(key, &upload_id, 2, 300).unwrap(), b"part two").await;
+    let first = upload_part(store, &store.presign_part(key, &upload_id, 1, 300).unwrap(), b"%PDF-1.4 part one, ").await;
+    assert!(store.presign_part(key, &upload_id, 0, 300).is_err());
+    assert!(store.get(key).await.unwrap().is_none(), "nothing is visible before completion");
+
+    let part = |part_number, etag: &str| UploadedPart { part_number, etag: etag.to_string() };
+    assert!(store.complete_multipart_upload(key, &upload_id, &[part(2, &second), part(1, &first)]).await.is_err());
+    assert!(store.complete_multipart_upload(key, &upload_id, &[part(1, &second), part(2, &first)]).await.is_err());
+    assert!(store.head(key).await.unwrap().is_none(), "a mismatched part leaves no object behind");
+    store.complete_multipart_upload(key, &upload_id, &[part(1, &first), part(2, &second)]).await.unwrap();
+    assert_eq!(store.get_bytes(key).await.unwrap().unwrap(), b"%PDF-1.4 part one, part two");
+    assert_eq!(store.head(key).await.unwrap().unwrap().content_type.as_deref(), Some("application/pdf"));
+    assert!(store.list("multipart/").await.unwrap().is_empty(), "staged parts are removed");
+
+    let aborted = "receipts/user-1/2026/video.bin";
+    let upload_id = store.create_multipart_upload(aborted, None).await.unwrap();
+    let etag = upload_part(store, &store.presign_part(aborted, &upload_id, 1, 300).unwrap(), b"frames").await;
+    store.abort_multipart_upload(aborted, &upload_id).await.unwrap();
+    store.abort_multipart_upload(aborted, &upload_id).await.unwrap();
+    assert!(store.complete_multipart_upload(aborted, &upload_id, &[part(1, &etag)]).await.is_err());
+    assert!(store.head(aborted).await.unwrap().is_none());
+    assert!(store.list("multipart/").await.unwrap().is_empty());
+    store.delete(key).await.unwrap();
+}
+
+#[tokio::test]
+async fn memory_store_round_trips_objects() {
+    let store = MemoryStore::new("local", signer());
+    exercise_store(&store).await;
+    exercise_multipart(&store).await;
+}
+
+#[tokio::test]
+async fn fs_store_round_trips_objects() {
+    let dir = tempfile::tempdir().unwrap();
+    let store = FsStore::new(dir.path().join("objects"), "local", signer()).unwrap();
+    exercise_store(&store).await;
+    exercise_multipart(&store).await;
+
+    // Objects are plain files under the root, and hidden segments are refused.
+    assert!(dir.path().join("objects/receipts/user-1/2026/b.png").is_file());
+    let leftovers = std::fs::read_dir(dir.path().join("objects/receipts/user-1/2026")).unwrap();
+    assert!(leftovers.flatten().all(|entry| !entry.file_name().to_string_lossy().starts_with(".upload-")));
+    assert!(store.put("receipts/user-1/.hidden", Bytes::from_static(b"x"), None).await.is_err());
+    assert!(store.put("receipts/user-1//double", Bytes::from_static(b"x"), None).await.is_err());
+}
+
+fn query_param(url: &str, name: &str) -> String {
+    let query = url.split_once('?').expect("query").1;
+    query
+        .split('&')
+        .find_map(|pair| pair.strip_prefix(&format!("{}=", name)))
+        .expect("param")
+        .to_string()
+}
+
+#[test]
+fn local_urls_are_bound_to_method_key_and_expiry() {
+    let store = MemoryStore::new("local", LocalUrlSigner::new("https://app.example/", b"secret"));
+    let url = store.presign("PUT", "local/receipts/u 1/a.jpg", 300).unwrap();
+    assert!(url.starts_with("https://app.example/storage/receipts/u%201/a.jpg?expires="));
+
+    let signer = store.local_signer().expect("memory URLs are app-served");
+    let expires: i64 = query_param(&url, "expires").parse().unwrap();
+    let signature = query_param(&url, "signature");
+    let key = "receipts/u 1/a.jpg";
+    assert!(signer.verify("PUT", key, expires, &signature));
+    assert!(!signer.verify("GET", key, expires, &signature));
+    assert!(!signer.verify("PUT", "receipts/u 1/b.jpg", expires, &signature));
+    assert!(!signer.verify("PUT", key, expires + 1, &signature));
+    assert!(!signer.verify("PUT", key, expires, "not-hex"));
+    assert!(!LocalUrlSigner::new("", b"other").verify("PUT", key, expires, &signature));
+}
+
+#[test]
+fn url_signing_key_derived_from_the_session_secret_is_distinct() {
+    let session_secret = b"jwt-session-secret";
+    let derived = storage::derive_url_signing_key(session_secret).unwrap();
+    assert_eq!(derived.len(), 32);
+    assert_eq!(derived, storage::derive_url_signing_key(session_secret).unwrap());
+    assert_ne!(derived.as_slice(), session_secret.as_slice());
+    assert_ne!(derived, storage::derive_url_signing_key(b"another-secret").unwrap());
+
+    let derived_store = MemoryStore::new("local", LocalUrlSigner::new("", &derived));
+    let url = derived_store.presign("GET", "receipts/u1/a.jpg", 300).unwrap();
+    let expires: i64 = query_param(&url, "expires").parse().unwrap();
+    let signature = query_param(&url, "signature");
+    assert!(derived_store.local_signer().unwrap().verify("GET", "receipts/u1/a.jpg", expires, &signature));
+    assert!(!LocalUrlSigner::new("", session_secret).verify("GET", "receipts/u1/a.jpg", expires, &signature));
+}
+
+fn receipt_policy<'a>(key: &'a str, max_bytes: u64) -> PostPolicy<'a> {
+    PostPolicy {
+        key,
+        key_prefix: "receipts/user-1/",
+        content_type: "application/pdf",
+        min_bytes: 1,
+        max_bytes,
+        expires_in_secs: 300,
+    }
+}
+
+fn with_field(fields: &[(String, String)], name: &str, value: &str) -> Vec<(String, String)> {
+    let mut fields = fields.to_vec();
+    match fields.iter_mut().find(|(field, _)| field == name) {
+        Some(field) => field.1 = value.to_string(),
+        None => fields.push((name.to_string(), value.to_string())),
+    }
+    fields
+}
+
+#[test]
+fn local_post_policies_bind_prefix_type_and_size() {
+    let store = MemoryStore::new("local", LocalUrlSigner::new("https://app.example", b"secret"));
+    let form = store.presign_post(&receipt_policy("receipts/user-1/2026/a.pdf", 100)).unwrap();
+    assert_eq!(form.url, "https://app.example/storage");
+    let signer = store.local_signer().unwrap();
+    assert_eq!(signer.verify_post(&form.fields, 42).unwrap(), "receipts/user-1/2026/a.pdf");
+
+    // Size, content type and key are limited by the signed policy.
+    assert!(signer.verify_post(&form.fields, 0).is_err());
+    assert!(signer.verify_post(&form.fields, 101).is_err());
+    assert!(signer.verify_post(&with_field(&form.fields, "Content-Type", "text/html"), 42).is_err());
+    assert!(signer.verify_post(&with_field(&form.fields, "key", "receipts/user-2/2026/a.pdf"), 42).is_err());
+    assert!(signer.verify_post(&with_field(&form.fields, "key", "receipts/user-1/other.pdf"), 42).is_ok());
+    // Unsigned extra fields, a rewritten policy and other secrets are refused.
+    assert!(signer.verify_post(&with_field(&form.fields, "acl", "public-read"), 42).is_err());
+    let loose = BASE64_STANDARD.encode(r#"{"expiration":"2099-01-01T00:00:00.000Z","conditions":[["starts-with","$key",""]]}"#);
+    assert!(signer.verify_post(&with_field(&form.fields, "policy", &loose), 42).is_err());
+    assert!(LocalUrlSigner::new("", b"other").verify_post(&form.fields, 42).is_err());
+
+    let expired = BASE64_STANDARD.encode(r#"{"expiration":"2000-01-01T00:00:00.000Z","conditions":[]}"#);
+    assert!(storage::check_post_policy(&expired, &[], 1).is_err());
+    assert!(store.presign_post(&receipt_policy("receipts/user-2/a.pdf", 100)).is_err());
+}
+
+/// Path-style S3 stand-in: keeps objects in memory, pages listings two keys at
+/// a time, supports multipart uploads and refuses requests without a SigV4
+/// query signature.
+#[derive(Clone, Default)]
+struct FakeS3 {
+    /// Body and content type by key.
+    objects: Arc<Mutex<BTreeMap<String, FakeObject>>>,
+    uploads: Arc<Mutex<HashMap<String, FakeUpload>>>,
+}
+
+type FakeObject = (Vec<u8>, String);
+
+struct FakeUpload {
+    key: String,
+    content_type: String,
+    parts: BTreeMap<u32, Vec<u8>>,
+}
+
+fn fake_etag(body: &[u8]) -> String {
+    format!("\"{:x}-{}\"", body.iter().map(|&b| b as u64).sum::<u64>(), body.len())
+}
+
+/// Form uploads to the bucket: the SigV4 policy signature is recomputed with
+/// the test secret, then the policy's conditions are enforced.
+async fn fake_s3_post(State(s3): State<FakeS3>, mut form: Multipart) -> Response {
+    let mut fields = Vec::new();
+    let mut file = None;
+    while let Some(field) = form.next_field().await.unwrap() {
+        let name = field.name().unwrap().to_string();
+        let value = field.bytes().await.unwrap();
+        if name == "file" {
+            file = Some(value);
+            break;
+        }
+        fields.push((name, String::from_utf8(value.to_vec()).unwrap()));
+    }
+    let field = |name: &str| fields.iter().find(|(n, _)| n.eq_ignore_ascii_case(name)).map(|(_, v)| v.clone());
+    let (Some(file), Some(policy), Some(credential), Some(signature)) =
+        (file, field("policy"), field("x-amz-credential"), field("x-amz-signature"))
+    else {
+        return StatusCode::BAD_REQUEST.into_response();
+    };
+
+    let date_stamp = credential.split('/').nth(1).unwrap();
+    let mut signing_key = b"AWS4secret".to_vec();
+    for scope in [date_stamp, "us-ashburn-1", "s3", "aws4_request"] {
+        signing_key = storage::hmac_sha256(&signing_key, scope.as_bytes()).unwrap();
+    }
+    if storage::hex_encode(&storage::hmac_sha256(&signing_key, policy.as_bytes()).unwrap()) != signature {
+        return StatusCode::FORBIDDEN.into_response();
+    }
+    let document: serde_json::Value = serde_json::from_slice(&BASE64_STANDARD.decode(&policy).unwrap()).unwrap();
+    let names_bucket = document["conditions"].as_array().unwrap().iter().any(|c| c["bucket"] == "bucket");
+    if !names_bucket || storage::check_post_policy(&policy, &fields, file.len() as u64).is_err() {
+        return StatusCode::FORBIDDEN.into_response();
+    }
+    let content_type = field("Content-Type").unwrap_or_default();
+    s3.objects.lock().unwrap().insert(field("key").unwrap(), (file.to_vec(), content_type));
+    StatusCode::NO_CONTENT.into_response()
+}
+
+/// Multipart requests: `?uploads`, `?partNumber&uploadId` and `?uploadId`.
+fn fake_multipart(
+    s3: &FakeS3,
+    method: &Method,
+    key: &str,
+    query: &HashMap<String, String>,
+    headers: &axum::http::HeaderMap,
+    body: &Bytes,
+) -> Option<Response> {
+    let mut uploads = s3.uploads.lock().unwrap();
+    if *method == Method::POST && query.contains_key("uploads") {
+        let upload_id = format!("upload-{}", uploads.len() + 1);
+        let content_type = headers.get(header::CONTENT_TYPE).map(|v| v.to_str().unwrap().to_string()).unwrap_or_default();
+        uploads.insert(upload_id.clone(), FakeUpload { key: key.to_string(), content_type, parts: BTreeMap::new() });
+        let xml = format!("<InitiateMultipartUploadResult><UploadId>{}</UploadId></InitiateMultipartUploadResult>", upload_id);
+        return Some(([(header::CONTENT_TYPE, "application/xml")], xml).into_response());
+    }
+    let upload_id = query.get("uploadId")?;
+    let Some(upload) = uploads.get_mut(upload_id).filter(|upload| upload.key == key) else {
+        return Some(StatusCode::NOT_FOUND.into_response());
+    };
+    match *method {
+        Method::PUT => {
+            let part_number: u32 = query.get("partNumber")?.parse().unwrap();
+            upload.parts.insert(part_number, body.to_vec());
+            Some(([(header::ETAG, fake_etag(body))], StatusCode::OK).into_response())
+        }
+        Method::POST => {
+            let xml = String::from_utf8_lossy(body);
+            let mut joined = Vec::new();
+            for part in xml.split("<Part>").skip(1) {
+                let field = |tag: &str| part.split(&format!("<{}>", tag)).nth(1)?.split('<').next().map(|v| v.replace("&quot;", "\""));
+                let part_number: u32 = field("PartNumber").unwrap().parse().unwrap();
+                match upload.parts.get(&part_number) {
+                    Some(bytes) if Some(fake_etag(bytes)) == field("ETag") => joined.extend_from_slice(bytes),
+                    // Real S3 answers 200 with an error document for some failures.
+                    _ => return Some("<Error><Code>InvalidPart</Code></Error>".into_response()),
+                }
+            }
+            let upload = uploads.remove(upload_id).unwrap();
+            s3.objects.lock().unwrap().insert(upload.key, (joined, upload.content_type));
+            Some("<CompleteMultipartUploadResult></CompleteMultipartUploadResult>".into_response())
+        }
+        Method::DELETE => {
+            uploads.remove(upload_id);
+            Some(StatusCode::NO_CONTENT.into_response())
+        }
+        _ => Some(StatusCode::METHOD_NOT_ALLOWED.into_response()),
+    }
+}
+
+async fn fake_s3(
+    State(s3): State<FakeS3>,
+    method: Method,
+    uri: Uri,
+    Query(query): Query<HashMap<String, String>>,
+    headers: axum::http::HeaderMap,
+    body: Bytes,
+) -> Response {
+    if !query.contains_key("X-Amz-Signature") || query.get("X-Amz-Algorithm").map(String::as_str) != Some("AWS4-HMAC-SHA256") {
+        return StatusCode::FORBIDDEN.into_response();
+    }
+    let path = uri.path().trim_start_matches('/');
+    let Some(rest) = path.strip_prefix("bucket") else {
+        return StatusCode::NOT_FOUND.into_response();
+    };
+    if let Some(response) = fake_multipart(&s3, &method, rest.trim_start_matches('/'), &query, &headers, &body) {
+        return response;
+    }
+    let mut objects = s3.objects.lock().unwrap();
+    if rest.is_empty() && query.get("list-type").map(String::as_str) == Some("2") {
+        let prefix = query.get("prefix").cloned().unwrap_or_default();
+        let after = query.get("continuation-token").cloned().unwrap_or_default();
+        let matching: Vec<_> = objects
+            .iter()
+            .filter(|(key, _)| key.starts_with(&prefix) && key.as_str() > after.as_str())
+            .collect();
+        let page = &matching[..matching.len().min(2)];
+        let mut xml = String::from("<?xml version=\"1.0\" encoding=\"UTF-8\"?><ListBucketResult>");
+        for (key, (body, _)) in page {
+            xml.push_str(&format!(
+                "<Contents><Key>{}</Key><LastModified>2026-01-02T03:04:05.000Z</LastModified><Size>{}</Size></Contents>",
+                key.replace('&', "&amp;"),
+                body.len()
+            ));
+        }
+        if matching.len() > page.len() {
+            xml.push_str(&format!(
+                "<IsTruncated>true</IsTruncated><NextContinuationToken>{}</NextContinuationToken>",
+                page.last().unwrap().0
+            ));
+        } else {
+            xml.push_str("<IsTruncated>false</IsTruncated>");
+        }
+        xml.push_str("</ListBucketResult>");
+        return ([(header::CONTENT_TYPE, "application/xml")], xml).into_response();
+    }
+    let key = rest.trim_start_matches('/').to_string();
+    match method {
+        Method::PUT => {
+            let content_type = headers
+                .get(header::CONTENT_TYPE)
+                .and_then(|v| v.to_str().ok())
+                .unwrap_or("application/octet-stream")
+                .to_string();
+            objects.insert(key, (body.to_vec(), content_type));
+            StatusCode::OK.into_response()
+        }
+        Method::GET | Method::HEAD => match objects.get(&key) {
+            Some((body, _)) if headers.contains_key(header::RANGE) => {
+                let range = headers[header::RANGE].to_str().unwrap().trim_start_matches("bytes=");
+                let (start, end) = range.split_once('-').unwrap();
+                let start: usize = start.parse().unwrap();
+                let end = (end.parse::<usize>().unwrap() + 1).min(body.len());
+                if start >= body.len() {
+                    return StatusCode::RANGE_NOT_SATISFIABLE.into_response();
+                }
+                (StatusCode::PARTIAL_CONTENT, body[start..end].to_vec()).into_response()
+            }
+            Some((body, content_type)) => (
+                [
+                    (header::CONTENT_TYPE, content_type.clone()),
+                    (header::LAST_MODIFIED, "Fri, 02 Jan 2026 03:04:05 GMT".to_string()),
+                ],
+                body.clone(),
+            )
+                .into_response(),
+            None => StatusCode::NOT_FOUND.into_response(),
+        },
+        Method::DELETE => {
+            objects.remove(&key);
+            StatusCode::NO_CONTENT.into_response()
+        }
+        _ => StatusCode::METHOD_NOT_ALLOWED.into_response(),
+    }
+}
+
+#[tokio::test]
+async fn s3_store_talks_to_an_s3_compatible_endpoint() {
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let endpoint = format!("http://{}", listener.local_addr().unwrap());
+    let app = Router::new()
+        .route("/bucket", post(fake_s3_post).fallback(fake_s3))
+        .fallback(fake_s3)
+        .with_state(FakeS3::default());
+    tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+
+    let store = S3Store::new(&endpoint, "bucket", "us-ashburn-1", "access", "secret").unwrap();
+    exercise_store(&store).await;
+    exercise_multipart(&store).await;
+
+    let url = store.presign("PUT", "receipts/user-1/2026/d.jpg", 300).unwrap();
+    assert!(url.starts_with(&format!("{}/bucket/receipts/user-1/2026/d.jpg?", endpoint)));
+    assert_eq!(query_param(&url, "X-Amz-Expires"), "300");
+    assert!(store.local_signer().is_none());
+}
+
+/// multipart/form-data body with `fields` and then the file.
+fn form_body(fields: &[(String, String)], file: &[u8]) -> (String, Vec<u8>) {
+    let boundary = "receipt-form-boundary";
+    let mut body = Vec::new();
+    for (name, value) in fields {
+        body.extend_from_slice(format!("--{boundary}\r\nContent-Disposition: form-data; name=\"{name}\"\r\n\r\n{value}\r\n").as_bytes());
+    }
+    body.extend_from_slice(
+        format!("--{boundary}\r\nContent-Disposition: form-data; name=\"file\"; filename=\"receipt\"\r\n\r\n").as_bytes(),
+    );
+    body.extend_from_slice(file);
+    body.extend_from_slice(format!("\r\n--{boundary}--\r\n").as_bytes());
+    (format!("multipart/form-data; boundary={boundary}"), body)
+}
+
+#[tokio::test]
+async fn s3_post_policy_is_enforced_by_an_s3_compatible_endpoint() {
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let endpoint = format!("http://{}", listener.local_addr().unwrap());
+    let app = Router::new()
+        .route("/bucket", post(fake_s3_post).fallback(fake_s3))
+        .fallback(fake_s3)
+        .with_state(FakeS3::default());
+    tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+    let store = S3Store::new(&endpoint, "bucket", "us-ashburn-1", "access", "secret").unwrap();
+
+    let key = "receipts/user-1/2026/form.pdf";
+    let form = store.presign_post(&receipt_policy(key, 16)).unwrap();
+    assert_eq!(form.url, format!("{}/bucket", endpoint));
+    let post = |fields: Vec<(String, String)>, file: &'static [u8]| {
+        let url = form.url.clone();
+        async move {
+            let (content_type, body) = form_body(&fields, file);
+            let resp = reqwest::Client::new()
+                .post(url)
+                .header(reqwest::header::CONTENT_TYPE, content_type)
+                .body(body)
+                .send()
+                .await
+                .unwrap();
+            resp.status().as_u16()
+        }
+    };
+
+    assert_eq!(post(form.fields.clone(), b"%PDF-1.4 too big for it").await, 403);
+    assert_eq!(post(with_field(&form.fields, "Content-Type", "image/png"), b"%PDF-1.4").await, 403);
+    assert_eq!(post(with_field(&form.fields, "key", "receipts/user-2/x.pdf"), b"%PDF-1.4").await, 403);
+    assert_eq!(post(with_field(&form.fields, "x-amz-signature", "00"), b"%PDF-1.4").await, 403);
+    assert!(store.head(key).await.unwrap().is_none());
+
+    assert_eq!(post(form.fields.clone(), b"%PDF-1.4").await, 204);
+    let meta = store.head(key).await.unwrap().expect("uploaded through the form");
+    assert_eq!((meta.size, meta.content_type.as_deref()), (8, Some("application/pdf")));
+}
+
@@ -0,0 +1,69 @@
+mod vault_envelope_test {
+    #![allow(dead_code)]
+
+    include!("../src/vault_envelope.rs");
+
+    fn key(fill: u8) -> VaultKey {
+        VaultKey::from_bytes(&[fill; 32]).unwrap()
+    }
+
+    // What clients wrote before envelopes: base64 or raw bytes of iv || ciphertext, no AAD.
+    fn legacy_sealed(key_bytes: &[u8; 32], plaintext: &[u8]) -> Vec<u8> {
+        let cipher = Aes256Gcm::new_from_slice(key_bytes).unwrap();
+        let iv = [7u8; NONCE_LEN];
+        let mut sealed = iv.to_vec();
+        sealed.extend(cipher.encrypt(Nonce::from_slice(&iv), plaintext).unwrap());
+        sealed
+    }
+
+    #[test]
+    fn key_id_is_a_sha256_prefix_shared_with_the_browser() {
+        // static/js/services/crypto.js derives the same id from the raw key.
+        assert_eq!(key(0).id(), "66687aadf862bd77");
+        assert!(VaultKey::from_bytes(&[0; 16]).is_err());
+        assert!(VaultKey::from_base64("not base64!").is_err());
+    }
+
+    #[test]
+    fn payloads_round_trip_under_a_versioned_envelope() {
+        let k = key(1);
+        let sealed = encrypt_payload(&k, br#"{"notes":"tithe"}"#).unwrap();
+        assert!(sealed.starts_with(&payload_prefix(&k.id())));
+        assert!(sealed.starts_with("v1:A256GCM:"));
+        assert_eq!(decrypt_payload(&k, &sealed).unwrap(), br#"{"notes":"tithe"}"#);
+
+        let err = decrypt_payload(&key(2), &sealed).unwrap_err().to_string();
+        assert!(err.contains(&k.id()), "{err}");
+    }
+
+    #[test]
+    fn legacy_payloads_and_objects_still_decrypt() {
+        let bytes = [3u8; 32];
+        let legacy = legacy_sealed(&bytes, b"receipt");
+        let k = VaultKey::from_bytes(&bytes).unwrap();
+        assert_eq!(decrypt_payload(&k, &BASE64_STANDARD.encode(&legacy)).unwrap(), b"receipt");
+        assert_eq!(object_key_id(&legacy), None);
+        assert_eq!(decrypt_object(&k, &legacy).unwrap(), b"receipt");
+    }
+
+    #[test]
+    fn envelope_headers_are_authenticated() {
+        let k = key(4);
+        let sealed = encrypt_payload(&k, b"agi").unwrap();
+        let relabelled = sealed.replacen("v1:A256GCM:", "v2:A256GCM:", 1);
+        assert!(decrypt_payload(&k, &relabelled).is_err());
+
+        let mut object = encrypt_object(&k, b"%PDF-1.7").unwrap();
+        assert_eq!(object_key_id(&object), Some(k.id()));
+        assert_eq!(decrypt_object(&k, &object).unwrap(), b"%PDF-1.7");
+        object[4] = 2;
+        assert!(decrypt_object(&k, &object).is_err());
+    }
+
+    #[test]
+    fn objects_name_the_key_they_need() {
+        let object = encrypt_object(&key(5), b"image").unwrap();
+        let err = decrypt_object(&key(6), &object).unwrap_err().to_string();
+        assert!(err.contains(&key(5).id()), "{err}");
+    }
+}
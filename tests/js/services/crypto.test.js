@@ -0,0 +1,65 @@
+import {
+  decryptData,
+  donationBlindIndex,
+  encryptBinaryData,
+  encryptData,
+  vaultKeyId,
+} from '../../../static/js/services/crypto.js';
+
+function importRawKey(fill) {
+  return crypto.subtle.importKey('raw', new Uint8Array(32).fill(fill), { name: 'AES-GCM' }, true, [
+    'encrypt',
+    'decrypt',
+  ]);
+}
+
+describe('vault envelopes', () => {
+  test('key ids match the server (src/vault_envelope.rs)', async () => {
+    expect(await vaultKeyId(await importRawKey(0))).toBe('66687aadf862bd77');
+  });
+
+  test('payloads carry version, algorithm and key id and round-trip', async () => {
+    const key = await importRawKey(1);
+    const payload = await encryptData(key, { notes: 'tithe' });
+    expect(payload.startsWith(`v1:A256GCM:${await vaultKeyId(key)}:`)).toBe(true);
+    expect(await decryptData(key, payload)).toEqual({ notes: 'tithe' });
+    await expect(decryptData(await importRawKey(2), payload)).rejects.toThrow('another vault key');
+  });
+
+  test('legacy payloads without an envelope still decrypt', async () => {
+    const key = await importRawKey(3);
+    const iv = new Uint8Array(12).fill(7);
+    const plaintext = new TextEncoder().encode(JSON.stringify({ agi: 1 }));
+    const ciphertext = new Uint8Array(await crypto.subtle.encrypt({ name: 'AES-GCM', iv }, key, plaintext));
+    const legacy = Buffer.concat([iv, ciphertext]).toString('base64');
+    expect(await decryptData(key, legacy)).toEqual({ agi: 1 });
+  });
+
+  test('receipt objects start with the DTV1 header and key id', async () => {
+    const key = await importRawKey(0);
+    const object = Buffer.from(await encryptBinaryData(key, new TextEncoder().encode('%PDF')), 'base64');
+    expect(object.subarray(0, 13).toString('hex')).toBe('445456310166687aadf862bd77');
+  });
+});
+
+describe('blind index tokens', () => {
+  test('equal values give equal tokens under one vault key only', async () => {
+    const key = await importRawKey(4);
+    const gift = { charity_id: 'c1', date: '2025-03-01', category: 'money', amount: 50 };
+    const first = await donationBlindIndex(key, gift);
+    const again = await donationBlindIndex(key, { ...gift, category: ' Money ', amount: '50.00' });
+    expect(again).toEqual(first);
+    expect(/^[A-Za-z0-9_-]{43}$/.test(first.dedupe)).toBe(true);
+    expect(first.year === first.charity).toBe(false);
+
+    const other = await donationBlindIndex(await importRawKey(5), gift);
+    expect(other.charity === first.charity).toBe(false);
+  });
+
+  test('missing fields get no token', async () => {
+    const tokens = await donationBlindIndex(await importRawKey(4), { charity_id: 'c1', year: 2025 });
+    expect(tokens.category).toBe(null);
+    expect(tokens.dedupe).toBe(null);
+    expect(typeof tokens.year).toBe('string');
+  });
+});
@@ -1,5 +1,5 @@
 import { jest } from '@jest/globals';
-import { apiJson } from '../../../static/js/services/http.js';
+import { apiJson, refreshSession } from '../../../static/js/services/http.js';
 
 describe('apiJson', () => {
   beforeEach(() => {
@@ -62,3 +62,59 @@ describe('apiJson', () => {
     );
   });
 });
+
+describe('session refresh', () => {
+  const response = (status) => ({
+    ok: status >= 200 && status < 300,
+    status,
+    headers: { get: () => 'text/plain' },
+    json: async () => null,
+    text: async () => String(status),
+  });
+
+  beforeEach(() => {
+    global.document = { cookie: 'csrf_token=old' };
+  });
+
+  afterEach(() => {
+    delete global.document;
+    delete global.fetch;
+  });
+
+  test('renews the session once and retries an api request that got 401', async () => {
+    let meCalls = 0;
+    global.fetch = jest.fn(async (path) => {
+      if (path === '/auth/refresh') {
+        global.document.cookie = 'csrf_token=new';
+        return response(200);
+      }
+      meCalls += 1;
+      return response(meCalls === 1 ? 401 : 200);
+    });
+
+    const { res } = await apiJson('/api/me', { method: 'PUT' });
+
+    expect(res.status).toBe(200);
+    expect(global.fetch).toHaveBeenCalledTimes(3);
+    expect(global.fetch.mock.calls[1][0]).toBe('/auth/refresh');
+    expect(global.fetch.mock.calls[2][1].headers).toMatchObject({ 'X-CSRF-Token': 'new' });
+  });
+
+  test('reports 401 when the session cannot be renewed', async () => {
+    global.fetch = jest.fn(async () => response(401));
+
+    const { res } = await apiJson('/api/me');
+
+    expect(res.status).toBe(401);
+    expect(global.fetch).toHaveBeenCalledTimes(2);
+  });
+
+  test('concurrent callers share one refresh request', async () => {
+    global.fetch = jest.fn(async () => response(200));
+
+    const results = await Promise.all([refreshSession(), refreshSession(), refreshSession()]);
+
+    expect(results).toEqual([true, true, true]);
+    expect(global.fetch).toHaveBeenCalledTimes(1);
+  });
+});
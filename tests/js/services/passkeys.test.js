@@ -0,0 +1,13 @@
+import { fromBase64Url, toBase64Url } from '../../../static/js/services/passkeys.js';
+
+describe('passkey base64url helpers', () => {
+  test('encodes without padding using the url-safe alphabet', () => {
+    expect(toBase64Url(new Uint8Array([0xfb, 0xff]).buffer)).toBe('-_8');
+    expect(toBase64Url(new Uint8Array([]).buffer)).toBe('');
+  });
+
+  test('round-trips arbitrary bytes', () => {
+    const bytes = new Uint8Array([0, 1, 2, 250, 251, 252, 253, 254, 255, 62, 63]);
+    expect(Array.from(fromBase64Url(toBase64Url(bytes.buffer)))).toEqual(Array.from(bytes));
+  });
+});
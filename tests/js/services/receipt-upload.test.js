@@ -1,6 +1,9 @@
 import {
+  buildReceiptUploadForm,
   mapReceiptSuggestionToDonationDraft,
   normalizeReceiptAnalysis,
+  planUploadParts,
+  sha256Hex,
 } from '../../../static/js/services/receipt-upload.js';
 
 describe('receipt upload helpers', () => {
@@ -60,4 +63,34 @@ describe('receipt upload helpers', () => {
     expect(mapReceiptSuggestionToDonationDraft(null)).toBeNull();
     expect(mapReceiptSuggestionToDonationDraft({ suggestion: null })).toBeNull();
   });
+
+  test('planUploadParts covers the file in numbered ranges with a short last part', () => {
+    expect(planUploadParts(25, 10)).toEqual([
+      { partNumber: 1, start: 0, end: 10 },
+      { partNumber: 2, start: 10, end: 20 },
+      { partNumber: 3, start: 20, end: 25 },
+    ]);
+    expect(planUploadParts(20, 10)).toHaveLength(2);
+    expect(planUploadParts(0, 10)).toEqual([]);
+  });
+
+  test('buildReceiptUploadForm sends the signed fields before the file', () => {
+    const file = new Blob(['%PDF-1.4'], { type: 'application/pdf' });
+    const form = buildReceiptUploadForm(
+      { key: 'receipts/u1/2026/a.pdf', 'Content-Type': 'application/pdf', policy: 'e30=' },
+      file,
+    );
+
+    expect([...form.keys()]).toEqual(['key', 'Content-Type', 'policy', 'file']);
+    expect(form.get('Content-Type')).toBe('application/pdf');
+  });
+
+  test('sha256Hex matches the hex digest the server records for receipts', async () => {
+    expect(await sha256Hex(new TextEncoder().encode('png'))).toBe(
+      '8f8cbb7dcf46e0bc7d53265749a6c17d116093a6ba95e442764060c76fd4a86c'
+    );
+    expect(await sha256Hex(new Uint8Array(0))).toBe(
+      'e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855'
+    );
+  });
 });
@@ -25,10 +25,15 @@ const mockDb = {
   receipts: {
     get: jest.fn(),
     update: jest.fn(),
+    delete: jest.fn(),
+    put: jest.fn(),
   },
   charities: {
     get: jest.fn(),
+    delete: jest.fn(),
+    put: jest.fn(),
   },
+  table: jest.fn((name) => mockDb[name]),
 };
 
 const currentUserState = {
@@ -215,4 +220,108 @@ describe('Sync profile updates', () => {
     expect(syncQueueCollection.bulkDelete).toHaveBeenCalledWith([12]);
     expect(apiJson).not.toHaveBeenCalledWith('/api/sync/batch', expect.anything());
   });
+
+  test('pushChanges keeps retryable failures queued and clears the rest', async () => {
+    syncQueueCollection._toArrayResult = [
+      { id: 1, user_id: 'user-1', table: 'donations', item_id: 'don-ok', action: 'create' },
+      { id: 2, user_id: 'user-1', table: 'donations', item_id: 'don-retry', action: 'create' },
+      { id: 3, user_id: 'user-1', table: 'donations', item_id: 'don-bad', action: 'create' },
+    ];
+    mockDb.donations.get.mockImplementation(async (id) => ({
+      id,
+      date: '2026-01-01',
+      year: 2026,
+      category: 'money',
+      amount: 10,
+      charity_id: 'charity-1',
+      notes: null,
+      updated_at: null,
+    }));
+    apiJson.mockResolvedValue({
+      res: { ok: true, status: 200 },
+      data: {
+        applied: 1,
+        failures: [
+          { table: 'donations', id: 'don-retry', action: 'create', reason: 'x', retryable: true },
+          { table: 'donations', id: 'don-bad', action: 'create', reason: 'y', retryable: false },
+        ],
+      },
+    });
+
+    await Sync.pushChanges();
+
+    expect(syncQueueCollection.bulkDelete).toHaveBeenCalledWith([1, 3]);
+    expect(mockDb.donations.update).toHaveBeenCalledTimes(1);
+    expect(mockDb.donations.update).toHaveBeenCalledWith('don-ok', { sync_status: 'synced' });
+  });
+
+  test('pullChanges follows the change feed cursor and applies tombstones', async () => {
+    apiJson
+      .mockResolvedValueOnce({
+        res: { ok: true, status: 200 },
+        data: {
+          changes: [
+            {
+              seq: 1,
+              table: 'donations',
+              id: 'don-1',
+              op: 'upsert',
+              data: { id: 'don-1', user_id: 'user-1', year: 2026, date: '2026-02-01', amount: 5 },
+            },
+          ],
+          next_cursor: 'cursor-1',
+          has_more: true,
+        },
+      })
+      .mockResolvedValueOnce({
+        res: { ok: true, status: 200 },
+        data: {
+          changes: [
+            { seq: 2, table: 'receipts', id: 'rec-1', op: 'delete', data: null },
+            { seq: 3, table: 'charities', id: 'char-1', op: 'delete', data: null },
+          ],
+          next_cursor: 'cursor-3',
+          has_more: false,
+        },
+      });
+
+    await Sync.pullChanges();
+
+    expect(apiJson).toHaveBeenNthCalledWith(1, '/api/sync/changes');
+    expect(apiJson).toHaveBeenNthCalledWith(2, '/api/sync/changes?cursor=cursor-1');
+    expect(mockDb.donations.put).toHaveBeenCalledWith(
+      expect.objectContaining({ id: 'don-1', amount: 5, sync_status: 'synced' })
+    );
+    expect(mockDb.receipts.delete).toHaveBeenCalledWith('rec-1');
+    expect(mockDb.charities.delete).toHaveBeenCalledWith('char-1');
+    expect(localStorage.getItem('sync_cursor_user-1')).toBe('cursor-3');
+  });
+
+  test('server change events trigger a pull and a remote-change notification', async () => {
+    const listeners = {};
+    global.EventSource = class {
+      constructor(url) {
+        this.url = url;
+      }
+      addEventListener(type, handler) {
+        listeners[type] = handler;
+      }
+      close() {}
+    };
+    apiJson.mockResolvedValue({
+      res: { ok: true, status: 200 },
+      data: { changes: [], next_cursor: 'cursor-9', has_more: false },
+    });
+
+    Sync.connectServerEvents();
+    await listeners.change({ data: JSON.stringify({ table: 'donations', id: 'don-9', op: 'upsert' }) });
+
+    expect(apiJson).toHaveBeenCalledWith('/api/sync/changes');
+    expect(window.dispatchEvent).toHaveBeenCalledWith(
+      expect.objectContaining({ type: 'sync-remote-change' })
+    );
+
+    Sync.disconnectServerEvents();
+    delete global.EventSource;
+  });
 });
@@ -107,10 +107,12 @@ mod asset_helpers_test {
     }
 }
 
-mod storage_test {
-    #![allow(dead_code)]
+#[allow(dead_code)]
+#[path = "../src/storage/mod.rs"]
+mod storage;
 
-    include!("../src/storage.rs");
+mod storage_test {
+    use super::storage::{hex_encode, hmac_sha256};
 
     #[test]
     fn hmac_sha256_matches_known_vector() {